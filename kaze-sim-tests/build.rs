@@ -1,55 +1,344 @@
+use kaze::components::Stream;
 use kaze::*;
 
-use std::env;
-use std::fs::File;
 use std::io::Result;
-use std::path::Path;
 
 fn main() -> Result<()> {
-    let out_dir = env::var("OUT_DIR").unwrap();
-    let dest_path = Path::new(&out_dir).join("modules.rs");
-    let mut file = File::create(&dest_path).unwrap();
-
     let c = Context::new();
 
-    sim::generate(input_masking(&c), &mut file)?;
-    sim::generate(widest_input(&c), &mut file)?;
-    sim::generate(add_test_module(&c), &mut file)?;
-    sim::generate(sub_test_module(&c), &mut file)?;
-    sim::generate(shl_test_module(&c), &mut file)?;
-    sim::generate(shr_test_module(&c), &mut file)?;
-    sim::generate(shr_arithmetic_test_module(&c), &mut file)?;
-    sim::generate(bit_and_test_module(&c), &mut file)?;
-    sim::generate(bit_or_test_module(&c), &mut file)?;
-    sim::generate(bit_xor_test_module(&c), &mut file)?;
-    sim::generate(not_test_module(&c), &mut file)?;
-    sim::generate(reg_test_module(&c), &mut file)?;
-    sim::generate(simple_reg_delay(&c), &mut file)?;
-    sim::generate(bit_test_module_0(&c), &mut file)?;
-    sim::generate(bit_test_module_1(&c), &mut file)?;
-    sim::generate(bits_test_module_0(&c), &mut file)?;
-    sim::generate(bits_test_module_1(&c), &mut file)?;
-    sim::generate(repeat_test_module(&c), &mut file)?;
-    sim::generate(concat_test_module(&c), &mut file)?;
-    sim::generate(eq_test_module(&c), &mut file)?;
-    sim::generate(ne_test_module(&c), &mut file)?;
-    sim::generate(lt_test_module(&c), &mut file)?;
-    sim::generate(le_test_module(&c), &mut file)?;
-    sim::generate(gt_test_module(&c), &mut file)?;
-    sim::generate(ge_test_module(&c), &mut file)?;
-    sim::generate(lt_signed_test_module(&c), &mut file)?;
-    sim::generate(le_signed_test_module(&c), &mut file)?;
-    sim::generate(gt_signed_test_module(&c), &mut file)?;
-    sim::generate(ge_signed_test_module(&c), &mut file)?;
-    sim::generate(mux_test_module(&c), &mut file)?;
-    sim::generate(instantiation_test_module_comb(&c), &mut file)?;
-    sim::generate(instantiation_test_module_reg(&c), &mut file)?;
-    sim::generate(nested_instantiation_test_module(&c), &mut file)?;
-    sim::generate(mem_test_module_0(&c), &mut file)?;
-    sim::generate(mem_test_module_1(&c), &mut file)?;
-    sim::generate(mem_test_module_2(&c), &mut file)?;
-
-    Ok(())
+    let sim_module_options = sim::GenerationOptions {
+        impl_sim_module: true,
+        ..Default::default()
+    };
+    let mut modules = build::SimBuilder::new();
+    modules.sim_module_trait();
+
+    modules.module(input_masking(&c));
+    modules.module_with_options(
+        checked_input_masking(&c),
+        sim::GenerationOptions {
+            check_inputs: true,
+            ..Default::default()
+        },
+    );
+    modules.module(widest_input(&c));
+    modules.module_with_options(add_test_module(&c), sim_module_options.clone());
+    modules.module(sub_test_module(&c));
+    modules.module(shl_test_module(&c));
+    modules.module(shr_test_module(&c));
+    modules.module(shr_arithmetic_test_module(&c));
+    modules.module(bit_and_test_module(&c));
+    modules.module(bit_or_test_module(&c));
+    modules.module(bit_xor_test_module(&c));
+    modules.module(not_test_module(&c));
+    modules.module_with_options(reg_test_module(&c), sim_module_options.clone());
+    modules.module_with_options(
+        simple_reg_delay(&c),
+        sim::GenerationOptions {
+            vcd: true,
+            save_state: true,
+            ..Default::default()
+        },
+    );
+    modules.module(bit_test_module_0(&c));
+    modules.module(bit_test_module_1(&c));
+    modules.module(bits_test_module_0(&c));
+    modules.module(bits_test_module_1(&c));
+    modules.module(repeat_test_module(&c));
+    modules.module(concat_test_module(&c));
+    modules.module(eq_test_module(&c));
+    modules.module(ne_test_module(&c));
+    modules.module(lt_test_module(&c));
+    modules.module(le_test_module(&c));
+    modules.module(gt_test_module(&c));
+    modules.module(ge_test_module(&c));
+    modules.module(lt_signed_test_module(&c));
+    modules.module(le_signed_test_module(&c));
+    modules.module(gt_signed_test_module(&c));
+    modules.module(ge_signed_test_module(&c));
+    modules.module_with_options(mux_test_module(&c), sim_module_options.clone());
+    modules.module(instantiation_test_module_comb(&c));
+    modules.module(instantiation_test_module_reg(&c));
+    modules.module(nested_instantiation_test_module(&c));
+    modules.module(mem_test_module_0(&c));
+    modules.module(mem_test_module_1(&c));
+    modules.module(mem_test_module_2(&c));
+    modules.module(mem_test_module_3(&c));
+    modules.module(mem_test_module_4(&c));
+    modules.module(mem_test_module_5(&c));
+    modules.module(mem_test_module_6(&c)?);
+    modules.module(mem_test_module_7(&c));
+    modules.module(true_dual_port_test_module(&c));
+    modules.module(constant_test_module(&c));
+    let (instance_array, unrolled_instance_array) = instance_array_test_modules(&c);
+    modules.module(instance_array);
+    modules.module(unrolled_instance_array);
+    modules.module_with_options(
+        mem_access_test_module(&c),
+        sim::GenerationOptions {
+            save_state: true,
+            ..Default::default()
+        },
+    );
+    modules.module(assertion_test_module(&c));
+    modules.module(cover_test_module(&c));
+    modules.module(trace_test_module(&c));
+    modules.module_with_options(
+        probe_test_module(&c),
+        sim::GenerationOptions {
+            probes: vec![
+                "pc".into(),
+                "fetch1.pc".into(),
+                "fetch2.pc".into(),
+                "fetch2.decode.next_pc".into(),
+            ],
+            ..Default::default()
+        },
+    );
+    modules.module(read_during_write_test_module(
+        &c,
+        "ReadDuringWriteOldTestModule",
+        ReadDuringWrite::Old,
+    ));
+    modules.module(read_during_write_test_module(
+        &c,
+        "ReadDuringWriteNewTestModule",
+        ReadDuringWrite::New,
+    ));
+    modules.module_with_options(
+        uninitialized_register_test_module(&c, "RandomRegTestModule"),
+        sim::GenerationOptions {
+            uninitialized_registers: sim::UninitializedRegisters::Random,
+            ..Default::default()
+        },
+    );
+    modules.module_with_options(
+        uninitialized_register_test_module(&c, "PoisonRegTestModule"),
+        sim::GenerationOptions {
+            uninitialized_registers: sim::UninitializedRegisters::Poison,
+            ..Default::default()
+        },
+    );
+    modules.module_with_options(
+        poison_counter_test_module(&c),
+        sim::GenerationOptions {
+            uninitialized_registers: sim::UninitializedRegisters::Poison,
+            ..Default::default()
+        },
+    );
+    modules.module_with_options(
+        poison_instance_test_module(&c),
+        sim::GenerationOptions {
+            uninitialized_registers: sim::UninitializedRegisters::Poison,
+            save_state: true,
+            ..Default::default()
+        },
+    );
+    modules.module(structural_sharing_test_module(&c));
+    modules.module(fan_out_test_module(&c));
+    for &bit_width in &[8, 9, 16, 17, 32, 33, 64, 65] {
+        modules.module(storage_type_test_module(&c, bit_width));
+    }
+    modules.module_with_options(
+        reserved_identifier_test_module(&c),
+        sim::GenerationOptions {
+            probes: vec!["loop".into(), "inner.let".into()],
+            vcd: true,
+            save_state: true,
+            impl_sim_module: true,
+            ..Default::default()
+        },
+    );
+    modules.module_with_options(debug_test_module(&c), sim_module_options.clone());
+    modules.module_with_options(sugar_debug_test_module(&c), sim_module_options.clone());
+    for &bit_width in &[32, 64, 128] {
+        modules.module(overflow_test_module(&c, bit_width));
+    }
+    modules.module(constant_folding_test_module(
+        &c,
+        "ConstantFoldingTestModule8",
+        8,
+        &[
+            (0x80, 0x01),
+            (0x7f, 0xff),
+            (0x12, 0x07),
+            (0xff, 0x09),
+            (0, 0),
+        ],
+    ));
+    modules.module(constant_folding_test_module(
+        &c,
+        "ConstantFoldingTestModule64",
+        64,
+        &[
+            (1 << 63, 0x01),
+            (0x7fffffffffffffff, 0xffffffffffffffff),
+            (0x123456789abcdef0, 0x3f),
+            (0xffffffffffffffff, 0x41),
+            (0, 0),
+        ],
+    ));
+    modules.module_with_options(
+        toggle_coverage_test_module(&c),
+        sim::GenerationOptions {
+            toggle_coverage: true,
+            ..Default::default()
+        },
+    );
+    modules.module(bus_test_module(&c));
+    modules.module(bundle_test_module(&c));
+    modules.module(enum_fsm_test_module(&c));
+    modules.module(components::sync_fifo(&c, "SyncFifoDepth1", 1, 8));
+    modules.module(components::sync_fifo(&c, "SyncFifoDepth3", 3, 8));
+    modules.module(components::sync_fifo(&c, "SyncFifoDepth4", 4, 8));
+    for &(name, depth) in &[("AsyncFifoDepth2", 2), ("AsyncFifoDepth8", 8)] {
+        let fifo = components::async_fifo(&c, name, depth, 16);
+        modules.module(fifo.push);
+        modules.module(fifo.pop);
+    }
+    modules.module(gray_test_module(&c));
+    modules.module(synchronizer_test_module(&c));
+    modules.module(stream_test_module(&c));
+    modules.module(stream_join_test_module(&c));
+    modules.module(integer_operand_test_module(&c));
+    modules.module(head_tail_test_module(&c));
+    modules.module(chunks_test_module(&c));
+    modules.module(swap_bytes_test_module(&c));
+    modules.module(one_hot_mux_test_module(&c));
+    modules.module(components::priority_arbiter(&c, "PriorityArbiter4", 4));
+    modules.module(components::round_robin_arbiter(&c, "RoundRobinArbiter3", 3));
+    modules.module(components::round_robin_arbiter(&c, "RoundRobinArbiter4", 4));
+    modules.module(register_file_test_module(&c));
+    modules.module(delay_test_module(&c));
+    modules.module(bit_dyn_test_module(&c));
+    modules.module(bits_dyn_test_module(&c));
+    modules.module(export_outputs_test_module(&c));
+    modules.module(min_max_test_module(&c));
+    modules.module(drive_inputs_test_module(&c));
+    modules.module_with_options(bmc_counter_test_module(&c), sim_module_options.clone());
+    modules.module_with_options(bmc_wide_input_test_module(&c), sim_module_options.clone());
+    modules.module(mux_masking_test_module(&c));
+    let core = core_test_module(&c);
+    modules.module_with_options(
+        core,
+        sim::GenerationOptions {
+            impl_sim_module: true,
+            save_state: true,
+            struct_name: Some("CoreSim".into()),
+            visibility: sim::Visibility::Crate,
+            ..Default::default()
+        },
+    );
+    modules.module_with_options(
+        core,
+        sim::GenerationOptions {
+            impl_sim_module: true,
+            toggle_coverage: true,
+            struct_name: Some("Core".into()),
+            visibility: sim::Visibility::Super,
+            module_name: Some("wrapped_core".into()),
+            ..Default::default()
+        },
+    );
+    modules.module(fsm_test_module(
+        &c,
+        "BinaryFsmTestModule",
+        FsmEncoding::Binary,
+    ));
+    modules.module(fsm_test_module(
+        &c,
+        "OneHotFsmTestModule",
+        FsmEncoding::OneHot,
+    ));
+    modules.module(negate_abs_test_module(&c, "NegateAbs8TestModule", 8));
+    modules.module(negate_abs_test_module(&c, "NegateAbs128TestModule", 128));
+    modules.module(components::csr_bank(
+        &c,
+        "CsrBankTestModule",
+        &csr_bank_test_spec(),
+    ));
+    modules.module(components::crc(
+        &c,
+        "Crc32Word32",
+        &components::CrcSpec::crc32(),
+        32,
+    ));
+    modules.module(components::crc(
+        &c,
+        "Crc32Word8",
+        &components::CrcSpec::crc32(),
+        8,
+    ));
+    modules.module(components::crc(
+        &c,
+        "Crc16CcittWord16",
+        &components::CrcSpec::crc16_ccitt(),
+        16,
+    ));
+    modules.module(components::crc(
+        &c,
+        "Crc8Word8",
+        &components::CrcSpec::crc8(),
+        8,
+    ));
+    modules.module(components::crc(
+        &c,
+        "Crc8Word16",
+        &components::CrcSpec::crc8(),
+        16,
+    ));
+    modules.module_with_options(
+        latch_test_module(&c),
+        sim::GenerationOptions {
+            save_state: true,
+            ..Default::default()
+        },
+    );
+    modules.module_with_options(decade_counter_test_module(&c), sim_module_options.clone());
+    modules.module_with_options(
+        refactored_decade_counter_test_module(&c, "RefactoredDecadeCounterTestModule", 9),
+        sim_module_options.clone(),
+    );
+    modules.module_with_options(
+        refactored_decade_counter_test_module(&c, "OffByOneDecadeCounterTestModule", 10),
+        sim_module_options.clone(),
+    );
+
+    // The pipelined module is checked against the original with each output delayed by its reported latency
+    pipeline_test_module(&c);
+    let pipelined_c = Context::new();
+    let pipelined = transform::pipeline(
+        c.module_by_name("PipelineTestModule").unwrap(),
+        3,
+        &pipelined_c,
+    );
+    modules.module_with_options(
+        delayed_pipeline_test_module(&c, &pipelined.output_latencies),
+        sim_module_options.clone(),
+    );
+
+    modules.write_to_out_dir("modules.rs")?;
+
+    // Verilog for cross-backend tests
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    verilog::generate(
+        c.module_by_name("InputMasking").unwrap(),
+        std::fs::File::create(std::path::Path::new(&out_dir).join("InputMasking.v"))?,
+    )?;
+
+    let flat_c = Context::new();
+    let mut flattened_modules = build::SimBuilder::new();
+    for name in &[
+        "InstantiationTestModuleReg",
+        "NestedInstantiationTestModule",
+    ] {
+        flattened_modules.module(transform::flatten(c.module_by_name(name).unwrap(), &flat_c));
+    }
+    flattened_modules.write_to_out_dir("flattened_modules.rs")?;
+
+    let mut pipelined_modules = build::SimBuilder::new();
+    pipelined_modules.module_with_options(pipelined.module, sim_module_options);
+    pipelined_modules.write_to_out_dir("pipelined_modules.rs")
 }
 
 fn input_masking<'a>(c: &'a Context<'a>) -> &Module<'a> {
@@ -60,6 +349,14 @@ fn input_masking<'a>(c: &'a Context<'a>) -> &Module<'a> {
     m
 }
 
+fn checked_input_masking<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("CheckedInputMasking");
+    m.output("o", m.input("i", 27));
+    m.output("p", m.input("j", 64));
+
+    m
+}
+
 fn widest_input<'a>(c: &'a Context<'a>) -> &Module<'a> {
     let m = c.module("WidestInput");
 
@@ -308,19 +605,673 @@ fn reg_test_module<'a>(c: &'a Context<'a>) -> &Module<'a> {
     m
 }
 
+fn uninitialized_register_test_module<'a>(c: &'a Context<'a>, name: &str) -> &'a Module<'a> {
+    let m = c.module(name);
+
+    let en = m.input("en", 1);
+    let i = m.input("i", 32);
+
+    let reset_reg = m.reg("reset_reg", 32);
+    reset_reg.default_value(0u32);
+    reset_reg.drive_next(en.mux(i, reset_reg.value));
+    m.output("reset_o", reset_reg.value);
+
+    let no_reset_reg = m.reg("no_reset_reg", 32);
+    no_reset_reg.drive_next(en.mux(i, no_reset_reg.value));
+    m.output("no_reset_o", no_reset_reg.value);
+
+    let mem = m.mem("mem", 1, 32);
+    mem.write_port(m.low(), reset_reg.value, en);
+    m.output("mem_o", mem.read_port(m.low(), m.high()));
+
+    m
+}
+
+fn poison_counter_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("PoisonCounterTestModule");
+
+    let counter = m.reg("counter", 8);
+    counter.drive_next(counter.value + m.lit(1u32, 8));
+    m.output("o", counter.value);
+
+    m
+}
+
+fn poison_instance_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("PoisonInstanceTestModule");
+
+    // With save_state, written flags for registers in instances are named after their paths (`u0__counter_written`)
+    let u0 = m.instance("u0", "PoisonCounterTestModule");
+    m.output("o", u0.output("o"));
+
+    m
+}
+
+fn toggle_coverage_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("ToggleCoverageTestModuleInner");
+    let q = m.reg("q", 1);
+    q.default_value(false);
+    q.drive_next(m.input("d", 1));
+    m.output("o", q.value);
+
+    let m = c.module("ToggleCoverageTestModule");
+    let i = m.input("i", 4);
+    let r = m.reg("r", 4);
+    r.default_value(0u32);
+    r.drive_next(i);
+    m.output("o", (r.value ^ i).name("diff"));
+    // Doesn't affect any outputs, but is still covered
+    (!i.bit(3)).name("idle");
+    let inner = m.instance("inner", "ToggleCoverageTestModuleInner");
+    inner.drive_input("d", i.bit(0));
+
+    m
+}
+
+fn bus_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("BusTestModule");
+
+    let lanes = (0..8)
+        .map(|i| m.input(format!("lane{}", i), 8))
+        .collect::<Vec<_>>();
+    m.output("packed", m.pack(&lanes));
+    m.output("selected", m.mux_index(&lanes, m.input("index", 3)));
+
+    let bus = m.unpack(m.input("bus", 64), 8);
+    m.output("bus_lane5", bus[5]);
+    m.output("repacked", m.pack(&bus));
+
+    m
+}
+
+fn bundle_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let cmd = BundleSpec::new()
+        .field("valid", 1)
+        .field("tag", 4)
+        .field("data", 32);
+
+    let inner = c.module("BundleTestModuleInner");
+    let i = inner.inputs_from_bundle("cmd", &cmd);
+    let resp = inner.bundle(
+        &cmd,
+        &[
+            i.field("valid"),
+            i.field("tag"),
+            i.field("data") + inner.lit(1u32, 32),
+        ],
+    );
+    inner.outputs_from_bundle("resp", &resp);
+    // The same bundle, crossing the instance boundary as a single flattened port
+    let flat = inner.unflatten(&cmd, inner.input("flat", cmd.bit_width()));
+    inner.outputs_from_bundle("flat", &flat);
+
+    let m = c.module("BundleTestModule");
+    let i = m.inputs_from_bundle("cmd", &cmd);
+    let inner = m.instance("inner", "BundleTestModuleInner");
+    inner.drive_bundle_inputs("cmd", &i);
+    inner.drive_input("flat", i.flatten());
+    m.outputs_from_bundle("resp", &inner.bundle_outputs("resp", &cmd));
+    m.outputs_from_bundle("unflattened", &inner.bundle_outputs("flat", &cmd));
+
+    m
+}
+
+fn gray_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("GrayTestModule");
+
+    let counter = m.reg("counter", 6);
+    counter.default_value(0u32);
+    counter.drive_next(counter.value + m.lit(1u32, 6));
+    let counter_gray = counter.value.to_gray();
+    m.output("counter", counter.value);
+    m.output("counter_gray", counter_gray);
+    m.output("counter_round_trip", counter_gray.from_gray());
+
+    for &bit_width in &[1, 2, 3, 32, 64, 128] {
+        let i = m.input(format!("i{}", bit_width), bit_width);
+        let gray = i.to_gray();
+        m.output(format!("gray{}", bit_width), gray);
+        m.output(format!("round_trip{}", bit_width), gray.from_gray());
+    }
+
+    m
+}
+
+fn synchronizer_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("SynchronizerTestModule");
+
+    let i = m.input("i", 1);
+    m.output("o2", m.synchronizer("sync2", i, 2));
+    m.output("o3", m.synchronizer("sync3", i, 3));
+
+    m
+}
+
+fn stream_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let inner = c.module("StreamTestModuleInner");
+    Stream::input(inner, "in", 8)
+        .register_slice("slice")
+        .output(inner, "out");
+
+    let m = c.module("StreamTestModule");
+    let inner = m.instance("inner", "StreamTestModuleInner");
+    Stream::input(m, "in", 8).drive_instance(inner, "in");
+    let mut branches = Stream::from_instance(inner, "out").fork("fork", 2);
+    let b = branches.pop().unwrap();
+    let a = branches.pop().unwrap();
+    a.output(m, "out_a");
+    b.register_slice("b_slice").output(m, "out_b");
+
+    m
+}
+
+fn stream_join_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("StreamJoinTestModule");
+
+    Stream::join(vec![Stream::input(m, "a", 8), Stream::input(m, "b", 8)])
+        .register_slice("slice")
+        .output(m, "out");
+
+    m
+}
+
+fn integer_operand_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("IntegerOperandTestModule");
+
+    for &(bit_width, k) in &[
+        (1, 1u128),
+        (32, 0x5a5a_0001),
+        (128, 0xdead_beef_0000_0000_0000_0000_0000_0001),
+    ] {
+        let a = m.input(format!("a{}", bit_width), bit_width);
+        m.output(format!("add{}", bit_width), a + k);
+        m.output(format!("radd{}", bit_width), k + a);
+        m.output(format!("sub{}", bit_width), a - k);
+        m.output(format!("rsub{}", bit_width), k - a);
+        m.output(format!("and{}", bit_width), a & k);
+        m.output(format!("rand{}", bit_width), k & a);
+        m.output(format!("or{}", bit_width), a | k);
+        m.output(format!("ror{}", bit_width), k | a);
+        m.output(format!("xor{}", bit_width), a ^ k);
+        m.output(format!("rxor{}", bit_width), k ^ a);
+        m.output(format!("shl{}", bit_width), a << 3u32);
+        m.output(format!("shr{}", bit_width), a >> 3u32);
+        m.output(format!("eq{}", bit_width), a.eq(k));
+        m.output(format!("lt{}", bit_width), a.lt(k));
+        m.output(format!("ge{}", bit_width), a.ge(k));
+    }
+
+    m
+}
+
+fn head_tail_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("HeadTailTestModule");
+
+    let i = m.input("i", 128);
+    m.output("msb", i.msb());
+    m.output("lsb", i.lsb());
+    for &n in &[1, 8, 64, 127, 128] {
+        m.output(format!("head{}", n), i.head(n));
+    }
+    for &n in &[0, 1, 8, 64, 127] {
+        m.output(format!("tail{}", n), i.tail(n));
+    }
+    m.output("round_trip", i.head(37).concat(i.tail(37)));
+
+    m
+}
+
+fn chunks_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("ChunksTestModule");
+
+    let i = m.input("i", 128);
+    let bytes = i.bytes();
+    m.output("byte0", bytes[0]);
+    m.output("byte15", bytes[15]);
+    m.output("bytes_round_trip", m.pack(&bytes));
+    let words = i.chunks(32);
+    m.output("word1", words[1]);
+    m.output("words_round_trip", m.pack(&words));
+    // Concatenating in order from the first chunk puts it in the most significant bits instead
+    let mut reversed = bytes[0];
+    for byte in bytes.iter().skip(1) {
+        reversed = reversed.concat(byte);
+    }
+    m.output("bytes_reversed", reversed);
+
+    m
+}
+
+fn swap_bytes_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("SwapBytesTestModule");
+
+    for &bit_width in &[16, 32, 64, 128] {
+        let i = m.input(format!("i{}", bit_width), bit_width);
+        m.output(format!("swapped{}", bit_width), i.swap_bytes());
+        m.output(
+            format!("round_trip{}", bit_width),
+            i.swap_bytes().swap_bytes(),
+        );
+    }
+    m.output(
+        "swapped_halfwords",
+        m.input("halfwords", 32).reverse_chunks(16),
+    );
+
+    m
+}
+
+fn one_hot_mux_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("OneHotMuxTestModule");
+
+    let select = m.input("select", 4);
+    let mut cases = Vec::new();
+    for i in 0..4 {
+        cases.push((select.bit(i), m.input(format!("i{}", i), 32)));
+    }
+    m.output("o", m.mux_one_hot(&cases));
+    m.output(
+        "o_checked",
+        m.mux_one_hot_checked(&cases, "select must be one-hot"),
+    );
+
+    m
+}
+
+fn register_file_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("RegisterFileTestModule");
+
+    let regs = m.register_file("regs", 5, 8);
+    regs.write_port(
+        m.input("write_addr", 3),
+        m.input("write_value", 8),
+        m.input("write_enable", 1),
+    );
+
+    let read_addr0 = m.input("read_addr0", 3);
+    let read_addr1 = m.input("read_addr1", 3);
+    m.output("read_data0", regs.read_port(read_addr0));
+    m.output("read_data1", regs.read_port(read_addr1));
+    m.output("read_data0_bypassed", regs.read_port_bypassed(read_addr0));
+    m.output("read_data1_bypassed", regs.read_port_bypassed(read_addr1));
+
+    m
+}
+
+fn delay_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("DelayTestModule");
+
+    let i = m.input("i", 8);
+    let enable = m.input("enable", 1);
+    m.output("o0", m.delay("d0", i, 0, 0u32));
+    m.output("o1", m.delay("d1", i, 1, 0u32));
+    m.output("o5", m.delay("d5", i, 5, 0xffu32));
+    m.output("o0_enabled", m.delay_enabled("e0", i, 0, 0u32, enable));
+    m.output("o1_enabled", m.delay_enabled("e1", i, 1, 0u32, enable));
+    m.output("o5_enabled", m.delay_enabled("e5", i, 5, 0xffu32, enable));
+
+    m
+}
+
+fn bit_dyn_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("BitDynTestModule");
+
+    let i = m.input("i", 9);
+    m.output("o1", i.bit_dyn(m.input("index1", 1)));
+    m.output("o4", i.bit_dyn(m.input("index4", 4)));
+    m.output("o100", i.bit_dyn(m.input("index100", 100)));
+
+    m
+}
+
+fn bits_dyn_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("BitsDynTestModule");
+
+    let i = m.input("i", 128);
+    let offset = m.input("offset", 8);
+    m.output("o1", i.bits_dyn(offset, 1));
+    m.output("o8", i.bits_dyn(offset, 8));
+    m.output("o100", i.bits_dyn(offset, 100));
+    m.output("o128", i.bits_dyn(offset, 128));
+
+    m
+}
+
+fn latch_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("LatchTestModule");
+
+    let data = m.input("data", 8);
+    let enable = m.input("enable", 1);
+
+    let l = m.latch("l", 8);
+    l.drive_data(data);
+    l.drive_enable(enable);
+    m.output("o", l.value);
+
+    // A second latch that's transparent while the first one holds
+    let inverted = m.latch("inverted", 8);
+    inverted.drive_data(!l.value);
+    inverted.drive_enable(!enable);
+    m.output("o_inverted", inverted.value);
+
+    m
+}
+
+fn export_outputs_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let inner = c.module("ExportOutputsTestModuleInner");
+    let r = inner.reg("r", 8);
+    r.default_value(0u32);
+    r.drive_next(inner.input("i", 8));
+    inner.output("sum", r.value + inner.input("j", 8));
+    inner.output("registered", r.value);
+
+    let m = c.module("ExportOutputsTestModule");
+    let inst = m.instance("inner", "ExportOutputsTestModuleInner");
+    inst.drive_input("i", m.input("i", 8));
+    inst.drive_input("j", m.input("j", 8));
+    m.export_outputs(inst);
+    m.output("sum_doubled", inst.output("sum") + inst.output("sum"));
+
+    m
+}
+
+fn drive_inputs_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let inner = c.module("DriveInputsTestModuleInner");
+    let mut o = inner.input("i0", 4);
+    for i in 1..12 {
+        o = o.concat(inner.input(format!("i{}", i), 4));
+    }
+    inner.output("o", o);
+
+    let m = c.module("DriveInputsTestModule");
+    let inputs = (0..12)
+        .map(|i| m.input(format!("i{}", i), 4))
+        .collect::<Vec<_>>();
+
+    let passthrough = m.instance("passthrough", "DriveInputsTestModuleInner");
+    passthrough.drive_all_inputs(m.inputs().iter().map(|(name, &i)| (name.clone(), i)));
+    m.output("o", passthrough.output("o"));
+    m.port_description(
+        "o",
+        "Concatenation of all inputs.\n\n`i0` is in the most significant bits.",
+    );
+
+    let reversed = m.instance("reversed", "DriveInputsTestModuleInner");
+    let pairs = (0..12)
+        .map(|i| (format!("i{}", i), inputs[11 - i]))
+        .collect::<Vec<_>>();
+    reversed.drive_inputs(pairs);
+    m.output("o_reversed", reversed.output("o"));
+
+    m
+}
+
+fn core_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("core");
+    let count = m.reg("count", 8);
+    count.default_value(0u32);
+    count.drive_next(
+        m.input("enable", 1)
+            .mux(count.value + m.lit(1u32, 8), count.value),
+    );
+    m.output("count", count.value);
+
+    m
+}
+
+fn mux_masking_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("MuxMaskingTestModule");
+    let sel = m.input("sel", 1);
+    let a = m.input("a", 27);
+    let b = m.input("b", 27);
+    // Both arms can have bits above 27 set in their containers unless they're masked
+    m.output("o", sel.mux(a + b, !a));
+    let x = m.input("x", 1);
+    m.output("o_bool", sel.mux(!x, x));
+
+    m
+}
+
+fn bmc_counter_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("BmcCounterTestModule");
+    let count = m.reg("count", 3);
+    count.default_value(0u32);
+    let enable = m.input("enable", 1);
+    let clear = m.input("clear", 1);
+    // Planted off-by-one: the counter should wrap after 3, but wraps after 4 instead
+    let next =
+        if_(count.value.eq(m.lit(4u32, 3)), m.lit(0u32, 3)).else_(count.value + m.lit(1u32, 3));
+    count.drive_next(
+        if_(clear, m.lit(0u32, 3))
+            .else_if(enable, next)
+            .else_(count.value),
+    );
+    m.assert(count.value.lt(m.lit(4u32, 3)), "count must be less than 4");
+    m.assume(!clear);
+    m.output("count", count.value);
+
+    m
+}
+
+fn bmc_wide_input_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("BmcWideInputTestModule");
+    let i = m.input("i", 9);
+    m.assert(i.ne(m.lit(0x1abu32, 9)), "i must not be 0x1ab");
+    m.output("o", i);
+
+    m
+}
+
+fn pipeline_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("PipelineTestModule");
+    let a = m.input("a", 16);
+    let b = m.input("b", 16);
+    let sel = m.input("sel", 1);
+    let sh = m.input("sh", 4);
+
+    let sum = a + b;
+    let mixed = ((sum ^ (a << sh)) - b).name("mixed");
+    let selected = sel.mux(mixed, !sum);
+    m.output("o", selected + a.bits(7, 0).concat(b.bits(15, 8)));
+    m.output("lt", selected.lt_signed(b));
+    m.output("version", m.lit(5u32, 4));
+
+    m
+}
+
+fn delayed_pipeline_test_module<'a>(
+    c: &'a Context<'a>,
+    output_latencies: &[(String, u32)],
+) -> &'a Module<'a> {
+    let m = c.module("DelayedPipelineTestModule");
+    let original = m.instance("original", "PipelineTestModule");
+    for &(name, bit_width) in &[("a", 16), ("b", 16), ("sel", 1), ("sh", 4)] {
+        original.drive_input(name, m.input(name, bit_width));
+    }
+
+    for (name, latency) in output_latencies {
+        let mut delayed = original.output(name.clone());
+        for stage in 0..*latency {
+            let reg = m.reg(format!("{}_delay{}", name, stage), delayed.bit_width());
+            reg.drive_next(delayed);
+            delayed = reg.value;
+        }
+        m.output(name.clone(), delayed);
+    }
+
+    m
+}
+
+fn csr_bank_test_spec() -> components::CsrBankSpec {
+    components::CsrBankSpec::new(8, 32)
+        .register(
+            components::CsrRegisterSpec::new("ctrl", 0x00)
+                .field("enable", 0, 1, components::CsrAccess::ReadWrite)
+                .field("mode", 4, 3, components::CsrAccess::ReadWrite)
+                .field("divider", 16, 16, components::CsrAccess::ReadWrite)
+                .reset_value(0x0064_0050),
+        )
+        .register(
+            components::CsrRegisterSpec::new("status", 0x04)
+                .field("busy", 0, 1, components::CsrAccess::ReadOnly)
+                .field("level", 8, 8, components::CsrAccess::ReadOnly),
+        )
+        .register(
+            components::CsrRegisterSpec::new("irq", 0x08)
+                .field("pending", 0, 4, components::CsrAccess::WriteOneToClear)
+                .field("overflow", 31, 1, components::CsrAccess::WriteOneToClear),
+        )
+        .register(
+            components::CsrRegisterSpec::new("count", 0x0c)
+                .field(
+                    "value",
+                    0,
+                    12,
+                    components::CsrAccess::ReadWriteHardwareUpdate,
+                )
+                .reset_value(0x123),
+        )
+}
+
+fn min_max_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("MinMaxTestModule");
+
+    let a = m.input("a", 8);
+    let b = m.input("b", 8);
+    let lo = m.input("lo", 8);
+    let hi = m.input("hi", 8);
+
+    m.output("min", a.min(b));
+    m.output("max", a.max(b));
+    m.output("min_signed", a.min_signed(b));
+    m.output("max_signed", a.max_signed(b));
+    m.output("clamp_lit", a.clamp(16, 200));
+    m.output("clamp", a.clamp(lo, hi));
+    m.output(
+        "clamp_signed_lit",
+        a.clamp_signed(m.lit(-100i8 as u8 as u32, 8), 100),
+    );
+    m.output("clamp_signed", a.clamp_signed(lo, hi));
+
+    m
+}
+
+fn negate_abs_test_module<'a>(c: &'a Context<'a>, name: &str, bit_width: u32) -> &'a Module<'a> {
+    let m = c.module(name);
+
+    let i = m.input("i", bit_width);
+    m.output("negate", i.negate());
+    m.output("abs", i.abs());
+
+    m
+}
+
+fn fsm_test_module<'a>(c: &'a Context<'a>, name: &str, encoding: FsmEncoding) -> &'a Module<'a> {
+    let m = c.module(name);
+
+    let start = m.input("start", 1);
+    let grant = m.input("grant", 1);
+    let abort = m.input("abort", 1);
+    let ack = m.input("ack", 1);
+
+    let fsm = m.fsm("state", &["Idle", "Request", "Wait", "Done"], encoding);
+    fsm.transition("Idle", start, "Request");
+    fsm.transition("Request", grant, "Wait");
+    fsm.transition("Request", abort, "Idle");
+    fsm.transition("Wait", ack, "Done");
+    fsm.transition("Done", m.high(), "Idle");
+
+    m.output("req", fsm.is("Request") | fsm.is("Wait"));
+    m.output("done", fsm.is("Done"));
+    m.output(
+        "code",
+        fsm.output(&[
+            ("Request", m.lit(1u32, 2)),
+            ("Wait", m.lit(2u32, 2)),
+            ("Done", m.lit(3u32, 2)),
+        ]),
+    );
+    fsm.build();
+
+    m
+}
+
+fn enum_fsm_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("EnumFsmTestModule");
+
+    let state_type = EnumType::new("State", &["Idle", "Fetch", "Execute"]);
+    let idle = state_type.variant("Idle");
+    let fetch = state_type.variant("Fetch");
+    let execute = state_type.variant("Execute");
+
+    let start = m.input("start", 1);
+    let stall = m.input("stall", 1);
+
+    let state = m.enum_reg("state", &state_type);
+    state.default_value(&idle);
+    state.drive_next(&m.enum_mux(
+        state.value.is(&idle),
+        &m.enum_mux(start, &m.enum_lit(&fetch), &state.value),
+        &m.enum_mux(
+            state.value.is(&fetch),
+            &m.enum_mux(stall, &state.value, &m.enum_lit(&execute)),
+            &m.enum_lit(&idle),
+        ),
+    ));
+
+    m.output("state", state.value.signal());
+    m.output("busy", !state.value.is(&idle));
+    m.output("executing", state.value.is(&execute));
+
+    m
+}
+
+fn decade_counter_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("DecadeCounterTestModule");
+
+    let en = m.input("en", 1);
+
+    let count = m.reg("count", 4);
+    count.default_value(0u32);
+    let wrap = count.value.eq(m.lit(9u32, 4));
+    count.drive_next(en.mux(
+        wrap.mux(m.lit(0u32, 4), count.value + m.lit(1u32, 4)),
+        count.value,
+    ));
+    m.output("count", count.value);
+    m.output("wrap", wrap);
+
+    m
+}
+
+// Same interface as `DecadeCounterTestModule`, restructured with `if_`; only equivalent when `last_count` is 9
+fn refactored_decade_counter_test_module<'a>(
+    c: &'a Context<'a>,
+    name: &str,
+    last_count: u32,
+) -> &'a Module<'a> {
+    let m = c.module(name);
+
+    let en = m.input("en", 1);
+
+    let count = m.reg("count", 4);
+    count.default_value(0u32);
+    let wrap = count.value.eq(m.lit(last_count, 4));
+    count.drive_next(
+        if_(!en, count.value)
+            .else_if(wrap, m.lit(0u32, 4))
+            .else_(count.value + m.lit(1u32, 4)),
+    );
+    m.output("count", count.value);
+    m.output("wrap", wrap);
+
+    m
+}
+
 fn simple_reg_delay<'a>(c: &'a Context<'a>) -> &Module<'a> {
     let m = c.module("SimpleRegDelay");
 
-    let r1 = m.reg("r1", 100);
-    r1.default_value(0u32);
-    r1.drive_next(m.input("i", 100));
-    let r2 = m.reg("r2", 100);
-    r2.default_value(0u32);
-    r2.drive_next(r1.value);
-    let r3 = m.reg("r3", 100);
-    r3.default_value(0u32);
-    r3.drive_next(r2.value);
-    m.output("o", r3.value);
+    m.output("o", m.delay("r", m.input("i", 100), 3, 0u32));
 
     m
 }
@@ -631,6 +1582,32 @@ fn nested_instantiation_test_module<'a>(c: &'a Context<'a>) -> &Module<'a> {
     m
 }
 
+fn mem_access_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let inner = c.module("MemAccessTestModuleInner");
+    let rom = inner.mem("rom", 2, 8);
+    rom.initial_contents(&[1u32, 2u32, 3u32, 4u32]);
+    inner.output("data", rom.read_port(inner.input("addr", 2), inner.high()));
+
+    let m = c.module("MemAccessTestModule");
+
+    let ram = m.mem("ram", 3, 12);
+    ram.write_port(
+        m.input("write_addr", 3),
+        m.input("write_value", 12),
+        m.input("write_enable", 1),
+    );
+    m.output(
+        "read_data",
+        ram.read_port(m.input("read_addr", 3), m.high()),
+    );
+
+    let inner = m.instance("inner", "MemAccessTestModuleInner");
+    inner.drive_input("addr", m.input("rom_addr", 2));
+    m.output("rom_data", inner.output("data"));
+
+    m
+}
+
 fn mem_test_module_0<'a>(c: &'a Context<'a>) -> &Module<'a> {
     let m = c.module("MemTestModule0");
 
@@ -680,3 +1657,508 @@ fn mem_test_module_2<'a>(c: &'a Context<'a>) -> &Module<'a> {
 
     m
 }
+
+fn mem_test_module_3<'a>(c: &'a Context<'a>) -> &Module<'a> {
+    let m = c.module("MemTestModule3");
+
+    // Initial contents, single write port, single read port whose enable is toggled
+    let mem = m.mem("mem", 2, 8);
+    mem.initial_contents(&[0x12u8, 0x34u8, 0x56u8, 0x78u8]);
+    mem.write_port(
+        m.input("write_addr", 2),
+        m.input("write_value", 8),
+        m.input("write_enable", 1),
+    );
+    m.output(
+        "read_data",
+        mem.read_port(m.input("read_addr", 2), m.input("read_enable", 1)),
+    );
+
+    m
+}
+
+fn mem_test_module_4<'a>(c: &'a Context<'a>) -> &Module<'a> {
+    let m = c.module("MemTestModule4");
+
+    // Initial contents, single write port, two combinational read ports
+    let mem = m.mem("mem", 2, 32);
+    mem.initial_contents(&[0xfadebabeu32, 0xdeadbeefu32, 0xabadcafeu32, 0xabad1deau32]);
+    mem.write_port(
+        m.input("write_addr", 2),
+        m.input("write_value", 32),
+        m.input("write_enable", 1),
+    );
+    m.output("read_data_0", mem.read_port_comb(m.input("read_addr_0", 2)));
+    m.output("read_data_1", mem.read_port_comb(m.input("read_addr_1", 2)));
+
+    m
+}
+
+fn mem_test_module_5<'a>(c: &'a Context<'a>) -> &Module<'a> {
+    let m = c.module("MemTestModule5");
+
+    // Two write ports which may target the same address, single combinational read port
+    let mem = m.mem("mem", 2, 8);
+    mem.initial_contents(&[0x00u8, 0x00u8, 0x00u8, 0x00u8]);
+    mem.write_port(
+        m.input("write_addr_0", 2),
+        m.input("write_value_0", 8),
+        m.input("write_enable_0", 1),
+    );
+    mem.write_port(
+        m.input("write_addr_1", 2),
+        m.input("write_value_1", 8),
+        m.input("write_enable_1", 1),
+    );
+    m.output("read_data", mem.read_port_comb(m.input("read_addr", 2)));
+
+    m
+}
+
+fn mem_test_module_6<'a>(c: &'a Context<'a>) -> Result<&Module<'a>> {
+    let m = c.module("MemTestModule6");
+
+    // Initial contents loaded from a data file, no write port
+    let mem = m.mem("mem", 2, 32);
+    mem.initial_contents_from_file("data/boot_rom.hex")?;
+    m.output(
+        "read_data",
+        mem.read_port(m.input("read_addr", 2), m.input("read_enable", 1)),
+    );
+
+    Ok(m)
+}
+
+fn mem_test_module_7<'a>(c: &'a Context<'a>) -> &Module<'a> {
+    let m = c.module("MemTestModule7");
+
+    // Byte-enable write port, single combinational read port
+    let mem = m.mem("mem", 1, 32);
+    mem.initial_contents(&[0x00000000u32, 0x00000000u32]);
+    mem.write_port_masked(
+        m.input("write_addr", 1),
+        m.input("write_value", 32),
+        m.input("write_enable", 1),
+        m.input("write_mask", 4),
+    );
+    m.output("read_data", mem.read_port_comb(m.input("read_addr", 1)));
+
+    m
+}
+
+fn true_dual_port_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("TrueDualPortTestModule");
+
+    // Same structure as the true dual-port Verilog golden test: each port reads and writes the same address
+    let ram = m.mem("ram", 4, 8);
+    for port in ["a", "b"].iter() {
+        let addr = m.input(format!("{}_addr", port), 4);
+        ram.write_port(
+            addr,
+            m.input(format!("{}_write_data", port), 8),
+            m.input(format!("{}_write_enable", port), 1),
+        );
+        m.output(
+            format!("{}_read_data", port),
+            ram.read_port(addr, m.input(format!("{}_read_enable", port), 1)),
+        );
+    }
+
+    m
+}
+
+fn constant_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("ConstantTestModule");
+
+    let i = m.input("i", 32);
+    let magic = m.constant("MAGIC", 0xdeadbeefu32, 32);
+    m.output("is_magic", i.eq(magic));
+    m.output("xor_magic", i ^ magic);
+    m.output("flag", m.constant("FLAG", true, 1));
+    m.output("wide", m.constant("WIDE", 0xfadebabedeadbeefu64, 64));
+
+    m
+}
+
+// Returns the same 4-lane design built with an instance array and with individual instances
+fn instance_array_test_modules<'a>(c: &'a Context<'a>) -> (&'a Module<'a>, &'a Module<'a>) {
+    let lane = c.module("InstanceArrayLane");
+    let acc = lane.reg("acc", 8);
+    acc.default_value(0u32);
+    let sum = acc.value + lane.input("i", 8);
+    acc.drive_next(if_(lane.input("en", 1), sum).else_(acc.value));
+    lane.output("acc", acc.value);
+    lane.output("carry", sum.lt(acc.value));
+
+    let build = |name: &str, lanes: &dyn Fn(&'a Module<'a>) -> Vec<&'a Instance<'a>>| {
+        let m = c.module(name);
+        let i = m.input("i", 32);
+        let en = m.input("en", 1);
+        let lanes = lanes(m);
+        for (index, lane) in lanes.iter().enumerate() {
+            let index = index as u32;
+            lane.drive_input("en", en);
+            lane.drive_input("i", i.bits(index * 8 + 7, index * 8));
+        }
+        m.output(
+            "acc",
+            lanes[3]
+                .output("acc")
+                .concat(lanes[2].output("acc"))
+                .concat(lanes[1].output("acc"))
+                .concat(lanes[0].output("acc")),
+        );
+        m.output(
+            "carry",
+            lanes[3]
+                .output("carry")
+                .concat(lanes[2].output("carry"))
+                .concat(lanes[1].output("carry"))
+                .concat(lanes[0].output("carry")),
+        );
+        m
+    };
+
+    (
+        build("InstanceArrayTestModule", &|m| {
+            m.instance_array("lane", "InstanceArrayLane", 4)
+        }),
+        build("UnrolledInstanceArrayTestModule", &|m| {
+            (0..4)
+                .map(|index| m.instance(format!("lane_{}", index), "InstanceArrayLane"))
+                .collect()
+        }),
+    )
+}
+
+fn read_during_write_test_module<'a>(
+    c: &'a Context<'a>,
+    name: &str,
+    policy: ReadDuringWrite,
+) -> &'a Module<'a> {
+    let m = c.module(name);
+
+    // Full-width and masked write ports, single synchronous read port
+    let mem = m.mem("mem", 2, 16);
+    mem.read_during_write(policy);
+    mem.initial_contents(&[0x0000u16, 0x1111u16, 0x2222u16, 0x3333u16]);
+    mem.write_port(
+        m.input("write_addr_0", 2),
+        m.input("write_value_0", 16),
+        m.input("write_enable_0", 1),
+    );
+    mem.write_port_masked(
+        m.input("write_addr_1", 2),
+        m.input("write_value_1", 16),
+        m.input("write_enable_1", 1),
+        m.input("write_mask_1", 2),
+    );
+    m.output(
+        "read_data",
+        mem.read_port(m.input("read_addr", 2), m.input("read_enable", 1)),
+    );
+
+    m
+}
+
+fn assertion_test_module<'a>(c: &'a Context<'a>) -> &Module<'a> {
+    let inner = c.module("AssertionTestModuleInner");
+    let i = inner.input("i", 4);
+    inner.assert(i.ne(inner.lit(0xfu32, 4)), "i must not be 0xf");
+    inner.output("o", i);
+
+    let m = c.module("AssertionTestModule");
+
+    // Counter which wraps; asserts in both this module and an instance
+    let counter = m.reg("counter", 4);
+    counter.default_value(0u32);
+    counter.drive_next(counter.value + m.lit(1u32, 4));
+    m.assert(
+        counter.value.le(m.input("limit", 4)),
+        "counter must not exceed limit",
+    );
+    let inner = m.instance("inner", "AssertionTestModuleInner");
+    inner.drive_input("i", counter.value);
+    m.output("counter", inner.output("o"));
+
+    m
+}
+
+fn cover_test_module<'a>(c: &'a Context<'a>) -> &Module<'a> {
+    let inner = c.module("CoverTestModuleInner");
+    let i = inner.input("i", 2);
+    inner.cover(i.eq(inner.lit(3u32, 2)));
+    inner.output("o", i);
+
+    let m = c.module("CoverTestModule");
+
+    let i = m.input("i", 2);
+    m.assume(i.ne(m.lit(2u32, 2)));
+    m.cover(i.eq(m.lit(0u32, 2)));
+    m.cover(i.eq(m.lit(1u32, 2)));
+    let inner = m.instance("inner", "CoverTestModuleInner");
+    inner.drive_input("i", i);
+    m.output("o", inner.output("o"));
+
+    m
+}
+
+fn trace_test_module<'a>(c: &'a Context<'a>) -> &Module<'a> {
+    let inner = c.module("TraceTestModuleInner");
+    let i = inner.input("i", 8);
+    let doubled = i.concat(inner.low());
+    inner.trace("doubled", doubled);
+    inner.output("o", doubled.bits(7, 0));
+
+    let m = c.module("TraceTestModule");
+
+    let counter = m.reg("counter", 8);
+    counter.default_value(0u32);
+    counter.drive_next(counter.value + m.lit(1u32, 8));
+    m.trace("counter", counter.value);
+    m.trace("counter_is_odd", counter.value.bit(0));
+    let inner = m.instance("inner", "TraceTestModuleInner");
+    inner.drive_input("i", counter.value);
+    m.output("o", inner.output("o"));
+
+    m
+}
+
+fn probe_test_module<'a>(c: &'a Context<'a>) -> &Module<'a> {
+    let m = c.module("ProbeTestModuleDecode");
+    let pc = m.input("pc", 32);
+    let next_pc = (pc + m.lit(4u32, 32)).name("next_pc");
+    m.output("next_pc", next_pc);
+
+    let m = c.module("ProbeTestModuleFetch");
+    let pc = m.reg("pc", 32);
+    pc.default_value(0u32);
+    let decode = m.instance("decode", "ProbeTestModuleDecode");
+    decode.drive_input("pc", pc.value);
+    pc.drive_next(m.mux(m.input("stall", 1), pc.value, decode.output("next_pc")));
+    m.output("pc_is_zero", pc.value.eq(m.lit(0u32, 32)));
+
+    let m = c.module("ProbeTestModule");
+    let pc = m.reg("pc", 4);
+    pc.default_value(0xfu32);
+    pc.drive_next(pc.value);
+    let fetch1 = m.instance("fetch1", "ProbeTestModuleFetch");
+    fetch1.drive_input("stall", m.low());
+    let fetch2 = m.instance("fetch2", "ProbeTestModuleFetch");
+    fetch2.drive_input("stall", m.input("stall", 1));
+    m.output(
+        "pcs_are_zero",
+        fetch1.output("pc_is_zero") & fetch2.output("pc_is_zero"),
+    );
+
+    m
+}
+
+fn structural_sharing_test_module<'a>(c: &'a Context<'a>) -> &Module<'a> {
+    fn duplicated_tree<'a>(a: &'a Signal<'a>, b: &'a Signal<'a>, depth: u32) -> &'a Signal<'a> {
+        if depth == 0 {
+            (a & b) ^ (a | b)
+        } else {
+            duplicated_tree(a, b, depth - 1) + duplicated_tree(a, b, depth - 1)
+        }
+    }
+
+    let m = c.module("StructuralSharingTestModule");
+    let a = m.input("a", 32);
+    let b = m.input("b", 32);
+    m.output("o", duplicated_tree(a, b, 16));
+
+    m
+}
+
+fn fan_out_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("FanOutTestModule");
+    let mut x = m.input("x", 32);
+    let y = m.input("y", 32);
+    // Each level uses the previous one three times, so naively expanding it would take 3^64 terms
+    for _ in 0..64 {
+        x = (x + y) ^ (x >> m.lit(1u32, 5)) ^ x.lt(y).mux(y, x);
+    }
+    for index in 0..4 {
+        m.output(format!("o{}", index), x ^ m.lit(index as u32, 32));
+    }
+
+    m
+}
+
+fn storage_type_test_module<'a>(c: &'a Context<'a>, bit_width: u32) -> &'a Module<'a> {
+    let m = c.module(format!("StorageTypeTestModule{}", bit_width));
+    let a = m.input("a", bit_width);
+    let b = m.input("b", bit_width);
+    let shift = m.input("shift", 8);
+    m.output("add", a + b);
+    m.output("sub", a - b);
+    m.output("not", !a);
+    m.output("shl", a << shift);
+    m.output("shr", a >> shift);
+    m.output("shr_arithmetic", a.shr_arithmetic(shift));
+    m.output("lt_signed", a.lt_signed(b));
+    m.output(
+        "rotate",
+        a.bits(bit_width - 2, 0).concat(a.bit(bit_width - 1)),
+    );
+    // These cross storage type boundaries for some of the tested widths
+    m.output("widen", a.concat(a.bit(0)));
+    m.output("narrow", a.bits(bit_width - 2, 0));
+
+    m
+}
+
+fn reserved_identifier_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let inner = c.module("struct");
+    let r = inner.reg("let", 4);
+    r.default_value(0u32);
+    r.drive_next(inner.input("in", 4));
+    inner.output("where", r.value);
+
+    let m = c.module("Mod-1");
+    let ty = m.input("type", 8);
+    let match_ = m.input("match", 1);
+    let self_ = m.input("self", 4);
+    let first = m.input("1st", 4);
+    let loop_ = m.reg("loop", 8);
+    loop_.default_value(0u32);
+    loop_.drive_next(match_.mux(ty, loop_.value));
+    let rom = m.mem("box-rom", 2, 8);
+    rom.initial_contents(&[0x12u32, 0x34, 0x56, 0x78]);
+    let inner = m.instance("inner", "struct");
+    inner.drive_input("in", self_ ^ first);
+    m.output("async", loop_.value + ty);
+    m.output("fn", rom.read_port_comb(first.bits(1, 0)));
+    m.output("Self", inner.output("where"));
+
+    m
+}
+
+// `DebugTestModule` with its register's next value written with `if_` instead of `mux`
+fn sugar_debug_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let m = c.module("SugarDebugTestModule");
+    let a = m.input("a", 4);
+    let en = m.input("en", 1);
+    let r = m.reg("r", 4);
+    r.default_value(0xau32);
+    r.drive_next(if_(en, a).else_(r.value));
+    let rom = m.mem("rom", 4, 12);
+    rom.initial_contents(&(0..16u32).map(|x| x * x * 0x11).collect::<Vec<_>>());
+    let inner = m.instance("inner", "DebugTestModuleInner");
+    m.output("o", rom.read_port_comb(a).bits(7, 0));
+    m.output("p", inner.output("o") & r.value.bit(3));
+
+    m
+}
+
+fn debug_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+    let inner = c.module("DebugTestModuleInner");
+    let counter = inner.reg("counter", 3);
+    counter.default_value(0u32);
+    counter.drive_next(counter.value + inner.lit(1u32, 3));
+    let flags = inner.mem("flags", 1, 1);
+    flags.initial_contents(&[true, false]);
+    inner.output("o", flags.read_port(counter.value.bit(0), inner.high()));
+
+    let m = c.module("DebugTestModule");
+    let a = m.input("a", 4);
+    let en = m.input("en", 1);
+    let r = m.reg("r", 4);
+    r.default_value(0xau32);
+    r.drive_next(en.mux(a, r.value));
+    let rom = m.mem("rom", 4, 12);
+    rom.initial_contents(&(0..16u32).map(|x| x * x * 0x11).collect::<Vec<_>>());
+    let inner = m.instance("inner", "DebugTestModuleInner");
+    m.output("o", rom.read_port_comb(a).bits(7, 0));
+    m.output("p", inner.output("o") & r.value.bit(3));
+
+    m
+}
+
+fn overflow_test_module<'a>(c: &'a Context<'a>, bit_width: u32) -> &'a Module<'a> {
+    let m = c.module(format!("OverflowTestModule{}", bit_width));
+    let a = m.input("a", bit_width);
+    let b = m.input("b", bit_width);
+    let shift = m.input("shift", 8);
+    m.output("add", a + b);
+    m.output("sub", a - b);
+    m.output("shl", a << shift);
+    m.output("shr", a >> shift);
+    m.output("shr_arithmetic", a.shr_arithmetic(shift));
+
+    m
+}
+
+fn constant_folding_test_module<'a>(
+    c: &'a Context<'a>,
+    name: &str,
+    bit_width: u32,
+    vectors: &[(u64, u64)],
+) -> &'a Module<'a> {
+    fn ops<'a>(m: &'a Module<'a>, a: &'a Signal<'a>, b: &'a Signal<'a>) -> Vec<&'a Signal<'a>> {
+        let shift = b.bits(6, 0);
+        vec![
+            a + b,
+            a - b,
+            a & b,
+            a | b,
+            a ^ b,
+            !a,
+            a.eq(b),
+            a.ne(b),
+            a.lt(b),
+            a.le(b),
+            a.gt(b),
+            a.ge(b),
+            a.lt_signed(b),
+            a.le_signed(b),
+            a.gt_signed(b),
+            a.ge_signed(b),
+            a << shift,
+            a >> shift,
+            a.shr_arithmetic(shift),
+            a.bits(6, 2),
+            a.repeat(2),
+            a.concat(b),
+            m.mux(a.bit(0), a, b),
+        ]
+    }
+
+    let m = c.module(name);
+    let a = m.input("a", bit_width);
+    let b = m.input("b", bit_width);
+
+    // Compare the dynamically-computed result of each op against its folded result for each test vector.
+    // Bit i of mismatches is set if op i disagrees for the test vector currently on the inputs.
+    let dynamic = ops(m, a, b);
+    let mut matched = m.low();
+    let mut mismatches = Vec::new();
+    for &(a_value, b_value) in vectors {
+        let folded = ops(m, m.lit(a_value, bit_width), m.lit(b_value, bit_width));
+        let selected = a.eq(m.lit(a_value, bit_width)) & b.eq(m.lit(b_value, bit_width));
+        matched = matched | selected;
+        for (i, (&dynamic, &folded)) in dynamic.iter().zip(folded.iter()).enumerate() {
+            let mismatch = selected & dynamic.ne(folded);
+            if i < mismatches.len() {
+                mismatches[i] = mismatches[i] | mismatch;
+            } else {
+                mismatches.push(mismatch);
+            }
+        }
+    }
+    m.output("matched", matched);
+    m.output(
+        "mismatches",
+        mismatches
+            .into_iter()
+            .rev()
+            .fold(None, |acc: Option<&Signal>, mismatch| match acc {
+                Some(acc) => Some(acc.concat(mismatch)),
+                None => Some(mismatch),
+            })
+            .unwrap(),
+    );
+
+    m
+}
@@ -1,9 +1,24 @@
 #[cfg(test)]
 mod tests {
+    // Not every generated method is used by the tests, but generated names (such as probe getters derived from instance paths) must not trigger naming lints
+    #[allow(dead_code)]
+    #[deny(non_camel_case_types, non_snake_case)]
     mod modules {
         include!(concat!(env!("OUT_DIR"), "/modules.rs"));
     }
 
+    #[allow(dead_code)]
+    mod flattened_modules {
+        include!(concat!(env!("OUT_DIR"), "/flattened_modules.rs"));
+    }
+
+    #[allow(dead_code)]
+    mod pipelined_modules {
+        use super::modules::SimModule;
+
+        include!(concat!(env!("OUT_DIR"), "/pipelined_modules.rs"));
+    }
+
     use modules::*;
 
     #[test]
@@ -15,6 +30,116 @@ mod tests {
         assert_eq!(m.o, 0x07ffffff);
     }
 
+    #[test]
+    #[ignore = "requires Icarus Verilog (iverilog and vvp); run with `cargo test -- --ignored`"]
+    fn input_masking_matches_verilog() {
+        // Drives the same out-of-range values into the generated Verilog through a 32-bit reg
+        use std::process::Command;
+
+        let stimulus = [0xffffffffu32, 0x08000000, 0x12345678, 0x07ffffff];
+
+        let dir = std::env::temp_dir().join("kaze-sim-tests-input-masking");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut testbench = String::from(
+            "module InputMasking_tb;\n    reg [31:0] i;\n    wire [26:0] o;\n    InputMasking dut(.i(i), .o(o));\n    initial begin\n",
+        );
+        for value in stimulus.iter() {
+            testbench.push_str(&format!(
+                "        i = 32'h{:x}; #1 $display(\"%h\", o);\n",
+                value
+            ));
+        }
+        testbench.push_str("        $finish;\n    end\nendmodule\n");
+        std::fs::write(dir.join("InputMasking_tb.v"), testbench).unwrap();
+
+        let status = Command::new("iverilog")
+            .args(["-g2012", "-o"])
+            .arg(dir.join("InputMasking_tb"))
+            .arg(concat!(env!("OUT_DIR"), "/InputMasking.v"))
+            .arg(dir.join("InputMasking_tb.v"))
+            .status()
+            .unwrap_or_else(|e| panic!("Couldn't run iverilog: {}", e));
+        assert!(status.success(), "iverilog failed: {}", status);
+        let output = Command::new("vvp")
+            .arg(dir.join("InputMasking_tb"))
+            .output()
+            .unwrap_or_else(|e| panic!("Couldn't run vvp: {}", e));
+        assert!(output.status.success(), "vvp failed: {}", output.status);
+        let verilog_outputs = String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .filter_map(|line| u32::from_str_radix(line.trim(), 16).ok())
+            .collect::<Vec<_>>();
+        assert_eq!(verilog_outputs.len(), stimulus.len());
+
+        let mut m = InputMasking::new();
+        let sim_outputs = stimulus
+            .iter()
+            .map(|&value| {
+                m.i = value;
+                m.prop();
+                m.o
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(verilog_outputs, sim_outputs);
+    }
+
+    #[test]
+    fn mux_masking() {
+        let mut m = MuxMaskingTestModule::new();
+
+        m.sel = true;
+        m.a = 0x07ffffff;
+        m.b = 2;
+        m.x = false;
+        m.prop();
+        assert_eq!(m.o, 0x00000001);
+        assert!(m.o_bool);
+
+        m.sel = false;
+        m.a = 0x00000001;
+        m.prop();
+        assert_eq!(m.o, 0x07fffffe);
+        assert!(!m.o_bool);
+
+        // Out-of-range input bits don't leak through either arm
+        m.a = 0xffffffff;
+        m.b = 0xffffffff;
+        m.prop();
+        assert_eq!(m.o, 0x00000000);
+        m.sel = true;
+        m.prop();
+        assert_eq!(m.o, 0x07fffffe);
+    }
+
+    #[test]
+    fn checked_input_masking_in_range() {
+        let mut m = CheckedInputMasking::new();
+
+        m.i = 0x07ffffff;
+        m.j = 0xffffffffffffffff;
+        m.prop();
+        assert_eq!(m.o, 0x07ffffff);
+        assert_eq!(m.p, 0xffffffffffffffff);
+    }
+
+    #[test]
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(
+            expected = "Input \"i\" of module \"CheckedInputMasking\" was set to 0xffffffff, which doesn't fit in 27 bit(s)."
+        )
+    )]
+    fn checked_input_masking_out_of_range() {
+        let mut m = CheckedInputMasking::new();
+
+        m.i = 0xffffffff;
+        m.prop();
+        // Out-of-range values are only checked in debug builds, and are masked otherwise
+        assert_eq!(m.o, 0x07ffffff);
+    }
+
     #[test]
     fn widest_input() {
         let mut m = WidestInput::new();
@@ -24,6 +149,1955 @@ mod tests {
         assert_eq!(m.o, 0xfadebabedeadbeefabad1deabadc0de5);
     }
 
+    // A step of a table-driven test: inputs to drive, followed by the expected outputs after propagation.
+    // The clock is pulsed after each step.
+    type TestVector = (
+        &'static [(&'static str, u128)],
+        &'static [(&'static str, u128)],
+    );
+
+    fn run_test_vectors<M: SimModule>(vectors: &[TestVector]) {
+        let mut m = M::new();
+        m.reset();
+
+        for (step, (inputs, outputs)) in vectors.iter().enumerate() {
+            for &(name, value) in inputs.iter() {
+                m.set_input(name, value);
+            }
+            m.prop();
+            for &(name, expected) in outputs.iter() {
+                assert_eq!(
+                    m.get_output(name),
+                    expected,
+                    "output \"{}\" mismatch on step {}",
+                    name,
+                    step
+                );
+            }
+            m.posedge_clk();
+        }
+    }
+
+    #[test]
+    fn sim_module_metadata() {
+        assert_eq!(
+            AddTestModule::input_names()[..4],
+            [("i1", 1), ("i10", 128), ("i11", 7), ("i12", 7)]
+        );
+        assert_eq!(AddTestModule::output_names().len(), 6);
+        assert_eq!(RegTestModule::input_names(), &[("i1", 32), ("i2", 32)]);
+        assert_eq!(RegTestModule::output_names(), &[("o1", 32), ("o2", 32)]);
+    }
+
+    #[test]
+    fn sim_module_test_vectors() {
+        run_test_vectors::<AddTestModule>(&[
+            (&[("i1", 1), ("i2", 1)], &[("o1", 0)]),
+            (&[("i3", 0xffff), ("i4", 2)], &[("o2", 1)]),
+            (&[("i11", 0x7f), ("i12", 0xff)], &[("o6", 0x7e)]),
+            (
+                &[("i9", u128::MAX), ("i10", 2)],
+                &[("o5", 1), ("o1", 0), ("o2", 1)],
+            ),
+        ]);
+
+        run_test_vectors::<RegTestModule>(&[
+            (&[("i1", 0xdeadbeef)], &[("o1", 0)]),
+            (&[("i1", 0xfadebabe), ("i2", 1)], &[("o1", 0xdeadbeef)]),
+            (&[], &[("o1", 0xfadebabe), ("o2", 1)]),
+        ]);
+
+        run_test_vectors::<MuxTestModule>(&[
+            (
+                &[("invert", 0), ("i1", 1), ("i2", 0)],
+                &[("o1", 1), ("o2", 0)],
+            ),
+            (&[("invert", 1)], &[("o1", 0), ("o2", 1)]),
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Module \"RegTestModule\" doesn't have an input called \"i3\".")]
+    fn sim_module_missing_input() {
+        let mut m = <RegTestModule as SimModule>::new();
+
+        // Panic
+        m.set_input("i3", 0);
+    }
+
+    #[test]
+    fn sim_module_tick() {
+        let mut m = RegTestModule::new();
+        m.reset();
+        m.set_input("i1", 5);
+        SimModule::tick(&mut m);
+        assert_eq!(m.get_output("o1"), 5);
+    }
+
+    #[test]
+    fn sim_module_fuzz() {
+        // xorshift64; any source of random u64s will do
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let mut num_cycles = 0;
+        fuzz_sim_module::<AddTestModule>(1000, &mut next_u64, |m| {
+            assert_eq!(m.o1, m.i1 ^ m.i2);
+            assert_eq!(m.o2, m.i3.wrapping_add(m.i4) & 0xffff);
+            assert_eq!(m.o3, m.i5.wrapping_add(m.i6));
+            assert_eq!(m.o4, m.i7.wrapping_add(m.i8));
+            assert_eq!(m.o5, m.i9.wrapping_add(m.i10));
+            assert!(m.i11 < 0x80 && m.i12 < 0x80);
+            assert_eq!(m.o6, m.i11.wrapping_add(m.i12) & 0x7f);
+            num_cycles += 1;
+        });
+        assert_eq!(num_cycles, 1000);
+    }
+
+    #[test]
+    fn toggle_coverage() {
+        let mut m = ToggleCoverageTestModule::new();
+        m.reset();
+
+        for &i in &[0b0001, 0b0011, 0b0000] {
+            m.i = i;
+            m.tick();
+        }
+
+        let report = m.coverage();
+        assert_eq!(
+            report.signals,
+            vec![
+                ("r", 4, 0b1111, 0b0011),
+                ("diff", 4, 0b1111, 0b0011),
+                ("idle", 1, 0b0, 0b1),
+                ("inner.q", 1, 0b1, 0b1),
+            ]
+        );
+        assert_eq!(report.uncovered_bits("r"), 0b1100);
+        assert_eq!(report.uncovered_bits("diff"), 0b1100);
+        assert_eq!(report.uncovered_bits("idle"), 0b1);
+        assert_eq!(report.uncovered_bits("inner.q"), 0);
+        assert_eq!(report.percentage(), 50.0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Module \"ToggleCoverageTestModule\" doesn't have a register or named signal called \"q\"."
+    )]
+    fn toggle_coverage_unknown_signal_error() {
+        ToggleCoverageTestModule::new()
+            .coverage()
+            .uncovered_bits("q");
+    }
+
+    #[test]
+    fn bus_test_module() {
+        let mut m = BusTestModule::new();
+
+        m.lane0 = 0x10;
+        m.lane1 = 0x21;
+        m.lane2 = 0x32;
+        m.lane3 = 0x43;
+        m.lane4 = 0x54;
+        m.lane5 = 0x65;
+        m.lane6 = 0x76;
+        m.lane7 = 0x87;
+        m.prop();
+        assert_eq!(m.packed, 0x8776655443322110);
+
+        for index in 0..8 {
+            m.index = index;
+            m.prop();
+            assert_eq!(m.selected, (index << 4 | index) + 0x10);
+        }
+
+        m.bus = 0x0123456789abcdef;
+        m.prop();
+        assert_eq!(m.bus_lane5, 0x45);
+        assert_eq!(m.repacked, 0x0123456789abcdef);
+    }
+
+    #[test]
+    fn bundle_test_module() {
+        let mut m = BundleTestModule::new();
+
+        m.cmd_valid = true;
+        m.cmd_tag = 0xa;
+        m.cmd_data = 0xdeadbeef;
+        m.prop();
+        assert_eq!(m.resp_valid, true);
+        assert_eq!(m.resp_tag, 0xa);
+        assert_eq!(m.resp_data, 0xdeadbef0);
+        assert_eq!(m.unflattened_valid, true);
+        assert_eq!(m.unflattened_tag, 0xa);
+        assert_eq!(m.unflattened_data, 0xdeadbeef);
+
+        m.cmd_valid = false;
+        m.cmd_tag = 0x5;
+        m.cmd_data = 0xffffffff;
+        m.prop();
+        assert_eq!(m.resp_valid, false);
+        assert_eq!(m.resp_tag, 0x5);
+        assert_eq!(m.resp_data, 0);
+        assert_eq!(m.unflattened_valid, false);
+        assert_eq!(m.unflattened_tag, 0x5);
+        assert_eq!(m.unflattened_data, 0xffffffff);
+    }
+
+    #[test]
+    fn enum_fsm_test_module() {
+        let mut m = EnumFsmTestModule::new();
+        m.reset();
+
+        m.prop();
+        assert_eq!(m.state, EnumFsmTestModule::STATE_IDLE);
+        assert_eq!(m.busy, false);
+
+        // Stays idle until started
+        m.tick();
+        assert_eq!(m.state, EnumFsmTestModule::STATE_IDLE);
+
+        m.start = true;
+        m.stall = true;
+        m.tick();
+        assert_eq!(m.state, EnumFsmTestModule::STATE_FETCH);
+        assert_eq!(m.busy, true);
+        assert_eq!(m.executing, false);
+
+        // Stalls in fetch
+        m.start = false;
+        m.tick();
+        assert_eq!(m.state, EnumFsmTestModule::STATE_FETCH);
+
+        m.stall = false;
+        m.tick();
+        assert_eq!(m.state, EnumFsmTestModule::STATE_EXECUTE);
+        assert_eq!(m.executing, true);
+
+        m.tick();
+        assert_eq!(m.state, EnumFsmTestModule::STATE_IDLE);
+        assert_eq!(m.busy, false);
+        assert_eq!(m.executing, false);
+    }
+
+    #[test]
+    fn gray_test_module() {
+        let mut m = GrayTestModule::new();
+        m.reset();
+
+        m.prop();
+        let mut prev_gray = m.counter_gray;
+        // Run past the wrap so the transition from 63 back to 0 is covered too
+        for i in 0..(64 + 1) {
+            assert_eq!(m.counter, i & 0x3f);
+            assert_eq!(m.counter_gray, m.counter ^ (m.counter >> 1));
+            assert_eq!(m.counter_round_trip, m.counter);
+            if i > 0 {
+                assert_eq!((m.counter_gray ^ prev_gray).count_ones(), 1);
+            }
+            prev_gray = m.counter_gray;
+            m.tick();
+        }
+
+        for &i in &[false, true] {
+            m.i1 = i;
+            m.prop();
+            assert_eq!(m.gray1, i);
+            assert_eq!(m.round_trip1, i);
+        }
+
+        for i in 0..4 {
+            m.i2 = i;
+            m.prop();
+            assert_eq!(m.gray2, i ^ (i >> 1));
+            assert_eq!(m.round_trip2, i);
+        }
+
+        for i in 0..8 {
+            m.i3 = i;
+            m.prop();
+            assert_eq!(m.gray3, i ^ (i >> 1));
+            assert_eq!(m.round_trip3, i);
+        }
+
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..1000 {
+            m.i32 = next_u64() as u32;
+            m.i64 = next_u64();
+            m.i128 = ((next_u64() as u128) << 64) | next_u64() as u128;
+            m.prop();
+            assert_eq!(m.gray32, m.i32 ^ (m.i32 >> 1));
+            assert_eq!(m.round_trip32, m.i32);
+            assert_eq!(m.gray64, m.i64 ^ (m.i64 >> 1));
+            assert_eq!(m.round_trip64, m.i64);
+            assert_eq!(m.gray128, m.i128 ^ (m.i128 >> 1));
+            assert_eq!(m.round_trip128, m.i128);
+        }
+    }
+
+    #[test]
+    fn synchronizer_test_module() {
+        let mut m = SynchronizerTestModule::new();
+
+        // Registers without default values aren't affected by reset, so flush the chains first
+        m.i = false;
+        m.tick_n(3);
+        assert_eq!(m.o2, false);
+        assert_eq!(m.o3, false);
+
+        m.i = true;
+        m.tick();
+        assert_eq!(m.o2, false);
+        assert_eq!(m.o3, false);
+        m.tick();
+        assert_eq!(m.o2, true);
+        assert_eq!(m.o3, false);
+        m.tick();
+        assert_eq!(m.o2, true);
+        assert_eq!(m.o3, true);
+
+        // Single-cycle pulses are delayed, not stretched
+        m.i = false;
+        m.tick_n(3);
+        assert_eq!((m.o2, m.o3), (false, false));
+        m.i = true;
+        m.tick();
+        m.i = false;
+        assert_eq!((m.o2, m.o3), (false, false));
+        m.tick();
+        assert_eq!((m.o2, m.o3), (true, false));
+        m.tick();
+        assert_eq!((m.o2, m.o3), (false, true));
+        m.tick();
+        assert_eq!((m.o2, m.o3), (false, false));
+    }
+
+    #[test]
+    fn stream_test_module() {
+        let mut m = StreamTestModule::new();
+        m.reset();
+
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let mut sent = 0u32;
+        let mut received_a = Vec::new();
+        let mut received_b = Vec::new();
+        let mut stalled_a = None;
+        let mut stalled_b = None;
+        for _ in 0..10000 {
+            let r = next_u64();
+            // Once asserted, valid must be held until the transfer occurs
+            m.in_valid = m.in_valid || (r & 1) != 0;
+            m.in_data = sent & 0xff;
+            m.out_a_ready = (r & 2) != 0;
+            m.out_b_ready = (r & 4) != 0;
+            m.prop();
+
+            if let Some(data) = stalled_a {
+                assert!(m.out_a_valid);
+                assert_eq!(m.out_a_data, data);
+            }
+            if let Some(data) = stalled_b {
+                assert!(m.out_b_valid);
+                assert_eq!(m.out_b_data, data);
+            }
+            stalled_a = if m.out_a_valid && !m.out_a_ready {
+                Some(m.out_a_data)
+            } else {
+                None
+            };
+            stalled_b = if m.out_b_valid && !m.out_b_ready {
+                Some(m.out_b_data)
+            } else {
+                None
+            };
+
+            if m.out_a_valid && m.out_a_ready {
+                received_a.push(m.out_a_data);
+            }
+            if m.out_b_valid && m.out_b_ready {
+                received_b.push(m.out_b_data);
+            }
+            let accepted = m.in_valid && m.in_ready;
+
+            m.tick();
+
+            if accepted {
+                sent += 1;
+                m.in_valid = false;
+            }
+        }
+
+        assert!(received_a.len() > 1000);
+        assert!(received_b.len() > 1000);
+        for (i, &data) in received_a.iter().enumerate() {
+            assert_eq!(data, i as u32 & 0xff);
+        }
+        for (i, &data) in received_b.iter().enumerate() {
+            assert_eq!(data, i as u32 & 0xff);
+        }
+        // Every transfer is delivered to both branches before the next one is accepted from the source
+        assert!(sent as usize <= received_a.len().min(received_b.len()) + 3);
+    }
+
+    #[test]
+    fn stream_test_module_throughput() {
+        let mut m = StreamTestModule::new();
+        m.reset();
+
+        m.in_valid = true;
+        m.out_a_ready = true;
+        m.out_b_ready = true;
+        let mut received_b = 0;
+        for i in 0..100 {
+            m.in_data = i;
+            m.prop();
+            assert!(m.in_ready);
+            if m.out_b_valid {
+                assert_eq!(m.out_b_data, received_b);
+                received_b += 1;
+            }
+            m.tick();
+        }
+        // Two register slices add two cycles of latency, but don't reduce throughput
+        assert_eq!(received_b, 98);
+    }
+
+    #[test]
+    fn stream_join_test_module() {
+        let mut m = StreamJoinTestModule::new();
+        m.reset();
+
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let mut sent_a = 0u32;
+        let mut sent_b = 0u32;
+        let mut received = Vec::new();
+        for _ in 0..10000 {
+            let r = next_u64();
+            m.a_valid = m.a_valid || (r & 1) != 0;
+            m.a_data = sent_a & 0xff;
+            m.b_valid = m.b_valid || (r & 2) != 0;
+            m.b_data = (sent_b * 3) & 0xff;
+            m.out_ready = (r & 4) != 0;
+            m.prop();
+
+            // Both sides are accepted together
+            assert_eq!(m.a_valid && m.a_ready, m.b_valid && m.b_ready);
+            if m.out_valid && m.out_ready {
+                received.push(m.out_data);
+            }
+            let accepted = m.a_valid && m.a_ready;
+
+            m.tick();
+
+            if accepted {
+                sent_a += 1;
+                sent_b += 1;
+                m.a_valid = false;
+                m.b_valid = false;
+            }
+        }
+
+        assert!(received.len() > 1000);
+        for (i, &data) in received.iter().enumerate() {
+            let i = i as u32;
+            assert_eq!(data, ((i & 0xff) << 8) | ((i * 3) & 0xff));
+        }
+    }
+
+    #[test]
+    fn integer_operand_test_module() {
+        let mut m = IntegerOperandTestModule::new();
+
+        for &a in &[false, true] {
+            m.a1 = a;
+            m.prop();
+            assert_eq!(m.add1, !a);
+            assert_eq!(m.radd1, !a);
+            assert_eq!(m.sub1, !a);
+            assert_eq!(m.rsub1, !a);
+            assert_eq!(m.and1, a);
+            assert_eq!(m.rand1, a);
+            assert_eq!(m.or1, true);
+            assert_eq!(m.ror1, true);
+            assert_eq!(m.xor1, !a);
+            assert_eq!(m.rxor1, !a);
+            assert_eq!(m.shl1, false);
+            assert_eq!(m.shr1, false);
+            assert_eq!(m.eq1, a);
+            assert_eq!(m.lt1, !a);
+            assert_eq!(m.ge1, a);
+        }
+
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let k32 = 0x5a5a_0001u32;
+        let k128 = 0xdead_beef_0000_0000_0000_0000_0000_0001u128;
+        for i in 0..1000 {
+            let (a32, a128) = match i {
+                0 => (k32, k128),
+                1 => (0, 0),
+                2 => (!0, !0),
+                _ => (
+                    next_u64() as u32,
+                    ((next_u64() as u128) << 64) | next_u64() as u128,
+                ),
+            };
+            m.a32 = a32;
+            m.a128 = a128;
+            m.prop();
+
+            assert_eq!(m.add32, a32.wrapping_add(k32));
+            assert_eq!(m.radd32, k32.wrapping_add(a32));
+            assert_eq!(m.sub32, a32.wrapping_sub(k32));
+            assert_eq!(m.rsub32, k32.wrapping_sub(a32));
+            assert_eq!(m.and32, a32 & k32);
+            assert_eq!(m.rand32, k32 & a32);
+            assert_eq!(m.or32, a32 | k32);
+            assert_eq!(m.ror32, k32 | a32);
+            assert_eq!(m.xor32, a32 ^ k32);
+            assert_eq!(m.rxor32, k32 ^ a32);
+            assert_eq!(m.shl32, a32 << 3);
+            assert_eq!(m.shr32, a32 >> 3);
+            assert_eq!(m.eq32, a32 == k32);
+            assert_eq!(m.lt32, a32 < k32);
+            assert_eq!(m.ge32, a32 >= k32);
+
+            assert_eq!(m.add128, a128.wrapping_add(k128));
+            assert_eq!(m.radd128, k128.wrapping_add(a128));
+            assert_eq!(m.sub128, a128.wrapping_sub(k128));
+            assert_eq!(m.rsub128, k128.wrapping_sub(a128));
+            assert_eq!(m.and128, a128 & k128);
+            assert_eq!(m.rand128, k128 & a128);
+            assert_eq!(m.or128, a128 | k128);
+            assert_eq!(m.ror128, k128 | a128);
+            assert_eq!(m.xor128, a128 ^ k128);
+            assert_eq!(m.rxor128, k128 ^ a128);
+            assert_eq!(m.shl128, a128 << 3);
+            assert_eq!(m.shr128, a128 >> 3);
+            assert_eq!(m.eq128, a128 == k128);
+            assert_eq!(m.lt128, a128 < k128);
+            assert_eq!(m.ge128, a128 >= k128);
+        }
+    }
+
+    #[test]
+    fn head_tail_test_module() {
+        let mut m = HeadTailTestModule::new();
+
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for n in 0..1000 {
+            let i = match n {
+                0 => 0,
+                1 => !0,
+                2 => 1 << 127,
+                3 => 1,
+                _ => ((next_u64() as u128) << 64) | next_u64() as u128,
+            };
+            m.i = i;
+            m.prop();
+
+            assert_eq!(m.msb, (i >> 127) != 0);
+            assert_eq!(m.lsb, (i & 1) != 0);
+            assert_eq!(m.head1, (i >> 127) != 0);
+            assert_eq!(m.head8, (i >> 120) as u32);
+            assert_eq!(m.head64, (i >> 64) as u64);
+            assert_eq!(m.head127, i >> 1);
+            assert_eq!(m.head128, i);
+            assert_eq!(m.tail0, i);
+            assert_eq!(m.tail1, i & !(1 << 127));
+            assert_eq!(m.tail8, i & ((1 << 120) - 1));
+            assert_eq!(m.tail64, i as u64);
+            assert_eq!(m.tail127, (i & 1) != 0);
+            assert_eq!(m.round_trip, i);
+        }
+    }
+
+    #[test]
+    fn chunks_test_module() {
+        let mut m = ChunksTestModule::new();
+
+        for &i in &[
+            0x0011_2233_4455_6677_8899_aabb_ccdd_eeffu128,
+            0xdead_beef_0123_4567_89ab_cdef_fedc_ba98,
+            0,
+            !0,
+        ] {
+            m.i = i;
+            m.prop();
+
+            assert_eq!(m.byte0, i as u32 & 0xff);
+            assert_eq!(m.byte15, (i >> 120) as u32);
+            assert_eq!(m.bytes_round_trip, i);
+            assert_eq!(m.word1, (i >> 32) as u32);
+            assert_eq!(m.words_round_trip, i);
+            assert_eq!(m.bytes_reversed, i.swap_bytes());
+        }
+    }
+
+    #[test]
+    fn swap_bytes_test_module() {
+        let mut m = SwapBytesTestModule::new();
+
+        m.i32 = 0xdeadbeef;
+        m.prop();
+        assert_eq!(m.swapped32, 0xefbeadde);
+        m.i32 = 0xefbeadde;
+        m.prop();
+        assert_eq!(m.swapped32, 0xdeadbeef);
+
+        m.i128 = 0x0011_2233_4455_6677_8899_aabb_ccdd_eeff;
+        m.prop();
+        assert_eq!(m.swapped128, 0xffee_ddcc_bbaa_9988_7766_5544_3322_1100);
+
+        m.halfwords = 0x1234_5678;
+        m.prop();
+        assert_eq!(m.swapped_halfwords, 0x5678_1234);
+
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..1000 {
+            let i = ((next_u64() as u128) << 64) | next_u64() as u128;
+            m.i16 = i as u32 & 0xffff;
+            m.i32 = i as u32;
+            m.i64 = i as u64;
+            m.i128 = i;
+            m.prop();
+
+            assert_eq!(m.swapped16, (i as u16).swap_bytes() as u32);
+            assert_eq!(m.swapped32, (i as u32).swap_bytes());
+            assert_eq!(m.swapped64, (i as u64).swap_bytes());
+            assert_eq!(m.swapped128, i.swap_bytes());
+            assert_eq!(m.round_trip16, m.i16);
+            assert_eq!(m.round_trip32, m.i32);
+            assert_eq!(m.round_trip64, m.i64);
+            assert_eq!(m.round_trip128, m.i128);
+        }
+    }
+
+    #[test]
+    fn one_hot_mux_test_module() {
+        let mut m = OneHotMuxTestModule::new();
+
+        m.i0 = 0xdeadbeef;
+        m.i1 = 0x0badf00d;
+        m.i2 = 0xfadebabe;
+        m.i3 = 0x12345678;
+
+        m.select = 0;
+        m.prop();
+        m.check(0);
+        assert_eq!(m.o, 0);
+        assert_eq!(m.o_checked, 0);
+
+        for (cycle, &expected) in [m.i0, m.i1, m.i2, m.i3].iter().enumerate() {
+            m.select = 1 << cycle;
+            m.prop();
+            m.check(cycle as _);
+            assert_eq!(m.o, expected);
+            assert_eq!(m.o_checked, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Assertion in module \"OneHotMuxTestModule\" failed on cycle 3: select must be one-hot"
+    )]
+    fn one_hot_mux_test_module_multiple_selected() {
+        let mut m = OneHotMuxTestModule::new();
+
+        m.select = 0b1010;
+        m.prop();
+        m.check(3);
+    }
+
+    #[test]
+    fn priority_arbiter() {
+        let mut m = PriorityArbiter4::new();
+
+        for requests in 0..16u32 {
+            m.requests = requests;
+            m.prop();
+            // Lowest set bit wins
+            assert_eq!(m.grant, requests & requests.wrapping_neg());
+            assert_eq!(m.grant_valid, requests != 0);
+        }
+    }
+
+    macro_rules! round_robin_arbiter_test {
+        ($name:ident, $module:ident, $num_requestors:expr) => {
+            #[test]
+            fn $name() {
+                let num_requestors: u32 = $num_requestors;
+                let all = (1u32 << num_requestors) - 1;
+
+                let mut m = $module::new();
+                m.reset();
+
+                // No requests: no grant, and the pointer stays put
+                m.requests = 0;
+                for _ in 0..3 {
+                    m.prop();
+                    assert_eq!(m.grant, 0);
+                    assert_eq!(m.grant_valid, false);
+                    m.tick();
+                }
+
+                // Fairness: with everyone requesting, grants rotate through all requestors in order
+                let mut grant_counts = vec![0; num_requestors as usize];
+                m.requests = all;
+                for cycle in 0..num_requestors * 10 {
+                    m.prop();
+                    assert_eq!(m.grant, 1 << (cycle % num_requestors));
+                    assert_eq!(m.grant_valid, true);
+                    grant_counts[(cycle % num_requestors) as usize] += 1;
+                    m.tick();
+                }
+                assert!(grant_counts.iter().all(|&count| count == 10));
+
+                // Wrap-around: a lone request below the pointer is still granted, repeatedly
+                m.requests = 0b1;
+                for _ in 0..3 {
+                    m.prop();
+                    assert_eq!(m.grant, 0b1);
+                    m.tick();
+                }
+
+                // Random sparse requests against a reference model
+                let mut state = 0x9e37_79b9_7f4a_7c15u64;
+                let mut next_u64 = move || {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state
+                };
+                let mut last_granted = 0;
+                let mut cycles_waiting = 0;
+                for _ in 0..1000 {
+                    // Requestor 0 always requests; the others only sometimes
+                    let requests = (next_u64() as u32 & next_u64() as u32 & all) | 1;
+                    m.requests = requests;
+                    m.prop();
+
+                    let expected = (1..=num_requestors)
+                        .map(|offset| (last_granted + offset) % num_requestors)
+                        .find(|&i| requests & (1 << i) != 0)
+                        .unwrap();
+                    assert_eq!(m.grant, 1 << expected);
+                    assert_eq!(m.grant_valid, true);
+                    last_granted = expected;
+
+                    // A persistent requestor never waits more than num_requestors - 1 cycles
+                    cycles_waiting = if expected == 0 { 0 } else { cycles_waiting + 1 };
+                    assert!(cycles_waiting < num_requestors);
+
+                    m.tick();
+                }
+            }
+        };
+    }
+
+    round_robin_arbiter_test!(round_robin_arbiter_3, RoundRobinArbiter3, 3);
+    round_robin_arbiter_test!(round_robin_arbiter_4, RoundRobinArbiter4, 4);
+
+    #[test]
+    fn register_file_test_module() {
+        let mut m = RegisterFileTestModule::new();
+
+        // Fill every element
+        m.write_enable = true;
+        for i in 0..5 {
+            m.write_addr = i;
+            m.write_value = 0x10 + i;
+            m.tick();
+        }
+        m.write_enable = false;
+
+        // Both read ports see every element, with and without bypass
+        for i in 0..5 {
+            m.read_addr0 = i;
+            m.read_addr1 = 4 - i;
+            m.prop();
+            assert_eq!(m.read_data0, 0x10 + i);
+            assert_eq!(m.read_data1, 0x10 + 4 - i);
+            assert_eq!(m.read_data0_bypassed, 0x10 + i);
+            assert_eq!(m.read_data1_bypassed, 0x10 + 4 - i);
+        }
+
+        // Same-cycle write to a read address: only the bypassed ports see the new value
+        m.write_enable = true;
+        m.write_addr = 2;
+        m.write_value = 0xab;
+        m.read_addr0 = 2;
+        m.read_addr1 = 3;
+        m.prop();
+        assert_eq!(m.read_data0, 0x12);
+        assert_eq!(m.read_data0_bypassed, 0xab);
+        assert_eq!(m.read_data1, 0x13);
+        assert_eq!(m.read_data1_bypassed, 0x13);
+
+        // ...and on the following cycle, every port sees it
+        m.tick();
+        m.write_enable = false;
+        m.prop();
+        assert_eq!(m.read_data0, 0xab);
+        assert_eq!(m.read_data0_bypassed, 0xab);
+
+        // A write that isn't enabled is neither bypassed nor committed
+        m.write_addr = 3;
+        m.write_value = 0xcd;
+        m.read_addr1 = 3;
+        m.prop();
+        assert_eq!(m.read_data1_bypassed, 0x13);
+        m.tick();
+        assert_eq!(m.read_data1, 0x13);
+
+        // Writes past the end have no effect
+        m.write_enable = true;
+        m.write_addr = 7;
+        m.write_value = 0xef;
+        m.tick();
+        m.write_enable = false;
+        for i in 0..5 {
+            m.read_addr0 = i;
+            m.prop();
+            assert_eq!(m.read_data0, if i == 2 { 0xab } else { 0x10 + i });
+        }
+    }
+
+    #[test]
+    fn delay_test_module() {
+        let mut m = DelayTestModule::new();
+
+        // Check default values
+        m.reset();
+        m.i = 0x12;
+        m.prop();
+        assert_eq!(m.o0, 0x12);
+        assert_eq!(m.o1, 0);
+        assert_eq!(m.o5, 0xff);
+        assert_eq!(m.o0_enabled, 0x12);
+        assert_eq!(m.o1_enabled, 0);
+        assert_eq!(m.o5_enabled, 0xff);
+
+        // Without enable, the chains hold their values
+        m.enable = false;
+        m.tick_n(10);
+        assert_eq!(m.o1, 0x12);
+        assert_eq!(m.o5, 0x12);
+        assert_eq!(m.o1_enabled, 0);
+        assert_eq!(m.o5_enabled, 0xff);
+
+        // With enable, the enabled chains behave exactly like the others
+        m.enable = true;
+        let mut history = vec![0x12u32; 5];
+        for value in 0x20..0x30 {
+            m.i = value;
+            m.prop();
+            assert_eq!(m.o0, value);
+            assert_eq!(m.o0_enabled, value);
+            m.tick();
+            history.insert(0, value);
+            assert_eq!(m.o1, history[0]);
+            assert_eq!(m.o5, history[4]);
+            if value >= 0x24 {
+                assert_eq!(m.o1_enabled, m.o1);
+                assert_eq!(m.o5_enabled, m.o5);
+            }
+        }
+
+        // Stall: enabled chains freeze while the others keep shifting
+        m.enable = false;
+        m.i = 0x99;
+        m.tick_n(2);
+        assert_eq!(m.o0_enabled, 0x99);
+        assert_eq!(m.o1_enabled, 0x2f);
+        assert_eq!(m.o5_enabled, 0x2b);
+        assert_eq!(m.o1, 0x99);
+        assert_eq!(m.o5, 0x2d);
+
+        // ...and resume where they left off
+        m.enable = true;
+        m.tick();
+        assert_eq!(m.o1_enabled, 0x99);
+        assert_eq!(m.o5_enabled, 0x2c);
+    }
+
+    #[test]
+    fn bit_dyn_test_module() {
+        let mut m = BitDynTestModule::new();
+
+        for &i in &[0b1_0110_0101u32, 0b0_1001_1010, 0x1ff, 0] {
+            m.i = i;
+            for index in 0..16u32 {
+                let expected = index < 9 && (i >> index) & 1 != 0;
+                m.index1 = index == 1;
+                m.index4 = index;
+                m.index100 = index as u128;
+                m.prop();
+                assert_eq!(m.o1, if index < 2 { expected } else { i & 1 != 0 });
+                assert_eq!(m.o4, expected);
+                assert_eq!(m.o100, expected);
+            }
+
+            // Indices past the top of a wide index are out of range too
+            for &index in &[9u128, 32, 127, 1 << 64, (1 << 100) - 1] {
+                m.index100 = index;
+                m.prop();
+                assert_eq!(m.o100, false);
+            }
+        }
+    }
+
+    #[test]
+    fn bits_dyn_test_module() {
+        let mut m = BitsDynTestModule::new();
+
+        let expected = |i: u128, offset: u32, bit_width: u32| {
+            let mask = if bit_width == 128 {
+                !0
+            } else {
+                (1u128 << bit_width) - 1
+            };
+            i.checked_shr(offset).unwrap_or(0) & mask
+        };
+
+        for &i in &[
+            0xfedc_ba98_7654_3210_0123_4567_89ab_cdefu128,
+            !0,
+            1 << 127,
+            0x8000_0000_0000_0001,
+        ] {
+            m.i = i;
+            for &offset in &[0, 1, 3, 7, 64, 99, 120, 127, 128, 200, 255] {
+                m.offset = offset;
+                m.prop();
+                assert_eq!(m.o1, expected(i, offset, 1) != 0);
+                assert_eq!(m.o8 as u128, expected(i, offset, 8));
+                assert_eq!(m.o100, expected(i, offset, 100));
+                assert_eq!(m.o128, expected(i, offset, 128));
+            }
+        }
+    }
+
+    #[test]
+    fn latch_test_module() {
+        let mut m = LatchTestModule::new();
+
+        // Latches hold 0 until they're first enabled
+        m.data = 0x12;
+        m.enable = false;
+        m.prop();
+        assert_eq!(m.o, 0x00);
+        assert_eq!(m.o_inverted, 0xff);
+
+        // Transparent while enabled
+        m.enable = true;
+        m.prop();
+        assert_eq!(m.o, 0x12);
+        assert_eq!(m.o_inverted, 0xff);
+        m.data = 0x34;
+        m.prop();
+        assert_eq!(m.o, 0x34);
+        assert_eq!(m.o_inverted, 0xff);
+
+        // Holds its value across `prop` calls while disabled
+        m.enable = false;
+        m.prop();
+        assert_eq!(m.o, 0x34);
+        assert_eq!(m.o_inverted, 0xcb);
+        let state = m.save_state();
+        assert_eq!(state.l, 0x34);
+        assert_eq!(state.inverted, 0xcb);
+        for &data in &[0x00, 0x56, 0xff] {
+            m.data = data;
+            m.prop();
+            assert_eq!(m.o, 0x34);
+            assert_eq!(m.o_inverted, 0xcb);
+        }
+
+        m.data = 0x78;
+        m.enable = true;
+        m.prop();
+        assert_eq!(m.o, 0x78);
+        assert_eq!(m.o_inverted, 0xcb);
+
+        m.load_state(&state);
+        m.enable = false;
+        m.prop();
+        assert_eq!(m.o, 0x34);
+        assert_eq!(m.o_inverted, 0xcb);
+    }
+
+    macro_rules! async_fifo_test {
+        ($name:ident, $push:ident, $pop:ident, $depth:expr, $push_period:expr, $pop_period:expr) => {
+            #[test]
+            fn $name() {
+                let depth: u32 = $depth;
+                let push_period: u32 = $push_period;
+                let pop_period: u32 = $pop_period;
+
+                let mut push = $push::new();
+                let mut pop = $pop::new();
+                push.reset();
+                pop.reset();
+
+                // Each half only sees the other's crossing ports after they're copied over
+                let settle = |push: &mut $push, pop: &mut $pop| {
+                    pop.prop();
+                    push.read_ptr_gray = pop.read_ptr_gray;
+                    push.read_address = pop.read_address;
+                    push.prop();
+                    pop.write_ptr_gray = push.write_ptr_gray;
+                    pop.read_data = push.read_data;
+                    pop.prop();
+                };
+
+                let mut state = 0x9e37_79b9_7f4a_7c15u64;
+                let mut next_u64 = move || {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state
+                };
+
+                let num_transfers = 5000u32;
+                let mut pushed = 0u32;
+                let mut popped = 0u32;
+                let mut was_full = false;
+                let mut was_empty_after_push = false;
+                let mut time = 0u32;
+                while popped < num_transfers {
+                    assert!(time < num_transfers * 100, "FIFO stopped making progress");
+                    let r = next_u64();
+
+                    // Alternate between phases that favor pushing and popping, so the FIFO regularly fills up and drains
+                    let push_heavy = (time / 500) % 2 == 0;
+                    let (push_threshold, pop_threshold) =
+                        if push_heavy { (14, 4) } else { (4, 14) };
+                    push.push = pushed < num_transfers && (r & 0xf) < push_threshold;
+                    push.push_data = pushed & 0xffff;
+                    pop.pop = ((r >> 4) & 0xf) < pop_threshold;
+
+                    let push_edge = time % push_period == 0;
+                    let pop_edge = time % pop_period == 0;
+                    // Coincident edges are applied in either order
+                    let push_first = (r >> 8) & 1 != 0;
+                    for &is_push_edge in &[push_first, !push_first] {
+                        if is_push_edge && push_edge {
+                            settle(&mut push, &mut pop);
+                            if push.full {
+                                was_full = true;
+                            } else if push.push {
+                                pushed += 1;
+                            }
+                            push.posedge_clk();
+                            settle(&mut push, &mut pop);
+                        } else if !is_push_edge && pop_edge {
+                            settle(&mut push, &mut pop);
+                            if pop.empty {
+                                was_empty_after_push |= pushed > 0;
+                            } else if pop.pop {
+                                assert_eq!(pop.pop_data, popped & 0xffff);
+                                popped += 1;
+                            }
+                            pop.posedge_clk();
+                            settle(&mut push, &mut pop);
+                        }
+                        assert!(popped <= pushed);
+                        assert!(pushed - popped <= depth);
+                    }
+
+                    time += 1;
+                }
+                assert_eq!(pushed, num_transfers);
+                assert!(was_full);
+                assert!(was_empty_after_push);
+
+                // Everything that was pushed has been popped, so the FIFO ends up empty once the pointers are synchronized
+                for _ in 0..4 {
+                    settle(&mut push, &mut pop);
+                    push.posedge_clk();
+                    pop.posedge_clk();
+                }
+                settle(&mut push, &mut pop);
+                assert_eq!(pop.empty, true);
+                assert_eq!(push.full, false);
+            }
+        };
+    }
+
+    async_fifo_test!(
+        async_fifo_depth2_faster_push,
+        AsyncFifoDepth2Push,
+        AsyncFifoDepth2Pop,
+        2,
+        2,
+        5
+    );
+    async_fifo_test!(
+        async_fifo_depth8_faster_push,
+        AsyncFifoDepth8Push,
+        AsyncFifoDepth8Pop,
+        8,
+        3,
+        7
+    );
+    async_fifo_test!(
+        async_fifo_depth8_faster_pop,
+        AsyncFifoDepth8Push,
+        AsyncFifoDepth8Pop,
+        8,
+        7,
+        3
+    );
+    async_fifo_test!(
+        async_fifo_depth8_same_clock,
+        AsyncFifoDepth8Push,
+        AsyncFifoDepth8Pop,
+        8,
+        1,
+        1
+    );
+
+    #[test]
+    fn export_outputs() {
+        let mut m = ExportOutputsTestModule::new();
+
+        m.reset();
+        m.i = 3;
+        m.j = 4;
+        m.prop();
+        assert_eq!(m.registered, 0);
+        assert_eq!(m.sum, 4);
+        assert_eq!(m.sum_doubled, 8);
+
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.registered, 3);
+        assert_eq!(m.sum, 7);
+        assert_eq!(m.sum_doubled, 14);
+
+        m.i = 200;
+        m.j = 100;
+        m.prop();
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.registered, 200);
+        assert_eq!(m.sum, 44);
+        assert_eq!(m.sum_doubled, 88);
+    }
+
+    #[test]
+    fn csr_bank() {
+        fn read(m: &mut CsrBankTestModule, address: u32) -> u32 {
+            m.address = address;
+            m.write_enable = false;
+            m.prop();
+            m.read_data
+        }
+
+        fn write(m: &mut CsrBankTestModule, address: u32, data: u32) {
+            m.address = address;
+            m.write_data = data;
+            m.write_enable = true;
+            m.prop();
+            m.posedge_clk();
+            m.write_enable = false;
+            m.prop();
+        }
+
+        let mut m = CsrBankTestModule::new();
+
+        m.reset();
+        m.prop();
+
+        // Reset values
+        assert_eq!(read(&mut m, 0x00), 0x0064_0050);
+        assert_eq!(m.ctrl_enable, false);
+        assert_eq!(m.ctrl_mode, 5);
+        assert_eq!(m.ctrl_divider, 100);
+        assert_eq!(read(&mut m, 0x08), 0);
+        assert_eq!(read(&mut m, 0x0c), 0x123);
+
+        // Read/write fields; bits outside of fields read as 0
+        write(&mut m, 0x00, 0xffff_ffff);
+        assert_eq!(read(&mut m, 0x00), 0xffff_0071);
+        assert_eq!(m.ctrl_enable, true);
+        assert_eq!(m.ctrl_mode, 7);
+        assert_eq!(m.ctrl_divider, 0xffff);
+        write(&mut m, 0x00, 0x1234_0020);
+        assert_eq!(read(&mut m, 0x00), 0x1234_0020);
+        assert_eq!(m.ctrl_enable, false);
+        assert_eq!(m.ctrl_mode, 2);
+        assert_eq!(m.ctrl_divider, 0x1234);
+
+        // Read-only fields reflect hardware inputs and ignore writes
+        m.status_busy = true;
+        m.status_level = 0xab;
+        assert_eq!(read(&mut m, 0x04), 0xab01);
+        write(&mut m, 0x04, 0);
+        assert_eq!(read(&mut m, 0x04), 0xab01);
+
+        // Unmapped addresses read as 0 and ignore writes
+        assert_eq!(read(&mut m, 0x10), 0);
+        write(&mut m, 0x10, 0xffff_ffff);
+        assert_eq!(read(&mut m, 0x00), 0x1234_0020);
+
+        // Write-1-to-clear fields are set by hardware...
+        m.irq_pending_set = 0b0101;
+        m.irq_overflow_set = true;
+        m.prop();
+        m.posedge_clk();
+        m.irq_pending_set = 0;
+        m.irq_overflow_set = false;
+        assert_eq!(read(&mut m, 0x08), 0x8000_0005);
+        assert_eq!(m.irq_pending, 0b0101);
+
+        // ...are unaffected by writing 0s...
+        write(&mut m, 0x08, 0);
+        assert_eq!(read(&mut m, 0x08), 0x8000_0005);
+
+        // ...are cleared by writing 1s...
+        write(&mut m, 0x08, 0x8000_0001);
+        assert_eq!(read(&mut m, 0x08), 0x0000_0004);
+        assert_eq!(m.irq_overflow, false);
+
+        // ...and hardware sets take priority over software clears
+        m.irq_pending_set = 0b0100;
+        write(&mut m, 0x08, 0xf);
+        m.irq_pending_set = 0;
+        assert_eq!(read(&mut m, 0x08), 0b0100);
+        write(&mut m, 0x08, 0xf);
+        assert_eq!(read(&mut m, 0x08), 0);
+
+        // Fields with hardware updates can be written by software...
+        write(&mut m, 0x0c, 0xabc);
+        assert_eq!(read(&mut m, 0x0c), 0xabc);
+        assert_eq!(m.count_value, 0xabc);
+
+        // ...and by hardware, which takes priority
+        m.count_value_update = true;
+        m.count_value_update_data = 0x456;
+        write(&mut m, 0x0c, 0x789);
+        m.count_value_update = false;
+        assert_eq!(read(&mut m, 0x0c), 0x456);
+        m.count_value_update = true;
+        m.count_value_update_data = 0x457;
+        m.prop();
+        m.posedge_clk();
+        m.count_value_update = false;
+        assert_eq!(read(&mut m, 0x0c), 0x457);
+
+        // Other registers are unaffected
+        assert_eq!(read(&mut m, 0x00), 0x1234_0020);
+    }
+
+    // Reference software CRC implementations, independent of kaze's own
+    fn software_crc32(data: &[u8]) -> u32 {
+        let mut crc = 0xffff_ffffu32;
+        for &byte in data {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    fn software_crc16_ccitt(data: &[u8]) -> u16 {
+        let mut crc = 0xffffu16;
+        for &byte in data {
+            crc ^= (byte as u16) << 8;
+            for _ in 0..8 {
+                crc = if crc & 0x8000 != 0 {
+                    (crc << 1) ^ 0x1021
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc
+    }
+
+    fn software_crc8(data: &[u8]) -> u8 {
+        let mut crc = 0u8;
+        for &byte in data {
+            crc ^= byte;
+            for _ in 0..8 {
+                crc = if crc & 0x80 != 0 {
+                    (crc << 1) ^ 0x07
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc
+    }
+
+    fn crc_test_messages() -> Vec<Vec<u8>> {
+        let mut rng_state = 0x1234_5678u32;
+        let mut random_bytes = |len: usize| {
+            (0..len)
+                .map(|_| {
+                    rng_state ^= rng_state << 13;
+                    rng_state ^= rng_state >> 17;
+                    rng_state ^= rng_state << 5;
+                    rng_state as u8
+                })
+                .collect::<Vec<_>>()
+        };
+        vec![
+            b"12345678".to_vec(),
+            vec![0; 16],
+            vec![0xff; 4],
+            random_bytes(64),
+            random_bytes(1024),
+        ]
+    }
+
+    #[test]
+    fn crc_check_values() {
+        assert_eq!(software_crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(software_crc16_ccitt(b"123456789"), 0x29b1);
+        assert_eq!(software_crc8(b"123456789"), 0xf4);
+    }
+
+    macro_rules! crc_test {
+        ($test_name:ident, $module:ident, $data_ty:ty, $little_endian:expr, $software_crc:ident) => {
+            #[test]
+            fn $test_name() {
+                let word_bytes = std::mem::size_of::<$data_ty>();
+
+                let mut m = $module::new();
+
+                m.reset();
+                m.prop();
+
+                for message in crc_test_messages() {
+                    // Each message starts with a clear that also processes its first word
+                    for (index, word) in message.chunks(word_bytes).enumerate() {
+                        let mut data: $data_ty = 0;
+                        for (byte_index, &byte) in word.iter().enumerate() {
+                            let shift = if $little_endian {
+                                byte_index
+                            } else {
+                                word_bytes - 1 - byte_index
+                            } * 8;
+                            data |= (byte as $data_ty) << shift;
+                        }
+                        m.data = data as _;
+                        m.valid = true;
+                        m.clear = index == 0;
+                        m.prop();
+                        m.posedge_clk();
+                    }
+                    m.valid = false;
+                    m.clear = false;
+                    m.prop();
+                    assert_eq!(m.crc, $software_crc(&message) as _);
+
+                    // Holding `valid` low leaves the CRC unchanged
+                    m.posedge_clk();
+                    m.prop();
+                    assert_eq!(m.crc, $software_crc(&message) as _);
+                }
+
+                // Clearing without processing any data returns to the initial value
+                m.clear = true;
+                m.prop();
+                m.posedge_clk();
+                m.clear = false;
+                m.prop();
+                assert_eq!(m.crc, $software_crc(&[]) as _);
+            }
+        };
+    }
+
+    crc_test!(crc32_word32, Crc32Word32, u32, true, software_crc32);
+    crc_test!(crc32_word8, Crc32Word8, u8, true, software_crc32);
+    crc_test!(
+        crc16_ccitt_word16,
+        Crc16CcittWord16,
+        u16,
+        false,
+        software_crc16_ccitt
+    );
+    crc_test!(crc8_word8, Crc8Word8, u8, false, software_crc8);
+    crc_test!(crc8_word16, Crc8Word16, u16, false, software_crc8);
+
+    #[test]
+    fn min_max() {
+        let mut m = MinMaxTestModule::new();
+
+        // Includes equal operands and the most negative signed value (0x80)
+        for a in 0..=255u32 {
+            for b in 0..=255u32 {
+                // Bounds for the non-literal clamps, derived from b and a third value so that a can be on either side of them
+                let c = (b * 37 + a + 11) & 0xff;
+
+                m.a = a;
+                m.b = b;
+                m.lo = b.min(c);
+                m.hi = b.max(c);
+                m.prop();
+
+                let a_signed = a as u8 as i8;
+                let b_signed = b as u8 as i8;
+                let c_signed = c as u8 as i8;
+                assert_eq!(m.min, a.min(b));
+                assert_eq!(m.max, a.max(b));
+                assert_eq!(m.min_signed, a_signed.min(b_signed) as u8 as u32);
+                assert_eq!(m.max_signed, a_signed.max(b_signed) as u8 as u32);
+                assert_eq!(m.clamp_lit, a.clamp(16, 200));
+                assert_eq!(m.clamp_signed_lit, a_signed.clamp(-100, 100) as u8 as u32);
+                assert_eq!(m.clamp, a.clamp(b.min(c), b.max(c)));
+
+                m.lo = b_signed.min(c_signed) as u8 as u32;
+                m.hi = b_signed.max(c_signed) as u8 as u32;
+                m.prop();
+                assert_eq!(
+                    m.clamp_signed,
+                    a_signed.clamp(b_signed.min(c_signed), b_signed.max(c_signed)) as u8 as u32
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn negate_abs_8() {
+        let mut m = NegateAbs8TestModule::new();
+
+        for i in 0..=255u32 {
+            m.i = i;
+            m.prop();
+
+            let i_signed = i as u8 as i8;
+            assert_eq!(m.negate, i_signed.wrapping_neg() as u8 as u32);
+            assert_eq!(m.abs, i_signed.wrapping_abs() as u8 as u32);
+        }
+
+        // The most negative value is its own negation and absolute value
+        m.i = 0x80;
+        m.prop();
+        assert_eq!(m.negate, 0x80);
+        assert_eq!(m.abs, 0x80);
+    }
+
+    #[test]
+    fn negate_abs_128() {
+        let mut m = NegateAbs128TestModule::new();
+
+        for &i in &[
+            0,
+            1,
+            5,
+            i128::MAX,
+            i128::MIN,
+            i128::MIN + 1,
+            -1,
+            -5,
+            0x0123_4567_89ab_cdef_0123_4567_89ab_cdef,
+            -0x0123_4567_89ab_cdef_0123_4567_89ab_cdef,
+        ] {
+            m.i = i as u128;
+            m.prop();
+
+            assert_eq!(m.negate, i.wrapping_neg() as u128);
+            assert_eq!(m.abs, i.wrapping_abs() as u128);
+        }
+
+        // The most negative value is its own negation and absolute value
+        m.i = 1 << 127;
+        m.prop();
+        assert_eq!(m.negate, 1 << 127);
+        assert_eq!(m.abs, 1 << 127);
+    }
+
+    macro_rules! fsm_test {
+        ($test_name:ident, $module:ident) => {
+            #[test]
+            fn $test_name() {
+                let mut m = $module::new();
+
+                m.reset();
+                m.prop();
+                assert_eq!(m.code, 0);
+                assert_eq!(m.req, false);
+
+                // (start, grant, abort, ack) for each cycle, and the expected state code afterwards
+                for &(start, grant, abort, ack, code) in &[
+                    (false, false, false, false, 0), // Stays Idle
+                    (true, false, false, false, 1),  // Idle -> Request
+                    (false, false, false, false, 1), // Stays in Request
+                    (false, true, true, false, 2), // Grant has priority over abort: Request -> Wait
+                    (false, false, false, false, 2), // Stays in Wait
+                    (false, false, false, true, 3), // Wait -> Done
+                    (true, true, true, true, 0),   // Done -> Idle unconditionally
+                    (true, false, false, false, 1), // Idle -> Request
+                    (false, false, true, false, 0), // Request -> Idle (abort)
+                ] {
+                    m.start = start;
+                    m.grant = grant;
+                    m.abort = abort;
+                    m.ack = ack;
+                    m.prop();
+                    m.posedge_clk();
+                    m.prop();
+                    assert_eq!(m.code, code);
+                    assert_eq!(m.req, code == 1 || code == 2);
+                    assert_eq!(m.done, code == 3);
+                }
+            }
+        };
+    }
+
+    fsm_test!(binary_fsm, BinaryFsmTestModule);
+    fsm_test!(one_hot_fsm, OneHotFsmTestModule);
+
+    #[test]
+    fn fsm_encodings_equivalent() {
+        let mut binary = BinaryFsmTestModule::new();
+        let mut one_hot = OneHotFsmTestModule::new();
+
+        binary.reset();
+        one_hot.reset();
+
+        let mut rng_state = 0x2545_f491u32;
+        for _ in 0..10000 {
+            rng_state ^= rng_state << 13;
+            rng_state ^= rng_state >> 17;
+            rng_state ^= rng_state << 5;
+
+            binary.start = rng_state & 1 != 0;
+            binary.grant = rng_state & 2 != 0;
+            binary.abort = rng_state & 4 != 0;
+            binary.ack = rng_state & 8 != 0;
+            one_hot.start = binary.start;
+            one_hot.grant = binary.grant;
+            one_hot.abort = binary.abort;
+            one_hot.ack = binary.ack;
+
+            binary.prop();
+            one_hot.prop();
+            assert_eq!(binary.code, one_hot.code);
+            assert_eq!(binary.req, one_hot.req);
+            assert_eq!(binary.done, one_hot.done);
+
+            binary.posedge_clk();
+            one_hot.posedge_clk();
+        }
+    }
+
+    #[test]
+    fn drive_inputs() {
+        let mut m = DriveInputsTestModule::new();
+
+        m.i0 = 0x1;
+        m.i1 = 0x2;
+        m.i2 = 0x3;
+        m.i3 = 0x4;
+        m.i4 = 0x5;
+        m.i5 = 0x6;
+        m.i6 = 0x7;
+        m.i7 = 0x8;
+        m.i8 = 0x9;
+        m.i9 = 0xa;
+        m.i10 = 0xb;
+        m.i11 = 0xc;
+        m.prop();
+        assert_eq!(m.o, 0x123456789abc);
+        assert_eq!(m.o_reversed, 0xcba987654321);
+
+        m.i0 = 0xf;
+        m.i11 = 0x0;
+        m.prop();
+        assert_eq!(m.o, 0xf23456789ab0);
+        assert_eq!(m.o_reversed, 0x0ba98765432f);
+    }
+
+    #[test]
+    fn struct_name_override() {
+        let mut m = CoreSim::new();
+
+        m.reset();
+        m.enable = true;
+        m.tick_n(3);
+        assert_eq!(m.count, 3);
+
+        let state: CoreSimState = m.save_state();
+        m.tick_n(2);
+        assert_eq!(m.count, 5);
+        m.load_state(&state);
+        m.prop();
+        assert_eq!(m.count, 3);
+    }
+
+    #[test]
+    fn module_wrapper() {
+        let mut m = wrapped_core::Core::new();
+
+        m.reset();
+        m.enable = true;
+        m.tick_n(0x100);
+        assert_eq!(m.count, 0);
+        let coverage: wrapped_core::CoreCoverageReport = m.coverage();
+        assert_eq!(coverage.uncovered_bits("count"), 0);
+
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        assert_sim_modules_equivalent::<CoreSim, wrapped_core::Core>(1000, &mut next_u64);
+    }
+
+    #[test]
+    fn bmc_exhaustive() {
+        let failure = bmc_sim_module::<BmcCounterTestModule>(5, BmcStrategy::Exhaustive).unwrap();
+
+        // Shortest counterexample: four enabled cycles bring the counter to 4
+        assert_eq!(failure.cycle, 4);
+        assert_eq!(failure.assertion, "count must be less than 4");
+        assert_eq!(
+            failure.inputs,
+            vec![
+                vec![("clear", 0), ("enable", 1)],
+                vec![("clear", 0), ("enable", 1)],
+                vec![("clear", 0), ("enable", 1)],
+                vec![("clear", 0), ("enable", 1)],
+                vec![("clear", 0), ("enable", 0)],
+            ]
+        );
+
+        // Too shallow to reach the failure
+        assert_eq!(
+            bmc_sim_module::<BmcCounterTestModule>(4, BmcStrategy::Exhaustive),
+            None
+        );
+    }
+
+    #[test]
+    fn bmc_random() {
+        let mut state = 0x2545f4914f6cdd1du64;
+        let mut next_u64 = || {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let failure = bmc_sim_module::<BmcCounterTestModule>(
+            5,
+            BmcStrategy::Random {
+                num_runs: 1000,
+                next_u64: &mut next_u64,
+            },
+        )
+        .unwrap();
+        assert_eq!(failure.cycle, 4);
+        assert_eq!(failure.assertion, "count must be less than 4");
+        assert_eq!(failure.inputs.len(), 5);
+        // Random sequences where clear is set violate the assumption, so they're discarded
+        assert!(failure
+            .inputs
+            .iter()
+            .all(|values| values[0] == ("clear", 0)));
+        assert_eq!(
+            replay_sim_module::<BmcCounterTestModule, _>(&failure.inputs),
+            Some(failure)
+        );
+    }
+
+    #[test]
+    fn bmc_replay() {
+        // Pasted from the `{:?}` output of an exhaustive check's failure
+        let inputs = [
+            [("clear", 0), ("enable", 1)],
+            [("clear", 0), ("enable", 1)],
+            [("clear", 0), ("enable", 1)],
+            [("clear", 0), ("enable", 1)],
+            [("clear", 0), ("enable", 0)],
+        ];
+        let failure = replay_sim_module::<BmcCounterTestModule, _>(&inputs).unwrap();
+        assert_eq!(failure.cycle, 4);
+        assert_eq!(failure.assertion, "count must be less than 4");
+
+        assert_eq!(
+            replay_sim_module::<BmcCounterTestModule, _>(&inputs[..4]),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "An assumption was violated on cycle 1 while replaying inputs into simulator BmcCounterTestModule."
+    )]
+    fn bmc_replay_assumption_violated() {
+        // Panic
+        replay_sim_module::<BmcCounterTestModule, _>(&[
+            [("clear", 0), ("enable", 1)],
+            [("clear", 1), ("enable", 1)],
+        ]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot exhaustively check simulator BmcWideInputTestModule, because its inputs have 9 bit(s) per cycle, which is more than BMC_MAX_EXHAUSTIVE_INPUT_BITS (8). Use BmcStrategy::Random instead."
+    )]
+    fn bmc_exhaustive_input_bits_error() {
+        // Panic
+        bmc_sim_module::<BmcWideInputTestModule>(1, BmcStrategy::Exhaustive);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot exhaustively check simulator BmcCounterTestModule to a depth of 13 cycle(s), because its input sequences would have 26 bit(s), which is more than BMC_MAX_EXHAUSTIVE_SEQUENCE_BITS (24). Use a smaller depth or BmcStrategy::Random instead."
+    )]
+    fn bmc_exhaustive_sequence_bits_error() {
+        // Panic
+        bmc_sim_module::<BmcCounterTestModule>(13, BmcStrategy::Exhaustive);
+    }
+
+    macro_rules! sync_fifo_test {
+        ($name:ident, $module:ident, $depth:expr) => {
+            #[test]
+            fn $name() {
+                let depth: u32 = $depth;
+
+                let mut m = $module::new();
+                m.reset();
+
+                // Underflow: popping while empty has no effect
+                m.pop = true;
+                m.tick();
+                assert_eq!(m.empty, true);
+                assert_eq!(m.count as u32, 0);
+
+                // Simultaneous push and pop while empty only pushes
+                m.push = true;
+                m.push_data = 0xa0;
+                m.tick();
+                assert_eq!(m.empty, false);
+                assert_eq!(m.count as u32, 1);
+                assert_eq!(m.pop_data, 0xa0);
+
+                // Fill
+                m.pop = false;
+                for i in 1..depth {
+                    m.push_data = 0xa0 + i;
+                    m.tick();
+                    assert_eq!(m.count as u32, i + 1);
+                }
+                assert_eq!(m.full, true);
+                assert_eq!(m.pop_data, 0xa0);
+
+                // Overflow: pushing while full has no effect
+                m.push_data = 0xff;
+                m.tick();
+                assert_eq!(m.full, true);
+                assert_eq!(m.count as u32, depth);
+                assert_eq!(m.pop_data, 0xa0);
+
+                // Simultaneous push and pop while full replaces the head element
+                m.pop = true;
+                m.push_data = 0xb0;
+                m.tick();
+                assert_eq!(m.full, true);
+                assert_eq!(m.count as u32, depth);
+
+                // Drain
+                m.push = false;
+                for i in 1..depth {
+                    assert_eq!(m.pop_data, 0xa0 + i);
+                    m.tick();
+                    assert_eq!(m.count as u32, depth - i);
+                }
+                assert_eq!(m.pop_data, 0xb0);
+                m.tick();
+                assert_eq!(m.empty, true);
+                assert_eq!(m.count as u32, 0);
+
+                // Random traffic against a reference model
+                let mut state = 0x9e37_79b9_7f4a_7c15u64;
+                let mut next_u64 = move || {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state
+                };
+                let mut model = std::collections::VecDeque::new();
+                for _ in 0..10000 {
+                    let r = next_u64();
+                    m.push = (r & 1) != 0;
+                    m.pop = (r & 2) != 0;
+                    m.push_data = (r >> 8) as u32 & 0xff;
+
+                    let popped = m.pop && !model.is_empty();
+                    let pushed = m.push && ((model.len() as u32) < depth || popped);
+                    if popped {
+                        assert_eq!(m.pop_data, model.pop_front().unwrap());
+                    }
+                    if pushed {
+                        model.push_back(m.push_data);
+                    }
+
+                    m.tick();
+
+                    assert_eq!(m.count as usize, model.len());
+                    assert_eq!(m.empty, model.is_empty());
+                    assert_eq!(m.full, model.len() as u32 == depth);
+                    if let Some(&head) = model.front() {
+                        assert_eq!(m.pop_data, head);
+                    }
+                }
+            }
+        };
+    }
+
+    sync_fifo_test!(sync_fifo_depth_1, SyncFifoDepth1, 1);
+    sync_fifo_test!(sync_fifo_depth_3, SyncFifoDepth3, 3);
+    sync_fifo_test!(sync_fifo_depth_4, SyncFifoDepth4, 4);
+
+    #[test]
+    fn sim_module_equivalence() {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        assert_eq!(
+            sim_module_interface_diff::<DecadeCounterTestModule, RefactoredDecadeCounterTestModule>(
+            ),
+            None
+        );
+        assert_sim_modules_equivalent::<DecadeCounterTestModule, RefactoredDecadeCounterTestModule>(
+            1000,
+            &mut next_u64,
+        );
+    }
+
+    #[test]
+    fn if_sugar_equivalence() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        assert_sim_modules_equivalent::<DebugTestModule, SugarDebugTestModule>(1000, &mut next_u64);
+    }
+
+    #[test]
+    fn pipeline_equivalence() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next_u64 = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        assert_sim_modules_equivalent::<
+            pipelined_modules::PipelineTestModule,
+            DelayedPipelineTestModule,
+        >(1000, &mut next_u64);
+
+        // Constant outputs aren't delayed
+        let mut m = pipelined_modules::PipelineTestModule::new();
+        m.prop();
+        assert_eq!(m.version, 5);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Simulators DecadeCounterTestModule and OffByOneDecadeCounterTestModule diverged on cycle 9: output \"wrap\" is 0x1 in DecadeCounterTestModule but 0x0 in OffByOneDecadeCounterTestModule. Inputs on that cycle: [en = 0x1]."
+    )]
+    fn sim_module_equivalence_off_by_one() {
+        assert_sim_modules_equivalent_with_stimulus::<
+            DecadeCounterTestModule,
+            OffByOneDecadeCounterTestModule,
+        >(100, |_, inputs| inputs[0] = 1);
+    }
+
+    #[test]
+    fn sim_module_equivalence_interface_mismatch() {
+        assert_eq!(
+            sim_module_interface_diff::<DecadeCounterTestModule, RegTestModule>().unwrap(),
+            "- input en: 1 bit(s)\n\
+             + input i1: 32 bit(s)\n\
+             + input i2: 32 bit(s)\n\
+             - output count: 4 bit(s)\n\
+             - output wrap: 1 bit(s)\n\
+             + output o1: 32 bit(s)\n\
+             + output o2: 32 bit(s)\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Simulators DecadeCounterTestModule and RegTestModule have different interfaces (\"-\" ports are only in DecadeCounterTestModule, \"+\" ports are only in RegTestModule):\n- input en: 1 bit(s)\n"
+    )]
+    fn sim_module_equivalence_interface_mismatch_error() {
+        assert_sim_modules_equivalent_with_stimulus::<DecadeCounterTestModule, RegTestModule>(
+            1,
+            |_, _| {},
+        );
+    }
+
     #[test]
     fn add_test_module() {
         let mut m = AddTestModule::new();
@@ -832,6 +2906,169 @@ mod tests {
         assert_eq!(m.o2, 0xfadebabe);
     }
 
+    #[test]
+    fn reg_test_module_tick() {
+        let mut m = RegTestModule::new();
+
+        // Check initial value
+        m.reset();
+        m.prop();
+        assert_eq!(m.o1, 0);
+
+        // Register value doesn't change without clock edge
+        m.i1 = 0xdeadbeef;
+        m.prop();
+        assert_eq!(m.o1, 0);
+        m.tick();
+        assert_eq!(m.o1, 0xdeadbeef);
+        m.i1 = 0xfadebabe;
+        m.prop();
+        assert_eq!(m.o1, 0xdeadbeef);
+
+        // Clock in initial value (second reg explicitly doesn't have one!)
+        m.i2 = 0xfadebabe;
+        m.tick();
+        assert_eq!(m.o2, 0xfadebabe);
+
+        // Register with no default value doesn't change with reset
+        m.reset();
+        m.prop();
+        assert_eq!(m.o2, 0xfadebabe);
+    }
+
+    #[test]
+    fn simple_reg_delay_tick_n() {
+        let mut m = SimpleRegDelay::new();
+
+        m.reset();
+        m.i = 0xffffffffffffffffffffffffffffffff;
+        m.tick_n(2);
+        assert_eq!(m.o, 0);
+        m.tick_n(1);
+        assert_eq!(m.o, 0xfffffffffffffffffffffffff);
+        m.i = 0;
+        m.tick_n(0);
+        assert_eq!(m.o, 0xfffffffffffffffffffffffff);
+        m.tick_n(3);
+        assert_eq!(m.o, 0);
+    }
+
+    #[test]
+    fn tick_without_state() {
+        let mut m = AddTestModule::new();
+
+        m.i3 = 1;
+        m.i4 = 2;
+        m.tick();
+        assert_eq!(m.o2, 3);
+    }
+
+    #[test]
+    fn random_reg_test_module() {
+        let mut m1 = RandomRegTestModule::new_with_seed(1);
+        let mut m2 = RandomRegTestModule::new_with_seed(2);
+
+        // The same seed always produces the same values
+        let mut m3 = RandomRegTestModule::new_with_seed(1);
+        m1.prop();
+        m3.prop();
+        assert_eq!(m1.reset_o, m3.reset_o);
+        assert_eq!(m1.no_reset_o, m3.no_reset_o);
+
+        // Different seeds diverge before reset
+        m2.prop();
+        assert_ne!(m1.reset_o, m2.reset_o);
+        assert_ne!(m1.no_reset_o, m2.no_reset_o);
+
+        // Reset converges registers with default values only
+        m1.reset();
+        m2.reset();
+        m1.prop();
+        m2.prop();
+        assert_eq!(m1.reset_o, 0);
+        assert_eq!(m2.reset_o, 0);
+        assert_ne!(m1.no_reset_o, m2.no_reset_o);
+
+        // Holding a value doesn't converge anything
+        m1.tick();
+        m2.tick();
+        assert_ne!(m1.no_reset_o, m2.no_reset_o);
+
+        // Writing converges the rest
+        for m in [&mut m1, &mut m2].iter_mut() {
+            m.en = true;
+            m.i = 0xdeadbeef;
+            m.tick();
+        }
+        assert_eq!(m1.reset_o, 0xdeadbeef);
+        assert_eq!(m2.reset_o, 0xdeadbeef);
+        assert_eq!(m1.no_reset_o, 0xdeadbeef);
+        assert_eq!(m2.no_reset_o, 0xdeadbeef);
+
+        // new() is the same as using a seed of 0
+        let mut m1 = RandomRegTestModule::new();
+        let mut m2 = RandomRegTestModule::new_with_seed(0);
+        m1.prop();
+        m2.prop();
+        assert_eq!(m1.no_reset_o, m2.no_reset_o);
+    }
+
+    #[test]
+    fn poison_reg_test_module() {
+        let mut m = PoisonRegTestModule::new();
+
+        // Holding poisoned values is OK, as is writing memory with a disabled write port
+        m.reset();
+        m.tick();
+
+        // Registers are written from inputs
+        m.en = true;
+        m.i = 0xfadebabe;
+        m.tick();
+        assert_eq!(m.reset_o, 0xfadebabe);
+        assert_eq!(m.no_reset_o, 0xfadebabe);
+
+        // Memory is written from a register that's been written
+        m.tick();
+        m.en = false;
+        m.tick_n(2);
+        assert_eq!(m.mem_o, 0xfadebabe);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Memory \"mem\" in module \"PoisonRegTestModule\" was written with a value derived from a register that hasn't been written yet."
+    )]
+    fn poison_reg_test_module_missing_reset() {
+        let mut m = PoisonRegTestModule::new();
+
+        // Panic
+        m.en = true;
+        m.tick();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Register \"counter\" in module \"PoisonCounterTestModule\" was clocked with a value derived from a register that hasn't been written yet."
+    )]
+    fn poison_counter_test_module() {
+        let mut m = PoisonCounterTestModule::new();
+
+        // Panic
+        m.tick();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Register \"counter\" in module \"PoisonCounterTestModule\" was clocked with a value derived from a register that hasn't been written yet."
+    )]
+    fn poison_instance_test_module() {
+        let mut m = PoisonInstanceTestModule::new();
+
+        // Panic
+        m.tick();
+    }
+
     #[test]
     fn simple_reg_delay() {
         let mut m = SimpleRegDelay::new();
@@ -841,19 +3078,153 @@ mod tests {
         m.prop();
         assert_eq!(m.o, 0);
 
-        // The input propagates through 3 registers, so we won't see it output for 3 cycles
-        m.i = 0xffffffffffffffffffffffffffffffff;
-        m.prop(); // Propagate to first register input
-        assert_eq!(m.o, 0);
-        m.posedge_clk();
-        m.prop();
-        assert_eq!(m.o, 0);
-        m.posedge_clk();
-        m.prop();
-        assert_eq!(m.o, 0);
-        m.posedge_clk();
-        m.prop();
-        assert_eq!(m.o, 0xfffffffffffffffffffffffff);
+        // The input propagates through 3 registers, so we won't see it output for 3 cycles
+        m.i = 0xffffffffffffffffffffffffffffffff;
+        m.prop(); // Propagate to first register input
+        assert_eq!(m.o, 0);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.o, 0);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.o, 0);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.o, 0xfffffffffffffffffffffffff);
+    }
+
+    #[test]
+    fn simple_reg_delay_save_state() {
+        let inputs = [0x1u128, 0x22, 0x333, 0x4444, 0x55555, 0x666666, 0, 0];
+
+        // Uninterrupted run
+        let mut m = SimpleRegDelay::new();
+        m.reset();
+        let mut expected = Vec::new();
+        for &i in inputs.iter() {
+            m.i = i;
+            m.tick();
+            expected.push(m.o);
+        }
+
+        // Checkpoint mid-pipeline and continue in a fresh simulator
+        let mut m = SimpleRegDelay::new();
+        m.reset();
+        let mut actual = Vec::new();
+        for &i in inputs[..4].iter() {
+            m.i = i;
+            m.tick();
+            actual.push(m.o);
+        }
+        let state = m.save_state();
+        assert_eq!(state.r_stage0, 0x4444);
+        assert_eq!(state.r_stage1, 0x333);
+        assert_eq!(state.r_stage2, 0x22);
+
+        // Diverge the original simulator to make sure the state is a copy
+        m.i = 0xdead;
+        m.tick_n(3);
+
+        let mut m = SimpleRegDelay::new();
+        m.load_state(&state);
+        for &i in inputs[4..].iter() {
+            m.i = i;
+            m.tick();
+            actual.push(m.o);
+        }
+
+        assert_eq!(actual, expected);
+        assert_eq!(
+            m.save_state(),
+            SimpleRegDelayState {
+                r_stage0: 0,
+                r_stage1: 0,
+                r_stage2: 0x666666,
+            }
+        );
+    }
+
+    #[test]
+    fn simple_reg_delay_vcd() {
+        use std::collections::HashMap;
+        use std::fs::{self, File};
+        use std::io::BufWriter;
+
+        let path = std::env::temp_dir().join(format!(
+            "kaze-sim-tests-simple-reg-delay-{}.vcd",
+            std::process::id()
+        ));
+
+        let mut m = SimpleRegDelay::new();
+        {
+            let mut w = BufWriter::new(File::create(&path).unwrap());
+            m.dump_vcd_header(&mut w).unwrap();
+
+            m.reset();
+            m.i = 0xffffffffffffffffffffffffffffffff;
+            m.prop();
+            m.dump_vcd(&mut w, 0).unwrap();
+            for time in 1..5 {
+                m.posedge_clk();
+                m.i = 0;
+                m.prop();
+                m.dump_vcd(&mut w, time).unwrap();
+            }
+        }
+        let vcd = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // Parse declarations and value changes
+        let mut names = HashMap::new();
+        let mut scopes = Vec::new();
+        let mut values = Vec::new();
+        let mut lines = vcd.lines();
+        for line in &mut lines {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            match tokens[0] {
+                "$scope" => scopes.push(tokens[2]),
+                "$var" => {
+                    assert_eq!(tokens[1], "wire");
+                    assert_eq!(tokens[2], "100");
+                    names.insert(tokens[3], tokens[4]);
+                }
+                "$enddefinitions" => break,
+                _ => (),
+            }
+        }
+        assert_eq!(scopes, vec!["SimpleRegDelay"]);
+        let mut sorted_names = names.values().cloned().collect::<Vec<_>>();
+        sorted_names.sort();
+        assert_eq!(
+            sorted_names,
+            vec!["i", "o", "r_stage0", "r_stage1", "r_stage2"]
+        );
+        for line in lines {
+            if let Some(time) = line.strip_prefix('#') {
+                values.push((time.parse::<u64>().unwrap(), HashMap::new()));
+            } else {
+                let mut tokens = line.split_whitespace();
+                let value = tokens.next().unwrap().strip_prefix('b').unwrap();
+                let name = names[tokens.next().unwrap()];
+                let value = u128::from_str_radix(value, 2).unwrap();
+                values.last_mut().unwrap().1.insert(name, value);
+            }
+        }
+
+        let all_ones = (1u128 << 100) - 1;
+        let times = values.iter().map(|(time, _)| *time).collect::<Vec<_>>();
+        assert_eq!(times, vec![0, 1, 2, 3, 4]);
+        let history = |name| {
+            values
+                .iter()
+                .map(|(_, values)| values[name])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(history("i"), vec![all_ones, 0, 0, 0, 0]);
+        assert_eq!(history("r_stage0"), vec![0, all_ones, 0, 0, 0]);
+        assert_eq!(history("r_stage1"), vec![0, 0, all_ones, 0, 0]);
+        assert_eq!(history("r_stage2"), vec![0, 0, 0, all_ones, 0]);
+        assert_eq!(history("o"), vec![0, 0, 0, all_ones, 0]);
     }
 
     #[test]
@@ -1200,461 +3571,1387 @@ mod tests {
         m.i3 = 0x00ff0000;
         m.i4 = 0x000f0000;
         m.prop();
-        assert_eq!(m.o, 0x000f0000u32);
+        assert_eq!(m.o, 0x000f0000u32);
+
+        m.i1 = 0x00000f00;
+        m.i2 = 0xffffffff;
+        m.i3 = 0x0000ffff;
+        m.i4 = 0xffffffff;
+        m.prop();
+        assert_eq!(m.o, 0x00000f00u32);
+    }
+
+    #[test]
+    fn instantiation_test_module_reg() {
+        let mut m = InstantiationTestModuleReg::new();
+
+        // Check initial value
+        m.reset();
+        m.prop();
+        assert_eq!(m.o, 0);
+
+        // The inputs propagate through 2 registers, so we won't see proper output for 2 cycles
+        m.i1 = 0xffffffff;
+        m.i2 = 0xffff0000;
+        m.i3 = 0x00ff0000;
+        m.i4 = 0x000f0000;
+        m.prop(); // Propagate to first register inputs
+        assert_eq!(m.o, 0u32);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.o, 0u32);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.o, 0x000f0000u32);
+    }
+
+    #[test]
+    fn flattened_instantiation_test_module_reg() {
+        let mut m = InstantiationTestModuleReg::new();
+        let mut flat = flattened_modules::InstantiationTestModuleReg::new();
+
+        m.reset();
+        flat.reset();
+
+        let mut x = 0x12345678u32;
+        for _ in 0..100 {
+            // xorshift32
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            m.i1 = x;
+            flat.i1 = x;
+            m.i2 = x.rotate_left(8);
+            flat.i2 = x.rotate_left(8);
+            m.i3 = x.rotate_left(16);
+            flat.i3 = x.rotate_left(16);
+            m.i4 = !x;
+            flat.i4 = !x;
+            m.prop();
+            flat.prop();
+            assert_eq!(m.o, flat.o);
+            m.posedge_clk();
+            flat.posedge_clk();
+        }
+    }
+
+    #[test]
+    fn flattened_nested_instantiation_test_module() {
+        let mut m = flattened_modules::NestedInstantiationTestModule::new();
+
+        m.i1 = 0xffffffff;
+        m.i2 = 0xffff0000;
+        m.i3 = 0x00ff0000;
+        m.i4 = 0x000f0000;
+        m.prop();
+        assert_eq!(m.o, 0x000f0000u32);
+    }
+
+    #[test]
+    fn nested_instantiation_test_module() {
+        let mut m = NestedInstantiationTestModule::new();
+
+        m.i1 = 0xffffffff;
+        m.i2 = 0xffff0000;
+        m.i3 = 0x00ff0000;
+        m.i4 = 0x000f0000;
+        m.prop();
+        assert_eq!(m.o, 0x000f0000u32);
+
+        m.i1 = 0x00000f00;
+        m.i2 = 0xffffffff;
+        m.i3 = 0x0000ffff;
+        m.i4 = 0xffffffff;
+        m.prop();
+        assert_eq!(m.o, 0x00000f00u32);
+    }
+
+    #[test]
+    fn mem_access_test_module_write_then_read() {
+        let mut m = MemAccessTestModule::new();
+
+        // Writes are visible in the backing array after the clock edge
+        m.write_addr = 3;
+        m.write_value = 0xabc;
+        m.write_enable = true;
+        m.read_addr = 3;
+        m.prop();
+        assert_eq!(m.ram_read(3), 0);
+        m.tick();
+        assert_eq!(m.ram_read(3), 0xabc);
+
+        // Reads of the same address on the same edge return the old value
+        assert_eq!(m.read_data, 0);
+        m.write_enable = false;
+        m.tick();
+        assert_eq!(m.read_data, 0xabc);
+    }
+
+    #[test]
+    fn mem_access_test_module_preload() {
+        let mut m = MemAccessTestModule::new();
+
+        m.ram_contents_mut()
+            .copy_from_slice(&[0x100, 0x201, 0x302, 0x403, 0x504, 0x605, 0x706, 0x807]);
+        m.ram_write(7, 0xffff);
+        assert_eq!(m.ram_read(7), 0xfff);
+        assert_eq!(m.ram_contents()[5], 0x605);
+
+        for address in 0..7 {
+            m.read_addr = address;
+            m.tick();
+            assert_eq!(m.read_data, (address + 1) << 8 | address);
+        }
+
+        // Instance memories are accessed by instance path
+        assert_eq!(m.inner__rom_contents(), &[1, 2, 3, 4]);
+        m.inner__rom_write(2, 0x55);
+        m.rom_addr = 2;
+        m.tick();
+        assert_eq!(m.rom_data, 0x55);
+        assert_eq!(m.inner__rom_read(3), 4);
+    }
+
+    #[test]
+    fn mem_access_test_module_save_state() {
+        let mut m = MemAccessTestModule::new();
+
+        m.write_addr = 1;
+        m.write_value = 0x123;
+        m.write_enable = true;
+        m.read_addr = 1;
+        m.rom_addr = 3;
+        m.tick_n(2);
+        let state = m.save_state();
+        assert_eq!(state.ram[1], 0x123);
+        assert_eq!(state.ram__read_port_0, 0x123);
+        assert_eq!(&*state.inner__rom, &[1, 2, 3, 4]);
+        assert_eq!(state.inner__rom__read_port_0, 4);
+
+        m.write_value = 0x456;
+        m.inner__rom_write(3, 5);
+        m.tick_n(2);
+        assert_eq!(m.read_data, 0x456);
+        assert_eq!(m.rom_data, 5);
+
+        m.load_state(&state);
+        m.prop();
+        assert_eq!(m.read_data, 0x123);
+        assert_eq!(m.rom_data, 4);
+        assert_eq!(m.ram_read(1), 0x123);
+        assert_eq!(m.inner__rom_read(3), 4);
+    }
+
+    #[test]
+    fn mem_test_module_0() {
+        let mut m = MemTestModule0::new();
+
+        // Initial state, no read/write
+        m.write_addr = false;
+        m.write_value = 0;
+        m.write_enable = false;
+        m.read_addr = false;
+        m.read_enable = false;
+        m.prop();
+        assert_eq!(m.read_data, 0);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0);
+
+        // Initial state, read from addr 0
+        m.write_addr = false;
+        m.write_value = 0;
+        m.write_enable = false;
+        m.read_addr = false;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0);
+
+        // Initial state, read from addr 1
+        m.write_addr = false;
+        m.write_value = 0;
+        m.write_enable = false;
+        m.read_addr = true;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0);
+
+        // Initial state, write to addr 0
+        m.write_addr = false;
+        m.write_value = 0x5;
+        m.write_enable = true;
+        m.read_addr = false;
+        m.read_enable = false;
+        m.prop();
+        assert_eq!(m.read_data, 0);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0);
+
+        // Write to addr 1
+        m.write_addr = true;
+        m.write_value = 0xa;
+        m.write_enable = true;
+        m.read_addr = false;
+        m.read_enable = false;
+        m.prop();
+        assert_eq!(m.read_data, 0);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0);
+
+        // Read from addr 0
+        m.write_addr = false;
+        m.write_value = 0;
+        m.write_enable = false;
+        m.read_addr = false;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x5);
+
+        // Read from addr 1
+        m.write_addr = false;
+        m.write_value = 0;
+        m.write_enable = false;
+        m.read_addr = true;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0x5);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0xa);
+
+        // Write to/read from addr 0
+        m.write_addr = false;
+        m.write_value = 0xc;
+        m.write_enable = true;
+        m.read_addr = false;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0xa);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x5);
+
+        // Write to/read from addr 1
+        m.write_addr = true;
+        m.write_value = 0x3;
+        m.write_enable = true;
+        m.read_addr = true;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0x5);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0xa);
+
+        // Read from addr 0
+        m.write_addr = false;
+        m.write_value = 0;
+        m.write_enable = false;
+        m.read_addr = false;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0xa);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0xc);
+
+        // Read from addr 1
+        m.write_addr = false;
+        m.write_value = 0;
+        m.write_enable = false;
+        m.read_addr = true;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0xc);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x3);
+
+        // Write to addr 0/read from addr 1
+        m.write_addr = false;
+        m.write_value = 0x9;
+        m.write_enable = true;
+        m.read_addr = true;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0x3);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x3);
+
+        // Write to addr 1/read from addr 0
+        m.write_addr = true;
+        m.write_value = 0x5;
+        m.write_enable = true;
+        m.read_addr = false;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0x3);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x9);
 
-        m.i1 = 0x00000f00;
-        m.i2 = 0xffffffff;
-        m.i3 = 0x0000ffff;
-        m.i4 = 0xffffffff;
+        // Read from addr 1
+        m.write_addr = false;
+        m.write_value = 0;
+        m.write_enable = false;
+        m.read_addr = true;
+        m.read_enable = true;
         m.prop();
-        assert_eq!(m.o, 0x00000f00u32);
+        assert_eq!(m.read_data, 0x9);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x5);
     }
 
     #[test]
-    fn instantiation_test_module_reg() {
-        let mut m = InstantiationTestModuleReg::new();
+    fn mem_test_module_1() {
+        let mut m = MemTestModule1::new();
 
-        // Check initial value
-        m.reset();
+        // No read
+        m.read_addr = 0;
+        m.read_enable = false;
         m.prop();
-        assert_eq!(m.o, 0);
+        assert_eq!(m.read_data, 0);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0);
 
-        // The inputs propagate through 2 registers, so we won't see proper output for 2 cycles
-        m.i1 = 0xffffffff;
-        m.i2 = 0xffff0000;
-        m.i3 = 0x00ff0000;
-        m.i4 = 0x000f0000;
-        m.prop(); // Propagate to first register inputs
-        assert_eq!(m.o, 0u32);
+        // Read from addr 0
+        m.read_addr = 0;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.o, 0u32);
+        assert_eq!(m.read_data, 0xfadebabe);
+
+        // Read from addr 1
+        m.read_addr = 1;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0xfadebabe);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.o, 0x000f0000u32);
-    }
+        assert_eq!(m.read_data, 0xdeadbeef);
 
-    #[test]
-    fn nested_instantiation_test_module() {
-        let mut m = NestedInstantiationTestModule::new();
+        // Read from addr 2
+        m.read_addr = 2;
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0xdeadbeef);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0xabadcafe);
 
-        m.i1 = 0xffffffff;
-        m.i2 = 0xffff0000;
-        m.i3 = 0x00ff0000;
-        m.i4 = 0x000f0000;
+        // Read from addr 3
+        m.read_addr = 3;
+        m.read_enable = true;
         m.prop();
-        assert_eq!(m.o, 0x000f0000u32);
+        assert_eq!(m.read_data, 0xabadcafe);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0xabad1dea);
 
-        m.i1 = 0x00000f00;
-        m.i2 = 0xffffffff;
-        m.i3 = 0x0000ffff;
-        m.i4 = 0xffffffff;
+        // No read
+        m.read_addr = 0;
+        m.read_enable = false;
         m.prop();
-        assert_eq!(m.o, 0x00000f00u32);
+        assert_eq!(m.read_data, 0xabad1dea);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0xabad1dea);
     }
 
     #[test]
-    fn mem_test_module_0() {
-        let mut m = MemTestModule0::new();
+    fn mem_test_module_2() {
+        let mut m = MemTestModule2::new();
 
         // Initial state, no read/write
         m.write_addr = false;
-        m.write_value = 0;
+        m.write_value = false;
         m.write_enable = false;
         m.read_addr = false;
         m.read_enable = false;
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
 
         // Initial state, read from addr 0
         m.write_addr = false;
-        m.write_value = 0;
+        m.write_value = false;
         m.write_enable = false;
         m.read_addr = false;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
 
         // Initial state, read from addr 1
         m.write_addr = false;
-        m.write_value = 0;
+        m.write_value = false;
         m.write_enable = false;
         m.read_addr = true;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
 
         // Initial state, write to addr 0
         m.write_addr = false;
-        m.write_value = 0x5;
+        m.write_value = true;
         m.write_enable = true;
         m.read_addr = false;
         m.read_enable = false;
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
 
         // Write to addr 1
         m.write_addr = true;
-        m.write_value = 0xa;
+        m.write_value = true;
         m.write_enable = true;
         m.read_addr = false;
         m.read_enable = false;
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
 
         // Read from addr 0
         m.write_addr = false;
-        m.write_value = 0;
+        m.write_value = false;
         m.write_enable = false;
         m.read_addr = false;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0);
+        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0x5);
+        assert_eq!(m.read_data, true);
 
         // Read from addr 1
         m.write_addr = false;
-        m.write_value = 0;
+        m.write_value = false;
         m.write_enable = false;
         m.read_addr = true;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0x5);
+        assert_eq!(m.read_data, true);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0xa);
+        assert_eq!(m.read_data, true);
 
         // Write to/read from addr 0
         m.write_addr = false;
-        m.write_value = 0xc;
+        m.write_value = false;
         m.write_enable = true;
         m.read_addr = false;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0xa);
+        assert_eq!(m.read_data, true);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0x5);
+        assert_eq!(m.read_data, true);
 
         // Write to/read from addr 1
         m.write_addr = true;
-        m.write_value = 0x3;
+        m.write_value = true;
         m.write_enable = true;
         m.read_addr = true;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0x5);
+        assert_eq!(m.read_data, true);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0xa);
+        assert_eq!(m.read_data, true);
 
         // Read from addr 0
         m.write_addr = false;
-        m.write_value = 0;
+        m.write_value = false;
         m.write_enable = false;
         m.read_addr = false;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0xa);
+        assert_eq!(m.read_data, true);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0xc);
+        assert_eq!(m.read_data, false);
 
         // Read from addr 1
         m.write_addr = false;
-        m.write_value = 0;
+        m.write_value = false;
         m.write_enable = false;
         m.read_addr = true;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0xc);
+        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0x3);
+        assert_eq!(m.read_data, true);
 
         // Write to addr 0/read from addr 1
         m.write_addr = false;
-        m.write_value = 0x9;
+        m.write_value = true;
         m.write_enable = true;
         m.read_addr = true;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0x3);
+        assert_eq!(m.read_data, true);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0x3);
+        assert_eq!(m.read_data, true);
 
         // Write to addr 1/read from addr 0
         m.write_addr = true;
-        m.write_value = 0x5;
+        m.write_value = false;
         m.write_enable = true;
         m.read_addr = false;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0x3);
+        assert_eq!(m.read_data, true);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0x9);
+        assert_eq!(m.read_data, true);
 
         // Read from addr 1
         m.write_addr = false;
-        m.write_value = 0;
+        m.write_value = false;
         m.write_enable = false;
         m.read_addr = true;
         m.read_enable = true;
         m.prop();
-        assert_eq!(m.read_data, 0x9);
+        assert_eq!(m.read_data, true);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, false);
+    }
+
+    #[test]
+    fn mem_test_module_3() {
+        let mut m = MemTestModule3::new();
+
+        // Read from addr 1
+        m.write_addr = 0;
+        m.write_value = 0;
+        m.write_enable = false;
+        m.read_addr = 1;
+        m.read_enable = true;
+        m.prop();
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x34);
+
+        // Disable reads and change address; read data holds
+        m.read_addr = 2;
+        m.read_enable = false;
+        m.prop();
+        assert_eq!(m.read_data, 0x34);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x34);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x34);
+
+        // Overwrite the previously-read location while reads are disabled; read data still holds (no re-read)
+        m.write_addr = 1;
+        m.write_value = 0xab;
+        m.write_enable = true;
+        m.read_addr = 1;
+        m.read_enable = false;
+        m.prop();
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x34);
+        m.write_enable = false;
+        m.prop();
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x34);
+
+        // Re-enable reads mid-stream; new value is visible on the following cycle
+        m.read_enable = true;
+        m.prop();
+        assert_eq!(m.read_data, 0x34);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0xab);
+
+        // Read from addr 3, then disable again
+        m.read_addr = 3;
+        m.prop();
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x78);
+        m.read_addr = 0;
+        m.read_enable = false;
+        m.prop();
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x78);
+
+        // Toggle enable on alternating cycles while sweeping addresses
+        let expected = [0x12, 0x12, 0x56, 0x56];
+        for (i, &expected) in expected.iter().enumerate() {
+            m.read_addr = i as _;
+            m.read_enable = i % 2 == 0;
+            m.prop();
+            m.posedge_clk();
+            m.prop();
+            assert_eq!(m.read_data, expected);
+        }
+    }
+
+    #[test]
+    fn mem_test_module_4() {
+        let mut m = MemTestModule4::new();
+
+        // Read back initial contents without any clock edges
+        m.write_enable = false;
+        for (i, &expected) in [0xfadebabe, 0xdeadbeef, 0xabadcafe, 0xabad1dea]
+            .iter()
+            .enumerate()
+        {
+            m.read_addr_0 = i as _;
+            m.read_addr_1 = 3 - i as u32;
+            m.prop();
+            assert_eq!(m.read_data_0, expected);
+        }
+        m.read_addr_0 = 1;
+        m.read_addr_1 = 2;
+        m.prop();
+        assert_eq!(m.read_data_0, 0xdeadbeef);
+        assert_eq!(m.read_data_1, 0xabadcafe);
+
+        // Write to addr 1; not visible until the following cycle
+        m.write_addr = 1;
+        m.write_value = 0x12345678;
+        m.write_enable = true;
+        m.prop();
+        assert_eq!(m.read_data_0, 0xdeadbeef);
+        m.posedge_clk();
+        m.write_enable = false;
+        m.prop();
+        assert_eq!(m.read_data_0, 0x12345678);
+        assert_eq!(m.read_data_1, 0xabadcafe);
+
+        // Changing the address is visible immediately
+        m.read_addr_1 = 1;
+        m.prop();
+        assert_eq!(m.read_data_1, 0x12345678);
+    }
+
+    #[test]
+    fn mem_test_module_5() {
+        let mut m = MemTestModule5::new();
+
+        // Writes to different addresses on the same cycle both land
+        m.write_addr_0 = 0;
+        m.write_value_0 = 0x12;
+        m.write_enable_0 = true;
+        m.write_addr_1 = 1;
+        m.write_value_1 = 0x34;
+        m.write_enable_1 = true;
+        m.prop();
+        m.posedge_clk();
+        m.write_enable_0 = false;
+        m.write_enable_1 = false;
+        m.read_addr = 0;
+        m.prop();
+        assert_eq!(m.read_data, 0x12);
+        m.read_addr = 1;
+        m.prop();
+        assert_eq!(m.read_data, 0x34);
+
+        // Colliding writes; the later-specified port takes priority
+        m.write_addr_0 = 2;
+        m.write_value_0 = 0x56;
+        m.write_enable_0 = true;
+        m.write_addr_1 = 2;
+        m.write_value_1 = 0x78;
+        m.write_enable_1 = true;
+        m.prop();
+        m.posedge_clk();
+        m.read_addr = 2;
+        m.prop();
+        assert_eq!(m.read_data, 0x78);
+
+        // Earlier port still writes when the later one is disabled
+        m.write_value_0 = 0x9a;
+        m.write_enable_1 = false;
+        m.prop();
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0x5);
+        assert_eq!(m.read_data, 0x9a);
     }
 
     #[test]
-    fn mem_test_module_1() {
-        let mut m = MemTestModule1::new();
+    fn mem_test_module_6() {
+        let mut m = MemTestModule6::new();
 
-        // No read
-        m.read_addr = 0;
-        m.read_enable = false;
+        // Contents come from data/boot_rom.hex
+        m.read_enable = true;
+        for (i, &expected) in [0xdeadbeef, 0x0badf00d, 0x12345678, 0xcafebabe]
+            .iter()
+            .enumerate()
+        {
+            m.read_addr = i as _;
+            m.prop();
+            m.posedge_clk();
+            m.prop();
+            assert_eq!(m.read_data, expected);
+        }
+    }
+
+    #[test]
+    fn mem_test_module_7() {
+        let mut m = MemTestModule7::new();
+
+        // Fill addr 0 with a known pattern
+        m.write_addr = false;
+        m.write_value = 0x11223344;
+        m.write_enable = true;
+        m.write_mask = 0b1111;
         m.prop();
-        assert_eq!(m.read_data, 0);
         m.posedge_clk();
-        m.prop();
-        assert_eq!(m.read_data, 0);
 
-        // Read from addr 0
-        m.read_addr = 0;
-        m.read_enable = true;
+        // Write alternating bytes; untouched bytes must retain their previous contents
+        m.write_value = 0xaabbccdd;
+        m.write_mask = 0b0101;
         m.prop();
-        assert_eq!(m.read_data, 0);
         m.posedge_clk();
+        m.write_enable = false;
+        m.read_addr = false;
         m.prop();
-        assert_eq!(m.read_data, 0xfadebabe);
+        assert_eq!(m.read_data, 0x11bb33dd);
 
-        // Read from addr 1
-        m.read_addr = 1;
-        m.read_enable = true;
+        m.write_value = 0xeeff0011;
+        m.write_enable = true;
+        m.write_mask = 0b1010;
         m.prop();
-        assert_eq!(m.read_data, 0xfadebabe);
         m.posedge_clk();
+        m.write_enable = false;
         m.prop();
-        assert_eq!(m.read_data, 0xdeadbeef);
+        assert_eq!(m.read_data, 0xeebb00dd);
 
-        // Read from addr 2
-        m.read_addr = 2;
-        m.read_enable = true;
+        // No bytes are written when the mask is empty, even if enable is asserted
+        m.write_value = 0xffffffff;
+        m.write_enable = true;
+        m.write_mask = 0b0000;
         m.prop();
-        assert_eq!(m.read_data, 0xdeadbeef);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, 0xabadcafe);
+        assert_eq!(m.read_data, 0xeebb00dd);
 
-        // Read from addr 3
-        m.read_addr = 3;
-        m.read_enable = true;
-        m.prop();
-        assert_eq!(m.read_data, 0xabadcafe);
-        m.posedge_clk();
+        // Other elements aren't affected
+        m.read_addr = true;
         m.prop();
-        assert_eq!(m.read_data, 0xabad1dea);
+        assert_eq!(m.read_data, 0x00000000);
+    }
 
-        // No read
-        m.read_addr = 0;
-        m.read_enable = false;
+    #[test]
+    fn assertion_test_module_passing() {
+        let mut m = AssertionTestModule::new();
+        m.reset();
+
+        m.limit = 0xf;
+        for cycle in 0..0xf {
+            m.prop();
+            m.check(cycle);
+            assert_eq!(m.counter, cycle as u32);
+            m.posedge_clk();
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Assertion in module \"AssertionTestModule\" failed on cycle 4: counter must not exceed limit"
+    )]
+    fn assertion_test_module_failing() {
+        let mut m = AssertionTestModule::new();
+        m.reset();
+
+        m.limit = 3;
+        for cycle in 0..0xf {
+            m.prop();
+            m.check(cycle);
+            m.posedge_clk();
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Assertion in module \"AssertionTestModuleInner\" failed on cycle 15: i must not be 0xf"
+    )]
+    fn assertion_test_module_failing_in_instance() {
+        let mut m = AssertionTestModule::new();
+        m.reset();
+
+        m.limit = 0xf;
+        for cycle in 0..0x10 {
+            m.prop();
+            m.check(cycle);
+            m.posedge_clk();
+        }
+    }
+
+    #[test]
+    fn cover_test_module() {
+        let mut m = CoverTestModule::new();
+
+        // This module's covers first, followed by the instance's
+        assert_eq!(m.cover_counts(), &[0, 0, 0]);
+
+        for (cycle, &i) in [0, 1, 1, 3, 0, 0, 1].iter().enumerate() {
+            m.i = i;
+            m.prop();
+            m.check(cycle as _);
+            m.posedge_clk();
+        }
+        assert_eq!(m.cover_counts(), &[3, 3, 1]);
+
+        // Covers are only counted on clock edges
+        m.i = 3;
         m.prop();
-        assert_eq!(m.read_data, 0xabad1dea);
+        assert_eq!(m.cover_counts(), &[3, 3, 1]);
         m.posedge_clk();
+        assert_eq!(m.cover_counts(), &[3, 3, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Assumption in module \"CoverTestModule\" was violated on cycle 5")]
+    fn cover_test_module_assumption_violated() {
+        let mut m = CoverTestModule::new();
+
+        m.i = 2;
         m.prop();
-        assert_eq!(m.read_data, 0xabad1dea);
+        m.check(5);
     }
 
     #[test]
-    fn mem_test_module_2() {
-        let mut m = MemTestModule2::new();
+    fn trace_test_module() {
+        let mut m = TraceTestModule::new();
+        m.reset();
 
-        // Initial state, no read/write
-        m.write_addr = false;
-        m.write_value = false;
-        m.write_enable = false;
-        m.read_addr = false;
-        m.read_enable = false;
+        let mut trace = Vec::new();
+        for _ in 0..3 {
+            m.prop();
+            m.trace(&mut |label, value| trace.push((label.to_string(), value)));
+            m.posedge_clk();
+        }
+
+        let expected = [
+            ("counter", 0),
+            ("counter_is_odd", 0),
+            ("inner.doubled", 0),
+            ("counter", 1),
+            ("counter_is_odd", 1),
+            ("inner.doubled", 2),
+            ("counter", 2),
+            ("counter_is_odd", 0),
+            ("inner.doubled", 4),
+        ];
+        assert_eq!(trace.len(), expected.len());
+        for ((label, value), &(expected_label, expected_value)) in trace.iter().zip(expected.iter())
+        {
+            assert_eq!(label, expected_label);
+            assert_eq!(*value, expected_value);
+        }
+    }
+
+    #[test]
+    fn probe_test_module() {
+        let mut m = ProbeTestModule::new();
+        m.reset();
+
+        assert_eq!(m.pc(), 0xf);
+        assert_eq!(m.fetch1__pc(), 0);
+        assert_eq!(m.fetch2__pc(), 0);
+
+        m.stall = false;
+        for _ in 0..3 {
+            m.prop();
+            m.posedge_clk();
+        }
+        m.stall = true;
+        for _ in 0..2 {
+            m.prop();
+            m.posedge_clk();
+        }
+        m.prop();
+
+        assert_eq!(m.pc(), 0xf);
+        assert_eq!(m.fetch1__pc(), 20);
+        assert_eq!(m.fetch2__pc(), 12);
+        assert_eq!(m.fetch2__decode__next_pc(), 16);
+        assert_eq!(m.pcs_are_zero, false);
+    }
+
+    #[test]
+    fn constant_test_module() {
+        assert_eq!(ConstantTestModule::MAGIC, 0xdeadbeef);
+        assert_eq!(ConstantTestModule::FLAG, true);
+        assert_eq!(ConstantTestModule::WIDE, 0xfadebabedeadbeef);
+
+        let mut m = ConstantTestModule::new();
+
+        m.i = ConstantTestModule::MAGIC;
         m.prop();
-        assert_eq!(m.read_data, false);
-        m.posedge_clk();
+        assert_eq!(m.is_magic, true);
+        assert_eq!(m.xor_magic, 0);
+
+        m.i = 0xffffffff;
         m.prop();
-        assert_eq!(m.read_data, false);
+        assert_eq!(m.is_magic, false);
+        assert_eq!(m.xor_magic, 0x21524110);
+        assert_eq!(m.flag, true);
+        assert_eq!(m.wide, 0xfadebabedeadbeef);
+    }
 
-        // Initial state, read from addr 0
-        m.write_addr = false;
-        m.write_value = false;
-        m.write_enable = false;
-        m.read_addr = false;
-        m.read_enable = true;
+    #[test]
+    fn instance_array_test_module() {
+        let mut m = InstanceArrayTestModule::new();
+        let mut unrolled = UnrolledInstanceArrayTestModule::new();
+
+        m.reset();
+        unrolled.reset();
+
+        for &(i, en) in &[
+            (0x01020304, true),
+            (0xffffffff, false),
+            (0x80ff7f01, true),
+            (0x80ff7f01, true),
+            (0x00000000, true),
+            (0xfedcba98, true),
+        ] {
+            m.i = i;
+            m.en = en;
+            unrolled.i = i;
+            unrolled.en = en;
+            m.prop();
+            unrolled.prop();
+            assert_eq!(m.acc, unrolled.acc);
+            assert_eq!(m.carry, unrolled.carry);
+
+            m.posedge_clk();
+            unrolled.posedge_clk();
+        }
+
+        m.prop();
+        assert_eq!(m.acc, 0xffdcbb9e);
+    }
+
+    #[test]
+    fn true_dual_port_test_module() {
+        let mut m = TrueDualPortTestModule::new();
+
+        // Each port writes its own address
+        m.a_addr = 1;
+        m.a_write_data = 0x11;
+        m.a_write_enable = true;
+        m.b_addr = 2;
+        m.b_write_data = 0x22;
+        m.b_write_enable = true;
         m.prop();
-        assert_eq!(m.read_data, false);
         m.posedge_clk();
-        m.prop();
-        assert_eq!(m.read_data, false);
 
-        // Initial state, read from addr 1
-        m.write_addr = false;
-        m.write_value = false;
-        m.write_enable = false;
-        m.read_addr = true;
-        m.read_enable = true;
+        // Both ports read back both addresses
+        m.a_write_enable = false;
+        m.b_write_enable = false;
+        m.a_read_enable = true;
+        m.b_read_enable = true;
+        m.a_addr = 2;
+        m.b_addr = 1;
         m.prop();
-        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, false);
+        assert_eq!(m.a_read_data, 0x22);
+        assert_eq!(m.b_read_data, 0x11);
 
-        // Initial state, write to addr 0
-        m.write_addr = false;
-        m.write_value = true;
-        m.write_enable = true;
-        m.read_addr = false;
-        m.read_enable = false;
+        // Colliding writes; port b was specified last, so it takes priority.
+        // Reads return the old value while the write is in progress.
+        m.a_addr = 3;
+        m.a_write_data = 0xaa;
+        m.a_write_enable = true;
+        m.b_addr = 3;
+        m.b_write_data = 0xbb;
+        m.b_write_enable = true;
         m.prop();
-        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, false);
+        assert_eq!(m.a_read_data, 0x00);
+        assert_eq!(m.b_read_data, 0x00);
 
-        // Write to addr 1
-        m.write_addr = true;
-        m.write_value = true;
-        m.write_enable = true;
-        m.read_addr = false;
-        m.read_enable = false;
+        m.a_write_enable = false;
+        m.b_write_enable = false;
         m.prop();
-        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, false);
+        assert_eq!(m.a_read_data, 0xbb);
+        assert_eq!(m.b_read_data, 0xbb);
 
-        // Read from addr 0
-        m.write_addr = false;
-        m.write_value = false;
-        m.write_enable = false;
-        m.read_addr = false;
-        m.read_enable = true;
+        // Disabled read ports hold their values
+        m.a_read_enable = false;
+        m.a_addr = 1;
         m.prop();
-        assert_eq!(m.read_data, false);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, true);
+        assert_eq!(m.a_read_data, 0xbb);
+    }
 
-        // Read from addr 1
-        m.write_addr = false;
-        m.write_value = false;
-        m.write_enable = false;
-        m.read_addr = true;
+    #[test]
+    fn read_during_write_old_test_module() {
+        let mut m = ReadDuringWriteOldTestModule::new();
+
+        // Colliding full-width write returns the old value
+        m.read_addr = 1;
         m.read_enable = true;
+        m.write_addr_0 = 1;
+        m.write_value_0 = 0xabcd;
+        m.write_enable_0 = true;
         m.prop();
-        assert_eq!(m.read_data, true);
         m.posedge_clk();
+        m.write_enable_0 = false;
         m.prop();
-        assert_eq!(m.read_data, true);
-
-        // Write to/read from addr 0
-        m.write_addr = false;
-        m.write_value = false;
-        m.write_enable = true;
-        m.read_addr = false;
-        m.read_enable = true;
-        m.prop();
-        assert_eq!(m.read_data, true);
+        assert_eq!(m.read_data, 0x1111);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, true);
+        assert_eq!(m.read_data, 0xabcd);
 
-        // Write to/read from addr 1
-        m.write_addr = true;
-        m.write_value = true;
-        m.write_enable = true;
-        m.read_addr = true;
-        m.read_enable = true;
+        // Colliding masked write returns the old value
+        m.read_addr = 2;
+        m.write_addr_1 = 2;
+        m.write_value_1 = 0xabcd;
+        m.write_enable_1 = true;
+        m.write_mask_1 = 0b01;
         m.prop();
-        assert_eq!(m.read_data, true);
         m.posedge_clk();
+        m.write_enable_1 = false;
         m.prop();
-        assert_eq!(m.read_data, true);
+        assert_eq!(m.read_data, 0x2222);
+        m.posedge_clk();
+        m.prop();
+        assert_eq!(m.read_data, 0x22cd);
+    }
 
-        // Read from addr 0
-        m.write_addr = false;
-        m.write_value = false;
-        m.write_enable = false;
-        m.read_addr = false;
+    #[test]
+    fn read_during_write_new_test_module() {
+        let mut m = ReadDuringWriteNewTestModule::new();
+
+        // Colliding full-width write returns the new value
+        m.read_addr = 1;
         m.read_enable = true;
+        m.write_addr_0 = 1;
+        m.write_value_0 = 0xabcd;
+        m.write_enable_0 = true;
         m.prop();
-        assert_eq!(m.read_data, true);
         m.posedge_clk();
+        m.write_enable_0 = false;
         m.prop();
-        assert_eq!(m.read_data, false);
+        assert_eq!(m.read_data, 0xabcd);
 
-        // Read from addr 1
-        m.write_addr = false;
-        m.write_value = false;
-        m.write_enable = false;
-        m.read_addr = true;
-        m.read_enable = true;
+        // Colliding masked write returns the merged value
+        m.read_addr = 2;
+        m.write_addr_1 = 2;
+        m.write_value_1 = 0xabcd;
+        m.write_enable_1 = true;
+        m.write_mask_1 = 0b10;
         m.prop();
-        assert_eq!(m.read_data, false);
         m.posedge_clk();
+        m.write_enable_1 = false;
         m.prop();
-        assert_eq!(m.read_data, true);
+        assert_eq!(m.read_data, 0xab22);
 
-        // Write to addr 0/read from addr 1
-        m.write_addr = false;
-        m.write_value = true;
-        m.write_enable = true;
-        m.read_addr = true;
-        m.read_enable = true;
+        // Both ports colliding; the later-specified port takes priority
+        m.read_addr = 3;
+        m.write_addr_0 = 3;
+        m.write_value_0 = 0x5555;
+        m.write_enable_0 = true;
+        m.write_addr_1 = 3;
+        m.write_value_1 = 0x6666;
+        m.write_enable_1 = true;
+        m.write_mask_1 = 0b01;
         m.prop();
-        assert_eq!(m.read_data, true);
         m.posedge_clk();
+        m.write_enable_0 = false;
+        m.write_enable_1 = false;
         m.prop();
-        assert_eq!(m.read_data, true);
+        assert_eq!(m.read_data, 0x5566);
 
-        // Write to addr 1/read from addr 0
-        m.write_addr = true;
-        m.write_value = false;
-        m.write_enable = true;
-        m.read_addr = false;
-        m.read_enable = true;
+        // Non-colliding reads are unaffected
+        m.read_addr = 0;
+        m.write_addr_0 = 1;
+        m.write_value_0 = 0xffff;
+        m.write_enable_0 = true;
         m.prop();
-        assert_eq!(m.read_data, true);
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, true);
+        assert_eq!(m.read_data, 0x0000);
+    }
 
-        // Read from addr 1
-        m.write_addr = false;
-        m.write_value = false;
-        m.write_enable = false;
-        m.read_addr = true;
-        m.read_enable = true;
+    #[test]
+    fn structural_sharing_test_module() {
+        let mut m = StructuralSharingTestModule::new();
+
+        for &(a, b) in &[
+            (0u32, 0u32),
+            (0xffffffff, 0),
+            (0x12345678, 0x9abcdef0),
+            (0xdeadbeef, 0xfadebabe),
+        ] {
+            m.a = a;
+            m.b = b;
+            m.prop();
+            assert_eq!(m.o, ((a & b) ^ (a | b)) << 16);
+        }
+    }
+
+    #[test]
+    fn fan_out_test_module() {
+        let mut m = FanOutTestModule::new();
+
+        for &(x, y) in &[
+            (0u32, 0u32),
+            (0xffffffff, 0),
+            (0x12345678, 0x9abcdef0),
+            (0xdeadbeef, 0xfadebabe),
+        ] {
+            m.x = x;
+            m.y = y;
+            m.prop();
+
+            let mut expected = x;
+            for _ in 0..64 {
+                expected = expected.wrapping_add(y)
+                    ^ (expected >> 1)
+                    ^ if expected < y { y } else { expected };
+            }
+            assert_eq!(m.o0, expected);
+            assert_eq!(m.o1, expected ^ 1);
+            assert_eq!(m.o2, expected ^ 2);
+            assert_eq!(m.o3, expected ^ 3);
+        }
+    }
+
+    macro_rules! storage_type_test {
+        ($name:ident, $module:ident, $bit_width:expr) => {
+            #[test]
+            fn $name() {
+                let bit_width: u32 = $bit_width;
+                let mask = (1u128 << bit_width) - 1;
+                let sign_extend = |value: u128| {
+                    if (value >> (bit_width - 1)) & 1 != 0 {
+                        (value | !mask) as i128
+                    } else {
+                        value as i128
+                    }
+                };
+                let mut m = $module::new();
+
+                for &(a, b, shift) in &[
+                    (0u128, 0u128, 0u32),
+                    (mask, 1, 1),
+                    (1 << (bit_width - 1), mask, bit_width - 1),
+                    (
+                        0x123456789abcdef0123456789abcdef0 & mask,
+                        0xfedcba9876543210fedcba9876543210 & mask,
+                        3,
+                    ),
+                    (mask >> 1, 1 << (bit_width - 1), bit_width),
+                    (mask, mask, 200),
+                ] {
+                    m.a = a as _;
+                    m.b = b as _;
+                    m.shift = shift;
+                    m.prop();
+
+                    assert_eq!(m.add as u128, a.wrapping_add(b) & mask);
+                    assert_eq!(m.sub as u128, a.wrapping_sub(b) & mask);
+                    assert_eq!(m.not as u128, !a & mask);
+                    assert_eq!(
+                        m.shl as u128,
+                        if shift < bit_width {
+                            (a << shift) & mask
+                        } else {
+                            0
+                        }
+                    );
+                    assert_eq!(
+                        m.shr as u128,
+                        if shift < bit_width { a >> shift } else { 0 }
+                    );
+                    assert_eq!(
+                        m.shr_arithmetic as u128,
+                        (sign_extend(a) >> shift.min(127)) as u128 & mask
+                    );
+                    assert_eq!(m.lt_signed, sign_extend(a) < sign_extend(b));
+                    assert_eq!(m.rotate as u128, ((a << 1) | (a >> (bit_width - 1))) & mask);
+                    assert_eq!(m.widen as u128, (a << 1) | (a & 1));
+                    assert_eq!(m.narrow as u128, a & (mask >> 1));
+                }
+            }
+        };
+    }
+
+    storage_type_test!(storage_type_test_module_8, StorageTypeTestModule8, 8);
+    storage_type_test!(storage_type_test_module_9, StorageTypeTestModule9, 9);
+    storage_type_test!(storage_type_test_module_16, StorageTypeTestModule16, 16);
+    storage_type_test!(storage_type_test_module_17, StorageTypeTestModule17, 17);
+    storage_type_test!(storage_type_test_module_32, StorageTypeTestModule32, 32);
+    storage_type_test!(storage_type_test_module_33, StorageTypeTestModule33, 33);
+    storage_type_test!(storage_type_test_module_64, StorageTypeTestModule64, 64);
+    storage_type_test!(storage_type_test_module_65, StorageTypeTestModule65, 65);
+
+    #[test]
+    fn reserved_identifier_test_module() {
+        let mut m = Mod_1::new();
+
+        m.reset();
+        m.r#type = 0x10;
+        m.r#match = true;
+        m.self_ = 0b0101;
+        m._1st = 0b0011;
         m.prop();
-        assert_eq!(m.read_data, true);
+        assert_eq!(m.r#async, 0x10);
+        assert_eq!(m.r#fn, 0x78);
+        assert_eq!(m.Self_, 0);
+
         m.posedge_clk();
         m.prop();
-        assert_eq!(m.read_data, false);
+        assert_eq!(m.r#loop(), 0x10);
+        assert_eq!(m.inner__let(), 0b0110);
+        assert_eq!(m.r#async, 0x20);
+        assert_eq!(m.Self_, 0b0110);
+        assert_eq!(m.box_rom_read(1), 0x34);
+
+        let state = m.save_state();
+        assert_eq!(state.r#loop, 0x10);
+        assert_eq!(state.inner__let, 0b0110);
+
+        // Ports keep their original names through `SimModule`
+        SimModule::set_input(&mut m, "type", 0x01);
+        SimModule::prop(&mut m);
+        assert_eq!(SimModule::get_output(&m, "async"), 0x11);
+    }
+
+    #[test]
+    fn debug_test_module() {
+        let mut m = DebugTestModule::new();
+
+        m.reset();
+        m.a = 0x3;
+        m.en = true;
+        m.prop();
+        assert_eq!(
+            format!("{:?}", m),
+            "DebugTestModule
+  inputs:
+    a: 4'h3
+    en: 1'h1
+  outputs:
+    o: 8'h99
+    p: 1'h0
+  registers:
+    r: 4'ha
+  mems:
+    rom: [12'h000, 12'h011, 12'h044, 12'h099, 12'h110, 12'h1a9, 12'h264, 12'h341, ...] (15/16 non-zero)
+  inner (DebugTestModuleInner):
+    registers:
+      counter: 3'h0
+    mems:
+      flags: [1'h1, 1'h0] (1/2 non-zero)"
+        );
+    }
+
+    // These operands overflow the underlying storage types, which would panic in debug builds (such as these tests) if the generated code didn't use wrapping arithmetic
+    macro_rules! overflow_test {
+        ($name:ident, $module:ident, $t:ty) => {
+            #[test]
+            fn $name() {
+                let mut m = $module::new();
+
+                m.a = <$t>::max_value();
+                m.b = 2;
+                m.shift = 255;
+                m.prop();
+                assert_eq!(m.add, 1);
+                assert_eq!(m.sub, <$t>::max_value() - 2);
+                assert_eq!(m.shl, 0);
+                assert_eq!(m.shr, 0);
+                assert_eq!(m.shr_arithmetic, <$t>::max_value());
+
+                m.a = 1;
+                m.b = <$t>::max_value();
+                m.prop();
+                assert_eq!(m.add, 0);
+                assert_eq!(m.sub, 2);
+                assert_eq!(m.shl, 0);
+                assert_eq!(m.shr, 0);
+                assert_eq!(m.shr_arithmetic, 0);
+            }
+        };
+    }
+
+    overflow_test!(overflow_test_module_32, OverflowTestModule32, u32);
+    overflow_test!(overflow_test_module_64, OverflowTestModule64, u64);
+    overflow_test!(overflow_test_module_128, OverflowTestModule128, u128);
+
+    #[test]
+    fn constant_folding_test_module_8() {
+        let mut m = ConstantFoldingTestModule8::new();
+
+        for &(a, b) in &[
+            (0x80, 0x01),
+            (0x7f, 0xff),
+            (0x12, 0x07),
+            (0xff, 0x09),
+            (0, 0),
+        ] {
+            m.a = a;
+            m.b = b;
+            m.prop();
+            assert!(m.matched);
+            assert_eq!(m.mismatches, 0, "a = {:#x}, b = {:#x}", a, b);
+        }
+    }
+
+    #[test]
+    fn constant_folding_test_module_64() {
+        let mut m = ConstantFoldingTestModule64::new();
+
+        for &(a, b) in &[
+            (1 << 63, 0x01),
+            (0x7fffffffffffffff, 0xffffffffffffffff),
+            (0x123456789abcdef0, 0x3f),
+            (0xffffffffffffffff, 0x41),
+            (0, 0),
+        ] {
+            m.a = a;
+            m.b = b;
+            m.prop();
+            assert!(m.matched);
+            assert_eq!(m.mismatches, 0, "a = {:#x}, b = {:#x}", a, b);
+        }
     }
 }
@@ -0,0 +1,939 @@
+//! Design analysis passes.
+
+use crate::graph;
+use crate::module_context::*;
+use crate::validation::*;
+
+use typed_arena::Arena;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::ops::AddAssign;
+
+/// Returns the `Signal`s in `m` that can't affect any of `m`'s outputs, instances, or annotations, in creation order.
+///
+/// A `Signal` is live if any of the following transitively depend on it (see [`Signal::dependencies`]):
+///
+/// - `m`'s outputs
+/// - Inputs driven on `m`'s [`Instance`]s
+/// - Conditions of assertions, assumptions, and cover points, and traced `Signal`s (see [`Module::assert`], [`Module::assume`], [`Module::cover`], and [`Module::trace`])
+///
+/// All other `Signal`s in `m` are dead. This includes [`Register`] values that are never read (even if they feed their own next values), [`Mem`] read ports whose data is never used, and any logic that only feeds these, as well as leftover expressions that aren't connected to anything.
+/// Note that `m`'s inputs are reported if they're unused, even though they always remain part of `m`'s interface.
+/// [Named `Signal`s](../struct.Signal.html#method.name) aren't considered live just because they're named.
+///
+/// Dead logic never appears in generated simulators, and can be omitted from generated Verilog with [`verilog::GenerationOptions::eliminate_dead_logic`].
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+///
+/// let a = m.input("a", 8);
+/// let b = m.input("b", 8);
+/// let debug_tap = a ^ b; // Never used
+/// m.output("o", a + b);
+///
+/// let dead = analysis::dead_signals(m);
+/// assert_eq!(dead.len(), 1);
+/// assert!(dead[0] == debug_tap);
+/// ```
+///
+/// [`Instance`]: ../struct.Instance.html
+/// [`Mem`]: ../struct.Mem.html
+/// [`Module::assert`]: ../struct.Module.html#method.assert
+/// [`Module::assume`]: ../struct.Module.html#method.assume
+/// [`Module::cover`]: ../struct.Module.html#method.cover
+/// [`Module::trace`]: ../struct.Module.html#method.trace
+/// [`Register`]: ../struct.Register.html
+/// [`Signal::dependencies`]: ../struct.Signal.html#method.dependencies
+/// [`verilog::GenerationOptions::eliminate_dead_logic`]: ../verilog/struct.GenerationOptions.html#structfield.eliminate_dead_logic
+pub fn dead_signals<'a>(m: &'a graph::Module<'a>) -> Vec<&'a graph::Signal<'a>> {
+    let live_signals = live_signals(m);
+    m.signals
+        .borrow()
+        .iter()
+        .filter(|signal| !live_signals.contains(*signal))
+        .copied()
+        .collect()
+}
+
+pub(crate) fn live_signals<'a>(m: &'a graph::Module<'a>) -> HashSet<&'a graph::Signal<'a>> {
+    let mut ret = HashSet::new();
+    let mut mark_live =
+        |signal: &'a graph::Signal<'a>| signal.visit_unvisited(&mut ret, &mut |_| ());
+
+    for output in m.outputs.borrow().values() {
+        mark_live(output);
+    }
+    for instance in m.instances.borrow().iter() {
+        for driven_input in instance.driven_inputs.borrow().values() {
+            mark_live(driven_input);
+        }
+    }
+    for (condition, _) in m.assertions.borrow().iter() {
+        mark_live(condition);
+    }
+    for condition in m.assumptions.borrow().iter() {
+        mark_live(condition);
+    }
+    for condition in m.covers.borrow().iter() {
+        mark_live(condition);
+    }
+    for (_, signal) in m.traces.borrow().iter() {
+        mark_live(signal);
+    }
+
+    ret
+}
+
+// Returns each `Module` in `m`'s hierarchy once, with instantiated `Module`s before the `Module`s that instantiate them, and `m` last
+pub(crate) fn hierarchy_modules<'a>(m: &'a graph::Module<'a>) -> Vec<&'a graph::Module<'a>> {
+    fn visit<'a>(
+        m: &'a graph::Module<'a>,
+        visited: &mut HashSet<*const graph::Module<'a>>,
+        modules: &mut Vec<&'a graph::Module<'a>>,
+    ) {
+        if !visited.insert(m) {
+            return;
+        }
+        for instance in m.instances.borrow().iter() {
+            visit(instance.instantiated_module, visited, modules);
+        }
+        modules.push(m);
+    }
+
+    let mut modules = Vec::new();
+    visit(m, &mut HashSet::new(), &mut modules);
+    modules
+}
+
+// Returns whether `m` or any `Module` in its hierarchy has any registers, memories, or annotations (which are all evaluated on clock edges), and therefore needs clock and reset ports
+pub(crate) fn uses_clock<'a>(m: &'a graph::Module<'a>) -> bool {
+    hierarchy_modules(m).iter().any(|m| {
+        !m.registers.borrow().is_empty()
+            || !m.mems.borrow().is_empty()
+            || !m.assertions.borrow().is_empty()
+            || !m.assumptions.borrow().is_empty()
+            || !m.covers.borrow().is_empty()
+            || !m.traces.borrow().is_empty()
+    })
+}
+
+// Returns the names of `m`'s clock and reset ports, as specified by `Module::clock_port_name` and `Module::reset_port_name`, or `default_clock` and `default_reset` otherwise
+pub(crate) fn clock_reset_port_names<'a>(
+    m: &'a graph::Module<'a>,
+    default_clock: &str,
+    default_reset: &str,
+) -> (String, String) {
+    let clock = m
+        .clock_port_name
+        .borrow()
+        .clone()
+        .unwrap_or_else(|| default_clock.to_string());
+    let reset = m
+        .reset_port_name
+        .borrow()
+        .clone()
+        .unwrap_or_else(|| default_reset.to_string());
+    if clock == reset {
+        panic!(
+            "Cannot generate code for module \"{}\" because its clock and reset ports would both be called \"{}\".",
+            m.name, clock
+        );
+    }
+    for name in [&clock, &reset].iter() {
+        if m.inputs.borrow().contains_key(*name) || m.outputs.borrow().contains_key(*name) {
+            panic!("Cannot generate code for module \"{}\" because it has a port called \"{}\", which is also the name of its clock or reset port.", m.name, name);
+        }
+    }
+    (clock, reset)
+}
+
+/// A kind of combinational node counted by [`DesignStats`].
+///
+/// [`DesignStats`]: ./struct.DesignStats.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum NodeKind {
+    /// Unary operators (`!`).
+    UnOp,
+    /// Binary operators (bitwise, arithmetic, comparison, and shift operators).
+    BinOp,
+    /// Bit/range selections ([`bit`](../struct.Signal.html#method.bit) and [`bits`](../struct.Signal.html#method.bits)).
+    Bits,
+    /// [`repeat`](../struct.Signal.html#method.repeat) nodes.
+    Repeat,
+    /// [`concat`](../struct.Signal.html#method.concat) nodes.
+    Concat,
+    /// 2:1 multiplexers.
+    Mux,
+    /// [`Mem`](../struct.Mem.html) read ports, both synchronous and combinational.
+    MemReadPort,
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            NodeKind::UnOp => "UnOp",
+            NodeKind::BinOp => "BinOp",
+            NodeKind::Bits => "Bits",
+            NodeKind::Repeat => "Repeat",
+            NodeKind::Concat => "Concat",
+            NodeKind::Mux => "Mux",
+            NodeKind::MemReadPort => "MemReadPort",
+        })
+    }
+}
+
+/// Size counts for part of a design, as reported by [`stats`].
+///
+/// [`stats`]: ./fn.stats.html
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DesignStats {
+    /// The number of [`Register`](../struct.Register.html)s.
+    pub registers: usize,
+    /// The total number of bits stored in [`Register`](../struct.Register.html)s.
+    pub register_bits: u64,
+    /// The number of [`Mem`](../struct.Mem.html)s.
+    pub mems: usize,
+    /// The total number of bits stored in [`Mem`](../struct.Mem.html)s.
+    pub mem_bits: u64,
+    /// The number of [`Instance`](../struct.Instance.html)s.
+    pub instances: usize,
+    /// The number of live combinational nodes of each kind. Kinds with no nodes are omitted.
+    pub nodes: BTreeMap<NodeKind, usize>,
+    /// The number of live combinational nodes of each bit width.
+    pub bit_widths: BTreeMap<u32, usize>,
+}
+
+impl DesignStats {
+    /// Returns the total number of live combinational nodes of all kinds.
+    pub fn total_nodes(&self) -> usize {
+        self.nodes.values().sum()
+    }
+}
+
+impl<'a> AddAssign<&'a DesignStats> for DesignStats {
+    fn add_assign(&mut self, other: &'a DesignStats) {
+        self.registers += other.registers;
+        self.register_bits += other.register_bits;
+        self.mems += other.mems;
+        self.mem_bits += other.mem_bits;
+        self.instances += other.instances;
+        for (&kind, &count) in other.nodes.iter() {
+            *self.nodes.entry(kind).or_insert(0) += count;
+        }
+        for (&bit_width, &count) in other.bit_widths.iter() {
+            *self.bit_widths.entry(bit_width).or_insert(0) += count;
+        }
+    }
+}
+
+/// Size statistics for a `Module` and its instance hierarchy, as reported by [`stats`].
+///
+/// The `Display` implementation formats these as a table with one row per instance, followed by a breakdown of the rolled-up combinational nodes.
+///
+/// [`stats`]: ./fn.stats.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleStats {
+    /// The name of the `Module`.
+    pub module_name: String,
+    /// Counts for the `Module` itself, excluding its instances.
+    pub local: DesignStats,
+    /// Counts for the `Module` including all of its instances, recursively.
+    pub total: DesignStats,
+    /// Statistics for each of the `Module`'s instances, paired with the instance names, in instantiation order.
+    pub instances: Vec<(String, ModuleStats)>,
+}
+
+impl ModuleStats {
+    fn write_rows(&self, f: &mut fmt::Formatter, label: &str, depth: usize) -> fmt::Result {
+        let label = format!("{}{}", "  ".repeat(depth), label);
+        writeln!(
+            f,
+            "{:<32} {:>9} {:>9} {:>6} {:>10} {:>9} {:>9}",
+            label,
+            self.local.registers,
+            self.local.register_bits,
+            self.local.mems,
+            self.local.mem_bits,
+            self.local.instances,
+            self.local.total_nodes()
+        )?;
+        for (name, instance) in self.instances.iter() {
+            instance.write_rows(
+                f,
+                &format!("{} ({})", name, instance.module_name),
+                depth + 1,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ModuleStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<32} {:>9} {:>9} {:>6} {:>10} {:>9} {:>9}",
+            "Module", "Regs", "Reg bits", "Mems", "Mem bits", "Instances", "Nodes"
+        )?;
+        self.write_rows(f, &self.module_name, 0)?;
+        writeln!(
+            f,
+            "{:<32} {:>9} {:>9} {:>6} {:>10} {:>9} {:>9}",
+            "Total",
+            self.total.registers,
+            self.total.register_bits,
+            self.total.mems,
+            self.total.mem_bits,
+            self.total.instances,
+            self.total.total_nodes()
+        )?;
+        writeln!(f)?;
+        writeln!(f, "Nodes by kind:")?;
+        for (kind, count) in self.total.nodes.iter() {
+            writeln!(f, "  {:<12} {:>9}", kind.to_string(), count)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Nodes by bit width:")?;
+        for (bit_width, count) in self.total.bit_widths.iter() {
+            writeln!(f, "  {:<12} {:>9}", bit_width, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes size statistics for `m` and its instance hierarchy.
+///
+/// [`Register`]s, [`Mem`]s, and [`Instance`]s are counted wherever they're declared. Combinational nodes are only counted if they're live (see [`dead_signals`]), and each node is counted once no matter how many times it's used. Inputs, literals, [`Register`] and [`Latch`] values, and [`Instance`] outputs aren't counted as combinational nodes.
+/// Each instance contributes its instantiated `Module`'s totals to the rolled-up counts.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+///
+/// let counter = m.reg("counter", 8);
+/// counter.default_value(0u32);
+/// counter.drive_next(counter.value + m.lit(1u32, 8));
+/// m.output("o", counter.value.bit(7));
+///
+/// let stats = analysis::stats(m);
+/// assert_eq!(stats.total.registers, 1);
+/// assert_eq!(stats.total.register_bits, 8);
+/// assert_eq!(stats.total.nodes[&analysis::NodeKind::BinOp], 1);
+/// assert_eq!(stats.total.nodes[&analysis::NodeKind::Bits], 1);
+/// println!("{}", stats);
+/// ```
+///
+/// [`dead_signals`]: ./fn.dead_signals.html
+/// [`Instance`]: ../struct.Instance.html
+/// [`Latch`]: ../struct.Latch.html
+/// [`Mem`]: ../struct.Mem.html
+/// [`Register`]: ../struct.Register.html
+pub fn stats<'a>(m: &'a graph::Module<'a>) -> ModuleStats {
+    module_stats(m, &mut HashMap::new())
+}
+
+fn module_stats<'a>(
+    m: &'a graph::Module<'a>,
+    cache: &mut HashMap<*const graph::Module<'a>, ModuleStats>,
+) -> ModuleStats {
+    if let Some(stats) = cache.get(&(m as *const _)) {
+        return stats.clone();
+    }
+
+    let mut local = DesignStats::default();
+    for reg in m.registers.borrow().iter() {
+        local.registers += 1;
+        local.register_bits += reg.bit_width() as u64;
+    }
+    for mem in m.mems.borrow().iter() {
+        local.mems += 1;
+        local.mem_bits += (mem.element_bit_width as u64) << mem.address_bit_width;
+    }
+    local.instances = m.instances.borrow().len();
+    let live_signals = live_signals(m);
+    // Iterate in creation order so that the results don't depend on hashing
+    for signal in m.signals.borrow().iter() {
+        if !live_signals.contains(signal) {
+            continue;
+        }
+        let kind = match signal.data {
+            graph::SignalData::Lit { .. }
+            | graph::SignalData::Input { .. }
+            | graph::SignalData::Reg { .. }
+            | graph::SignalData::Latch { .. }
+            | graph::SignalData::InstanceOutput { .. } => continue,
+            graph::SignalData::UnOp { .. } => NodeKind::UnOp,
+            graph::SignalData::SimpleBinOp { .. }
+            | graph::SignalData::AdditiveBinOp { .. }
+            | graph::SignalData::ComparisonBinOp { .. }
+            | graph::SignalData::ShiftBinOp { .. } => NodeKind::BinOp,
+            graph::SignalData::Bits { .. } => NodeKind::Bits,
+            graph::SignalData::Repeat { .. } => NodeKind::Repeat,
+            graph::SignalData::Concat { .. } => NodeKind::Concat,
+            graph::SignalData::Mux { .. } => NodeKind::Mux,
+            graph::SignalData::MemReadPortOutput { .. }
+            | graph::SignalData::MemCombReadPortOutput { .. } => NodeKind::MemReadPort,
+        };
+        *local.nodes.entry(kind).or_insert(0) += 1;
+        *local.bit_widths.entry(signal.bit_width()).or_insert(0) += 1;
+    }
+
+    let mut total = local.clone();
+    let mut instances = Vec::new();
+    for instance in m.instances.borrow().iter() {
+        let instance_stats = module_stats(instance.instantiated_module, cache);
+        total += &instance_stats.total;
+        instances.push((instance.name.clone(), instance_stats));
+    }
+
+    let ret = ModuleStats {
+        module_name: m.name.clone(),
+        local,
+        total,
+        instances,
+    };
+    cache.insert(m, ret.clone());
+    ret
+}
+
+/// The combinational logic depth of a single endpoint, as reported by [`logic_depth`].
+///
+/// [`logic_depth`]: ./fn.logic_depth.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EndpointDepth {
+    /// The endpoint's name: either an output of the analyzed `Module`, or a `.`-separated instance path followed by a [`Register`](../struct.Register.html)'s name, whose next value is the endpoint.
+    pub endpoint: String,
+    /// The maximum number of logic nodes on any path from a start point to this endpoint.
+    pub depth: u32,
+    /// The named points along a deepest path, starting with its start point and ending with [`endpoint`](#structfield.endpoint).
+    ///
+    /// Intermediate points include instance inputs and outputs crossed by the path and [named `Signal`s](../struct.Signal.html#method.name) on the path. If the path only starts at literals, the first point is the first named point after them.
+    pub path: Vec<String>,
+}
+
+/// Combinational logic depth estimates for a `Module` and its instance hierarchy, as reported by [`logic_depth`].
+///
+/// [`logic_depth`]: ./fn.logic_depth.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogicDepth {
+    /// The depth of each endpoint: the analyzed `Module`'s outputs in name order, followed by the [`Register`](../struct.Register.html)s in the hierarchy (those in the analyzed `Module` first, followed by those in each of its instances, depth-first in instantiation order).
+    pub endpoints: Vec<EndpointDepth>,
+}
+
+impl LogicDepth {
+    /// Returns the endpoint with the deepest logic (the first one, if several have the same depth), or `None` if there are no endpoints.
+    pub fn worst(&self) -> Option<&EndpointDepth> {
+        self.endpoints.iter().fold(
+            None,
+            |worst: Option<&EndpointDepth>, endpoint| match worst {
+                Some(worst) if worst.depth >= endpoint.depth => Some(worst),
+                _ => Some(endpoint),
+            },
+        )
+    }
+}
+
+/// Estimates the combinational logic depth of `m`'s outputs and of the next values of all [`Register`]s in `m`'s hierarchy.
+///
+/// The depth of a path is the number of logic nodes on it: operators (including comparisons and shifts), multiplexers, and combinational [`Mem`] read ports each count as one node, while bit selections, [`repeat`], and [`concat`] are free, as they're just wiring.
+/// Paths start at `m`'s inputs, [`Register`] values, synchronous [`Mem`] read port outputs, and literals, and are followed through instance boundaries.
+/// This is only a rough, technology-independent estimate of a design's critical path, but it's cheap to compute, even for graphs with a lot of reconvergent fan-out.
+///
+/// # Panics
+///
+/// Panics if `m`'s hierarchy contains errors (for example, undriven registers or combinational loops).
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+///
+/// let a = m.input("a", 8);
+/// let b = m.input("b", 8);
+/// let r = m.reg("r", 8);
+/// r.default_value(0u32);
+/// r.drive_next(((a + b) ^ r.value).name("mixed"));
+/// m.output("o", r.value + a);
+///
+/// let depth = analysis::logic_depth(m);
+/// let worst = depth.worst().unwrap();
+/// assert_eq!(worst.endpoint, "r");
+/// assert_eq!(worst.depth, 2);
+/// assert_eq!(worst.path, vec!["a", "mixed", "r"]);
+/// ```
+///
+/// [`concat`]: ../struct.Signal.html#method.concat
+/// [`Mem`]: ../struct.Mem.html
+/// [`Register`]: ../struct.Register.html
+/// [`repeat`]: ../struct.Signal.html#method.repeat
+pub fn logic_depth<'a>(m: &'a graph::Module<'a>) -> LogicDepth {
+    validate_module_hierarchy(m);
+
+    let context_arena = Arena::new();
+    let root_context = context_arena.alloc(ModuleContext::new());
+
+    let mut analyzer = DepthAnalyzer {
+        context_arena: &context_arena,
+        depths: HashMap::new(),
+    };
+
+    let mut endpoints = Vec::new();
+    for (name, output) in m.outputs.borrow().iter() {
+        endpoints.push(analyzer.endpoint_depth(name.clone(), output, root_context));
+    }
+    analyzer.register_depths(m, root_context, String::new(), &mut endpoints);
+
+    LogicDepth { endpoints }
+}
+
+// The logic depth of every signal that `m`'s outputs depend on within `m` itself, as estimated by `logic_depth`
+pub(crate) fn signal_depths<'a>(
+    m: &'a graph::Module<'a>,
+) -> HashMap<*const graph::Signal<'a>, u32> {
+    let context_arena = Arena::new();
+    let root_context = context_arena.alloc(ModuleContext::new());
+
+    let mut analyzer = DepthAnalyzer {
+        context_arena: &context_arena,
+        depths: HashMap::new(),
+    };
+
+    for output in m.outputs.borrow().values() {
+        analyzer.depth(output, root_context);
+    }
+
+    analyzer
+        .depths
+        .iter()
+        .filter(|((context, _), _)| std::ptr::eq(*context, root_context))
+        .map(|(&(_, signal), &(depth, _))| (signal as *const _, depth))
+        .collect()
+}
+
+type DepthKey<'a, 'arena> = (&'arena ModuleContext<'a, 'arena>, &'a graph::Signal<'a>);
+
+struct DepthAnalyzer<'a, 'arena> {
+    context_arena: &'arena Arena<ModuleContext<'a, 'arena>>,
+    // Each entry holds a signal's depth and the dependency on its deepest path, if any
+    depths: HashMap<DepthKey<'a, 'arena>, (u32, Option<DepthKey<'a, 'arena>>)>,
+}
+
+impl<'a, 'arena> DepthAnalyzer<'a, 'arena> {
+    fn register_depths(
+        &mut self,
+        m: &'a graph::Module<'a>,
+        context: &'arena ModuleContext<'a, 'arena>,
+        prefix: String,
+        endpoints: &mut Vec<EndpointDepth>,
+    ) {
+        for reg in m.registers.borrow().iter() {
+            let data = match reg.data {
+                graph::SignalData::Reg { data } => data,
+                _ => unreachable!(),
+            };
+            let next = data.next.borrow().unwrap();
+            endpoints.push(self.endpoint_depth(format!("{}{}", prefix, data.name), next, context));
+        }
+
+        for &instance in m.instances.borrow().iter() {
+            self.register_depths(
+                instance.instantiated_module,
+                context.get_child(instance, self.context_arena),
+                format!("{}{}.", prefix, instance.name),
+                endpoints,
+            );
+        }
+    }
+
+    fn endpoint_depth(
+        &mut self,
+        endpoint: String,
+        signal: &'a graph::Signal<'a>,
+        context: &'arena ModuleContext<'a, 'arena>,
+    ) -> EndpointDepth {
+        let (depth, _) = self.depth(signal, context);
+
+        let mut path = Vec::new();
+        let mut key = Some((context, signal));
+        while let Some((context, signal)) = key {
+            if let Some(name) = point_name(signal, context) {
+                path.push(name);
+            }
+            key = self.depths[&(context, signal)].1;
+        }
+        path.reverse();
+        path.push(endpoint.clone());
+
+        EndpointDepth {
+            endpoint,
+            depth,
+            path,
+        }
+    }
+
+    fn depth(
+        &mut self,
+        signal: &'a graph::Signal<'a>,
+        context: &'arena ModuleContext<'a, 'arena>,
+    ) -> (u32, Option<DepthKey<'a, 'arena>>) {
+        // Sources are visited bottom-up with an explicit stack first, so that this never recurses, regardless of how deep the graph is
+        let mut stack = vec![((context, signal), false)];
+        while let Some((key, sources_visited)) = stack.pop() {
+            if self.depths.contains_key(&key) {
+                continue;
+            }
+            let (cost, sources) = self.cost_and_sources(key.1, key.0);
+            if sources_visited {
+                let mut ret = (cost, None);
+                for source in sources {
+                    let (source_depth, _) = self.depths[&source];
+                    if ret.1.is_none() || cost + source_depth > ret.0 {
+                        ret = (cost + source_depth, Some(source));
+                    }
+                }
+                self.depths.insert(key, ret);
+                continue;
+            }
+            stack.push((key, true));
+            stack.extend(sources.into_iter().rev().map(|source| (source, false)));
+        }
+
+        self.depths[&(context, signal)]
+    }
+
+    // The cost of `signal` itself, and the signals it depends on
+    fn cost_and_sources(
+        &mut self,
+        signal: &'a graph::Signal<'a>,
+        context: &'arena ModuleContext<'a, 'arena>,
+    ) -> (u32, Vec<DepthKey<'a, 'arena>>) {
+        match signal.data {
+            graph::SignalData::Lit { .. }
+            | graph::SignalData::Reg { .. }
+            | graph::SignalData::MemReadPortOutput { .. } => (0, Vec::new()),
+
+            graph::SignalData::Input { name, .. } => match context.instance_and_parent {
+                Some((instance, parent)) => {
+                    (0, vec![(parent, instance.driven_inputs.borrow()[name])])
+                }
+                None => (0, Vec::new()),
+            },
+
+            // Transparent latches pass their data through combinationally
+            graph::SignalData::Latch { data } => (
+                0,
+                vec![
+                    (context, data.data.borrow().unwrap()),
+                    (context, data.enable.borrow().unwrap()),
+                ],
+            ),
+
+            graph::SignalData::UnOp { source, .. } => (1, vec![(context, source)]),
+            graph::SignalData::SimpleBinOp { lhs, rhs, .. }
+            | graph::SignalData::AdditiveBinOp { lhs, rhs, .. }
+            | graph::SignalData::ComparisonBinOp { lhs, rhs, .. }
+            | graph::SignalData::ShiftBinOp { lhs, rhs, .. } => {
+                (1, vec![(context, lhs), (context, rhs)])
+            }
+
+            graph::SignalData::Bits { source, .. } | graph::SignalData::Repeat { source, .. } => {
+                (0, vec![(context, source)])
+            }
+            graph::SignalData::Concat { lhs, rhs } => (0, vec![(context, lhs), (context, rhs)]),
+
+            graph::SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            } => (
+                1,
+                vec![(context, cond), (context, when_true), (context, when_false)],
+            ),
+
+            graph::SignalData::InstanceOutput { instance, name } => (
+                0,
+                vec![(
+                    context.get_child(instance, self.context_arena),
+                    instance.instantiated_module.outputs.borrow()[name],
+                )],
+            ),
+
+            graph::SignalData::MemCombReadPortOutput { address, .. } => {
+                (1, vec![(context, address)])
+            }
+        }
+    }
+}
+
+fn context_prefix<'a, 'arena>(context: &'arena ModuleContext<'a, 'arena>) -> String {
+    match context.instance_and_parent {
+        Some((instance, parent)) => format!("{}{}.", context_prefix(parent), instance.name),
+        None => String::new(),
+    }
+}
+
+fn point_name<'a, 'arena>(
+    signal: &'a graph::Signal<'a>,
+    context: &'arena ModuleContext<'a, 'arena>,
+) -> Option<String> {
+    let name = match signal.data {
+        graph::SignalData::Input { name, .. } => Some(name.to_string()),
+        graph::SignalData::Reg { data } => Some(data.name.clone()),
+        graph::SignalData::Latch { data } => Some(data.name.clone()),
+        graph::SignalData::InstanceOutput { instance, name } => {
+            Some(format!("{}.{}", instance.name, name))
+        }
+        graph::SignalData::MemReadPortOutput { mem, .. } => Some(mem.name.clone()),
+        _ => signal
+            .module
+            .named_signals
+            .borrow()
+            .iter()
+            .find(|(_, &named_signal)| named_signal == signal)
+            .map(|(name, _)| name.clone()),
+    };
+    name.map(|name| format!("{}{}", context_prefix(context), name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::*;
+
+    #[test]
+    fn no_dead_signals() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("i", 1));
+
+        let m = c.module("A");
+        let r = m.reg("r", 1);
+        r.drive_next(!r.value);
+        let inst = m.instance("inner", "Inner");
+        inst.drive_input("i", r.value & m.input("i", 1));
+        m.assert(inst.output("o") | m.high(), "tautology");
+
+        assert!(dead_signals(m).is_empty());
+    }
+
+    #[test]
+    fn dead_registers_and_mems() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 4);
+        let unused_input = m.input("unused", 4);
+
+        // Register that only feeds itself
+        let r = m.reg("r", 4);
+        r.drive_next(r.value + i);
+
+        // Memory whose read port is never used
+        let mem = m.mem("mem", 1, 4);
+        mem.initial_contents(&[0u32, 1]);
+        let read = mem.read_port(i.bit(0), m.high());
+
+        m.output("o", i);
+
+        let dead = dead_signals(m);
+        assert_eq!(dead.len(), 6);
+        assert!(dead.contains(&unused_input));
+        assert!(dead.contains(&r.value));
+        assert!(dead.contains(&read));
+        assert!(!dead.contains(&i));
+    }
+
+    #[test]
+    fn stats_counts() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let i = inner.input("i", 8);
+        let shared = i ^ inner.lit(0x5au32, 8);
+        let r = inner.reg("r", 8);
+        r.default_value(0u32);
+        r.drive_next(inner.mux(i.bit(0), shared, !shared));
+        inner.output("o", r.value + shared);
+        // Dead logic isn't counted
+        let _ = i - shared;
+
+        let outer = c.module("Outer");
+        let mem = outer.mem("mem", 4, 8);
+        mem.initial_contents(&[0u32; 16]);
+        let a = outer.instance("a", "Inner");
+        a.drive_input("i", mem.read_port(outer.input("addr", 4), outer.high()));
+        let b = outer.instance("b", "Inner");
+        b.drive_input("i", a.output("o"));
+        outer.output("o", b.output("o").concat(a.output("o")));
+
+        let stats = stats(outer);
+
+        assert_eq!(stats.module_name, "Outer");
+        assert_eq!(stats.local.registers, 0);
+        assert_eq!(stats.local.mems, 1);
+        assert_eq!(stats.local.mem_bits, 128);
+        assert_eq!(stats.local.instances, 2);
+        assert_eq!(stats.local.total_nodes(), 2);
+        assert_eq!(stats.local.nodes[&NodeKind::MemReadPort], 1);
+        assert_eq!(stats.local.nodes[&NodeKind::Concat], 1);
+
+        assert_eq!(stats.instances.len(), 2);
+        assert_eq!(stats.instances[0].0, "a");
+        assert_eq!(stats.instances[1].0, "b");
+        let inner_stats = &stats.instances[0].1;
+        assert_eq!(inner_stats.module_name, "Inner");
+        assert_eq!(inner_stats.local.registers, 1);
+        assert_eq!(inner_stats.local.register_bits, 8);
+        // shared, bit 0, !shared, mux, add
+        assert_eq!(inner_stats.local.total_nodes(), 5);
+        assert_eq!(inner_stats.local.nodes[&NodeKind::BinOp], 2);
+        assert_eq!(inner_stats.local.nodes[&NodeKind::UnOp], 1);
+        assert_eq!(inner_stats.local.nodes[&NodeKind::Bits], 1);
+        assert_eq!(inner_stats.local.nodes[&NodeKind::Mux], 1);
+        assert_eq!(inner_stats.local.bit_widths[&1], 1);
+        assert_eq!(inner_stats.local.bit_widths[&8], 4);
+        assert_eq!(inner_stats.total, inner_stats.local);
+
+        assert_eq!(stats.total.registers, 2);
+        assert_eq!(stats.total.register_bits, 16);
+        assert_eq!(stats.total.mems, 1);
+        assert_eq!(stats.total.instances, 2);
+        assert_eq!(stats.total.total_nodes(), 12);
+        assert_eq!(stats.total.nodes[&NodeKind::BinOp], 4);
+        assert_eq!(stats.total.bit_widths[&1], 2);
+        assert_eq!(stats.total.bit_widths[&8], 9);
+        assert_eq!(stats.total.bit_widths[&16], 1);
+    }
+
+    #[test]
+    fn stats_display() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let r = inner.reg("r", 4);
+        r.drive_next(!r.value);
+        inner.output("o", r.value);
+
+        let outer = c.module("Outer");
+        let inst = outer.instance("inst", "Inner");
+        outer.output("o", inst.output("o"));
+
+        let output = stats(outer).to_string();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("Module "));
+        assert!(lines[1].starts_with("Outer "));
+        assert!(lines[2].starts_with("  inst (Inner) "));
+        assert!(lines[3].starts_with("Total "));
+        assert!(lines[3].ends_with(" 1"));
+        assert!(output.contains("\n  UnOp "));
+        assert!(output.contains("\n  4 "));
+    }
+
+    #[test]
+    fn logic_depth_adder_chain() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+        let r = m.reg("r", 8);
+        r.default_value(0u32);
+
+        let mut sum = r.value;
+        for _ in 0..5 {
+            sum = sum + a;
+        }
+        r.drive_next(sum);
+        m.output("sum", sum.name("sum"));
+        m.output("direct", a.bits(3, 0).concat(r.value.bits(7, 4)));
+
+        let depth = logic_depth(m);
+        let endpoints = depth
+            .endpoints
+            .iter()
+            .map(|endpoint| (endpoint.endpoint.as_str(), endpoint.depth))
+            .collect::<Vec<_>>();
+        assert_eq!(endpoints, vec![("direct", 0), ("sum", 5), ("r", 5)]);
+
+        let worst = depth.worst().unwrap();
+        assert_eq!(worst.endpoint, "sum");
+        assert_eq!(worst.path, vec!["r", "sum", "sum"]);
+    }
+
+    #[test]
+    fn logic_depth_crosses_instances() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let i = inner.input("i", 4);
+        inner.output("o", !(i + inner.lit(1u32, 4)));
+
+        let outer = c.module("Outer");
+        let r = outer.reg("r", 4);
+        r.default_value(0u32);
+        let inst1 = outer.instance("inst1", "Inner");
+        inst1.drive_input("i", r.value);
+        let inst2 = outer.instance("inst2", "Inner");
+        inst2.drive_input("i", inst1.output("o") ^ r.value);
+        r.drive_next(inst2.output("o"));
+        outer.output("o", inst1.output("o"));
+
+        let depth = logic_depth(outer);
+        assert_eq!(depth.endpoints.len(), 2);
+        assert_eq!(depth.endpoints[0].endpoint, "o");
+        assert_eq!(depth.endpoints[0].depth, 2);
+        assert_eq!(
+            depth.endpoints[0].path,
+            vec!["r", "inst1.i", "inst1.o", "o"]
+        );
+
+        let worst = depth.worst().unwrap();
+        assert_eq!(worst.endpoint, "r");
+        assert_eq!(worst.depth, 5);
+        assert_eq!(
+            worst.path,
+            vec!["r", "inst1.i", "inst1.o", "inst2.i", "inst2.o", "r"]
+        );
+    }
+
+    #[test]
+    fn logic_depth_nested_registers() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let r = inner.reg("r", 1);
+        r.default_value(false);
+        r.drive_next(inner.input("i", 1).mux(!r.value, r.value));
+        inner.output("o", r.value);
+
+        let outer = c.module("Outer");
+        let inst = outer.instance("inst", "Inner");
+        inst.drive_input("i", outer.input("a", 1) & outer.input("b", 1));
+        outer.output("o", inst.output("o"));
+
+        let depth = logic_depth(outer);
+        assert_eq!(depth.endpoints.len(), 2);
+        assert_eq!(depth.endpoints[0].depth, 0);
+        assert_eq!(depth.endpoints[0].path, vec!["inst.r", "inst.o", "o"]);
+        assert_eq!(depth.endpoints[1].endpoint, "inst.r");
+        assert_eq!(depth.endpoints[1].depth, 2);
+        assert_eq!(depth.endpoints[1].path, vec!["a", "inst.i", "inst.r"]);
+    }
+}
@@ -0,0 +1,426 @@
+//! Helpers for generating simulators from build scripts.
+
+use crate::graph;
+use crate::sim;
+
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Generates Rust simulators for a set of [`Module`]s into a single file, typically from a build script.
+///
+/// Simulators are generated in the order their `Module`s were added, so the generated file only changes when the design does.
+///
+/// [`Module`]: ../struct.Module.html
+///
+/// # Examples
+///
+/// In `build.rs`:
+///
+/// ```no_run
+/// use kaze::*;
+///
+/// fn main() -> std::io::Result<()> {
+///     let c = Context::new();
+///
+///     let inverter = c.module("Inverter");
+///     inverter.output("o", !inverter.input("i", 1));
+///
+///     build::SimBuilder::new()
+///         .module(inverter)
+///         .write_to_out_dir("modules.rs")
+/// }
+/// ```
+///
+/// And then in the crate itself:
+///
+/// ```text
+/// include!(concat!(env!("OUT_DIR"), "/modules.rs"));
+/// ```
+pub struct SimBuilder<'a> {
+    sim_module_trait: bool,
+    modules: Vec<(&'a graph::Module<'a>, sim::GenerationOptions)>,
+}
+
+impl<'a> SimBuilder<'a> {
+    /// Creates a new `SimBuilder` with no `Module`s.
+    pub fn new() -> SimBuilder<'a> {
+        SimBuilder {
+            sim_module_trait: false,
+            modules: Vec::new(),
+        }
+    }
+
+    /// Adds `m` to be generated with the default [`sim::GenerationOptions`].
+    ///
+    /// [`sim::GenerationOptions`]: ../sim/struct.GenerationOptions.html
+    pub fn module(&mut self, m: &'a graph::Module<'a>) -> &mut SimBuilder<'a> {
+        self.module_with_options(m, sim::GenerationOptions::default())
+    }
+
+    /// Adds `m` to be generated according to `options`.
+    pub fn module_with_options(
+        &mut self,
+        m: &'a graph::Module<'a>,
+        options: sim::GenerationOptions,
+    ) -> &mut SimBuilder<'a> {
+        self.modules.push((m, options));
+        self
+    }
+
+    /// Also generates the `SimModule` trait (see [`sim::generate_sim_module_trait`]) before any simulators.
+    ///
+    /// [`sim::generate_sim_module_trait`]: ../sim/fn.generate_sim_module_trait.html
+    pub fn sim_module_trait(&mut self) -> &mut SimBuilder<'a> {
+        self.sim_module_trait = true;
+        self
+    }
+
+    /// Returns the paths of the files that any [`Mem`]s in the added `Module`s' hierarchies were initialized from with [`Mem::initial_contents_from_file`], in the order they're first found.
+    ///
+    /// [`Mem`]: ../struct.Mem.html
+    /// [`Mem::initial_contents_from_file`]: ../struct.Mem.html#method.initial_contents_from_file
+    pub fn mem_image_files(&self) -> Vec<PathBuf> {
+        fn visit<'a>(
+            m: &'a graph::Module<'a>,
+            visited: &mut HashSet<*const graph::Module<'a>>,
+            paths: &mut Vec<PathBuf>,
+        ) {
+            if !visited.insert(m) {
+                return;
+            }
+            for mem in m.mems.borrow().iter() {
+                if let Some(ref path) = *mem.initial_contents_path.borrow() {
+                    if !paths.contains(path) {
+                        paths.push(path.clone());
+                    }
+                }
+            }
+            for instance in m.instances.borrow().iter() {
+                visit(instance.instantiated_module, visited, paths);
+            }
+        }
+
+        let mut visited = HashSet::new();
+        let mut paths = Vec::new();
+        for &(m, _) in self.modules.iter() {
+            visit(m, &mut visited, &mut paths);
+        }
+        paths
+    }
+
+    /// Generates all added `Module`s' simulators and writes them to `w`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if two added `Module`s would generate simulators with the same name (taking [`sim::GenerationOptions::struct_name`] and [`sim::GenerationOptions::module_name`] into account), such as when the same `Module` is added more than once with the same options, or if writing to `w` fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`sim::generate_with_options`].
+    ///
+    /// [`sim::GenerationOptions::module_name`]: ../sim/struct.GenerationOptions.html#structfield.module_name
+    /// [`sim::GenerationOptions::struct_name`]: ../sim/struct.GenerationOptions.html#structfield.struct_name
+    /// [`sim::generate_with_options`]: ../sim/fn.generate_with_options.html
+    pub fn write<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let mut simulator_names = HashMap::new();
+        for &(m, ref options) in self.modules.iter() {
+            let struct_name = options
+                .struct_name
+                .clone()
+                .unwrap_or_else(|| sim::identifier(&m.name));
+            let simulator_name = match options.module_name {
+                Some(ref module_name) => format!("{}::{}", module_name, struct_name),
+                _ => struct_name,
+            };
+            if let Some(other_name) = simulator_names.insert(simulator_name.clone(), &m.name) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    if *other_name == m.name {
+                        format!(
+                            "Module \"{}\" was added more than once, which would generate conflicting simulators.",
+                            m.name
+                        )
+                    } else {
+                        format!(
+                            "Modules \"{}\" and \"{}\" would both generate a simulator called \"{}\".",
+                            other_name, m.name, simulator_name
+                        )
+                    },
+                ));
+            }
+        }
+
+        if self.sim_module_trait {
+            sim::generate_sim_module_trait(&mut w).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("Couldn't write the SimModule trait: {}", e),
+                )
+            })?;
+        }
+        for &(m, ref options) in self.modules.iter() {
+            sim::generate_with_options(m, options, &mut w).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!(
+                        "Couldn't write the simulator for module \"{}\": {}",
+                        m.name, e
+                    ),
+                )
+            })?;
+        }
+
+        w.flush()
+    }
+
+    /// Generates all added `Module`s' simulators and writes them to a file at `path`, replacing the file if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`write`], or if the file can't be created.
+    ///
+    /// [`write`]: #method.write
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Couldn't create \"{}\": {}", path.display(), e),
+            )
+        })?;
+        self.write(BufWriter::new(file))
+    }
+
+    /// Generates all added `Module`s' simulators and writes them to a file called `file_name` in the build script's output directory (`OUT_DIR`).
+    ///
+    /// This also prints `cargo:rerun-if-changed` lines for any files returned by [`mem_image_files`], so that the build script is re-run when they change.
+    /// Note that when any such lines are printed, Cargo no longer re-runs the build script whenever any file in the package changes, so `build.rs` itself is also listed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`write_to_file`], or if `OUT_DIR` isn't set (which is the case outside of build scripts).
+    ///
+    /// [`mem_image_files`]: #method.mem_image_files
+    /// [`write_to_file`]: #method.write_to_file
+    pub fn write_to_out_dir<P: AsRef<Path>>(&self, file_name: P) -> io::Result<()> {
+        let out_dir = env::var_os("OUT_DIR").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "The OUT_DIR environment variable isn't set; write_to_out_dir should only be called from build scripts.",
+            )
+        })?;
+        self.write_to_file(Path::new(&out_dir).join(file_name))?;
+
+        let mem_image_files = self.mem_image_files();
+        if !mem_image_files.is_empty() {
+            println!("cargo:rerun-if-changed=build.rs");
+            for path in mem_image_files {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Default for SimBuilder<'a> {
+    fn default() -> SimBuilder<'a> {
+        SimBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::*;
+
+    #[test]
+    fn duplicate_module_error() {
+        let c = Context::new();
+        let m = c.module("A");
+        m.output("o", m.input("i", 1));
+
+        let e = build::SimBuilder::new()
+            .module(m)
+            .module(m)
+            .write(Vec::new())
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            e.to_string(),
+            "Module \"A\" was added more than once, which would generate conflicting simulators."
+        );
+    }
+
+    #[test]
+    fn duplicate_module_with_different_names() {
+        let c = Context::new();
+        let m = c.module("A");
+        m.output("o", m.input("i", 1));
+
+        let mut w = Vec::new();
+        build::SimBuilder::new()
+            .module(m)
+            .module_with_options(
+                m,
+                sim::GenerationOptions {
+                    struct_name: Some("OtherA".into()),
+                    ..Default::default()
+                },
+            )
+            .module_with_options(
+                m,
+                sim::GenerationOptions {
+                    module_name: Some("other".into()),
+                    ..Default::default()
+                },
+            )
+            .write(&mut w)
+            .unwrap();
+
+        let code = String::from_utf8(w).unwrap();
+        assert!(code.contains("pub struct A {"));
+        assert!(code.contains("pub struct OtherA {"));
+        assert!(code.contains("pub mod other {"));
+    }
+
+    #[test]
+    fn simulator_name_collision_error() {
+        let c = Context::new();
+        let a = c.module("A");
+        a.output("o", a.input("i", 1));
+        let b = c.module("B");
+        b.output("o", b.input("i", 1));
+
+        let e = build::SimBuilder::new()
+            .module(a)
+            .module_with_options(
+                b,
+                sim::GenerationOptions {
+                    struct_name: Some("A".into()),
+                    ..Default::default()
+                },
+            )
+            .write(Vec::new())
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            e.to_string(),
+            "Modules \"A\" and \"B\" would both generate a simulator called \"A\"."
+        );
+    }
+
+    #[test]
+    fn write_error_names_module() {
+        struct FailingWriter;
+
+        impl Write for FailingWriter {
+            fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let c = Context::new();
+        let m = c.module("A");
+        m.output("o", m.input("i", 1));
+
+        let e = build::SimBuilder::new()
+            .module(m)
+            .write(FailingWriter)
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Couldn't write the simulator for module \"A\": disk full"
+        );
+    }
+
+    #[test]
+    fn write_to_file_error_names_path() {
+        let c = Context::new();
+        let m = c.module("A");
+        m.output("o", m.input("i", 1));
+
+        let path = env::temp_dir()
+            .join("kaze_build_test_nonexistent_dir")
+            .join("modules.rs");
+        let e = build::SimBuilder::new()
+            .module(m)
+            .write_to_file(&path)
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+        assert!(e
+            .to_string()
+            .starts_with(&format!("Couldn't create \"{}\": ", path.display())));
+    }
+
+    #[test]
+    fn write_to_out_dir_outside_build_script() {
+        // OUT_DIR is only set for build scripts (and crates that have one), so this should always fail here
+        if env::var_os("OUT_DIR").is_some() {
+            return;
+        }
+
+        let c = Context::new();
+        let m = c.module("A");
+        m.output("o", m.input("i", 1));
+
+        let e = build::SimBuilder::new()
+            .module(m)
+            .write_to_out_dir("modules.rs")
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn output_order_and_mem_image_files() {
+        let path = env::temp_dir().join(format!(
+            "kaze_build_test_mem_image_files_{}.hex",
+            std::process::id()
+        ));
+        std::fs::write(&path, "12 34\n").unwrap();
+
+        let c = Context::new();
+        let inner = c.module("Inner");
+        let rom = inner.mem("rom", 1, 8);
+        rom.initial_contents_from_file(&path).unwrap();
+        inner.output("o", rom.read_port(inner.input("a", 1), inner.high()));
+
+        let b = c.module("B");
+        let inst = b.instance("inst", "Inner");
+        inst.drive_input("a", b.input("a", 1));
+        b.output("o", inst.output("o"));
+
+        let a = c.module("A");
+        let inst = a.instance("inst", "Inner");
+        inst.drive_input("a", a.input("a", 1));
+        a.output("o", inst.output("o"));
+
+        let mut builder = build::SimBuilder::new();
+        builder.sim_module_trait().module(b).module_with_options(
+            a,
+            sim::GenerationOptions {
+                impl_sim_module: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(builder.mem_image_files(), vec![path.clone()]);
+
+        let mut w = Vec::new();
+        builder.write(&mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+        let trait_index = code.find("pub trait SimModule").unwrap();
+        let b_index = code.find("pub struct B").unwrap();
+        let a_index = code.find("pub struct A").unwrap();
+        assert!(trait_index < b_index && b_index < a_index);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,13 +1,122 @@
 use std::io::{Result, Write};
 
+/// Formatting options for generated code, used with [`verilog::GenerationOptions`] and [`sim::GenerationOptions`].
+///
+/// By default, generated code is indented with 4 spaces, lines are never wrapped, and lines end with `\n`.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let style = CodeStyle::new()
+///     .indent("  ")
+///     .max_line_width(100)
+///     .line_ending(LineEnding::CrLf);
+/// ```
+///
+/// [`sim::GenerationOptions`]: ./sim/struct.GenerationOptions.html
+/// [`verilog::GenerationOptions`]: ./verilog/struct.GenerationOptions.html
+#[derive(Clone, Debug)]
+pub struct CodeStyle {
+    indent: String,
+    max_line_width: Option<usize>,
+    line_ending: LineEnding,
+}
+
+impl CodeStyle {
+    /// Creates a `CodeStyle` with the default options.
+    pub fn new() -> CodeStyle {
+        CodeStyle {
+            indent: "    ".into(),
+            max_line_width: None,
+            line_ending: LineEnding::Lf,
+        }
+    }
+
+    /// Specifies the string used for each level of indentation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `indent` is empty or contains characters other than spaces and tabs.
+    pub fn indent<S: Into<String>>(mut self, indent: S) -> CodeStyle {
+        let indent = indent.into();
+        if indent.is_empty() || !indent.chars().all(|c| c == ' ' || c == '\t') {
+            panic!(
+                "Attempted to specify an indent of {:?}, but indents must contain only spaces and tabs, and must not be empty.",
+                indent
+            );
+        }
+        self.indent = indent;
+        self
+    }
+
+    /// Specifies the maximum width of generated lines, in characters.
+    ///
+    /// Lines that are too long are wrapped at operator boundaries within expressions, with continuation lines indented one level further than the line they continue.
+    /// Lines without any suitable place to wrap (for example, a single very long identifier) may still exceed this width.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_line_width` is `0`.
+    pub fn max_line_width(mut self, max_line_width: usize) -> CodeStyle {
+        if max_line_width == 0 {
+            panic!("Attempted to specify a maximum line width of 0 characters, but the maximum line width must be at least 1 character.");
+        }
+        self.max_line_width = Some(max_line_width);
+        self
+    }
+
+    /// Specifies the [`LineEnding`] used to end each generated line.
+    ///
+    /// [`LineEnding`]: ./enum.LineEnding.html
+    pub fn line_ending(mut self, line_ending: LineEnding) -> CodeStyle {
+        self.line_ending = line_ending;
+        self
+    }
+}
+
+impl Default for CodeStyle {
+    fn default() -> CodeStyle {
+        CodeStyle::new()
+    }
+}
+
+/// The sequence of characters that ends each line of generated code, used with [`CodeStyle::line_ending`].
+///
+/// [`CodeStyle::line_ending`]: ./struct.CodeStyle.html#method.line_ending
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineEnding {
+    /// `\n`, as used on Unix-like systems.
+    Lf,
+    /// `\r\n`, as used on Windows.
+    CrLf,
+}
+
 pub struct CodeWriter<W: Write> {
     w: W,
+    style: CodeStyle,
     indent_level: u32,
+
+    // The current line is buffered so that it can be wrapped at break points when it's finished
+    line: String,
+    break_points: Vec<usize>,
 }
 
 impl<W: Write> CodeWriter<W> {
     pub fn new(w: W) -> CodeWriter<W> {
-        CodeWriter { w, indent_level: 0 }
+        CodeWriter::with_style(w, CodeStyle::default())
+    }
+
+    pub fn with_style(w: W, style: CodeStyle) -> CodeWriter<W> {
+        CodeWriter {
+            w,
+            style,
+            indent_level: 0,
+
+            line: String::new(),
+            break_points: Vec::new(),
+        }
     }
 
     pub fn indent(&mut self) {
@@ -24,18 +133,68 @@ impl<W: Write> CodeWriter<W> {
 
     pub fn append_indent(&mut self) -> Result<()> {
         for _ in 0..self.indent_level {
-            write!(self.w, "    ")?;
+            self.line.push_str(&self.style.indent);
         }
         Ok(())
     }
 
+    // Marks the current position as a place where the current line may be wrapped if it's too long
+    pub fn append_break(&mut self) {
+        if self.style.max_line_width.is_some() {
+            self.break_points.push(self.line.len());
+        }
+    }
+
     pub fn append_newline(&mut self) -> Result<()> {
-        writeln!(self.w, "")?;
+        let line = std::mem::take(&mut self.line);
+        let break_points = std::mem::take(&mut self.break_points);
+        let line_ending = match self.style.line_ending {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        };
+
+        let mut start = 0;
+        if let Some(max_line_width) = self.style.max_line_width {
+            let indent_len = line.len() - line.trim_start().len();
+            let continuation_indent = format!("{}{}", &line[..indent_len], self.style.indent);
+            let mut prefix = "";
+            loop {
+                let width = |end: usize| {
+                    prefix.chars().count() + line[start..end].trim_end().chars().count()
+                };
+                if width(line.len()) <= max_line_width {
+                    break;
+                }
+                let candidates = break_points
+                    .iter()
+                    .cloned()
+                    .filter(|&point| point > start && !line[start..point].trim().is_empty());
+                let fitting = candidates
+                    .clone()
+                    .rfind(|&point| width(point) <= max_line_width);
+                let end = match fitting.or_else(|| candidates.clone().next()) {
+                    Some(end) => end,
+                    _ => break,
+                };
+                write!(
+                    self.w,
+                    "{}{}{}",
+                    prefix,
+                    line[start..end].trim_end(),
+                    line_ending
+                )?;
+                start = end + (line[end..].len() - line[end..].trim_start().len());
+                prefix = &continuation_indent;
+            }
+            write!(self.w, "{}", prefix)?;
+        }
+        write!(self.w, "{}{}", &line[start..], line_ending)?;
+
         Ok(())
     }
 
     pub fn append(&mut self, s: &str) -> Result<()> {
-        write!(self.w, "{}", s)?;
+        self.line.push_str(s);
         Ok(())
     }
 
@@ -46,3 +205,13 @@ impl<W: Write> CodeWriter<W> {
         Ok(())
     }
 }
+
+impl<W: Write> Drop for CodeWriter<W> {
+    fn drop(&mut self) {
+        // Flush any unterminated line
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            let _ = write!(self.w, "{}", line);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Reusable hardware building blocks, built entirely from kaze's [`Module`], [`Register`], and [`Mem`] primitives.
+//!
+//! Each generator creates a new [`Module`] in a given [`Context`], which can then be [instantiated](../struct.Module.html#method.instance) like any other.
+//!
+//! [`Context`]: ../struct.Context.html
+//! [`Mem`]: ../struct.Mem.html
+//! [`Module`]: ../struct.Module.html
+//! [`Register`]: ../struct.Register.html
+
+mod arbiter;
+mod crc;
+mod csr;
+mod fifo;
+mod stream;
+
+pub use arbiter::*;
+pub use crc::*;
+pub use csr::*;
+pub use fifo::*;
+pub use stream::*;
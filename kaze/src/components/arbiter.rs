@@ -0,0 +1,209 @@
+use crate::graph::*;
+
+/// Creates a new [`Module`] called `name` in the given [`Context`] that implements a combinational fixed-priority arbiter for `num_requestors` requestors.
+///
+/// Requestor 0 has the highest priority and requestor `num_requestors - 1` the lowest; the lowest-indexed active request is always granted.
+/// Note that this means low-priority requestors can be starved indefinitely; see [`round_robin_arbiter`] for a fair alternative.
+///
+/// The generated module has the following ports:
+///
+/// | Port          | Direction | Bit width        | Description |
+/// |---------------|-----------|------------------|-------------|
+/// | `requests`    | input     | `num_requestors` | One request bit per requestor. |
+/// | `grant`       | output    | `num_requestors` | One-hot grant for the winning requestor, or `0` if there are no requests. |
+/// | `grant_valid` | output    | 1                | High when any request is granted. |
+///
+/// # Panics
+///
+/// Panics if `num_requestors` is `0`, or if it's greater than [`MAX_SIGNAL_BIT_WIDTH`].
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let arbiter = components::priority_arbiter(&c, "Arbiter", 4);
+///
+/// let m = c.module("Top");
+/// let inner = m.instance("arbiter", "Arbiter");
+/// inner.drive_input("requests", m.input("requests", 4));
+/// m.output("grant", inner.output("grant"));
+/// m.output("grant_valid", inner.output("grant_valid"));
+///
+/// assert_eq!(arbiter.outputs()["grant"].bit_width(), 4);
+/// ```
+///
+/// [`Context`]: ../struct.Context.html
+/// [`MAX_SIGNAL_BIT_WIDTH`]: ../constant.MAX_SIGNAL_BIT_WIDTH.html
+/// [`Module`]: ../struct.Module.html
+/// [`round_robin_arbiter`]: fn.round_robin_arbiter.html
+#[track_caller]
+pub fn priority_arbiter<'a, S: Into<String>>(
+    c: &'a Context<'a>,
+    name: S,
+    num_requestors: u32,
+) -> &'a Module<'a> {
+    let name = name.into();
+    check_num_requestors(&name, num_requestors);
+
+    let m = c.module(name);
+
+    let requests = m.input("requests", num_requestors);
+    let grant = priority_grant(m, requests);
+
+    m.output("grant", grant);
+    m.output("grant_valid", grant_valid(m, grant));
+
+    m
+}
+
+/// Creates a new [`Module`] called `name` in the given [`Context`] that implements a round-robin arbiter for `num_requestors` requestors.
+///
+/// The arbiter keeps a rotating priority pointer in a register called `mask`, which holds a bit for each requestor that is *after* the most recently granted one.
+/// Each cycle, requests from those requestors are considered first (lowest index first); if there aren't any, the search wraps around and considers all requests, again lowest index first.
+/// Whenever a grant is issued, the pointer moves to just after the granted requestor, so it becomes the lowest-priority requestor on the next cycle.
+/// As a result, a requestor that keeps its request asserted is granted within at most `num_requestors` cycles, no matter what the others do.
+///
+/// After reset, requestor 0 has the highest priority.
+///
+/// The generated module has the following ports:
+///
+/// | Port          | Direction | Bit width        | Description |
+/// |---------------|-----------|------------------|-------------|
+/// | `requests`    | input     | `num_requestors` | One request bit per requestor. |
+/// | `grant`       | output    | `num_requestors` | One-hot grant for the winning requestor, or `0` if there are no requests. |
+/// | `grant_valid` | output    | 1                | High when any request is granted. |
+///
+/// The grant is combinational with respect to `requests`; only the priority pointer is registered.
+///
+/// # Panics
+///
+/// Panics if `num_requestors` is `0`, or if it's greater than [`MAX_SIGNAL_BIT_WIDTH`].
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let arbiter = components::round_robin_arbiter(&c, "Arbiter", 3);
+///
+/// let m = c.module("Top");
+/// let inner = m.instance("arbiter", "Arbiter");
+/// inner.drive_input("requests", m.input("requests", 3));
+/// m.output("grant", inner.output("grant"));
+/// m.output("grant_valid", inner.output("grant_valid"));
+///
+/// assert_eq!(arbiter.registers().len(), 1);
+/// ```
+///
+/// [`Context`]: ../struct.Context.html
+/// [`MAX_SIGNAL_BIT_WIDTH`]: ../constant.MAX_SIGNAL_BIT_WIDTH.html
+/// [`Module`]: ../struct.Module.html
+#[track_caller]
+pub fn round_robin_arbiter<'a, S: Into<String>>(
+    c: &'a Context<'a>,
+    name: S,
+    num_requestors: u32,
+) -> &'a Module<'a> {
+    let name = name.into();
+    check_num_requestors(&name, num_requestors);
+
+    let m = c.module(name);
+
+    let requests = m.input("requests", num_requestors);
+
+    let mask = m.reg("mask", num_requestors);
+    mask.default_value(0u32);
+
+    let masked_grant = priority_grant(m, requests & mask.value);
+    let unmasked_grant = priority_grant(m, requests);
+    let grant = grant_valid(m, masked_grant).mux(masked_grant, unmasked_grant);
+    let grant_valid = grant_valid(m, grant);
+
+    // The next mask has a bit set for each requestor strictly after the granted one. If the
+    //  last requestor was granted, the mask is empty, which makes the next search wrap around.
+    let mut next_mask_bits = Vec::new();
+    let mut granted_before = m.low();
+    for i in 0..num_requestors {
+        next_mask_bits.push(granted_before);
+        granted_before = granted_before | grant.bit(i);
+    }
+    mask.drive_next(grant_valid.mux(m.pack(&next_mask_bits), mask.value));
+
+    m.output("grant", grant);
+    m.output("grant_valid", grant_valid);
+
+    m
+}
+
+fn check_num_requestors(name: &str, num_requestors: u32) {
+    if num_requestors == 0 {
+        panic!(
+            "Cannot create an arbiter called \"{}\" with 0 requestors. Arbiters must have at least 1 requestor.",
+            name
+        );
+    }
+}
+
+/// Grants the lowest-indexed set bit of `requests`.
+fn priority_grant<'a>(m: &'a Module<'a>, requests: &'a Signal<'a>) -> &'a Signal<'a> {
+    let mut grant_bits = Vec::new();
+    let mut requested_before = m.low();
+    for i in 0..requests.bit_width() {
+        let request = requests.bit(i);
+        grant_bits.push(request & !requested_before);
+        requested_before = requested_before | request;
+    }
+    m.pack(&grant_bits)
+}
+
+fn grant_valid<'a>(m: &'a Module<'a>, grant: &'a Signal<'a>) -> &'a Signal<'a> {
+    grant.ne(m.lit(0u32, grant.bit_width()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create an arbiter called \"Arbiter\" with 0 requestors. Arbiters must have at least 1 requestor."
+    )]
+    fn priority_arbiter_zero_requestors_error() {
+        let c = Context::new();
+
+        let _ = components::priority_arbiter(&c, "Arbiter", 0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create an arbiter called \"Arbiter\" with 0 requestors. Arbiters must have at least 1 requestor."
+    )]
+    fn round_robin_arbiter_zero_requestors_error() {
+        let c = Context::new();
+
+        let _ = components::round_robin_arbiter(&c, "Arbiter", 0);
+    }
+
+    #[test]
+    fn port_widths() {
+        for &num_requestors in &[1, 2, 5, 128] {
+            let c = Context::new();
+
+            let priority = components::priority_arbiter(&c, "Priority", num_requestors);
+            let round_robin = components::round_robin_arbiter(&c, "RoundRobin", num_requestors);
+
+            for m in &[priority, round_robin] {
+                assert_eq!(m.inputs()["requests"].bit_width(), num_requestors);
+                assert_eq!(m.outputs()["grant"].bit_width(), num_requestors);
+                assert_eq!(m.outputs()["grant_valid"].bit_width(), 1);
+            }
+            assert_eq!(round_robin.registers().len(), 1);
+            assert_eq!(round_robin.registers()[0].bit_width(), num_requestors);
+        }
+    }
+}
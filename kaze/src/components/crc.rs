@@ -0,0 +1,363 @@
+use crate::graph::*;
+
+/// Describes a cyclic redundancy check (CRC) algorithm, using the same parameters as the [Catalogue of parametrised CRC algorithms](https://reveng.sourceforge.io/crc-catalogue/).
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// // CRC-16/ARC
+/// let spec = components::CrcSpec::new(16, 0x8005)
+///     .reflect_input(true)
+///     .reflect_output(true);
+/// assert_eq!(spec.checksum(b"123456789"), 0xbb3d);
+///
+/// assert_eq!(components::CrcSpec::crc32().checksum(b"123456789"), 0xcbf43926);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CrcSpec {
+    bit_width: u32,
+    polynomial: u128,
+    init: u128,
+    final_xor: u128,
+    reflect_input: bool,
+    reflect_output: bool,
+}
+
+impl CrcSpec {
+    /// Creates a new `CrcSpec` for a CRC with `bit_width` bits and the given generator `polynomial` (in normal representation, without the implicit highest term), with an initial value and final XOR value of `0`, and without any reflection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`], or if `polynomial` doesn't fit in `bit_width` bits.
+    ///
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ../constant.MAX_SIGNAL_BIT_WIDTH.html
+    /// [`MIN_SIGNAL_BIT_WIDTH`]: ../constant.MIN_SIGNAL_BIT_WIDTH.html
+    pub fn new(bit_width: u32, polynomial: u128) -> CrcSpec {
+        if !(MIN_SIGNAL_BIT_WIDTH..=MAX_SIGNAL_BIT_WIDTH).contains(&bit_width) {
+            panic!(
+                "Cannot create a CRC with {} bit(s). CRCs must have between {} and {} bits.",
+                bit_width, MIN_SIGNAL_BIT_WIDTH, MAX_SIGNAL_BIT_WIDTH
+            );
+        }
+        let spec = CrcSpec {
+            bit_width,
+            polynomial: 0,
+            init: 0,
+            final_xor: 0,
+            reflect_input: false,
+            reflect_output: false,
+        };
+        spec.check_value("polynomial", polynomial);
+        CrcSpec { polynomial, ..spec }
+    }
+
+    /// Returns a `CrcSpec` for CRC-32 (also known as CRC-32/ISO-HDLC), as used by Ethernet, zlib, and PNG.
+    pub fn crc32() -> CrcSpec {
+        CrcSpec::new(32, 0x04c1_1db7)
+            .init(0xffff_ffff)
+            .final_xor(0xffff_ffff)
+            .reflect_input(true)
+            .reflect_output(true)
+    }
+
+    /// Returns a `CrcSpec` for CRC-16-CCITT (also known as CRC-16/IBM-3740 or CRC-16/CCITT-FALSE).
+    pub fn crc16_ccitt() -> CrcSpec {
+        CrcSpec::new(16, 0x1021).init(0xffff)
+    }
+
+    /// Returns a `CrcSpec` for CRC-8 (also known as CRC-8/SMBUS).
+    pub fn crc8() -> CrcSpec {
+        CrcSpec::new(8, 0x07)
+    }
+
+    /// Specifies the value the CRC is initialized to before any data is processed, and returns the resulting `CrcSpec`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `init` doesn't fit in this CRC's bit width.
+    pub fn init(self, init: u128) -> CrcSpec {
+        self.check_value("initial value", init);
+        CrcSpec { init, ..self }
+    }
+
+    /// Specifies the value the CRC is XOR'd with after any output reflection, and returns the resulting `CrcSpec`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `final_xor` doesn't fit in this CRC's bit width.
+    pub fn final_xor(self, final_xor: u128) -> CrcSpec {
+        self.check_value("final XOR value", final_xor);
+        CrcSpec { final_xor, ..self }
+    }
+
+    /// Specifies whether input bits are processed least significant bit first (`true`) or most significant bit first (`false`, the default), and returns the resulting `CrcSpec`.
+    pub fn reflect_input(self, reflect_input: bool) -> CrcSpec {
+        CrcSpec {
+            reflect_input,
+            ..self
+        }
+    }
+
+    /// Specifies whether the CRC's bits are reversed before the final XOR (`true`) or not (`false`, the default), and returns the resulting `CrcSpec`.
+    pub fn reflect_output(self, reflect_output: bool) -> CrcSpec {
+        CrcSpec {
+            reflect_output,
+            ..self
+        }
+    }
+
+    /// Returns the number of bits in this CRC.
+    pub fn bit_width(&self) -> u32 {
+        self.bit_width
+    }
+
+    /// Returns this CRC's generator polynomial.
+    pub fn polynomial(&self) -> u128 {
+        self.polynomial
+    }
+
+    /// Computes this CRC over `data` in software, processing each byte in order.
+    ///
+    /// This is useful as a reference model when testing hardware generated by [`crc`].
+    ///
+    /// [`crc`]: ./fn.crc.html
+    pub fn checksum(&self, data: &[u8]) -> u128 {
+        let mut state = self.init;
+        for &byte in data {
+            for index in self.bit_order(8) {
+                state = self.step(state, (byte >> index) & 1 != 0);
+            }
+        }
+        self.finalize(state)
+    }
+
+    fn check_value(&self, description: &str, value: u128) {
+        if value & !mask(self.bit_width) != 0 {
+            panic!(
+                "Cannot use {:#x} as the {} of a CRC with {} bit(s), as it doesn't fit in {} bit(s).",
+                value, description, self.bit_width, self.bit_width
+            );
+        }
+    }
+
+    // The indices of the bits of a `bit_width`-bit input word, in the order they're processed
+    fn bit_order(&self, bit_width: u32) -> Vec<u32> {
+        if self.reflect_input {
+            (0..bit_width).collect()
+        } else {
+            (0..bit_width).rev().collect()
+        }
+    }
+
+    fn step(&self, state: u128, bit: bool) -> u128 {
+        let feedback = ((state >> (self.bit_width - 1)) & 1 != 0) ^ bit;
+        let state = (state << 1) & mask(self.bit_width);
+        if feedback {
+            state ^ self.polynomial
+        } else {
+            state
+        }
+    }
+
+    fn finalize(&self, state: u128) -> u128 {
+        let state = if self.reflect_output {
+            state.reverse_bits() >> (128 - self.bit_width)
+        } else {
+            state
+        };
+        state ^ self.final_xor
+    }
+}
+
+/// Creates a new [`Module`] called `name` in the given [`Context`] that computes the CRC described by `spec` over a `data_bit_width`-bit data word per cycle.
+///
+/// The CRC's next state is a function of its current state and the data word that's derived when the module is created, by symbolically simulating the CRC's linear feedback shift register for each input bit, and is implemented as a matrix of XORs.
+///
+/// The generated module has the following ports:
+///
+/// | Port    | Direction | Bit width          | Description |
+/// |---------|-----------|--------------------|-------------|
+/// | `data`  | input     | `data_bit_width`   | The data word to process. |
+/// | `valid` | input     | 1                  | Requests that `data` is processed on this cycle. |
+/// | `clear` | input     | 1                  | Requests that the CRC is reset to its initial value on this cycle. If `valid` is also high, `data` is processed starting from the initial value, so that back-to-back messages don't need an idle cycle in between. |
+/// | `crc`   | output    | `spec.bit_width()` | The CRC of the data processed since the last `clear` (or reset), with any output reflection and final XOR applied. |
+///
+/// Like the bytes passed to [`CrcSpec::checksum`], the bits of each data word are processed most significant bit first, or least significant bit first if the CRC reflects its input.
+/// This means that a data word containing several bytes is equivalent to those bytes in big-endian order (for CRCs that don't reflect their input) or little-endian order (for CRCs that do).
+///
+/// # Panics
+///
+/// Panics if `data_bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`].
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// // Processes 4 bytes of an Ethernet frame per cycle, with the first byte in the least significant bits
+/// let crc = components::crc(&c, "EthernetCrc", &components::CrcSpec::crc32(), 32);
+///
+/// assert_eq!(crc.outputs()["crc"].bit_width(), 32);
+/// ```
+///
+/// [`Context`]: ../struct.Context.html
+/// [`CrcSpec::checksum`]: ./struct.CrcSpec.html#method.checksum
+/// [`MAX_SIGNAL_BIT_WIDTH`]: ../constant.MAX_SIGNAL_BIT_WIDTH.html
+/// [`MIN_SIGNAL_BIT_WIDTH`]: ../constant.MIN_SIGNAL_BIT_WIDTH.html
+/// [`Module`]: ../struct.Module.html
+pub fn crc<'a, S: Into<String>>(
+    c: &'a Context<'a>,
+    name: S,
+    spec: &CrcSpec,
+    data_bit_width: u32,
+) -> &'a Module<'a> {
+    let name = name.into();
+    if !(MIN_SIGNAL_BIT_WIDTH..=MAX_SIGNAL_BIT_WIDTH).contains(&data_bit_width) {
+        panic!(
+            "Cannot create a CRC called \"{}\" with {} data bit(s). CRCs must process between {} and {} data bits per cycle.",
+            name, data_bit_width, MIN_SIGNAL_BIT_WIDTH, MAX_SIGNAL_BIT_WIDTH
+        );
+    }
+
+    let m = c.module(name);
+
+    let data = m.input("data", data_bit_width);
+    let valid = m.input("valid", 1);
+    let clear = m.input("clear", 1);
+
+    let state = m.reg("state", spec.bit_width);
+    state.default_value(spec.init);
+
+    let base = clear.mux(m.lit(spec.init, spec.bit_width), state.value);
+
+    // Each state bit is tracked as the set of current state bits and data bits that are XOR'd together to produce it
+    let mut terms = (0..spec.bit_width)
+        .map(|index| (1u128 << index, 0u128))
+        .collect::<Vec<_>>();
+    for data_index in spec.bit_order(data_bit_width) {
+        let (msb_state_terms, msb_data_terms) = terms[(spec.bit_width - 1) as usize];
+        let feedback = (msb_state_terms, msb_data_terms ^ (1 << data_index));
+        let mut next_terms = Vec::with_capacity(terms.len());
+        for index in 0..spec.bit_width {
+            let mut next = if index > 0 {
+                terms[(index - 1) as usize]
+            } else {
+                (0, 0)
+            };
+            if (spec.polynomial >> index) & 1 != 0 {
+                next = (next.0 ^ feedback.0, next.1 ^ feedback.1);
+            }
+            next_terms.push(next);
+        }
+        terms = next_terms;
+    }
+
+    let mut next_bits = Vec::with_capacity(terms.len());
+    for (state_terms, data_terms) in terms {
+        let mut inputs = Vec::new();
+        for (source, source_terms) in [(base, state_terms), (data, data_terms)] {
+            for index in 0..source.bit_width() {
+                if (source_terms >> index) & 1 != 0 {
+                    inputs.push(source.bit(index));
+                }
+            }
+        }
+        next_bits.push(xor_tree(m, inputs));
+    }
+    let next = m.pack(&next_bits);
+
+    state.drive_next(valid.mux(next, base));
+
+    let value = if spec.reflect_output {
+        state.value.reverse_chunks(1)
+    } else {
+        state.value
+    };
+    m.output("crc", value ^ m.lit(spec.final_xor, spec.bit_width));
+
+    m
+}
+
+// XORs `inputs` together pairwise, to keep the logic depth logarithmic in the number of inputs
+fn xor_tree<'a>(m: &'a Module<'a>, mut inputs: Vec<&'a Signal<'a>>) -> &'a Signal<'a> {
+    if inputs.is_empty() {
+        return m.low();
+    }
+    while inputs.len() > 1 {
+        inputs = inputs
+            .chunks(2)
+            .map(|pair| match *pair {
+                [a, b] => a ^ b,
+                [a] => a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    inputs[0]
+}
+
+fn mask(bit_width: u32) -> u128 {
+    if bit_width >= 128 {
+        !0
+    } else {
+        (1 << bit_width) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn checksum() {
+        for (spec, check) in [
+            (components::CrcSpec::crc32(), 0xcbf4_3926),
+            (components::CrcSpec::crc16_ccitt(), 0x29b1),
+            (components::CrcSpec::crc8(), 0xf4),
+            // CRC-16/XMODEM
+            (components::CrcSpec::new(16, 0x1021), 0x31c3),
+            // CRC-5/USB
+            (
+                components::CrcSpec::new(5, 0x05)
+                    .init(0x1f)
+                    .final_xor(0x1f)
+                    .reflect_input(true)
+                    .reflect_output(true),
+                0x19,
+            ),
+            // CRC-64/XZ
+            (
+                components::CrcSpec::new(64, 0x42f0_e1eb_a9ea_3693)
+                    .init(0xffff_ffff_ffff_ffff)
+                    .final_xor(0xffff_ffff_ffff_ffff)
+                    .reflect_input(true)
+                    .reflect_output(true),
+                0x995d_c9bb_df19_39fa,
+            ),
+        ] {
+            assert_eq!(spec.checksum(b"123456789"), check, "{:?}", spec);
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot use 0x107 as the polynomial of a CRC with 8 bit(s), as it doesn't fit in 8 bit(s)."
+    )]
+    fn polynomial_too_wide_error() {
+        let _ = components::CrcSpec::new(8, 0x107);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create a CRC called \"Crc\" with 0 data bit(s). CRCs must process between 1 and 128 data bits per cycle."
+    )]
+    fn data_bit_width_lt_min_error() {
+        let c = Context::new();
+
+        let _ = components::crc(&c, "Crc", &components::CrcSpec::crc8(), 0);
+    }
+}
@@ -0,0 +1,682 @@
+use crate::graph::*;
+use crate::json_netlist::quote;
+
+use std::io::{Result, Write};
+
+/// Describes how software (through the bus interface of a [`csr_bank`]) and hardware (through its per-field ports) can access a field of a [`CsrRegisterSpec`].
+///
+/// [`csr_bank`]: ./fn.csr_bank.html
+/// [`CsrRegisterSpec`]: ./struct.CsrRegisterSpec.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CsrAccess {
+    /// The field's value is provided by hardware through an input port on every cycle, and can't be written by software.
+    ///
+    /// Read-only fields don't hold any state, so their bits in the register's reset value must be `0`.
+    ReadOnly,
+    /// The field holds a value that's written by software, and is available to hardware through an output port.
+    ReadWrite,
+    /// Like [`ReadWrite`](#variant.ReadWrite), but hardware can also update the field's value through a pair of input ports.
+    ///
+    /// If hardware and software both write the field on the same cycle, the hardware update takes priority.
+    ReadWriteHardwareUpdate,
+    /// The field holds a value whose bits are set by hardware through an input port, and cleared by software writing `1` to them (writing `0` has no effect).
+    ///
+    /// If hardware sets a bit on the same cycle that software clears it, the bit stays set, so events aren't lost.
+    WriteOneToClear,
+}
+
+impl CsrAccess {
+    fn short_name(&self) -> &'static str {
+        match self {
+            CsrAccess::ReadOnly => "RO",
+            CsrAccess::ReadWrite => "RW",
+            CsrAccess::ReadWriteHardwareUpdate => "RW/HW",
+            CsrAccess::WriteOneToClear => "W1C",
+        }
+    }
+
+    fn holds_state(&self) -> bool {
+        *self != CsrAccess::ReadOnly
+    }
+}
+
+/// Describes a field of a [`CsrRegisterSpec`]: a contiguous range of bits with a given [`CsrAccess`].
+///
+/// [`CsrAccess`]: ./enum.CsrAccess.html
+/// [`CsrRegisterSpec`]: ./struct.CsrRegisterSpec.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CsrFieldSpec {
+    name: String,
+    lsb: u32,
+    bit_width: u32,
+    access: CsrAccess,
+}
+
+impl CsrFieldSpec {
+    /// Returns this field's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the index of this field's least significant bit within its register.
+    pub fn lsb(&self) -> u32 {
+        self.lsb
+    }
+
+    /// Returns the number of bits in this field.
+    pub fn bit_width(&self) -> u32 {
+        self.bit_width
+    }
+
+    /// Returns how this field can be accessed.
+    pub fn access(&self) -> CsrAccess {
+        self.access
+    }
+
+    fn msb(&self) -> u32 {
+        self.lsb + self.bit_width - 1
+    }
+}
+
+/// Describes a register of a [`CsrBankSpec`]: its name, bus address, fields, and reset value.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let status = components::CsrRegisterSpec::new("status", 0x4)
+///     .field("busy", 0, 1, components::CsrAccess::ReadOnly)
+///     .field("irq", 8, 4, components::CsrAccess::WriteOneToClear);
+///
+/// assert_eq!(status.fields().len(), 2);
+/// assert_eq!(status.fields()[1].lsb(), 8);
+/// ```
+///
+/// [`CsrBankSpec`]: ./struct.CsrBankSpec.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CsrRegisterSpec {
+    name: String,
+    address: u32,
+    fields: Vec<CsrFieldSpec>,
+    reset_value: u128,
+}
+
+impl CsrRegisterSpec {
+    /// Creates a new `CsrRegisterSpec` called `name` at bus address `address`, without any fields and with a reset value of `0`.
+    pub fn new<S: Into<String>>(name: S, address: u32) -> CsrRegisterSpec {
+        CsrRegisterSpec {
+            name: name.into(),
+            address,
+            fields: Vec::new(),
+            reset_value: 0,
+        }
+    }
+
+    /// Adds a field called `name` occupying `bit_width` bits starting at bit `lsb` with the given `access`, and returns the resulting `CsrRegisterSpec`.
+    ///
+    /// Bits that aren't covered by any field read as `0` and ignore writes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `CsrRegisterSpec` already has a field called `name`, if `bit_width` is `0`, or if the field overlaps another field.
+    pub fn field<S: Into<String>>(
+        mut self,
+        name: S,
+        lsb: u32,
+        bit_width: u32,
+        access: CsrAccess,
+    ) -> CsrRegisterSpec {
+        let name = name.into();
+        if self.fields.iter().any(|field| field.name == name) {
+            panic!(
+                "A field called \"{}\" has already been specified for register \"{}\".",
+                name, self.name
+            );
+        }
+        if bit_width == 0 {
+            panic!(
+                "Cannot create field \"{}\" in register \"{}\" with 0 bits. Fields must be at least 1 bit wide.",
+                name, self.name
+            );
+        }
+        let field = CsrFieldSpec {
+            name,
+            lsb,
+            bit_width,
+            access,
+        };
+        if let Some(other) = self
+            .fields
+            .iter()
+            .find(|other| field.lsb <= other.msb() && other.lsb <= field.msb())
+        {
+            panic!(
+                "Field \"{}\" (bits {}:{}) in register \"{}\" overlaps field \"{}\" (bits {}:{}).",
+                field.name,
+                field.msb(),
+                field.lsb,
+                self.name,
+                other.name,
+                other.msb(),
+                other.lsb
+            );
+        }
+        self.fields.push(field);
+        self
+    }
+
+    /// Specifies the value this register's fields hold after reset, and returns the resulting `CsrRegisterSpec`.
+    ///
+    /// Only bits belonging to fields that hold state (that is, fields that aren't [`CsrAccess::ReadOnly`]) may be set; this is checked when the register is added to a [`CsrBankSpec`].
+    ///
+    /// [`CsrAccess::ReadOnly`]: ./enum.CsrAccess.html#variant.ReadOnly
+    /// [`CsrBankSpec`]: ./struct.CsrBankSpec.html
+    pub fn reset_value(mut self, reset_value: u128) -> CsrRegisterSpec {
+        self.reset_value = reset_value;
+        self
+    }
+
+    /// Returns this register's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns this register's bus address.
+    pub fn address(&self) -> u32 {
+        self.address
+    }
+
+    /// Returns this register's fields, in the order they were specified.
+    pub fn fields(&self) -> &[CsrFieldSpec] {
+        &self.fields
+    }
+
+    fn field_reset_value(&self, field: &CsrFieldSpec) -> u128 {
+        (self.reset_value >> field.lsb) & mask(field.bit_width)
+    }
+}
+
+/// Describes the registers of a [`csr_bank`], and the bit widths of its bus interface.
+///
+/// A `CsrBankSpec` can also describe the register map it specifies as [Markdown](#method.write_markdown) or [JSON](#method.write_json), for documentation or for generating software headers.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let spec = components::CsrBankSpec::new(8, 32)
+///     .register(
+///         components::CsrRegisterSpec::new("ctrl", 0x0)
+///             .field("enable", 0, 1, components::CsrAccess::ReadWrite)
+///             .field("mode", 4, 2, components::CsrAccess::ReadWrite)
+///             .reset_value(0x20),
+///     )
+///     .register(
+///         components::CsrRegisterSpec::new("status", 0x4)
+///             .field("busy", 0, 1, components::CsrAccess::ReadOnly),
+///     );
+///
+/// let mut w = Vec::new();
+/// spec.write_markdown(&mut w).unwrap();
+/// let markdown = String::from_utf8(w).unwrap();
+/// assert!(markdown.contains("| 5:4 | mode | RW | 0x2 |"));
+/// ```
+///
+/// [`csr_bank`]: ./fn.csr_bank.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CsrBankSpec {
+    address_bit_width: u32,
+    data_bit_width: u32,
+    registers: Vec<CsrRegisterSpec>,
+}
+
+impl CsrBankSpec {
+    /// Creates a new `CsrBankSpec` without any registers, whose bus has `address_bit_width` address bits and `data_bit_width` data bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `address_bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than 32, or if `data_bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`].
+    ///
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ../constant.MAX_SIGNAL_BIT_WIDTH.html
+    /// [`MIN_SIGNAL_BIT_WIDTH`]: ../constant.MIN_SIGNAL_BIT_WIDTH.html
+    pub fn new(address_bit_width: u32, data_bit_width: u32) -> CsrBankSpec {
+        if !(MIN_SIGNAL_BIT_WIDTH..=32).contains(&address_bit_width) {
+            panic!(
+                "Cannot create a register bank with {} address bit(s). Register banks must have between {} and 32 address bits.",
+                address_bit_width, MIN_SIGNAL_BIT_WIDTH
+            );
+        }
+        if !(MIN_SIGNAL_BIT_WIDTH..=MAX_SIGNAL_BIT_WIDTH).contains(&data_bit_width) {
+            panic!(
+                "Cannot create a register bank with {} data bit(s). Register banks must have between {} and {} data bits.",
+                data_bit_width, MIN_SIGNAL_BIT_WIDTH, MAX_SIGNAL_BIT_WIDTH
+            );
+        }
+        CsrBankSpec {
+            address_bit_width,
+            data_bit_width,
+            registers: Vec::new(),
+        }
+    }
+
+    /// Adds `register` after the registers already in this `CsrBankSpec`, and returns the resulting `CsrBankSpec`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `CsrBankSpec` already has a register with the same name or address as `register`, if `register`'s address doesn't fit in this bank's address bits, if any of `register`'s fields don't fit in this bank's data bits, or if `register`'s reset value sets any bits that don't belong to a field that holds state.
+    pub fn register(mut self, register: CsrRegisterSpec) -> CsrBankSpec {
+        if let Some(other) = self
+            .registers
+            .iter()
+            .find(|other| other.name == register.name || other.address == register.address)
+        {
+            panic!(
+                "Cannot add register \"{}\" at address {:#x}, as register \"{}\" at address {:#x} already has the same name or address.",
+                register.name, register.address, other.name, other.address
+            );
+        }
+        if u128::from(register.address) > mask(self.address_bit_width) {
+            panic!(
+                "Cannot add register \"{}\" at address {:#x}, as this address doesn't fit in the register bank's {} address bit(s).",
+                register.name, register.address, self.address_bit_width
+            );
+        }
+        for field in register.fields.iter() {
+            if field.msb() >= self.data_bit_width {
+                panic!(
+                    "Field \"{}\" (bits {}:{}) in register \"{}\" doesn't fit in the register bank's {} data bit(s).",
+                    field.name,
+                    field.msb(),
+                    field.lsb,
+                    register.name,
+                    self.data_bit_width
+                );
+            }
+        }
+        let state_bits = register
+            .fields
+            .iter()
+            .filter(|field| field.access.holds_state())
+            .fold(0, |acc, field| acc | (mask(field.bit_width) << field.lsb));
+        if register.reset_value & !state_bits != 0 {
+            panic!(
+                "Register \"{}\" has a reset value of {:#x}, which sets bits that don't belong to any field that holds state.",
+                register.name, register.reset_value
+            );
+        }
+        self.registers.push(register);
+        self
+    }
+
+    /// Returns the number of address bits in this bank's bus interface.
+    pub fn address_bit_width(&self) -> u32 {
+        self.address_bit_width
+    }
+
+    /// Returns the number of data bits in this bank's bus interface, which is also the bit width of each of its registers.
+    pub fn data_bit_width(&self) -> u32 {
+        self.data_bit_width
+    }
+
+    /// Returns this bank's registers, in the order they were specified.
+    pub fn registers(&self) -> &[CsrRegisterSpec] {
+        &self.registers
+    }
+
+    /// Writes a Markdown description of this bank's register map to `w`, with a section for each register that lists its fields, their bits, access, and reset values.
+    pub fn write_markdown<W: Write>(&self, mut w: W) -> Result<()> {
+        let hex_digits = self.data_bit_width.div_ceil(4) as usize;
+        for (index, register) in self.registers.iter().enumerate() {
+            if index > 0 {
+                writeln!(w)?;
+            }
+            writeln!(w, "## {} ({:#x})", register.name, register.address)?;
+            writeln!(w)?;
+            writeln!(
+                w,
+                "Reset value: `{:#0width$x}`",
+                register.reset_value,
+                width = hex_digits + 2
+            )?;
+            writeln!(w)?;
+            writeln!(w, "| Bits | Field | Access | Reset value |")?;
+            writeln!(w, "|------|-------|--------|-------------|")?;
+            let mut fields = register.fields.iter().collect::<Vec<_>>();
+            fields.sort_by_key(|field| field.lsb);
+            for field in fields {
+                let bits = if field.bit_width == 1 {
+                    format!("{}", field.lsb)
+                } else {
+                    format!("{}:{}", field.msb(), field.lsb)
+                };
+                let reset_value = if field.access.holds_state() {
+                    format!("{:#x}", register.field_reset_value(field))
+                } else {
+                    String::from("-")
+                };
+                writeln!(
+                    w,
+                    "| {} | {} | {} | {} |",
+                    bits,
+                    field.name,
+                    field.access.short_name(),
+                    reset_value
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a JSON description of this bank's register map to `w`.
+    ///
+    /// The description is an object with `address_bit_width`, `data_bit_width`, and `registers` properties, where each register has `name`, `address`, `reset_value`, and `fields` properties, and each field has `name`, `lsb`, `bit_width`, `access` (`"RO"`, `"RW"`, `"RW/HW"`, or `"W1C"`), and `reset_value` properties.
+    /// Reset values are represented as hexadecimal strings, since they can be wider than JSON numbers can represent exactly.
+    pub fn write_json<W: Write>(&self, mut w: W) -> Result<()> {
+        writeln!(w, "{{")?;
+        writeln!(w, "  \"address_bit_width\": {},", self.address_bit_width)?;
+        writeln!(w, "  \"data_bit_width\": {},", self.data_bit_width)?;
+        writeln!(w, "  \"registers\": [")?;
+        for (register_index, register) in self.registers.iter().enumerate() {
+            writeln!(w, "    {{")?;
+            writeln!(w, "      \"name\": {},", quote(&register.name))?;
+            writeln!(w, "      \"address\": {},", register.address)?;
+            writeln!(w, "      \"reset_value\": \"{:#x}\",", register.reset_value)?;
+            writeln!(w, "      \"fields\": [")?;
+            for (field_index, field) in register.fields.iter().enumerate() {
+                writeln!(
+                    w,
+                    "        {{ \"name\": {}, \"lsb\": {}, \"bit_width\": {}, \"access\": \"{}\", \"reset_value\": \"{:#x}\" }}{}",
+                    quote(&field.name),
+                    field.lsb,
+                    field.bit_width,
+                    field.access.short_name(),
+                    register.field_reset_value(field),
+                    if field_index < register.fields.len() - 1 { "," } else { "" }
+                )?;
+            }
+            writeln!(w, "      ]")?;
+            writeln!(
+                w,
+                "    }}{}",
+                if register_index < self.registers.len() - 1 {
+                    ","
+                } else {
+                    ""
+                }
+            )?;
+        }
+        writeln!(w, "  ]")?;
+        writeln!(w, "}}")
+    }
+}
+
+/// Creates a new [`Module`] called `name` in the given [`Context`] that implements the memory-mapped register bank described by `spec`.
+///
+/// The generated module has the following bus ports:
+///
+/// | Port           | Direction | Bit width                  | Description |
+/// |----------------|-----------|----------------------------|-------------|
+/// | `address`      | input     | `spec.address_bit_width()` | The address of the register to read or write. |
+/// | `write_data`   | input     | `spec.data_bit_width()`    | The value to write to the addressed register. |
+/// | `write_enable` | input     | 1                          | Requests that `write_data` is written to the addressed register on this cycle. |
+/// | `read_data`    | output    | `spec.data_bit_width()`    | The addressed register's current value, or `0` if no register has that address. |
+///
+/// Reads are combinational, and writes take effect on the next cycle.
+/// Bits that don't belong to any field read as `0`, and writes to them (or to addresses without a register) are ignored.
+///
+/// In addition, each field gets ports named after its register and itself (`{register}_{field}`), depending on its [`CsrAccess`]:
+///
+/// | Access                    | Ports |
+/// |---------------------------|-------|
+/// | `ReadOnly`                | Input `{register}_{field}`, which provides the field's value. |
+/// | `ReadWrite`               | Output `{register}_{field}`, which holds the field's value. |
+/// | `ReadWriteHardwareUpdate` | Output `{register}_{field}`, as for `ReadWrite`, and inputs `{register}_{field}_update` (1 bit) and `{register}_{field}_update_data`, which replace the field's value with `{register}_{field}_update_data` on the next cycle while `{register}_{field}_update` is high. |
+/// | `WriteOneToClear`         | Output `{register}_{field}`, which holds the field's value, and input `{register}_{field}_set`, whose high bits are set in the field's value on the next cycle. |
+///
+/// All fields that hold state are initialized to their bits of their register's reset value.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let spec = components::CsrBankSpec::new(4, 16)
+///     .register(
+///         components::CsrRegisterSpec::new("ctrl", 0x0)
+///             .field("enable", 0, 1, components::CsrAccess::ReadWrite)
+///             .reset_value(1),
+///     )
+///     .register(
+///         components::CsrRegisterSpec::new("irq", 0x1)
+///             .field("pending", 0, 4, components::CsrAccess::WriteOneToClear),
+///     );
+/// let csrs = components::csr_bank(&c, "Csrs", &spec);
+///
+/// assert_eq!(csrs.inputs()["irq_pending_set"].bit_width(), 4);
+/// assert_eq!(csrs.outputs()["ctrl_enable"].bit_width(), 1);
+/// assert_eq!(csrs.outputs()["read_data"].bit_width(), 16);
+/// ```
+///
+/// [`Context`]: ../struct.Context.html
+/// [`CsrAccess`]: ./enum.CsrAccess.html
+/// [`Module`]: ../struct.Module.html
+pub fn csr_bank<'a, S: Into<String>>(
+    c: &'a Context<'a>,
+    name: S,
+    spec: &CsrBankSpec,
+) -> &'a Module<'a> {
+    let m = c.module(name);
+
+    let address = m.input("address", spec.address_bit_width);
+    let write_data = m.input("write_data", spec.data_bit_width);
+    let write_enable = m.input("write_enable", 1);
+
+    let mut read_data = m.lit(0u32, spec.data_bit_width);
+    for register in spec.registers.iter() {
+        let selected = address.eq(m.lit(register.address, spec.address_bit_width));
+        let software_write = write_enable & selected;
+
+        let mut fields = register.fields.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|field| field.lsb);
+        let mut word: Option<&'a Signal<'a>> = None;
+        let mut next_bit = 0;
+        for field in fields {
+            let port_name = format!("{}_{}", register.name, field.name);
+            let software_data = write_data.bits(field.msb(), field.lsb);
+
+            let value = match field.access {
+                CsrAccess::ReadOnly => m.input(port_name, field.bit_width),
+                _ => {
+                    let reg = m.reg(port_name.clone(), field.bit_width);
+                    reg.default_value(register.field_reset_value(field));
+                    let next = match field.access {
+                        CsrAccess::ReadWrite => software_write.mux(software_data, reg.value),
+                        CsrAccess::ReadWriteHardwareUpdate => {
+                            let update = m.input(format!("{}_update", port_name), 1);
+                            let update_data =
+                                m.input(format!("{}_update_data", port_name), field.bit_width);
+                            update.mux(update_data, software_write.mux(software_data, reg.value))
+                        }
+                        CsrAccess::WriteOneToClear => {
+                            let set = m.input(format!("{}_set", port_name), field.bit_width);
+                            software_write.mux(reg.value & !software_data, reg.value) | set
+                        }
+                        CsrAccess::ReadOnly => unreachable!(),
+                    };
+                    reg.drive_next(next);
+                    m.output(port_name, reg.value);
+                    reg.value
+                }
+            };
+
+            if field.lsb > next_bit {
+                word = Some(prepend(word, m.lit(0u32, field.lsb - next_bit)));
+            }
+            word = Some(prepend(word, value));
+            next_bit = field.lsb + field.bit_width;
+        }
+        if next_bit < spec.data_bit_width {
+            word = Some(prepend(word, m.lit(0u32, spec.data_bit_width - next_bit)));
+        }
+
+        read_data = selected.mux(word.unwrap(), read_data);
+    }
+    m.output("read_data", read_data);
+
+    m
+}
+
+fn prepend<'a>(word: Option<&'a Signal<'a>>, high_bits: &'a Signal<'a>) -> &'a Signal<'a> {
+    match word {
+        Some(word) => high_bits.concat(word),
+        None => high_bits,
+    }
+}
+
+fn mask(bit_width: u32) -> u128 {
+    if bit_width >= 128 {
+        !0
+    } else {
+        (1 << bit_width) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    #[should_panic(
+        expected = "Field \"b\" (bits 4:3) in register \"r\" overlaps field \"a\" (bits 3:0)."
+    )]
+    fn overlapping_fields_error() {
+        let _ = components::CsrRegisterSpec::new("r", 0)
+            .field("a", 0, 4, components::CsrAccess::ReadWrite)
+            .field("b", 3, 2, components::CsrAccess::ReadWrite);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot add register \"b\" at address 0x4, as register \"a\" at address 0x4 already has the same name or address."
+    )]
+    fn duplicate_address_error() {
+        let _ = components::CsrBankSpec::new(8, 32)
+            .register(components::CsrRegisterSpec::new("a", 4))
+            .register(components::CsrRegisterSpec::new("b", 4));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot add register \"a\" at address 0x10, as this address doesn't fit in the register bank's 4 address bit(s)."
+    )]
+    fn address_out_of_range_error() {
+        let _ =
+            components::CsrBankSpec::new(4, 32).register(components::CsrRegisterSpec::new("a", 16));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Field \"f\" (bits 8:1) in register \"a\" doesn't fit in the register bank's 8 data bit(s)."
+    )]
+    fn field_out_of_range_error() {
+        let _ = components::CsrBankSpec::new(4, 8).register(
+            components::CsrRegisterSpec::new("a", 0).field(
+                "f",
+                1,
+                8,
+                components::CsrAccess::ReadWrite,
+            ),
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Register \"a\" has a reset value of 0x3, which sets bits that don't belong to any field that holds state."
+    )]
+    fn read_only_reset_value_error() {
+        let _ = components::CsrBankSpec::new(4, 8).register(
+            components::CsrRegisterSpec::new("a", 0)
+                .field("rw", 0, 1, components::CsrAccess::ReadWrite)
+                .field("ro", 1, 1, components::CsrAccess::ReadOnly)
+                .reset_value(0b11),
+        );
+    }
+
+    #[test]
+    fn ports() {
+        let c = Context::new();
+
+        let spec = components::CsrBankSpec::new(6, 32).register(
+            components::CsrRegisterSpec::new("r", 0)
+                .field("ro", 0, 3, components::CsrAccess::ReadOnly)
+                .field("rw", 3, 4, components::CsrAccess::ReadWrite)
+                .field("hw", 8, 5, components::CsrAccess::ReadWriteHardwareUpdate)
+                .field("w1c", 16, 6, components::CsrAccess::WriteOneToClear),
+        );
+        let m = components::csr_bank(&c, "Csrs", &spec);
+
+        let inputs = m
+            .inputs()
+            .iter()
+            .map(|(name, signal)| (name.clone(), signal.bit_width()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            inputs,
+            vec![
+                ("address".into(), 6),
+                ("r_hw_update".into(), 1),
+                ("r_hw_update_data".into(), 5),
+                ("r_ro".into(), 3),
+                ("r_w1c_set".into(), 6),
+                ("write_data".into(), 32),
+                ("write_enable".into(), 1),
+            ]
+        );
+        let outputs = m
+            .outputs()
+            .iter()
+            .map(|(name, signal)| (name.clone(), signal.bit_width()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outputs,
+            vec![
+                ("r_hw".into(), 5),
+                ("r_rw".into(), 4),
+                ("r_w1c".into(), 6),
+                ("read_data".into(), 32),
+            ]
+        );
+    }
+
+    #[test]
+    fn write_json() {
+        let spec = components::CsrBankSpec::new(8, 16).register(
+            components::CsrRegisterSpec::new("ctrl", 2)
+                .field("mode", 4, 2, components::CsrAccess::ReadWrite)
+                .field("busy", 0, 1, components::CsrAccess::ReadOnly)
+                .reset_value(0x30),
+        );
+
+        let mut w = Vec::new();
+        spec.write_json(&mut w).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&w).unwrap();
+
+        assert_eq!(json["address_bit_width"], 8);
+        assert_eq!(json["data_bit_width"], 16);
+        let register = &json["registers"][0];
+        assert_eq!(register["name"], "ctrl");
+        assert_eq!(register["address"], 2);
+        assert_eq!(register["reset_value"], "0x30");
+        assert_eq!(
+            register["fields"],
+            serde_json::json!([
+                { "name": "mode", "lsb": 4, "bit_width": 2, "access": "RW", "reset_value": "0x3" },
+                { "name": "busy", "lsb": 0, "bit_width": 1, "access": "RO", "reset_value": "0x0" },
+            ])
+        );
+    }
+}
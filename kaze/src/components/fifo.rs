@@ -0,0 +1,403 @@
+use crate::graph::*;
+
+/// Creates a new [`Module`] called `name` in the given [`Context`] that implements a synchronous (single-clock) FIFO holding up to `depth` elements of `element_bit_width` bits each.
+///
+/// Any `depth` of at least 1 is supported; it doesn't need to be a power of two.
+/// Elements are stored in a [`Mem`] with `max(1, ceil(log2(depth)))` address bits, and the read and write pointers wrap explicitly at `depth`.
+///
+/// The generated module has the following ports:
+///
+/// | Port        | Direction | Bit width                    | Description |
+/// |-------------|-----------|------------------------------|-------------|
+/// | `push`      | input     | 1                            | Requests that `push_data` is enqueued on this cycle. |
+/// | `push_data` | input     | `element_bit_width`          | The element to enqueue. |
+/// | `pop`       | input     | 1                            | Requests that the element at the head of the FIFO is dequeued on this cycle. |
+/// | `pop_data`  | output    | `element_bit_width`          | The element at the head of the FIFO (first-word fall-through); only meaningful when `empty` is low. |
+/// | `full`      | output    | 1                            | High when the FIFO holds `depth` elements. |
+/// | `empty`     | output    | 1                            | High when the FIFO holds no elements. |
+/// | `count`     | output    | `32 - depth.leading_zeros()` | The number of elements currently held. |
+///
+/// Requests that can't be serviced are ignored rather than corrupting the FIFO's state: a `pop` while `empty` has no effect, and neither does a `push` while `full`, **unless** `pop` is also asserted on the same cycle.
+/// In that case the head element is dequeued and `push_data` is enqueued in its place, so a full FIFO can sustain one push and one pop per cycle.
+/// A simultaneous `push` and `pop` on an empty FIFO enqueues `push_data` only, since there's nothing to dequeue yet.
+///
+/// Pushed elements become visible on `pop_data` on the cycle after they're accepted.
+///
+/// # Panics
+///
+/// Panics if `depth` is `0`, or if `element_bit_width` is outside the range of legal signal bit widths.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let fifo = components::sync_fifo(&c, "Fifo", 5, 8);
+///
+/// let m = c.module("Top");
+/// let inner = m.instance("fifo", "Fifo");
+/// inner.drive_input("push", m.input("push", 1));
+/// inner.drive_input("push_data", m.input("push_data", 8));
+/// inner.drive_input("pop", m.input("pop", 1));
+/// m.output("pop_data", inner.output("pop_data"));
+/// m.output("empty", inner.output("empty"));
+///
+/// assert_eq!(fifo.outputs()["count"].bit_width(), 3);
+/// ```
+///
+/// [`Context`]: ../struct.Context.html
+/// [`Mem`]: ../struct.Mem.html
+/// [`Module`]: ../struct.Module.html
+#[track_caller]
+pub fn sync_fifo<'a, S: Into<String>>(
+    c: &'a Context<'a>,
+    name: S,
+    depth: u32,
+    element_bit_width: u32,
+) -> &'a Module<'a> {
+    let name = name.into();
+    if depth == 0 {
+        panic!(
+            "Cannot create a FIFO called \"{}\" with a depth of 0. FIFOs must be able to hold at least 1 element.",
+            name
+        );
+    }
+
+    let m = c.module(name);
+
+    let address_bit_width = (32 - (depth - 1).leading_zeros()).max(1);
+    let count_bit_width = 32 - depth.leading_zeros();
+
+    let push = m.input("push", 1);
+    let push_data = m.input("push_data", element_bit_width);
+    let pop = m.input("pop", 1);
+
+    let elements = m.mem("elements", address_bit_width, element_bit_width);
+
+    let read_ptr = m.reg("read_ptr", address_bit_width);
+    read_ptr.default_value(0u32);
+    let write_ptr = m.reg("write_ptr", address_bit_width);
+    write_ptr.default_value(0u32);
+    let count = m.reg("count", count_bit_width);
+    count.default_value(0u32);
+
+    let full = count.value.eq(m.lit(depth, count_bit_width));
+    let empty = count.value.eq(m.lit(0u32, count_bit_width));
+
+    let pop_accepted = pop & !empty;
+    let push_accepted = push & (!full | pop_accepted);
+
+    elements.write_port(write_ptr.value, push_data, push_accepted);
+
+    read_ptr.drive_next(pop_accepted.mux(next_ptr(m, read_ptr.value, depth), read_ptr.value));
+    write_ptr.drive_next(push_accepted.mux(next_ptr(m, write_ptr.value, depth), write_ptr.value));
+
+    let one = m.lit(1u32, count_bit_width);
+    count.drive_next(
+        if_(push_accepted & !pop_accepted, count.value + one)
+            .else_if(pop_accepted & !push_accepted, count.value - one)
+            .else_(count.value),
+    );
+
+    m.output("pop_data", elements.read_port_comb(read_ptr.value));
+    m.output("full", full);
+    m.output("empty", empty);
+    m.output("count", count.value);
+
+    m
+}
+
+/// The two halves of an asynchronous (dual-clock) FIFO created by [`async_fifo`], each of which is a [`Module`] clocked by its own clock domain.
+///
+/// [`async_fifo`]: ./fn.async_fifo.html
+/// [`Module`]: ../struct.Module.html
+pub struct AsyncFifo<'a> {
+    /// The half of the FIFO in the producer's (write) clock domain, which holds the FIFO's storage.
+    pub push: &'a Module<'a>,
+    /// The half of the FIFO in the consumer's (read) clock domain.
+    pub pop: &'a Module<'a>,
+}
+
+/// Creates an asynchronous (dual-clock) FIFO holding up to `depth` elements of `element_bit_width` bits each, and returns its two halves.
+///
+/// kaze assumes that all of a `Module`'s logic is in a single clock domain, so the FIFO is split into two new [`Module`]s in the given [`Context`]: one called `namePush` for the producer's clock domain, and one called `namePop` for the consumer's.
+/// Each half is instantiated in the `Module` for its clock domain, and their crossing ports (marked *crossing* below) are connected to each other, while the clocks and resets of the two domains are connected outside of kaze.
+///
+/// The read and write pointers have one more bit than the FIFO's address, so that a full FIFO can be told apart from an empty one.
+/// Each pointer is passed to the other clock domain Gray-coded (see [`Signal::to_gray`]) straight from a register, and sampled there by a two-stage synchronizer whose [`Register`]s are marked with the `ASYNC_REG = "TRUE"` [attribute] (like [`Module::synchronizer`]), so only one bit can change at a time.
+/// Elements are stored in a [`Mem`] in the push half, which the pop half reads combinationally through `read_address`/`read_data`.
+///
+/// The push half (`namePush`) has the following ports:
+///
+/// | Port            | Direction | Bit width                 | Description |
+/// |-----------------|-----------|---------------------------|-------------|
+/// | `push`          | input     | 1                         | Requests that `push_data` is enqueued on this cycle. |
+/// | `push_data`     | input     | `element_bit_width`       | The element to enqueue. |
+/// | `full`          | output    | 1                         | High when the FIFO can't accept any more elements. |
+/// | `read_ptr_gray` | input     | `log2(depth) + 1`         | *Crossing*: the pop half's `read_ptr_gray` output. |
+/// | `read_address`  | input     | `log2(depth)`             | *Crossing*: the pop half's `read_address` output. |
+/// | `write_ptr_gray`| output    | `log2(depth) + 1`         | *Crossing*: drives the pop half's `write_ptr_gray` input. |
+/// | `read_data`     | output    | `element_bit_width`       | *Crossing*: drives the pop half's `read_data` input. |
+///
+/// The pop half (`namePop`) has the following ports:
+///
+/// | Port            | Direction | Bit width                 | Description |
+/// |-----------------|-----------|---------------------------|-------------|
+/// | `pop`           | input     | 1                         | Requests that the element at the head of the FIFO is dequeued on this cycle. |
+/// | `pop_data`      | output    | `element_bit_width`       | The element at the head of the FIFO (first-word fall-through); only meaningful when `empty` is low. |
+/// | `empty`         | output    | 1                         | High when the FIFO has no elements to pop. |
+/// | `write_ptr_gray`| input     | `log2(depth) + 1`         | *Crossing*: the push half's `write_ptr_gray` output. |
+/// | `read_data`     | input     | `element_bit_width`       | *Crossing*: the push half's `read_data` output. |
+/// | `read_ptr_gray` | output    | `log2(depth) + 1`         | *Crossing*: drives the push half's `read_ptr_gray` input. |
+/// | `read_address`  | output    | `log2(depth)`             | *Crossing*: drives the push half's `read_address` input. |
+///
+/// As with [`sync_fifo`], a `push` while `full` and a `pop` while `empty` are ignored.
+/// Because each side only sees the other side's pointer after it's been synchronized, `full` and `empty` are conservative: they're asserted as soon as they become true, but are only released a few cycles of the observing clock domain after the other side frees up space or pushes an element.
+///
+/// In generated simulators, each half is simulated separately with its own `posedge_clk`, so the two clocks' edges can be interleaved in any order by copying the crossing ports from one simulator to the other (and calling `prop`) in between.
+///
+/// # Panics
+///
+/// Panics if `depth` isn't a power of two that's at least 2, or if `element_bit_width` is outside the range of legal signal bit widths.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let fifo = components::async_fifo(&c, "CdcFifo", 16, 32);
+/// assert_eq!(fifo.push.name(), "CdcFifoPush");
+/// assert_eq!(fifo.pop.name(), "CdcFifoPop");
+///
+/// // Producer clock domain
+/// let producer = c.module("Producer");
+/// let push = producer.instance("fifo", "CdcFifoPush");
+/// push.drive_input("push", producer.input("push", 1));
+/// push.drive_input("push_data", producer.input("push_data", 32));
+/// push.drive_input("read_ptr_gray", producer.input("read_ptr_gray", 5));
+/// push.drive_input("read_address", producer.input("read_address", 4));
+/// producer.output("full", push.output("full"));
+/// producer.output("write_ptr_gray", push.output("write_ptr_gray"));
+/// producer.output("read_data", push.output("read_data"));
+/// ```
+///
+/// [`Context`]: ../struct.Context.html
+/// [`Mem`]: ../struct.Mem.html
+/// [`Module`]: ../struct.Module.html
+/// [`Module::synchronizer`]: ../struct.Module.html#method.synchronizer
+/// [`Register`]: ../struct.Register.html
+/// [`Signal::to_gray`]: ../struct.Signal.html#method.to_gray
+/// [`sync_fifo`]: ./fn.sync_fifo.html
+/// [attribute]: ../struct.Register.html#method.attribute
+#[track_caller]
+pub fn async_fifo<'a, S: Into<String>>(
+    c: &'a Context<'a>,
+    name: S,
+    depth: u32,
+    element_bit_width: u32,
+) -> AsyncFifo<'a> {
+    let name = name.into();
+    if depth < 2 || !depth.is_power_of_two() {
+        panic!(
+            "Cannot create an asynchronous FIFO called \"{}\" with a depth of {}. Asynchronous FIFOs must have a depth that's a power of two and at least 2.",
+            name, depth
+        );
+    }
+
+    let address_bit_width = depth.trailing_zeros();
+    let ptr_bit_width = address_bit_width + 1;
+
+    let push = c.module(format!("{}Push", name));
+    {
+        let m = push;
+
+        let push = m.input("push", 1);
+        let push_data = m.input("push_data", element_bit_width);
+        let read_ptr_gray = m.input("read_ptr_gray", ptr_bit_width);
+        let read_address = m.input("read_address", address_bit_width);
+
+        let elements = m.mem("elements", address_bit_width, element_bit_width);
+
+        let write_ptr = m.reg("write_ptr", ptr_bit_width);
+        write_ptr.default_value(0u32);
+        let write_ptr_gray = m.reg("write_ptr_gray", ptr_bit_width);
+        write_ptr_gray.default_value(0u32);
+
+        let read_ptr_gray = gray_ptr_synchronizer(m, "read_ptr_gray_sync", read_ptr_gray);
+
+        // Full when the pointers differ only in their wrap bits, which are the top two bits of a Gray-coded pointer
+        let full = write_ptr_gray
+            .value
+            .eq(read_ptr_gray ^ wrap_bits(m, ptr_bit_width));
+        let push_accepted = push & !full;
+
+        elements.write_port(
+            write_ptr.value.bits(address_bit_width - 1, 0),
+            push_data,
+            push_accepted,
+        );
+
+        let next_write_ptr = push_accepted.mux(
+            write_ptr.value + m.lit(1u32, ptr_bit_width),
+            write_ptr.value,
+        );
+        write_ptr.drive_next(next_write_ptr);
+        write_ptr_gray.drive_next(next_write_ptr.to_gray());
+
+        m.output("full", full);
+        m.output("write_ptr_gray", write_ptr_gray.value);
+        m.output("read_data", elements.read_port_comb(read_address));
+    }
+
+    let pop = c.module(format!("{}Pop", name));
+    {
+        let m = pop;
+
+        let pop = m.input("pop", 1);
+        let write_ptr_gray = m.input("write_ptr_gray", ptr_bit_width);
+        let read_data = m.input("read_data", element_bit_width);
+
+        let read_ptr = m.reg("read_ptr", ptr_bit_width);
+        read_ptr.default_value(0u32);
+        let read_ptr_gray = m.reg("read_ptr_gray", ptr_bit_width);
+        read_ptr_gray.default_value(0u32);
+
+        let write_ptr_gray = gray_ptr_synchronizer(m, "write_ptr_gray_sync", write_ptr_gray);
+
+        let empty = read_ptr_gray.value.eq(write_ptr_gray);
+        let pop_accepted = pop & !empty;
+
+        let next_read_ptr =
+            pop_accepted.mux(read_ptr.value + m.lit(1u32, ptr_bit_width), read_ptr.value);
+        read_ptr.drive_next(next_read_ptr);
+        read_ptr_gray.drive_next(next_read_ptr.to_gray());
+
+        m.output("pop_data", read_data);
+        m.output("empty", empty);
+        m.output("read_ptr_gray", read_ptr_gray.value);
+        m.output(
+            "read_address",
+            read_ptr.value.bits(address_bit_width - 1, 0),
+        );
+    }
+
+    AsyncFifo { push, pop }
+}
+
+// Like `Module::synchronizer`, but for a Gray-coded pointer, which is safe to synchronize as a whole since only one of its bits changes at a time
+fn gray_ptr_synchronizer<'a>(
+    m: &'a Module<'a>,
+    name: &str,
+    source: &'a Signal<'a>,
+) -> &'a Signal<'a> {
+    let mut ret = source;
+    for stage in 0..2 {
+        let reg = m.reg(format!("{}_stage{}", name, stage), source.bit_width());
+        reg.default_value(0u32);
+        reg.attribute("ASYNC_REG", "TRUE");
+        reg.drive_next(ret);
+        ret = reg.value;
+    }
+    ret
+}
+
+// The bits that differ between Gray-coded pointers that are exactly `depth` elements apart
+fn wrap_bits<'a>(m: &'a Module<'a>, ptr_bit_width: u32) -> &'a Signal<'a> {
+    m.lit(0b11u32 << (ptr_bit_width - 2), ptr_bit_width)
+}
+
+fn next_ptr<'a>(m: &'a Module<'a>, ptr: &'a Signal<'a>, depth: u32) -> &'a Signal<'a> {
+    let bit_width = ptr.bit_width();
+    ptr.eq(m.lit(depth - 1, bit_width))
+        .mux(m.lit(0u32, bit_width), ptr + m.lit(1u32, bit_width))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create a FIFO called \"Fifo\" with a depth of 0. FIFOs must be able to hold at least 1 element."
+    )]
+    fn zero_depth_error() {
+        let c = Context::new();
+
+        let _ = components::sync_fifo(&c, "Fifo", 0, 8);
+    }
+
+    #[test]
+    fn port_widths() {
+        for &(depth, address_bit_width, count_bit_width) in &[
+            (1, 1, 1),
+            (2, 1, 2),
+            (3, 2, 2),
+            (4, 2, 3),
+            (5, 3, 3),
+            (16, 4, 5),
+        ] {
+            let c = Context::new();
+
+            let m = components::sync_fifo(&c, "Fifo", depth, 8);
+
+            assert_eq!(m.outputs()["count"].bit_width(), count_bit_width);
+            assert_eq!(m.outputs()["pop_data"].bit_width(), 8);
+            assert_eq!(m.mems()[0].address_bit_width(), address_bit_width);
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create an asynchronous FIFO called \"Fifo\" with a depth of 1. Asynchronous FIFOs must have a depth that's a power of two and at least 2."
+    )]
+    fn async_fifo_depth_too_small_error() {
+        let c = Context::new();
+
+        let _ = components::async_fifo(&c, "Fifo", 1, 8);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create an asynchronous FIFO called \"Fifo\" with a depth of 6. Asynchronous FIFOs must have a depth that's a power of two and at least 2."
+    )]
+    fn async_fifo_depth_not_power_of_two_error() {
+        let c = Context::new();
+
+        let _ = components::async_fifo(&c, "Fifo", 6, 8);
+    }
+
+    #[test]
+    fn async_fifo_port_widths() {
+        for &(depth, address_bit_width) in &[(2, 1), (4, 2), (16, 4), (1 << 10, 10)] {
+            let c = Context::new();
+
+            let fifo = components::async_fifo(&c, "Fifo", depth, 8);
+
+            assert_eq!(fifo.push.name(), "FifoPush");
+            assert_eq!(fifo.pop.name(), "FifoPop");
+            assert_eq!(fifo.push.mems()[0].address_bit_width(), address_bit_width);
+            for m in &[fifo.push, fifo.pop] {
+                let ports = m
+                    .inputs()
+                    .iter()
+                    .chain(m.outputs().iter())
+                    .map(|(name, signal)| (name.clone(), signal.bit_width()))
+                    .collect::<Vec<_>>();
+                for (name, bit_width) in ports {
+                    let expected = match name.as_str() {
+                        "push" | "pop" | "full" | "empty" => 1,
+                        "push_data" | "pop_data" | "read_data" => 8,
+                        "read_address" => address_bit_width,
+                        "read_ptr_gray" | "write_ptr_gray" => address_bit_width + 1,
+                        _ => unreachable!(),
+                    };
+                    assert_eq!(bit_width, expected, "port {}", name);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,412 @@
+use crate::graph::*;
+
+use std::cell::RefCell;
+use std::ptr;
+use std::rc::Rc;
+
+type ReadySink<'a> = Box<dyn FnOnce(&'a Signal<'a>) + 'a>;
+
+/// A valid/ready stream carrying `data` from a producer to a single consumer.
+///
+/// A transfer occurs on each cycle where both `valid` and `ready` are high.
+/// Following the usual convention (eg. AXI4-Stream), a producer must not make `valid` depend on `ready`, and once `valid` is asserted, it must hold `valid` and `data` steady until the transfer occurs.
+///
+/// `valid` and `data` flow forwards, and are available as soon as a `Stream` is created.
+/// `ready` flows backwards, so a `Stream` instead holds a *ready sink*: whatever is responsible for using the consumer's `ready` signal on the producer's side, such as an output port, an instance input, or some [`Register`]s' next values.
+/// Every `Stream` must be consumed exactly once, either by passing it to one of the combinators below (which take ownership of it), or by calling [`drive_ready`] directly.
+/// This allows pipelines to be built front-to-back, even though `ready` is determined back-to-front.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+/// use kaze::components::Stream;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+///
+/// // in_valid/in_data inputs, in_ready output
+/// let i = Stream::input(m, "in", 32);
+/// let mut branches = i.register_slice("slice").fork("fork", 2);
+/// let b = branches.pop().unwrap();
+/// let a = branches.pop().unwrap();
+/// // out_a_valid/out_a_data outputs, out_a_ready input (and similarly for out_b)
+/// a.output(m, "out_a");
+/// b.register_slice("b_slice").output(m, "out_b");
+/// ```
+///
+/// [`drive_ready`]: #method.drive_ready
+/// [`Register`]: ../struct.Register.html
+#[must_use = "every Stream must be consumed, otherwise its producer's ready signal is left undriven"]
+pub struct Stream<'a> {
+    valid: &'a Signal<'a>,
+    data: &'a Signal<'a>,
+    ready_sink: ReadySink<'a>,
+}
+
+impl<'a> Stream<'a> {
+    /// Creates a new `Stream` from a producer's `valid` and `data` signals.
+    ///
+    /// `ready_sink` is called exactly once with the consumer's `ready` signal when this `Stream` is consumed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `valid` and `data` belong to different [`Module`]s, or if `valid`'s bit width is not 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    /// use kaze::components::Stream;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// // Produces 0, 1, 2, ... as fast as the consumer accepts them
+    /// let count = m.reg("count", 8);
+    /// count.default_value(0u32);
+    /// let s = Stream::new(m.high(), count.value, move |ready| {
+    ///     count.drive_next(ready.mux(count.value + m.lit(1u32, 8), count.value));
+    /// });
+    /// s.output(m, "count");
+    /// ```
+    ///
+    /// [`Module`]: ../struct.Module.html
+    #[track_caller]
+    pub fn new(
+        valid: &'a Signal<'a>,
+        data: &'a Signal<'a>,
+        ready_sink: impl FnOnce(&'a Signal<'a>) + 'a,
+    ) -> Stream<'a> {
+        if !ptr::eq(valid.module, data.module) {
+            panic!("Attempted to create a stream from valid and data signals that belong to different modules.");
+        }
+        if valid.bit_width() != 1 {
+            panic!("Attempted to create a stream with a valid signal with {} bit(s), but valid signals must be 1 bit wide. The signal was created at {}.", valid.bit_width(), valid.location);
+        }
+        Stream {
+            valid,
+            data,
+            ready_sink: Box::new(ready_sink),
+        }
+    }
+
+    /// Creates a `Stream` from new input ports called `prefix_valid` and `prefix_data` (with `bit_width` bits) on `m`.
+    ///
+    /// When the `Stream` is consumed, its `ready` signal is exposed as an output port called `prefix_ready`.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Module::input`].
+    ///
+    /// [`Module::input`]: ../struct.Module.html#method.input
+    #[track_caller]
+    pub fn input<S: Into<String>>(m: &'a Module<'a>, prefix: S, bit_width: u32) -> Stream<'a> {
+        let prefix = prefix.into();
+        let valid = m.input(format!("{}_valid", prefix), 1);
+        let data = m.input(format!("{}_data", prefix), bit_width);
+        Stream::new(valid, data, move |ready| {
+            m.output(format!("{}_ready", prefix), ready)
+        })
+    }
+
+    /// Creates a `Stream` from the outputs called `prefix_valid` and `prefix_data` of `instance`.
+    ///
+    /// When the `Stream` is consumed, its `ready` signal drives `instance`'s input called `prefix_ready`.
+    /// This is the counterpart of [`drive_instance`] for streams produced by an instance, eg. a submodule's [`output`] stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Instance::output`].
+    ///
+    /// [`drive_instance`]: #method.drive_instance
+    /// [`output`]: #method.output
+    /// [`Instance::output`]: ../struct.Instance.html#method.output
+    #[track_caller]
+    pub fn from_instance<S: Into<String>>(instance: &'a Instance<'a>, prefix: S) -> Stream<'a> {
+        let prefix = prefix.into();
+        let valid = instance.output(format!("{}_valid", prefix));
+        let data = instance.output(format!("{}_data", prefix));
+        Stream::new(valid, data, move |ready| {
+            instance.drive_input(format!("{}_ready", prefix), ready)
+        })
+    }
+
+    /// Returns this `Stream`'s `valid` signal.
+    pub fn valid(&self) -> &'a Signal<'a> {
+        self.valid
+    }
+
+    /// Returns this `Stream`'s `data` signal.
+    pub fn data(&self) -> &'a Signal<'a> {
+        self.data
+    }
+
+    /// Consumes this `Stream`, passing the consumer's `ready` signal back to the producer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ready` belongs to a different [`Module`] than this `Stream`, or if `ready`'s bit width is not 1.
+    ///
+    /// [`Module`]: ../struct.Module.html
+    #[track_caller]
+    pub fn drive_ready(self, ready: &'a Signal<'a>) {
+        if !ptr::eq(self.valid.module, ready.module) {
+            panic!("Attempted to drive a stream's ready signal with a signal from another module.");
+        }
+        if ready.bit_width() != 1 {
+            panic!("Attempted to drive a stream's ready signal with a signal with {} bit(s), but ready signals must be 1 bit wide. The signal was created at {}.", ready.bit_width(), ready.location);
+        }
+        (self.ready_sink)(ready);
+    }
+
+    /// Consumes this `Stream`, exposing it as output ports called `prefix_valid` and `prefix_data`, and driving its `ready` signal with a new input port called `prefix_ready`.
+    ///
+    /// All ports are created on the [`Module`] this `Stream` belongs to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` isn't the [`Module`] this `Stream` belongs to, or under the same conditions as [`Module::input`] and [`Module::output`].
+    ///
+    /// [`Module`]: ../struct.Module.html
+    /// [`Module::input`]: ../struct.Module.html#method.input
+    /// [`Module::output`]: ../struct.Module.html#method.output
+    #[track_caller]
+    pub fn output<S: Into<String>>(self, m: &'a Module<'a>, prefix: S) {
+        if !ptr::eq(self.valid.module, m) {
+            panic!(
+                "Attempted to output a stream from module \"{}\" as ports of another module.",
+                self.valid.module.name
+            );
+        }
+        let prefix = prefix.into();
+        m.output(format!("{}_valid", prefix), self.valid);
+        m.output(format!("{}_data", prefix), self.data);
+        let ready = m.input(format!("{}_ready", prefix), 1);
+        self.drive_ready(ready);
+    }
+
+    /// Consumes this `Stream`, driving `instance`'s inputs called `prefix_valid` and `prefix_data`, and driving its `ready` signal with `instance`'s output called `prefix_ready`.
+    ///
+    /// This is the counterpart of [`input`] for streams consumed by an instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Instance::drive_input`] and [`Instance::output`].
+    ///
+    /// [`input`]: #method.input
+    /// [`Instance::drive_input`]: ../struct.Instance.html#method.drive_input
+    /// [`Instance::output`]: ../struct.Instance.html#method.output
+    #[track_caller]
+    pub fn drive_instance<S: Into<String>>(self, instance: &'a Instance<'a>, prefix: S) {
+        let prefix = prefix.into();
+        instance.drive_input(format!("{}_valid", prefix), self.valid);
+        instance.drive_input(format!("{}_data", prefix), self.data);
+        let ready = instance.output(format!("{}_ready", prefix));
+        self.drive_ready(ready);
+    }
+
+    /// Consumes this `Stream`, inserting a register slice (also known as a skid buffer) and returning the registered `Stream`.
+    ///
+    /// Both the returned `Stream`'s `valid`/`data` and this `Stream`'s `ready` are driven directly by registers, which breaks all combinational paths through the slice in both directions.
+    /// The slice can still accept a transfer on every cycle, so it adds one cycle of latency without reducing throughput.
+    ///
+    /// The slice's registers are called `name_valid`, `name_data`, `name_skid_valid`, and `name_skid_data`.
+    #[track_caller]
+    pub fn register_slice<S: Into<String>>(self, name: S) -> Stream<'a> {
+        let name = name.into();
+        let m = self.valid.module;
+        let bit_width = self.data.bit_width();
+
+        let out_valid = m.reg(format!("{}_valid", name), 1);
+        out_valid.default_value(false);
+        let out_data = m.reg(format!("{}_data", name), bit_width);
+        let skid_valid = m.reg(format!("{}_skid_valid", name), 1);
+        skid_valid.default_value(false);
+        let skid_data = m.reg(format!("{}_skid_data", name), bit_width);
+
+        let in_valid = self.valid;
+        let in_data = self.data;
+        self.drive_ready(!skid_valid.value);
+
+        Stream::new(out_valid.value, out_data.value, move |out_ready| {
+            // The output registers can be loaded whenever they're empty or being drained
+            let load = !out_valid.value | out_ready;
+            out_valid.drive_next(!load | skid_valid.value | in_valid);
+            out_data.drive_next(load.mux(
+                skid_valid.value.mux(skid_data.value, in_data),
+                out_data.value,
+            ));
+            // A transfer accepted while the output is stalled is parked in the skid registers
+            skid_valid.drive_next(!load & (skid_valid.value | in_valid));
+            skid_data.drive_next(skid_valid.value.mux(skid_data.value, in_data));
+        })
+    }
+
+    /// Consumes this `Stream`, duplicating it into `num_branches` `Stream`s that each receive every transfer.
+    ///
+    /// Each branch can accept a given transfer on a different cycle; a transfer is only accepted from this `Stream` once all branches have accepted it, and branches that already have are not offered it again.
+    /// This requires one register per branch, called `name_doneN`.
+    ///
+    /// All of the returned `Stream`s must be consumed before this `Stream`'s `ready` signal is driven.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_branches` is `0`.
+    #[track_caller]
+    pub fn fork<S: Into<String>>(self, name: S, num_branches: u32) -> Vec<Stream<'a>> {
+        let name = name.into();
+        if num_branches == 0 {
+            panic!(
+                "Attempted to fork stream \"{}\" into 0 branches, but at least 1 branch is required.",
+                name
+            );
+        }
+        let m = self.valid.module;
+
+        struct ForkState<'a> {
+            source: Option<Stream<'a>>,
+            done: Vec<&'a Register<'a>>,
+            readies: Vec<Option<&'a Signal<'a>>>,
+        }
+
+        let mut done = Vec::new();
+        for i in 0..num_branches {
+            let reg = m.reg(format!("{}_done{}", name, i), 1);
+            reg.default_value(false);
+            done.push(reg);
+        }
+        let valid = self.valid;
+        let data = self.data;
+        let state = Rc::new(RefCell::new(ForkState {
+            source: Some(self),
+            done: done.clone(),
+            readies: vec![None; num_branches as usize],
+        }));
+
+        let mut branches = Vec::new();
+        for (i, reg) in done.into_iter().enumerate() {
+            let state = state.clone();
+            branches.push(Stream::new(valid & !reg.value, data, move |ready| {
+                let mut state = state.borrow_mut();
+                state.readies[i] = Some(ready);
+                if state.readies.iter().any(|ready| ready.is_none()) {
+                    return;
+                }
+                let mut source_ready = m.high();
+                for (reg, ready) in state.done.iter().zip(state.readies.iter()) {
+                    source_ready = source_ready & (reg.value | ready.unwrap());
+                }
+                for (reg, ready) in state.done.iter().zip(state.readies.iter()) {
+                    reg.drive_next(valid & !source_ready & (reg.value | ready.unwrap()));
+                }
+                state.source.take().unwrap().drive_ready(source_ready);
+            }));
+        }
+        branches
+    }
+
+    /// Consumes `streams`, combining them into a single `Stream` that carries one transfer from each of them at a time.
+    ///
+    /// The returned `Stream` is valid when all of `streams` are valid, and its `data` is the concatenation of their `data` signals, with the first `Stream`'s `data` in the most significant bits.
+    /// A transfer on the returned `Stream` accepts a transfer from each of `streams` simultaneously.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `streams` is empty, if `streams` belong to different [`Module`]s, or if the combined `data` is wider than [`MAX_SIGNAL_BIT_WIDTH`].
+    ///
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ../constant.MAX_SIGNAL_BIT_WIDTH.html
+    /// [`Module`]: ../struct.Module.html
+    #[track_caller]
+    pub fn join(streams: Vec<Stream<'a>>) -> Stream<'a> {
+        if streams.is_empty() {
+            panic!(
+                "Attempted to join an empty list of streams, but at least 1 stream is required."
+            );
+        }
+        let mut valid = streams[0].valid;
+        let mut data = streams[0].data;
+        for stream in &streams[1..] {
+            if !ptr::eq(stream.valid.module, valid.module) {
+                panic!("Attempted to join streams that belong to different modules.");
+            }
+            valid = valid & stream.valid;
+            data = data.concat(stream.data);
+        }
+        Stream::new(valid, data, move |ready| {
+            let accepted = valid & ready;
+            for stream in streams {
+                stream.drive_ready(accepted);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::components::Stream;
+    use crate::*;
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to create a stream with a valid signal with 2 bit(s), but valid signals must be 1 bit wide."
+    )]
+    fn valid_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = Stream::new(m.input("valid", 2), m.input("data", 8), |_| ());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive a stream's ready signal with a signal with 2 bit(s), but ready signals must be 1 bit wide."
+    )]
+    fn ready_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let s = Stream::input(m, "in", 8);
+
+        // Panic
+        s.drive_ready(m.input("ready", 2));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to fork stream \"fork\" into 0 branches, but at least 1 branch is required."
+    )]
+    fn fork_zero_branches_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = Stream::input(m, "in", 8).fork("fork", 0);
+    }
+
+    #[test]
+    fn ports() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        Stream::join(vec![Stream::input(m, "a", 8), Stream::input(m, "b", 4)])
+            .register_slice("slice")
+            .output(m, "o");
+
+        assert_eq!(
+            m.inputs().keys().collect::<Vec<_>>(),
+            ["a_data", "a_valid", "b_data", "b_valid", "o_ready"]
+        );
+        assert_eq!(
+            m.outputs().keys().collect::<Vec<_>>(),
+            ["a_ready", "b_ready", "o_data", "o_valid"]
+        );
+        assert_eq!(m.outputs()["o_data"].bit_width(), 12);
+        assert_eq!(m.registers().len(), 4);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::graph;
+
+use std::collections::HashMap;
+
+/// Evaluates `Signal`s whose values can be determined at code generation time, so that code generators can emit them as constants.
+///
+/// Only literals and operations whose operands are (transitively) constant are evaluated; anything that depends on an input, register, instance output, or memory read port isn't constant.
+/// The evaluated values match those computed by the generated simulator for the unfolded graph.
+pub struct ConstantFolder<'graph> {
+    values: HashMap<&'graph graph::Signal<'graph>, Option<u128>>,
+}
+
+impl<'graph> ConstantFolder<'graph> {
+    pub fn new() -> ConstantFolder<'graph> {
+        ConstantFolder {
+            values: HashMap::new(),
+        }
+    }
+
+    /// Returns `signal`'s value if it's constant, or `None` otherwise.
+    pub fn evaluate(&mut self, signal: &'graph graph::Signal<'graph>) -> Option<graph::Constant> {
+        // Operands are evaluated bottom-up with an explicit stack first, so that evaluating `signal` itself never recurses more than one level deep, regardless of how deep the graph is
+        let mut stack = vec![(signal, false)];
+        while let Some((signal, operands_evaluated)) = stack.pop() {
+            if self.values.contains_key(&signal) {
+                continue;
+            }
+            if operands_evaluated {
+                self.evaluate_value(signal);
+                continue;
+            }
+            stack.push((signal, true));
+            match signal.data {
+                graph::SignalData::Lit { .. }
+                | graph::SignalData::Input { .. }
+                | graph::SignalData::Reg { .. }
+                | graph::SignalData::Latch { .. }
+                | graph::SignalData::InstanceOutput { .. }
+                | graph::SignalData::MemReadPortOutput { .. }
+                | graph::SignalData::MemCombReadPortOutput { .. } => (),
+                _ => stack.extend(
+                    signal
+                        .dependencies()
+                        .into_iter()
+                        .map(|operand| (operand, false)),
+                ),
+            }
+        }
+
+        self.evaluate_value(signal).map(graph::Constant::from)
+    }
+
+    fn evaluate_value(&mut self, signal: &'graph graph::Signal<'graph>) -> Option<u128> {
+        if let Some(&value) = self.values.get(&signal) {
+            return value;
+        }
+
+        let bit_width = signal.bit_width();
+        let value = match signal.data {
+            graph::SignalData::Lit { ref value, .. } => Some(value.numeric_value()),
+
+            graph::SignalData::Input { .. }
+            | graph::SignalData::Reg { .. }
+            | graph::SignalData::Latch { .. }
+            | graph::SignalData::InstanceOutput { .. }
+            | graph::SignalData::MemReadPortOutput { .. }
+            | graph::SignalData::MemCombReadPortOutput { .. } => None,
+
+            graph::SignalData::UnOp { source, op } => {
+                self.evaluate_value(source).map(|source| match op {
+                    graph::UnOp::Not => !source,
+                })
+            }
+            graph::SignalData::SimpleBinOp { lhs, rhs, op } => {
+                self.evaluate_binary(lhs, rhs, |lhs, rhs| match op {
+                    graph::SimpleBinOp::BitAnd => lhs & rhs,
+                    graph::SimpleBinOp::BitOr => lhs | rhs,
+                    graph::SimpleBinOp::BitXor => lhs ^ rhs,
+                })
+            }
+            graph::SignalData::AdditiveBinOp { lhs, rhs, op } => {
+                self.evaluate_binary(lhs, rhs, |lhs, rhs| match op {
+                    graph::AdditiveBinOp::Add => lhs.wrapping_add(rhs),
+                    graph::AdditiveBinOp::Sub => lhs.wrapping_sub(rhs),
+                })
+            }
+            graph::SignalData::ComparisonBinOp { lhs, rhs, op } => {
+                let source_bit_width = lhs.bit_width();
+                self.evaluate_binary(lhs, rhs, |lhs, rhs| {
+                    let signed_lhs = sign_extend(lhs, source_bit_width);
+                    let signed_rhs = sign_extend(rhs, source_bit_width);
+                    (match op {
+                        graph::ComparisonBinOp::Equal => lhs == rhs,
+                        graph::ComparisonBinOp::NotEqual => lhs != rhs,
+                        graph::ComparisonBinOp::LessThan => lhs < rhs,
+                        graph::ComparisonBinOp::LessThanEqual => lhs <= rhs,
+                        graph::ComparisonBinOp::GreaterThan => lhs > rhs,
+                        graph::ComparisonBinOp::GreaterThanEqual => lhs >= rhs,
+                        graph::ComparisonBinOp::LessThanSigned => signed_lhs < signed_rhs,
+                        graph::ComparisonBinOp::LessThanEqualSigned => signed_lhs <= signed_rhs,
+                        graph::ComparisonBinOp::GreaterThanSigned => signed_lhs > signed_rhs,
+                        graph::ComparisonBinOp::GreaterThanEqualSigned => signed_lhs >= signed_rhs,
+                    }) as u128
+                })
+            }
+            graph::SignalData::ShiftBinOp { lhs, rhs, op } => {
+                self.evaluate_binary(lhs, rhs, |lhs, rhs| match op {
+                    graph::ShiftBinOp::Shl if rhs < bit_width as u128 => lhs << rhs,
+                    graph::ShiftBinOp::Shr if rhs < bit_width as u128 => lhs >> rhs,
+                    graph::ShiftBinOp::Shl | graph::ShiftBinOp::Shr => 0,
+                    graph::ShiftBinOp::ShrArithmetic => {
+                        (sign_extend(lhs, bit_width) >> rhs.min(127)) as u128
+                    }
+                })
+            }
+
+            graph::SignalData::Bits {
+                source, range_low, ..
+            } => self
+                .evaluate_value(source)
+                .map(|source| source >> range_low),
+
+            graph::SignalData::Repeat { source, count } => {
+                let source_bit_width = source.bit_width();
+                self.evaluate_value(source).map(|source| {
+                    (0..count).fold(0u128, |acc, _| {
+                        // Avoid overflowing shifts when the result is exactly 128 bits wide
+                        acc.checked_shl(source_bit_width).unwrap_or(0) | source
+                    })
+                })
+            }
+            graph::SignalData::Concat { lhs, rhs } => {
+                let rhs_bit_width = rhs.bit_width();
+                self.evaluate_binary(lhs, rhs, |lhs, rhs| (lhs << rhs_bit_width) | rhs)
+            }
+
+            graph::SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            } => match self.evaluate_value(cond) {
+                Some(cond) => self.evaluate_value(if cond != 0 { when_true } else { when_false }),
+                None => None,
+            },
+        };
+        let value = value.map(|value| value & mask(bit_width));
+
+        self.values.insert(signal, value);
+        value
+    }
+
+    fn evaluate_binary(
+        &mut self,
+        lhs: &'graph graph::Signal<'graph>,
+        rhs: &'graph graph::Signal<'graph>,
+        f: impl FnOnce(u128, u128) -> u128,
+    ) -> Option<u128> {
+        let lhs = self.evaluate_value(lhs)?;
+        let rhs = self.evaluate_value(rhs)?;
+        Some(f(lhs, rhs))
+    }
+}
+
+fn mask(bit_width: u32) -> u128 {
+    if bit_width >= 128 {
+        !0
+    } else {
+        (1 << bit_width) - 1
+    }
+}
+
+fn sign_extend(value: u128, bit_width: u32) -> i128 {
+    let shift = 128 - bit_width;
+    ((value << shift) as i128) >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::*;
+
+    fn evaluate<'a>(signal: &'a Signal<'a>) -> Option<u128> {
+        ConstantFolder::new()
+            .evaluate(signal)
+            .map(|value| value.numeric_value())
+    }
+
+    #[test]
+    fn non_constant_signals() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 8);
+        let r = m.reg("r", 8);
+        r.drive_next(m.lit(0u32, 8));
+
+        assert_eq!(evaluate(i), None);
+        assert_eq!(evaluate(r.value), None);
+        assert_eq!(evaluate(i + m.lit(1u32, 8)), None);
+        assert_eq!(evaluate(i.bit(0).mux(m.lit(1u32, 8), m.lit(2u32, 8))), None);
+    }
+
+    #[test]
+    fn arithmetic_wraps() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        assert_eq!(evaluate(m.lit(1u32, 8) + m.lit(2u32, 8)), Some(3));
+        assert_eq!(evaluate(m.lit(0xffu32, 8) + m.lit(2u32, 8)), Some(1));
+        assert_eq!(evaluate(m.lit(1u32, 8) - m.lit(2u32, 8)), Some(0xff));
+        assert_eq!(evaluate(m.lit(!0u128, 128) + m.lit(1u32, 128)), Some(0));
+        assert_eq!(evaluate(!m.lit(0x0fu32, 8)), Some(0xf0));
+        assert_eq!(evaluate(!m.lit(0u32, 128)), Some(!0));
+        assert_eq!(evaluate(m.lit(0xccu32, 8) & m.lit(0xaau32, 8)), Some(0x88));
+        assert_eq!(evaluate(m.lit(0xccu32, 8) | m.lit(0xaau32, 8)), Some(0xee));
+        assert_eq!(evaluate(m.lit(0xccu32, 8) ^ m.lit(0xaau32, 8)), Some(0x66));
+    }
+
+    #[test]
+    fn comparisons() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.lit(0x80u32, 8);
+        let b = m.lit(0x01u32, 8);
+
+        assert_eq!(evaluate(a.eq(b)), Some(0));
+        assert_eq!(evaluate(a.ne(b)), Some(1));
+        assert_eq!(evaluate(a.lt(b)), Some(0));
+        assert_eq!(evaluate(a.le(a)), Some(1));
+        assert_eq!(evaluate(a.gt(b)), Some(1));
+        assert_eq!(evaluate(a.ge(b)), Some(1));
+        assert_eq!(evaluate(a.lt_signed(b)), Some(1));
+        assert_eq!(evaluate(a.le_signed(b)), Some(1));
+        assert_eq!(evaluate(a.gt_signed(b)), Some(0));
+        assert_eq!(evaluate(a.ge_signed(b)), Some(0));
+    }
+
+    #[test]
+    fn shifts() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.lit(0x81u32, 8);
+
+        assert_eq!(evaluate(a << m.lit(1u32, 4)), Some(0x02));
+        assert_eq!(evaluate(a << m.lit(8u32, 4)), Some(0));
+        assert_eq!(evaluate(a >> m.lit(1u32, 4)), Some(0x40));
+        assert_eq!(evaluate(a >> m.lit(15u32, 4)), Some(0));
+        assert_eq!(evaluate(a.shr_arithmetic(m.lit(1u32, 4))), Some(0xc0));
+        assert_eq!(evaluate(a.shr_arithmetic(m.lit(15u32, 4))), Some(0xff));
+        assert_eq!(
+            evaluate(m.lit(0x7fu32, 8).shr_arithmetic(m.lit(15u32, 4))),
+            Some(0)
+        );
+        assert_eq!(
+            evaluate(m.lit(1u128 << 127, 128).shr_arithmetic(m.lit(127u32, 7))),
+            Some(!0)
+        );
+    }
+
+    #[test]
+    fn bit_manipulation() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.lit(0xa5u32, 8);
+
+        assert_eq!(evaluate(a.bit(0)), Some(1));
+        assert_eq!(evaluate(a.bit(1)), Some(0));
+        assert_eq!(evaluate(a.bits(7, 4)), Some(0xa));
+        assert_eq!(evaluate(a.repeat(3)), Some(0xa5a5a5));
+        assert_eq!(evaluate(m.lit(0xffffu32, 16).repeat(8)), Some(!0));
+        assert_eq!(evaluate(a.concat(m.lit(0x3u32, 2))), Some(0x297));
+    }
+
+    #[test]
+    fn constant_mux_select() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 8);
+
+        assert_eq!(
+            evaluate(m.high().mux(m.lit(1u32, 8), m.lit(2u32, 8))),
+            Some(1)
+        );
+        assert_eq!(
+            evaluate(m.low().mux(m.lit(1u32, 8), m.lit(2u32, 8))),
+            Some(2)
+        );
+        assert_eq!(evaluate(m.low().mux(i, m.lit(2u32, 8))), Some(2));
+        assert_eq!(evaluate(m.high().mux(i, m.lit(2u32, 8))), None);
+    }
+}
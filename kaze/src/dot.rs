@@ -0,0 +1,535 @@
+//! [Graphviz](https://graphviz.org/) DOT export of a `Module`'s graph, for documentation and debugging.
+
+use crate::code_writer;
+use crate::graph;
+use crate::index_map::*;
+
+use std::collections::BTreeMap;
+use std::io::{Result, Write};
+
+/// Options for DOT generation, used with [`generate_with_options`].
+///
+/// [`generate_with_options`]: ./fn.generate_with_options.html
+#[derive(Clone, Debug, Default)]
+pub struct GenerationOptions {
+    /// The maximum number of nodes to render.
+    ///
+    /// Graphviz struggles with very large graphs, so if a hierarchy has more nodes than this, only the first `max_nodes` nodes (and the edges between them) are rendered, followed by a note saying how many nodes were omitted.
+    /// If `None` (the default), all nodes are rendered.
+    pub max_nodes: Option<usize>,
+}
+
+/// Generates a DOT graph for the `Module` `m` using the default [`GenerationOptions`] and writes it to `w`.
+///
+/// See [`generate_with_options`] for details.
+///
+/// [`GenerationOptions`]: ./struct.GenerationOptions.html
+/// [`generate_with_options`]: ./fn.generate_with_options.html
+pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
+    generate_with_options(m, &GenerationOptions::default(), w)
+}
+
+/// Generates a DOT graph for the `Module` `m` according to `options` and writes it to `w`.
+///
+/// Each [`Signal`] is a node labeled with its name (if it was given one with [`Signal::name`]), its kind, and its bit width, and edges follow data flow from each `Signal` to the `Signal`s that use it.
+/// `Signal`s used in several places are still rendered as a single node with multiple outgoing edges.
+/// Inputs, outputs, [`Register`]s, and [`Mem`]s are drawn with distinct shapes, and each [`Instance`] is drawn as a cluster containing the graph of the `Module` it instantiates, with edges crossing the cluster boundary at its inputs and outputs.
+///
+/// Unlike the Verilog and sim generators, `m` doesn't need to be valid (for example, undriven [`Register`]s are simply drawn without an incoming edge), so this can be used to debug incomplete designs.
+///
+/// [`Instance`]: ../struct.Instance.html
+/// [`Mem`]: ../struct.Mem.html
+/// [`Register`]: ../struct.Register.html
+/// [`Signal`]: ../struct.Signal.html
+/// [`Signal::name`]: ../struct.Signal.html#method.name
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("Adder");
+/// let sum = (m.input("a", 8) + m.input("b", 8)).name("sum");
+/// m.output("o", sum);
+///
+/// let mut w = Vec::new();
+/// dot::generate_with_options(m, &dot::GenerationOptions { max_nodes: Some(1000) }, &mut w)
+///     .unwrap();
+/// let dot = String::from_utf8(w).unwrap();
+/// assert!(dot.starts_with("digraph \"Adder\" {"));
+/// assert!(dot.contains("label=\"sum\\n+\\n8 bits\""));
+/// ```
+pub fn generate_with_options<'a, W: Write>(
+    m: &'a graph::Module<'a>,
+    options: &GenerationOptions,
+    w: W,
+) -> Result<()> {
+    let mut builder = GraphBuilder {
+        node_count: 0,
+        cluster_count: 0,
+        edges: Vec::new(),
+    };
+    let mut root = Cluster {
+        id: 0,
+        label: String::new(),
+        nodes: Vec::new(),
+        clusters: Vec::new(),
+    };
+    builder.add_module(m, &mut root);
+
+    let max_nodes = options.max_nodes.unwrap_or(builder.node_count);
+    let is_rendered = |id: usize| id < max_nodes;
+
+    let mut w = code_writer::CodeWriter::new(w);
+
+    w.append_line(&format!("digraph {} {{", quote(&m.name)))?;
+    w.indent();
+    w.append_line("node [fontname=\"monospace\"];")?;
+    w.append_line("edge [fontname=\"monospace\"];")?;
+    write_cluster_contents(&root, &is_rendered, &mut w)?;
+    for edge in builder.edges.iter() {
+        if !is_rendered(edge.from) || !is_rendered(edge.to) {
+            continue;
+        }
+        w.append_indent()?;
+        w.append(&format!("n{} -> n{}", edge.from, edge.to))?;
+        if let Some(label) = edge.label {
+            w.append(&format!(" [label={}]", quote(label)))?;
+        }
+        w.append(";")?;
+        w.append_newline()?;
+    }
+    if builder.node_count > max_nodes {
+        w.append_line(&format!(
+            "omitted [label={}, shape=note];",
+            quote(&format!(
+                "{} more nodes not shown",
+                builder.node_count - max_nodes
+            ))
+        ))?;
+    }
+    w.unindent()?;
+    w.append_line("}")?;
+
+    Ok(())
+}
+
+struct Node {
+    id: usize,
+    label: String,
+    shape: &'static str,
+}
+
+struct Cluster {
+    id: usize,
+    label: String,
+    nodes: Vec<Node>,
+    clusters: Vec<Cluster>,
+}
+
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<&'static str>,
+}
+
+struct GraphBuilder {
+    node_count: usize,
+    cluster_count: usize,
+    edges: Vec<Edge>,
+}
+
+impl GraphBuilder {
+    fn node(&mut self, cluster: &mut Cluster, label: String, shape: &'static str) -> usize {
+        let id = self.node_count;
+        self.node_count += 1;
+        cluster.nodes.push(Node { id, label, shape });
+        id
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: Option<&'static str>) {
+        self.edges.push(Edge { from, to, label });
+    }
+
+    // Adds the nodes for `m` (including those of its instances, in nested clusters) to `cluster`, and returns the nodes representing its inputs and outputs by name
+    fn add_module<'a>(
+        &mut self,
+        m: &'a graph::Module<'a>,
+        cluster: &mut Cluster,
+    ) -> (BTreeMap<String, usize>, BTreeMap<String, usize>) {
+        let mut names = IndexMap::new();
+        for (name, signal) in m.named_signals.borrow().iter() {
+            if !names.contains_key(signal) {
+                names.insert(*signal, name.clone());
+            }
+        }
+
+        let mut signal_ids = IndexMap::new();
+        for &signal in m.signals.borrow().iter() {
+            // Extern modules' outputs are only placeholders, since their logic is defined outside of kaze
+            if m.is_extern && !matches!(signal.data, graph::SignalData::Input { .. }) {
+                continue;
+            }
+            let mut lines = Vec::new();
+            if let Some(name) = names.get(&signal) {
+                lines.push(name.clone());
+            }
+            lines.push(kind(signal));
+            lines.push(bit_width_label(signal.bit_width()));
+            let shape = match signal.data {
+                graph::SignalData::Lit { .. } => "plaintext",
+                graph::SignalData::Input { .. } => "invhouse",
+                graph::SignalData::Reg { .. } | graph::SignalData::Latch { .. } => "box",
+                _ => "ellipse",
+            };
+            let id = self.node(cluster, lines.join("\n"), shape);
+            signal_ids.insert(signal, id);
+        }
+
+        let mut mem_ids = IndexMap::new();
+        for &mem in m.mems.borrow().iter() {
+            let elements = match 1u128.checked_shl(mem.address_bit_width) {
+                Some(elements) => elements.to_string(),
+                _ => format!("2^{}", mem.address_bit_width),
+            };
+            let label = format!(
+                "mem {}\n{} x {}",
+                mem.name,
+                elements,
+                bit_width_label(mem.element_bit_width)
+            );
+            let id = self.node(cluster, label, "cylinder");
+            mem_ids.insert(mem, id);
+        }
+
+        let mut instance_output_ids = IndexMap::new();
+        for &instance in m.instances.borrow().iter() {
+            self.cluster_count += 1;
+            let mut instance_cluster = Cluster {
+                id: self.cluster_count,
+                label: format!("{}: {}", instance.name, instance.instantiated_module.name),
+                nodes: Vec::new(),
+                clusters: Vec::new(),
+            };
+            let (input_ids, output_ids) =
+                self.add_module(instance.instantiated_module, &mut instance_cluster);
+            cluster.clusters.push(instance_cluster);
+
+            for (name, signal) in instance.driven_inputs.borrow().iter() {
+                if let (Some(&from), Some(&to)) = (signal_ids.get(signal), input_ids.get(name)) {
+                    self.edge(from, to, None);
+                }
+            }
+            instance_output_ids.insert(instance, output_ids);
+        }
+
+        let mut output_ids = BTreeMap::new();
+        for (name, signal) in m.outputs.borrow().iter() {
+            let label = format!("output {}\n{}", name, bit_width_label(signal.bit_width()));
+            let id = self.node(cluster, label, "house");
+            if let Some(&from) = signal_ids.get(signal) {
+                self.edge(from, id, None);
+            }
+            output_ids.insert(name.clone(), id);
+        }
+
+        for (&signal, &id) in signal_ids.iter() {
+            let mut edge = |from: &'a graph::Signal<'a>, label| {
+                if let Some(&from) = signal_ids.get(&from) {
+                    self.edge(from, id, label);
+                }
+            };
+            match signal.data {
+                graph::SignalData::Mux {
+                    cond,
+                    when_true,
+                    when_false,
+                } => {
+                    edge(cond, Some("cond"));
+                    edge(when_true, Some("1"));
+                    edge(when_false, Some("0"));
+                }
+                graph::SignalData::InstanceOutput { instance, name } => {
+                    if let Some(&from) = instance_output_ids
+                        .get(&instance)
+                        .and_then(|output_ids| output_ids.get(name))
+                    {
+                        self.edge(from, id, None);
+                    }
+                }
+                graph::SignalData::MemReadPortOutput {
+                    mem,
+                    address,
+                    enable,
+                } => {
+                    edge(address, Some("addr"));
+                    edge(enable, Some("en"));
+                    self.edge(mem_ids[&mem], id, None);
+                }
+                graph::SignalData::MemCombReadPortOutput { mem, address } => {
+                    edge(address, Some("addr"));
+                    self.edge(mem_ids[&mem], id, None);
+                }
+                _ => {
+                    for source in signal.dependencies() {
+                        edge(source, None);
+                    }
+                }
+            }
+        }
+
+        for (&mem, &id) in mem_ids.iter() {
+            for &(address, value, enable, mask) in mem.write_ports.borrow().iter() {
+                let mut ports = vec![(address, "addr"), (value, "data"), (enable, "en")];
+                if let Some(mask) = mask {
+                    ports.push((mask, "mask"));
+                }
+                for (signal, label) in ports {
+                    if let Some(&from) = signal_ids.get(&signal) {
+                        self.edge(from, id, Some(label));
+                    }
+                }
+            }
+        }
+
+        let input_ids = m
+            .inputs
+            .borrow()
+            .iter()
+            .filter_map(|(name, signal)| signal_ids.get(signal).map(|&id| (name.clone(), id)))
+            .collect();
+        (input_ids, output_ids)
+    }
+}
+
+fn kind(signal: &graph::Signal) -> String {
+    match signal.data {
+        graph::SignalData::Lit { ref value, .. } => format!("0x{:x}", value.numeric_value()),
+        graph::SignalData::Input { name, .. } => format!("input {}", name),
+        graph::SignalData::Reg { data } => format!("reg {}", data.name),
+        graph::SignalData::Latch { data } => format!("latch {}", data.name),
+        graph::SignalData::UnOp { op, .. } => match op {
+            graph::UnOp::Not => "~",
+        }
+        .into(),
+        graph::SignalData::SimpleBinOp { op, .. } => match op {
+            graph::SimpleBinOp::BitAnd => "&",
+            graph::SimpleBinOp::BitOr => "|",
+            graph::SimpleBinOp::BitXor => "^",
+        }
+        .into(),
+        graph::SignalData::AdditiveBinOp { op, .. } => match op {
+            graph::AdditiveBinOp::Add => "+",
+            graph::AdditiveBinOp::Sub => "-",
+        }
+        .into(),
+        graph::SignalData::ComparisonBinOp { op, .. } => match op {
+            graph::ComparisonBinOp::Equal => "==",
+            graph::ComparisonBinOp::NotEqual => "!=",
+            graph::ComparisonBinOp::LessThan => "<",
+            graph::ComparisonBinOp::LessThanEqual => "<=",
+            graph::ComparisonBinOp::GreaterThan => ">",
+            graph::ComparisonBinOp::GreaterThanEqual => ">=",
+            graph::ComparisonBinOp::LessThanSigned => "< (signed)",
+            graph::ComparisonBinOp::LessThanEqualSigned => "<= (signed)",
+            graph::ComparisonBinOp::GreaterThanSigned => "> (signed)",
+            graph::ComparisonBinOp::GreaterThanEqualSigned => ">= (signed)",
+        }
+        .into(),
+        graph::SignalData::ShiftBinOp { op, .. } => match op {
+            graph::ShiftBinOp::Shl => "<<",
+            graph::ShiftBinOp::Shr => ">>",
+            graph::ShiftBinOp::ShrArithmetic => ">>>",
+        }
+        .into(),
+        graph::SignalData::Bits {
+            range_high,
+            range_low,
+            ..
+        } => {
+            if range_high != range_low {
+                format!("[{}:{}]", range_high, range_low)
+            } else {
+                format!("[{}]", range_high)
+            }
+        }
+        graph::SignalData::Repeat { count, .. } => format!("repeat x{}", count),
+        graph::SignalData::Concat { .. } => "concat".into(),
+        graph::SignalData::Mux { .. } => "mux".into(),
+        graph::SignalData::InstanceOutput {
+            instance, ref name, ..
+        } => format!("{}.{}", instance.name, name),
+        graph::SignalData::MemReadPortOutput { mem, .. } => format!("{} read port", mem.name),
+        graph::SignalData::MemCombReadPortOutput { mem, .. } => {
+            format!("{} comb read port", mem.name)
+        }
+    }
+}
+
+fn bit_width_label(bit_width: u32) -> String {
+    if bit_width == 1 {
+        "1 bit".into()
+    } else {
+        format!("{} bits", bit_width)
+    }
+}
+
+fn write_cluster_contents<W: Write>(
+    cluster: &Cluster,
+    is_rendered: &impl Fn(usize) -> bool,
+    w: &mut code_writer::CodeWriter<W>,
+) -> Result<()> {
+    for node in cluster.nodes.iter() {
+        if !is_rendered(node.id) {
+            continue;
+        }
+        w.append_line(&format!(
+            "n{} [label={}, shape={}];",
+            node.id,
+            quote(&node.label),
+            node.shape
+        ))?;
+    }
+    for child in cluster.clusters.iter() {
+        if !has_rendered_nodes(child, is_rendered) {
+            continue;
+        }
+        w.append_line(&format!("subgraph cluster_{} {{", child.id))?;
+        w.indent();
+        w.append_line(&format!("label={};", quote(&child.label)))?;
+        write_cluster_contents(child, is_rendered, w)?;
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+    Ok(())
+}
+
+fn has_rendered_nodes(cluster: &Cluster, is_rendered: &impl Fn(usize) -> bool) -> bool {
+    cluster.nodes.iter().any(|node| is_rendered(node.id))
+        || cluster
+            .clusters
+            .iter()
+            .any(|child| has_rendered_nodes(child, is_rendered))
+}
+
+// Returns `s` as a quoted DOT string, escaping any characters that would otherwise end the string or be interpreted as escape sequences
+fn quote(s: &str) -> String {
+    let escaped: String = s
+        .chars()
+        .map(|c| match c {
+            '"' => "\\\"".into(),
+            '\\' => "\\\\".into(),
+            '\n' => "\\n".into(),
+            '\r' => String::new(),
+            c => c.to_string(),
+        })
+        .collect();
+    format!("\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::*;
+
+    #[test]
+    fn golden() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let acc = inner.reg("acc", 4);
+        acc.default_value(0u32);
+        acc.drive_next(acc.value + inner.input("i", 4));
+        inner.output("o", acc.value);
+
+        let m = c.module("Top \"quoted\"");
+        let i = m.input("i", 4);
+        let inner1 = m.instance("inner1", "Inner");
+        inner1.drive_input("i", i);
+        let mem = m.mem("mem", 1, 4);
+        mem.initial_contents(&[0u32, 0]);
+        mem.write_port(m.input("wa", 1), i, m.high());
+        let sel = m.input("sel", 1).name("sel");
+        // `i` fans out to the instance, the memory, and the mux
+        m.output(
+            "o",
+            sel.mux(inner1.output("o"), mem.read_port_comb(m.low()) & i),
+        );
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            include_str!("dot/golden/small.dot")
+        );
+    }
+
+    #[test]
+    fn max_nodes() {
+        let c = Context::new();
+
+        let lane = c.module("Lane");
+        let r = lane.reg("r", 16);
+        r.default_value(0u32);
+        r.drive_next((r.value ^ lane.input("i", 16)) + lane.lit(1u32, 16));
+        lane.output("o", r.value);
+
+        let m = c.module("Big");
+        let mut acc = m.input("i", 16);
+        for index in 0..50 {
+            let lane = m.instance(format!("lane_{}", index), "Lane");
+            lane.drive_input("i", acc);
+            acc = lane
+                .output("o")
+                .name(format!("lane_{}_o \\ \"{{}}\"", index));
+        }
+        m.output("o", acc);
+
+        let mut w = Vec::new();
+        generate_with_options(
+            m,
+            &GenerationOptions {
+                max_nodes: Some(100),
+            },
+            &mut w,
+        )
+        .unwrap();
+        let dot = String::from_utf8(w).unwrap();
+
+        // Braces inside quoted strings don't count
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut chars = dot.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if in_string => {
+                    let _ = chars.next();
+                }
+                '"' => in_string = !in_string,
+                '{' if !in_string => depth += 1,
+                '}' if !in_string => {
+                    assert!(depth > 0);
+                    depth -= 1;
+                }
+                _ => (),
+            }
+        }
+        assert!(!in_string);
+        assert_eq!(depth, 0);
+
+        assert!(dot.contains("omitted [label="));
+        let node_count = dot
+            .lines()
+            .filter(|line| line.contains(" [label=") && !line.contains(" -> "))
+            .count();
+        // Including the note about omitted nodes
+        assert_eq!(node_count, 101);
+        assert!(!dot.contains("n100 "));
+        for line in dot.lines().filter(|line| line.contains("label=")) {
+            assert!(line.contains("label=\""));
+        }
+    }
+}
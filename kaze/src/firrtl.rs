@@ -0,0 +1,1138 @@
+//! [FIRRTL](https://github.com/chipsalliance/firrtl-spec) code generation.
+
+use crate::analysis::*;
+use crate::code_writer;
+use crate::constant_folding::*;
+use crate::graph;
+use crate::validation::*;
+
+use std::collections::HashMap;
+use std::io::{Result, Write};
+
+/// Generates a FIRRTL circuit for the `Module` `m` and writes it to `w`.
+///
+/// Unlike [`verilog::generate`], which only generates `m` itself, the circuit contains a FIRRTL module for each `Module` in `m`'s hierarchy (instantiated `Module`s first), with `m` as its main module.
+///
+/// Names match the ones used by generated Verilog, so that the two can be compared (for example, after lowering the circuit with `firtool`).
+/// Each module has `clk` and `reset_n` ports (unless its hierarchy is purely combinational, and renamed as specified by [`Module::clock_port_name`] and [`Module::reset_port_name`]) followed by its inputs and outputs, [`Instance`]s and [`Mem`]s keep their names, and [`Register`]s are called `__reg_{name}_{index}`.
+/// Names that aren't valid FIRRTL identifiers are changed as described in [`identifier`].
+///
+/// [`Register`]s with initial values are reset asynchronously while `reset_n` is low, as they are in generated Verilog.
+/// [`Mem`]s are declared with a read latency of 0 and a write latency of 1, and each synchronous read port is modeled with an explicit register that's only updated while the port is enabled, which keeps its value stable between reads and implements the `Mem`'s [`ReadDuringWrite`] policy.
+/// FIRRTL has no syntax for initializing memories, so initial contents aren't emitted.
+/// Extern `Module`s are declared as `extmodule`s that refer to the externally-defined module by name.
+///
+/// [`identifier`]: ./fn.identifier.html
+/// [`Instance`]: ../struct.Instance.html
+/// [`Instance::parameter`]: ../struct.Instance.html#method.parameter
+/// [`Latch`]: ../struct.Latch.html
+/// [`Mem`]: ../struct.Mem.html
+/// [`Module::clock_port_name`]: ../struct.Module.html#method.clock_port_name
+/// [`Module::reset_port_name`]: ../struct.Module.html#method.reset_port_name
+/// [`ReadDuringWrite`]: ../enum.ReadDuringWrite.html
+/// [`Register`]: ../struct.Register.html
+/// [`verilog::generate`]: ../verilog/fn.generate.html
+///
+/// # Panics
+///
+/// Panics if `m`'s hierarchy contains errors (for example, undriven registers or combinational loops), if any instance of an extern module in `m`'s hierarchy has parameters (see [`Instance::parameter`]), since FIRRTL can only specify parameters on an `extmodule` declaration, or if `m`'s hierarchy contains any [`Latch`]es, which FIRRTL can't express.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let inner = c.module("Inner");
+/// inner.output("o", !inner.input("i", 8));
+///
+/// let m = c.module("Top");
+/// let inner = m.instance("inner", "Inner");
+/// inner.drive_input("i", m.input("i", 8));
+/// m.output("o", inner.output("o"));
+///
+/// let mut w = Vec::new();
+/// firrtl::generate(m, &mut w).unwrap();
+/// let firrtl = String::from_utf8(w).unwrap();
+/// assert!(firrtl.contains("circuit Top :"));
+/// assert!(firrtl.contains("module Inner :"));
+/// assert!(firrtl.contains("inst inner of Inner"));
+/// ```
+pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
+    validate_module_hierarchy(m);
+
+    let mut w = code_writer::CodeWriter::new(w);
+
+    w.append_line("FIRRTL version 3.3.0")?;
+    w.append_line(&format!("circuit {} :", identifier(&m.name)))?;
+    w.indent();
+    for module in hierarchy_modules(m) {
+        generate_module(module, &mut w)?;
+    }
+    w.unindent()?;
+
+    Ok(())
+}
+
+/// Returns the FIRRTL identifier that generated code uses for `name`, such as the name of a `Module`, input, output, instance, or [`Mem`].
+///
+/// Any characters other than ASCII letters, digits, and `_` are replaced with `_`, as FIRRTL has no way to represent them.
+/// Names that start with a digit or that are FIRRTL keywords are emitted as literal identifiers, delimited by backticks.
+///
+/// [`Mem`]: ../struct.Mem.html
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// assert_eq!(firrtl::identifier("data"), "data");
+/// assert_eq!(firrtl::identifier("node"), "`node`");
+/// assert_eq!(firrtl::identifier("Mod-1"), "Mod_1");
+/// assert_eq!(firrtl::identifier("1st"), "`1st`");
+/// ```
+pub fn identifier(name: &str) -> String {
+    // Sorted, so they can be binary searched
+    const KEYWORDS: &[&str] = &[
+        "Analog",
+        "AsyncReset",
+        "Clock",
+        "FIRRTL",
+        "Probe",
+        "RWProbe",
+        "Reset",
+        "SInt",
+        "UInt",
+        "assert",
+        "assume",
+        "attach",
+        "circuit",
+        "cmem",
+        "connect",
+        "const",
+        "cover",
+        "define",
+        "else",
+        "extmodule",
+        "flip",
+        "input",
+        "inst",
+        "instchoice",
+        "intmodule",
+        "invalidate",
+        "layer",
+        "layerblock",
+        "match",
+        "mem",
+        "module",
+        "node",
+        "of",
+        "option",
+        "output",
+        "printf",
+        "propassign",
+        "public",
+        "reg",
+        "regreset",
+        "skip",
+        "smem",
+        "stop",
+        "type",
+        "when",
+        "wire",
+    ];
+
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if name.starts_with(|c: char| c.is_ascii_digit()) || KEYWORDS.binary_search(&&*name).is_ok() {
+        return format!("`{}`", name);
+    }
+
+    name
+}
+
+struct ModuleDecls<'a> {
+    regs: HashMap<&'a graph::Signal<'a>, String>,
+    read_port_values: HashMap<
+        (
+            &'a graph::Mem<'a>,
+            &'a graph::Signal<'a>,
+            &'a graph::Signal<'a>,
+        ),
+        String,
+    >,
+    comb_read_port_data: HashMap<&'a graph::Signal<'a>, String>,
+}
+
+struct MemPorts<'a> {
+    mem: &'a graph::Mem<'a>,
+    name: String,
+    lane_bit_width: Option<u32>,
+    read_ports: Vec<(&'a graph::Signal<'a>, &'a graph::Signal<'a>, String, String)>,
+    comb_read_ports: Vec<(&'a graph::Signal<'a>, String)>,
+}
+
+enum Statement {
+    Line(String),
+    When(String, Vec<String>),
+}
+
+// Extern modules are declared with only their ports, and refer to the externally-defined module by name
+fn generate_extmodule<'a, W: Write>(
+    m: &'a graph::Module<'a>,
+    w: &mut code_writer::CodeWriter<W>,
+) -> Result<()> {
+    w.append_newline()?;
+    w.append_line(&format!("extmodule {} :", identifier(&m.name)))?;
+    w.indent();
+    for (name, input) in m.inputs.borrow().iter() {
+        w.append_line(&format!(
+            "input {} : UInt<{}>",
+            identifier(name),
+            input.bit_width()
+        ))?;
+    }
+    for (name, output) in m.outputs.borrow().iter() {
+        w.append_line(&format!(
+            "output {} : UInt<{}>",
+            identifier(name),
+            output.bit_width()
+        ))?;
+    }
+    w.append_line(&format!("defname = {}", m.name))?;
+    w.unindent()?;
+
+    Ok(())
+}
+
+fn generate_module<'a, W: Write>(
+    m: &'a graph::Module<'a>,
+    w: &mut code_writer::CodeWriter<W>,
+) -> Result<()> {
+    if m.is_extern {
+        return generate_extmodule(m, w);
+    }
+
+    let mut c = Compiler::new();
+    let mut decls = ModuleDecls {
+        regs: HashMap::new(),
+        read_port_values: HashMap::new(),
+        comb_read_port_data: HashMap::new(),
+    };
+    let mut decl_lines = Vec::new();
+
+    let has_clock = uses_clock(m);
+    let (clock, reset) = if has_clock {
+        let (clock, reset) = clock_reset_port_names(m, "clk", "reset_n");
+        (identifier(&clock), identifier(&reset))
+    } else {
+        Default::default()
+    };
+
+    let registers = m.registers.borrow();
+    let register_data = registers
+        .iter()
+        .map(|reg| match reg.data {
+            graph::SignalData::Reg { data } => data,
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    if register_data
+        .iter()
+        .any(|data| data.initial_value.borrow().is_some())
+    {
+        decl_lines.push(format!("node __reset = asAsyncReset(not({}))", reset));
+    }
+
+    if let Some(latch) = m.latches.borrow().first() {
+        let name = match latch.data {
+            graph::SignalData::Latch { data } => &data.name,
+            _ => unreachable!(),
+        };
+        panic!("Cannot generate FIRRTL code for module \"{}\" because it contains a latch called \"{}\", which FIRRTL can't express.", m.name, name);
+    }
+
+    for instance in m.instances.borrow().iter() {
+        if !instance.parameters.borrow().is_empty() {
+            panic!("Cannot generate FIRRTL code for module \"{}\" because its instance \"{}\" of extern module \"{}\" has parameters, which FIRRTL can't specify per instance.", m.name, instance.name, instance.instantiated_module.name);
+        }
+        decl_lines.push(format!(
+            "inst {} of {}",
+            identifier(&instance.name),
+            identifier(&instance.instantiated_module.name)
+        ));
+    }
+
+    for (index, (reg, data)) in registers.iter().zip(register_data.iter()).enumerate() {
+        let name = identifier(&format!("__reg_{}_{}", data.name, index));
+        decl_lines.push(match *data.initial_value.borrow() {
+            Some(ref initial_value) => format!(
+                "regreset {} : UInt<{}>, {}, __reset, {}",
+                name,
+                data.bit_width,
+                clock,
+                literal(initial_value.numeric_value(), data.bit_width)
+            ),
+            None => format!("reg {} : UInt<{}>, {}", name, data.bit_width, clock),
+        });
+        decls.regs.insert(*reg, name);
+    }
+
+    let mut mem_ports = Vec::new();
+    for mem in m.mems.borrow().iter() {
+        let name = identifier(&mem.name);
+        let mem_name = format!("__mem_{}", mem.name);
+
+        // Masks with more than one bit write lanes of the element independently, which FIRRTL can only express with a vector data type
+        let lane_bit_width = mem
+            .write_ports
+            .borrow()
+            .iter()
+            .filter_map(|(_, _, _, mask)| *mask)
+            .filter(|mask| mask.bit_width() > 1)
+            .map(|mask| mem.element_bit_width / mask.bit_width())
+            .fold(None, |acc, lane_bit_width| {
+                Some(acc.map_or(lane_bit_width, |acc| gcd(acc, lane_bit_width)))
+            });
+        let data_type = match lane_bit_width {
+            Some(lane_bit_width) => format!(
+                "UInt<{}>[{}]",
+                lane_bit_width,
+                mem.element_bit_width / lane_bit_width
+            ),
+            None => format!("UInt<{}>", mem.element_bit_width),
+        };
+
+        let mut read_ports = Vec::new();
+        for (index, (address, enable)) in mem.read_ports.borrow().iter().enumerate() {
+            let key = (*mem, *address, *enable);
+            if decls.read_port_values.contains_key(&key) {
+                continue;
+            }
+            let port_name = format!("read_port_{}", index);
+            let value_name = identifier(&format!("{}_{}_value", mem_name, port_name));
+            decl_lines.push(format!(
+                "reg {} : UInt<{}>, {}",
+                value_name, mem.element_bit_width, clock
+            ));
+            decls.read_port_values.insert(key, value_name.clone());
+            read_ports.push((*address, *enable, port_name, value_name));
+        }
+
+        let mut comb_read_ports = Vec::new();
+        for signal in m.signals.borrow().iter() {
+            if let graph::SignalData::MemCombReadPortOutput {
+                mem: read_mem,
+                address,
+            } = signal.data
+            {
+                if read_mem == *mem {
+                    let port_name = format!("comb_read_port_{}", comb_read_ports.len());
+                    let data = read_data(&mut c, &name, &port_name, mem, lane_bit_width);
+                    decls.comb_read_port_data.insert(*signal, data);
+                    comb_read_ports.push((address, port_name));
+                }
+            }
+        }
+
+        decl_lines.push(format!("mem {} :", name));
+        decl_lines.push(format!("    data-type => {}", data_type));
+        decl_lines.push(format!("    depth => {}", 1u64 << mem.address_bit_width));
+        for (_, _, port_name, _) in read_ports.iter() {
+            decl_lines.push(format!("    reader => {}", port_name));
+        }
+        for (_, port_name) in comb_read_ports.iter() {
+            decl_lines.push(format!("    reader => {}", port_name));
+        }
+        for index in 0..mem.write_ports.borrow().len() {
+            decl_lines.push(format!("    writer => write_port_{}", index));
+        }
+        decl_lines.push("    read-latency => 0".to_string());
+        decl_lines.push("    write-latency => 1".to_string());
+        decl_lines.push("    read-under-write => undefined".to_string());
+
+        mem_ports.push(MemPorts {
+            mem,
+            name,
+            lane_bit_width,
+            read_ports,
+            comb_read_ports,
+        });
+    }
+
+    let mut statements = Vec::new();
+
+    for instance in m.instances.borrow().iter() {
+        let instance_name = identifier(&instance.name);
+        if uses_clock(instance.instantiated_module) {
+            let (instance_clock, instance_reset) =
+                clock_reset_port_names(instance.instantiated_module, "clk", "reset_n");
+            statements.push(Statement::Line(format!(
+                "connect {}.{}, {}",
+                instance_name,
+                identifier(&instance_clock),
+                clock
+            )));
+            statements.push(Statement::Line(format!(
+                "connect {}.{}, {}",
+                instance_name,
+                identifier(&instance_reset),
+                reset
+            )));
+        }
+        for (name, input) in instance.driven_inputs.borrow().iter() {
+            let expr = c.compile_signal(input, &decls);
+            statements.push(Statement::Line(format!(
+                "connect {}.{}, {}",
+                instance_name,
+                identifier(name),
+                expr
+            )));
+        }
+    }
+
+    for mem_ports in mem_ports.iter() {
+        let mem = mem_ports.mem;
+        let name = &mem_ports.name;
+
+        for (address, enable, port_name, value_name) in mem_ports.read_ports.iter() {
+            let address = c.compile_signal(address, &decls);
+            let enable = c.compile_signal(enable, &decls);
+            let port = format!("{}.{}", name, port_name);
+            statements.push(Statement::Line(format!("connect {}.clk, {}", port, clock)));
+            statements.push(Statement::Line(format!(
+                "connect {}.addr, {}",
+                port, address
+            )));
+            statements.push(Statement::Line(format!("connect {}.en, {}", port, enable)));
+
+            let mut value = read_data(&mut c, name, port_name, mem, mem_ports.lane_bit_width);
+            if let graph::ReadDuringWrite::New = *mem.read_during_write.borrow() {
+                // Write-first; forward colliding write values to the read data register.
+                // Write ports specified later take priority, so they're applied last.
+                for (write_address, write_value, write_enable, mask) in
+                    mem.write_ports.borrow().iter()
+                {
+                    let write_address = c.compile_signal(write_address, &decls);
+                    let write_value = c.compile_signal(write_value, &decls);
+                    let write_enable = c.compile_signal(write_enable, &decls);
+                    let mask = mask.map(|mask| (c.compile_signal(mask, &decls), mask.bit_width()));
+                    let merged = merge_write(&value, &write_value, mask, mem.element_bit_width);
+                    value = c.gen_node(format!(
+                        "mux(and({}, eq({}, {})), {}, {})",
+                        write_enable, write_address, address, merged, value
+                    ));
+                }
+            }
+            statements.push(Statement::When(
+                enable,
+                vec![format!("connect {}, {}", value_name, value)],
+            ));
+        }
+
+        for (address, port_name) in mem_ports.comb_read_ports.iter() {
+            let address = c.compile_signal(address, &decls);
+            let port = format!("{}.{}", name, port_name);
+            statements.push(Statement::Line(format!("connect {}.clk, {}", port, clock)));
+            statements.push(Statement::Line(format!(
+                "connect {}.addr, {}",
+                port, address
+            )));
+            statements.push(Statement::Line(format!(
+                "connect {}.en, {}",
+                port,
+                literal(1, 1)
+            )));
+        }
+
+        for (index, (address, value, enable, mask)) in mem.write_ports.borrow().iter().enumerate() {
+            let address = c.compile_signal(address, &decls);
+            let value = c.compile_signal(value, &decls);
+            let enable = c.compile_signal(enable, &decls);
+            let mask = mask.map(|mask| (c.compile_signal(mask, &decls), mask.bit_width()));
+            let port = format!("{}.write_port_{}", name, index);
+            statements.push(Statement::Line(format!("connect {}.clk, {}", port, clock)));
+            statements.push(Statement::Line(format!(
+                "connect {}.addr, {}",
+                port, address
+            )));
+            statements.push(Statement::Line(format!("connect {}.en, {}", port, enable)));
+            match mem_ports.lane_bit_width {
+                Some(lane_bit_width) => {
+                    for lane_index in 0..mem.element_bit_width / lane_bit_width {
+                        let range_low = lane_index * lane_bit_width;
+                        statements.push(Statement::Line(format!(
+                            "connect {}.data[{}], bits({}, {}, {})",
+                            port,
+                            lane_index,
+                            value,
+                            range_low + lane_bit_width - 1,
+                            range_low
+                        )));
+                        let lane_mask = match mask {
+                            Some((ref mask, 1)) => mask.clone(),
+                            Some((ref mask, mask_bit_width)) => {
+                                let mask_index =
+                                    range_low / (mem.element_bit_width / mask_bit_width);
+                                format!("bits({}, {}, {})", mask, mask_index, mask_index)
+                            }
+                            None => literal(1, 1),
+                        };
+                        statements.push(Statement::Line(format!(
+                            "connect {}.mask[{}], {}",
+                            port, lane_index, lane_mask
+                        )));
+                    }
+                }
+                None => {
+                    statements.push(Statement::Line(format!("connect {}.data, {}", port, value)));
+                    statements.push(Statement::Line(format!(
+                        "connect {}.mask, {}",
+                        port,
+                        mask.map_or_else(|| literal(1, 1), |(mask, _)| mask)
+                    )));
+                }
+            }
+        }
+    }
+
+    for reg in registers.iter() {
+        let next = match reg.data {
+            graph::SignalData::Reg { data } => data.next.borrow().unwrap(),
+            _ => unreachable!(),
+        };
+        let expr = c.compile_signal(next, &decls);
+        statements.push(Statement::Line(format!(
+            "connect {}, {}",
+            decls.regs[reg], expr
+        )));
+    }
+
+    for (name, output) in m.outputs.borrow().iter() {
+        let expr = c.compile_signal(output, &decls);
+        statements.push(Statement::Line(format!(
+            "connect {}, {}",
+            identifier(name),
+            expr
+        )));
+    }
+
+    for (condition, message) in m.assertions.borrow().iter() {
+        let condition = c.compile_signal(condition, &decls);
+        statements.push(Statement::Line(format!(
+            "assert({}, {}, {}, \"{}\")",
+            clock,
+            condition,
+            literal(1, 1),
+            escape_string(message)
+        )));
+    }
+    for condition in m.assumptions.borrow().iter() {
+        let condition = c.compile_signal(condition, &decls);
+        statements.push(Statement::Line(format!(
+            "assume({}, {}, {}, \"Assumption violated\")",
+            clock,
+            condition,
+            literal(1, 1)
+        )));
+    }
+    for condition in m.covers.borrow().iter() {
+        let condition = c.compile_signal(condition, &decls);
+        statements.push(Statement::Line(format!(
+            "cover({}, {}, {}, \"\")",
+            clock,
+            condition,
+            literal(1, 1)
+        )));
+    }
+    for (label, signal) in m.traces.borrow().iter() {
+        let expr = c.compile_signal(signal, &decls);
+        statements.push(Statement::Line(format!(
+            "printf({}, {}, \"{} = %x\\n\", {})",
+            clock,
+            literal(1, 1),
+            escape_string(label),
+            expr
+        )));
+    }
+
+    w.append_newline()?;
+    w.append_line(&format!("module {} :", identifier(&m.name)))?;
+    w.indent();
+
+    if has_clock {
+        w.append_line(&format!("input {} : Clock", clock))?;
+        w.append_line(&format!("input {} : UInt<1>", reset))?;
+    }
+    for (name, input) in m.inputs.borrow().iter() {
+        w.append_line(&format!(
+            "input {} : UInt<{}>",
+            identifier(name),
+            input.bit_width()
+        ))?;
+    }
+    for (name, output) in m.outputs.borrow().iter() {
+        w.append_line(&format!(
+            "output {} : UInt<{}>",
+            identifier(name),
+            output.bit_width()
+        ))?;
+    }
+
+    if decl_lines.is_empty() && c.nodes.is_empty() && statements.is_empty() {
+        w.append_newline()?;
+        w.append_line("skip")?;
+    }
+
+    if !decl_lines.is_empty() {
+        w.append_newline()?;
+        for line in decl_lines.iter() {
+            w.append_line(line)?;
+        }
+    }
+
+    if !c.nodes.is_empty() {
+        w.append_newline()?;
+        for (name, expr) in c.nodes.iter() {
+            w.append_line(&format!("node {} = {}", name, expr))?;
+        }
+    }
+
+    if !statements.is_empty() {
+        w.append_newline()?;
+        for statement in statements.iter() {
+            match statement {
+                Statement::Line(line) => w.append_line(line)?,
+                Statement::When(condition, lines) => {
+                    w.append_line(&format!("when {} :", condition))?;
+                    w.indent();
+                    for line in lines.iter() {
+                        w.append_line(line)?;
+                    }
+                    w.unindent()?;
+                }
+            }
+        }
+    }
+
+    w.unindent()?;
+
+    Ok(())
+}
+
+struct Compiler<'a> {
+    constant_folder: ConstantFolder<'a>,
+    signal_exprs: HashMap<&'a graph::Signal<'a>, String>,
+    nodes: Vec<(String, String)>,
+}
+
+impl<'a> Compiler<'a> {
+    fn new() -> Compiler<'a> {
+        Compiler {
+            constant_folder: ConstantFolder::new(),
+            signal_exprs: HashMap::new(),
+            nodes: Vec::new(),
+        }
+    }
+
+    fn gen_node(&mut self, expr: String) -> String {
+        let name = format!("__temp_{}", self.nodes.len());
+        self.nodes.push((name.clone(), expr));
+        name
+    }
+
+    fn compile_signal(&mut self, signal: &'a graph::Signal<'a>, decls: &ModuleDecls<'a>) -> String {
+        // Operands are compiled bottom-up with an explicit stack first (in the same order recursion would compile them), so that compiling `signal` itself never recurses more than one level deep, regardless of how deep the graph is
+        let mut stack = vec![(signal, false)];
+        while let Some((signal, operands_compiled)) = stack.pop() {
+            if self.constant_folder.evaluate(signal).is_some()
+                || self.signal_exprs.contains_key(&signal)
+            {
+                continue;
+            }
+            if operands_compiled {
+                self.compile_single_signal(signal, decls);
+                continue;
+            }
+            stack.push((signal, true));
+            let operands = self.operands(signal);
+            stack.extend(operands.into_iter().rev().map(|operand| (operand, false)));
+        }
+
+        self.compile_single_signal(signal, decls)
+    }
+
+    // The signals that compiling `signal` compiles first, in order
+    fn operands(&mut self, signal: &'a graph::Signal<'a>) -> Vec<&'a graph::Signal<'a>> {
+        match signal.data {
+            graph::SignalData::Lit { .. }
+            | graph::SignalData::Input { .. }
+            | graph::SignalData::Reg { .. }
+            | graph::SignalData::Latch { .. }
+            | graph::SignalData::InstanceOutput { .. }
+            | graph::SignalData::MemReadPortOutput { .. }
+            | graph::SignalData::MemCombReadPortOutput { .. } => Vec::new(),
+
+            graph::SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            } => match self.constant_folder.evaluate(cond) {
+                Some(cond) => vec![if cond.numeric_value() != 0 {
+                    when_true
+                } else {
+                    when_false
+                }],
+                None => vec![cond, when_true, when_false],
+            },
+
+            _ => signal.dependencies(),
+        }
+    }
+
+    fn compile_single_signal(
+        &mut self,
+        signal: &'a graph::Signal<'a>,
+        decls: &ModuleDecls<'a>,
+    ) -> String {
+        if let Some(value) = self.constant_folder.evaluate(signal) {
+            return literal(value.numeric_value(), signal.bit_width());
+        }
+
+        if !self.signal_exprs.contains_key(&signal) {
+            let expr = match signal.data {
+                graph::SignalData::Lit {
+                    ref value,
+                    bit_width,
+                } => literal(value.numeric_value(), bit_width),
+
+                graph::SignalData::Input { name, .. } => identifier(name),
+
+                graph::SignalData::Reg { .. } => decls.regs[&signal].clone(),
+
+                graph::SignalData::Latch { .. } => unreachable!(),
+
+                graph::SignalData::UnOp { source, op } => {
+                    let source = self.compile_signal(source, decls);
+                    self.gen_node(match op {
+                        graph::UnOp::Not => format!("not({})", source),
+                    })
+                }
+                graph::SignalData::SimpleBinOp { lhs, rhs, op } => {
+                    let lhs = self.compile_signal(lhs, decls);
+                    let rhs = self.compile_signal(rhs, decls);
+                    self.gen_node(format!(
+                        "{}({}, {})",
+                        match op {
+                            graph::SimpleBinOp::BitAnd => "and",
+                            graph::SimpleBinOp::BitOr => "or",
+                            graph::SimpleBinOp::BitXor => "xor",
+                        },
+                        lhs,
+                        rhs
+                    ))
+                }
+                graph::SignalData::AdditiveBinOp { lhs, rhs, op } => {
+                    let lhs = self.compile_signal(lhs, decls);
+                    let rhs = self.compile_signal(rhs, decls);
+                    // FIRRTL widens sums and differences by a bit; drop it to wrap like kaze does
+                    self.gen_node(format!(
+                        "tail({}({}, {}), 1)",
+                        match op {
+                            graph::AdditiveBinOp::Add => "add",
+                            graph::AdditiveBinOp::Sub => "sub",
+                        },
+                        lhs,
+                        rhs
+                    ))
+                }
+                graph::SignalData::ComparisonBinOp { lhs, rhs, op } => {
+                    let mut lhs = self.compile_signal(lhs, decls);
+                    let mut rhs = self.compile_signal(rhs, decls);
+                    match op {
+                        graph::ComparisonBinOp::GreaterThanEqualSigned
+                        | graph::ComparisonBinOp::GreaterThanSigned
+                        | graph::ComparisonBinOp::LessThanEqualSigned
+                        | graph::ComparisonBinOp::LessThanSigned => {
+                            lhs = format!("asSInt({})", lhs);
+                            rhs = format!("asSInt({})", rhs);
+                        }
+                        _ => (),
+                    }
+                    self.gen_node(format!(
+                        "{}({}, {})",
+                        match op {
+                            graph::ComparisonBinOp::Equal => "eq",
+                            graph::ComparisonBinOp::NotEqual => "neq",
+                            graph::ComparisonBinOp::LessThan
+                            | graph::ComparisonBinOp::LessThanSigned => "lt",
+                            graph::ComparisonBinOp::LessThanEqual
+                            | graph::ComparisonBinOp::LessThanEqualSigned => "leq",
+                            graph::ComparisonBinOp::GreaterThan
+                            | graph::ComparisonBinOp::GreaterThanSigned => "gt",
+                            graph::ComparisonBinOp::GreaterThanEqual
+                            | graph::ComparisonBinOp::GreaterThanEqualSigned => "geq",
+                        },
+                        lhs,
+                        rhs
+                    ))
+                }
+                graph::SignalData::ShiftBinOp { lhs, rhs, op } => {
+                    let bit_width = lhs.bit_width();
+                    let rhs_bit_width = rhs.bit_width();
+                    let lhs = self.compile_signal(lhs, decls);
+                    let rhs = self.compile_signal(rhs, decls);
+                    match op {
+                        graph::ShiftBinOp::Shl => {
+                            // dshl widens its result by 2^(shift amount bits) - 1 bits, so only the low shift amount bits
+                            //  that can produce a nonzero result are passed to it, and any higher bits produce 0 directly
+                            let shift_bit_width = (32 - (bit_width - 1).leading_zeros()).max(1);
+                            if rhs_bit_width <= shift_bit_width {
+                                self.gen_node(format!(
+                                    "bits(dshl({}, {}), {}, 0)",
+                                    lhs,
+                                    rhs,
+                                    bit_width - 1
+                                ))
+                            } else {
+                                self.gen_node(format!(
+                                    "mux(orr(bits({}, {}, {})), {}, bits(dshl({}, bits({}, {}, 0)), {}, 0))",
+                                    rhs,
+                                    rhs_bit_width - 1,
+                                    shift_bit_width,
+                                    literal(0, bit_width),
+                                    lhs,
+                                    rhs,
+                                    shift_bit_width - 1,
+                                    bit_width - 1
+                                ))
+                            }
+                        }
+                        graph::ShiftBinOp::Shr => self.gen_node(format!("dshr({}, {})", lhs, rhs)),
+                        graph::ShiftBinOp::ShrArithmetic => {
+                            self.gen_node(format!("asUInt(dshr(asSInt({}), {}))", lhs, rhs))
+                        }
+                    }
+                }
+
+                graph::SignalData::Bits {
+                    source,
+                    range_high,
+                    range_low,
+                } => {
+                    let source = self.compile_signal(source, decls);
+                    self.gen_node(format!("bits({}, {}, {})", source, range_high, range_low))
+                }
+
+                graph::SignalData::Repeat { source, count } => {
+                    let source = self.compile_signal(source, decls);
+                    self.gen_node(cat(&vec![source; count as usize]))
+                }
+                graph::SignalData::Concat { lhs, rhs } => {
+                    let lhs = self.compile_signal(lhs, decls);
+                    let rhs = self.compile_signal(rhs, decls);
+                    self.gen_node(format!("cat({}, {})", lhs, rhs))
+                }
+
+                graph::SignalData::Mux {
+                    cond,
+                    when_true,
+                    when_false,
+                } => match self.constant_folder.evaluate(cond) {
+                    Some(cond) => {
+                        let source = if cond.numeric_value() != 0 {
+                            when_true
+                        } else {
+                            when_false
+                        };
+                        self.compile_signal(source, decls)
+                    }
+                    None => {
+                        let cond = self.compile_signal(cond, decls);
+                        let when_true = self.compile_signal(when_true, decls);
+                        let when_false = self.compile_signal(when_false, decls);
+                        self.gen_node(format!("mux({}, {}, {})", cond, when_true, when_false))
+                    }
+                },
+
+                graph::SignalData::InstanceOutput { instance, name } => {
+                    format!("{}.{}", identifier(&instance.name), identifier(name))
+                }
+
+                graph::SignalData::MemReadPortOutput {
+                    mem,
+                    address,
+                    enable,
+                } => decls.read_port_values[&(mem, address, enable)].clone(),
+                graph::SignalData::MemCombReadPortOutput { .. } => {
+                    decls.comb_read_port_data[&signal].clone()
+                }
+            };
+            self.signal_exprs.insert(signal, expr);
+        }
+
+        self.signal_exprs[&signal].clone()
+    }
+}
+
+fn literal(value: u128, bit_width: u32) -> String {
+    format!("UInt<{}>(0h{:x})", bit_width, value)
+}
+
+// Concatenates `exprs`, with the first one in the most significant bits
+fn cat(exprs: &[String]) -> String {
+    match exprs {
+        [expr] => expr.clone(),
+        [expr, rest @ ..] => format!("cat({}, {})", expr, cat(rest)),
+        [] => unreachable!(),
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn read_data<'a>(
+    c: &mut Compiler<'a>,
+    mem_name: &str,
+    port_name: &str,
+    mem: &graph::Mem<'a>,
+    lane_bit_width: Option<u32>,
+) -> String {
+    let data = format!("{}.{}.data", mem_name, port_name);
+    match lane_bit_width {
+        Some(lane_bit_width) => {
+            let lanes = (0..mem.element_bit_width / lane_bit_width)
+                .rev()
+                .map(|lane_index| format!("{}[{}]", data, lane_index))
+                .collect::<Vec<_>>();
+            c.gen_node(cat(&lanes))
+        }
+        None => data,
+    }
+}
+
+// Returns the element that results from writing `value` over `old` with an optional `(mask, mask_bit_width)`
+fn merge_write(
+    old: &str,
+    value: &str,
+    mask: Option<(String, u32)>,
+    element_bit_width: u32,
+) -> String {
+    match mask {
+        Some((mask, 1)) => format!("mux({}, {}, {})", mask, value, old),
+        Some((mask, mask_bit_width)) => {
+            let lane_bit_width = element_bit_width / mask_bit_width;
+            let lanes = (0..mask_bit_width)
+                .rev()
+                .map(|lane_index| {
+                    let range_low = lane_index * lane_bit_width;
+                    let range_high = range_low + lane_bit_width - 1;
+                    format!(
+                        "mux(bits({}, {}, {}), bits({}, {}, {}), bits({}, {}, {}))",
+                        mask,
+                        lane_index,
+                        lane_index,
+                        value,
+                        range_high,
+                        range_low,
+                        old,
+                        range_high,
+                        range_low
+                    )
+                })
+                .collect::<Vec<_>>();
+            cat(&lanes)
+        }
+        None => value.into(),
+    }
+}
+
+fn escape_string(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '"' => "\\\"".into(),
+            '\\' => "\\\\".into(),
+            '\n' => "\\n".into(),
+            '%' => "%%".into(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::*;
+
+    fn golden_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+        let inner = c.module("Inner");
+        let counter = inner.reg("counter", 4);
+        counter.default_value(0u32);
+        counter.drive_next(counter.value + inner.lit(1u32, 4));
+        inner.output("count", counter.value);
+        inner.output("o", !inner.input("i", 8));
+
+        let m = c.module("Golden");
+        let a = m.input("a", 8);
+        let b = m.input("b", 8);
+        let sel = m.input("sel", 1);
+        let inner = m.instance("inner", "Inner");
+        inner.drive_input("i", a ^ b);
+
+        let acc = m.reg("acc", 8);
+        acc.drive_next(m.mux(sel, acc.value - b, (a & b) | inner.output("o")));
+
+        let ram = m.mem("ram", 2, 16);
+        ram.read_during_write(ReadDuringWrite::New);
+        ram.write_port_masked(a.bits(1, 0), a.concat(b), sel, b.bits(1, 0));
+        let read_data = ram.read_port(b.bits(1, 0), sel);
+        let lut = m.mem("lut", 2, 4);
+        lut.initial_contents(&[1u32, 2, 4, 8]);
+        lut.write_port(a.bits(3, 2), b.bits(3, 0), sel);
+
+        m.output("read_data", read_data);
+        m.output("lut_data", lut.read_port_comb(a.bits(1, 0)));
+        m.output("count", inner.output("count"));
+        m.output("acc", acc.value);
+        m.output("shl", a << b);
+        m.output("shr", a >> b.bits(2, 0));
+        m.output("sra", a.shr_arithmetic(b));
+        m.output("lt", a.lt_signed(b).concat(a.ge(b)).concat(a.eq(b)));
+        m.output("rep", sel.repeat(3));
+        m.assert(a.ne(m.lit(0xffu32, 8)), "a is never \"all ones\"");
+        m.trace("sum", a + b);
+
+        m
+    }
+
+    #[test]
+    fn golden_output() {
+        let c = Context::new();
+        let m = golden_module(&c);
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            include_str!("firrtl/golden.fir")
+        );
+    }
+
+    #[test]
+    fn extern_module() {
+        let c = Context::new();
+
+        let bram = c.extern_module("VendorBram");
+        bram.input("addr", 10);
+        bram.extern_output("data", 32);
+
+        let m = c.module("Top");
+        let bram = m.instance("bram", "VendorBram");
+        bram.drive_input("addr", m.input("addr", 10));
+        m.output("data", bram.output("data"));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let firrtl = String::from_utf8(w).unwrap();
+        assert!(firrtl.contains(
+            "    extmodule VendorBram :\n        input addr : UInt<10>\n        output data : UInt<32>\n        defname = VendorBram\n"
+        ));
+        assert!(firrtl.contains("inst bram of VendorBram"));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate FIRRTL code for module \"Top\" because its instance \"bram\" of extern module \"VendorBram\" has parameters, which FIRRTL can't specify per instance."
+    )]
+    fn extern_module_parameters_error() {
+        let c = Context::new();
+
+        let bram = c.extern_module("VendorBram");
+        bram.input("addr", 10);
+        bram.extern_output("data", 32);
+
+        let m = c.module("Top");
+        let bram = m.instance("bram", "VendorBram");
+        bram.parameter("INIT_FILE", "rom.hex");
+        bram.drive_input("addr", m.input("addr", 10));
+        m.output("data", bram.output("data"));
+
+        // Panic
+        generate(m, Vec::new()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate FIRRTL code for module \"Top\" because it contains a latch called \"l\", which FIRRTL can't express."
+    )]
+    fn latch_error() {
+        let c = Context::new();
+
+        let m = c.module("Top");
+        let l = m.latch("l", 4);
+        l.drive_data(m.input("data", 4));
+        l.drive_enable(m.input("enable", 1));
+        m.output("o", l.value);
+
+        // Panic
+        generate(m, Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn clock_reset_port_names() {
+        let c = Context::new();
+
+        let child = c.module("Child");
+        child.clock_port_name("i_clk");
+        child.reset_port_name("i_rst_n");
+        let r = child.reg("r", 4);
+        r.default_value(0u32);
+        r.drive_next(child.input("i", 4));
+        child.output("o", r.value);
+
+        let m = c.module("Parent");
+        let child = m.instance("child", "Child");
+        child.drive_input("i", m.input("i", 4));
+        m.output("o", child.output("o"));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let firrtl = String::from_utf8(w).unwrap();
+        assert!(firrtl.contains("input i_clk : Clock"));
+        assert!(firrtl.contains("node __reset = asAsyncReset(not(i_rst_n))"));
+        assert!(firrtl.contains("regreset __reg_r_0 : UInt<4>, i_clk, __reset, UInt<4>(0h0)"));
+        assert!(firrtl.contains("connect child.i_clk, clk"));
+        assert!(firrtl.contains("connect child.i_rst_n, reset_n"));
+    }
+
+    #[test]
+    fn deep_signal_chain() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+        // A chain of ~100k signals, which overflows the stack if code generation recurses through it
+        let mut x = a;
+        for _ in 0..34_000 {
+            x = (x + a).bits(6, 0).concat(a.bit(0));
+        }
+        m.output("o", x);
+
+        generate(m, std::io::sink()).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+//! Helpers for golden-file regression testing of generated code.
+//!
+//! Golden-file tests compare generated code against known-good ("golden") copies checked into a repository, so that any change to the generated code, whether caused by a design change or by a new version of kaze, shows up as a readable diff.
+//! Setting the `UPDATE_GOLDENS` environment variable to `1` (eg. `UPDATE_GOLDENS=1 cargo test`) rewrites the golden files with the current output instead of comparing against them.
+
+use crate::graph;
+use crate::verilog;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// The name of the environment variable which, when set to `1`, causes golden files to be updated instead of checked.
+pub const UPDATE_GOLDENS_VAR: &str = "UPDATE_GOLDENS";
+
+// Number of unchanged lines shown around each change in a diff
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Checks that `actual` matches the contents of the golden file at `path`.
+///
+/// If the `UPDATE_GOLDENS` environment variable is set to `1`, `path` (and any missing parent directories) is instead written with `actual`.
+///
+/// # Panics
+///
+/// Panics if `actual` doesn't match the golden file, with a line diff between the two in the panic message, or if the golden file can't be read (or written, when updating).
+///
+/// # Examples
+///
+/// ```no_run
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("Inverter");
+/// m.output("o", !m.input("i", 1));
+///
+/// let mut verilog = Vec::new();
+/// verilog::generate(m, &mut verilog).unwrap();
+/// golden::assert_golden(
+///     &String::from_utf8(verilog).unwrap(),
+///     concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/inverter.sv"),
+/// );
+/// ```
+pub fn assert_golden<P: AsRef<Path>>(actual: &str, path: P) {
+    check_golden(
+        actual,
+        path.as_ref(),
+        env::var(UPDATE_GOLDENS_VAR).ok().as_deref() == Some("1"),
+    );
+}
+
+fn check_golden(actual: &str, path: &Path, update: bool) {
+    if update {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap_or_else(|error| {
+                panic!(
+                    "Couldn't create directory \"{}\" for golden file: {}",
+                    parent.display(),
+                    error
+                )
+            });
+        }
+        fs::write(path, actual).unwrap_or_else(|error| {
+            panic!(
+                "Couldn't write golden file \"{}\": {}",
+                path.display(),
+                error
+            )
+        });
+        return;
+    }
+
+    let golden = fs::read_to_string(path).unwrap_or_else(|error| {
+        panic!(
+            "Couldn't read golden file \"{}\": {}. Run with {}=1 to create it.",
+            path.display(),
+            error,
+            UPDATE_GOLDENS_VAR
+        )
+    });
+    if actual != golden {
+        panic!(
+            "Generated output doesn't match golden file \"{}\" (\"-\" lines are from the golden file, \"+\" lines are generated). Run with {}=1 to update it.\n{}",
+            path.display(),
+            UPDATE_GOLDENS_VAR,
+            diff(&golden, actual)
+        );
+    }
+}
+
+/// Generates Verilog code for `m` and all of the [`Module`]s it instantiates (see [`verilog::generate_hierarchy`]), and checks that it matches the golden file at `path` using [`assert_golden`].
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`assert_golden`] and [`verilog::generate_hierarchy`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("Inverter");
+/// m.output("o", !m.input("i", 1));
+///
+/// golden::assert_verilog_golden(
+///     m,
+///     concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/inverter.sv"),
+/// );
+/// ```
+///
+/// [`assert_golden`]: ./fn.assert_golden.html
+/// [`Module`]: ../struct.Module.html
+/// [`verilog::generate_hierarchy`]: ../verilog/fn.generate_hierarchy.html
+pub fn assert_verilog_golden<'a, P: AsRef<Path>>(m: &'a graph::Module<'a>, path: P) {
+    let mut actual = Vec::new();
+    verilog::generate_hierarchy(m, &mut actual).unwrap();
+    assert_golden(&String::from_utf8(actual).unwrap(), path);
+}
+
+// Produces a line diff from `old` to `new`, showing only changed lines and a few lines of context around them
+fn diff(old: &str, new: &str) -> String {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // Longest common subsequence lengths of each pair of suffixes
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', i, old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', i, old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', i, new[j]));
+            j += 1;
+        }
+    }
+
+    let is_near_change = |index: usize| {
+        let start = index.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (index + DIFF_CONTEXT_LINES + 1).min(lines.len());
+        lines[start..end].iter().any(|&(kind, _, _)| kind != ' ')
+    };
+    let mut ret = String::new();
+    let mut skipped = false;
+    for (index, &(kind, old_index, line)) in lines.iter().enumerate() {
+        if !is_near_change(index) {
+            skipped = true;
+            continue;
+        }
+        if skipped || ret.is_empty() {
+            ret.push_str(&format!("@@ golden line {} @@\n", old_index + 1));
+            skipped = false;
+        }
+        ret.push_str(&format!("{} {}\n", kind, line));
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn diff_shows_changes_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nb\nc\nd\ne\nf\nG\nh\ni\nj\nk\n";
+
+        assert_eq!(
+            diff(old, new),
+            "@@ golden line 4 @@\n  d\n  e\n  f\n- g\n+ G\n  h\n  i\n  j\n+ k\n"
+        );
+    }
+
+    #[test]
+    fn mismatch_error() {
+        let path = env::temp_dir().join("kaze_golden_mismatch_error.sv");
+        fs::write(&path, "module A;\nendmodule\n").unwrap();
+
+        check_golden("module A;\nendmodule\n", &path, false);
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            check_golden("module B;\nendmodule\n", &path, false)
+        }))
+        .unwrap_err();
+        let message = payload.downcast::<String>().unwrap();
+        assert!(message.contains("Run with UPDATE_GOLDENS=1 to update it."));
+        assert!(message.ends_with("- module A;\n+ module B;\n  endmodule\n"));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
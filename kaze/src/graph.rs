@@ -1,17 +1,34 @@
+// Graph-building methods are `#[track_caller]`, so that each node records where in user code it was created, for use in error messages.
+// Methods that build several nodes on their caller's behalf create them in loops rather than in closures (eg. passed to `map` or `fold`), since closures don't forward the caller's location, and nodes created in them would be attributed to kaze itself instead.
+
+mod bundle;
 mod constant;
 mod context;
+mod enum_type;
+mod error;
+mod fsm;
 mod instance;
+mod interface;
+mod latch;
 mod mem;
 mod module;
 mod register;
+mod register_file;
 mod signal;
 mod sugar;
 
+pub use bundle::*;
 pub use constant::*;
 pub use context::*;
+pub use enum_type::*;
+pub use error::*;
+pub use fsm::*;
 pub use instance::*;
+pub use interface::*;
+pub use latch::*;
 pub use mem::*;
 pub use module::*;
 pub use register::*;
+pub use register_file::*;
 pub use signal::*;
 pub use sugar::*;
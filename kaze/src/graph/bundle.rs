@@ -0,0 +1,481 @@
+use super::error::*;
+use super::instance::*;
+use super::module::*;
+use super::signal::*;
+
+use std::ptr;
+
+/// Describes the fields of a [`Bundle`] as a list of names and bit widths, in order.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let cmd = BundleSpec::new()
+///     .field("valid", 1)
+///     .field("tag", 4)
+///     .field("data", 32);
+///
+/// assert_eq!(cmd.fields().len(), 3);
+/// assert_eq!(cmd.bit_width(), 37);
+/// ```
+///
+/// [`Bundle`]: ./struct.Bundle.html
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BundleSpec {
+    fields: Vec<(String, u32)>,
+}
+
+impl BundleSpec {
+    /// Creates a new `BundleSpec` without any fields.
+    pub fn new() -> BundleSpec {
+        BundleSpec::default()
+    }
+
+    /// Adds a field called `name` with `bit_width` bits after the fields already in this `BundleSpec`, and returns the resulting `BundleSpec`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `BundleSpec` already has a field called `name`, or if `bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`].
+    ///
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
+    pub fn field<S: Into<String>>(mut self, name: S, bit_width: u32) -> BundleSpec {
+        let name = name.into();
+        if self
+            .fields
+            .iter()
+            .any(|(field_name, _)| *field_name == name)
+        {
+            panic!(
+                "A bundle field called \"{}\" has already been specified.",
+                name
+            );
+        }
+        if let Err(error) = check_bit_width("a bundle field", bit_width) {
+            panic!("{}", error);
+        }
+        self.fields.push((name, bit_width));
+        self
+    }
+
+    /// Returns the name and bit width of each of this `BundleSpec`'s fields, in order.
+    pub fn fields(&self) -> &[(String, u32)] {
+        &self.fields
+    }
+
+    /// Returns the sum of the bit widths of this `BundleSpec`'s fields, which is the bit width of a flattened [`Bundle`] with this spec.
+    ///
+    /// [`Bundle`]: ./struct.Bundle.html
+    pub fn bit_width(&self) -> u32 {
+        self.fields.iter().map(|&(_, bit_width)| bit_width).sum()
+    }
+}
+
+/// A group of named [`Signal`]s with the fields described by a [`BundleSpec`], created by the [`Module`]::[`bundle`], [`Module`]::[`unflatten`], [`Module`]::[`inputs_from_bundle`], or [`Instance`]::[`bundle_outputs`] methods.
+///
+/// Bundles make it easier to pass related signals (such as `valid`, `tag`, and `data`) around together, and can be [flattened](#method.flatten) into a single [`Signal`] when that's more convenient.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let cmd = BundleSpec::new().field("valid", 1).field("data", 8);
+///
+/// let inner = c.module("Inner");
+/// let i = inner.inputs_from_bundle("cmd", &cmd);
+/// inner.outputs_from_bundle("resp", &inner.bundle(&cmd, &[i.field("valid"), !i.field("data")]));
+///
+/// let outer = c.module("Outer");
+/// let inner_inst = outer.instance("inner", "Inner");
+/// inner_inst.drive_bundle_inputs("cmd", &outer.inputs_from_bundle("cmd", &cmd));
+/// outer.outputs_from_bundle("resp", &inner_inst.bundle_outputs("resp", &cmd));
+/// ```
+///
+/// [`bundle`]: ./struct.Module.html#method.bundle
+/// [`bundle_outputs`]: ./struct.Instance.html#method.bundle_outputs
+/// [`BundleSpec`]: ./struct.BundleSpec.html
+/// [`inputs_from_bundle`]: ./struct.Module.html#method.inputs_from_bundle
+/// [`Instance`]: ./struct.Instance.html
+/// [`Module`]: ./struct.Module.html
+/// [`Signal`]: ./struct.Signal.html
+/// [`unflatten`]: ./struct.Module.html#method.unflatten
+#[derive(Clone)]
+pub struct Bundle<'a> {
+    fields: Vec<(String, &'a Signal<'a>)>,
+}
+
+impl<'a> Bundle<'a> {
+    /// Returns the [`Signal`] for the field called `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Bundle` doesn't have a field called `name`.
+    ///
+    /// [`Signal`]: ./struct.Signal.html
+    pub fn field(&self, name: &str) -> &'a Signal<'a> {
+        match self
+            .fields
+            .iter()
+            .find(|(field_name, _)| field_name == name)
+        {
+            Some(&(_, signal)) => signal,
+            _ => panic!(
+                "Bundle doesn't have a field called \"{}\". Its fields are {}.",
+                name,
+                self.field_names()
+            ),
+        }
+    }
+
+    /// Returns the name and [`Signal`] of each of this `Bundle`'s fields, in the order they were specified in its [`BundleSpec`].
+    ///
+    /// [`BundleSpec`]: ./struct.BundleSpec.html
+    /// [`Signal`]: ./struct.Signal.html
+    pub fn fields(&self) -> &[(String, &'a Signal<'a>)] {
+        &self.fields
+    }
+
+    /// Concatenates this `Bundle`'s fields into a single [`Signal`], with the first field in the most significant bits.
+    ///
+    /// This is the inverse of [`Module`]::[`unflatten`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sum of the fields' bit widths is greater than [`MAX_SIGNAL_BIT_WIDTH`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let cmd = BundleSpec::new().field("valid", 1).field("data", 8);
+    /// let bundle = m.inputs_from_bundle("cmd", &cmd);
+    /// let flat = bundle.flatten();
+    /// assert_eq!(flat.bit_width(), 9);
+    /// let unflattened = m.unflatten(&cmd, flat);
+    /// m.output("data", unflattened.field("data"));
+    /// ```
+    ///
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    /// [`Module`]: ./struct.Module.html
+    /// [`Signal`]: ./struct.Signal.html
+    /// [`unflatten`]: ./struct.Module.html#method.unflatten
+    #[track_caller]
+    pub fn flatten(&self) -> &'a Signal<'a> {
+        let bit_width = self
+            .fields
+            .iter()
+            .map(|(_, signal)| signal.bit_width() as u64)
+            .sum::<u64>();
+        if bit_width > MAX_SIGNAL_BIT_WIDTH as u64 {
+            panic!(
+                "Cannot flatten a bundle with fields {} into a single signal, as its bit width of {} would be greater than the maximum signal bit width of {} bit(s).",
+                self.field_names(),
+                bit_width,
+                MAX_SIGNAL_BIT_WIDTH
+            );
+        }
+
+        let mut ret = self.fields[0].1;
+        for &(_, signal) in self.fields.iter().skip(1) {
+            ret = ret.concat(signal);
+        }
+        ret
+    }
+
+    fn field_names(&self) -> String {
+        self.fields
+            .iter()
+            .map(|(name, _)| format!("\"{}\"", name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn check_spec(spec: &BundleSpec) {
+    if spec.fields.is_empty() {
+        panic!("Cannot create a bundle from a spec without any fields.");
+    }
+}
+
+fn port_name(prefix: &str, field_name: &str) -> String {
+    format!("{}_{}", prefix, field_name)
+}
+
+impl<'a> Module<'a> {
+    /// Creates a [`Bundle`] with the fields described by `spec` from `fields`, which must be in the same order as `spec`'s fields.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spec` doesn't have any fields, if the number of `fields` doesn't match the number of fields in `spec`, if any of `fields` belongs to a different `Module` than `self`, or if any of `fields` has a different bit width than its field in `spec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let cmd = BundleSpec::new().field("valid", 1).field("data", 8);
+    /// let bundle = m.bundle(&cmd, &[m.high(), m.lit(0x42u32, 8)]);
+    /// m.outputs_from_bundle("cmd", &bundle);
+    /// ```
+    ///
+    /// [`Bundle`]: ./struct.Bundle.html
+    pub fn bundle(&'a self, spec: &BundleSpec, fields: &[&'a Signal<'a>]) -> Bundle<'a> {
+        check_spec(spec);
+        if fields.len() != spec.fields.len() {
+            panic!(
+                "Cannot create a bundle with {} field(s) from {} signal(s).",
+                spec.fields.len(),
+                fields.len()
+            );
+        }
+        for (&(ref name, bit_width), signal) in spec.fields.iter().zip(fields.iter()) {
+            if !ptr::eq(self, signal.module) {
+                panic!("Attempted to combine signals from different modules.");
+            }
+            if signal.bit_width() != bit_width {
+                panic!(
+                    "Cannot use a signal with {} bit(s) for bundle field \"{}\", which has {} bit(s). The signal was created at {}.",
+                    signal.bit_width(),
+                    name,
+                    bit_width,
+                    signal.location
+                );
+            }
+        }
+
+        Bundle {
+            fields: spec
+                .fields
+                .iter()
+                .map(|(name, _)| name.clone())
+                .zip(fields.iter().cloned())
+                .collect(),
+        }
+    }
+
+    /// Splits `source` into a [`Bundle`] with the fields described by `spec`, with the first field taken from the most significant bits.
+    ///
+    /// This is the inverse of [`Bundle`]::[`flatten`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spec` doesn't have any fields, if `source` belongs to a different `Module` than `self`, or if `source`'s bit width doesn't match `spec`'s [total bit width](./struct.BundleSpec.html#method.bit_width).
+    ///
+    /// [`Bundle`]: ./struct.Bundle.html
+    /// [`flatten`]: ./struct.Bundle.html#method.flatten
+    #[track_caller]
+    pub fn unflatten(&'a self, spec: &BundleSpec, source: &'a Signal<'a>) -> Bundle<'a> {
+        check_spec(spec);
+        if !ptr::eq(self, source.module) {
+            panic!("Attempted to combine signals from different modules.");
+        }
+        if source.bit_width() != spec.bit_width() {
+            panic!(
+                "Cannot unflatten a signal with {} bit(s) into a bundle with {} bit(s). The signal was created at {}.",
+                source.bit_width(),
+                spec.bit_width(),
+                source.location
+            );
+        }
+
+        let mut fields = Vec::new();
+        let mut range_high = source.bit_width();
+        for (name, bit_width) in spec.fields.iter() {
+            fields.push((
+                name.clone(),
+                source.bits(range_high - 1, range_high - bit_width),
+            ));
+            range_high -= bit_width;
+        }
+        Bundle { fields }
+    }
+
+    /// Creates an input for each field described by `spec`, named `prefix` followed by `_` and the field's name, and returns them as a [`Bundle`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spec` doesn't have any fields, or under the same conditions as [`input`] for any of the created inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let cmd = BundleSpec::new().field("valid", 1).field("data", 8);
+    /// let bundle = m.inputs_from_bundle("cmd", &cmd);
+    /// assert_eq!(m.inputs()["cmd_data"].bit_width(), 8);
+    /// m.output("o", bundle.field("valid"));
+    /// ```
+    ///
+    /// [`Bundle`]: ./struct.Bundle.html
+    /// [`input`]: #method.input
+    #[track_caller]
+    pub fn inputs_from_bundle(&'a self, prefix: &str, spec: &BundleSpec) -> Bundle<'a> {
+        check_spec(spec);
+        let mut fields = Vec::new();
+        for (name, bit_width) in spec.fields.iter() {
+            fields.push((
+                name.clone(),
+                self.input(port_name(prefix, name), *bit_width),
+            ));
+        }
+        Bundle { fields }
+    }
+
+    /// Creates an output for each field of `bundle`, named `prefix` followed by `_` and the field's name.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`output`] for any of the created outputs.
+    ///
+    /// [`output`]: #method.output
+    #[track_caller]
+    pub fn outputs_from_bundle(&'a self, prefix: &str, bundle: &Bundle<'a>) {
+        for (name, signal) in bundle.fields.iter() {
+            self.output(port_name(prefix, name), signal);
+        }
+    }
+}
+
+impl<'a> Instance<'a> {
+    /// Drives each of this `Instance`'s inputs named `prefix` followed by `_` and a field name of `bundle` with that field's [`Signal`].
+    ///
+    /// This is the counterpart of [`Module`]::[`inputs_from_bundle`] on the instantiated [`Module`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`drive_input`] for any of the driven inputs.
+    ///
+    /// [`drive_input`]: #method.drive_input
+    /// [`inputs_from_bundle`]: ./struct.Module.html#method.inputs_from_bundle
+    /// [`Module`]: ./struct.Module.html
+    /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
+    pub fn drive_bundle_inputs(&'a self, prefix: &str, bundle: &Bundle<'a>) {
+        for (name, signal) in bundle.fields.iter() {
+            self.drive_input(port_name(prefix, name), signal);
+        }
+    }
+
+    /// Returns this `Instance`'s outputs named `prefix` followed by `_` and the name of each field described by `spec` as a [`Bundle`].
+    ///
+    /// This is the counterpart of [`Module`]::[`outputs_from_bundle`] on the instantiated [`Module`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spec` doesn't have any fields, if any of the outputs doesn't exist, or if any output's bit width doesn't match its field in `spec`.
+    ///
+    /// [`Bundle`]: ./struct.Bundle.html
+    /// [`Module`]: ./struct.Module.html
+    /// [`outputs_from_bundle`]: ./struct.Module.html#method.outputs_from_bundle
+    #[track_caller]
+    pub fn bundle_outputs(&'a self, prefix: &str, spec: &BundleSpec) -> Bundle<'a> {
+        check_spec(spec);
+        let mut fields = Vec::new();
+        for (name, bit_width) in spec.fields.iter() {
+            let output_name = port_name(prefix, name);
+            let signal = self.output(output_name.clone());
+            if signal.bit_width() != *bit_width {
+                panic!(
+                    "Output \"{}\" of instance \"{}\" has {} bit(s), but bundle field \"{}\" has {} bit(s).",
+                    output_name,
+                    self.name,
+                    signal.bit_width(),
+                    name,
+                    bit_width
+                );
+            }
+            fields.push((name.clone(), signal));
+        }
+        Bundle { fields }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    #[should_panic(expected = "A bundle field called \"data\" has already been specified.")]
+    fn spec_duplicate_field_error() {
+        let _ = BundleSpec::new().field("data", 8).field("data", 4);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot use a signal with 4 bit(s) for bundle field \"data\", which has 8 bit(s)."
+    )]
+    fn bundle_field_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let spec = BundleSpec::new().field("valid", 1).field("data", 8);
+
+        // Panic
+        let _ = m.bundle(&spec, &[m.high(), m.input("i", 4)]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot unflatten a signal with 8 bit(s) into a bundle with 9 bit(s)."
+    )]
+    fn unflatten_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let spec = BundleSpec::new().field("valid", 1).field("data", 8);
+
+        // Panic
+        let _ = m.unflatten(&spec, m.input("i", 8));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Bundle doesn't have a field called \"tag\". Its fields are \"valid\", \"data\"."
+    )]
+    fn missing_field_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let spec = BundleSpec::new().field("valid", 1).field("data", 8);
+
+        // Panic
+        let _ = m.inputs_from_bundle("cmd", &spec).field("tag");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Output \"resp_data\" of instance \"inner\" has 4 bit(s), but bundle field \"data\" has 8 bit(s)."
+    )]
+    fn bundle_outputs_bit_width_error() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("resp_data", inner.input("i", 4));
+
+        let m = c.module("A");
+        let inner = m.instance("inner", "Inner");
+        let spec = BundleSpec::new().field("data", 8);
+
+        // Panic
+        let _ = inner.bundle_outputs("resp", &spec);
+    }
+}
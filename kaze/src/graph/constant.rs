@@ -2,6 +2,10 @@
 ///
 /// This type isn't typically used explicitly, as the graph API always takes `Constant` parameters as `Into<Constant>`, and `Constant` implements `From` for most of Rust's unsigned integer types. If an API entry point requires a `Constant`, prefer passing integer values/literals directly.
 ///
+/// `Constant` implements `From` for `bool`, `u8`, `u16`, `u32`, `u64`, and `u128`, which covers literal values ([`Module::lit`]), register default values ([`Register::default_value`]), and memory contents ([`Mem::initial_contents`]).
+/// Signed types aren't supported, so unsuffixed integer literals (which default to `i32`) must be given an unsigned suffix, eg. `m.lit(42u32, 8)` rather than `m.lit(42, 8)`.
+/// The suffix only determines how the value is passed in; the resulting bit width is always specified separately.
+///
 /// # Examples
 ///
 /// ```
@@ -16,7 +20,14 @@
 /// let c = m.reg("data", 20);
 /// c.default_value(5u32);
 /// let d = m.lit(42u32, 8);
+/// let e = m.lit(0x1234u16, 16);
+/// let rom = m.mem("rom", 1, 8);
+/// rom.initial_contents(&[0x12u8, 0x34u8]);
 /// ```
+///
+/// [`Mem::initial_contents`]: ./struct.Mem.html#method.initial_contents
+/// [`Module::lit`]: ./struct.Module.html#method.lit
+/// [`Register::default_value`]: ./struct.Register.html#method.default_value
 pub enum Constant {
     /// Contains a boolean value
     Bool(bool),
@@ -1,13 +1,18 @@
+use super::constant::*;
 use super::instance::*;
+use super::latch::*;
 use super::mem::*;
 use super::module::*;
 use super::register::*;
+use super::register_file::*;
 use super::signal::*;
 
 use typed_arena::Arena;
 
 use std::cell::{Ref, RefCell};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
+use std::mem::size_of;
+use std::panic::Location;
 
 /// A top-level container/owner object for a [`Module`] graph.
 ///
@@ -28,13 +33,18 @@ use std::collections::BTreeMap;
 #[must_use]
 pub struct Context<'a> {
     module_arena: Arena<Module<'a>>,
-    pub(super) signal_arena: Arena<Signal<'a>>,
+    signal_arena: Arena<Signal<'a>>,
     pub(super) register_data_arena: Arena<RegisterData<'a>>,
     pub(super) register_arena: Arena<Register<'a>>,
     pub(super) instance_arena: Arena<Instance<'a>>,
+    pub(super) latch_data_arena: Arena<LatchData<'a>>,
+    pub(super) latch_arena: Arena<Latch<'a>>,
     pub(super) mem_arena: Arena<Mem<'a>>,
+    pub(super) register_file_arena: Arena<RegisterFile<'a>>,
+    name_arena: Arena<u8>,
 
     pub(super) modules: RefCell<BTreeMap<String, &'a Module<'a>>>,
+    names: RefCell<HashSet<&'a str>>,
 }
 
 impl<'a> Context<'a> {
@@ -54,9 +64,14 @@ impl<'a> Context<'a> {
             register_data_arena: Arena::new(),
             register_arena: Arena::new(),
             instance_arena: Arena::new(),
+            latch_data_arena: Arena::new(),
+            latch_arena: Arena::new(),
             mem_arena: Arena::new(),
+            register_file_arena: Arena::new(),
+            name_arena: Arena::new(),
 
             modules: RefCell::new(BTreeMap::new()),
+            names: RefCell::new(HashSet::new()),
         }
     }
 
@@ -67,6 +82,7 @@ impl<'a> Context<'a> {
     /// # Panics
     ///
     /// Panics if a [`Module`] with the same `name` already exists in this `Context`.
+    /// [`has_module`] and [`module_by_name`] can be used to check for an existing [`Module`] first.
     ///
     /// # Examples
     ///
@@ -92,21 +108,178 @@ impl<'a> Context<'a> {
     /// let _ = c.module("A"); // Non-unique name, panic!
     /// ```
     ///
+    /// [`has_module`]: #method.has_module
     /// [`Module`]: ./struct.Module.html
+    /// [`module_by_name`]: #method.module_by_name
     pub fn module<S: Into<String>>(&'a self, name: S) -> &Module {
         let name = name.into();
         let mut modules = self.modules.borrow_mut();
         if modules.contains_key(&name) {
             panic!(
-                "A module with the name \"{}\" already exists in this context.",
+                "A module with the name \"{}\" already exists in this context. Use Context::has_module or Context::module_by_name to check for an existing module before creating one, or Context::module_once to get or create it.",
                 name
             );
         }
-        let module = self.module_arena.alloc(Module::new(self, name.clone()));
+        let module = self
+            .module_arena
+            .alloc(Module::new(self, name.clone(), false));
         modules.insert(name, module);
         module
     }
 
+    /// Returns the [`Module`] called `name` in this `Context` if it already exists, or otherwise creates it and calls `build` to define its contents.
+    ///
+    /// This makes parameterized generator functions idempotent: a generator can encode its parameters in the module name and call `module_once` every time it's needed, and the module is only built (and only appears in generated code) once.
+    ///
+    /// If a [`Module`] called `name` already exists, `build` is **not** called, and the existing [`Module`] is returned as-is, even if `build` would have defined a different interface.
+    /// It's therefore up to the caller to make sure that the name uniquely identifies the module's contents.
+    ///
+    /// `build` may itself create other [`Module`]s (with [`module`] or `module_once`) that the new [`Module`] instantiates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// fn adder<'a>(c: &'a Context<'a>, bit_width: u32) -> &'a Module<'a> {
+    ///     c.module_once(format!("Adder{}", bit_width), |m| {
+    ///         m.output("sum", m.input("a", bit_width) + m.input("b", bit_width));
+    ///     })
+    /// }
+    ///
+    /// let c = Context::new();
+    ///
+    /// let a = adder(&c, 8);
+    /// let b = adder(&c, 8); // Returns the same module
+    /// let _ = adder(&c, 16);
+    ///
+    /// assert!(std::ptr::eq(a, b));
+    /// assert_eq!(c.modules().len(), 2);
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`module`]: #method.module
+    pub fn module_once<S: Into<String>, F: FnOnce(&'a Module<'a>)>(
+        &'a self,
+        name: S,
+        build: F,
+    ) -> &'a Module<'a> {
+        let name = name.into();
+        if let Some(module) = self.module_by_name(&name) {
+            return module;
+        }
+        let module = self.module(name);
+        build(module);
+        module
+    }
+
+    /// Declares a new extern [`Module`] called `name` in this `Context`.
+    ///
+    /// An extern [`Module`] describes the interface of a module that's defined outside of kaze, such as a vendor primitive or a hand-written Verilog module (often called a black box).
+    /// Its inputs are declared with [`input`] as usual, and its outputs are declared with [`extern_output`], but it has no body: attempting to create registers, memories, instances, or driven outputs inside it panics.
+    /// It can be instantiated with [`instance`] like any other [`Module`], and values for its parameters can be specified per instance with [`Instance::parameter`].
+    ///
+    /// Generated Verilog contains instantiations of extern [`Module`]s, but never their definitions, which must be provided separately.
+    /// Extern [`Module`]s can't be simulated, so simulator generation panics for any [`Module`] hierarchy that contains one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`Module`] with the same `name` already exists in this `Context`.
+    /// [`has_module`] and [`module_by_name`] can be used to check for an existing [`Module`] first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let pll = c.extern_module("PLLE2_BASE");
+    /// pll.input("CLKIN1", 1);
+    /// pll.extern_output("CLKOUT0", 1);
+    ///
+    /// let top = c.module("Top");
+    /// let pll_inst = top.instance("pll", "PLLE2_BASE");
+    /// pll_inst.parameter("CLKFBOUT_MULT", 10);
+    /// pll_inst.parameter("BANDWIDTH", "OPTIMIZED");
+    /// pll_inst.drive_input("CLKIN1", top.input("clk_in", 1));
+    /// top.output("clk_out", pll_inst.output("CLKOUT0"));
+    /// ```
+    ///
+    /// [`extern_output`]: ./struct.Module.html#method.extern_output
+    /// [`input`]: ./struct.Module.html#method.input
+    /// [`instance`]: ./struct.Module.html#method.instance
+    /// [`Instance::parameter`]: ./struct.Instance.html#method.parameter
+    /// [`has_module`]: #method.has_module
+    /// [`Module`]: ./struct.Module.html
+    /// [`module_by_name`]: #method.module_by_name
+    pub fn extern_module<S: Into<String>>(&'a self, name: S) -> &'a Module<'a> {
+        let name = name.into();
+        let mut modules = self.modules.borrow_mut();
+        if modules.contains_key(&name) {
+            panic!(
+                "A module with the name \"{}\" already exists in this context. Use Context::has_module or Context::module_by_name to check for an existing module before creating one, or Context::module_once to get or create it.",
+                name
+            );
+        }
+        let module = self
+            .module_arena
+            .alloc(Module::new(self, name.clone(), true));
+        modules.insert(name, module);
+        module
+    }
+
+    /// Returns a copy of `name` owned by this `Context`, which is shared by every node that refers to the same name.
+    pub(super) fn intern(&'a self, name: &str) -> &'a str {
+        let mut names = self.names.borrow_mut();
+        if let Some(&interned) = names.get(name) {
+            return interned;
+        }
+        let interned: &'a str = self.name_arena.alloc_str(name);
+        names.insert(interned);
+        interned
+    }
+
+    pub(super) fn alloc_signal(
+        &'a self,
+        module: &'a Module<'a>,
+        location: &'static Location<'static>,
+        data: SignalData<'a>,
+    ) -> &'a Signal<'a> {
+        // Structurally-identical signals are shared, so duplicated expressions only result in a single node
+        let key = data.structural_key();
+        if let Some(ref key) = key {
+            if let Some(&existing) = module.structural_signals.borrow().get(key) {
+                return existing;
+            }
+        }
+        let ret = self.alloc_distinct_signal(module, location, data);
+        if let Some(key) = key {
+            ret.module.structural_signals.borrow_mut().insert(key, ret);
+        }
+        ret
+    }
+
+    // Allocates a signal that's never shared with structurally-identical signals, for signals with an identity of their own (such as named constants)
+    pub(super) fn alloc_distinct_signal(
+        &'a self,
+        module: &'a Module<'a>,
+        location: &'static Location<'static>,
+        data: SignalData<'a>,
+    ) -> &'a Signal<'a> {
+        // Bit widths are computed once up front (from operands' already-computed widths), so querying them never has to walk arbitrarily deep graphs
+        let bit_width = data.bit_width();
+        let ret = self.signal_arena.alloc(Signal {
+            module,
+            location,
+            bit_width,
+
+            data,
+        });
+        ret.module.signals.borrow_mut().push(ret);
+        ret
+    }
+
     /// Immutably borrows this `Context`'s [`Module`]s.
     ///
     /// This is primarily useful for iterating over every [`Module`] in this `Context` when generating code.
@@ -128,14 +301,159 @@ impl<'a> Context<'a> {
     pub fn modules(&'a self) -> Ref<BTreeMap<String, &'a Module<'a>>> {
         self.modules.borrow()
     }
+
+    /// Looks up the [`Module`] called `name` in this `Context`, returning `None` if no such [`Module`] exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let my_module = c.module("MyModule");
+    ///
+    /// assert!(c.module_by_name("MyModule").is_some());
+    /// assert!(c.module_by_name("AnotherMod").is_none());
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub fn module_by_name(&'a self, name: &str) -> Option<&'a Module<'a>> {
+        self.modules.borrow().get(name).copied()
+    }
+
+    /// Returns `true` if a [`Module`] called `name` exists in this `Context`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// assert!(!c.has_module("MyModule"));
+    ///
+    /// let _ = c.module("MyModule");
+    ///
+    /// assert!(c.has_module("MyModule"));
+    /// assert!(!c.has_module("AnotherMod"));
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub fn has_module(&self, name: &str) -> bool {
+        self.modules.borrow().contains_key(name)
+    }
+
+    /// Returns an estimate of the number of bytes of memory used by this `Context`'s graph.
+    ///
+    /// The estimate covers the graph's nodes and the per-[`Module`] bookkeeping that grows with the number of nodes (such as the tables used to share structurally-identical [`Signal`]s), which together account for almost all of the memory used by large graphs.
+    /// Smaller allocations (such as port and register names) aren't included.
+    /// The result isn't exact, but it's deterministic, so it's useful for tracking memory usage regressions in generators for large designs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    /// let empty_usage = c.memory_usage();
+    ///
+    /// let m = c.module("MyModule");
+    /// m.output("o", m.input("a", 8) + m.input("b", 8));
+    ///
+    /// assert!(c.memory_usage() > empty_usage);
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`Signal`]: ./struct.Signal.html
+    pub fn memory_usage(&self) -> usize {
+        let mut usage = size_of::<Context>()
+            + self.module_arena.len() * size_of::<Module>()
+            + self.signal_arena.len() * size_of::<Signal>()
+            + self.register_data_arena.len() * size_of::<RegisterData>()
+            + self.register_arena.len() * size_of::<Register>()
+            + self.instance_arena.len() * size_of::<Instance>()
+            + self.latch_data_arena.len() * size_of::<LatchData>()
+            + self.latch_arena.len() * size_of::<Latch>()
+            + self.mem_arena.len() * size_of::<Mem>()
+            + self.register_file_arena.len() * size_of::<RegisterFile>()
+            + self.name_arena.len()
+            + self.names.borrow().capacity() * size_of::<&str>();
+        for module in self.modules.borrow().values() {
+            let signals = module.signals.borrow();
+            usage += signals.capacity() * size_of::<&Signal>();
+            usage += signals
+                .iter()
+                .filter(|signal| matches!(signal.data, SignalData::Lit { .. }))
+                .count()
+                * size_of::<Constant>();
+            // Each entry also has a control byte
+            usage += module.structural_signals.borrow().capacity()
+                * (size_of::<StructuralKey>() + size_of::<&Signal>() + 1);
+        }
+        usage
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::ptr;
+
     #[test]
-    #[should_panic(expected = "A module with the name \"A\" already exists in this context.")]
+    fn module_once_builds_once() {
+        let c = Context::new();
+
+        let mut num_builds = 0;
+        let a = c.module_once("A", |m| {
+            num_builds += 1;
+            m.output("o", m.input("i", 8));
+        });
+        let b = c.module_once("A", |m| {
+            num_builds += 1;
+            // Would define a different interface, but isn't run
+            m.output("o", m.input("i", 16));
+        });
+
+        assert_eq!(num_builds, 1);
+        assert!(ptr::eq(a, b));
+        assert_eq!(a.inputs()["i"].bit_width(), 8);
+        assert_eq!(c.modules().len(), 1);
+    }
+
+    #[test]
+    fn module_once_existing_module() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let b = c.module_once("A", |_| panic!("Module shouldn't be built"));
+
+        assert!(ptr::eq(a, b));
+    }
+
+    #[test]
+    fn module_once_nested() {
+        let c = Context::new();
+
+        let outer = c.module_once("Outer", |m| {
+            let inner = c.module_once("Inner", |m| {
+                m.output("o", m.input("i", 1));
+            });
+            assert_eq!(inner.name(), "Inner");
+            let i = m.instance("inner", "Inner");
+            i.drive_input("i", m.input("i", 1));
+            m.output("o", i.output("o"));
+        });
+
+        assert_eq!(outer.instances().len(), 1);
+        assert_eq!(c.modules().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "A module with the name \"A\" already exists in this context. Use Context::has_module or Context::module_by_name to check for an existing module before creating one, or Context::module_once to get or create it."
+    )]
     fn unique_module_names() {
         let c = Context::new();
 
@@ -152,4 +470,110 @@ mod tests {
 
         assert!(c.modules().is_empty());
     }
+
+    #[test]
+    fn modules_enumerated_in_name_order() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("i", 1));
+        let outer = c.module("Outer");
+        let inst = outer.instance("inner", "Inner");
+        inst.drive_input("i", outer.input("i", 1));
+        outer.output("o", inst.output("o"));
+        let _ = c.module("AAA");
+
+        let names = c
+            .modules()
+            .iter()
+            .map(|(name, m)| {
+                assert_eq!(m.name(), name);
+                name.clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["AAA", "Inner", "Outer"]);
+    }
+
+    #[test]
+    fn names_are_interned() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let b = c.module("B");
+        let a_clk = a.input("clk_en", 1);
+        let b_clk = b.input("clk_en", 1);
+
+        match (&a_clk.data, &b_clk.data) {
+            (SignalData::Input { name: a_name, .. }, SignalData::Input { name: b_name, .. }) => {
+                assert_eq!(*a_name, "clk_en");
+                assert!(ptr::eq(*a_name, *b_name));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn memory_usage_per_node() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 32);
+        let mut acc = m.lit(0u32, 32);
+        for n in 0..10_000u32 {
+            let x = (i ^ m.lit(n, 32)) + acc;
+            acc = x.eq(i).mux(acc, x);
+        }
+        m.output("o", acc);
+
+        // About 40k nodes; guards against regressions in per-node overhead
+        let usage = c.memory_usage();
+        assert!(usage > 40_000 * size_of::<Signal>());
+        assert!(usage < 40_000 * 160, "{} bytes", usage);
+    }
+
+    #[test]
+    fn module_by_name_lookups() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let b = c.module("B");
+
+        assert!(ptr::eq(c.module_by_name("A").unwrap(), a));
+        assert!(ptr::eq(c.module_by_name("B").unwrap(), b));
+        assert!(c.module_by_name("C").is_none());
+        assert!(c.module_by_name("a").is_none());
+    }
+
+    #[test]
+    fn has_module_lookups() {
+        let c = Context::new();
+
+        assert!(!c.has_module("A"));
+
+        let _ = c.module("A");
+        let _ = c.extern_module("B");
+
+        assert!(c.has_module("A"));
+        assert!(c.has_module("B"));
+        assert!(!c.has_module("C"));
+        assert!(!c.has_module("a"));
+
+        // Modules created after a miss are found by later lookups
+        let c_module = c.module("C");
+        assert!(c.has_module("C"));
+        assert!(ptr::eq(c.module_by_name("C").unwrap(), c_module));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "A module with the name \"A\" already exists in this context. Use Context::has_module"
+    )]
+    fn unique_extern_module_names() {
+        let c = Context::new();
+
+        let _ = c.module("A");
+
+        // Panic
+        let _ = c.extern_module("A");
+    }
 }
@@ -0,0 +1,546 @@
+use super::module::*;
+use super::register::*;
+use super::signal::*;
+
+use std::ptr;
+
+/// Describes an enumerated type with named variants, such as the states of a state machine, for use with [`EnumSignal`]s.
+///
+/// Each variant has a distinct encoding, and the type's bit width is the smallest one that can represent every encoding.
+/// In generated code, each variant is represented by a [named constant](./struct.Module.html#method.constant) called the type's name followed by `_` and the variant's name, in upper snake case (for example, variant `ReadData` of type `State` is called `STATE_READ_DATA`).
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let state = EnumType::new("State", &["Idle", "Fetch", "Execute"]);
+/// assert_eq!(state.bit_width(), 2);
+/// assert_eq!(state.variant("Execute").encoding(), 2);
+///
+/// let one_hot = EnumType::with_encodings("OneHot", &[("A", 0b001), ("B", 0b010), ("C", 0b100)]);
+/// assert_eq!(one_hot.bit_width(), 3);
+/// ```
+///
+/// [`EnumSignal`]: ./struct.EnumSignal.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnumType {
+    name: String,
+    variants: Vec<(String, u128)>,
+    bit_width: u32,
+}
+
+impl EnumType {
+    /// Creates an `EnumType` called `name` with the given `variants`, which are encoded as `0`, `1`, `2`, and so on, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `variants` is empty or contains the same name more than once.
+    pub fn new<S: Into<String>>(name: S, variants: &[&str]) -> EnumType {
+        let variants = variants
+            .iter()
+            .enumerate()
+            .map(|(index, &variant)| (variant, index as u128))
+            .collect::<Vec<_>>();
+        EnumType::with_encodings(name, &variants)
+    }
+
+    /// Creates an `EnumType` called `name` with the given `(name, encoding)` pairs as its variants.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `variants` is empty, or if it contains the same name or encoding more than once.
+    pub fn with_encodings<S: Into<String>>(name: S, variants: &[(&str, u128)]) -> EnumType {
+        let name = name.into();
+        if variants.is_empty() {
+            panic!("Cannot create enum \"{}\" without any variants.", name);
+        }
+        for (index, &(variant, encoding)) in variants.iter().enumerate() {
+            for &(other_variant, other_encoding) in variants[..index].iter() {
+                if other_variant == variant {
+                    panic!(
+                        "Enum \"{}\" has more than one variant called \"{}\".",
+                        name, variant
+                    );
+                }
+                if other_encoding == encoding {
+                    panic!(
+                        "Variants \"{}\" and \"{}\" of enum \"{}\" have the same encoding ({}).",
+                        other_variant, variant, name, encoding
+                    );
+                }
+            }
+        }
+        let max_encoding = variants
+            .iter()
+            .map(|&(_, encoding)| encoding)
+            .max()
+            .unwrap();
+        let bit_width = (128 - max_encoding.leading_zeros()).max(MIN_SIGNAL_BIT_WIDTH);
+
+        EnumType {
+            name,
+            variants: variants
+                .iter()
+                .map(|&(variant, encoding)| (variant.into(), encoding))
+                .collect(),
+            bit_width,
+        }
+    }
+
+    /// Returns this `EnumType`'s name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the bit width of [`EnumSignal`]s of this `EnumType`.
+    ///
+    /// [`EnumSignal`]: ./struct.EnumSignal.html
+    pub fn bit_width(&self) -> u32 {
+        self.bit_width
+    }
+
+    /// Returns the name and encoding of each of this `EnumType`'s variants, in the order they were specified.
+    pub fn variants(&self) -> &[(String, u128)] {
+        &self.variants
+    }
+
+    /// Returns the variant of this `EnumType` called `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `EnumType` doesn't have a variant called `name`.
+    pub fn variant(&self, name: &str) -> EnumVariant<'_> {
+        match self
+            .variants
+            .iter()
+            .position(|(variant, _)| variant == name)
+        {
+            Some(index) => EnumVariant {
+                enum_type: self,
+                index,
+            },
+            _ => panic!(
+                "Enum \"{}\" doesn't have a variant called \"{}\". Its variants are {}.",
+                self.name,
+                name,
+                self.variants
+                    .iter()
+                    .map(|(variant, _)| format!("\"{}\"", variant))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    // The name of the named constant that represents the variant called `variant` in generated code
+    fn constant_name(&self, variant: &str) -> String {
+        let mut ret = String::new();
+        for part in &[&self.name[..], variant] {
+            if !ret.is_empty() {
+                ret.push('_');
+            }
+            let mut prev_is_lower = false;
+            for c in part.chars() {
+                if c.is_uppercase() && prev_is_lower {
+                    ret.push('_');
+                }
+                prev_is_lower = c.is_lowercase() || c.is_ascii_digit();
+                ret.extend(c.to_uppercase());
+            }
+        }
+        ret
+    }
+}
+
+/// A variant of an [`EnumType`], returned by [`EnumType::variant`].
+///
+/// [`EnumType`]: ./struct.EnumType.html
+/// [`EnumType::variant`]: ./struct.EnumType.html#method.variant
+#[derive(Clone, Copy, Debug)]
+pub struct EnumVariant<'t> {
+    enum_type: &'t EnumType,
+    index: usize,
+}
+
+impl<'t> EnumVariant<'t> {
+    /// Returns the [`EnumType`] that this variant belongs to.
+    ///
+    /// [`EnumType`]: ./struct.EnumType.html
+    pub fn enum_type(&self) -> &'t EnumType {
+        self.enum_type
+    }
+
+    /// Returns this variant's name.
+    pub fn name(&self) -> &'t str {
+        &self.enum_type.variants[self.index].0
+    }
+
+    /// Returns this variant's encoding.
+    pub fn encoding(&self) -> u128 {
+        self.enum_type.variants[self.index].1
+    }
+}
+
+/// A [`Signal`] whose value is a variant of an [`EnumType`], created by the [`Module`]::[`enum_lit`], [`Module`]::[`enum_signal`], or [`Module`]::[`enum_reg`] methods.
+///
+/// `EnumSignal`s can only be compared with variants of (and multiplexed with other `EnumSignal`s of) the same [`EnumType`], which rules out mixing up encodings of unrelated enums.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+///
+/// let state_type = EnumType::new("State", &["Idle", "Busy"]);
+/// let state = m.enum_reg("state", &state_type);
+/// state.default_value(&state_type.variant("Idle"));
+/// let start = m.input("start", 1);
+/// let done = m.input("done", 1);
+/// let is_idle = state.value.is(&state_type.variant("Idle"));
+/// state.drive_next(&m.enum_mux(
+///     is_idle,
+///     &m.enum_mux(start, &m.enum_lit(&state_type.variant("Busy")), &state.value),
+///     &m.enum_mux(done, &m.enum_lit(&state_type.variant("Idle")), &state.value),
+/// ));
+/// m.output("busy", !is_idle);
+/// ```
+///
+/// [`EnumType`]: ./struct.EnumType.html
+/// [`enum_lit`]: ./struct.Module.html#method.enum_lit
+/// [`enum_reg`]: ./struct.Module.html#method.enum_reg
+/// [`enum_signal`]: ./struct.Module.html#method.enum_signal
+/// [`Module`]: ./struct.Module.html
+/// [`Signal`]: ./struct.Signal.html
+#[derive(Clone)]
+pub struct EnumSignal<'a> {
+    enum_type: EnumType,
+    signal: &'a Signal<'a>,
+}
+
+impl<'a> EnumSignal<'a> {
+    /// Returns the [`EnumType`] of this `EnumSignal`'s value.
+    ///
+    /// [`EnumType`]: ./struct.EnumType.html
+    pub fn enum_type(&self) -> &EnumType {
+        &self.enum_type
+    }
+
+    /// Returns the underlying [`Signal`] that holds this `EnumSignal`'s encoded value, eg. for connecting it to an output.
+    ///
+    /// [`Signal`]: ./struct.Signal.html
+    pub fn signal(&self) -> &'a Signal<'a> {
+        self.signal
+    }
+
+    /// Creates a 1-bit [`Signal`] that's high when this `EnumSignal`'s value is `variant`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `variant` belongs to a different [`EnumType`] than this `EnumSignal`.
+    ///
+    /// [`EnumType`]: ./struct.EnumType.html
+    /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
+    pub fn is(&self, variant: &EnumVariant) -> &'a Signal<'a> {
+        if *variant.enum_type != self.enum_type {
+            panic!(
+                "Attempted to compare a signal of enum \"{}\" with variant \"{}\" of enum \"{}\".",
+                self.enum_type.name,
+                variant.name(),
+                variant.enum_type.name
+            );
+        }
+        self.signal.eq(self
+            .signal
+            .module
+            .variant_constant(&self.enum_type, variant))
+    }
+}
+
+/// A [`Register`] whose value is a variant of an [`EnumType`], created by the [`Module`]::[`enum_reg`] method.
+///
+/// [`enum_reg`]: ./struct.Module.html#method.enum_reg
+/// [`EnumType`]: ./struct.EnumType.html
+/// [`Module`]: ./struct.Module.html
+/// [`Register`]: ./struct.Register.html
+pub struct EnumRegister<'a> {
+    /// This `EnumRegister`'s current value.
+    pub value: EnumSignal<'a>,
+    register: &'a Register<'a>,
+}
+
+impl<'a> EnumRegister<'a> {
+    /// Returns the underlying [`Register`].
+    ///
+    /// [`Register`]: ./struct.Register.html
+    pub fn register(&self) -> &'a Register<'a> {
+        self.register
+    }
+
+    /// Specifies the variant this `EnumRegister` holds after reset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `variant` belongs to a different [`EnumType`] than this `EnumRegister`, or under the same conditions as [`Register::default_value`].
+    ///
+    /// [`EnumType`]: ./struct.EnumType.html
+    /// [`Register::default_value`]: ./struct.Register.html#method.default_value
+    pub fn default_value(&self, variant: &EnumVariant) {
+        if *variant.enum_type != self.value.enum_type {
+            panic!(
+                "Attempted to specify variant \"{}\" of enum \"{}\" as the default value of register \"{}\", which holds enum \"{}\".",
+                variant.name(),
+                variant.enum_type.name,
+                self.register.data.name,
+                self.value.enum_type.name
+            );
+        }
+        self.register.default_value(variant.encoding());
+    }
+
+    /// Specifies the next value of this `EnumRegister`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `next` has a different [`EnumType`] than this `EnumRegister`, or under the same conditions as [`Register::drive_next`].
+    ///
+    /// [`EnumType`]: ./struct.EnumType.html
+    /// [`Register::drive_next`]: ./struct.Register.html#method.drive_next
+    pub fn drive_next(&self, next: &EnumSignal<'a>) {
+        if next.enum_type != self.value.enum_type {
+            panic!(
+                "Attempted to drive register \"{}\", which holds enum \"{}\", with a signal of enum \"{}\".",
+                self.register.data.name, self.value.enum_type.name, next.enum_type.name
+            );
+        }
+        self.register.drive_next(next.signal);
+    }
+}
+
+impl<'a> Module<'a> {
+    /// Creates an [`EnumSignal`] that represents the constant value `variant`.
+    ///
+    /// In generated code, the value is referred to by the variant's [named constant](./struct.EnumType.html).
+    ///
+    /// [`EnumSignal`]: ./struct.EnumSignal.html
+    #[track_caller]
+    pub fn enum_lit(&'a self, variant: &EnumVariant) -> EnumSignal<'a> {
+        EnumSignal {
+            enum_type: variant.enum_type.clone(),
+            signal: self.variant_constant(variant.enum_type, variant),
+        }
+    }
+
+    /// Interprets `signal` as holding an encoded value of `enum_type`, eg. for an input or an [`Instance`] output that carries an enum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `signal` belongs to a different `Module` than `self`, or if `signal`'s bit width doesn't match `enum_type`'s.
+    ///
+    /// [`Instance`]: ./struct.Instance.html
+    #[track_caller]
+    pub fn enum_signal(&'a self, enum_type: &EnumType, signal: &'a Signal<'a>) -> EnumSignal<'a> {
+        if !ptr::eq(self, signal.module) {
+            panic!("Attempted to combine signals from different modules.");
+        }
+        if signal.bit_width() != enum_type.bit_width {
+            panic!(
+                "Cannot use a signal with {} bit(s) as enum \"{}\", which has {} bit(s). The signal was created at {}.",
+                signal.bit_width(),
+                enum_type.name,
+                enum_type.bit_width,
+                signal.location
+            );
+        }
+        EnumSignal {
+            enum_type: enum_type.clone(),
+            signal,
+        }
+    }
+
+    /// Creates a [`Register`] called `name` that holds a value of `enum_type`, and returns it as an [`EnumRegister`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`reg`].
+    ///
+    /// [`EnumRegister`]: ./struct.EnumRegister.html
+    /// [`reg`]: #method.reg
+    /// [`Register`]: ./struct.Register.html
+    #[track_caller]
+    pub fn enum_reg<S: Into<String>>(&'a self, name: S, enum_type: &EnumType) -> EnumRegister<'a> {
+        let register = self.reg(name, enum_type.bit_width);
+        EnumRegister {
+            value: EnumSignal {
+                enum_type: enum_type.clone(),
+                signal: register.value,
+            },
+            register,
+        }
+    }
+
+    /// Creates a 2:1 multiplexer of [`EnumSignal`]s, which represents `when_true`'s value when `cond` is high, and `when_false`'s value when `cond` is low.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `when_true` and `when_false` have different [`EnumType`]s, or under the same conditions as [`mux`].
+    ///
+    /// [`EnumSignal`]: ./struct.EnumSignal.html
+    /// [`EnumType`]: ./struct.EnumType.html
+    /// [`mux`]: #method.mux
+    #[track_caller]
+    pub fn enum_mux(
+        &'a self,
+        cond: &'a Signal<'a>,
+        when_true: &EnumSignal<'a>,
+        when_false: &EnumSignal<'a>,
+    ) -> EnumSignal<'a> {
+        if when_true.enum_type != when_false.enum_type {
+            panic!(
+                "Cannot multiplex signals of different enums (\"{}\" and \"{}\", respectively).",
+                when_true.enum_type.name, when_false.enum_type.name
+            );
+        }
+        EnumSignal {
+            enum_type: when_true.enum_type.clone(),
+            signal: self.mux(cond, when_true.signal, when_false.signal),
+        }
+    }
+
+    // Returns the named constant for `variant`, declaring constants for all of `enum_type`'s variants the first time it's used in this module
+    #[track_caller]
+    fn variant_constant(&'a self, enum_type: &EnumType, variant: &EnumVariant) -> &'a Signal<'a> {
+        let name = enum_type.constant_name(variant.name());
+        if !self.constants.borrow().contains_key(&name) {
+            for (variant, encoding) in enum_type.variants.iter() {
+                self.constant(
+                    enum_type.constant_name(variant),
+                    *encoding,
+                    enum_type.bit_width,
+                );
+            }
+        }
+        let ret = self.constants.borrow()[&name];
+        let matches = match ret.data {
+            SignalData::Lit {
+                ref value,
+                bit_width,
+            } => value.numeric_value() == variant.encoding() && bit_width == enum_type.bit_width,
+            _ => false,
+        };
+        if !matches {
+            panic!(
+                "Cannot use variant \"{}\" of enum \"{}\" in module \"{}\", because the module already contains a different constant called \"{}\".",
+                variant.name(),
+                enum_type.name,
+                self.name,
+                name
+            );
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn bit_widths() {
+        assert_eq!(EnumType::new("A", &["X"]).bit_width(), 1);
+        assert_eq!(EnumType::new("A", &["X", "Y"]).bit_width(), 1);
+        assert_eq!(EnumType::new("A", &["X", "Y", "Z"]).bit_width(), 2);
+        assert_eq!(EnumType::with_encodings("A", &[("X", 0x80)]).bit_width(), 8);
+    }
+
+    #[test]
+    fn variant_names_only_used_for_variants() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let state_type = EnumType::new("State", &["Idle", "Run", "Done"]);
+        let mode_type = EnumType::new("Mode", &["Off", "On"]);
+        let state = m.enum_reg("state", &state_type);
+        state.default_value(&state_type.variant("Idle"));
+        state.drive_next(&m.enum_lit(&state_type.variant("Run")));
+        let mode = m.enum_signal(&mode_type, m.input("mode", 1));
+        let cnt = m.reg("cnt", 2);
+        cnt.default_value(0u32);
+        cnt.drive_next(cnt.value + m.lit(1u32, 2));
+        let go = m.input("go", 1);
+        m.output("running", state.value.is(&state_type.variant("Run")));
+        m.output("on", mode.is(&mode_type.variant("On")));
+        m.output("go", go.eq(m.high()));
+        m.output("cnt", cnt.value);
+
+        let mut w = Vec::new();
+        verilog::generate(m, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+
+        // Plain literals with the same values and bit widths as variants are emitted as literals
+        assert!(verilog.contains(" + 2'h1;"));
+        assert!(verilog.contains(" = go == 1'h1;"));
+        assert!(verilog.contains(" == STATE_RUN;"));
+        assert!(verilog.contains(" = mode == MODE_ON;"));
+        assert!(!verilog.contains("+ STATE_"));
+        assert!(!verilog.contains("go == MODE_"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Variants \"X\" and \"Z\" of enum \"A\" have the same encoding (1).")]
+    fn duplicate_encoding_error() {
+        let _ = EnumType::with_encodings("A", &[("X", 1), ("Y", 2), ("Z", 1)]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to compare a signal of enum \"State\" with variant \"Red\" of enum \"Color\"."
+    )]
+    fn compare_different_enum_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let state_type = EnumType::new("State", &["Idle", "Busy"]);
+        let color_type = EnumType::new("Color", &["Red", "Green"]);
+        let state = m.enum_reg("state", &state_type);
+
+        // Panic
+        let _ = state.value.is(&color_type.variant("Red"));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive register \"state\", which holds enum \"State\", with a signal of enum \"Color\"."
+    )]
+    fn drive_different_enum_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let state_type = EnumType::new("State", &["Idle", "Busy"]);
+        let color_type = EnumType::new("Color", &["Red", "Green"]);
+        let state = m.enum_reg("state", &state_type);
+
+        // Panic
+        state.drive_next(&m.enum_lit(&color_type.variant("Green")));
+    }
+
+    #[test]
+    fn verilog_localparams() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let state_type = EnumType::new("FsmState", &["Idle", "ReadData"]);
+        let state = m.enum_signal(&state_type, m.input("state", 1));
+        m.output("reading", state.is(&state_type.variant("ReadData")));
+
+        let mut w = Vec::new();
+        verilog::generate(m, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+        assert!(code.contains("localparam FSM_STATE_READ_DATA = 1'h1;"));
+        assert!(code.contains("state == FSM_STATE_READ_DATA"));
+    }
+}
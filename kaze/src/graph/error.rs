@@ -0,0 +1,193 @@
+use super::signal::*;
+
+use std::error;
+use std::fmt;
+use std::panic::Location;
+
+/// Describes why a graph construction method failed, returned by the `try_` variants of panicking graph methods (eg. [`Signal::try_bit`], [`Module::try_lit`]).
+///
+/// The `Display` output of each variant is the same message the corresponding panicking method panics with.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+/// let i = m.input("i", 4);
+///
+/// match i.try_bit(4) {
+///     Err(GraphError::BitIndexOutOfRange { index, bit_width, .. }) => {
+///         assert_eq!(index, 4);
+///         assert_eq!(bit_width, 4);
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+///
+/// [`Module::try_lit`]: ./struct.Module.html#method.try_lit
+/// [`Signal::try_bit`]: ./struct.Signal.html#method.try_bit
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GraphError {
+    /// Two `Signal`s from different `Module`s were combined.
+    DifferentModules,
+    /// Two `Signal`s that must have the same bit width were combined, but their bit widths differ.
+    BitWidthMismatch {
+        /// The bit width of the left-hand side `Signal`.
+        lhs_bit_width: u32,
+        /// The bit width of the right-hand side `Signal`.
+        rhs_bit_width: u32,
+        /// Where the left-hand side `Signal` was created.
+        lhs_location: &'static Location<'static>,
+        /// Where the right-hand side `Signal` was created.
+        rhs_location: &'static Location<'static>,
+    },
+    /// A bit index was greater than or equal to the bit width of the indexed `Signal`.
+    BitIndexOutOfRange {
+        /// The requested bit index.
+        index: u32,
+        /// The bit width of the indexed `Signal`.
+        bit_width: u32,
+        /// Where the indexed `Signal` was created.
+        location: &'static Location<'static>,
+    },
+    /// The lower bound of a bit range was greater than or equal to the bit width of the indexed `Signal`.
+    BitRangeLowOutOfRange {
+        /// The requested lower bound.
+        range_low: u32,
+        /// The bit width of the indexed `Signal`.
+        bit_width: u32,
+        /// Where the indexed `Signal` was created.
+        location: &'static Location<'static>,
+    },
+    /// The upper bound of a bit range was greater than or equal to the bit width of the indexed `Signal`.
+    BitRangeHighOutOfRange {
+        /// The requested upper bound.
+        range_high: u32,
+        /// The bit width of the indexed `Signal`.
+        bit_width: u32,
+        /// Where the indexed `Signal` was created.
+        location: &'static Location<'static>,
+    },
+    /// The lower bound of a bit range was greater than its upper bound.
+    BitRangeInverted {
+        /// The requested upper bound.
+        range_high: u32,
+        /// The requested lower bound.
+        range_low: u32,
+    },
+    /// Concatenating two `Signal`s would result in a bit width greater than [`MAX_SIGNAL_BIT_WIDTH`].
+    ///
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    ConcatTooWide {
+        /// The bit width of the left-hand side (upper) `Signal`.
+        lhs_bit_width: u32,
+        /// The bit width of the right-hand side (lower) `Signal`.
+        rhs_bit_width: u32,
+        /// Where the left-hand side `Signal` was created.
+        lhs_location: &'static Location<'static>,
+        /// Where the right-hand side `Signal` was created.
+        rhs_location: &'static Location<'static>,
+    },
+    /// A `Signal` was requested with a bit width less than [`MIN_SIGNAL_BIT_WIDTH`].
+    ///
+    /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
+    BitWidthTooSmall {
+        /// What was being created, including its article (eg. `"a literal"`, `"an input"`).
+        what: &'static str,
+        /// The requested bit width.
+        bit_width: u32,
+    },
+    /// A `Signal` was requested with a bit width greater than [`MAX_SIGNAL_BIT_WIDTH`].
+    ///
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    BitWidthTooLarge {
+        /// What was being created, including its article (eg. `"a literal"`, `"an input"`).
+        what: &'static str,
+        /// The requested bit width.
+        bit_width: u32,
+    },
+    /// A literal value doesn't fit into the requested bit width.
+    LitValueTooWide {
+        /// The literal value.
+        value: u128,
+        /// The requested bit width.
+        bit_width: u32,
+        /// The number of bits required to represent `value`.
+        required_bits: u32,
+    },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GraphError::DifferentModules => {
+                write!(f, "Attempted to combine signals from different modules.")
+            }
+            GraphError::BitWidthMismatch {
+                lhs_bit_width,
+                rhs_bit_width,
+                lhs_location,
+                rhs_location,
+            } => write!(
+                f,
+                "Signals have different bit widths ({} and {}, respectively). The signals were created at {} and {}.",
+                lhs_bit_width, rhs_bit_width, lhs_location, rhs_location
+            ),
+            GraphError::BitIndexOutOfRange {
+                index,
+                bit_width,
+                location,
+            } => write!(f, "Attempted to take bit index {} from a signal with a width of {} bits. Bit indices must be in the range [0, {}] for a signal with a width of {} bits. The signal was created at {}.", index, bit_width, bit_width - 1, bit_width, location),
+            GraphError::BitRangeLowOutOfRange {
+                range_low,
+                bit_width,
+                location,
+            } => write!(f, "Cannot specify a range of bits where the lower bound is greater than or equal to the number of bits in the source signal. The bounds must be in the range [0, {}] for a signal with a width of {} bits, but a lower bound of {} was given. The signal was created at {}.", bit_width - 1, bit_width, range_low, location),
+            GraphError::BitRangeHighOutOfRange {
+                range_high,
+                bit_width,
+                location,
+            } => write!(f, "Cannot specify a range of bits where the upper bound is greater than or equal to the number of bits in the source signal. The bounds must be in the range [0, {}] for a signal with a width of {} bits, but an upper bound of {} was given. The signal was created at {}.", bit_width - 1, bit_width, range_high, location),
+            GraphError::BitRangeInverted { .. } => write!(
+                f,
+                "Cannot specify a range of bits where the lower bound is greater than the upper bound."
+            ),
+            GraphError::ConcatTooWide {
+                lhs_bit_width,
+                rhs_bit_width,
+                lhs_location,
+                rhs_location,
+            } => write!(f, "Attempted to concatenate signals with {} bit(s) and {} bit(s) respectively, but this would result in a bit width of {}, which is greater than the maximum signal bit width of {} bit(s). The signals were created at {} and {}.", lhs_bit_width, rhs_bit_width, lhs_bit_width + rhs_bit_width, MAX_SIGNAL_BIT_WIDTH, lhs_location, rhs_location),
+            GraphError::BitWidthTooSmall { what, bit_width } => write!(
+                f,
+                "Cannot create {} with {} bit(s). Signals must not be narrower than {} bit(s).",
+                what, bit_width, MIN_SIGNAL_BIT_WIDTH
+            ),
+            GraphError::BitWidthTooLarge { what, bit_width } => write!(
+                f,
+                "Cannot create {} with {} bit(s). Signals must not be wider than {} bit(s).",
+                what, bit_width, MAX_SIGNAL_BIT_WIDTH
+            ),
+            GraphError::LitValueTooWide {
+                value,
+                bit_width,
+                required_bits,
+            } => write!(f, "Cannot fit the specified value '{}' into the specified bit width '{}'. The value '{}' requires a bit width of at least {} bit(s).", value, bit_width, value, required_bits),
+        }
+    }
+}
+
+impl error::Error for GraphError {}
+
+pub(super) fn check_bit_width(what: &'static str, bit_width: u32) -> Result<(), GraphError> {
+    if bit_width < MIN_SIGNAL_BIT_WIDTH {
+        return Err(GraphError::BitWidthTooSmall { what, bit_width });
+    }
+    if bit_width > MAX_SIGNAL_BIT_WIDTH {
+        return Err(GraphError::BitWidthTooLarge { what, bit_width });
+    }
+    Ok(())
+}
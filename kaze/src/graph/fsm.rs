@@ -0,0 +1,449 @@
+use super::module::*;
+use super::register::*;
+use super::signal::*;
+
+use std::cell::RefCell;
+use std::panic::Location;
+use std::ptr;
+
+/// How the states of an [`Fsm`] are encoded in its state register.
+///
+/// The encoding only changes the generated logic; an [`Fsm`] with the same states, transitions, and outputs behaves identically with either encoding.
+///
+/// [`Fsm`]: ./struct.Fsm.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FsmEncoding {
+    /// Each state is encoded as its index, so the state register has `max(1, ceil(log2(num_states)))` bits.
+    Binary,
+    /// Each state is encoded as a single set bit at its index, so the state register has one bit per state, and checking whether the `Fsm` is in a given state only requires looking at a single bit.
+    OneHot,
+}
+
+/// A finite state machine, created by the [`Module`]::[`fsm`] method.
+///
+/// An `Fsm` is described by its states (the first of which is its initial state, which it's in after reset), its transitions (added with [`transition`]), and any outputs that depend on its state (created with [`output`]).
+/// Once all transitions have been added, [`build`] creates the `Fsm`'s next-state logic.
+/// While in a given state, the `Fsm` takes the first transition from that state (in the order they were added) whose condition is high, and stays in the same state if none of them are.
+///
+/// [`validation::lint`] reports states that can't be reached from the initial state, and transitions that can never be taken, as warnings.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+///
+/// let start = m.input("start", 1);
+/// let done = m.input("done", 1);
+///
+/// let fsm = m.fsm("state", &["Idle", "Busy", "Finished"], FsmEncoding::Binary);
+/// fsm.transition("Idle", start, "Busy");
+/// fsm.transition("Busy", done, "Finished");
+/// fsm.transition("Finished", m.high(), "Idle");
+/// m.output("busy", fsm.is("Busy"));
+/// m.output("code", fsm.output(&[("Busy", m.lit(1u32, 2)), ("Finished", m.lit(2u32, 2))]));
+/// fsm.build();
+/// ```
+///
+/// [`build`]: #method.build
+/// [`fsm`]: ./struct.Module.html#method.fsm
+/// [`Module`]: ./struct.Module.html
+/// [`output`]: #method.output
+/// [`transition`]: #method.transition
+/// [`validation::lint`]: ./validation/fn.lint.html
+#[must_use]
+pub struct Fsm<'a> {
+    module: &'a Module<'a>,
+    location: &'static Location<'static>,
+
+    name: String,
+    states: Vec<String>,
+    encoding: FsmEncoding,
+    register: &'a Register<'a>,
+    transitions: RefCell<Vec<FsmTransition<'a>>>,
+}
+
+pub(crate) struct FsmTransition<'a> {
+    pub from: usize,
+    pub condition: &'a Signal<'a>,
+    pub to: usize,
+}
+
+pub(crate) struct FsmData<'a> {
+    pub location: &'static Location<'static>,
+
+    pub name: String,
+    pub states: Vec<String>,
+    pub transitions: Vec<FsmTransition<'a>>,
+}
+
+impl<'a> Fsm<'a> {
+    /// Returns the [`Signal`] holding this `Fsm`'s encoded state, as specified by its [`FsmEncoding`].
+    ///
+    /// [`FsmEncoding`]: ./enum.FsmEncoding.html
+    /// [`Signal`]: ./struct.Signal.html
+    pub fn state(&self) -> &'a Signal<'a> {
+        self.register.value
+    }
+
+    /// Creates a 1-bit [`Signal`] that's high while this `Fsm` is in the state called `state`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Fsm` doesn't have a state called `state`.
+    ///
+    /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
+    pub fn is(&self, state: &str) -> &'a Signal<'a> {
+        let index = self.state_index(state);
+        self.is_index(index)
+    }
+
+    /// Adds a transition from the state called `from` to the state called `to`, which is taken when `condition` is high.
+    ///
+    /// Transitions from the same state are prioritized in the order they're added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Fsm` doesn't have states called `from` or `to`, if `condition` belongs to a different [`Module`] than this `Fsm`, or if `condition`'s bit width is not 1.
+    ///
+    /// [`Module`]: ./struct.Module.html
+    #[track_caller]
+    pub fn transition(&self, from: &str, condition: &'a Signal<'a>, to: &str) {
+        let from = self.state_index(from);
+        let to = self.state_index(to);
+        if !ptr::eq(self.module, condition.module) {
+            panic!(
+                "Attempted to add a transition to state machine \"{}\" with a condition from another module.",
+                self.name
+            );
+        }
+        if condition.bit_width() != 1 {
+            panic!("Attempted to add a transition to state machine \"{}\" with a condition with {} bit(s), but transition conditions are required to be 1 bit wide. The condition was created at {}.", self.name, condition.bit_width(), condition.location);
+        }
+        self.transitions.borrow_mut().push(FsmTransition {
+            from,
+            condition,
+            to,
+        });
+    }
+
+    /// Creates a [`Signal`] whose value depends only on this `Fsm`'s state (a [Moore output](https://en.wikipedia.org/wiki/Moore_machine)), from a list of `(state, value)` pairs.
+    ///
+    /// While this `Fsm` is in one of the listed states, the returned `Signal` has the corresponding value; in every other state, it's `0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty or lists the same state more than once, if this `Fsm` doesn't have one of the listed states, or if the values don't all belong to this `Fsm`'s [`Module`] and have the same bit width.
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
+    pub fn output(&self, values: &[(&str, &'a Signal<'a>)]) -> &'a Signal<'a> {
+        if values.is_empty() {
+            panic!(
+                "Cannot create an output for state machine \"{}\" without any values.",
+                self.name
+            );
+        }
+        let bit_width = values[0].1.bit_width();
+        let mut ret: Option<&'a Signal<'a>> = None;
+        for (index, &(state, value)) in values.iter().enumerate() {
+            if values[..index]
+                .iter()
+                .any(|&(other_state, _)| other_state == state)
+            {
+                panic!(
+                    "Attempted to specify the value of an output of state machine \"{}\" in state \"{}\" more than once.",
+                    self.name, state
+                );
+            }
+            let state_index = self.state_index(state);
+            if !ptr::eq(self.module, value.module) {
+                panic!(
+                    "Attempted to specify the value of an output of state machine \"{}\" in state \"{}\" with a signal from another module.",
+                    self.name, state
+                );
+            }
+            if value.bit_width() != bit_width {
+                panic!("Attempted to specify the value of an output of state machine \"{}\" in state \"{}\" with a signal with {} bit(s), but its value in state \"{}\" has {} bit(s). The signal was created at {}.", self.name, state, value.bit_width(), values[0].0, bit_width, value.location);
+            }
+            // Exactly one state is active at a time, so masking each value by its state and ORing them together selects the active state's value
+            let masked = value & self.is_index(state_index).repeat(bit_width);
+            ret = Some(match ret {
+                Some(ret) => ret | masked,
+                None => masked,
+            });
+        }
+        ret.unwrap()
+    }
+
+    /// Creates this `Fsm`'s next-state logic from the transitions added with [`transition`], and records this `Fsm`'s description so that [`validation::lint`] can check it.
+    ///
+    /// [`transition`]: #method.transition
+    /// [`validation::lint`]: ./validation/fn.lint.html
+    #[track_caller]
+    pub fn build(self) {
+        let transitions = self.transitions.replace(Vec::new());
+        let num_states = self.states.len();
+
+        // For each state, whether each of its transitions is taken (taking priority into account), and whether it stays in the same state
+        let mut taken = Vec::with_capacity(transitions.len());
+        let mut stays = Vec::with_capacity(num_states);
+        for state in 0..num_states {
+            let mut any_earlier: Option<&'a Signal<'a>> = None;
+            for transition in transitions.iter().filter(|t| t.from == state) {
+                let condition = match any_earlier {
+                    Some(any_earlier) => transition.condition & !any_earlier,
+                    None => transition.condition,
+                };
+                taken.push((transition, condition));
+                any_earlier = Some(match any_earlier {
+                    Some(any_earlier) => any_earlier | transition.condition,
+                    None => transition.condition,
+                });
+            }
+            stays.push(any_earlier.map(|any_transition| !any_transition));
+        }
+
+        let next = match self.encoding {
+            FsmEncoding::Binary => {
+                let bit_width = self.register.value.bit_width();
+                let mut next = self.register.value;
+                for state in (0..num_states).rev() {
+                    let mut state_next = self.module.lit(state as u128, bit_width);
+                    for &(transition, condition) in taken.iter().rev() {
+                        if transition.from == state {
+                            state_next = condition.mux(
+                                self.module.lit(transition.to as u128, bit_width),
+                                state_next,
+                            );
+                        }
+                    }
+                    next = self.is_index(state).mux(state_next, next);
+                }
+                next
+            }
+            FsmEncoding::OneHot => {
+                let mut next_bits = Vec::with_capacity(num_states);
+                for state in 0..num_states {
+                    let mut bit = match stays[state] {
+                        Some(stays) => self.is_index(state) & stays,
+                        None => self.is_index(state),
+                    };
+                    for &(transition, condition) in taken.iter() {
+                        if transition.to == state {
+                            bit = bit | (self.is_index(transition.from) & condition);
+                        }
+                    }
+                    next_bits.push(bit);
+                }
+                self.module.pack(&next_bits)
+            }
+        };
+        self.register.drive_next(next);
+
+        self.module.fsms.borrow_mut().push(FsmData {
+            location: self.location,
+
+            name: self.name,
+            states: self.states,
+            transitions,
+        });
+    }
+
+    #[track_caller]
+    fn state_index(&self, state: &str) -> usize {
+        match self.states.iter().position(|other| other == state) {
+            Some(index) => index,
+            _ => panic!(
+                "State machine \"{}\" doesn't have a state called \"{}\". Its states are {}.",
+                self.name,
+                state,
+                self.states
+                    .iter()
+                    .map(|state| format!("\"{}\"", state))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    #[track_caller]
+    fn is_index(&self, index: usize) -> &'a Signal<'a> {
+        let value = self.register.value;
+        match self.encoding {
+            FsmEncoding::Binary => value.eq(self.module.lit(index as u128, value.bit_width())),
+            FsmEncoding::OneHot => value.bit(index as u32),
+        }
+    }
+}
+
+impl<'a> Module<'a> {
+    /// Creates an [`Fsm`] in this `Module` called `name` with the given `states`, whose state register is encoded as specified by `encoding`.
+    ///
+    /// The first state is the `Fsm`'s initial state, which it's in after reset.
+    /// The `Fsm`'s state register is also called `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `states` is empty or contains the same name more than once, if `encoding` is [`FsmEncoding::OneHot`] and there are more than [`MAX_SIGNAL_BIT_WIDTH`] states, or if this `Module` is an extern module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let fsm = m.fsm("state", &["A", "B", "C"], FsmEncoding::OneHot);
+    /// assert_eq!(fsm.state().bit_width(), 3);
+    /// fsm.transition("A", m.input("go", 1), "B");
+    /// fsm.transition("B", m.high(), "C");
+    /// m.output("done", fsm.is("C"));
+    /// fsm.build();
+    /// ```
+    ///
+    /// [`Fsm`]: ./struct.Fsm.html
+    /// [`FsmEncoding::OneHot`]: ./enum.FsmEncoding.html#variant.OneHot
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    #[track_caller]
+    pub fn fsm<S: Into<String>>(
+        &'a self,
+        name: S,
+        states: &[&str],
+        encoding: FsmEncoding,
+    ) -> Fsm<'a> {
+        let name = name.into();
+        if states.is_empty() {
+            panic!(
+                "Cannot create state machine \"{}\" without any states.",
+                name
+            );
+        }
+        for (index, state) in states.iter().enumerate() {
+            if states[..index].contains(state) {
+                panic!(
+                    "State machine \"{}\" has more than one state called \"{}\".",
+                    name, state
+                );
+            }
+        }
+        let (bit_width, initial_value) = match encoding {
+            FsmEncoding::Binary => (
+                (usize::BITS - (states.len() - 1).leading_zeros()).max(MIN_SIGNAL_BIT_WIDTH),
+                0u32,
+            ),
+            FsmEncoding::OneHot => {
+                if states.len() > MAX_SIGNAL_BIT_WIDTH as usize {
+                    panic!("Cannot create one-hot state machine \"{}\" with {} states. One-hot state machines must not have more than {} states.", name, states.len(), MAX_SIGNAL_BIT_WIDTH);
+                }
+                (states.len() as u32, 1u32)
+            }
+        };
+        let register = self.reg(name.clone(), bit_width);
+        register.default_value(initial_value);
+        Fsm {
+            module: self,
+            location: Location::caller(),
+
+            name,
+            states: states.iter().map(|&state| state.into()).collect(),
+            encoding,
+            register,
+            transitions: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    #[should_panic(expected = "Cannot create state machine \"fsm\" without any states.")]
+    fn no_states_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.fsm("fsm", &[], FsmEncoding::Binary);
+    }
+
+    #[test]
+    #[should_panic(expected = "State machine \"fsm\" has more than one state called \"A\".")]
+    fn duplicate_state_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.fsm("fsm", &["A", "B", "A"], FsmEncoding::Binary);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "State machine \"fsm\" doesn't have a state called \"C\". Its states are \"A\", \"B\"."
+    )]
+    fn unknown_state_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let fsm = m.fsm("fsm", &["A", "B"], FsmEncoding::Binary);
+
+        // Panic
+        fsm.transition("A", m.high(), "C");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to add a transition to state machine \"fsm\" with a condition with 2 bit(s), but transition conditions are required to be 1 bit wide."
+    )]
+    fn transition_condition_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let fsm = m.fsm("fsm", &["A", "B"], FsmEncoding::Binary);
+
+        // Panic
+        fsm.transition("A", m.input("i", 2), "B");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify the value of an output of state machine \"fsm\" in state \"B\" with a signal with 3 bit(s), but its value in state \"A\" has 2 bit(s)."
+    )]
+    fn output_bit_width_mismatch_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let fsm = m.fsm("fsm", &["A", "B"], FsmEncoding::Binary);
+
+        // Panic
+        let _ = fsm.output(&[("A", m.lit(1u32, 2)), ("B", m.lit(1u32, 3))]);
+    }
+
+    #[test]
+    fn state_bit_widths() {
+        for &(num_states, binary_bit_width) in &[(1, 1), (2, 1), (3, 2), (4, 2), (5, 3), (128, 7)] {
+            let c = Context::new();
+
+            let m = c.module("A");
+            let states = (0..num_states)
+                .map(|index| format!("S{}", index))
+                .collect::<Vec<_>>();
+            let states = states.iter().map(|state| &state[..]).collect::<Vec<_>>();
+
+            let binary = m.fsm("binary", &states, FsmEncoding::Binary);
+            assert_eq!(binary.state().bit_width(), binary_bit_width);
+            let one_hot = m.fsm("one_hot", &states, FsmEncoding::OneHot);
+            assert_eq!(one_hot.state().bit_width(), num_states);
+        }
+    }
+}
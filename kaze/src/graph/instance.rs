@@ -1,10 +1,12 @@
 use super::context::*;
 use super::module::*;
+use super::register::is_valid_attribute_key;
 use super::signal::*;
 
 use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
+use std::panic::Location;
 use std::ptr;
 
 /// An instance of a [`Module`], created by the [`Module`]::[`instance`] method.
@@ -33,13 +35,47 @@ use std::ptr;
 pub struct Instance<'a> {
     pub(super) context: &'a Context<'a>,
     pub(super) module: &'a Module<'a>,
+    pub(crate) location: &'static Location<'static>,
 
     pub(crate) instantiated_module: &'a Module<'a>,
     pub(crate) name: String,
     pub(crate) driven_inputs: RefCell<BTreeMap<String, &'a Signal<'a>>>,
+    pub(crate) parameters: RefCell<Vec<(String, ParameterValue)>>,
 }
 
 impl<'a> Instance<'a> {
+    /// Returns this `Instance`'s name, as specified when it was created by the [`Module`]::[`instance`] method.
+    ///
+    /// [`instance`]: ./struct.Module.html#method.instance
+    /// [`Module`]: ./struct.Module.html
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the [`Module`] that this `Instance` instantiates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let inner = c.module("Inner");
+    /// inner.output("o", inner.input("i", 32));
+    ///
+    /// let outer = c.module("Outer");
+    /// let inner_inst = outer.instance("inner_inst", "Inner");
+    ///
+    /// assert_eq!(inner_inst.name(), "inner_inst");
+    /// assert_eq!(inner_inst.instantiated_module().name(), "Inner");
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub fn instantiated_module(&self) -> &'a Module<'a> {
+        self.instantiated_module
+    }
+
     /// Drives the input of this [`Module`] `Instance` specified by `name` with the given [`Signal`].
     ///
     /// # Panics
@@ -71,18 +107,109 @@ impl<'a> Instance<'a> {
             panic!("Attempted to drive an instance input with a signal from a different module.");
         }
         if !self.instantiated_module.inputs.borrow().contains_key(&name) {
-            panic!("Attempted to drive an input called \"{}\" on an instance of \"{}\", but no such input with this name exists on this module.", name, self.instantiated_module.name);
+            panic!("Attempted to drive an input called \"{}\" on an instance of \"{}\", but no such input with this name exists on this module. The instance \"{}\" was created at {}, and {}.", name, self.instantiated_module.name, self.name, self.location, self.expected_inputs());
         }
         if driven_inputs.contains_key(&name) {
             panic!("Attempted to drive an input called \"{}\" on an instance of \"{}\", but this input is already driven for this instance.", name, self.instantiated_module.name);
         }
         let input_bit_width = self.instantiated_module.inputs.borrow()[&name].bit_width();
         if input_bit_width != i.bit_width() {
-            panic!("Attempted to drive an input called \"{}\" on an instance of \"{}\", but this input and the provided signal have different bit widths ({} and {}, respectively).", name, self.instantiated_module.name, input_bit_width, i.bit_width());
+            panic!("Attempted to drive an input called \"{}\" on an instance of \"{}\", but this input and the provided signal have different bit widths ({} and {}, respectively). The instance \"{}\" was created at {}, and the signal was created at {}. For reference, {}.", name, self.instantiated_module.name, input_bit_width, i.bit_width(), self.name, self.location, i.location, self.expected_inputs());
         }
         driven_inputs.insert(name, i);
     }
 
+    /// Drives several inputs of this [`Module`] `Instance` at once, given as `(name, signal)` pairs.
+    ///
+    /// This is equivalent to calling [`drive_input`] for each pair, in order, which is convenient for instances with many ports, or when the pairs are computed (e.g. collected into a `Vec` or a map).
+    /// To drive inputs that were created from a [`BundleSpec`], see [`drive_bundle_inputs`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`drive_input`] for any of the pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let adder = c.module("Adder");
+    /// adder.output("sum", adder.input("a", 8) + adder.input("b", 8));
+    ///
+    /// let top = c.module("Top");
+    /// let adder_inst = top.instance("adder_inst", "Adder");
+    /// adder_inst.drive_inputs(vec![
+    ///     ("a", top.input("x", 8)),
+    ///     ("b", top.lit(1u32, 8)),
+    /// ]);
+    /// top.output("y", adder_inst.output("sum"));
+    /// ```
+    ///
+    /// [`BundleSpec`]: ./struct.BundleSpec.html
+    /// [`drive_bundle_inputs`]: #method.drive_bundle_inputs
+    /// [`drive_input`]: #method.drive_input
+    /// [`Module`]: ./struct.Module.html
+    pub fn drive_inputs<S: Into<String>, I: IntoIterator<Item = (S, &'a Signal<'a>)>>(
+        &'a self,
+        inputs: I,
+    ) {
+        for (name, i) in inputs {
+            self.drive_input(name, i);
+        }
+    }
+
+    /// Drives inputs of this [`Module`] `Instance` like [`drive_inputs`], and additionally checks that every input of the instantiated [`Module`] is driven afterwards.
+    ///
+    /// Inputs that were already driven before this call count as driven.
+    /// This is useful for passing through same-named inputs of the enclosing [`Module`], or for driving an instance from a map that's expected to cover all of its ports.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`drive_input`] for any of the pairs, or if any input of this `Instance` is left undriven.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let adder = c.module("Adder");
+    /// adder.output("sum", adder.input("a", 8) + adder.input("b", 8));
+    ///
+    /// let top = c.module("Top");
+    /// let a = top.input("a", 8);
+    /// let b = top.input("b", 8);
+    /// let adder_inst = top.instance("adder_inst", "Adder");
+    /// // Drive each of adder_inst's inputs with top's input of the same name
+    /// adder_inst.drive_all_inputs(top.inputs().iter().map(|(name, &i)| (name.clone(), i)));
+    /// top.output("sum", adder_inst.output("sum"));
+    /// ```
+    ///
+    /// [`drive_input`]: #method.drive_input
+    /// [`drive_inputs`]: #method.drive_inputs
+    /// [`Module`]: ./struct.Module.html
+    pub fn drive_all_inputs<S: Into<String>, I: IntoIterator<Item = (S, &'a Signal<'a>)>>(
+        &'a self,
+        inputs: I,
+    ) {
+        self.drive_inputs(inputs);
+        let driven_inputs = self.driven_inputs.borrow();
+        let undriven_inputs = self
+            .instantiated_module
+            .inputs
+            .borrow()
+            .keys()
+            .filter(|name| !driven_inputs.contains_key(*name))
+            .map(|name| format!("\"{}\"", name))
+            .collect::<Vec<_>>();
+        if !undriven_inputs.is_empty() {
+            panic!("Attempted to drive all inputs on instance \"{}\" of \"{}\", but the following input(s) were left undriven: {}. The instance was created at {}.", self.name, self.instantiated_module.name, undriven_inputs.join(", "), self.location);
+        }
+    }
+
     /// Creates a [`Signal`] that represents this `Instance`'s output called `name`.
     ///
     /// # Panics
@@ -107,6 +234,7 @@ impl<'a> Instance<'a> {
     ///
     /// [`Module`]: ./struct.Module.html
     /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
     pub fn output<S: Into<String>>(&'a self, name: S) -> &Signal<'a> {
         let name = name.into();
         if !self
@@ -117,15 +245,255 @@ impl<'a> Instance<'a> {
         {
             panic!("Attempted to create a signal for an output called \"{}\" on an instance of \"{}\", but no such output with this name exists on this module.", name, self.instantiated_module.name);
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::InstanceOutput {
+        self.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::InstanceOutput {
                 instance: self,
-                name,
+                name: self.context.intern(&name),
             },
-        })
+        )
+    }
+
+    /// Creates a [`Signal`] for each of this `Instance`'s outputs, returned as `(name, signal)` pairs ordered by name.
+    ///
+    /// This is mostly useful for collecting the signals available to [`connect_by_name`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let inner = c.module("Inner");
+    /// inner.output("b", inner.lit(true, 1));
+    /// inner.output("a", inner.lit(0u32, 8));
+    ///
+    /// let outer = c.module("Outer");
+    /// let inner_inst = outer.instance("inner_inst", "Inner");
+    /// let outputs = inner_inst.outputs();
+    /// assert_eq!(outputs.len(), 2);
+    /// assert_eq!(outputs[0].0, "a");
+    /// assert_eq!(outputs[0].1.bit_width(), 8);
+    /// ```
+    ///
+    /// [`connect_by_name`]: #method.connect_by_name
+    /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
+    pub fn outputs(&'a self) -> Vec<(String, &'a Signal<'a>)> {
+        let output_names: Vec<String> = self
+            .instantiated_module
+            .outputs
+            .borrow()
+            .keys()
+            .cloned()
+            .collect();
+        let mut ret = Vec::new();
+        for name in output_names {
+            let signal = self.output(name.clone());
+            ret.push((name, signal));
+        }
+        ret
+    }
+
+    /// Drives each undriven input of this `Instance` with the signal of the same name from `available`, and returns the names of the inputs that couldn't be resolved this way, ordered by name.
+    ///
+    /// `available` is typically assembled from the enclosing [`Module`]'s [`inputs`] and the [`outputs`] of other `Instance`s, which is convenient for stitching together pipeline stages whose port names line up by convention.
+    /// Inputs that are already driven are left alone, and signals in `available` that don't match any undriven input are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than one signal in `available` has the name of an undriven input, since there's no way to choose between them, or under the same conditions as [`drive_input`] for any input that is driven.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let stage0 = c.module("Stage0");
+    /// stage0.output("data", stage0.input("in", 8) + stage0.lit(1u32, 8));
+    ///
+    /// let stage1 = c.module("Stage1");
+    /// stage1.output("out", stage1.input("data", 8) ^ stage1.input("mask", 8));
+    ///
+    /// let top = c.module("Top");
+    /// let _ = top.input("in", 8);
+    /// let stage0_inst = top.instance("stage0", "Stage0");
+    /// let stage1_inst = top.instance("stage1", "Stage1");
+    ///
+    /// let mut available = top
+    ///     .inputs()
+    ///     .iter()
+    ///     .map(|(name, &i)| (name.clone(), i))
+    ///     .collect::<Vec<_>>();
+    /// assert!(stage0_inst.connect_by_name(available.clone()).is_empty());
+    ///
+    /// available.extend(stage0_inst.outputs());
+    /// assert_eq!(stage1_inst.connect_by_name(available), vec!["mask"]);
+    /// stage1_inst.drive_input("mask", top.lit(0x0fu32, 8));
+    ///
+    /// top.output("out", stage1_inst.output("out"));
+    /// ```
+    ///
+    /// [`drive_input`]: #method.drive_input
+    /// [`inputs`]: ./struct.Module.html#method.inputs
+    /// [`Module`]: ./struct.Module.html
+    /// [`outputs`]: #method.outputs
+    pub fn connect_by_name<S: Into<String>, I: IntoIterator<Item = (S, &'a Signal<'a>)>>(
+        &'a self,
+        available: I,
+    ) -> Vec<String> {
+        let mut candidates: BTreeMap<String, Vec<&'a Signal<'a>>> = BTreeMap::new();
+        for (name, signal) in available {
+            candidates.entry(name.into()).or_default().push(signal);
+        }
+        let undriven_inputs: Vec<String> = {
+            let driven_inputs = self.driven_inputs.borrow();
+            self.instantiated_module
+                .inputs
+                .borrow()
+                .keys()
+                .filter(|name| !driven_inputs.contains_key(*name))
+                .cloned()
+                .collect()
+        };
+        let mut unresolved_inputs = Vec::new();
+        for name in undriven_inputs {
+            match candidates.get(&name).map(|signals| &signals[..]) {
+                None => unresolved_inputs.push(name),
+                Some([signal]) => self.drive_input(name, signal),
+                Some(signals) => panic!("Attempted to connect the input called \"{}\" on instance \"{}\" of \"{}\" by name, but {} signals with this name are available, so the connection is ambiguous. The instance was created at {}, and the signals were created at {}.", name, self.name, self.instantiated_module.name, signals.len(), self.location, signals.iter().map(|signal| signal.location.to_string()).collect::<Vec<_>>().join(", ")),
+            }
+        }
+        unresolved_inputs
+    }
+
+    /// Specifies the value of the parameter called `key` on this `Instance` of an extern [`Module`].
+    ///
+    /// Parameters are emitted in generated Verilog code as `#(.key(value), ...)` in this `Instance`'s instantiation, in the order they were specified.
+    /// Integer values are emitted in decimal, real values with a decimal point, and string values as quoted string literals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Instance`'s [`Module`] isn't an extern [`Module`], if `key` isn't a valid Verilog identifier, if this `Instance` already has a parameter called `key`, or if `value` is a real value that isn't finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let pll = c.extern_module("PLLE2_BASE");
+    /// pll.input("CLKIN1", 1);
+    /// pll.extern_output("CLKOUT0", 1);
+    ///
+    /// let top = c.module("Top");
+    /// let pll_inst = top.instance("pll", "PLLE2_BASE");
+    /// pll_inst.parameter("CLKFBOUT_MULT", 10);
+    /// pll_inst.parameter("CLKIN1_PERIOD", 10.0);
+    /// pll_inst.parameter("BANDWIDTH", "OPTIMIZED");
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub fn parameter<K: Into<String>, V: Into<ParameterValue>>(&'a self, key: K, value: V) {
+        let key = key.into();
+        let value = value.into();
+        if !self.instantiated_module.is_extern {
+            panic!("Attempted to specify a parameter called \"{}\" on instance \"{}\" of \"{}\", but parameters can only be specified on instances of extern modules.", key, self.name, self.instantiated_module.name);
+        }
+        if !is_valid_attribute_key(&key) {
+            panic!("Attempted to specify a parameter called \"{}\" on instance \"{}\" of \"{}\", but parameter names must be valid Verilog identifiers.", key, self.name, self.instantiated_module.name);
+        }
+        if let ParameterValue::Real(value) = value {
+            if !value.is_finite() {
+                panic!("Attempted to specify a parameter called \"{}\" on instance \"{}\" of \"{}\" with the value {}, but real parameter values must be finite.", key, self.name, self.instantiated_module.name, value);
+            }
+        }
+        let mut parameters = self.parameters.borrow_mut();
+        if parameters
+            .iter()
+            .any(|(existing_key, _)| *existing_key == key)
+        {
+            panic!("Attempted to specify a parameter called \"{}\" on instance \"{}\" of \"{}\", but this instance already has a parameter with this name.", key, self.name, self.instantiated_module.name);
+        }
+        parameters.push((key, value));
+    }
+
+    // Describes the inputs of the instantiated module, for error messages
+    fn expected_inputs(&self) -> String {
+        let inputs = self.instantiated_module.inputs.borrow();
+        if inputs.is_empty() {
+            return format!(
+                "\"{}\" doesn't have any inputs",
+                self.instantiated_module.name
+            );
+        }
+        format!(
+            "the inputs of \"{}\" are {}",
+            self.instantiated_module.name,
+            inputs
+                .iter()
+                .map(|(name, input)| format!("\"{}\" ({} bit(s))", name, input.bit_width()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// The value of a parameter on an [`Instance`] of an extern [`Module`], specified by the [`Instance`]::[`parameter`] method.
+///
+/// This type is usually constructed implicitly from an integer, `f64`, or string value.
+///
+/// [`Instance`]: ./struct.Instance.html
+/// [`Module`]: ./struct.Module.html
+/// [`parameter`]: ./struct.Instance.html#method.parameter
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParameterValue {
+    /// An integer value.
+    Integer(i64),
+    /// A real (floating-point) value.
+    Real(f64),
+    /// A string value.
+    String(String),
+}
+
+impl From<i32> for ParameterValue {
+    fn from(value: i32) -> Self {
+        ParameterValue::Integer(value as _)
+    }
+}
+
+impl From<u32> for ParameterValue {
+    fn from(value: u32) -> Self {
+        ParameterValue::Integer(value as _)
+    }
+}
+
+impl From<i64> for ParameterValue {
+    fn from(value: i64) -> Self {
+        ParameterValue::Integer(value)
+    }
+}
+
+impl From<f64> for ParameterValue {
+    fn from(value: f64) -> Self {
+        ParameterValue::Real(value)
+    }
+}
+
+impl<'a> From<&'a str> for ParameterValue {
+    fn from(value: &'a str) -> Self {
+        ParameterValue::String(value.into())
+    }
+}
+
+impl From<String> for ParameterValue {
+    fn from(value: String) -> Self {
+        ParameterValue::String(value)
     }
 }
 
@@ -219,6 +587,288 @@ mod tests {
         inner_inst.drive_input("a", m.input("i1", 32));
     }
 
+    #[test]
+    fn drive_inputs() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let inner_inputs = (0..12)
+            .map(|i| inner.input(format!("i{}", i), i + 1))
+            .collect::<Vec<_>>();
+        inner.output(
+            "o",
+            inner_inputs
+                .iter()
+                .skip(1)
+                .fold(inner_inputs[0], |acc, i| acc.concat(i)),
+        );
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+        let pairs = (0..12)
+            .map(|i| (format!("i{}", i), m.lit(i, i + 1)))
+            .collect::<Vec<_>>();
+        inner_inst.drive_inputs(pairs);
+
+        let driven_inputs = inner_inst.driven_inputs.borrow();
+        assert_eq!(driven_inputs.len(), 12);
+        for i in 0..12 {
+            assert_eq!(driven_inputs[&format!("i{}", i)].bit_width(), i + 1);
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive an input called \"c\" on an instance of \"Inner\", but no such input with this name exists on this module. The instance \"inner_inst\" was created at"
+    )]
+    fn drive_inputs_nonexistent_input_error() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let _ = inner.input("a", 1);
+        let _ = inner.input("b", 4);
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+
+        // Panic
+        inner_inst.drive_inputs(vec![("a", m.input("a", 1)), ("c", m.input("c", 4))]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the inputs of \"Inner\" are \"a\" (1 bit(s)), \"b\" (4 bit(s)).")]
+    fn drive_inputs_nonexistent_input_lists_inputs_error() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let _ = inner.input("a", 1);
+        let _ = inner.input("b", 4);
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+
+        // Panic
+        inner_inst.drive_inputs(vec![("B", m.input("b", 4))]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive an input called \"b\" on an instance of \"Inner\", but this input and the provided signal have different bit widths (4 and 3, respectively). The instance \"inner_inst\" was created at"
+    )]
+    fn drive_inputs_incompatible_bit_widths_error() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let _ = inner.input("a", 1);
+        let _ = inner.input("b", 4);
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+
+        // Panic
+        inner_inst.drive_inputs(vec![("a", m.input("a", 1)), ("b", m.input("b", 3))]);
+    }
+
+    #[test]
+    fn drive_all_inputs() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("a", 1) & inner.input("b", 1));
+
+        let m = c.module("A");
+        let _ = m.input("b", 1);
+        let _ = m.input("a", 1);
+        let inner_inst = m.instance("inner_inst", "Inner");
+        inner_inst.drive_all_inputs(m.inputs().iter().map(|(name, &i)| (name.clone(), i)));
+
+        assert_eq!(inner_inst.driven_inputs.borrow().len(), 2);
+    }
+
+    #[test]
+    fn drive_all_inputs_already_driven() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("a", 1) & inner.input("b", 1));
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+        inner_inst.drive_input("a", m.high());
+        inner_inst.drive_all_inputs(vec![("b", m.low())]);
+
+        assert_eq!(inner_inst.driven_inputs.borrow().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive all inputs on instance \"inner_inst\" of \"Inner\", but the following input(s) were left undriven: \"b\", \"d\"."
+    )]
+    fn drive_all_inputs_undriven_input_error() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        for name in &["a", "b", "c", "d"] {
+            let _ = inner.input(*name, 1);
+        }
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+
+        // Panic
+        inner_inst.drive_all_inputs(vec![("a", m.high()), ("c", m.low())]);
+    }
+
+    #[test]
+    fn outputs() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("z", inner.input("i", 3));
+        inner.output("a", inner.lit(0u32, 5));
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+        let outputs = inner_inst.outputs();
+
+        assert_eq!(
+            outputs
+                .iter()
+                .map(|(name, signal)| (&name[..], signal.bit_width()))
+                .collect::<Vec<_>>(),
+            vec![("a", 5), ("z", 3)]
+        );
+        for (_, signal) in outputs {
+            assert!(ptr::eq(signal.module, m));
+        }
+    }
+
+    #[test]
+    fn connect_by_name() {
+        let c = Context::new();
+
+        let fetch = c.module("Fetch");
+        let pc = fetch.input("pc", 32);
+        fetch.output("pc", pc + fetch.lit(4u32, 32));
+        fetch.output("insn", pc ^ fetch.input("seed", 32));
+        fetch.output("valid", fetch.input("enable", 1));
+
+        let decode = c.module("Decode");
+        decode.output("op", decode.input("insn", 32).bits(6, 0));
+        decode.output(
+            "valid",
+            decode.input("valid", 1) & decode.input("enable", 1),
+        );
+        decode.output("pc", decode.input("pc", 32));
+
+        let execute = c.module("Execute");
+        execute.output(
+            "result",
+            execute.input("pc", 32) + execute.lit(0u32, 25).concat(execute.input("op", 7)),
+        );
+        execute.output(
+            "valid",
+            execute.input("valid", 1) & !execute.input("stall", 1),
+        );
+        execute.output("trace", execute.input("trace_data", 32));
+
+        let m = c.module("Top");
+        let _ = m.input("pc", 32);
+        let _ = m.input("enable", 1);
+        let fetch_inst = m.instance("fetch", "Fetch");
+        let decode_inst = m.instance("decode", "Decode");
+        let execute_inst = m.instance("execute", "Execute");
+
+        // Each stage sees the top-level inputs and the outputs of the previous stage only, since
+        //  stage outputs share names with each other
+        let top_inputs = m
+            .inputs()
+            .iter()
+            .map(|(name, &i)| (name.clone(), i))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            fetch_inst.connect_by_name(top_inputs.clone()),
+            vec!["seed".to_string()]
+        );
+
+        let mut available = fetch_inst.outputs();
+        available.push(("enable".into(), m.input("enable_decode", 1)));
+        assert!(decode_inst.connect_by_name(available).is_empty());
+
+        execute_inst.drive_input("stall", m.low());
+        let mut available = decode_inst.outputs();
+        available.extend(top_inputs.into_iter().filter(|(name, _)| name != "pc"));
+        assert_eq!(
+            execute_inst.connect_by_name(available),
+            vec!["trace_data".to_string()]
+        );
+
+        let decode_inputs = decode_inst.driven_inputs.borrow();
+        assert_eq!(decode_inputs.len(), 4);
+        assert_eq!(decode_inputs["enable"].bit_width(), 1);
+        let execute_inputs = execute_inst.driven_inputs.borrow();
+        assert_eq!(
+            execute_inputs.keys().cloned().collect::<Vec<_>>(),
+            vec!["op", "pc", "stall", "valid"]
+        );
+
+        // Resolving again only considers inputs that are still undriven
+        assert_eq!(
+            fetch_inst.connect_by_name(vec![("pc", m.input("other_pc", 32))]),
+            vec!["seed".to_string()]
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to connect the input called \"valid\" on instance \"sink_inst\" of \"Sink\" by name, but 2 signals with this name are available, so the connection is ambiguous."
+    )]
+    fn connect_by_name_ambiguous_error() {
+        let c = Context::new();
+
+        let source = c.module("Source");
+        source.output("valid", source.input("i", 1));
+
+        let sink = c.module("Sink");
+        sink.output("o", sink.input("valid", 1));
+
+        let m = c.module("Top");
+        let source0 = m.instance("source0", "Source");
+        let source1 = m.instance("source1", "Source");
+        let sink_inst = m.instance("sink_inst", "Sink");
+
+        let mut available = source0.outputs();
+        available.extend(source1.outputs());
+
+        // Panic
+        let _ = sink_inst.connect_by_name(available);
+    }
+
+    #[test]
+    fn connect_by_name_ambiguous_unused() {
+        let c = Context::new();
+
+        let source = c.module("Source");
+        source.output("valid", source.input("i", 1));
+
+        let sink = c.module("Sink");
+        sink.output("o", sink.input("ready", 1));
+
+        let m = c.module("Top");
+        let source0 = m.instance("source0", "Source");
+        let source1 = m.instance("source1", "Source");
+        let sink_inst = m.instance("sink_inst", "Sink");
+
+        let mut available = source0.outputs();
+        available.extend(source1.outputs());
+
+        assert_eq!(
+            sink_inst.connect_by_name(available),
+            vec!["ready".to_string()]
+        );
+    }
+
     #[test]
     #[should_panic(
         expected = "Attempted to create a signal for an output called \"nope\" on an instance of \"Inner\", but no such output with this name exists on this module."
@@ -234,4 +884,70 @@ mod tests {
         // Panic
         let _ = inner_inst.output("nope");
     }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a parameter called \"WIDTH\" on instance \"inner_inst\" of \"Inner\", but parameters can only be specified on instances of extern modules."
+    )]
+    fn parameter_non_extern_module_error() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("i", 1));
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+
+        // Panic
+        inner_inst.parameter("WIDTH", 8);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a parameter called \"not valid\" on instance \"inner_inst\" of \"Inner\", but parameter names must be valid Verilog identifiers."
+    )]
+    fn parameter_invalid_key_error() {
+        let c = Context::new();
+
+        let _ = c.extern_module("Inner");
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+
+        // Panic
+        inner_inst.parameter("not valid", 8);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a parameter called \"WIDTH\" on instance \"inner_inst\" of \"Inner\", but this instance already has a parameter with this name."
+    )]
+    fn parameter_duplicate_key_error() {
+        let c = Context::new();
+
+        let _ = c.extern_module("Inner");
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+        inner_inst.parameter("WIDTH", 8);
+
+        // Panic
+        inner_inst.parameter("WIDTH", 16);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a parameter called \"PERIOD\" on instance \"inner_inst\" of \"Inner\" with the value NaN, but real parameter values must be finite."
+    )]
+    fn parameter_non_finite_real_error() {
+        let c = Context::new();
+
+        let _ = c.extern_module("Inner");
+
+        let m = c.module("A");
+        let inner_inst = m.instance("inner_inst", "Inner");
+
+        // Panic
+        inner_inst.parameter("PERIOD", f64::NAN);
+    }
 }
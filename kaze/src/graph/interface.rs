@@ -0,0 +1,304 @@
+use super::module::*;
+use super::signal::*;
+
+use std::collections::BTreeMap;
+
+/// A description of a [`Module`]'s interface as plain data, created by the [`Module`]::[`interface`] method.
+///
+/// Unlike the [`Module`] itself, a `ModuleInterface` doesn't borrow from the [`Context`] it was created from, so it can be stored, compared, and (with the `serde` feature enabled) serialized by documentation and integration tooling.
+/// Ports are listed in name order and instances in creation order, so the same design always produces the same `ModuleInterface`.
+///
+/// [`Context`]: ./struct.Context.html
+/// [`interface`]: ./struct.Module.html#method.interface
+/// [`Module`]: ./struct.Module.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModuleInterface {
+    /// The name of the [`Module`].
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub name: String,
+    /// Whether the [`Module`] is an extern [`Module`], created by [`Context::extern_module`].
+    ///
+    /// [`Context::extern_module`]: ./struct.Context.html#method.extern_module
+    /// [`Module`]: ./struct.Module.html
+    pub is_extern: bool,
+    /// The name of the [`Module`]'s implicit clock port in generated code. All ports belong to this clock domain.
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub clock_port_name: String,
+    /// The name of the [`Module`]'s implicit active-low reset port in generated code.
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub reset_port_name: String,
+    /// The [`Module`]'s inputs, in name order.
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub inputs: Vec<PortInterface>,
+    /// The [`Module`]'s outputs, in name order.
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub outputs: Vec<PortInterface>,
+    /// The [`Module`]'s instances, in creation order, each with the interface of the [`Module`] it instantiates.
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub instances: Vec<InstanceInterface>,
+}
+
+/// A description of an input or output port in a [`ModuleInterface`].
+///
+/// [`ModuleInterface`]: ./struct.ModuleInterface.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortInterface {
+    /// The name of the port.
+    pub name: String,
+    /// The bit width of the port.
+    pub bit_width: u32,
+    /// The port's description, if one was specified with [`Module::port_description`].
+    ///
+    /// [`Module::port_description`]: ./struct.Module.html#method.port_description
+    pub description: Option<String>,
+}
+
+/// A description of an [`Instance`] in a [`ModuleInterface`].
+///
+/// [`Instance`]: ./struct.Instance.html
+/// [`ModuleInterface`]: ./struct.ModuleInterface.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstanceInterface {
+    /// The name of the [`Instance`].
+    ///
+    /// [`Instance`]: ./struct.Instance.html
+    pub name: String,
+    /// The interface of the [`Module`] that the [`Instance`] instantiates, including its own instances.
+    ///
+    /// [`Instance`]: ./struct.Instance.html
+    /// [`Module`]: ./struct.Module.html
+    pub module: ModuleInterface,
+}
+
+impl<'a> Module<'a> {
+    /// Returns a description of this `Module`'s interface and instance hierarchy as plain data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let inner = c.module("Inner");
+    /// inner.output("o", inner.input("i", 8));
+    ///
+    /// let outer = c.module("Outer");
+    /// let inner_inst = outer.instance("inner_inst", "Inner");
+    /// inner_inst.drive_input("i", outer.input("i", 8));
+    /// outer.output("o", inner_inst.output("o"));
+    ///
+    /// let interface = outer.interface();
+    /// assert_eq!(interface.name, "Outer");
+    /// assert_eq!(interface.inputs[0].name, "i");
+    /// assert_eq!(interface.inputs[0].bit_width, 8);
+    /// assert_eq!(interface.instances[0].name, "inner_inst");
+    /// assert_eq!(interface.instances[0].module.name, "Inner");
+    /// ```
+    pub fn interface(&'a self) -> ModuleInterface {
+        ModuleInterface {
+            name: self.name.clone(),
+            is_extern: self.is_extern,
+            clock_port_name: self
+                .clock_port_name
+                .borrow()
+                .clone()
+                .unwrap_or_else(|| "clk".into()),
+            reset_port_name: self
+                .reset_port_name
+                .borrow()
+                .clone()
+                .unwrap_or_else(|| "reset_n".into()),
+            inputs: port_interfaces(self, &self.inputs.borrow()),
+            outputs: port_interfaces(self, &self.outputs.borrow()),
+            instances: self
+                .instances
+                .borrow()
+                .iter()
+                .map(|instance| InstanceInterface {
+                    name: instance.name.clone(),
+                    module: instance.instantiated_module.interface(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn port_interfaces(m: &Module, ports: &BTreeMap<String, &Signal>) -> Vec<PortInterface> {
+    let port_descriptions = m.port_descriptions.borrow();
+    ports
+        .iter()
+        .map(|(name, port)| PortInterface {
+            name: name.clone(),
+            bit_width: port.bit_width(),
+            description: port_descriptions.get(name).cloned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn hierarchy<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+        let ram = c.extern_module("Ram");
+        ram.input("addr", 10);
+        ram.extern_output("data", 32);
+
+        let stage = c.module("Stage");
+        stage.clock_port_name("i_clk");
+        let ram_inst = stage.instance("ram", "Ram");
+        ram_inst.drive_input("addr", stage.input("addr", 10));
+        stage.output("valid", stage.input("enable", 1));
+        stage.port_description("valid", "High when data is valid");
+        stage.output("data", ram_inst.output("data"));
+
+        let top = c.module("Top");
+        let addr = top.input("addr", 10);
+        let enable = top.input("enable", 1);
+        let mut data = top.lit(0u32, 32);
+        for name in &["stage1", "stage0"] {
+            let stage_inst = top.instance(*name, "Stage");
+            stage_inst.drive_input("addr", addr);
+            stage_inst.drive_input("enable", enable);
+            data = data ^ stage_inst.output("data");
+        }
+        top.output("data", data);
+
+        top
+    }
+
+    #[test]
+    fn interface() {
+        let c = Context::new();
+
+        let ram_interface = ModuleInterface {
+            name: "Ram".into(),
+            is_extern: true,
+            clock_port_name: "clk".into(),
+            reset_port_name: "reset_n".into(),
+            inputs: vec![PortInterface {
+                name: "addr".into(),
+                bit_width: 10,
+                description: None,
+            }],
+            outputs: vec![PortInterface {
+                name: "data".into(),
+                bit_width: 32,
+                description: None,
+            }],
+            instances: Vec::new(),
+        };
+        let stage_interface = ModuleInterface {
+            name: "Stage".into(),
+            is_extern: false,
+            clock_port_name: "i_clk".into(),
+            reset_port_name: "reset_n".into(),
+            inputs: vec![
+                PortInterface {
+                    name: "addr".into(),
+                    bit_width: 10,
+                    description: None,
+                },
+                PortInterface {
+                    name: "enable".into(),
+                    bit_width: 1,
+                    description: None,
+                },
+            ],
+            outputs: vec![
+                PortInterface {
+                    name: "data".into(),
+                    bit_width: 32,
+                    description: None,
+                },
+                PortInterface {
+                    name: "valid".into(),
+                    bit_width: 1,
+                    description: Some("High when data is valid".into()),
+                },
+            ],
+            instances: vec![InstanceInterface {
+                name: "ram".into(),
+                module: ram_interface,
+            }],
+        };
+
+        let interface = hierarchy(&c).interface();
+
+        assert_eq!(interface.name, "Top");
+        assert_eq!(
+            interface
+                .inputs
+                .iter()
+                .map(|port| (&port.name[..], port.bit_width))
+                .collect::<Vec<_>>(),
+            vec![("addr", 10), ("enable", 1)]
+        );
+        assert_eq!(
+            interface.instances,
+            vec![
+                InstanceInterface {
+                    name: "stage1".into(),
+                    module: stage_interface.clone(),
+                },
+                InstanceInterface {
+                    name: "stage0".into(),
+                    module: stage_interface,
+                },
+            ]
+        );
+
+        // Interfaces are stable across contexts that describe the same design
+        let c2 = Context::new();
+        assert_eq!(hierarchy(&c2).interface(), interface);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn interface_json() {
+        let c = Context::new();
+
+        let interface = hierarchy(&c).interface();
+        let json = serde_json::to_value(&interface).unwrap();
+
+        assert_eq!(json["name"], "Top");
+        assert_eq!(json["is_extern"], false);
+        assert_eq!(json["clock_port_name"], "clk");
+        assert_eq!(json["inputs"][1]["name"], "enable");
+        assert_eq!(json["inputs"][1]["bit_width"], 1);
+        assert_eq!(json["outputs"].as_array().unwrap().len(), 1);
+        assert_eq!(json["instances"][0]["name"], "stage1");
+        let stage = &json["instances"][0]["module"];
+        assert_eq!(stage["name"], "Stage");
+        assert_eq!(stage["clock_port_name"], "i_clk");
+        assert_eq!(stage["outputs"][1]["name"], "valid");
+        assert_eq!(
+            stage["outputs"][1]["description"],
+            "High when data is valid"
+        );
+        assert!(stage["outputs"][0]["description"].is_null());
+        assert_eq!(stage["instances"][0]["name"], "ram");
+        assert_eq!(stage["instances"][0]["module"]["name"], "Ram");
+        assert_eq!(stage["instances"][0]["module"]["is_extern"], true);
+        assert_eq!(
+            stage["instances"][0]["module"]["instances"]
+                .as_array()
+                .unwrap()
+                .len(),
+            0
+        );
+
+        let deserialized: ModuleInterface = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, interface);
+    }
+}
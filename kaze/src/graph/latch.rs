@@ -0,0 +1,235 @@
+use super::module::*;
+use super::signal::*;
+
+use std::cell::RefCell;
+use std::ptr;
+
+/// A level-sensitive (transparent) latch, created by the [`Module`]::[`latch`] method.
+///
+/// A `Latch` is a stateful component that behaves like a [gated D latch](https://en.wikipedia.org/wiki/Flip-flop_(electronics)#Gated_D_latch).
+/// While its enable signal (specified by the [`drive_enable`] method) is high, the latch is transparent: its [`value`] combinationally follows its data signal (specified by the [`drive_data`] method).
+/// While its enable signal is low, it holds the last value that passed through it.
+///
+/// Unlike a [`Register`], a `Latch` isn't clocked and doesn't have a default value; it holds `0` until its enable signal is first asserted.
+/// Because its [`value`] depends combinationally on its data and enable signals, neither of them may depend on the latch's own [`value`]; this is reported as a combinational loop.
+///
+/// Latches are rarely needed outside of special cases (eg. interfacing with legacy designs), and are usually the result of a mistake, so every latch is reported as a warning by [`validation::lint`].
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+///
+/// let my_latch = m.latch("my_latch", 8);
+/// my_latch.drive_data(m.input("data", 8));
+/// my_latch.drive_enable(m.input("enable", 1));
+/// m.output("my_output", my_latch.value);
+/// ```
+///
+/// [`drive_data`]: #method.drive_data
+/// [`drive_enable`]: #method.drive_enable
+/// [`latch`]: ./struct.Module.html#method.latch
+/// [`Module`]: ./struct.Module.html
+/// [`Register`]: ./struct.Register.html
+/// [`validation::lint`]: ./validation/fn.lint.html
+/// [`value`]: #structfield.value
+#[must_use]
+pub struct Latch<'a> {
+    pub(crate) data: &'a LatchData<'a>,
+    /// This `Latch`'s current value.
+    pub value: &'a Signal<'a>,
+}
+
+impl<'a> Latch<'a> {
+    /// Specifies the data signal for this `Latch`.
+    ///
+    /// This `Latch`'s [`value`] will follow this signal while its enable signal is high.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` belongs to a different [`Module`] than this `Latch`, if `data`'s bit width doesn't match this `Latch`'s bit width, or if this `Latch`'s data signal is already driven.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let my_latch = m.latch("my_latch", 32);
+    /// my_latch.drive_data(m.input("data", 32));
+    /// my_latch.drive_enable(m.input("enable", 1));
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`value`]: #structfield.value
+    pub fn drive_data(&'a self, data: &'a Signal<'a>) {
+        if !ptr::eq(self.data.module, data.module) {
+            panic!(
+                "Attempted to drive latch \"{}\"'s data with a signal from another module.",
+                self.data.name
+            );
+        }
+        if data.bit_width() != self.data.bit_width {
+            panic!("Attempted to drive latch \"{}\"'s data with a signal that has a different bit width than the latch ({} and {}, respectively). The latch was created at {}, and the signal was created at {}.", self.data.name, data.bit_width(), self.data.bit_width, self.value.location, data.location);
+        }
+        if self.data.data.borrow().is_some() {
+            panic!("Attempted to drive latch \"{}\"'s data in module \"{}\", but this latch's data is already driven.", self.data.name, self.data.module.name);
+        }
+        *self.data.data.borrow_mut() = Some(data);
+    }
+
+    /// Specifies the enable signal for this `Latch`.
+    ///
+    /// This `Latch` is transparent while this signal is high, and holds its [`value`] while this signal is low.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `enable` belongs to a different [`Module`] than this `Latch`, if `enable`'s bit width is not 1, or if this `Latch`'s enable signal is already driven.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let my_latch = m.latch("my_latch", 1);
+    /// my_latch.drive_data(m.input("data", 1));
+    /// my_latch.drive_enable(m.input("enable", 1));
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`value`]: #structfield.value
+    pub fn drive_enable(&'a self, enable: &'a Signal<'a>) {
+        if !ptr::eq(self.data.module, enable.module) {
+            panic!(
+                "Attempted to drive latch \"{}\"'s enable with a signal from another module.",
+                self.data.name
+            );
+        }
+        if enable.bit_width() != 1 {
+            panic!("Attempted to drive latch \"{}\"'s enable with a signal with {} bit(s), but latch enables are required to be 1 bit wide. The latch was created at {}, and the signal was created at {}.", self.data.name, enable.bit_width(), self.value.location, enable.location);
+        }
+        if self.data.enable.borrow().is_some() {
+            panic!("Attempted to drive latch \"{}\"'s enable in module \"{}\", but this latch's enable is already driven.", self.data.name, self.data.module.name);
+        }
+        *self.data.enable.borrow_mut() = Some(enable);
+    }
+}
+
+pub(crate) struct LatchData<'a> {
+    pub module: &'a Module<'a>,
+
+    pub name: String,
+    pub bit_width: u32,
+    pub data: RefCell<Option<&'a Signal<'a>>>,
+    pub enable: RefCell<Option<&'a Signal<'a>>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive latch \"l\"'s data with a signal from another module."
+    )]
+    fn drive_data_separate_module_error() {
+        let c = Context::new();
+
+        let m1 = c.module("A");
+        let i = m1.input("i", 1);
+
+        let m2 = c.module("B");
+        let l = m2.latch("l", 1);
+
+        // Panic
+        l.drive_data(i);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive latch \"l\"'s data with a signal that has a different bit width than the latch (5 and 3, respectively)."
+    )]
+    fn drive_data_incompatible_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let l = m.latch("l", 3);
+
+        // Panic
+        l.drive_data(m.input("i", 5));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive latch \"l\"'s data in module \"A\", but this latch's data is already driven."
+    )]
+    fn drive_data_already_driven_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let l = m.latch("l", 3);
+
+        l.drive_data(m.input("i1", 3));
+
+        // Panic
+        l.drive_data(m.input("i2", 3));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive latch \"l\"'s enable with a signal from another module."
+    )]
+    fn drive_enable_separate_module_error() {
+        let c = Context::new();
+
+        let m1 = c.module("A");
+        let i = m1.input("i", 1);
+
+        let m2 = c.module("B");
+        let l = m2.latch("l", 1);
+
+        // Panic
+        l.drive_enable(i);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive latch \"l\"'s enable with a signal with 2 bit(s), but latch enables are required to be 1 bit wide."
+    )]
+    fn drive_enable_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let l = m.latch("l", 3);
+
+        // Panic
+        l.drive_enable(m.input("i", 2));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive latch \"l\"'s enable in module \"A\", but this latch's enable is already driven."
+    )]
+    fn drive_enable_already_driven_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let l = m.latch("l", 3);
+
+        l.drive_enable(m.input("i1", 1));
+
+        // Panic
+        l.drive_enable(m.input("i2", 1));
+    }
+}
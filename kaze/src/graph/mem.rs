@@ -1,23 +1,39 @@
 use super::constant::*;
 use super::context::*;
 use super::module::*;
+use super::register::*;
 use super::signal::*;
 
 use std::cell::RefCell;
+use std::fs;
 use std::hash::{Hash, Hasher};
+use std::io;
+use std::panic::Location;
+use std::path::{Path, PathBuf};
 use std::ptr;
 
+/// A memory write port's address, value, enable, and optional mask signals.
+pub(crate) type WritePort<'a> = (
+    &'a Signal<'a>,
+    &'a Signal<'a>,
+    &'a Signal<'a>,
+    Option<&'a Signal<'a>>,
+);
+
 /// A synchronous memory, created by the [`Module`]::[`mem`] method.
 ///
-/// Memories in kaze are always sequential/synchronous-read, sequential/synchronous-write memories.
+/// Memories in kaze are sequential/synchronous-write memories, and are sequential/synchronous-read by default.
 /// This means that when a read and/or write is asserted, the read/write will be visible on the cycle immediately following the cycle in which it's asserted.
-/// If both a write and a read to the same location occurs within the same cycle, the read will return the previous value at the memory location, **not** the newly-written value.
+/// If both a write and a read to the same location occurs within the same cycle, the read will return the previous value at the memory location, **not** the newly-written value, unless a different policy is selected with [`read_during_write`].
+///
+/// Memories may also have combinational/asynchronous read ports (see [`read_port_comb`]), which reflect the memory contents within the same cycle.
+/// These are typically only suitable for small, LUT-based memories.
 ///
 /// Memories must have at least one read port specified.
 /// Multiple reads to the same location within the same cycle will return the same value.
 ///
-/// Memories may optionally have initial contents and/or a write port specified.
-/// If either of these are missing, the contents of the memory can't be determined, so this is a logical error.
+/// Memories may optionally have initial contents and/or write ports specified.
+/// If both of these are missing, the contents of the memory can't be determined, so this is a logical error.
 ///
 /// # Examples
 ///
@@ -38,27 +54,77 @@ use std::ptr;
 ///
 /// [`Module`]: ./struct.Module.html
 /// [`mem`]: ./struct.Module.html#method.mem
+/// [`read_port_comb`]: #method.read_port_comb
+/// [`read_during_write`]: #method.read_during_write
 #[must_use]
 pub struct Mem<'a> {
     pub(super) context: &'a Context<'a>,
-    pub(super) module: &'a Module<'a>,
+    pub(crate) module: &'a Module<'a>,
+    pub(crate) location: &'static Location<'static>,
 
     pub(crate) name: String,
     pub(crate) address_bit_width: u32,
     pub(crate) element_bit_width: u32,
 
     pub(crate) initial_contents: RefCell<Option<Vec<Constant>>>,
+    pub(crate) initial_contents_path: RefCell<Option<PathBuf>>,
+    pub(crate) read_during_write: RefCell<ReadDuringWrite>,
 
     pub(crate) read_ports: RefCell<Vec<(&'a Signal<'a>, &'a Signal<'a>)>>,
-    pub(crate) write_port: RefCell<Option<(&'a Signal<'a>, &'a Signal<'a>, &'a Signal<'a>)>>,
+    pub(crate) comb_read_ports: RefCell<Vec<&'a Signal<'a>>>,
+    pub(crate) write_ports: RefCell<Vec<WritePort<'a>>>,
+
+    pub(crate) attributes: RefCell<Vec<(String, String)>>,
 }
 
 impl<'a> Mem<'a> {
+    /// Returns this `Mem`'s name, as specified when it was created by the [`Module`]::[`mem`] method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    /// let my_mem = m.mem("my_mem", 4, 8);
+    ///
+    /// assert_eq!(my_mem.name(), "my_mem");
+    /// assert_eq!(my_mem.address_bit_width(), 4);
+    /// assert_eq!(my_mem.element_bit_width(), 8);
+    /// ```
+    ///
+    /// [`mem`]: ./struct.Module.html#method.mem
+    /// [`Module`]: ./struct.Module.html
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of address bits of this `Mem`.
+    pub fn address_bit_width(&self) -> u32 {
+        self.address_bit_width
+    }
+
+    /// Returns the number of bits in each element of this `Mem`.
+    pub fn element_bit_width(&self) -> u32 {
+        self.element_bit_width
+    }
+
+    pub(super) fn write_port_dependencies(&self, dependencies: &mut Vec<&'a Signal<'a>>) {
+        for &(address, value, enable, mask) in self.write_ports.borrow().iter() {
+            dependencies.push(address);
+            dependencies.push(value);
+            dependencies.push(enable);
+            dependencies.extend(mask);
+        }
+    }
+
     /// Specifies the initial contents for this `Mem`.
     ///
-    /// Reads from this `Mem` will reflect the values specified unless writes have overwritten them (if the `Mem` has a write port).
+    /// Reads from this `Mem` will reflect the values specified unless writes have overwritten them (if the `Mem` has any write ports).
     ///
-    /// By default, a `Mem` does not have initial contents, and it is not required to specify them unless the `Mem` does not have a write port.
+    /// By default, a `Mem` does not have initial contents, and it is not required to specify them unless the `Mem` does not have any write ports.
     /// If initial contents are not specified, then this `Mem`'s contents will be undefined initially.
     ///
     /// Note that these contents are **not** restored when the containing [`Module`]'s implicit reset is asserted.
@@ -102,6 +168,117 @@ impl<'a> Mem<'a> {
         }).collect());
     }
 
+    /// Specifies the initial contents for this `Mem` by loading them from the hex data file at `path`.
+    ///
+    /// The file format is a subset of the format read by Verilog's `$readmemh`: element values are specified in hexadecimal (without a `0x` prefix) and separated by whitespace, `_` characters within values are ignored, and `//` starts a comment that runs to the end of the line.
+    /// Elements are read in order starting at address `0`; address directives (`@...`) are not supported.
+    ///
+    /// The file is read when this method is called, so the contents are stored in the graph and both the generated simulator and generated Verilog code initialize this `Mem` exactly as if [`initial_contents`] was called with the same values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read, or if it contains a value that isn't valid hexadecimal or doesn't fit in 128 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`initial_contents`], including if the number of elements in the file doesn't match the number of elements in this `Mem`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let boot_rom = m.mem("boot_rom", 10, 32);
+    /// boot_rom.initial_contents_from_file("boot_rom.hex").unwrap();
+    /// m.output("data", boot_rom.read_port(m.input("addr", 10), m.high()));
+    /// ```
+    ///
+    /// [`initial_contents`]: #method.initial_contents
+    pub fn initial_contents_from_file<P: AsRef<Path>>(&'a self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let contents = parse_hex_contents(&fs::read_to_string(path)?).map_err(|message| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} (in \"{}\")", message, path.display()),
+            )
+        })?;
+        self.initial_contents(&contents);
+        *self.initial_contents_path.borrow_mut() = Some(path.to_path_buf());
+
+        Ok(())
+    }
+
+    /// Specifies what this `Mem`'s (synchronous) read ports return when they read a location that's written in the same cycle.
+    ///
+    /// By default, a `Mem` uses [`ReadDuringWrite::Old`].
+    /// Both the generated simulator and generated Verilog code honor the selected policy; see [`ReadDuringWrite`] for details.
+    ///
+    /// Combinational read ports (see [`read_port_comb`]) are not affected by this policy, as they always reflect the current memory contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let my_mem = m.mem("my_mem", 1, 32);
+    /// my_mem.read_during_write(ReadDuringWrite::New);
+    /// my_mem.write_port(m.input("write_addr", 1), m.input("write_value", 32), m.high());
+    /// m.output("my_output", my_mem.read_port(m.input("read_addr", 1), m.high()));
+    /// ```
+    ///
+    /// [`ReadDuringWrite`]: ./enum.ReadDuringWrite.html
+    /// [`ReadDuringWrite::Old`]: ./enum.ReadDuringWrite.html#variant.Old
+    /// [`read_port_comb`]: #method.read_port_comb
+    pub fn read_during_write(&'a self, policy: ReadDuringWrite) {
+        *self.read_during_write.borrow_mut() = policy;
+    }
+
+    /// Attaches a synthesis attribute called `key` with the given `value` to this `Mem`.
+    ///
+    /// Attributes are passed through to synthesis tools to control how this `Mem` is implemented; they're emitted as `(* key = "value" *)` immediately before this `Mem`'s declaration in generated Verilog code, in the order they were specified.
+    /// They have no effect on the generated simulator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` isn't a valid Verilog identifier, or if this `Mem` already has an attribute called `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let my_mem = m.mem("my_mem", 1, 32);
+    /// my_mem.attribute("ram_style", "distributed");
+    /// my_mem.initial_contents(&[0xfadebabeu32, 0xdeadbeefu32]);
+    /// m.output("my_output", my_mem.read_port(m.high(), m.high()));
+    /// ```
+    pub fn attribute<K: Into<String>, V: Into<String>>(&'a self, key: K, value: V) {
+        let key = key.into();
+        if !is_valid_attribute_key(&key) {
+            panic!("Attempted to attach an attribute called \"{}\" to memory \"{}\" in module \"{}\", but attribute names must be valid Verilog identifiers.", key, self.name, self.module.name);
+        }
+        let mut attributes = self.attributes.borrow_mut();
+        if attributes
+            .iter()
+            .any(|(existing_key, _)| *existing_key == key)
+        {
+            panic!("Attempted to attach an attribute called \"{}\" to memory \"{}\" in module \"{}\", but this memory already has an attribute with this name.", key, self.name, self.module.name);
+        }
+        attributes.push((key, value.into()));
+    }
+
     /// Specifies a read port for this `Mem` and returns a [`Signal`] representing the data read from this port.
     ///
     /// `Mem`s are required to have at least one read port, otherwise the memory contents could never be read, which would be a logical error.
@@ -110,6 +287,7 @@ impl<'a> Mem<'a> {
     /// Read ports always have an `address` signal and an `enable` signal.
     /// When `enable` is asserted, the returned [`Signal`] will reflect the data read from the location specified by `address` on the following cycle.
     /// If `enable` is not asserted, then the value of the returned [`Signal`] is unchanged on the following cycle and reflects the value of the most recent read (note that this may be undefined before a valid read has occurred).
+    /// Both the generated simulator and generated Verilog model this with a read data register that's only updated when `enable` is asserted, so the memory contents are never re-read while `enable` is low (and synthesis tools can map `enable` to a block RAM's output enable).
     ///
     /// # Panics
     ///
@@ -134,39 +312,85 @@ impl<'a> Mem<'a> {
     ///
     /// [`Module`]: ./struct.Module.html
     /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
     pub fn read_port(&'a self, address: &'a Signal<'a>, enable: &'a Signal<'a>) -> &Signal<'a> {
         // TODO: Limit amount of read ports added?
         if address.bit_width() != self.address_bit_width {
-            panic!("Attempted to specify a read port for memory \"{}\" in module \"{}\" with an address signal with {} bit(s), but this memory has {} address bit(s).", self.name, self.module.name, address.bit_width(), self.address_bit_width);
+            panic!("Attempted to specify a read port for memory \"{}\" in module \"{}\" with an address signal with {} bit(s), but this memory has {} address bit(s). The memory was created at {}, and the address signal was created at {}.", self.name, self.module.name, address.bit_width(), self.address_bit_width, self.location, address.location);
         }
         if enable.bit_width() != 1 {
-            panic!("Attempted to specify a read port for memory \"{}\" in module \"{}\" with an enable signal with {} bit(s), but memory read/write ports are required to be 1 bit wide.", self.name, self.module.name, enable.bit_width());
+            panic!("Attempted to specify a read port for memory \"{}\" in module \"{}\" with an enable signal with {} bit(s), but memory read/write ports are required to be 1 bit wide. The memory was created at {}, and the enable signal was created at {}.", self.name, self.module.name, enable.bit_width(), self.location, enable.location);
         }
-        let ret = self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::MemReadPortOutput {
+        let ret = self.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::MemReadPortOutput {
                 mem: self,
                 address,
                 enable,
             },
-        });
+        );
         self.read_ports.borrow_mut().push((address, enable));
         ret
     }
 
+    /// Specifies a combinational (asynchronous) read port for this `Mem` and returns a [`Signal`] representing the data read from this port.
+    ///
+    /// Unlike [`read_port`], combinational read ports don't have an `enable` signal, and the returned [`Signal`] reflects the data at the location specified by `address` within the same cycle, without any clock edges.
+    /// Writes to this `Mem` are still synchronous, so a write will be visible through a combinational read port on the cycle immediately following the cycle in which it's asserted.
+    ///
+    /// Combinational read ports count towards the read ports a `Mem` is required to have.
+    /// Note that most devices can only implement combinational reads for small, LUT-based memories, not block RAMs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `address`'s bit width doesn't match this `Mem`'s address bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let my_rom = m.mem("my_rom", 2, 8);
+    /// my_rom.initial_contents(&[0x00u8, 0x01u8, 0x04u8, 0x09u8]);
+    /// m.output("square", my_rom.read_port_comb(m.input("i", 2)));
+    /// ```
+    ///
+    /// [`read_port`]: #method.read_port
+    /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
+    pub fn read_port_comb(&'a self, address: &'a Signal<'a>) -> &Signal<'a> {
+        if address.bit_width() != self.address_bit_width {
+            panic!("Attempted to specify a combinational read port for memory \"{}\" in module \"{}\" with an address signal with {} bit(s), but this memory has {} address bit(s). The memory was created at {}, and the address signal was created at {}.", self.name, self.module.name, address.bit_width(), self.address_bit_width, self.location, address.location);
+        }
+        let ret = self.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::MemCombReadPortOutput { mem: self, address },
+        );
+        self.comb_read_ports.borrow_mut().push(address);
+        ret
+    }
+
     /// Specifies a write port for this `Mem`.
     ///
     /// By default, a `Mem` does not have any write ports, and it is not required to specify one unless the `Mem` does not have initial contents.
+    /// There is no upper bound to the number of write ports specified in kaze, however a target device may not be able to synthesize the resulting Verilog code if too many are used.
     ///
     /// Write ports always have an `address` signal, a `value` signal, and an `enable` signal.
     /// When `enable` is asserted, the value at the location specified by `address` will reflect the value of the `value` signal on the following cycle.
     /// If `enable` is not asserted, then the memory contents will not change.
     ///
+    /// If multiple write ports write to the same location within the same cycle, the write port that was specified **last** takes priority, and the values written by the other ports are discarded.
+    /// This matches the behavior of the sequence of nonblocking assignments in the generated Verilog code.
+    ///
     /// # Panics
     ///
-    /// Panics if this `Mem` already has a write port specified, if `address`'s bit width doesn't match this `Mem`'s address bit width, if `value`'s bit width doesn't match this `Mem`'s element bit width, or if `enable`'s bit width is not `1`.
+    /// Panics if `address`'s bit width doesn't match this `Mem`'s address bit width, if `value`'s bit width doesn't match this `Mem`'s element bit width, or if `enable`'s bit width is not `1`.
     ///
     /// # Examples
     ///
@@ -187,27 +411,122 @@ impl<'a> Mem<'a> {
     ///
     /// [`Module`]: ./struct.Module.html
     /// [`Signal`]: ./struct.Signal.html
-    // TODO: byte/word enable? How might that interface look?
     pub fn write_port(
         &'a self,
         address: &'a Signal<'a>,
         value: &'a Signal<'a>,
         enable: &'a Signal<'a>,
     ) {
-        if self.write_port.borrow().is_some() {
-            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\", but this memory already has a write port.", self.name, self.module.name);
+        if address.bit_width() != self.address_bit_width {
+            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\" with an address signal with {} bit(s), but this memory has {} address bit(s). The memory was created at {}, and the address signal was created at {}.", self.name, self.module.name, address.bit_width(), self.address_bit_width, self.location, address.location);
         }
+        if value.bit_width() != self.element_bit_width {
+            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\" with a value signal with {} bit(s), but this memory has {} element bit(s). The memory was created at {}, and the value signal was created at {}.", self.name, self.module.name, value.bit_width(), self.element_bit_width, self.location, value.location);
+        }
+        if enable.bit_width() != 1 {
+            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\" with an enable signal with {} bit(s), but memory read/write ports are required to be 1 bit wide. The memory was created at {}, and the enable signal was created at {}.", self.name, self.module.name, enable.bit_width(), self.location, enable.location);
+        }
+        self.write_ports
+            .borrow_mut()
+            .push((address, value, enable, None));
+    }
+
+    /// Specifies a masked write port for this `Mem`, typically used for byte-enable writes.
+    ///
+    /// Masked write ports behave exactly like those specified with [`write_port`], except that they also have a `mask` signal which selects which parts of the addressed element are written.
+    /// The element is divided into `mask.bit_width()` equally-sized lanes, where bit `i` of `mask` corresponds to the lane containing element bits `i * lane_width` through `(i + 1) * lane_width - 1`.
+    /// When `enable` is asserted, only the lanes whose `mask` bits are also asserted are written; the remaining lanes keep their previous contents.
+    /// For example, a 32-bit memory with a 4-bit `mask` has byte-enable writes.
+    ///
+    /// The generated Verilog code writes each lane with a separate part-select assignment, which is the style synthesis tools expect for inferring block RAMs with byte enables.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`write_port`], or if this `Mem`'s element bit width is not evenly divisible by `mask`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let my_mem = m.mem("my_mem", 8, 32);
+    /// // Write the bytes selected by `byte_enable`
+    /// my_mem.write_port_masked(
+    ///     m.input("write_addr", 8),
+    ///     m.input("write_value", 32),
+    ///     m.input("write_enable", 1),
+    ///     m.input("byte_enable", 4),
+    /// );
+    /// m.output("read_data", my_mem.read_port(m.input("read_addr", 8), m.high()));
+    /// ```
+    ///
+    /// [`write_port`]: #method.write_port
+    pub fn write_port_masked(
+        &'a self,
+        address: &'a Signal<'a>,
+        value: &'a Signal<'a>,
+        enable: &'a Signal<'a>,
+        mask: &'a Signal<'a>,
+    ) {
         if address.bit_width() != self.address_bit_width {
-            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\" with an address signal with {} bit(s), but this memory has {} address bit(s).", self.name, self.module.name, address.bit_width(), self.address_bit_width);
+            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\" with an address signal with {} bit(s), but this memory has {} address bit(s). The memory was created at {}, and the address signal was created at {}.", self.name, self.module.name, address.bit_width(), self.address_bit_width, self.location, address.location);
         }
         if value.bit_width() != self.element_bit_width {
-            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\" with a value signal with {} bit(s), but this memory has {} element bit(s).", self.name, self.module.name, value.bit_width(), self.element_bit_width);
+            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\" with a value signal with {} bit(s), but this memory has {} element bit(s). The memory was created at {}, and the value signal was created at {}.", self.name, self.module.name, value.bit_width(), self.element_bit_width, self.location, value.location);
         }
         if enable.bit_width() != 1 {
-            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\" with an enable signal with {} bit(s), but memory read/write ports are required to be 1 bit wide.", self.name, self.module.name, enable.bit_width());
+            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\" with an enable signal with {} bit(s), but memory read/write ports are required to be 1 bit wide. The memory was created at {}, and the enable signal was created at {}.", self.name, self.module.name, enable.bit_width(), self.location, enable.location);
+        }
+        if !self.element_bit_width.is_multiple_of(mask.bit_width()) {
+            panic!("Attempted to specify a write port for memory \"{}\" in module \"{}\" with a mask signal with {} bit(s), but this memory's element bit width ({}) is not evenly divisible by the mask bit width. The memory was created at {}, and the mask signal was created at {}.", self.name, self.module.name, mask.bit_width(), self.element_bit_width, self.location, mask.location);
+        }
+        self.write_ports
+            .borrow_mut()
+            .push((address, value, enable, Some(mask)));
+    }
+}
+
+/// A [`Mem`]'s read-during-write policy, which determines what a synchronous read port returns when it reads a location that's written in the same cycle.
+///
+/// [`Mem`]: ./struct.Mem.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReadDuringWrite {
+    /// The read returns the value at the location **before** the write (read-first). This is the default.
+    Old,
+    /// The read returns the newly-written value (write-first). For masked write ports, only the lanes that are written reflect the new value.
+    New,
+    /// The read returns an unspecified value.
+    ///
+    /// This policy should only be used if the design guarantees that reads and writes never collide, as it allows synthesis tools to pick whichever behavior is cheapest for the target device.
+    /// The generated simulator and generated Verilog code may return different values if they do collide.
+    Undefined,
+}
+
+fn parse_hex_contents(source: &str) -> Result<Vec<u128>, String> {
+    let mut contents = Vec::new();
+    for (line_index, line) in source.lines().enumerate() {
+        let line = match line.find("//") {
+            Some(comment_start) => &line[..comment_start],
+            _ => line,
+        };
+        for word in line.split_whitespace() {
+            let digits = word.replace('_', "");
+            let value = u128::from_str_radix(&digits, 16).map_err(|_| {
+                format!(
+                    "Invalid memory contents value \"{}\" on line {}; values must be hexadecimal and fit in 128 bits.",
+                    word,
+                    line_index + 1
+                )
+            })?;
+            contents.push(value);
         }
-        *self.write_port.borrow_mut() = Some((address, value, enable));
     }
+
+    Ok(contents)
 }
 
 impl<'a> Eq for &'a Mem<'a> {}
@@ -228,6 +547,22 @@ impl<'a> PartialEq for &'a Mem<'a> {
 mod tests {
     use crate::*;
 
+    #[test]
+    #[should_panic(
+        expected = "Attempted to attach an attribute called \"ram_style\" to memory \"mem\" in module \"A\", but this memory already has an attribute with this name."
+    )]
+    fn attribute_already_specified_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let mem = m.mem("mem", 1, 1);
+
+        mem.attribute("ram_style", "block");
+
+        // Panic
+        mem.attribute("ram_style", "distributed");
+    }
+
     #[test]
     #[should_panic(
         expected = "Attempted to specify initial contents for memory \"mem\" in module \"A\", but this memory already has initial contents."
@@ -258,6 +593,47 @@ mod tests {
         mem.initial_contents(&[true, false, true]);
     }
 
+    #[test]
+    fn parse_hex_contents() {
+        assert_eq!(
+            super::parse_hex_contents("// Boot ROM\n0 1\tff\n\nDEAD_beef // trailing comment\n"),
+            Ok(vec![0x0, 0x1, 0xff, 0xdeadbeef])
+        );
+        assert_eq!(
+            super::parse_hex_contents("0\n@10 1\n"),
+            Err("Invalid memory contents value \"@10\" on line 2; values must be hexadecimal and fit in 128 bits.".into())
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify initial contents for memory \"mem\" in module \"A\" that contains 3 element(s), but this memory has 2 address bit(s), and requires 4 element(s)."
+    )]
+    fn initial_contents_from_file_length_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let mem = m.mem("mem", 2, 8);
+
+        // Removes the file even though the test unwinds
+        struct RemoveOnDrop(std::path::PathBuf);
+
+        impl Drop for RemoveOnDrop {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_file(&self.0);
+            }
+        }
+
+        let path = RemoveOnDrop(std::env::temp_dir().join(format!(
+            "kaze_initial_contents_from_file_length_error_{}.hex",
+            std::process::id()
+        )));
+        std::fs::write(&path.0, "12 34 56\n").unwrap();
+
+        // Panic
+        let _ = mem.initial_contents_from_file(&path.0);
+    }
+
     #[test]
     #[should_panic(
         expected = "Attempted to specify initial contents for memory \"mem\" in module \"A\", but this memory has an element width of 1 bit(s), and these initial contents specify element 0 with value 2 which requires 2 bit(s)."
@@ -302,18 +678,16 @@ mod tests {
 
     #[test]
     #[should_panic(
-        expected = "Attempted to specify a write port for memory \"mem\" in module \"A\", but this memory already has a write port."
+        expected = "Attempted to specify a combinational read port for memory \"mem\" in module \"A\" with an address signal with 2 bit(s), but this memory has 1 address bit(s)."
     )]
-    fn write_port_already_specified_error() {
+    fn read_port_comb_address_bit_width_error() {
         let c = Context::new();
 
         let m = c.module("A");
         let mem = m.mem("mem", 1, 1);
 
-        mem.write_port(m.low(), m.low(), m.low());
-
         // Panic
-        mem.write_port(m.low(), m.low(), m.low());
+        let _ = mem.read_port_comb(m.lit(0u32, 2));
     }
 
     #[test]
@@ -357,4 +731,32 @@ mod tests {
         // Panic
         mem.write_port(m.low(), m.low(), m.lit(0u32, 2));
     }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a write port for memory \"mem\" in module \"A\" with a value signal with 2 bit(s), but this memory has 32 element bit(s)."
+    )]
+    fn write_port_masked_value_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let mem = m.mem("mem", 1, 32);
+
+        // Panic
+        mem.write_port_masked(m.low(), m.lit(0u32, 2), m.low(), m.lit(0u32, 4));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a write port for memory \"mem\" in module \"A\" with a mask signal with 3 bit(s), but this memory's element bit width (32) is not evenly divisible by the mask bit width."
+    )]
+    fn write_port_masked_mask_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let mem = m.mem("mem", 1, 32);
+
+        // Panic
+        mem.write_port_masked(m.low(), m.lit(0u32, 32), m.low(), m.lit(0u32, 3));
+    }
 }
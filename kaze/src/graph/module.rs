@@ -1,12 +1,17 @@
 use super::constant::*;
 use super::context::*;
+use super::error::*;
+use super::fsm::*;
 use super::instance::*;
+use super::latch::*;
 use super::mem::*;
 use super::register::*;
+use super::register_file::*;
 use super::signal::*;
 
-use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::cell::{Ref, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::panic::Location;
 use std::ptr;
 
 /// A self-contained and potentially-reusable hardware design unit, created by the [`Context`]::[`module`] method.
@@ -31,35 +36,217 @@ use std::ptr;
 // TODO: Validation error if a module has no inputs/outputs
 #[must_use]
 pub struct Module<'a> {
-    context: &'a Context<'a>,
+    pub(super) context: &'a Context<'a>,
 
     pub(crate) name: String,
+    pub(crate) is_extern: bool,
+    pub(crate) clock_port_name: RefCell<Option<String>>,
+    pub(crate) reset_port_name: RefCell<Option<String>>,
+    pub(crate) port_descriptions: RefCell<BTreeMap<String, String>>,
 
     pub(crate) inputs: RefCell<BTreeMap<String, &'a Signal<'a>>>,
     pub(crate) outputs: RefCell<BTreeMap<String, &'a Signal<'a>>>,
     pub(crate) registers: RefCell<Vec<&'a Signal<'a>>>,
+    pub(crate) latches: RefCell<Vec<&'a Signal<'a>>>,
+    pub(crate) fsms: RefCell<Vec<FsmData<'a>>>,
     pub(crate) instances: RefCell<Vec<&'a Instance<'a>>>,
+    pub(crate) instance_arrays: RefCell<Vec<(String, Vec<&'a Instance<'a>>)>>,
     pub(crate) mems: RefCell<Vec<&'a Mem<'a>>>,
+    pub(crate) assertions: RefCell<Vec<(&'a Signal<'a>, String)>>,
+    pub(crate) assumptions: RefCell<Vec<&'a Signal<'a>>>,
+    pub(crate) covers: RefCell<Vec<&'a Signal<'a>>>,
+    pub(crate) traces: RefCell<Vec<(String, &'a Signal<'a>)>>,
+    pub(crate) named_signals: RefCell<BTreeMap<String, &'a Signal<'a>>>,
+    pub(crate) constants: RefCell<BTreeMap<String, &'a Signal<'a>>>,
+    pub(crate) signals: RefCell<Vec<&'a Signal<'a>>>,
+    pub(super) structural_signals: RefCell<HashMap<StructuralKey<'a>, &'a Signal<'a>>>,
 }
 
 impl<'a> Module<'a> {
-    pub(super) fn new(context: &'a Context<'a>, name: String) -> Module<'a> {
+    pub(super) fn new(context: &'a Context<'a>, name: String, is_extern: bool) -> Module<'a> {
         Module {
             context,
 
             name,
+            is_extern,
+            clock_port_name: RefCell::new(None),
+            reset_port_name: RefCell::new(None),
+            port_descriptions: RefCell::new(BTreeMap::new()),
 
             inputs: RefCell::new(BTreeMap::new()),
             outputs: RefCell::new(BTreeMap::new()),
             registers: RefCell::new(Vec::new()),
+            latches: RefCell::new(Vec::new()),
+            fsms: RefCell::new(Vec::new()),
             instances: RefCell::new(Vec::new()),
+            instance_arrays: RefCell::new(Vec::new()),
             mems: RefCell::new(Vec::new()),
+            assertions: RefCell::new(Vec::new()),
+            assumptions: RefCell::new(Vec::new()),
+            covers: RefCell::new(Vec::new()),
+            traces: RefCell::new(Vec::new()),
+            named_signals: RefCell::new(BTreeMap::new()),
+            constants: RefCell::new(BTreeMap::new()),
+            signals: RefCell::new(Vec::new()),
+            structural_signals: RefCell::new(HashMap::new()),
         }
     }
 
+    /// Returns this `Module`'s name, as specified when it was created by the [`Context`]::[`module`] method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// assert_eq!(m.name(), "MyModule");
+    /// ```
+    ///
+    /// [`Context`]: ./struct.Context.html
+    /// [`module`]: ./struct.Context.html#method.module
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Immutably borrows this `Module`'s inputs, keyed by name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    /// let _ = m.input("b", 8);
+    /// let _ = m.input("a", 1);
+    ///
+    /// let inputs = m.inputs();
+    /// assert_eq!(inputs.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+    /// assert_eq!(inputs["b"].bit_width(), 8);
+    /// ```
+    pub fn inputs(&self) -> Ref<'_, BTreeMap<String, &'a Signal<'a>>> {
+        self.inputs.borrow()
+    }
+
+    /// Immutably borrows this `Module`'s outputs, keyed by name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    /// m.output("o", m.input("i", 4));
+    ///
+    /// assert_eq!(m.outputs()["o"].bit_width(), 4);
+    /// ```
+    pub fn outputs(&self) -> Ref<'_, BTreeMap<String, &'a Signal<'a>>> {
+        self.outputs.borrow()
+    }
+
+    /// Immutably borrows the value [`Signal`]s of this `Module`'s [`Register`]s, in creation order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    /// let r = m.reg("r", 3);
+    /// r.drive_next(r.value);
+    ///
+    /// assert_eq!(m.registers().len(), 1);
+    /// assert_eq!(m.registers()[0].bit_width(), 3);
+    /// ```
+    ///
+    /// [`Register`]: ./struct.Register.html
+    /// [`Signal`]: ./struct.Signal.html
+    pub fn registers(&self) -> Ref<'_, Vec<&'a Signal<'a>>> {
+        self.registers.borrow()
+    }
+
+    /// Immutably borrows the value [`Signal`]s of this `Module`'s [`Latch`]es, in creation order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    /// let l = m.latch("l", 3);
+    /// l.drive_data(m.input("data", 3));
+    /// l.drive_enable(m.input("enable", 1));
+    ///
+    /// assert_eq!(m.latches().len(), 1);
+    /// assert_eq!(m.latches()[0].bit_width(), 3);
+    /// ```
+    ///
+    /// [`Latch`]: ./struct.Latch.html
+    /// [`Signal`]: ./struct.Signal.html
+    pub fn latches(&self) -> Ref<'_, Vec<&'a Signal<'a>>> {
+        self.latches.borrow()
+    }
+
+    /// Immutably borrows this `Module`'s [`Instance`]s, in creation order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let inner = c.module("Inner");
+    /// inner.output("o", inner.input("i", 1));
+    ///
+    /// let outer = c.module("Outer");
+    /// let _ = outer.instance("inner", "Inner");
+    ///
+    /// let instances = outer.instances();
+    /// assert_eq!(instances[0].name(), "inner");
+    /// assert_eq!(instances[0].instantiated_module().name(), "Inner");
+    /// ```
+    ///
+    /// [`Instance`]: ./struct.Instance.html
+    pub fn instances(&self) -> Ref<'_, Vec<&'a Instance<'a>>> {
+        self.instances.borrow()
+    }
+
+    /// Immutably borrows this `Module`'s [`Mem`]s, in creation order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    /// let _ = m.mem("my_mem", 4, 8);
+    ///
+    /// assert_eq!(m.mems()[0].name(), "my_mem");
+    /// ```
+    ///
+    /// [`Mem`]: ./struct.Mem.html
+    pub fn mems(&self) -> Ref<'_, Vec<&'a Mem<'a>>> {
+        self.mems.borrow()
+    }
+
     /// Creates a [`Signal`] that represents the constant literal specified by `value` with `bit_width` bits.
     ///
     /// The bit width of the type provided by `value` doesn't need to match `bit_width`, but the value represented by `value` must fit into `bit_width` bits.
+    /// `value` can be a `bool` or any of Rust's unsigned integer types (see [`Constant`]); unsuffixed integer literals default to `i32`, which isn't supported, so they need a suffix (eg. `0xffu32` rather than `0xff`).
     ///
     /// # Panics
     ///
@@ -79,34 +266,50 @@ impl<'a> Module<'a> {
     /// let twenty_seven_bit_const = m.lit(true, 27);
     /// ```
     ///
+    /// [`Constant`]: ./enum.Constant.html
     /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
     /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
     /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
     pub fn lit<C: Into<Constant>>(&'a self, value: C, bit_width: u32) -> &Signal<'a> {
-        if bit_width < MIN_SIGNAL_BIT_WIDTH {
-            panic!(
-                "Cannot create a literal with {} bit(s). Signals must not be narrower than {} bit(s).",
-                bit_width, MIN_SIGNAL_BIT_WIDTH
-            );
-        }
-        if bit_width > MAX_SIGNAL_BIT_WIDTH {
-            panic!(
-                "Cannot create a literal with {} bit(s). Signals must not be wider than {} bit(s).",
-                bit_width, MAX_SIGNAL_BIT_WIDTH
-            );
-        }
-        let value = value.into();
-        let required_bits = value.required_bits();
-        if required_bits > bit_width {
-            let numeric_value = value.numeric_value();
-            panic!("Cannot fit the specified value '{}' into the specified bit width '{}'. The value '{}' requires a bit width of at least {} bit(s).", numeric_value, bit_width, numeric_value, required_bits);
+        match self.try_lit(value, bit_width) {
+            Ok(lit) => lit,
+            Err(error) => panic!("{}", error),
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self,
+    }
 
-            data: SignalData::Lit { value, bit_width },
-        })
+    /// Non-panicking variant of [`lit`], which returns a [`GraphError`] instead of panicking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// assert!(m.try_lit(0xffu32, 8).is_ok());
+    /// assert_eq!(
+    ///     m.try_lit(0x1ffu32, 8).err(),
+    ///     Some(GraphError::LitValueTooWide {
+    ///         value: 0x1ff,
+    ///         bit_width: 8,
+    ///         required_bits: 9,
+    ///     })
+    /// );
+    /// ```
+    ///
+    /// [`GraphError`]: ./enum.GraphError.html
+    /// [`lit`]: #method.lit
+    #[track_caller]
+    pub fn try_lit<C: Into<Constant>>(
+        &'a self,
+        value: C,
+        bit_width: u32,
+    ) -> Result<&'a Signal<'a>, GraphError> {
+        let data = lit_data(value, bit_width)?;
+        Ok(self.context.alloc_signal(self, Location::caller(), data))
     }
 
     /// Convenience method to create a [`Signal`] that represents a single `0` bit.
@@ -126,6 +329,7 @@ impl<'a> Module<'a> {
     /// ```
     ///
     /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
     pub fn low(&'a self) -> &Signal<'a> {
         self.lit(false, 1)
     }
@@ -147,10 +351,60 @@ impl<'a> Module<'a> {
     /// ```
     ///
     /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
     pub fn high(&'a self) -> &Signal<'a> {
         self.lit(true, 1)
     }
 
+    /// Creates a named constant called `name` in this `Module`, and returns a [`Signal`] that represents its value, specified by `value` with `bit_width` bits.
+    ///
+    /// The returned [`Signal`] behaves exactly like one created by [`lit`], but generated code refers to it by name: generated Verilog declares it as a `localparam`, and generated simulators declare it as an associated `const` on the simulator struct.
+    /// Unlike literals, each named constant is a distinct [`Signal`], so only expressions built from the returned [`Signal`] refer to `name`; literals (and other constants) with the same value and bit width are emitted as they were created.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`lit`], or if this `Module` already contains a constant called `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let magic = m.constant("MAGIC", 0xdeadbeefu32, 32);
+    /// m.output("is_magic", m.input("i", 32).eq(magic));
+    /// ```
+    ///
+    /// [`lit`]: #method.lit
+    /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
+    pub fn constant<S: Into<String>, C: Into<Constant>>(
+        &'a self,
+        name: S,
+        value: C,
+        bit_width: u32,
+    ) -> &'a Signal<'a> {
+        let name = name.into();
+        if self.constants.borrow().contains_key(&name) {
+            panic!(
+                "Attempted to create a constant called \"{}\" in module \"{}\", but this module already contains a constant with this name.",
+                name, self.name
+            );
+        }
+        let data = match lit_data(value, bit_width) {
+            Ok(data) => data,
+            Err(error) => panic!("{}", error),
+        };
+        let ret = self
+            .context
+            .alloc_distinct_signal(self, Location::caller(), data);
+        self.constants.borrow_mut().insert(name, ret);
+        ret
+    }
+
     /// Creates an input for this `Module` called `name` with `bit_width` bits, and returns a [`Signal`] that represents the value of this input.
     ///
     /// # Panics
@@ -172,39 +426,63 @@ impl<'a> Module<'a> {
     /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
     /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
     /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
     pub fn input<S: Into<String>>(&'a self, name: S, bit_width: u32) -> &Signal<'a> {
-        let name = name.into();
-        // TODO: Error if name already exists in this context
-        if bit_width < MIN_SIGNAL_BIT_WIDTH {
-            panic!(
-                "Cannot create an input with {} bit(s). Signals must not be narrower than {} bit(s).",
-                bit_width, MIN_SIGNAL_BIT_WIDTH
-            );
-        }
-        if bit_width > MAX_SIGNAL_BIT_WIDTH {
-            panic!(
-                "Cannot create an input with {} bit(s). Signals must not be wider than {} bit(s).",
-                bit_width, MAX_SIGNAL_BIT_WIDTH
-            );
+        match self.try_input(name, bit_width) {
+            Ok(input) => input,
+            Err(error) => panic!("{}", error),
         }
-        let input = self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self,
+    }
 
-            data: SignalData::Input {
-                name: name.clone(),
+    /// Non-panicking variant of [`input`], which returns a [`GraphError`] instead of panicking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// assert!(m.try_input("a", 80).is_ok());
+    /// assert_eq!(
+    ///     m.try_input("b", 0).err(),
+    ///     Some(GraphError::BitWidthTooSmall {
+    ///         what: "an input",
+    ///         bit_width: 0,
+    ///     })
+    /// );
+    /// ```
+    ///
+    /// [`GraphError`]: ./enum.GraphError.html
+    /// [`input`]: #method.input
+    #[track_caller]
+    pub fn try_input<S: Into<String>>(
+        &'a self,
+        name: S,
+        bit_width: u32,
+    ) -> Result<&'a Signal<'a>, GraphError> {
+        let name = name.into();
+        // TODO: Error if name already exists in this context
+        check_bit_width("an input", bit_width)?;
+        let input = self.context.alloc_signal(
+            self,
+            Location::caller(),
+            SignalData::Input {
+                name: self.context.intern(&name),
                 bit_width,
             },
-        });
+        );
         self.inputs.borrow_mut().insert(name, input);
-        input
+        Ok(input)
     }
 
     /// Creates an output for this `Module` called `name` with the same number of bits as `source`, and drives this output with `source`.
     ///
     /// # Panics
     ///
-    /// Panics of `source` doesn't belong to this `Module`.
+    /// Panics if `source` doesn't belong to this `Module`, or if this `Module` is an extern module.
     ///
     /// # Examples
     ///
@@ -218,19 +496,26 @@ impl<'a> Module<'a> {
     /// let some_signal = m.high();
     /// m.output("my_output", some_signal);
     /// ```
+    #[track_caller]
     pub fn output<S: Into<String>>(&'a self, name: S, source: &'a Signal<'a>) {
+        let name = name.into();
+        if self.is_extern {
+            panic!("Attempted to drive an output called \"{}\" in extern module \"{}\", but extern modules don't have a body. Use `Module::extern_output` to declare its outputs instead.", name, self.name);
+        }
         if !ptr::eq(self, source.module) {
             panic!("Cannot output a signal from another module.");
         }
         // TODO: Error if name already exists in this context
-        self.outputs.borrow_mut().insert(name.into(), source);
+        self.outputs.borrow_mut().insert(name, source);
     }
 
-    /// Creates a [`Register`] in this `Module` called `name` with `bit_width` bits.
+    /// Creates an output for this `Module` for each of `instance`'s outputs, with the same names, and drives each one with the corresponding [`Instance::output`].
+    ///
+    /// This is a shorthand for forwarding every output of a child [`Instance`] through this `Module`. Outputs that directly forward an instance output don't cost any extra logic: the generated Verilog connects the instance's port straight to the output port, and the generated simulator reads the instance's value without an intermediate copy.
     ///
     /// # Panics
     ///
-    /// Panics if `bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`], respectively.
+    /// Panics if `instance` doesn't belong to this `Module`, or if this `Module` is an extern module.
     ///
     /// # Examples
     ///
@@ -239,54 +524,99 @@ impl<'a> Module<'a> {
     ///
     /// let c = Context::new();
     ///
-    /// let m = c.module("MyModule");
+    /// let inner = c.module("Inner");
+    /// inner.output("a", inner.input("i", 1));
+    /// inner.output("b", inner.high());
     ///
-    /// let my_reg = m.reg("my_reg", 32);
-    /// my_reg.default_value(0xfadebabeu32); // Optional
-    /// my_reg.drive_next(!my_reg.value);
-    /// m.output("my_output", my_reg.value);
+    /// let outer = c.module("Outer");
+    /// let inner_inst = outer.instance("inner_inst", "Inner");
+    /// inner_inst.drive_input("i", outer.input("i", 1));
+    /// outer.export_outputs(inner_inst);
+    /// ```
+    ///
+    /// [`Instance`]: ./struct.Instance.html
+    /// [`Instance::output`]: ./struct.Instance.html#method.output
+    #[track_caller]
+    pub fn export_outputs(&'a self, instance: &'a Instance<'a>) {
+        if !ptr::eq(self, instance.module) {
+            panic!("Attempted to export the outputs of instance \"{}\" from module \"{}\", but this instance belongs to module \"{}\". The instance was created at {}.", instance.name, self.name, instance.module.name, instance.location);
+        }
+        let output_names: Vec<String> = instance
+            .instantiated_module
+            .outputs
+            .borrow()
+            .keys()
+            .cloned()
+            .collect();
+        for name in output_names {
+            let source = instance.output(name.clone());
+            self.output(name, source);
+        }
+    }
+
+    /// Declares an output for this extern `Module` called `name` with `bit_width` bits.
+    ///
+    /// Since extern `Module`s don't have a body, their outputs aren't driven by any [`Signal`]; this only declares the output so that it can be used via [`Instance::output`] on instances of this `Module`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Module` wasn't created by [`Context::extern_module`], or if `bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`], respectively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let bram = c.extern_module("VendorBram");
+    /// bram.input("addr", 10);
+    /// bram.extern_output("data", 32);
     /// ```
     ///
+    /// [`Context::extern_module`]: ./struct.Context.html#method.extern_module
+    /// [`Instance::output`]: ./struct.Instance.html#method.output
     /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
     /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
-    /// [`Register`]: ./struct.Register.html
-    pub fn reg<S: Into<String>>(&'a self, name: S, bit_width: u32) -> &Register<'a> {
-        // TODO: Error if name already exists in this context
+    /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
+    pub fn extern_output<S: Into<String>>(&'a self, name: S, bit_width: u32) {
+        let name = name.into();
+        if !self.is_extern {
+            panic!("Attempted to declare an extern output called \"{}\" in module \"{}\", but this module isn't an extern module. Use `Module::output` instead.", name, self.name);
+        }
         if bit_width < MIN_SIGNAL_BIT_WIDTH {
             panic!(
-                "Cannot create a register with {} bit(s). Signals must not be narrower than {} bit(s).",
+                "Cannot create an output with {} bit(s). Signals must not be narrower than {} bit(s).",
                 bit_width, MIN_SIGNAL_BIT_WIDTH
             );
         }
         if bit_width > MAX_SIGNAL_BIT_WIDTH {
             panic!(
-                "Cannot create a register with {} bit(s). Signals must not be wider than {} bit(s).",
+                "Cannot create an output with {} bit(s). Signals must not be wider than {} bit(s).",
                 bit_width, MAX_SIGNAL_BIT_WIDTH
             );
         }
-        let data = self.context.register_data_arena.alloc(RegisterData {
-            module: self,
-
-            name: name.into(),
-            initial_value: RefCell::new(None),
-            bit_width,
-            next: RefCell::new(None),
-        });
-        let value = self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self,
+        // Extern outputs have no driver; a placeholder of the right width records the output's bit width
+        let placeholder = self.lit(0u32, bit_width);
+        self.outputs.borrow_mut().insert(name, placeholder);
+    }
 
-            data: SignalData::Reg { data },
-        });
-        self.registers.borrow_mut().push(value);
-        self.context.register_arena.alloc(Register { data, value })
+    /// Returns `true` if this `Module` was created by [`Context::extern_module`], and `false` otherwise.
+    ///
+    /// [`Context::extern_module`]: ./struct.Context.html#method.extern_module
+    pub fn is_extern(&self) -> bool {
+        self.is_extern
     }
 
-    /// Creates a 2:1 [multiplexer](https://en.wikipedia.org/wiki/Multiplexer) that represents `when_true`'s value when `cond` is high, and `when_false`'s value when `cond` is low.
+    /// Specifies the name of this `Module`'s clock port in generated code, which is `clk` by default.
+    ///
+    /// Instances of this `Module` are connected to the clock port by this name in the generated code for the `Module`s that instantiate it.
+    /// This has no effect on `Module`s that don't need a clock port (see [`verilog::generate_with_options`]).
     ///
     /// # Panics
     ///
-    /// Panics if `cond`, `when_true`, or `when_false` belong to a different `Module` than `self`, if `cond`'s bit width is not 1, or if the bit widths of `when_true` and `when_false` aren't equal.
+    /// Panics if this `Module` already has a clock port name specified, or if `name` isn't a valid Verilog identifier.
     ///
     /// # Examples
     ///
@@ -296,59 +626,105 @@ impl<'a> Module<'a> {
     /// let c = Context::new();
     ///
     /// let m = c.module("MyModule");
-    ///
-    /// let cond = m.input("cond", 1);
-    /// let a = m.input("a", 8);
-    /// let b = m.input("b", 8);
-    /// m.output("my_output", m.mux(cond, a, b)); // Outputs a when cond is high, b otherwise
+    /// m.clock_port_name("i_clk");
+    /// m.reset_port_name("i_rst_n");
+    ///
+    /// let r = m.reg("r", 1);
+    /// r.default_value(false);
+    /// r.drive_next(m.input("i", 1));
+    /// m.output("o", r.value);
+    ///
+    /// let mut w = Vec::new();
+    /// verilog::generate(m, &mut w).unwrap();
+    /// let verilog = String::from_utf8(w).unwrap();
+    /// assert!(verilog.contains("input wire i_clk"));
+    /// assert!(verilog.contains("input wire i_rst_n"));
     /// ```
-    pub fn mux(
-        &'a self,
-        cond: &'a Signal<'a>,
-        when_true: &'a Signal<'a>,
-        when_false: &'a Signal<'a>,
-    ) -> &Signal<'a> {
-        // TODO: This is an optimization to support sugar; if that doesn't go well, remove this
-        if when_true == when_false {
-            return when_true;
+    ///
+    /// [`verilog::generate_with_options`]: ./verilog/fn.generate_with_options.html
+    pub fn clock_port_name<S: Into<String>>(&'a self, name: S) {
+        let name = name.into();
+        if self.clock_port_name.borrow().is_some() {
+            panic!("Attempted to specify a clock port name for module \"{}\", but this module already has a clock port name specified.", self.name);
         }
-
-        if !ptr::eq(self, cond.module) {
-            panic!("Attempted to combine signals from different modules.");
+        if !is_valid_attribute_key(&name) {
+            panic!("Attempted to specify \"{}\" as the clock port name for module \"{}\", but port names must be valid Verilog identifiers.", name, self.name);
         }
-        if !ptr::eq(self, when_true.module) {
-            panic!("Attempted to combine signals from different modules.");
+        *self.clock_port_name.borrow_mut() = Some(name);
+    }
+
+    /// Specifies the name of this `Module`'s active-low reset port in generated code, which is `reset_n` by default.
+    ///
+    /// Instances of this `Module` are connected to the reset port by this name in the generated code for the `Module`s that instantiate it.
+    /// This has no effect on `Module`s that don't need a reset port (see [`verilog::generate_with_options`]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Module` already has a reset port name specified, or if `name` isn't a valid Verilog identifier.
+    ///
+    /// # Examples
+    ///
+    /// See [`clock_port_name`].
+    ///
+    /// [`clock_port_name`]: #method.clock_port_name
+    /// [`verilog::generate_with_options`]: ./verilog/fn.generate_with_options.html
+    pub fn reset_port_name<S: Into<String>>(&'a self, name: S) {
+        let name = name.into();
+        if self.reset_port_name.borrow().is_some() {
+            panic!("Attempted to specify a reset port name for module \"{}\", but this module already has a reset port name specified.", self.name);
         }
-        if !ptr::eq(self, when_false.module) {
-            panic!("Attempted to combine signals from different modules.");
+        if !is_valid_attribute_key(&name) {
+            panic!("Attempted to specify \"{}\" as the reset port name for module \"{}\", but port names must be valid Verilog identifiers.", name, self.name);
         }
-        if cond.bit_width() != 1 {
-            panic!("Multiplexer conditionals can only be 1 bit wide.");
+        *self.reset_port_name.borrow_mut() = Some(name);
+    }
+
+    /// Attaches a human-readable `description` to this `Module`'s input or output called `name`.
+    ///
+    /// Descriptions don't affect the behavior of the design. They're emitted as doc comments on the corresponding fields of generated Rust simulators, and are included in this `Module`'s [`interface`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Module` doesn't have an input or output called `name`, or if this port already has a description.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("Uart");
+    /// m.output("tx", m.input("loopback", 1));
+    /// m.port_description("tx", "Serial output, idle high");
+    ///
+    /// assert_eq!(m.interface().outputs[0].description.as_deref(), Some("Serial output, idle high"));
+    /// ```
+    ///
+    /// [`interface`]: #method.interface
+    pub fn port_description<S: Into<String>, D: Into<String>>(&'a self, name: S, description: D) {
+        let name = name.into();
+        if !self.inputs.borrow().contains_key(&name) && !self.outputs.borrow().contains_key(&name) {
+            panic!("Attempted to describe a port called \"{}\" on module \"{}\", but this module doesn't have an input or output with this name.", name, self.name);
         }
-        if when_true.bit_width() != when_false.bit_width() {
-            panic!(
-                "Cannot multiplex signals with different bit widths ({} and {}, respectively).",
-                when_true.bit_width(),
-                when_false.bit_width()
-            );
+        let mut port_descriptions = self.port_descriptions.borrow_mut();
+        if port_descriptions.contains_key(&name) {
+            panic!("Attempted to describe a port called \"{}\" on module \"{}\", but this port already has a description.", name, self.name);
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self,
-
-            data: SignalData::Mux {
-                cond,
-                when_true,
-                when_false,
-            },
-        })
+        port_descriptions.insert(name, description.into());
     }
 
-    /// Creates an [`Instance`] called `instance_name` of the `Module` identified by `module_name` in this [`Context`] inside this `Module` definition.
+    /// Attaches an assertion to this `Module` which states that `condition` is expected to be high on every cycle, described by `message`.
+    ///
+    /// Assertions don't affect the behavior of the design; they're only checked in simulation.
+    /// The generated Rust simulator evaluates each assertion in `prop()` and gets a `check(cycle)` method (where `cycle` is a cycle count maintained by the testbench) which panics with `message` if any assertion failed.
+    /// Assertions attached to instantiated `Module`s are checked as well.
+    /// The generated Verilog code contains an immediate assertion for each assertion that's evaluated on each rising clock edge, guarded by `` `ifndef SYNTHESIS `` so synthesis tools ignore it.
+    /// It also contains an equivalent `assert property` statement guarded by `` `ifdef FORMAL `` for use with formal verification tools (see also [`assume`] and [`cover`]).
     ///
     /// # Panics
     ///
-    /// Panics if a `Module` identified by `module_name` doesn't exist in this [`Context`].
+    /// Panics if `condition` doesn't belong to this `Module`, or if `condition`'s bit width is not `1`.
     ///
     /// # Examples
     ///
@@ -357,49 +733,72 @@ impl<'a> Module<'a> {
     ///
     /// let c = Context::new();
     ///
-    /// // Inner module (simple pass-through)
-    /// let inner = c.module("Inner");
-    /// inner.output("o", inner.input("i", 32));
+    /// let m = c.module("MyModule");
     ///
-    /// // Outer module (wraps a single `inner` instance)
-    /// let outer = c.module("Outer");
-    /// let inner_inst = outer.instance("inner_inst", "Inner");
-    /// inner_inst.drive_input("i", outer.input("i", 32));
-    /// outer.output("o", inner_inst.output("o"));
+    /// let counter = m.reg("counter", 4);
+    /// counter.default_value(0u32);
+    /// counter.drive_next(counter.value + m.lit(1u32, 4));
+    /// m.assert(counter.value.ne(m.lit(0xfu32, 4)), "counter should never reach 0xf");
+    /// m.output("counter", counter.value);
     /// ```
     ///
-    /// [`Context`]: ./struct.Context.html
-    /// [`Instance`]: ./struct.Instance.html
-    pub fn instance<S: Into<String>>(
-        &'a self,
-        instance_name: S,
-        module_name: &str,
-    ) -> &Instance<'a> {
-        // TODO: Error if instance_name already exists in this context
-        match self.context.modules.borrow().get(module_name) {
-            Some(instantiated_module) => {
-                let ret = self.context.instance_arena.alloc(Instance {
-                    context: self.context,
-                    module: self,
+    /// [`assume`]: #method.assume
+    /// [`cover`]: #method.cover
+    pub fn assert<S: Into<String>>(&'a self, condition: &'a Signal<'a>, message: S) {
+        if !ptr::eq(self, condition.module) {
+            panic!("Cannot assert a signal from another module.");
+        }
+        if condition.bit_width() != 1 {
+            panic!("Attempted to add an assertion to module \"{}\" with a condition signal with {} bit(s), but assertion conditions are required to be 1 bit wide.", self.name, condition.bit_width());
+        }
+        self.assertions
+            .borrow_mut()
+            .push((condition, message.into()));
+    }
 
-                    instantiated_module,
-                    name: instance_name.into(),
-                    driven_inputs: RefCell::new(BTreeMap::new()),
-                });
-                self.instances.borrow_mut().push(ret);
-                ret
-            }
-            _ => panic!("Attempted to instantiate a module identified by \"{}\", but no such module exists in this context.", module_name)
+    /// Attaches an assumption to this `Module` which states that `condition` is high on every cycle, typically used to constrain this `Module`'s inputs for formal verification.
+    ///
+    /// The generated Verilog code contains an `assume property` statement for each assumption, guarded by `` `ifdef FORMAL ``.
+    /// In simulation, assumptions are checked alongside [assertions](#method.assert), so the generated Rust simulator's `check(cycle)` method panics if any assumption doesn't hold, and the generated Verilog code contains an immediate assertion guarded by `` `ifndef SYNTHESIS ``.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `condition` doesn't belong to this `Module`, or if `condition`'s bit width is not `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let a = m.input("a", 1);
+    /// let b = m.input("b", 1);
+    /// // a and b are never asserted at the same time
+    /// m.assume(!(a & b));
+    /// m.output("o", a | b);
+    /// ```
+    pub fn assume(&'a self, condition: &'a Signal<'a>) {
+        if !ptr::eq(self, condition.module) {
+            panic!("Cannot assume a signal from another module.");
         }
+        if condition.bit_width() != 1 {
+            panic!("Attempted to add an assumption to module \"{}\" with a condition signal with {} bit(s), but assumption conditions are required to be 1 bit wide.", self.name, condition.bit_width());
+        }
+        self.assumptions.borrow_mut().push(condition);
     }
 
-    /// Creates a [`Mem`] in this `Module` called `name` with `address_bit_width` address bits and `element_bit_width` element bits.
+    /// Attaches a cover point to this `Module` which tracks whether `condition` is ever high.
     ///
-    /// The size of this memory will be `1 << address_bit_width` elements, each `element_bit_width` bits wide.
+    /// The generated Verilog code contains a `cover property` statement for each cover point, guarded by `` `ifdef FORMAL ``.
+    /// The generated Rust simulator counts the number of rising clock edges on which each cover point's condition was high; these counts can be inspected with its `cover_counts()` method, which returns a slice with one count per cover point.
+    /// Cover points are ordered by the order in which they're specified, with this `Module`'s cover points first, followed by those of each of its [instances](#method.instance) (recursively) in the order the instances were created.
     ///
     /// # Panics
     ///
-    /// Panics if `address_bit_width` or `element_bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`], respectively.
+    /// Panics if `condition` doesn't belong to this `Module`, or if `condition`'s bit width is not `1`.
     ///
     /// # Examples
     ///
@@ -410,216 +809,1597 @@ impl<'a> Module<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let my_mem = m.mem("my_mem", 1, 32);
-    /// // Optional, unless no write port is specified
-    /// my_mem.initial_contents(&[0xfadebabeu32, 0xdeadbeefu32]);
-    /// // Optional, unless no initial contents are specified
-    /// my_mem.write_port(m.high(), m.lit(0xabad1deau32, 32), m.high());
-    /// m.output("my_output", my_mem.read_port(m.high(), m.high()));
+    /// let i = m.input("i", 8);
+    /// // Did we ever see the maximum value?
+    /// m.cover(i.eq(m.lit(0xffu32, 8)));
+    /// m.output("o", i);
+    /// ```
+    pub fn cover(&'a self, condition: &'a Signal<'a>) {
+        if !ptr::eq(self, condition.module) {
+            panic!("Cannot cover a signal from another module.");
+        }
+        if condition.bit_width() != 1 {
+            panic!("Attempted to add a cover point to module \"{}\" with a condition signal with {} bit(s), but cover point conditions are required to be 1 bit wide.", self.name, condition.bit_width());
+        }
+        self.covers.borrow_mut().push(condition);
+    }
+
+    /// Attaches a trace point to this `Module` which makes the value of `signal` observable in simulation under the name `label`, without having to output it.
+    ///
+    /// The generated Rust simulator gets a `trace(f)` method which calls `f(label, value)` for each trace point with the value computed by the most recent call to `prop()`, in a deterministic order: this `Module`'s trace points in the order they're specified, followed by those of each of its [instances](#method.instance) (recursively) in the order the instances were created.
+    /// Labels of trace points in instantiated `Module`s are prefixed with the instance names, separated by `.` (for example, `inner.my_label`).
+    ///
+    /// The generated Verilog code contains a `$display` statement for each trace point that's executed on each rising clock edge, guarded by `` `ifndef SYNTHESIS `` so synthesis tools ignore it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `signal` doesn't belong to this `Module`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let sum = m.input("a", 8) + m.input("b", 8);
+    /// m.trace("sum", sum);
+    /// m.output("o", sum.bit(7));
+    /// ```
+    pub fn trace<S: Into<String>>(&'a self, label: S, signal: &'a Signal<'a>) {
+        if !ptr::eq(self, signal.module) {
+            panic!("Cannot trace a signal from another module.");
+        }
+        self.traces.borrow_mut().push((label.into(), signal));
+    }
+
+    /// Creates a [`Register`] in this `Module` called `name` with `bit_width` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`], respectively, or if this `Module` is an extern module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let my_reg = m.reg("my_reg", 32);
+    /// my_reg.default_value(0xfadebabeu32); // Optional
+    /// my_reg.drive_next(!my_reg.value);
+    /// m.output("my_output", my_reg.value);
     /// ```
     ///
     /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
     /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
-    /// [`Mem`]: ./struct.Mem.html
-    pub fn mem<S: Into<String>>(
-        &'a self,
-        name: S,
-        address_bit_width: u32,
-        element_bit_width: u32,
-    ) -> &Mem<'a> {
+    /// [`Register`]: ./struct.Register.html
+    #[track_caller]
+    pub fn reg<S: Into<String>>(&'a self, name: S, bit_width: u32) -> &Register<'a> {
+        if self.is_extern {
+            panic!("Attempted to create a register in extern module \"{}\", but extern modules don't have a body.", self.name);
+        }
         // TODO: Error if name already exists in this context
-        if address_bit_width < MIN_SIGNAL_BIT_WIDTH {
+        if bit_width < MIN_SIGNAL_BIT_WIDTH {
             panic!(
-                "Cannot create a memory with {} address bit(s). Signals must not be narrower than {} bit(s).",
-                address_bit_width, MIN_SIGNAL_BIT_WIDTH
+                "Cannot create a register with {} bit(s). Signals must not be narrower than {} bit(s).",
+                bit_width, MIN_SIGNAL_BIT_WIDTH
             );
         }
-        if address_bit_width > MAX_SIGNAL_BIT_WIDTH {
+        if bit_width > MAX_SIGNAL_BIT_WIDTH {
             panic!(
-                "Cannot create a memory with {} address bit(s). Signals must not be wider than {} bit(s).",
-                address_bit_width, MAX_SIGNAL_BIT_WIDTH
+                "Cannot create a register with {} bit(s). Signals must not be wider than {} bit(s).",
+                bit_width, MAX_SIGNAL_BIT_WIDTH
             );
         }
-        if element_bit_width < MIN_SIGNAL_BIT_WIDTH {
+        let data = self.context.register_data_arena.alloc(RegisterData {
+            module: self,
+
+            name: name.into(),
+            initial_value: RefCell::new(None),
+            bit_width,
+            next: RefCell::new(None),
+            attributes: RefCell::new(Vec::new()),
+        });
+        let value = self
+            .context
+            .alloc_signal(self, Location::caller(), SignalData::Reg { data });
+        self.registers.borrow_mut().push(value);
+        self.context.register_arena.alloc(Register { data, value })
+    }
+
+    /// Creates a [`Latch`] in this `Module` called `name` with `bit_width` bits.
+    ///
+    /// Latches are rarely needed, and are reported as warnings by [`validation::lint`]; prefer a [`Register`] (see [`reg`]) unless a level-sensitive storage element is specifically required.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`], respectively, or if this `Module` is an extern module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let my_latch = m.latch("my_latch", 32);
+    /// my_latch.drive_data(m.input("data", 32));
+    /// my_latch.drive_enable(m.input("enable", 1));
+    /// m.output("my_output", my_latch.value);
+    /// ```
+    ///
+    /// [`Latch`]: ./struct.Latch.html
+    /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    /// [`reg`]: #method.reg
+    /// [`Register`]: ./struct.Register.html
+    /// [`validation::lint`]: ./validation/fn.lint.html
+    #[track_caller]
+    pub fn latch<S: Into<String>>(&'a self, name: S, bit_width: u32) -> &'a Latch<'a> {
+        if self.is_extern {
+            panic!("Attempted to create a latch in extern module \"{}\", but extern modules don't have a body.", self.name);
+        }
+        if bit_width < MIN_SIGNAL_BIT_WIDTH {
             panic!(
-                "Cannot create a memory with {} element bit(s). Signals must not be narrower than {} bit(s).",
-                element_bit_width, MIN_SIGNAL_BIT_WIDTH
+                "Cannot create a latch with {} bit(s). Signals must not be narrower than {} bit(s).",
+                bit_width, MIN_SIGNAL_BIT_WIDTH
             );
         }
-        if element_bit_width > MAX_SIGNAL_BIT_WIDTH {
+        if bit_width > MAX_SIGNAL_BIT_WIDTH {
             panic!(
-                "Cannot create a memory with {} element bit(s). Signals must not be wider than {} bit(s).",
-                element_bit_width, MAX_SIGNAL_BIT_WIDTH
+                "Cannot create a latch with {} bit(s). Signals must not be wider than {} bit(s).",
+                bit_width, MAX_SIGNAL_BIT_WIDTH
             );
         }
-        let ret = self.context.mem_arena.alloc(Mem {
-            context: self.context,
+        let data = self.context.latch_data_arena.alloc(LatchData {
             module: self,
 
-            name: name.into(),
-            address_bit_width,
-            element_bit_width,
+            name: name.into(),
+            bit_width,
+            data: RefCell::new(None),
+            enable: RefCell::new(None),
+        });
+        let value = self
+            .context
+            .alloc_signal(self, Location::caller(), SignalData::Latch { data });
+        self.latches.borrow_mut().push(value);
+        self.context.latch_arena.alloc(Latch { data, value })
+    }
+
+    /// Creates a chain of `stages` [`Register`]s in this `Module` that synchronizes the 1-bit signal `source` from another clock domain, and returns the output of the last stage.
+    ///
+    /// The registers are called `name_stage0` through `name_stageN` (where `N` is `stages - 1`), have no default value, and are marked with the `ASYNC_REG = "TRUE"` [attribute] so that synthesis tools (eg. Vivado) keep them together and exclude the first stage from timing analysis.
+    /// The registers are clocked by this `Module`'s (destination) clock.
+    /// In the generated simulator, the synchronizer is simply a `stages`-cycle delay.
+    ///
+    /// Only single-bit signals can be safely synchronized this way; multi-bit values should be passed across clock domains with Gray-coded pointers or a handshake instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` belongs to a different `Module` than `self`, if `source`'s bit width is not 1, if `stages` is less than 2, or if this `Module` is an extern module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let async_irq = m.input("async_irq", 1);
+    /// m.output("irq", m.synchronizer("irq_sync", async_irq, 2));
+    /// ```
+    ///
+    /// [`Register`]: ./struct.Register.html
+    /// [attribute]: ./struct.Register.html#method.attribute
+    #[track_caller]
+    pub fn synchronizer<S: Into<String>>(
+        &'a self,
+        name: S,
+        source: &'a Signal<'a>,
+        stages: u32,
+    ) -> &'a Signal<'a> {
+        let name = name.into();
+        if !ptr::eq(self, source.module) {
+            panic!("Attempted to create a synchronizer called \"{}\" in module \"{}\" for a signal from another module.", name, self.name);
+        }
+        if source.bit_width() != 1 {
+            panic!("Attempted to create a synchronizer called \"{}\" in module \"{}\" for a signal with {} bit(s), but synchronizers only support 1-bit signals. The signal was created at {}.", name, self.name, source.bit_width(), source.location);
+        }
+        if stages < 2 {
+            panic!("Attempted to create a synchronizer called \"{}\" in module \"{}\" with {} stage(s), but synchronizers require at least 2 stages.", name, self.name, stages);
+        }
+        let mut ret = source;
+        for stage in 0..stages {
+            let reg = self.reg(format!("{}_stage{}", name, stage), 1);
+            reg.attribute("ASYNC_REG", "TRUE");
+            reg.drive_next(ret);
+            ret = reg.value;
+        }
+        ret
+    }
+
+    /// Creates a chain of `stages` back-to-back [`Register`]s in this `Module` that delays `source` by `stages` cycles, and returns the output of the last stage.
+    ///
+    /// The registers are called `name_stage0` through `name_stageN` (where `N` is `stages - 1`), have the same bit width as `source`, and all have the default value `default_value`.
+    /// If `stages` is 0, no registers are created and `source` is returned as-is.
+    ///
+    /// This is typically used to align signals with the stages of a pipeline; see [`delay_enabled`] for a pipeline that can stall.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` belongs to a different `Module` than `self`, if `default_value` doesn't fit into `source`'s bit width, or if this `Module` is an extern module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let valid = m.input("valid", 1);
+    /// // Align `valid` with a 3-stage datapath
+    /// m.output("valid_out", m.delay("valid_pipe", valid, 3, false));
+    ///
+    /// assert_eq!(m.registers().len(), 3);
+    /// ```
+    ///
+    /// [`delay_enabled`]: #method.delay_enabled
+    /// [`Register`]: ./struct.Register.html
+    #[track_caller]
+    pub fn delay<S: Into<String>, C: Into<Constant>>(
+        &'a self,
+        name: S,
+        source: &'a Signal<'a>,
+        stages: u32,
+        default_value: C,
+    ) -> &'a Signal<'a> {
+        self.delay_impl(name.into(), source, stages, default_value.into(), None)
+    }
+
+    /// Like [`delay`], except that the registers only advance on cycles where `enable` is high, and otherwise hold their values.
+    ///
+    /// All stages share `enable`, so the whole chain stalls together, like a pipeline that can be stalled.
+    /// If `stages` is 0, no registers are created, `enable` is ignored, and `source` is returned as-is.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`delay`], if `enable` belongs to a different `Module` than `self`, or if `enable`'s bit width is not 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let data = m.input("data", 32);
+    /// let advance = m.input("advance", 1);
+    /// m.output("data_out", m.delay_enabled("data_pipe", data, 2, 0u32, advance));
+    /// ```
+    ///
+    /// [`delay`]: #method.delay
+    #[track_caller]
+    pub fn delay_enabled<S: Into<String>, C: Into<Constant>>(
+        &'a self,
+        name: S,
+        source: &'a Signal<'a>,
+        stages: u32,
+        default_value: C,
+        enable: &'a Signal<'a>,
+    ) -> &'a Signal<'a> {
+        let name = name.into();
+        if !ptr::eq(self, enable.module) {
+            panic!("Attempted to create a delay called \"{}\" in module \"{}\" with an enable signal from another module.", name, self.name);
+        }
+        if enable.bit_width() != 1 {
+            panic!("Attempted to create a delay called \"{}\" in module \"{}\" with an enable signal with {} bit(s), but enable signals must be 1 bit wide. The enable signal was created at {}.", name, self.name, enable.bit_width(), enable.location);
+        }
+        self.delay_impl(name, source, stages, default_value.into(), Some(enable))
+    }
+
+    #[track_caller]
+    fn delay_impl(
+        &'a self,
+        name: String,
+        source: &'a Signal<'a>,
+        stages: u32,
+        default_value: Constant,
+        enable: Option<&'a Signal<'a>>,
+    ) -> &'a Signal<'a> {
+        if !ptr::eq(self, source.module) {
+            panic!("Attempted to create a delay called \"{}\" in module \"{}\" for a signal from another module.", name, self.name);
+        }
+        if default_value.required_bits() > source.bit_width() {
+            panic!("Cannot fit the specified default value '{}' into delay \"{}\"'s bit width '{}'. The value '{}' requires a bit width of at least {} bit(s). The signal was created at {}.", default_value.numeric_value(), name, source.bit_width(), default_value.numeric_value(), default_value.required_bits(), source.location);
+        }
+        let mut ret = source;
+        for stage in 0..stages {
+            let reg = self.reg(format!("{}_stage{}", name, stage), source.bit_width());
+            reg.default_value(default_value.numeric_value());
+            reg.drive_next(match enable {
+                Some(enable) => self.mux(enable, ret, reg.value),
+                _ => ret,
+            });
+            ret = reg.value;
+        }
+        ret
+    }
+
+    /// Creates a 2:1 [multiplexer](https://en.wikipedia.org/wiki/Multiplexer) that represents `when_true`'s value when `cond` is high, and `when_false`'s value when `cond` is low.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cond`, `when_true`, or `when_false` belong to a different `Module` than `self`, if `cond`'s bit width is not 1, or if the bit widths of `when_true` and `when_false` aren't equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let cond = m.input("cond", 1);
+    /// let a = m.input("a", 8);
+    /// let b = m.input("b", 8);
+    /// m.output("my_output", m.mux(cond, a, b)); // Outputs a when cond is high, b otherwise
+    /// ```
+    #[track_caller]
+    pub fn mux(
+        &'a self,
+        cond: &'a Signal<'a>,
+        when_true: &'a Signal<'a>,
+        when_false: &'a Signal<'a>,
+    ) -> &Signal<'a> {
+        for &(signal, description) in [
+            (cond, "condition"),
+            (when_true, "\"when_true\" signal"),
+            (when_false, "\"when_false\" signal"),
+        ]
+        .iter()
+        {
+            if !ptr::eq(self, signal.module) {
+                panic!(
+                    "Attempted to multiplex signals from different modules. The {} belongs to module \"{}\", but the multiplexer is being created in module \"{}\". The {} was created at {}.",
+                    description, signal.module.name, self.name, description, signal.location
+                );
+            }
+        }
+        if cond.bit_width() != 1 {
+            panic!(
+                "Multiplexer conditionals can only be 1 bit wide, but the condition has {} bit(s). The condition was created at {}.",
+                cond.bit_width(),
+                cond.location
+            );
+        }
+        if when_true.bit_width() != when_false.bit_width() {
+            panic!(
+                "Cannot multiplex signals with different bit widths ({} and {}, respectively). The signals were created at {} and {}.",
+                when_true.bit_width(),
+                when_false.bit_width(),
+                when_true.location,
+                when_false.location
+            );
+        }
+
+        // TODO: This is an optimization to support sugar; if that doesn't go well, remove this
+        if when_true == when_false {
+            return when_true;
+        }
+
+        self.context.alloc_signal(
+            self,
+            Location::caller(),
+            SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            },
+        )
+    }
+
+    /// Concatenates `elements` into a single [`Signal`], with `elements[0]` in the least significant bits and each following element in the next more significant bits.
+    ///
+    /// This is the inverse of [`unpack`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `elements` is empty, if any element belongs to a different `Module` than `self`, if the elements' bit widths aren't all equal, or if the combined bit width would be greater than [`MAX_SIGNAL_BIT_WIDTH`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lanes = (0..4)
+    ///     .map(|i| m.input(format!("lane{}", i), 8))
+    ///     .collect::<Vec<_>>();
+    /// let packed = m.pack(&lanes);
+    /// assert_eq!(packed.bit_width(), 32);
+    /// m.output("packed", packed);
+    /// ```
+    ///
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    /// [`Signal`]: ./struct.Signal.html
+    /// [`unpack`]: #method.unpack
+    #[track_caller]
+    pub fn pack(&'a self, elements: &[&'a Signal<'a>]) -> &'a Signal<'a> {
+        let element_bit_width = self.check_elements("pack", elements);
+        let bit_width = element_bit_width as u64 * elements.len() as u64;
+        if bit_width > MAX_SIGNAL_BIT_WIDTH as u64 {
+            panic!(
+                "Cannot pack {} signals with {} bit(s) each, as this would result in a bit width of {}, which is greater than the maximum signal bit width of {} bit(s).",
+                elements.len(),
+                element_bit_width,
+                bit_width,
+                MAX_SIGNAL_BIT_WIDTH
+            );
+        }
+
+        let mut ret = elements[0];
+        for element in elements.iter().skip(1) {
+            ret = element.concat(ret);
+        }
+        ret
+    }
+
+    /// Splits `source` into elements of `element_bit_width` bits each, with the least significant bits of `source` in the first element.
+    ///
+    /// This is the inverse of [`pack`], and is equivalent to [`Signal::chunks`] (except that `source` must belong to this `Module`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` belongs to a different `Module` than `self`, if `element_bit_width` is 0, or if `source`'s bit width isn't a multiple of `element_bit_width`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let bytes = m.unpack(m.input("word", 32), 8);
+    /// assert_eq!(bytes.len(), 4);
+    /// m.output("low_byte", bytes[0]);
+    /// m.output("high_byte", bytes[3]);
+    /// ```
+    ///
+    /// [`pack`]: #method.pack
+    /// [`Signal::chunks`]: ./struct.Signal.html#method.chunks
+    #[track_caller]
+    pub fn unpack(&'a self, source: &'a Signal<'a>, element_bit_width: u32) -> Vec<&'a Signal<'a>> {
+        if !ptr::eq(self, source.module) {
+            panic!("Attempted to combine signals from different modules.");
+        }
+        if element_bit_width < MIN_SIGNAL_BIT_WIDTH
+            || !source.bit_width().is_multiple_of(element_bit_width)
+        {
+            panic!(
+                "Cannot unpack a signal with a width of {} bit(s) into elements of {} bit(s), as its width isn't a multiple of the element width. The signal was created at {}.",
+                source.bit_width(),
+                element_bit_width,
+                source.location
+            );
+        }
+
+        source.chunks(element_bit_width)
+    }
+
+    /// Creates a multiplexer that selects `elements[index]`, built as a balanced tree of 2:1 [`mux`]es so that its depth is logarithmic in the number of elements.
+    ///
+    /// Bit `n` of `index` selects between pairs at level `n` of the tree, so `index` only needs to be wide enough to address every element; any bits above that are ignored, and an `index` greater than or equal to `elements.len()` selects an unspecified element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `elements` is empty, if any element or `index` belongs to a different `Module` than `self`, if the elements' bit widths aren't all equal, or if `index` isn't wide enough to address every element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lanes = (0..8)
+    ///     .map(|i| m.input(format!("lane{}", i), 16))
+    ///     .collect::<Vec<_>>();
+    /// m.output("selected", m.mux_index(&lanes, m.input("index", 3)));
+    /// ```
+    ///
+    /// [`mux`]: #method.mux
+    #[track_caller]
+    pub fn mux_index(
+        &'a self,
+        elements: &[&'a Signal<'a>],
+        index: &'a Signal<'a>,
+    ) -> &'a Signal<'a> {
+        self.check_elements("multiplex", elements);
+        if !ptr::eq(self, index.module) {
+            panic!("Attempted to combine signals from different modules.");
+        }
+        let index_bit_width = 32 - (elements.len() as u32 - 1).leading_zeros();
+        if index.bit_width() < index_bit_width {
+            panic!(
+                "Cannot select between {} signals with an index of {} bit(s). An index of at least {} bit(s) is required. The index was created at {}.",
+                elements.len(),
+                index.bit_width(),
+                index_bit_width,
+                index.location
+            );
+        }
+
+        let mut level = elements.to_vec();
+        let mut index_bit = 0;
+        while level.len() > 1 {
+            let cond = index.bit(index_bit);
+            let mut next_level = Vec::new();
+            for pair in level.chunks(2) {
+                next_level.push(match *pair {
+                    [when_false, when_true] => self.mux(cond, when_true, when_false),
+                    _ => pair[0],
+                });
+            }
+            level = next_level;
+            index_bit += 1;
+        }
+        level[0]
+    }
+
+    /// Creates a multiplexer that selects the value paired with the select signal that's high, assuming that at most one select signal is high at a time.
+    ///
+    /// Unlike a chain of [`mux`]es (eg. built with [`if_`]), which encodes a priority between the select signals, this builds a flat AND/OR network: each value is masked with its select signal (replicated across the value's bit width), and the masked values are OR'd together.
+    /// If no select signal is high, the result is `0`; if more than one is high, the result is the bitwise OR of the selected values, which is typically not meaningful.
+    /// Use [`mux_one_hot_checked`] to also attach an [assertion] that catches this in simulation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cases` is empty, if any select signal or value belongs to a different `Module` than `self`, if any select signal's bit width is not 1, or if the values' bit widths aren't all equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let grant = m.input("grant", 3);
+    /// let data = m.mux_one_hot(&[
+    ///     (grant.bit(0), m.input("data0", 32)),
+    ///     (grant.bit(1), m.input("data1", 32)),
+    ///     (grant.bit(2), m.input("data2", 32)),
+    /// ]);
+    /// m.output("data", data);
+    /// ```
+    ///
+    /// [assertion]: #method.assert
+    /// [`if_`]: ./fn.if_.html
+    /// [`mux`]: #method.mux
+    /// [`mux_one_hot_checked`]: #method.mux_one_hot_checked
+    #[track_caller]
+    pub fn mux_one_hot(&'a self, cases: &[(&'a Signal<'a>, &'a Signal<'a>)]) -> &'a Signal<'a> {
+        let mut values = Vec::new();
+        for (index, &(select, value)) in cases.iter().enumerate() {
+            if !ptr::eq(self, select.module) {
+                panic!(
+                    "Attempted to one-hot multiplex signals from different modules. Select {} belongs to module \"{}\", but the signals are being combined in module \"{}\". The select was created at {}.",
+                    index, select.module.name, self.name, select.location
+                );
+            }
+            if select.bit_width() != 1 {
+                panic!(
+                    "Cannot one-hot multiplex signals with a select signal with {} bit(s). Select {} must be 1 bit wide. The select was created at {}.",
+                    select.bit_width(),
+                    index,
+                    select.location
+                );
+            }
+            values.push(value);
+        }
+        let bit_width = self.check_elements("one-hot multiplex", &values);
+
+        let mut ret = None;
+        for &(select, value) in cases {
+            let masked = value & select.repeat(bit_width);
+            ret = Some(match ret {
+                Some(ret) => ret | masked,
+                None => masked,
+            });
+        }
+        ret.unwrap()
+    }
+
+    /// Creates a one-hot multiplexer like [`mux_one_hot`], and also attaches an [assertion] to this `Module` (described by `message`) which states that at most one of the select signals is high on every cycle.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`mux_one_hot`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let grant = m.input("grant", 2);
+    /// let data = m.mux_one_hot_checked(
+    ///     &[
+    ///         (grant.bit(0), m.input("data0", 32)),
+    ///         (grant.bit(1), m.input("data1", 32)),
+    ///     ],
+    ///     "grant must be one-hot",
+    /// );
+    /// m.output("data", data);
+    /// ```
+    ///
+    /// [assertion]: #method.assert
+    /// [`mux_one_hot`]: #method.mux_one_hot
+    #[track_caller]
+    pub fn mux_one_hot_checked<S: Into<String>>(
+        &'a self,
+        cases: &[(&'a Signal<'a>, &'a Signal<'a>)],
+        message: S,
+    ) -> &'a Signal<'a> {
+        let ret = self.mux_one_hot(cases);
+
+        let mut any_selected = self.low();
+        let mut multiple_selected = self.low();
+        for &(select, _) in cases {
+            multiple_selected = multiple_selected | (any_selected & select);
+            any_selected = any_selected | select;
+        }
+        self.assert(!multiple_selected, message);
+
+        ret
+    }
+
+    #[track_caller]
+    fn check_elements(&'a self, what: &str, elements: &[&'a Signal<'a>]) -> u32 {
+        if elements.is_empty() {
+            panic!("Cannot {} an empty list of signals.", what);
+        }
+        let element_bit_width = elements[0].bit_width();
+        for (index, element) in elements.iter().enumerate() {
+            if !ptr::eq(self, element.module) {
+                panic!(
+                    "Attempted to {} signals from different modules. Element {} belongs to module \"{}\", but the signals are being combined in module \"{}\". The element was created at {}.",
+                    what, index, element.module.name, self.name, element.location
+                );
+            }
+            if element.bit_width() != element_bit_width {
+                panic!(
+                    "Cannot {} signals with different bit widths. Element {} has {} bit(s), but element 0 has {} bit(s). Element {} was created at {}.",
+                    what,
+                    index,
+                    element.bit_width(),
+                    element_bit_width,
+                    index,
+                    element.location
+                );
+            }
+        }
+        element_bit_width
+    }
+
+    /// Creates an [`Instance`] called `instance_name` of the `Module` identified by `module_name` in this [`Context`] inside this `Module` definition.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `Module` identified by `module_name` doesn't exist in this [`Context`], or if this `Module` is an extern module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// // Inner module (simple pass-through)
+    /// let inner = c.module("Inner");
+    /// inner.output("o", inner.input("i", 32));
+    ///
+    /// // Outer module (wraps a single `inner` instance)
+    /// let outer = c.module("Outer");
+    /// let inner_inst = outer.instance("inner_inst", "Inner");
+    /// inner_inst.drive_input("i", outer.input("i", 32));
+    /// outer.output("o", inner_inst.output("o"));
+    /// ```
+    ///
+    /// [`Context`]: ./struct.Context.html
+    /// [`Instance`]: ./struct.Instance.html
+    #[track_caller]
+    pub fn instance<S: Into<String>>(
+        &'a self,
+        instance_name: S,
+        module_name: &str,
+    ) -> &Instance<'a> {
+        if self.is_extern {
+            panic!("Attempted to create an instance in extern module \"{}\", but extern modules don't have a body.", self.name);
+        }
+        // TODO: Error if instance_name already exists in this context
+        match self.context.modules.borrow().get(module_name) {
+            Some(instantiated_module) => {
+                let ret = self.context.instance_arena.alloc(Instance {
+                    context: self.context,
+                    module: self,
+                    location: Location::caller(),
+
+                    instantiated_module,
+                    name: instance_name.into(),
+                    driven_inputs: RefCell::new(BTreeMap::new()),
+                    parameters: RefCell::new(Vec::new()),
+                });
+                self.instances.borrow_mut().push(ret);
+                ret
+            }
+            _ => panic!("Attempted to instantiate a module identified by \"{}\", but no such module exists in this context.", module_name)
+        }
+    }
+
+    /// Creates `count` [`Instance`]s of the `Module` identified by `module_name` in this [`Context`] inside this `Module` definition, returned in index order.
+    ///
+    /// The `Instance` at index `i` is called `<array_name>_<i>`, and behaves exactly like one created by the [`instance`] method.
+    /// However, when generating Verilog, the instances are emitted as a single `generate` `for` loop (instead of one instance per lane) as long as each input is either driven by the same [`Signal`] for every lane, or driven by lane `i` with bits `[base + i * w + w - 1:base + i * w]` of the same [`Signal`] (where `w` is the input's bit width, as produced by slicing a wide bus into consecutive lanes).
+    /// Otherwise, the instances are emitted individually.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is `0`, or if a `Module` identified by `module_name` doesn't exist in this [`Context`], or if this `Module` is an extern module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let lane = c.module("Lane");
+    /// lane.output("o", !lane.input("i", 8));
+    ///
+    /// let m = c.module("Lanes");
+    /// let i = m.input("i", 32);
+    /// let lanes = m.instance_array("lane", "Lane", 4);
+    /// for (index, lane) in lanes.iter().enumerate() {
+    ///     let index = index as u32;
+    ///     lane.drive_input("i", i.bits(index * 8 + 7, index * 8));
+    /// }
+    /// m.output(
+    ///     "o",
+    ///     lanes[3]
+    ///         .output("o")
+    ///         .concat(lanes[2].output("o"))
+    ///         .concat(lanes[1].output("o"))
+    ///         .concat(lanes[0].output("o")),
+    /// );
+    ///
+    /// assert_eq!(lanes[2].name(), "lane_2");
+    /// ```
+    ///
+    /// [`Context`]: ./struct.Context.html
+    /// [`Instance`]: ./struct.Instance.html
+    /// [`instance`]: #method.instance
+    /// [`Signal`]: ./struct.Signal.html
+    #[track_caller]
+    pub fn instance_array<S: Into<String>>(
+        &'a self,
+        array_name: S,
+        module_name: &str,
+        count: u32,
+    ) -> Vec<&'a Instance<'a>> {
+        let array_name = array_name.into();
+        if count == 0 {
+            panic!(
+                "Attempted to create an instance array called \"{}\" with 0 instances, but instance arrays must contain at least 1 instance.",
+                array_name
+            );
+        }
+        let instances = (0..count)
+            .map(|index| self.instance(format!("{}_{}", array_name, index), module_name))
+            .collect::<Vec<_>>();
+        self.instance_arrays
+            .borrow_mut()
+            .push((array_name, instances.clone()));
+        instances
+    }
+
+    /// Creates a [`Mem`] in this `Module` called `name` with `address_bit_width` address bits and `element_bit_width` element bits.
+    ///
+    /// The size of this memory will be `1 << address_bit_width` elements, each `element_bit_width` bits wide.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `address_bit_width` or `element_bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`], respectively, or if this `Module` is an extern module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let my_mem = m.mem("my_mem", 1, 32);
+    /// // Optional, unless no write port is specified
+    /// my_mem.initial_contents(&[0xfadebabeu32, 0xdeadbeefu32]);
+    /// // Optional, unless no initial contents are specified
+    /// my_mem.write_port(m.high(), m.lit(0xabad1deau32, 32), m.high());
+    /// m.output("my_output", my_mem.read_port(m.high(), m.high()));
+    /// ```
+    ///
+    /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    /// [`Mem`]: ./struct.Mem.html
+    #[track_caller]
+    pub fn mem<S: Into<String>>(
+        &'a self,
+        name: S,
+        address_bit_width: u32,
+        element_bit_width: u32,
+    ) -> &Mem<'a> {
+        if self.is_extern {
+            panic!("Attempted to create a memory in extern module \"{}\", but extern modules don't have a body.", self.name);
+        }
+        // TODO: Error if name already exists in this context
+        if address_bit_width < MIN_SIGNAL_BIT_WIDTH {
+            panic!(
+                "Cannot create a memory with {} address bit(s). Signals must not be narrower than {} bit(s).",
+                address_bit_width, MIN_SIGNAL_BIT_WIDTH
+            );
+        }
+        if address_bit_width > MAX_SIGNAL_BIT_WIDTH {
+            panic!(
+                "Cannot create a memory with {} address bit(s). Signals must not be wider than {} bit(s).",
+                address_bit_width, MAX_SIGNAL_BIT_WIDTH
+            );
+        }
+        if element_bit_width < MIN_SIGNAL_BIT_WIDTH {
+            panic!(
+                "Cannot create a memory with {} element bit(s). Signals must not be narrower than {} bit(s).",
+                element_bit_width, MIN_SIGNAL_BIT_WIDTH
+            );
+        }
+        if element_bit_width > MAX_SIGNAL_BIT_WIDTH {
+            panic!(
+                "Cannot create a memory with {} element bit(s). Signals must not be wider than {} bit(s).",
+                element_bit_width, MAX_SIGNAL_BIT_WIDTH
+            );
+        }
+        let ret = self.context.mem_arena.alloc(Mem {
+            context: self.context,
+            module: self,
+            location: Location::caller(),
+
+            name: name.into(),
+            address_bit_width,
+            element_bit_width,
+
+            initial_contents: RefCell::new(None),
+            initial_contents_path: RefCell::new(None),
+            read_during_write: RefCell::new(ReadDuringWrite::Old),
+
+            read_ports: RefCell::new(Vec::new()),
+            comb_read_ports: RefCell::new(Vec::new()),
+            write_ports: RefCell::new(Vec::new()),
+
+            attributes: RefCell::new(Vec::new()),
+        });
+        self.mems.borrow_mut().push(ret);
+        ret
+    }
+
+    /// Creates a [`RegisterFile`] in this `Module` called `name` with `depth` elements of `element_bit_width` bits each.
+    ///
+    /// The register file is built from `depth` [`Register`]s called `name_0` through `name_N` (where `N` is `depth - 1`), which have no default value.
+    /// Its address bit width is `max(1, ceil(log2(depth)))`, and any `depth` of at least 1 is supported; it doesn't need to be a power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is 0, if `element_bit_width` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`], or if this `Module` is an extern module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let regs = m.register_file("regs", 32, 64);
+    /// regs.write_port(m.input("rd", 5), m.input("rd_data", 64), m.input("rd_enable", 1));
+    /// m.output("rs1_data", regs.read_port(m.input("rs1", 5)));
+    /// m.output("rs2_data", regs.read_port(m.input("rs2", 5)));
+    ///
+    /// assert_eq!(m.registers().len(), 32);
+    /// ```
+    ///
+    /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    /// [`Register`]: ./struct.Register.html
+    /// [`RegisterFile`]: ./struct.RegisterFile.html
+    #[track_caller]
+    pub fn register_file<S: Into<String>>(
+        &'a self,
+        name: S,
+        depth: u32,
+        element_bit_width: u32,
+    ) -> &'a RegisterFile<'a> {
+        let name = name.into();
+        if depth == 0 {
+            panic!(
+                "Cannot create a register file called \"{}\" with a depth of 0. Register files must hold at least 1 element.",
+                name
+            );
+        }
+        let address_bit_width = (32 - (depth - 1).leading_zeros()).max(1);
+
+        let mut registers = Vec::new();
+        for index in 0..depth {
+            registers.push(self.reg(format!("{}_{}", name, index), element_bit_width));
+        }
+
+        self.context.register_file_arena.alloc(RegisterFile {
+            module: self,
+            location: Location::caller(),
+
+            name,
+            address_bit_width,
+            element_bit_width,
+
+            registers,
+            write_port: RefCell::new(None),
+        })
+    }
+}
+
+fn lit_data<'a, C: Into<Constant>>(value: C, bit_width: u32) -> Result<SignalData<'a>, GraphError> {
+    check_bit_width("a literal", bit_width)?;
+    let value = value.into();
+    let required_bits = value.required_bits();
+    if required_bits > bit_width {
+        return Err(GraphError::LitValueTooWide {
+            value: value.numeric_value(),
+            bit_width,
+            required_bits,
+        });
+    }
+    Ok(SignalData::Lit {
+        value: Box::new(value),
+        bit_width,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Cannot pack an empty list of signals.")]
+    fn pack_empty_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.pack(&[]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot pack signals with different bit widths. Element 2 has 4 bit(s), but element 0 has 8 bit(s). Element 2 was created at"
+    )]
+    fn pack_bit_width_mismatch_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+        let b = m.input("b", 8);
+        let d = m.input("d", 4);
+
+        // Panic
+        let _ = m.pack(&[a, b, d]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot pack 3 signals with 64 bit(s) each, as this would result in a bit width of 192, which is greater than the maximum signal bit width of 128 bit(s)."
+    )]
+    fn pack_too_wide_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 64);
+
+        // Panic
+        let _ = m.pack(&[i, i, i]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to multiplex signals from different modules. Element 1 belongs to module \"B\", but the signals are being combined in module \"A\"."
+    )]
+    fn mux_index_different_modules_error() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let b = c.module("B");
+
+        // Panic
+        let _ = a.mux_index(&[a.input("i", 1), b.input("i", 1)], a.input("index", 1));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot select between 5 signals with an index of 2 bit(s). An index of at least 3 bit(s) is required."
+    )]
+    fn mux_index_narrow_index_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 8);
+
+        // Panic
+        let _ = m.mux_index(&[i, i, i, i, i], m.input("index", 2));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot unpack a signal with a width of 30 bit(s) into elements of 8 bit(s), as its width isn't a multiple of the element width."
+    )]
+    fn unpack_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.unpack(m.input("i", 30), 8);
+    }
+
+    #[test]
+    fn mux_index_depth() {
+        fn depth(signal: &Signal) -> u32 {
+            match signal.data {
+                SignalData::Mux {
+                    when_true,
+                    when_false,
+                    ..
+                } => 1 + depth(when_true).max(depth(when_false)),
+                _ => 0,
+            }
+        }
+
+        let c = Context::new();
+
+        let m = c.module("A");
+        let elements = (0..16)
+            .map(|i| m.input(format!("i{}", i), 8))
+            .collect::<Vec<_>>();
+
+        assert_eq!(depth(m.mux_index(&elements, m.input("index", 4))), 4);
+        assert_eq!(depth(m.mux_index(&elements[..9], m.input("index9", 4))), 4);
+        assert_eq!(depth(m.mux_index(&elements[..1], m.input("index1", 1))), 0);
+    }
+
+    #[test]
+    fn mux_one_hot_is_flat() {
+        use crate::analysis::{stats, NodeKind};
+
+        let c = Context::new();
+
+        let m = c.module("A");
+        let select = m.input("select", 4);
+        let mut cases = Vec::new();
+        for i in 0..4 {
+            cases.push((select.bit(i), m.input(format!("i{}", i), 8)));
+        }
+        m.output("o", m.mux_one_hot(&cases));
+
+        let nodes = stats(m).total.nodes;
+        assert_eq!(nodes.get(&NodeKind::Mux), None);
+        // One AND per case, and one OR for each case after the first
+        assert_eq!(nodes[&NodeKind::BinOp], 4 + 3);
+        assert_eq!(nodes[&NodeKind::Repeat], 4);
+        assert_eq!(nodes[&NodeKind::Bits], 4);
+    }
+
+    #[test]
+    fn mux_one_hot_checked_adds_assertion() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let select = m.input("select", 2);
+        let cases = [
+            (select.bit(0), m.input("i0", 8)),
+            (select.bit(1), m.input("i1", 8)),
+        ];
+        let o = m.mux_one_hot_checked(&cases, "select must be one-hot");
+
+        assert!(o == m.mux_one_hot(&cases));
+        assert_eq!(m.assertions.borrow().len(), 1);
+        assert_eq!(m.assertions.borrow()[0].1, "select must be one-hot");
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot one-hot multiplex an empty list of signals.")]
+    fn mux_one_hot_empty_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.mux_one_hot(&[]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot one-hot multiplex signals with a select signal with 2 bit(s). Select 1 must be 1 bit wide."
+    )]
+    fn mux_one_hot_select_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.mux_one_hot(&[
+            (m.input("s0", 1), m.input("i0", 8)),
+            (m.input("s1", 2), m.input("i1", 8)),
+        ]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot one-hot multiplex signals with different bit widths. Element 1 has 4 bit(s), but element 0 has 8 bit(s)."
+    )]
+    fn mux_one_hot_value_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.mux_one_hot(&[
+            (m.input("s0", 1), m.input("i0", 8)),
+            (m.input("s1", 1), m.input("i1", 4)),
+        ]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to one-hot multiplex signals from different modules. Select 0 belongs to module \"B\", but the signals are being combined in module \"A\"."
+    )]
+    fn mux_one_hot_different_modules_error() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let b = c.module("B");
+
+        // Panic
+        let _ = a.mux_one_hot(&[(b.input("s0", 1), a.input("i0", 8))]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create a literal with 0 bit(s). Signals must not be narrower than 1 bit(s)."
+    )]
+    fn lit_bit_width_lt_min_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.lit(false, 0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create a literal with 129 bit(s). Signals must not be wider than 128 bit(s)."
+    )]
+    fn lit_bit_width_gt_max_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.lit(false, 129);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot fit the specified value '128' into the specified bit width '7'. The value '128' requires a bit width of at least 8 bit(s)."
+    )]
+    fn lit_value_cannot_bit_into_bit_width_error_1() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.lit(128u32, 7);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot fit the specified value '128' into the specified bit width '2'. The value '128' requires a bit width of at least 8 bit(s)."
+    )]
+    fn lit_value_cannot_bit_into_bit_width_error_2() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.lit(128u64, 2);
+    }
+
+    #[test]
+    fn constants_are_distinct_signals() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        let magic = m.constant("MAGIC", 5u32, 8);
+        let other = m.constant("OTHER", 5u32, 8);
+        let lit = m.lit(5u32, 8);
+
+        assert!(!std::ptr::eq(magic, other));
+        assert!(!std::ptr::eq(magic, lit));
+        assert!(!std::ptr::eq(other, lit));
+        assert!(std::ptr::eq(lit, m.lit(5u32, 8)));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to create a constant called \"MAGIC\" in module \"A\", but this module already contains a constant with this name."
+    )]
+    fn constant_name_dupe_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        let _ = m.constant("MAGIC", 0xdeadbeefu32, 32);
+
+        // Panic
+        let _ = m.constant("MAGIC", 0xfadebabeu32, 32);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot fit the specified value '1023' into the specified bit width '4'. The value '1023' requires a bit width of at least 10 bit(s)."
+    )]
+    fn lit_value_cannot_bit_into_bit_width_error_3() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.lit(1023u128, 4);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot fit the specified value '65536' into the specified bit width '1'. The value '65536' requires a bit width of at least 17 bit(s)."
+    )]
+    fn lit_value_cannot_bit_into_bit_width_error_4() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.lit(65536u32, 1);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create an input with 0 bit(s). Signals must not be narrower than 1 bit(s)."
+    )]
+    fn input_width_lt_min_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.input("i", 0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create an input with 129 bit(s). Signals must not be wider than 128 bit(s)."
+    )]
+    fn input_width_gt_max_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.input("i", 129);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot output a signal from another module.")]
+    fn output_separate_module_error() {
+        let c = Context::new();
 
-            initial_contents: RefCell::new(None),
+        let m1 = c.module("A");
 
-            read_ports: RefCell::new(Vec::new()),
-            write_port: RefCell::new(None),
-        });
-        self.mems.borrow_mut().push(ret);
-        ret
+        let m2 = c.module("B");
+        let i = m2.high();
+
+        // Panic
+        m1.output("a", i);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    #[should_panic(expected = "Cannot assert a signal from another module.")]
+    fn assert_separate_module_error() {
+        let c = Context::new();
+
+        let m1 = c.module("A");
+
+        let m2 = c.module("B");
+        let i = m2.high();
+
+        // Panic
+        m1.assert(i, "a");
+    }
 
     #[test]
     #[should_panic(
-        expected = "Cannot create a literal with 0 bit(s). Signals must not be narrower than 1 bit(s)."
+        expected = "Attempted to add an assertion to module \"A\" with a condition signal with 2 bit(s), but assertion conditions are required to be 1 bit wide."
     )]
-    fn lit_bit_width_lt_min_error() {
+    fn assert_condition_bit_width_error() {
         let c = Context::new();
 
         let m = c.module("A");
 
         // Panic
-        let _ = m.lit(false, 0);
+        m.assert(m.lit(0u32, 2), "a");
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot assume a signal from another module.")]
+    fn assume_separate_module_error() {
+        let c = Context::new();
+
+        let m1 = c.module("A");
+
+        let m2 = c.module("B");
+        let i = m2.high();
+
+        // Panic
+        m1.assume(i);
     }
 
     #[test]
     #[should_panic(
-        expected = "Cannot create a literal with 129 bit(s). Signals must not be wider than 128 bit(s)."
+        expected = "Attempted to add an assumption to module \"A\" with a condition signal with 2 bit(s), but assumption conditions are required to be 1 bit wide."
     )]
-    fn lit_bit_width_gt_max_error() {
+    fn assume_condition_bit_width_error() {
         let c = Context::new();
 
         let m = c.module("A");
 
         // Panic
-        let _ = m.lit(false, 129);
+        m.assume(m.lit(0u32, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot cover a signal from another module.")]
+    fn cover_separate_module_error() {
+        let c = Context::new();
+
+        let m1 = c.module("A");
+
+        let m2 = c.module("B");
+        let i = m2.high();
+
+        // Panic
+        m1.cover(i);
     }
 
     #[test]
     #[should_panic(
-        expected = "Cannot fit the specified value '128' into the specified bit width '7'. The value '128' requires a bit width of at least 8 bit(s)."
+        expected = "Attempted to add a cover point to module \"A\" with a condition signal with 2 bit(s), but cover point conditions are required to be 1 bit wide."
     )]
-    fn lit_value_cannot_bit_into_bit_width_error_1() {
+    fn cover_condition_bit_width_error() {
         let c = Context::new();
 
         let m = c.module("A");
 
         // Panic
-        let _ = m.lit(128u32, 7);
+        m.cover(m.lit(0u32, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot trace a signal from another module.")]
+    fn trace_separate_module_error() {
+        let c = Context::new();
+
+        let m1 = c.module("A");
+
+        let m2 = c.module("B");
+        let i = m2.high();
+
+        // Panic
+        m1.trace("a", i);
     }
 
     #[test]
     #[should_panic(
-        expected = "Cannot fit the specified value '128' into the specified bit width '2'. The value '128' requires a bit width of at least 8 bit(s)."
+        expected = "Cannot create a register with 0 bit(s). Signals must not be narrower than 1 bit(s)."
     )]
-    fn lit_value_cannot_bit_into_bit_width_error_2() {
+    fn reg_bit_width_lt_min_error() {
         let c = Context::new();
 
         let m = c.module("A");
 
         // Panic
-        let _ = m.lit(128u64, 2);
+        let _ = m.reg("r", 0);
     }
 
     #[test]
     #[should_panic(
-        expected = "Cannot fit the specified value '1023' into the specified bit width '4'. The value '1023' requires a bit width of at least 10 bit(s)."
+        expected = "Cannot create a register with 129 bit(s). Signals must not be wider than 128 bit(s)."
     )]
-    fn lit_value_cannot_bit_into_bit_width_error_3() {
+    fn reg_bit_width_gt_max_error() {
         let c = Context::new();
 
         let m = c.module("A");
 
         // Panic
-        let _ = m.lit(1023u128, 4);
+        let _ = m.reg("r", 129);
     }
 
     #[test]
     #[should_panic(
-        expected = "Cannot fit the specified value '65536' into the specified bit width '1'. The value '65536' requires a bit width of at least 17 bit(s)."
+        expected = "Attempted to create a synchronizer called \"sync\" in module \"B\" for a signal from another module."
     )]
-    fn lit_value_cannot_bit_into_bit_width_error_4() {
+    fn synchronizer_separate_module_error() {
         let c = Context::new();
 
-        let m = c.module("A");
+        let a = c.module("A");
+        let b = c.module("B");
 
         // Panic
-        let _ = m.lit(65536u32, 1);
+        let _ = b.synchronizer("sync", a.input("i", 1), 2);
     }
 
     #[test]
     #[should_panic(
-        expected = "Cannot create an input with 0 bit(s). Signals must not be narrower than 1 bit(s)."
+        expected = "Attempted to create a synchronizer called \"sync\" in module \"A\" for a signal with 2 bit(s), but synchronizers only support 1-bit signals."
     )]
-    fn input_width_lt_min_error() {
+    fn synchronizer_bit_width_error() {
         let c = Context::new();
 
         let m = c.module("A");
 
         // Panic
-        let _ = m.input("i", 0);
+        let _ = m.synchronizer("sync", m.input("i", 2), 2);
     }
 
     #[test]
     #[should_panic(
-        expected = "Cannot create an input with 129 bit(s). Signals must not be wider than 128 bit(s)."
+        expected = "Attempted to create a synchronizer called \"sync\" in module \"A\" with 1 stage(s), but synchronizers require at least 2 stages."
     )]
-    fn input_width_gt_max_error() {
+    fn synchronizer_stages_error() {
         let c = Context::new();
 
         let m = c.module("A");
 
         // Panic
-        let _ = m.input("i", 129);
+        let _ = m.synchronizer("sync", m.input("i", 1), 1);
     }
 
     #[test]
-    #[should_panic(expected = "Cannot output a signal from another module.")]
-    fn output_separate_module_error() {
+    fn synchronizer_registers() {
         let c = Context::new();
 
-        let m1 = c.module("A");
+        let m = c.module("A");
 
-        let m2 = c.module("B");
-        let i = m2.high();
+        let o = m.synchronizer("sync", m.input("i", 1), 3);
+
+        let registers = m.registers();
+        assert_eq!(registers.len(), 3);
+        assert!(o == registers[2]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to create a delay called \"d\" in module \"A\" for a signal from another module."
+    )]
+    fn delay_separate_module_error() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let b = c.module("B");
 
         // Panic
-        m1.output("a", i);
+        let _ = a.delay("d", b.input("i", 8), 2, 0u32);
     }
 
     #[test]
     #[should_panic(
-        expected = "Cannot create a register with 0 bit(s). Signals must not be narrower than 1 bit(s)."
+        expected = "Cannot fit the specified default value '256' into delay \"d\"'s bit width '8'. The value '256' requires a bit width of at least 9 bit(s)."
     )]
-    fn reg_bit_width_lt_min_error() {
+    fn delay_default_value_error() {
         let c = Context::new();
 
         let m = c.module("A");
 
         // Panic
-        let _ = m.reg("r", 0);
+        let _ = m.delay("d", m.input("i", 8), 0, 256u32);
     }
 
     #[test]
     #[should_panic(
-        expected = "Cannot create a register with 129 bit(s). Signals must not be wider than 128 bit(s)."
+        expected = "Attempted to create a delay called \"d\" in module \"A\" with an enable signal with 2 bit(s), but enable signals must be 1 bit wide."
     )]
-    fn reg_bit_width_gt_max_error() {
+    fn delay_enabled_enable_bit_width_error() {
         let c = Context::new();
 
         let m = c.module("A");
 
         // Panic
-        let _ = m.reg("r", 129);
+        let _ = m.delay_enabled("d", m.input("i", 8), 2, 0u32, m.input("en", 2));
+    }
+
+    #[test]
+    fn delay_zero_stages() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 8);
+
+        assert!(ptr::eq(m.delay("d", i, 0, 0u32), i));
+        assert!(ptr::eq(
+            m.delay_enabled("e", i, 0, 0u32, m.input("en", 1)),
+            i
+        ));
+        assert!(m.registers().is_empty());
     }
 
     #[test]
-    #[should_panic(expected = "Attempted to combine signals from different modules.")]
+    fn delay_registers() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        let o = m.delay("d", m.input("i", 8), 5, 0u32);
+
+        let registers = m.registers();
+        assert_eq!(registers.len(), 5);
+        assert!(o == registers[4]);
+        assert_eq!(o.bit_width(), 8);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to multiplex signals from different modules. The condition belongs to module \"A\", but the multiplexer is being created in module \"B\"."
+    )]
     fn mux_cond_separate_module_error() {
         let c = Context::new();
 
@@ -635,7 +2415,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Attempted to combine signals from different modules.")]
+    #[should_panic(
+        expected = "Attempted to multiplex signals from different modules. The \"when_true\" signal belongs to module \"A\", but the multiplexer is being created in module \"B\"."
+    )]
     fn mux_when_true_separate_module_error() {
         let c = Context::new();
 
@@ -651,7 +2433,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Attempted to combine signals from different modules.")]
+    #[should_panic(
+        expected = "Attempted to multiplex signals from different modules. The \"when_false\" signal belongs to module \"A\", but the multiplexer is being created in module \"B\"."
+    )]
     fn mux_when_false_separate_module_error() {
         let c = Context::new();
 
@@ -667,7 +2451,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Multiplexer conditionals can only be 1 bit wide.")]
+    #[should_panic(
+        expected = "Multiplexer conditionals can only be 1 bit wide, but the condition has 2 bit(s)."
+    )]
     fn mux_cond_bit_width_error() {
         let c = Context::new();
 
@@ -696,6 +2482,169 @@ mod tests {
         let _ = a.mux(l1, l2, l3);
     }
 
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a clock port name for module \"A\", but this module already has a clock port name specified."
+    )]
+    fn clock_port_name_already_specified_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        m.clock_port_name("i_clk");
+
+        // Panic
+        m.clock_port_name("clock");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify \"rst n\" as the reset port name for module \"A\", but port names must be valid Verilog identifiers."
+    )]
+    fn reset_port_name_invalid_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        m.reset_port_name("rst n");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to describe a port called \"b\" on module \"A\", but this module doesn't have an input or output with this name."
+    )]
+    fn port_description_nonexistent_port_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        m.output("o", m.input("a", 1));
+
+        // Panic
+        m.port_description("b", "Nope");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to describe a port called \"a\" on module \"A\", but this port already has a description."
+    )]
+    fn port_description_already_specified_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        m.output("o", m.input("a", 1));
+        m.port_description("a", "First");
+
+        // Panic
+        m.port_description("a", "Second");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drive an output called \"o\" in extern module \"Blackbox\", but extern modules don't have a body. Use `Module::extern_output` to declare its outputs instead."
+    )]
+    fn extern_module_output_error() {
+        let c = Context::new();
+
+        let m = c.extern_module("Blackbox");
+        let i = m.input("i", 1);
+
+        // Panic
+        m.output("o", i);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to create a register in extern module \"Blackbox\", but extern modules don't have a body."
+    )]
+    fn extern_module_reg_error() {
+        let c = Context::new();
+
+        let m = c.extern_module("Blackbox");
+
+        // Panic
+        let _ = m.reg("r", 1);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to declare an extern output called \"o\" in module \"A\", but this module isn't an extern module. Use `Module::output` instead."
+    )]
+    fn extern_output_non_extern_module_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        m.extern_output("o", 1);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create an output with 0 bit(s). Signals must not be narrower than 1 bit(s)."
+    )]
+    fn extern_output_width_lt_min_error() {
+        let c = Context::new();
+
+        let m = c.extern_module("Blackbox");
+
+        // Panic
+        m.extern_output("o", 0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to create an instance array called \"lane\" with 0 instances, but instance arrays must contain at least 1 instance."
+    )]
+    fn instance_array_empty_error() {
+        let c = Context::new();
+
+        let lane = c.module("Lane");
+        lane.output("o", lane.input("i", 1));
+
+        let m = c.module("Lanes");
+
+        // Panic
+        let _ = m.instance_array("lane", "Lane", 0);
+    }
+
+    #[test]
+    fn hierarchy_accessors() {
+        let c = Context::new();
+
+        let leaf = c.module("Leaf");
+        let r = leaf.reg("r", 4);
+        r.drive_next(leaf.input("i", 4));
+        leaf.output("o", r.value);
+
+        let top = c.module("Top");
+        let mem = top.mem("mem", 2, 4);
+        mem.initial_contents(&[0u32, 1, 2, 3]);
+        let a = top.instance("a", "Leaf");
+        a.drive_input("i", mem.read_port(top.input("addr", 2), top.high()));
+        let b = top.instance("b", "Leaf");
+        b.drive_input("i", a.output("o"));
+        top.output("o", b.output("o"));
+
+        assert_eq!(
+            top.instances()
+                .iter()
+                .map(|i| (i.name(), i.instantiated_module().name()))
+                .collect::<Vec<_>>(),
+            vec![("a", "Leaf"), ("b", "Leaf")]
+        );
+        assert!(ptr::eq(top.instances()[0].instantiated_module(), leaf));
+        assert_eq!(top.mems().len(), 1);
+        assert!(ptr::eq(top.mems()[0], mem));
+        assert!(top.registers().is_empty());
+        assert_eq!(top.inputs().keys().collect::<Vec<_>>(), vec!["addr"]);
+        assert_eq!(top.outputs().keys().collect::<Vec<_>>(), vec!["o"]);
+
+        assert_eq!(leaf.registers().len(), 1);
+        assert!(ptr::eq(leaf.registers()[0], r.value));
+        assert!(leaf.instances().is_empty());
+        assert!(leaf.mems().is_empty());
+    }
+
     #[test]
     #[should_panic(
         expected = "Attempted to instantiate a module identified by \"nope\", but no such module exists in this context."
@@ -760,4 +2709,42 @@ mod tests {
         // Panic
         let _ = m.mem("mem", 1, 129);
     }
+
+    #[test]
+    fn export_outputs() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("a", inner.input("i", 3));
+        inner.output("b", inner.high());
+
+        let outer = c.module("Outer");
+        let inner_inst = outer.instance("inner_inst", "Inner");
+        inner_inst.drive_input("i", outer.input("i", 3));
+        outer.export_outputs(inner_inst);
+
+        let outputs = outer.outputs();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs["a"].bit_width(), 3);
+        assert_eq!(outputs["b"].bit_width(), 1);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to export the outputs of instance \"inner_inst\" from module \"B\", but this instance belongs to module \"A\"."
+    )]
+    fn export_outputs_separate_module_error() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.high());
+
+        let a = c.module("A");
+        let inner_inst = a.instance("inner_inst", "Inner");
+
+        let b = c.module("B");
+
+        // Panic
+        b.export_outputs(inner_inst);
+    }
 }
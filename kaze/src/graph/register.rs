@@ -115,13 +115,62 @@ impl<'a> Register<'a> {
             panic!("Attempted to drive register \"{}\"'s next value with a signal from another module.", self.data.name);
         }
         if n.bit_width() != self.data.bit_width {
-            panic!("Attempted to drive register \"{}\"'s next value with a signal that has a different bit width than the register ({} and {}, respectively).", self.data.name, n.bit_width(), self.data.bit_width);
+            panic!("Attempted to drive register \"{}\"'s next value with a signal that has a different bit width than the register ({} and {}, respectively). The register was created at {}, and the signal was created at {}.", self.data.name, n.bit_width(), self.data.bit_width, self.value.location, n.location);
         }
         if self.data.next.borrow().is_some() {
             panic!("Attempted to drive register \"{}\"'s next value in module \"{}\", but this register's next value is already driven.", self.data.name, self.data.module.name);
         }
         *self.data.next.borrow_mut() = Some(n);
     }
+
+    /// Attaches a synthesis attribute called `key` with the given `value` to this `Register`.
+    ///
+    /// Attributes are passed through to synthesis tools to control how this `Register` is implemented; they're emitted as `(* key = "value" *)` immediately before this `Register`'s declaration in generated Verilog code, in the order they were specified.
+    /// They have no effect on the generated simulator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` isn't a valid Verilog identifier, or if this `Register` already has an attribute called `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let my_reg = m.reg("my_reg", 32);
+    /// my_reg.attribute("max_fanout", "16");
+    /// my_reg.attribute("keep", "true");
+    /// my_reg.drive_next(!my_reg.value);
+    /// m.output("my_output", my_reg.value);
+    /// ```
+    pub fn attribute<K: Into<String>, V: Into<String>>(&'a self, key: K, value: V) {
+        let key = key.into();
+        if !is_valid_attribute_key(&key) {
+            panic!("Attempted to attach an attribute called \"{}\" to register \"{}\" in module \"{}\", but attribute names must be valid Verilog identifiers.", key, self.data.name, self.data.module.name);
+        }
+        let mut attributes = self.data.attributes.borrow_mut();
+        if attributes
+            .iter()
+            .any(|(existing_key, _)| *existing_key == key)
+        {
+            panic!("Attempted to attach an attribute called \"{}\" to register \"{}\" in module \"{}\", but this register already has an attribute with this name.", key, self.data.name, self.data.module.name);
+        }
+        attributes.push((key, value.into()));
+    }
+}
+
+pub(super) fn is_valid_attribute_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        }
+        _ => false,
+    }
 }
 
 pub(crate) struct RegisterData<'a> {
@@ -131,6 +180,7 @@ pub(crate) struct RegisterData<'a> {
     pub initial_value: RefCell<Option<Constant>>,
     pub bit_width: u32,
     pub next: RefCell<Option<&'a Signal<'a>>>,
+    pub attributes: RefCell<Vec<(String, String)>>,
 }
 
 #[cfg(test)]
@@ -257,4 +307,34 @@ mod tests {
         // Panic
         r.drive_next(i);
     }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to attach an attribute called \"max fanout\" to register \"r\" in module \"A\", but attribute names must be valid Verilog identifiers."
+    )]
+    fn attribute_invalid_key_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let r = m.reg("r", 32);
+
+        // Panic
+        r.attribute("max fanout", "16");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to attach an attribute called \"keep\" to register \"r\" in module \"A\", but this register already has an attribute with this name."
+    )]
+    fn attribute_already_specified_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let r = m.reg("r", 32);
+
+        r.attribute("keep", "true");
+
+        // Panic
+        r.attribute("keep", "false");
+    }
 }
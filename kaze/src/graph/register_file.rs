@@ -0,0 +1,364 @@
+use super::module::*;
+use super::register::*;
+use super::signal::*;
+
+use std::cell::RefCell;
+use std::panic::Location;
+use std::ptr;
+
+/// A register file built from an array of [`Register`]s, created by the [`Module`]::[`register_file`] method.
+///
+/// A `RegisterFile` has a single write port (see [`write_port`]) and any number of combinational read ports (see [`read_port`] and [`read_port_bypassed`]).
+/// Writes are synchronous: a value written on one cycle is visible to all read ports on the following cycle.
+/// Each read port is a balanced tree of 2:1 muxes over the registers, so unlike a [`Mem`], any depth is supported and reads don't need to be registered.
+///
+/// The write port must be specified exactly once; otherwise the underlying registers are never driven, which is reported as an error when the containing [`Module`] is compiled.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+///
+/// let regs = m.register_file("regs", 32, 32);
+/// regs.write_port(m.input("rd", 5), m.input("rd_data", 32), m.input("rd_enable", 1));
+/// m.output("rs1_data", regs.read_port(m.input("rs1", 5)));
+/// m.output("rs2_data", regs.read_port_bypassed(m.input("rs2", 5)));
+/// ```
+///
+/// [`Mem`]: ./struct.Mem.html
+/// [`Module`]: ./struct.Module.html
+/// [`read_port`]: #method.read_port
+/// [`read_port_bypassed`]: #method.read_port_bypassed
+/// [`Register`]: ./struct.Register.html
+/// [`register_file`]: ./struct.Module.html#method.register_file
+/// [`write_port`]: #method.write_port
+#[must_use]
+pub struct RegisterFile<'a> {
+    pub(super) module: &'a Module<'a>,
+    pub(super) location: &'static Location<'static>,
+
+    pub(super) name: String,
+    pub(super) address_bit_width: u32,
+    pub(super) element_bit_width: u32,
+
+    pub(super) registers: Vec<&'a Register<'a>>,
+    pub(super) write_port: RefCell<Option<(&'a Signal<'a>, &'a Signal<'a>, &'a Signal<'a>)>>,
+}
+
+impl<'a> RegisterFile<'a> {
+    /// Returns this `RegisterFile`'s name, as specified when it was created by the [`Module`]::[`register_file`] method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    /// let regs = m.register_file("regs", 5, 8);
+    ///
+    /// assert_eq!(regs.name(), "regs");
+    /// assert_eq!(regs.depth(), 5);
+    /// assert_eq!(regs.address_bit_width(), 3);
+    /// assert_eq!(regs.element_bit_width(), 8);
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`register_file`]: ./struct.Module.html#method.register_file
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of elements in this `RegisterFile`.
+    pub fn depth(&self) -> u32 {
+        self.registers.len() as u32
+    }
+
+    /// Returns the number of address bits of this `RegisterFile`.
+    pub fn address_bit_width(&self) -> u32 {
+        self.address_bit_width
+    }
+
+    /// Returns the number of bits in each element of this `RegisterFile`.
+    pub fn element_bit_width(&self) -> u32 {
+        self.element_bit_width
+    }
+
+    /// Specifies the write port for this `RegisterFile`.
+    ///
+    /// When `enable` is asserted, `value` is written to the element at `address`, and is visible to all read ports on the following cycle.
+    /// Writes to addresses greater than or equal to this `RegisterFile`'s depth have no effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `RegisterFile` already has a write port specified, if any of the signals belong to a different [`Module`] than this `RegisterFile`, if `address`'s bit width doesn't match this `RegisterFile`'s address bit width, if `value`'s bit width doesn't match this `RegisterFile`'s element bit width, or if `enable`'s bit width is not 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let regs = m.register_file("regs", 4, 16);
+    /// regs.write_port(m.input("write_addr", 2), m.input("write_value", 16), m.input("write_enable", 1));
+    /// m.output("read_data", regs.read_port(m.input("read_addr", 2)));
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    #[track_caller]
+    pub fn write_port(
+        &'a self,
+        address: &'a Signal<'a>,
+        value: &'a Signal<'a>,
+        enable: &'a Signal<'a>,
+    ) {
+        if self.write_port.borrow().is_some() {
+            panic!("Attempted to specify a write port for register file \"{}\" in module \"{}\", but it already has one. The register file was created at {}.", self.name, self.module.name, self.location);
+        }
+        self.check_address("write port", address);
+        if value.bit_width() != self.element_bit_width {
+            panic!("Attempted to specify a write port for register file \"{}\" in module \"{}\" with a value signal with {} bit(s), but this register file has {} element bit(s). The register file was created at {}, and the value signal was created at {}.", self.name, self.module.name, value.bit_width(), self.element_bit_width, self.location, value.location);
+        }
+        if enable.bit_width() != 1 {
+            panic!("Attempted to specify a write port for register file \"{}\" in module \"{}\" with an enable signal with {} bit(s), but register file write enables are required to be 1 bit wide. The register file was created at {}, and the enable signal was created at {}.", self.name, self.module.name, enable.bit_width(), self.location, enable.location);
+        }
+
+        for (index, register) in self.registers.iter().enumerate() {
+            let selected =
+                enable & address.eq(self.module.lit(index as u32, self.address_bit_width));
+            register.drive_next(self.module.mux(selected, value, register.value));
+        }
+
+        *self.write_port.borrow_mut() = Some((address, value, enable));
+    }
+
+    /// Creates a combinational read port for this `RegisterFile` and returns the element at `address`.
+    ///
+    /// The returned value reflects writes from previous cycles only; a write to `address` on the same cycle is visible on the following cycle.
+    /// Use [`read_port_bypassed`] to see same-cycle writes instead.
+    /// Reading an address greater than or equal to this `RegisterFile`'s depth returns an unspecified element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `address` belongs to a different [`Module`] than this `RegisterFile`, or if its bit width doesn't match this `RegisterFile`'s address bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let regs = m.register_file("regs", 8, 32);
+    /// regs.write_port(m.input("write_addr", 3), m.input("write_value", 32), m.input("write_enable", 1));
+    /// m.output("a", regs.read_port(m.input("a_addr", 3)));
+    /// m.output("b", regs.read_port(m.input("b_addr", 3)));
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`read_port_bypassed`]: #method.read_port_bypassed
+    #[track_caller]
+    pub fn read_port(&'a self, address: &'a Signal<'a>) -> &'a Signal<'a> {
+        self.check_address("read port", address);
+
+        let mut elements = Vec::new();
+        for register in self.registers.iter() {
+            elements.push(register.value);
+        }
+        self.module.mux_index(&elements, address)
+    }
+
+    /// Creates a combinational read port for this `RegisterFile` that bypasses the write port, and returns the element at `address`.
+    ///
+    /// This behaves like [`read_port`], except that if the write port writes to `address` on the same cycle, the value being written is returned instead of the element's previous value.
+    /// This is typically used to forward a result to an instruction that reads it in the same cycle it's written back.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`read_port`], or if this `RegisterFile` doesn't have a write port specified yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let regs = m.register_file("regs", 8, 32);
+    /// // The write port must be specified first
+    /// regs.write_port(m.input("write_addr", 3), m.input("write_value", 32), m.input("write_enable", 1));
+    /// m.output("a", regs.read_port_bypassed(m.input("a_addr", 3)));
+    /// ```
+    ///
+    /// [`read_port`]: #method.read_port
+    #[track_caller]
+    pub fn read_port_bypassed(&'a self, address: &'a Signal<'a>) -> &'a Signal<'a> {
+        let (write_address, write_value, write_enable) = match *self.write_port.borrow() {
+            Some(write_port) => write_port,
+            _ => panic!("Attempted to create a bypassed read port for register file \"{}\" in module \"{}\", but it doesn't have a write port yet. The write port must be specified before any bypassed read ports. The register file was created at {}.", self.name, self.module.name, self.location),
+        };
+        let read_value = self.read_port(address);
+        self.module.mux(
+            write_enable & write_address.eq(address),
+            write_value,
+            read_value,
+        )
+    }
+
+    #[track_caller]
+    fn check_address(&self, port_kind: &str, address: &'a Signal<'a>) {
+        if !ptr::eq(self.module, address.module) {
+            panic!("Attempted to specify a {} for register file \"{}\" in module \"{}\" with an address signal from module \"{}\". The register file was created at {}, and the address signal was created at {}.", port_kind, self.name, self.module.name, address.module.name, self.location, address.location);
+        }
+        if address.bit_width() != self.address_bit_width {
+            panic!("Attempted to specify a {} for register file \"{}\" in module \"{}\" with an address signal with {} bit(s), but this register file has {} address bit(s). The register file was created at {}, and the address signal was created at {}.", port_kind, self.name, self.module.name, address.bit_width(), self.address_bit_width, self.location, address.location);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot create a register file called \"regs\" with a depth of 0. Register files must hold at least 1 element."
+    )]
+    fn zero_depth_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.register_file("regs", 0, 32);
+    }
+
+    #[test]
+    fn address_bit_widths() {
+        for &(depth, address_bit_width) in
+            &[(1, 1), (2, 1), (3, 2), (4, 2), (5, 3), (32, 5), (33, 6)]
+        {
+            let c = Context::new();
+
+            let m = c.module("A");
+            let regs = m.register_file("regs", depth, 8);
+
+            assert_eq!(regs.depth(), depth);
+            assert_eq!(regs.address_bit_width(), address_bit_width);
+            assert_eq!(m.registers().len(), depth as usize);
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a write port for register file \"regs\" in module \"A\", but it already has one."
+    )]
+    fn write_port_already_specified_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let regs = m.register_file("regs", 4, 8);
+
+        regs.write_port(m.lit(0u32, 2), m.lit(0u32, 8), m.high());
+
+        // Panic
+        regs.write_port(m.lit(1u32, 2), m.lit(0u32, 8), m.high());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a write port for register file \"regs\" in module \"A\" with an address signal with 3 bit(s), but this register file has 2 address bit(s)."
+    )]
+    fn write_port_address_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let regs = m.register_file("regs", 4, 8);
+
+        // Panic
+        regs.write_port(m.lit(0u32, 3), m.lit(0u32, 8), m.high());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a write port for register file \"regs\" in module \"A\" with a value signal with 7 bit(s), but this register file has 8 element bit(s)."
+    )]
+    fn write_port_value_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let regs = m.register_file("regs", 4, 8);
+
+        // Panic
+        regs.write_port(m.lit(0u32, 2), m.lit(0u32, 7), m.high());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a write port for register file \"regs\" in module \"A\" with an enable signal with 2 bit(s), but register file write enables are required to be 1 bit wide."
+    )]
+    fn write_port_enable_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let regs = m.register_file("regs", 4, 8);
+
+        // Panic
+        regs.write_port(m.lit(0u32, 2), m.lit(0u32, 8), m.lit(0u32, 2));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a read port for register file \"regs\" in module \"A\" with an address signal from module \"B\"."
+    )]
+    fn read_port_separate_module_error() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let b = c.module("B");
+        let regs = a.register_file("regs", 4, 8);
+
+        // Panic
+        let _ = regs.read_port(b.lit(0u32, 2));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to specify a read port for register file \"regs\" in module \"A\" with an address signal with 1 bit(s), but this register file has 2 address bit(s)."
+    )]
+    fn read_port_address_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let regs = m.register_file("regs", 4, 8);
+
+        // Panic
+        let _ = regs.read_port(m.low());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to create a bypassed read port for register file \"regs\" in module \"A\", but it doesn't have a write port yet."
+    )]
+    fn read_port_bypassed_without_write_port_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let regs = m.register_file("regs", 4, 8);
+
+        // Panic
+        let _ = regs.read_port_bypassed(m.lit(0u32, 2));
+    }
+}
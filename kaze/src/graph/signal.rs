@@ -1,12 +1,15 @@
 use super::constant::*;
-use super::context::*;
+use super::error::*;
 use super::instance::*;
+use super::latch::*;
 use super::mem::*;
 use super::module::*;
 use super::register::*;
 
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::ops::{Add, BitAnd, BitOr, BitXor, Not, Shl, Shr, Sub};
+use std::panic::Location;
 use std::ptr;
 
 /// The minimum allowed bit width for any given [`Signal`].
@@ -28,6 +31,8 @@ pub const MAX_SIGNAL_BIT_WIDTH: u32 = 128;
 ///
 /// A `Signal` behaves similarly to a `wire` in Verilog, except that it's always driven.
 ///
+/// `Signal`s are structurally shared: creating a `Signal` that's identical to an existing one in the same [`Module`] (for example, the same literal, or the same operation applied to the same operand `Signal`s) returns the existing `Signal` instead of a new one, so duplicated expressions don't result in duplicated logic. Inputs, [`Register`] values, and [`Mem`] read port outputs are never shared this way.
+///
 /// # Examples
 ///
 /// ```
@@ -45,11 +50,14 @@ pub const MAX_SIGNAL_BIT_WIDTH: u32 = 128;
 ///
 /// [`concat`]: #method.concat
 /// [`lit`]: ./struct.Module.html#method.lit
+/// [`Mem`]: ./struct.Mem.html
 /// [`Module`]: ./struct.Module.html
+/// [`Register`]: ./struct.Register.html
 #[must_use]
 pub struct Signal<'a> {
-    pub(super) context: &'a Context<'a>,
-    pub(super) module: &'a Module<'a>,
+    pub(crate) module: &'a Module<'a>,
+    pub(crate) location: &'static Location<'static>,
+    pub(super) bit_width: u32,
 
     pub(crate) data: SignalData<'a>,
 }
@@ -90,35 +98,17 @@ impl<'a> Signal<'a> {
     /// ```
     #[must_use]
     pub fn bit_width(&self) -> u32 {
-        match &self.data {
-            SignalData::Lit { bit_width, .. } => *bit_width,
-            SignalData::Input { bit_width, .. } => *bit_width,
-            SignalData::Reg { data } => data.bit_width,
-            SignalData::UnOp { source, .. } => source.bit_width(),
-            SignalData::SimpleBinOp { lhs, .. } => lhs.bit_width(),
-            SignalData::AdditiveBinOp { lhs, .. } => lhs.bit_width(),
-            SignalData::ComparisonBinOp { .. } => 1,
-            SignalData::ShiftBinOp { lhs, .. } => lhs.bit_width(),
-            SignalData::Bits {
-                range_high,
-                range_low,
-                ..
-            } => range_high - range_low + 1,
-            SignalData::Repeat { source, count } => source.bit_width() * count,
-            SignalData::Concat { lhs, rhs } => lhs.bit_width() + rhs.bit_width(),
-            SignalData::Mux { when_true, .. } => when_true.bit_width(),
-            SignalData::InstanceOutput { instance, name } => {
-                instance.instantiated_module.outputs.borrow()[name].bit_width()
-            }
-            SignalData::MemReadPortOutput { mem, .. } => mem.element_bit_width,
-        }
+        self.bit_width
     }
 
-    /// Creates a `Signal` that represents the value of the single bit of this `Signal` at index `index`, where `index` equal to `0` represents this `Signal`'s least significant bit.
+    /// Gives this `Signal` a `name` which tools can use to refer to it, and returns this `Signal`.
+    ///
+    /// Naming a `Signal` doesn't affect the behavior of the design.
+    /// Named `Signal`s can be [probed](sim/struct.GenerationOptions.html#structfield.probes) in the generated Rust simulator, even if they're part of an instantiated [`Module`].
     ///
     /// # Panics
     ///
-    /// Panics if `index` is greater than or equal to this `Signal`'s `bit_width`.
+    /// Panics if this `Signal`'s [`Module`] already contains a `Signal` with the same `name`.
     ///
     /// # Examples
     ///
@@ -129,33 +119,32 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit = m.lit(0b0110u32, 4);
-    /// let bit_0 = lit.bit(0); // Represents 0
-    /// let bit_1 = lit.bit(1); // Represents 1
-    /// let bit_2 = lit.bit(2); // Represents 1
-    /// let bit_3 = lit.bit(3); // Represents 0
+    /// let sum = (m.input("a", 8) + m.input("b", 8)).name("sum");
+    /// m.output("o", sum.bit(7));
     /// ```
-    pub fn bit(&'a self, index: u32) -> &Signal<'a> {
-        if index >= self.bit_width() {
-            panic!("Attempted to take bit index {} from a signal with a width of {} bits. Bit indices must be in the range [0, {}] for a signal with a width of {} bits.", index, self.bit_width(), self.bit_width() - 1, self.bit_width());
+    ///
+    /// [`Module`]: ./struct.Module.html
+    pub fn name<S: Into<String>>(&'a self, name: S) -> &Signal<'a> {
+        let name = name.into();
+        let mut named_signals = self.module.named_signals.borrow_mut();
+        if named_signals.contains_key(&name) {
+            panic!(
+                "Attempted to name a signal \"{}\" in module \"{}\", but this module already contains a signal with this name.",
+                name, self.module.name
+            );
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::Bits {
-                source: self,
-                range_high: index,
-                range_low: index,
-            },
-        })
+        named_signals.insert(name, self);
+        self
     }
 
-    /// Creates a `Signal` that represents a contiguous subset of the bits of this `Signal`, starting at `range_low` as the least significant bit and ending at `range_high` as the most significant bit, inclusive.
+    /// Returns the `Signal`s that this `Signal` directly depends on, in a stable order.
     ///
-    /// # Panics
+    /// Dependencies never cross [`Module`] boundaries:
+    /// - A [`Register`]'s value depends on the `Signal` driving its next value (if any).
+    /// - A [`Mem`] read port's output depends on its address and enable `Signal`s, as well as the address, value, enable, and mask `Signal`s of each of the [`Mem`]'s write ports, in that order.
+    /// - An [`Instance`] output depends on all of the [`Instance`]'s driven inputs, in input name order, since the instantiated [`Module`] may connect any of them to the output.
     ///
-    /// Panics if either `range_low` or `range_high` is greater than or equal to the bit width of this `Signal`, or if `range_low` is greater than `range_high`.
+    /// Note that because [`Register`]s depend on their next values, following dependencies transitively may revisit the same `Signal`; use [`visit`] for a traversal that handles this.
     ///
     /// # Examples
     ///
@@ -166,40 +155,71 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit = m.lit(0b0110u32, 4);
-    /// let bits_210 = lit.bits(2, 0); // Represents 0b110
-    /// let bits_321 = lit.bits(3, 1); // Represents 0b011
-    /// let bits_10 = lit.bits(1, 0); // Represents 0b10
-    /// let bits_32 = lit.bits(3, 2); // Represents 0b01
-    /// let bits_2 = lit.bits(2, 2); // Represents 1, equivalent to lit.bit(2)
+    /// let a = m.input("a", 8);
+    /// let b = m.input("b", 8);
+    /// let sum = a + b;
+    ///
+    /// assert!(sum.dependencies() == vec![a, b]);
+    /// assert!(a.dependencies().is_empty());
     /// ```
-    pub fn bits(&'a self, range_high: u32, range_low: u32) -> &Signal<'a> {
-        if range_low >= self.bit_width() {
-            panic!("Cannot specify a range of bits where the lower bound is greater than or equal to the number of bits in the source signal. The bounds must be in the range [0, {}] for a signal with a width of {} bits, but a lower bound of {} was given.", self.bit_width() - 1, self.bit_width(), range_low);
-        }
-        if range_high >= self.bit_width() {
-            panic!("Cannot specify a range of bits where the upper bound is greater than or equal to the number of bits in the source signal. The bounds must be in the range [0, {}] for a signal with a width of {} bits, but an upper bound of {} was given.", self.bit_width() - 1, self.bit_width(), range_high);
-        }
-        if range_low > range_high {
-            panic!("Cannot specify a range of bits where the lower bound is greater than the upper bound.");
-        }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
+    ///
+    /// [`Instance`]: ./struct.Instance.html
+    /// [`Mem`]: ./struct.Mem.html
+    /// [`Module`]: ./struct.Module.html
+    /// [`Register`]: ./struct.Register.html
+    /// [`visit`]: #method.visit
+    pub fn dependencies(&'a self) -> Vec<&'a Signal<'a>> {
+        match self.data {
+            SignalData::Lit { .. } | SignalData::Input { .. } => Vec::new(),
+
+            SignalData::Reg { data } => data.next.borrow().iter().copied().collect(),
+            SignalData::Latch { data } => data
+                .data
+                .borrow()
+                .iter()
+                .chain(data.enable.borrow().iter())
+                .copied()
+                .collect(),
+
+            SignalData::UnOp { source, .. }
+            | SignalData::Bits { source, .. }
+            | SignalData::Repeat { source, .. } => vec![source],
+            SignalData::SimpleBinOp { lhs, rhs, .. }
+            | SignalData::AdditiveBinOp { lhs, rhs, .. }
+            | SignalData::ComparisonBinOp { lhs, rhs, .. }
+            | SignalData::ShiftBinOp { lhs, rhs, .. }
+            | SignalData::Concat { lhs, rhs } => vec![lhs, rhs],
+
+            SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            } => vec![cond, when_true, when_false],
+
+            SignalData::InstanceOutput { instance, .. } => {
+                instance.driven_inputs.borrow().values().copied().collect()
+            }
 
-            data: SignalData::Bits {
-                source: self,
-                range_high,
-                range_low,
-            },
-        })
+            SignalData::MemReadPortOutput {
+                mem,
+                address,
+                enable,
+            } => {
+                let mut ret = vec![address, enable];
+                mem.write_port_dependencies(&mut ret);
+                ret
+            }
+            SignalData::MemCombReadPortOutput { mem, address } => {
+                let mut ret = vec![address];
+                mem.write_port_dependencies(&mut ret);
+                ret
+            }
+        }
     }
 
-    /// Creates a `Signal` that represents this `Signal` repeated `count` times.
-    ///
-    /// # Panics
+    /// Calls `f` once for this `Signal` and once for each `Signal` it transitively depends on (as defined by [`dependencies`]).
     ///
-    /// Panics if `self.bit_width() * count` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`].
+    /// Each `Signal` is visited exactly once, even if it's reachable via multiple paths or via a cycle through a [`Register`]. `Signal`s are identified by reference, so two structurally-identical `Signal`s are visited separately. `self` is always visited first, and the visiting order is otherwise unspecified but deterministic.
     ///
     /// # Examples
     ///
@@ -210,41 +230,43 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit = m.lit(0xau32, 4);
-    /// let repeat_1 = lit.repeat(1); // Equivalent to just lit
-    /// let repeat_2 = lit.repeat(2); // Equivalent to 8-bit lit with value 0xaa
-    /// let repeat_5 = lit.repeat(5); // Equivalent to 20-bit lit with value 0xaaaaa
-    /// let repeat_8 = lit.repeat(8); // Equivalent to 32-bit lit with value 0xaaaaaaaa
+    /// let counter = m.reg("counter", 8);
+    /// counter.default_value(0u32);
+    /// counter.drive_next(counter.value + m.lit(1u32, 8));
+    ///
+    /// // counter.value, counter.value + 1, and the literal 1
+    /// let mut count = 0;
+    /// counter.value.visit(&mut |_| count += 1);
+    /// assert_eq!(count, 3);
     /// ```
     ///
-    /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
-    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
-    pub fn repeat(&'a self, count: u32) -> &Signal<'a> {
-        let target_bit_width = self.bit_width() * count;
-        if target_bit_width < MIN_SIGNAL_BIT_WIDTH {
-            panic!("Attempted to repeat a {}-bit signal {} times, but this would result in a bit width of {}, which is less than the minimal signal bit width of {} bit(s).", self.bit_width(), count, target_bit_width, MIN_SIGNAL_BIT_WIDTH);
-        }
-        if target_bit_width > MAX_SIGNAL_BIT_WIDTH {
-            panic!("Attempted to repeat a {}-bit signal {} times, but this would result in a bit width of {}, which is greater than the maximum signal bit width of {} bit(s).", self.bit_width(), count, target_bit_width, MAX_SIGNAL_BIT_WIDTH);
+    /// [`dependencies`]: #method.dependencies
+    /// [`Register`]: ./struct.Register.html
+    pub fn visit(&'a self, f: &mut impl FnMut(&'a Signal<'a>)) {
+        self.visit_unvisited(&mut HashSet::new(), f);
+    }
+
+    pub(crate) fn visit_unvisited(
+        &'a self,
+        visited: &mut HashSet<&'a Signal<'a>>,
+        f: &mut impl FnMut(&'a Signal<'a>),
+    ) {
+        // An explicit stack is used instead of recursion so that very deep graphs can't overflow the call stack
+        let mut stack = vec![self];
+        while let Some(signal) = stack.pop() {
+            if !visited.insert(signal) {
+                continue;
+            }
+            f(signal);
+            stack.extend(signal.dependencies().into_iter().rev());
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::Repeat {
-                source: self,
-                count,
-            },
-        })
     }
 
-    /// Creates a `Signal` that represents this `Signal` concatenated with `rhs`.
-    ///
-    /// `self` represents the upper bits in the resulting `Signal`, and `rhs` represents the lower bits.
+    /// Creates a `Signal` that represents the value of the single bit of this `Signal` at index `index`, where `index` equal to `0` represents this `Signal`'s least significant bit.
     ///
     /// # Panics
     ///
-    /// Panics if `self.bit_width() + rhs.bit_width()` is greater than [`MAX_SIGNAL_BIT_WIDTH`].
+    /// Panics if `index` is greater than or equal to this `Signal`'s `bit_width`.
     ///
     /// # Examples
     ///
@@ -255,35 +277,21 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xffu32, 8);
-    /// let concat_1 = lit_a.concat(lit_b); // Equivalent to 12-bit lit with value 0xaff
-    /// let concat_2 = lit_b.concat(lit_a); // Equivalent to 12-bit lit with value 0xffa
-    /// let concat_3 = lit_a.concat(lit_a); // Equivalent to 8-bit lit with value 0xaa
+    /// let lit = m.lit(0b0110u32, 4);
+    /// let bit_0 = lit.bit(0); // Represents 0
+    /// let bit_1 = lit.bit(1); // Represents 1
+    /// let bit_2 = lit.bit(2); // Represents 1
+    /// let bit_3 = lit.bit(3); // Represents 0
     /// ```
-    ///
-    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
-    pub fn concat(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
-        }
-        let target_bit_width = self.bit_width() + rhs.bit_width();
-        if target_bit_width > MAX_SIGNAL_BIT_WIDTH {
-            panic!("Attempted to concatenate signals with {} bit(s) and {} bit(s) respectively, but this would result in a bit width of {}, which is greater than the maximum signal bit width of {} bit(s).", self.bit_width(), rhs.bit_width(), target_bit_width, MAX_SIGNAL_BIT_WIDTH);
+    #[track_caller]
+    pub fn bit(&'a self, index: u32) -> &Signal<'a> {
+        match self.try_bit(index) {
+            Ok(bit) => bit,
+            Err(error) => panic!("{}", error),
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::Concat { lhs: self, rhs },
-        })
     }
 
-    /// Creates a `Signal` that represents the single-bit result of a bitwise boolean equality comparison between `self` and `rhs`.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `self` and `rhs` belong to different [`Module`]s, or if the bit widths of `self` and `rhs` aren't equal.
+    /// Non-panicking variant of [`bit`], which returns a [`GraphError`] instead of panicking.
     ///
     /// # Examples
     ///
@@ -294,43 +302,42 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xbu32, 4);
-    /// let eq_1 = lit_a.eq(lit_a); // Equivalent to m.high()
-    /// let eq_2 = lit_b.eq(lit_b); // Equivalent to m.high()
-    /// let eq_3 = lit_a.eq(lit_b); // Equivalent to m.low()
-    /// let eq_4 = lit_b.eq(lit_a); // Equivalent to m.low()
+    /// let lit = m.lit(0b0110u32, 4);
+    /// assert!(lit.try_bit(3).is_ok());
+    /// assert!(lit.try_bit(4).is_err());
     /// ```
     ///
-    /// [`Module`]: ./struct.Module.html
-    pub fn eq(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
-        }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+    /// [`bit`]: #method.bit
+    /// [`GraphError`]: ./enum.GraphError.html
+    #[track_caller]
+    pub fn try_bit(&'a self, index: u32) -> Result<&'a Signal<'a>, GraphError> {
+        if index >= self.bit_width() {
+            return Err(GraphError::BitIndexOutOfRange {
+                index,
+                bit_width: self.bit_width(),
+                location: self.location,
+            });
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::ComparisonBinOp {
-                lhs: self,
-                rhs,
-                op: ComparisonBinOp::Equal,
+        Ok(self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::Bits {
+                source: self,
+                range_high: index,
+                range_low: index,
             },
-        })
+        ))
     }
 
-    /// Creates a `Signal` that represents the single-bit result of a bitwise boolean inequality comparison between `self` and `rhs`.
+    /// Creates a `Signal` that represents the value of the single bit of this `Signal` selected by the runtime value of `index`, where `index` equal to `0` selects this `Signal`'s least significant bit.
+    ///
+    /// `index` may have any bit width. If `index` is greater than or equal to this `Signal`'s `bit_width`, the resulting value is `0`.
+    ///
+    /// This is equivalent to `(self >> index).bit(0)`, and is built that way, so no new logic is required in generated code.
     ///
     /// # Panics
     ///
-    /// Panics if `self` and `rhs` belong to different [`Module`]s, or if the bit widths of `self` and `rhs` aren't equal.
+    /// Panics if `index` belongs to a different [`Module`] than `self`.
     ///
     /// # Examples
     ///
@@ -341,43 +348,29 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xbu32, 4);
-    /// let ne_1 = lit_a.ne(lit_a); // Equivalent to m.low()
-    /// let ne_2 = lit_b.ne(lit_b); // Equivalent to m.low()
-    /// let ne_3 = lit_a.ne(lit_b); // Equivalent to m.high()
-    /// let ne_4 = lit_b.ne(lit_a); // Equivalent to m.high()
+    /// let status = m.input("status", 8);
+    /// let lane = m.input("lane", 3);
+    /// m.output("lane_ready", status.bit_dyn(lane));
     /// ```
     ///
     /// [`Module`]: ./struct.Module.html
-    pub fn ne(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
+    #[track_caller]
+    pub fn bit_dyn(&'a self, index: &'a Signal<'a>) -> &'a Signal<'a> {
+        if !ptr::eq(self.module, index.module) {
             panic!("Attempted to combine signals from different modules.");
         }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
-        }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::ComparisonBinOp {
-                lhs: self,
-                rhs,
-                op: ComparisonBinOp::NotEqual,
-            },
-        })
+        (self >> index).bit(0)
     }
 
-    /// Creates a `Signal` that represents the single-bit result of an unsigned `<` comparison between `self` and `rhs`.
+    /// Creates a `Signal` that represents `bit_width` contiguous bits of this `Signal`, starting at the bit selected by the runtime value of `offset` as the least significant bit.
+    ///
+    /// `offset` may have any bit width. Bits that would come from above the top of this `Signal` (because `offset + bit_width` is greater than this `Signal`'s `bit_width`) are `0`.
+    ///
+    /// This is equivalent to `(self >> offset).bits(bit_width - 1, 0)`, and is built that way, so no new logic is required in generated code.
     ///
     /// # Panics
     ///
-    /// Panics if `self` and `rhs` belong to different [`Module`]s, or if the bit widths of `self` and `rhs` aren't equal.
+    /// Panics if `offset` belongs to a different [`Module`] than `self`, or if `bit_width` is 0 or greater than this `Signal`'s `bit_width`.
     ///
     /// # Examples
     ///
@@ -388,43 +381,33 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xbu32, 4);
-    /// let lt_1 = lit_a.lt(lit_a); // Equivalent to m.low()
-    /// let lt_2 = lit_b.lt(lit_b); // Equivalent to m.low()
-    /// let lt_3 = lit_a.lt(lit_b); // Equivalent to m.high()
-    /// let lt_4 = lit_b.lt(lit_a); // Equivalent to m.low()
+    /// let word = m.input("word", 64);
+    /// let field_offset = m.input("field_offset", 6);
+    /// m.output("field", word.bits_dyn(field_offset, 12));
     /// ```
     ///
     /// [`Module`]: ./struct.Module.html
-    pub fn lt(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
+    #[track_caller]
+    pub fn bits_dyn(&'a self, offset: &'a Signal<'a>, bit_width: u32) -> &'a Signal<'a> {
+        if !ptr::eq(self.module, offset.module) {
             panic!("Attempted to combine signals from different modules.");
         }
-        if self.bit_width() != rhs.bit_width() {
+        if bit_width == 0 || bit_width > self.bit_width() {
             panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
+                "Attempted to take {} bit(s) at a dynamic offset from a signal with {} bit(s), but at least 1 bit and at most the signal's bit width must be taken. The signal was created at {}.",
+                bit_width,
                 self.bit_width(),
-                rhs.bit_width()
+                self.location
             );
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::ComparisonBinOp {
-                lhs: self,
-                rhs,
-                op: ComparisonBinOp::LessThan,
-            },
-        })
+        (self >> offset).bits(bit_width - 1, 0)
     }
 
-    /// Creates a `Signal` that represents the single-bit result of an unsigned `<=` comparison between `self` and `rhs`.
+    /// Creates a `Signal` that represents a contiguous subset of the bits of this `Signal`, starting at `range_low` as the least significant bit and ending at `range_high` as the most significant bit, inclusive.
     ///
     /// # Panics
     ///
-    /// Panics if `self` and `rhs` belong to different [`Module`]s, or if the bit widths of `self` and `rhs` aren't equal.
+    /// Panics if either `range_low` or `range_high` is greater than or equal to the bit width of this `Signal`, or if `range_low` is greater than `range_high`.
     ///
     /// # Examples
     ///
@@ -435,43 +418,22 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xbu32, 4);
-    /// let le_1 = lit_a.le(lit_a); // Equivalent to m.high()
-    /// let le_2 = lit_b.le(lit_b); // Equivalent to m.high()
-    /// let le_3 = lit_a.le(lit_b); // Equivalent to m.high()
-    /// let le_4 = lit_b.le(lit_a); // Equivalent to m.low()
+    /// let lit = m.lit(0b0110u32, 4);
+    /// let bits_210 = lit.bits(2, 0); // Represents 0b110
+    /// let bits_321 = lit.bits(3, 1); // Represents 0b011
+    /// let bits_10 = lit.bits(1, 0); // Represents 0b10
+    /// let bits_32 = lit.bits(3, 2); // Represents 0b01
+    /// let bits_2 = lit.bits(2, 2); // Represents 1, equivalent to lit.bit(2)
     /// ```
-    ///
-    /// [`Module`]: ./struct.Module.html
-    pub fn le(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
-        }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+    #[track_caller]
+    pub fn bits(&'a self, range_high: u32, range_low: u32) -> &Signal<'a> {
+        match self.try_bits(range_high, range_low) {
+            Ok(bits) => bits,
+            Err(error) => panic!("{}", error),
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::ComparisonBinOp {
-                lhs: self,
-                rhs,
-                op: ComparisonBinOp::LessThanEqual,
-            },
-        })
     }
 
-    /// Creates a `Signal` that represents the single-bit result of an unsigned `>` comparison between `self` and `rhs`.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `self` and `rhs` belong to different [`Module`]s, or if the bit widths of `self` and `rhs` aren't equal.
+    /// Non-panicking variant of [`bits`], which returns a [`GraphError`] instead of panicking.
     ///
     /// # Examples
     ///
@@ -482,43 +444,59 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xbu32, 4);
-    /// let gt_1 = lit_a.gt(lit_a); // Equivalent to m.low()
-    /// let gt_2 = lit_b.gt(lit_b); // Equivalent to m.low()
-    /// let gt_3 = lit_a.gt(lit_b); // Equivalent to m.low()
-    /// let gt_4 = lit_b.gt(lit_a); // Equivalent to m.high()
+    /// let lit = m.lit(0b0110u32, 4);
+    /// assert!(lit.try_bits(2, 0).is_ok());
+    /// assert_eq!(
+    ///     lit.try_bits(0, 2).err(),
+    ///     Some(GraphError::BitRangeInverted {
+    ///         range_high: 0,
+    ///         range_low: 2,
+    ///     })
+    /// );
     /// ```
     ///
-    /// [`Module`]: ./struct.Module.html
-    pub fn gt(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
+    /// [`bits`]: #method.bits
+    /// [`GraphError`]: ./enum.GraphError.html
+    #[track_caller]
+    pub fn try_bits(
+        &'a self,
+        range_high: u32,
+        range_low: u32,
+    ) -> Result<&'a Signal<'a>, GraphError> {
+        if range_low >= self.bit_width() {
+            return Err(GraphError::BitRangeLowOutOfRange {
+                range_low,
+                bit_width: self.bit_width(),
+                location: self.location,
+            });
         }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+        if range_high >= self.bit_width() {
+            return Err(GraphError::BitRangeHighOutOfRange {
+                range_high,
+                bit_width: self.bit_width(),
+                location: self.location,
+            });
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::ComparisonBinOp {
-                lhs: self,
-                rhs,
-                op: ComparisonBinOp::GreaterThan,
+        if range_low > range_high {
+            return Err(GraphError::BitRangeInverted {
+                range_high,
+                range_low,
+            });
+        }
+        Ok(self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::Bits {
+                source: self,
+                range_high,
+                range_low,
             },
-        })
+        ))
     }
 
-    /// Creates a `Signal` that represents the single-bit result of an unsigned `>=` comparison between `self` and `rhs`.
-    ///
-    /// # Panics
+    /// Creates a `Signal` that represents the most significant bit of this `Signal`.
     ///
-    /// Panics if `self` and `rhs` belong to different [`Module`]s, or if the bit widths of `self` and `rhs` aren't equal.
+    /// This is equivalent to `self.bit(self.bit_width() - 1)`.
     ///
     /// # Examples
     ///
@@ -529,43 +507,78 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xbu32, 4);
-    /// let ge_1 = lit_a.ge(lit_a); // Equivalent to m.high()
-    /// let ge_2 = lit_b.ge(lit_b); // Equivalent to m.high()
-    /// let ge_3 = lit_a.ge(lit_b); // Equivalent to m.low()
-    /// let ge_4 = lit_b.ge(lit_a); // Equivalent to m.high()
+    /// let lit = m.lit(0b0110u32, 4);
+    /// let msb = lit.msb(); // Represents 0, equivalent to lit.bit(3)
     /// ```
+    #[track_caller]
+    pub fn msb(&'a self) -> &'a Signal<'a> {
+        self.bit(self.bit_width() - 1)
+    }
+
+    /// Creates a `Signal` that represents the least significant bit of this `Signal`.
     ///
-    /// [`Module`]: ./struct.Module.html
-    pub fn ge(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
-        }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
-        }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
+    /// This is equivalent to `self.bit(0)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit = m.lit(0b0110u32, 4);
+    /// let lsb = lit.lsb(); // Represents 0, equivalent to lit.bit(0)
+    /// ```
+    #[track_caller]
+    pub fn lsb(&'a self) -> &'a Signal<'a> {
+        self.bit(0)
+    }
 
-            data: SignalData::ComparisonBinOp {
-                lhs: self,
-                rhs,
-                op: ComparisonBinOp::GreaterThanEqual,
-            },
-        })
+    /// Creates a `Signal` that represents the `n` most significant bits of this `Signal`.
+    ///
+    /// This is equivalent to `self.bits(self.bit_width() - 1, self.bit_width() - n)`.
+    /// Together with [`tail`], this splits a `Signal` into two parts: `self.head(n).concat(self.tail(n))` is equivalent to `self` (for `n` less than `self`'s bit width).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is `0` or greater than this `Signal`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit = m.lit(0b0110u32, 4);
+    /// let head_1 = lit.head(1); // Represents 0, equivalent to lit.bits(3, 3)
+    /// let head_3 = lit.head(3); // Represents 0b011, equivalent to lit.bits(3, 1)
+    /// let head_4 = lit.head(4); // Represents 0b0110, equivalent to lit.bits(3, 0)
+    /// ```
+    ///
+    /// [`tail`]: #method.tail
+    #[track_caller]
+    pub fn head(&'a self, n: u32) -> &'a Signal<'a> {
+        let bit_width = self.bit_width();
+        if n == 0 || n > bit_width {
+            panic!("Attempted to take the top {} bit(s) of a signal with {} bit(s), but at least 1 bit and at most the signal's bit width must be taken. The signal was created at {}.", n, bit_width, self.location);
+        }
+        self.bits(bit_width - 1, bit_width - n)
     }
 
-    /// Creates a `Signal` that represents the single-bit result of a signed `<` comparison between `self` and `rhs`.
+    /// Creates a `Signal` that represents all of the bits of this `Signal` except for its `n` most significant bits.
+    ///
+    /// This is equivalent to `self.bits(self.bit_width() - 1 - n, 0)`.
+    /// Note that `n` is the number of bits that are *dropped*, not the number of bits that are kept; to take the `n` least significant bits, use `self.bits(n - 1, 0)`.
+    /// Together with [`head`], this splits a `Signal` into two parts: `self.head(n).concat(self.tail(n))` is equivalent to `self`.
     ///
     /// # Panics
     ///
-    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if the bit widths of `self` and `rhs` are 1.
+    /// Panics if `n` is greater than or equal to this `Signal`'s bit width, as the resulting `Signal` would have no bits.
     ///
     /// # Examples
     ///
@@ -576,46 +589,99 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xbu32, 4);
-    /// let lt_signed_1 = lit_a.lt_signed(lit_a); // Equivalent to m.low()
-    /// let lt_signed_2 = lit_b.lt_signed(lit_b); // Equivalent to m.low()
-    /// let lt_signed_3 = lit_a.lt_signed(lit_b); // Equivalent to m.high()
-    /// let lt_signed_4 = lit_b.lt_signed(lit_a); // Equivalent to m.low()
+    /// let lit = m.lit(0b0110u32, 4);
+    /// let tail_0 = lit.tail(0); // Represents 0b0110, equivalent to lit.bits(3, 0)
+    /// let tail_1 = lit.tail(1); // Represents 0b110, equivalent to lit.bits(2, 0)
+    /// let tail_3 = lit.tail(3); // Represents 0, equivalent to lit.bits(0, 0)
     /// ```
     ///
-    /// [`Module`]: ./struct.Module.html
-    pub fn lt_signed(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
+    /// [`head`]: #method.head
+    #[track_caller]
+    pub fn tail(&'a self, n: u32) -> &'a Signal<'a> {
+        let bit_width = self.bit_width();
+        if n >= bit_width {
+            panic!("Attempted to drop the top {} bit(s) of a signal with {} bit(s), but at least 1 bit must remain. The signal was created at {}.", n, bit_width, self.location);
         }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+        self.bits(bit_width - 1 - n, 0)
+    }
+
+    /// Splits this `Signal` into chunks of `chunk_width` bits each, returning them in order from least to most significant.
+    ///
+    /// The first element of the returned `Vec` represents bits `chunk_width - 1` through `0`, the second represents bits `2 * chunk_width - 1` through `chunk_width`, and so on.
+    /// The chunks can be recombined with [`Module::pack`], which uses the same order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_width` is `0`, or if this `Signal`'s bit width isn't a multiple of `chunk_width`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit = m.lit(0x123456u32, 24);
+    /// let chunks = lit.chunks(8);
+    /// assert_eq!(chunks.len(), 3);
+    /// // chunks[0] represents 0x56, chunks[1] represents 0x34, and chunks[2] represents 0x12
+    /// let repacked = m.pack(&chunks); // Represents 0x123456
+    /// ```
+    ///
+    /// [`Module::pack`]: ./struct.Module.html#method.pack
+    #[track_caller]
+    pub fn chunks(&'a self, chunk_width: u32) -> Vec<&'a Signal<'a>> {
+        let bit_width = self.bit_width();
+        if chunk_width < MIN_SIGNAL_BIT_WIDTH || !bit_width.is_multiple_of(chunk_width) {
+            panic!("Cannot split a signal with a width of {} bit(s) into chunks of {} bit(s), as its width isn't a multiple of the chunk width. The signal was created at {}.", bit_width, chunk_width, self.location);
         }
-        if self.bit_width() == 1 {
-            panic!("Cannot perform signed comparison of 1-bit signals.");
+
+        let mut ret = Vec::new();
+        for index in 0..bit_width / chunk_width {
+            let range_low = index * chunk_width;
+            ret.push(self.bits(range_low + chunk_width - 1, range_low));
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
+        ret
+    }
 
-            data: SignalData::ComparisonBinOp {
-                lhs: self,
-                rhs,
-                op: ComparisonBinOp::LessThanSigned,
-            },
-        })
+    /// Splits this `Signal` into bytes, returning them in order from least to most significant.
+    ///
+    /// This is equivalent to [`chunks`]`(8)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Signal`'s bit width isn't a multiple of 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let bytes = m.input("data", 128).bytes();
+    /// assert_eq!(bytes.len(), 16);
+    /// m.output("first_byte", bytes[0]);
+    /// ```
+    ///
+    /// [`chunks`]: #method.chunks
+    #[track_caller]
+    pub fn bytes(&'a self) -> Vec<&'a Signal<'a>> {
+        self.chunks(8)
     }
 
-    /// Creates a `Signal` that represents the single-bit result of a signed `<=` comparison between `self` and `rhs`.
+    /// Creates a `Signal` that represents this `Signal` with the order of its `chunk_width`-bit chunks reversed, while the order of the bits within each chunk is preserved.
+    ///
+    /// The least significant chunk of this `Signal` becomes the most significant chunk of the returned `Signal`, and vice versa.
+    /// The returned `Signal` is built by [`concat`]enating the [`chunks`] of this `Signal`, so it has the same bit width as this `Signal`.
     ///
     /// # Panics
     ///
-    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if the bit widths of `self` and `rhs` are 1.
+    /// Panics if `chunk_width` is `0`, or if this `Signal`'s bit width isn't a multiple of `chunk_width`.
     ///
     /// # Examples
     ///
@@ -626,46 +692,60 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xbu32, 4);
-    /// let le_signed_1 = lit_a.le_signed(lit_a); // Equivalent to m.high()
-    /// let le_signed_2 = lit_b.le_signed(lit_b); // Equivalent to m.high()
-    /// let le_signed_3 = lit_a.le_signed(lit_b); // Equivalent to m.high()
-    /// let le_signed_4 = lit_b.le_signed(lit_a); // Equivalent to m.low()
+    /// let lit = m.lit(0x1234_5678u32, 32);
+    /// let swapped_halfwords = lit.reverse_chunks(16); // Represents 0x5678_1234
+    /// let swapped_nibbles = lit.reverse_chunks(4); // Represents 0x8765_4321
     /// ```
     ///
-    /// [`Module`]: ./struct.Module.html
-    pub fn le_signed(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
-        }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+    /// [`chunks`]: #method.chunks
+    /// [`concat`]: #method.concat
+    #[track_caller]
+    pub fn reverse_chunks(&'a self, chunk_width: u32) -> &'a Signal<'a> {
+        let bit_width = self.bit_width();
+        if chunk_width < MIN_SIGNAL_BIT_WIDTH || !bit_width.is_multiple_of(chunk_width) {
+            panic!("Cannot reverse the {}-bit chunks of a signal with a width of {} bit(s), as its width isn't a multiple of the chunk width. The signal was created at {}.", chunk_width, bit_width, self.location);
         }
-        if self.bit_width() == 1 {
-            panic!("Cannot perform signed comparison of 1-bit signals.");
+
+        let chunks = self.chunks(chunk_width);
+        let mut ret = chunks[0];
+        for chunk in chunks.iter().skip(1) {
+            ret = ret.concat(chunk);
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
+        ret
+    }
 
-            data: SignalData::ComparisonBinOp {
-                lhs: self,
-                rhs,
-                op: ComparisonBinOp::LessThanEqualSigned,
-            },
-        })
+    /// Creates a `Signal` that represents this `Signal` with the order of its bytes reversed, eg. to convert between little- and big-endian representations.
+    ///
+    /// This is equivalent to [`reverse_chunks`]`(8)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `Signal`'s bit width isn't a multiple of 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit = m.lit(0xdeadbeefu32, 32);
+    /// let swapped = lit.swap_bytes(); // Represents 0xefbeadde
+    /// ```
+    ///
+    /// [`reverse_chunks`]: #method.reverse_chunks
+    #[track_caller]
+    pub fn swap_bytes(&'a self) -> &'a Signal<'a> {
+        self.reverse_chunks(8)
     }
 
-    /// Creates a `Signal` that represents the single-bit result of a signed `>` comparison between `self` and `rhs`.
+    /// Creates a `Signal` that represents this `Signal` repeated `count` times.
     ///
     /// # Panics
     ///
-    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if the bit widths of `self` and `rhs` are 1.
+    /// Panics if `self.bit_width() * count` is less than [`MIN_SIGNAL_BIT_WIDTH`] or greater than [`MAX_SIGNAL_BIT_WIDTH`].
     ///
     /// # Examples
     ///
@@ -676,46 +756,41 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xbu32, 4);
-    /// let gt_signed_1 = lit_a.gt_signed(lit_a); // Equivalent to m.low()
-    /// let gt_signed_2 = lit_b.gt_signed(lit_b); // Equivalent to m.low()
-    /// let gt_signed_3 = lit_a.gt_signed(lit_b); // Equivalent to m.low()
-    /// let gt_signed_4 = lit_b.gt_signed(lit_a); // Equivalent to m.high()
+    /// let lit = m.lit(0xau32, 4);
+    /// let repeat_1 = lit.repeat(1); // Equivalent to just lit
+    /// let repeat_2 = lit.repeat(2); // Equivalent to 8-bit lit with value 0xaa
+    /// let repeat_5 = lit.repeat(5); // Equivalent to 20-bit lit with value 0xaaaaa
+    /// let repeat_8 = lit.repeat(8); // Equivalent to 32-bit lit with value 0xaaaaaaaa
     /// ```
     ///
-    /// [`Module`]: ./struct.Module.html
-    pub fn gt_signed(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
-        }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+    /// [`MIN_SIGNAL_BIT_WIDTH`]: ./constant.MIN_SIGNAL_BIT_WIDTH.html
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    #[track_caller]
+    pub fn repeat(&'a self, count: u32) -> &Signal<'a> {
+        let target_bit_width = self.bit_width() * count;
+        if target_bit_width < MIN_SIGNAL_BIT_WIDTH {
+            panic!("Attempted to repeat a {}-bit signal {} times, but this would result in a bit width of {}, which is less than the minimal signal bit width of {} bit(s).", self.bit_width(), count, target_bit_width, MIN_SIGNAL_BIT_WIDTH);
         }
-        if self.bit_width() == 1 {
-            panic!("Cannot perform signed comparison of 1-bit signals.");
+        if target_bit_width > MAX_SIGNAL_BIT_WIDTH {
+            panic!("Attempted to repeat a {}-bit signal {} times, but this would result in a bit width of {}, which is greater than the maximum signal bit width of {} bit(s).", self.bit_width(), count, target_bit_width, MAX_SIGNAL_BIT_WIDTH);
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::ComparisonBinOp {
-                lhs: self,
-                rhs,
-                op: ComparisonBinOp::GreaterThanSigned,
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::Repeat {
+                source: self,
+                count,
             },
-        })
+        )
     }
 
-    /// Creates a `Signal` that represents the single-bit result of a signed `>=` comparison between `self` and `rhs`.
+    /// Creates a `Signal` that represents this `Signal` concatenated with `rhs`.
+    ///
+    /// `self` represents the upper bits in the resulting `Signal`, and `rhs` represents the lower bits.
     ///
     /// # Panics
     ///
-    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if the bit widths of `self` and `rhs` are 1.
+    /// Panics if `self.bit_width() + rhs.bit_width()` is greater than [`MAX_SIGNAL_BIT_WIDTH`].
     ///
     /// # Examples
     ///
@@ -727,47 +802,67 @@ impl<'a> Signal<'a> {
     /// let m = c.module("MyModule");
     ///
     /// let lit_a = m.lit(0xau32, 4);
-    /// let lit_b = m.lit(0xbu32, 4);
-    /// let ge_signed_1 = lit_a.ge_signed(lit_a); // Equivalent to m.high()
-    /// let ge_signed_2 = lit_b.ge_signed(lit_b); // Equivalent to m.high()
-    /// let ge_signed_3 = lit_a.ge_signed(lit_b); // Equivalent to m.low()
-    /// let ge_signed_4 = lit_b.ge_signed(lit_a); // Equivalent to m.high()
+    /// let lit_b = m.lit(0xffu32, 8);
+    /// let concat_1 = lit_a.concat(lit_b); // Equivalent to 12-bit lit with value 0xaff
+    /// let concat_2 = lit_b.concat(lit_a); // Equivalent to 12-bit lit with value 0xffa
+    /// let concat_3 = lit_a.concat(lit_a); // Equivalent to 8-bit lit with value 0xaa
     /// ```
     ///
-    /// [`Module`]: ./struct.Module.html
-    pub fn ge_signed(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
+    /// [`MAX_SIGNAL_BIT_WIDTH`]: ./constant.MAX_SIGNAL_BIT_WIDTH.html
+    #[track_caller]
+    pub fn concat(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
+        match self.try_concat(rhs) {
+            Ok(concat) => concat,
+            Err(error) => panic!("{}", error),
         }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+    }
+
+    /// Non-panicking variant of [`concat`], which returns a [`GraphError`] instead of panicking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0u32, 128);
+    /// assert!(lit_a.try_concat(lit_a).is_ok());
+    /// assert!(lit_a.try_concat(lit_b).is_err());
+    /// ```
+    ///
+    /// [`concat`]: #method.concat
+    /// [`GraphError`]: ./enum.GraphError.html
+    #[track_caller]
+    pub fn try_concat(&'a self, rhs: &'a Signal<'a>) -> Result<&'a Signal<'a>, GraphError> {
+        if !ptr::eq(self.module, rhs.module) {
+            return Err(GraphError::DifferentModules);
         }
-        if self.bit_width() == 1 {
-            panic!("Cannot perform signed comparison of 1-bit signals.");
+        if self.bit_width() + rhs.bit_width() > MAX_SIGNAL_BIT_WIDTH {
+            return Err(GraphError::ConcatTooWide {
+                lhs_bit_width: self.bit_width(),
+                rhs_bit_width: rhs.bit_width(),
+                lhs_location: self.location,
+                rhs_location: rhs.location,
+            });
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::ComparisonBinOp {
-                lhs: self,
-                rhs,
-                op: ComparisonBinOp::GreaterThanEqualSigned,
-            },
-        })
+        Ok(self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::Concat { lhs: self, rhs },
+        ))
     }
 
-    /// Combines two `Signal`s, producing a new `Signal` that represents `self` arithmetically shifted right by `rhs` bits.
+    /// Creates a `Signal` that represents the single-bit result of a bitwise boolean equality comparison between `self` and `rhs`.
     ///
-    /// The difference is truncated to `self`'s `bit_width`. If `rhs` specifies a value that's greater than or equal to `self`'s `bit_width`, the resulting value will be all `self`'s top bit repeated `self`'s `bit_width` times.
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
     ///
     /// # Panics
     ///
-    /// Panics if `lhs` and `rhs` belong to different [`Module`]s.
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
     ///
     /// # Examples
     ///
@@ -778,35 +873,40 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let lhs = m.lit(0x80000000u32, 32);
-    /// let rhs = m.lit(1u32, 1);
-    /// let shifted = lhs.shr_arithmetic(rhs); // Equivalent to m.lit(0xc0000000u32, 32)
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0xbu32, 4);
+    /// let eq_1 = lit_a.eq(lit_a); // Equivalent to m.high()
+    /// let eq_2 = lit_b.eq(lit_b); // Equivalent to m.high()
+    /// let eq_3 = lit_a.eq(lit_b); // Equivalent to m.low()
+    /// let eq_4 = lit_b.eq(lit_a); // Equivalent to m.low()
     /// ```
     ///
     /// [`Module`]: ./struct.Module.html
-    pub fn shr_arithmetic(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn eq(&'a self, rhs: impl SignalOperand<'a>) -> &Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        if let Err(error) = self.check_combinable(rhs) {
+            panic!("{}", error);
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::ShiftBinOp {
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ComparisonBinOp {
                 lhs: self,
                 rhs,
-                op: ShiftBinOp::ShrArithmetic,
+                op: ComparisonBinOp::Equal,
             },
-        })
+        )
     }
 
-    /// Creates a 2:1 [multiplexer](https://en.wikipedia.org/wiki/Multiplexer) that represents `when_true`'s value when `self` is high, and `when_false`'s value when `self` is low.
+    /// Creates a `Signal` that represents the single-bit result of a bitwise boolean inequality comparison between `self` and `rhs`.
     ///
-    /// This is a convenience wrapper for [`Module`]::[`mux`].
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
     ///
     /// # Panics
     ///
-    /// Panics if `when_true` or `when_false` belong to a different [`Module`] than `self`, if `self`'s bit width is not 1, or if the bit widths of `when_true` and `when_false` aren't equal.
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
     ///
     /// # Examples
     ///
@@ -817,7 +917,911 @@ impl<'a> Signal<'a> {
     ///
     /// let m = c.module("MyModule");
     ///
-    /// let cond = m.input("cond", 1);
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0xbu32, 4);
+    /// let ne_1 = lit_a.ne(lit_a); // Equivalent to m.low()
+    /// let ne_2 = lit_b.ne(lit_b); // Equivalent to m.low()
+    /// let ne_3 = lit_a.ne(lit_b); // Equivalent to m.high()
+    /// let ne_4 = lit_b.ne(lit_a); // Equivalent to m.high()
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn ne(&'a self, rhs: impl SignalOperand<'a>) -> &Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        if let Err(error) = self.check_combinable(rhs) {
+            panic!("{}", error);
+        }
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ComparisonBinOp {
+                lhs: self,
+                rhs,
+                op: ComparisonBinOp::NotEqual,
+            },
+        )
+    }
+
+    /// Creates a `Signal` that represents the single-bit result of an unsigned `<` comparison between `self` and `rhs`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0xbu32, 4);
+    /// let lt_1 = lit_a.lt(lit_a); // Equivalent to m.low()
+    /// let lt_2 = lit_b.lt(lit_b); // Equivalent to m.low()
+    /// let lt_3 = lit_a.lt(lit_b); // Equivalent to m.high()
+    /// let lt_4 = lit_b.lt(lit_a); // Equivalent to m.low()
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn lt(&'a self, rhs: impl SignalOperand<'a>) -> &Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        if let Err(error) = self.check_combinable(rhs) {
+            panic!("{}", error);
+        }
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ComparisonBinOp {
+                lhs: self,
+                rhs,
+                op: ComparisonBinOp::LessThan,
+            },
+        )
+    }
+
+    /// Creates a `Signal` that represents the single-bit result of an unsigned `<=` comparison between `self` and `rhs`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0xbu32, 4);
+    /// let le_1 = lit_a.le(lit_a); // Equivalent to m.high()
+    /// let le_2 = lit_b.le(lit_b); // Equivalent to m.high()
+    /// let le_3 = lit_a.le(lit_b); // Equivalent to m.high()
+    /// let le_4 = lit_b.le(lit_a); // Equivalent to m.low()
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn le(&'a self, rhs: impl SignalOperand<'a>) -> &Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        if let Err(error) = self.check_combinable(rhs) {
+            panic!("{}", error);
+        }
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ComparisonBinOp {
+                lhs: self,
+                rhs,
+                op: ComparisonBinOp::LessThanEqual,
+            },
+        )
+    }
+
+    /// Creates a `Signal` that represents the single-bit result of an unsigned `>` comparison between `self` and `rhs`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0xbu32, 4);
+    /// let gt_1 = lit_a.gt(lit_a); // Equivalent to m.low()
+    /// let gt_2 = lit_b.gt(lit_b); // Equivalent to m.low()
+    /// let gt_3 = lit_a.gt(lit_b); // Equivalent to m.low()
+    /// let gt_4 = lit_b.gt(lit_a); // Equivalent to m.high()
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn gt(&'a self, rhs: impl SignalOperand<'a>) -> &Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        if let Err(error) = self.check_combinable(rhs) {
+            panic!("{}", error);
+        }
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ComparisonBinOp {
+                lhs: self,
+                rhs,
+                op: ComparisonBinOp::GreaterThan,
+            },
+        )
+    }
+
+    /// Creates a `Signal` that represents the single-bit result of an unsigned `>=` comparison between `self` and `rhs`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0xbu32, 4);
+    /// let ge_1 = lit_a.ge(lit_a); // Equivalent to m.high()
+    /// let ge_2 = lit_b.ge(lit_b); // Equivalent to m.high()
+    /// let ge_3 = lit_a.ge(lit_b); // Equivalent to m.low()
+    /// let ge_4 = lit_b.ge(lit_a); // Equivalent to m.high()
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn ge(&'a self, rhs: impl SignalOperand<'a>) -> &Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        if let Err(error) = self.check_combinable(rhs) {
+            panic!("{}", error);
+        }
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ComparisonBinOp {
+                lhs: self,
+                rhs,
+                op: ComparisonBinOp::GreaterThanEqual,
+            },
+        )
+    }
+
+    /// Creates a `Signal` that represents the single-bit result of a signed `<` comparison between `self` and `rhs`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, if the bit widths of `self` and `rhs` are 1, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0xbu32, 4);
+    /// let lt_signed_1 = lit_a.lt_signed(lit_a); // Equivalent to m.low()
+    /// let lt_signed_2 = lit_b.lt_signed(lit_b); // Equivalent to m.low()
+    /// let lt_signed_3 = lit_a.lt_signed(lit_b); // Equivalent to m.high()
+    /// let lt_signed_4 = lit_b.lt_signed(lit_a); // Equivalent to m.low()
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn lt_signed(&'a self, rhs: impl SignalOperand<'a>) -> &Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        if let Err(error) = self.check_combinable(rhs) {
+            panic!("{}", error);
+        }
+        if self.bit_width() == 1 {
+            panic!("Cannot perform signed comparison of 1-bit signals.");
+        }
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ComparisonBinOp {
+                lhs: self,
+                rhs,
+                op: ComparisonBinOp::LessThanSigned,
+            },
+        )
+    }
+
+    /// Creates a `Signal` that represents the single-bit result of a signed `<=` comparison between `self` and `rhs`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, if the bit widths of `self` and `rhs` are 1, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0xbu32, 4);
+    /// let le_signed_1 = lit_a.le_signed(lit_a); // Equivalent to m.high()
+    /// let le_signed_2 = lit_b.le_signed(lit_b); // Equivalent to m.high()
+    /// let le_signed_3 = lit_a.le_signed(lit_b); // Equivalent to m.high()
+    /// let le_signed_4 = lit_b.le_signed(lit_a); // Equivalent to m.low()
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn le_signed(&'a self, rhs: impl SignalOperand<'a>) -> &Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        if let Err(error) = self.check_combinable(rhs) {
+            panic!("{}", error);
+        }
+        if self.bit_width() == 1 {
+            panic!("Cannot perform signed comparison of 1-bit signals.");
+        }
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ComparisonBinOp {
+                lhs: self,
+                rhs,
+                op: ComparisonBinOp::LessThanEqualSigned,
+            },
+        )
+    }
+
+    /// Creates a `Signal` that represents the single-bit result of a signed `>` comparison between `self` and `rhs`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, if the bit widths of `self` and `rhs` are 1, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0xbu32, 4);
+    /// let gt_signed_1 = lit_a.gt_signed(lit_a); // Equivalent to m.low()
+    /// let gt_signed_2 = lit_b.gt_signed(lit_b); // Equivalent to m.low()
+    /// let gt_signed_3 = lit_a.gt_signed(lit_b); // Equivalent to m.low()
+    /// let gt_signed_4 = lit_b.gt_signed(lit_a); // Equivalent to m.high()
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn gt_signed(&'a self, rhs: impl SignalOperand<'a>) -> &Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        if let Err(error) = self.check_combinable(rhs) {
+            panic!("{}", error);
+        }
+        if self.bit_width() == 1 {
+            panic!("Cannot perform signed comparison of 1-bit signals.");
+        }
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ComparisonBinOp {
+                lhs: self,
+                rhs,
+                op: ComparisonBinOp::GreaterThanSigned,
+            },
+        )
+    }
+
+    /// Creates a `Signal` that represents the single-bit result of a signed `>=` comparison between `self` and `rhs`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, if the bit widths of `self` and `rhs` are 1, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lit_a = m.lit(0xau32, 4);
+    /// let lit_b = m.lit(0xbu32, 4);
+    /// let ge_signed_1 = lit_a.ge_signed(lit_a); // Equivalent to m.high()
+    /// let ge_signed_2 = lit_b.ge_signed(lit_b); // Equivalent to m.high()
+    /// let ge_signed_3 = lit_a.ge_signed(lit_b); // Equivalent to m.low()
+    /// let ge_signed_4 = lit_b.ge_signed(lit_a); // Equivalent to m.high()
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn ge_signed(&'a self, rhs: impl SignalOperand<'a>) -> &Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        if let Err(error) = self.check_combinable(rhs) {
+            panic!("{}", error);
+        }
+        if self.bit_width() == 1 {
+            panic!("Cannot perform signed comparison of 1-bit signals.");
+        }
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ComparisonBinOp {
+                lhs: self,
+                rhs,
+                op: ComparisonBinOp::GreaterThanEqualSigned,
+            },
+        )
+    }
+
+    /// Creates a `Signal` that represents the smaller of `self` and `rhs`, interpreted as unsigned values.
+    ///
+    /// This is equivalent to `self.lt(rhs).mux(self, rhs)`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let a = m.input("a", 8);
+    /// let b = m.input("b", 8);
+    /// m.output("smaller", a.min(b));
+    /// m.output("at_most_100", a.min(100));
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn min(&'a self, rhs: impl SignalOperand<'a>) -> &'a Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        self.lt(rhs).mux(self, rhs)
+    }
+
+    /// Creates a `Signal` that represents the larger of `self` and `rhs`, interpreted as unsigned values.
+    ///
+    /// This is equivalent to `self.gt(rhs).mux(self, rhs)`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let a = m.input("a", 8);
+    /// let b = m.input("b", 8);
+    /// m.output("larger", a.max(b));
+    /// m.output("at_least_10", a.max(10));
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn max(&'a self, rhs: impl SignalOperand<'a>) -> &'a Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        self.gt(rhs).mux(self, rhs)
+    }
+
+    /// Creates a `Signal` that represents `self` limited to the range from `lo` to `hi` (inclusive), interpreted as unsigned values.
+    ///
+    /// This is equivalent to `self.max(lo).min(hi)`.
+    ///
+    /// `lo` and `hi` can also be unsigned integers (or non-negative `i32`s, such as unsuffixed integer literals), in which case they're converted to literals with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self`, `lo`, and `hi` don't all belong to the same [`Module`], if their bit widths aren't equal, if `lo` or `hi` is an integer that doesn't fit in `self`'s bit width, or if `lo` and `hi` are both literals and `lo` is greater than `hi`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let volume = m.input("volume", 8);
+    /// m.output("clamped_volume", volume.clamp(16, 200));
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn clamp(
+        &'a self,
+        lo: impl SignalOperand<'a>,
+        hi: impl SignalOperand<'a>,
+    ) -> &'a Signal<'a> {
+        let lo = lo.into_signal(self);
+        let hi = hi.into_signal(self);
+        if let (Some(lo_value), Some(hi_value)) = (lo.lit_value(), hi.lit_value()) {
+            if lo_value > hi_value {
+                panic!("Attempted to clamp a signal to the range from {} to {}, but the lower bound is greater than the upper bound. The signal was created at {}.", lo_value, hi_value, self.location);
+            }
+        }
+        self.max(lo).min(hi)
+    }
+
+    /// Creates a `Signal` that represents the smaller of `self` and `rhs`, interpreted as signed (two's complement) values.
+    ///
+    /// This is equivalent to `self.lt_signed(rhs).mux(self, rhs)`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, if the bit widths of `self` and `rhs` are 1, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let a = m.input("a", 8);
+    /// let b = m.input("b", 8);
+    /// m.output("smaller", a.min_signed(b));
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn min_signed(&'a self, rhs: impl SignalOperand<'a>) -> &'a Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        self.lt_signed(rhs).mux(self, rhs)
+    }
+
+    /// Creates a `Signal` that represents the larger of `self` and `rhs`, interpreted as signed (two's complement) values.
+    ///
+    /// This is equivalent to `self.gt_signed(rhs).mux(self, rhs)`.
+    ///
+    /// `rhs` can also be an unsigned integer (or a non-negative `i32`, such as an unsuffixed integer literal), in which case it's converted to a literal with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `rhs` belong to different [`Module`]s, if the bit widths of `self` and `rhs` aren't equal, if the bit widths of `self` and `rhs` are 1, or if `rhs` is an integer that doesn't fit in `self`'s bit width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let a = m.input("a", 8);
+    /// m.output("non_negative", a.max_signed(0)); // Negative values become 0
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn max_signed(&'a self, rhs: impl SignalOperand<'a>) -> &'a Signal<'a> {
+        let rhs = rhs.into_signal(self);
+        self.gt_signed(rhs).mux(self, rhs)
+    }
+
+    /// Creates a `Signal` that represents `self` limited to the range from `lo` to `hi` (inclusive), interpreted as signed (two's complement) values.
+    ///
+    /// This is equivalent to `self.max_signed(lo).min_signed(hi)`.
+    ///
+    /// `lo` and `hi` can also be unsigned integers (or non-negative `i32`s, such as unsuffixed integer literals), in which case they're converted to literals with the same bit width as `self`. See [`SignalOperand`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self`, `lo`, and `hi` don't all belong to the same [`Module`], if their bit widths aren't equal, if their bit widths are 1, if `lo` or `hi` is an integer that doesn't fit in `self`'s bit width, or if `lo` and `hi` are both literals and `lo` is greater than `hi` (as signed values).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let sample = m.input("sample", 16);
+    /// // Limits sample to -1000..=1000
+    /// m.output("clamped_sample", sample.clamp_signed(m.lit(-1000i16 as u16 as u32, 16), 1000));
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    /// [`SignalOperand`]: ./trait.SignalOperand.html
+    #[track_caller]
+    pub fn clamp_signed(
+        &'a self,
+        lo: impl SignalOperand<'a>,
+        hi: impl SignalOperand<'a>,
+    ) -> &'a Signal<'a> {
+        let lo = lo.into_signal(self);
+        let hi = hi.into_signal(self);
+        if let (Some(lo_value), Some(hi_value)) = (lo.lit_value(), hi.lit_value()) {
+            let lo_value = sign_extend(lo_value, lo.bit_width());
+            let hi_value = sign_extend(hi_value, hi.bit_width());
+            if lo_value > hi_value {
+                panic!("Attempted to clamp a signal to the range from {} to {} (as signed values), but the lower bound is greater than the upper bound. The signal was created at {}.", lo_value, hi_value, self.location);
+            }
+        }
+        self.max_signed(lo).min_signed(hi)
+    }
+
+    fn lit_value(&self) -> Option<u128> {
+        match self.data {
+            SignalData::Lit { ref value, .. } => Some(value.numeric_value()),
+            _ => None,
+        }
+    }
+
+    /// Combines two `Signal`s, producing a new `Signal` that represents `self` arithmetically shifted right by `rhs` bits.
+    ///
+    /// The difference is truncated to `self`'s `bit_width`. If `rhs` specifies a value that's greater than or equal to `self`'s `bit_width`, the resulting value will be all `self`'s top bit repeated `self`'s `bit_width` times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lhs` and `rhs` belong to different [`Module`]s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let lhs = m.lit(0x80000000u32, 32);
+    /// let rhs = m.lit(1u32, 1);
+    /// let shifted = lhs.shr_arithmetic(rhs); // Equivalent to m.lit(0xc0000000u32, 32)
+    /// ```
+    ///
+    /// [`Module`]: ./struct.Module.html
+    #[track_caller]
+    pub fn shr_arithmetic(&'a self, rhs: &'a Signal<'a>) -> &Signal<'a> {
+        if !ptr::eq(self.module, rhs.module) {
+            panic!("Attempted to combine signals from different modules.");
+        }
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ShiftBinOp {
+                lhs: self,
+                rhs,
+                op: ShiftBinOp::ShrArithmetic,
+            },
+        )
+    }
+
+    /// Non-panicking variant of `self + rhs`, which returns a [`GraphError`] instead of panicking.
+    ///
+    /// [`GraphError`]: ./enum.GraphError.html
+    #[track_caller]
+    pub fn try_add(&'a self, rhs: &'a Signal<'a>) -> Result<&'a Signal<'a>, GraphError> {
+        self.check_combinable(rhs)?;
+        Ok(self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::AdditiveBinOp {
+                lhs: self,
+                rhs,
+                op: AdditiveBinOp::Add,
+            },
+        ))
+    }
+
+    /// Non-panicking variant of `self - rhs`, which returns a [`GraphError`] instead of panicking.
+    ///
+    /// [`GraphError`]: ./enum.GraphError.html
+    #[track_caller]
+    pub fn try_sub(&'a self, rhs: &'a Signal<'a>) -> Result<&'a Signal<'a>, GraphError> {
+        self.check_combinable(rhs)?;
+        Ok(self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::AdditiveBinOp {
+                lhs: self,
+                rhs,
+                op: AdditiveBinOp::Sub,
+            },
+        ))
+    }
+
+    /// Non-panicking variant of `self & rhs`, which returns a [`GraphError`] instead of panicking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let a = m.input("a", 3);
+    /// let b = m.input("b", 5);
+    /// assert!(a.try_bitand(a).is_ok());
+    /// match a.try_bitand(b) {
+    ///     Err(GraphError::BitWidthMismatch { lhs_bit_width, rhs_bit_width, .. }) => {
+    ///         assert_eq!((lhs_bit_width, rhs_bit_width), (3, 5));
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    ///
+    /// [`GraphError`]: ./enum.GraphError.html
+    #[track_caller]
+    pub fn try_bitand(&'a self, rhs: &'a Signal<'a>) -> Result<&'a Signal<'a>, GraphError> {
+        self.check_combinable(rhs)?;
+        Ok(self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::SimpleBinOp {
+                lhs: self,
+                rhs,
+                op: SimpleBinOp::BitAnd,
+            },
+        ))
+    }
+
+    /// Non-panicking variant of `self | rhs`, which returns a [`GraphError`] instead of panicking.
+    ///
+    /// [`GraphError`]: ./enum.GraphError.html
+    #[track_caller]
+    pub fn try_bitor(&'a self, rhs: &'a Signal<'a>) -> Result<&'a Signal<'a>, GraphError> {
+        self.check_combinable(rhs)?;
+        Ok(self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::SimpleBinOp {
+                lhs: self,
+                rhs,
+                op: SimpleBinOp::BitOr,
+            },
+        ))
+    }
+
+    /// Non-panicking variant of `self ^ rhs`, which returns a [`GraphError`] instead of panicking.
+    ///
+    /// [`GraphError`]: ./enum.GraphError.html
+    #[track_caller]
+    pub fn try_bitxor(&'a self, rhs: &'a Signal<'a>) -> Result<&'a Signal<'a>, GraphError> {
+        self.check_combinable(rhs)?;
+        Ok(self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::SimpleBinOp {
+                lhs: self,
+                rhs,
+                op: SimpleBinOp::BitXor,
+            },
+        ))
+    }
+
+    fn check_combinable(&self, rhs: &Signal<'a>) -> Result<(), GraphError> {
+        if !ptr::eq(self.module, rhs.module) {
+            return Err(GraphError::DifferentModules);
+        }
+        if self.bit_width() != rhs.bit_width() {
+            return Err(GraphError::BitWidthMismatch {
+                lhs_bit_width: self.bit_width(),
+                rhs_bit_width: rhs.bit_width(),
+                lhs_location: self.location,
+                rhs_location: rhs.location,
+            });
+        }
+        Ok(())
+    }
+
+    /// Creates a `Signal` that represents the two's complement negation of this `Signal`'s value, with the same bit width as `self`.
+    ///
+    /// This is equivalent to `0 - self`, so the result wraps within `self`'s bit width: negating the most negative value (only the top bit set) results in that same value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let a = m.input("a", 8);
+    /// m.output("minus_a", a.negate());
+    ///
+    /// let minus_one = m.lit(1u32, 8).negate(); // Equivalent to 8-bit lit with value 0xff
+    /// let most_negative = m.lit(0x80u32, 8).negate(); // Equivalent to 8-bit lit with value 0x80
+    /// ```
+    #[track_caller]
+    pub fn negate(&'a self) -> &'a Signal<'a> {
+        self.module.lit(0u32, self.bit_width()) - self
+    }
+
+    /// Creates a `Signal` that represents the absolute value of this `Signal`'s value, interpreted as a signed (two's complement) number, with the same bit width as `self`.
+    ///
+    /// This is equivalent to `self.msb().mux(self.negate(), self)`.
+    /// Like [`negate`], the result wraps within `self`'s bit width, so the absolute value of the most negative value (only the top bit set) is that same value; this is the only input for which the result's top bit is set, so the result can also be interpreted as an unsigned number to get the correct magnitude for every input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let sample = m.input("sample", 16);
+    /// m.output("magnitude", sample.abs());
+    ///
+    /// let five = m.lit(0xfbu32, 8).abs(); // -5; equivalent to 8-bit lit with value 5
+    /// let most_negative = m.lit(0x80u32, 8).abs(); // Equivalent to 8-bit lit with value 0x80
+    /// ```
+    ///
+    /// [`negate`]: #method.negate
+    #[track_caller]
+    pub fn abs(&'a self) -> &'a Signal<'a> {
+        self.msb().mux(self.negate(), self)
+    }
+
+    /// Creates a `Signal` that represents the [Gray code](https://en.wikipedia.org/wiki/Gray_code) encoding of this `Signal`'s value, interpreted as an unsigned binary number.
+    ///
+    /// The returned `Signal` has the same bit width as `self`, and is equivalent to `self ^ (self >> 1)`.
+    /// Successive binary values (including the wrap from the maximum value back to `0`) map to Gray codes that differ by exactly one bit, which makes them safe to pass across clock domains, eg. as FIFO pointers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let ptr = m.input("ptr", 4);
+    /// m.output("ptr_gray", ptr.to_gray());
+    ///
+    /// let gray = m.lit(0b0110u32, 4).to_gray(); // Equivalent to 4-bit lit with value 0b0101
+    /// ```
+    #[track_caller]
+    pub fn to_gray(&'a self) -> &'a Signal<'a> {
+        let bit_width = self.bit_width();
+        if bit_width == 1 {
+            return self;
+        }
+        self ^ self.module.lit(0u32, 1).concat(self.bits(bit_width - 1, 1))
+    }
+
+    /// Creates a `Signal` that represents the unsigned binary value of this `Signal`, interpreted as a [Gray code](https://en.wikipedia.org/wiki/Gray_code).
+    ///
+    /// This is the inverse of [`to_gray`]: each bit of the result is the XOR of all bits at or above the same position in `self`.
+    /// The returned `Signal` has the same bit width as `self`, and the prefix XOR is built in `ceil(log2(bit_width))` stages so that the logic depth stays small for wide signals.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let ptr_gray = m.input("ptr_gray", 4);
+    /// m.output("ptr", ptr_gray.from_gray());
+    ///
+    /// let binary = m.lit(0b0101u32, 4).from_gray(); // Equivalent to 4-bit lit with value 0b0110
+    /// ```
+    ///
+    /// [`to_gray`]: #method.to_gray
+    #[track_caller]
+    pub fn from_gray(&'a self) -> &'a Signal<'a> {
+        let bit_width = self.bit_width();
+        let mut ret = self;
+        let mut shift = 1;
+        while shift < bit_width {
+            ret = ret
+                ^ self
+                    .module
+                    .lit(0u32, shift)
+                    .concat(ret.bits(bit_width - 1, shift));
+            shift *= 2;
+        }
+        ret
+    }
+
+    /// Creates a 2:1 [multiplexer](https://en.wikipedia.org/wiki/Multiplexer) that represents `when_true`'s value when `self` is high, and `when_false`'s value when `self` is low.
+    ///
+    /// This is a convenience wrapper for [`Module`]::[`mux`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `when_true` or `when_false` belong to a different [`Module`] than `self`, if `self`'s bit width is not 1, or if the bit widths of `when_true` and `when_false` aren't equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kaze::*;
+    ///
+    /// let c = Context::new();
+    ///
+    /// let m = c.module("MyModule");
+    ///
+    /// let cond = m.input("cond", 1);
     /// let a = m.input("a", 8);
     /// let b = m.input("b", 8);
     /// m.output("my_output", cond.mux(a, b)); // Outputs a when cond is high, b otherwise
@@ -826,26 +1830,197 @@ impl<'a> Signal<'a> {
     /// [`Module`]: ./struct.Module.html
     /// [`mux`]: ./struct.Module.html#method.mux
     // TODO: This is currently only used to support sugar; if it doesn't work out, remove this
+    #[track_caller]
     pub fn mux(&'a self, when_true: &'a Signal<'a>, when_false: &'a Signal<'a>) -> &Signal<'a> {
         self.module.mux(self, when_true, when_false)
     }
 }
 
-pub(crate) enum SignalData<'a> {
+pub(crate) enum SignalData<'a> {
+    // Literals are boxed, as their (16-byte aligned) values would otherwise make every node larger
+    Lit {
+        value: Box<Constant>,
+        bit_width: u32,
+    },
+
+    Input {
+        name: &'a str,
+        bit_width: u32,
+    },
+
+    Reg {
+        data: &'a RegisterData<'a>,
+    },
+
+    Latch {
+        data: &'a LatchData<'a>,
+    },
+
+    UnOp {
+        source: &'a Signal<'a>,
+        op: UnOp,
+    },
+    SimpleBinOp {
+        lhs: &'a Signal<'a>,
+        rhs: &'a Signal<'a>,
+        op: SimpleBinOp,
+    },
+    AdditiveBinOp {
+        lhs: &'a Signal<'a>,
+        rhs: &'a Signal<'a>,
+        op: AdditiveBinOp,
+    },
+    ComparisonBinOp {
+        lhs: &'a Signal<'a>,
+        rhs: &'a Signal<'a>,
+        op: ComparisonBinOp,
+    },
+    ShiftBinOp {
+        lhs: &'a Signal<'a>,
+        rhs: &'a Signal<'a>,
+        op: ShiftBinOp,
+    },
+
+    Bits {
+        source: &'a Signal<'a>,
+        range_high: u32,
+        range_low: u32,
+    },
+
+    Repeat {
+        source: &'a Signal<'a>,
+        count: u32,
+    },
+    Concat {
+        lhs: &'a Signal<'a>,
+        rhs: &'a Signal<'a>,
+    },
+
+    Mux {
+        cond: &'a Signal<'a>,
+        when_true: &'a Signal<'a>,
+        when_false: &'a Signal<'a>,
+    },
+
+    InstanceOutput {
+        instance: &'a Instance<'a>,
+        name: &'a str,
+    },
+
+    MemReadPortOutput {
+        mem: &'a Mem<'a>,
+        address: &'a Signal<'a>,
+        enable: &'a Signal<'a>,
+    },
+    MemCombReadPortOutput {
+        mem: &'a Mem<'a>,
+        address: &'a Signal<'a>,
+    },
+}
+
+impl<'a> SignalData<'a> {
+    pub(super) fn bit_width(&self) -> u32 {
+        match self {
+            SignalData::Lit { bit_width, .. } => *bit_width,
+            SignalData::Input { bit_width, .. } => *bit_width,
+            SignalData::Reg { data } => data.bit_width,
+            SignalData::Latch { data } => data.bit_width,
+            SignalData::UnOp { source, .. } => source.bit_width(),
+            SignalData::SimpleBinOp { lhs, .. } => lhs.bit_width(),
+            SignalData::AdditiveBinOp { lhs, .. } => lhs.bit_width(),
+            SignalData::ComparisonBinOp { .. } => 1,
+            SignalData::ShiftBinOp { lhs, .. } => lhs.bit_width(),
+            SignalData::Bits {
+                range_high,
+                range_low,
+                ..
+            } => range_high - range_low + 1,
+            SignalData::Repeat { source, count } => source.bit_width() * count,
+            SignalData::Concat { lhs, rhs } => lhs.bit_width() + rhs.bit_width(),
+            SignalData::Mux { when_true, .. } => when_true.bit_width(),
+            SignalData::InstanceOutput { instance, name } => {
+                instance.instantiated_module.outputs.borrow()[*name].bit_width()
+            }
+            SignalData::MemReadPortOutput { mem, .. } => mem.element_bit_width,
+            SignalData::MemCombReadPortOutput { mem, .. } => mem.element_bit_width,
+        }
+    }
+
+    /// Returns a key identifying this node by its structure and operands, or `None` if this node must never be shared with structurally-identical nodes.
+    pub(super) fn structural_key(&self) -> Option<StructuralKey<'a>> {
+        match *self {
+            SignalData::Lit {
+                ref value,
+                bit_width,
+            } => {
+                let value = value.numeric_value();
+                Some(StructuralKey::Lit {
+                    value_low: value as u64,
+                    value_high: (value >> 64) as u64,
+                    bit_width,
+                })
+            }
+
+            // Inputs and registers have identities of their own, and memory read ports are tracked by their memories
+            SignalData::Input { .. }
+            | SignalData::Reg { .. }
+            | SignalData::Latch { .. }
+            | SignalData::MemReadPortOutput { .. }
+            | SignalData::MemCombReadPortOutput { .. } => None,
+
+            SignalData::UnOp { source, op } => Some(StructuralKey::UnOp { source, op }),
+            SignalData::SimpleBinOp { lhs, rhs, op } => {
+                Some(StructuralKey::SimpleBinOp { lhs, rhs, op })
+            }
+            SignalData::AdditiveBinOp { lhs, rhs, op } => {
+                Some(StructuralKey::AdditiveBinOp { lhs, rhs, op })
+            }
+            SignalData::ComparisonBinOp { lhs, rhs, op } => {
+                Some(StructuralKey::ComparisonBinOp { lhs, rhs, op })
+            }
+            SignalData::ShiftBinOp { lhs, rhs, op } => {
+                Some(StructuralKey::ShiftBinOp { lhs, rhs, op })
+            }
+
+            SignalData::Bits {
+                source,
+                range_high,
+                range_low,
+            } => Some(StructuralKey::Bits {
+                source,
+                range_high,
+                range_low,
+            }),
+
+            SignalData::Repeat { source, count } => Some(StructuralKey::Repeat { source, count }),
+            SignalData::Concat { lhs, rhs } => Some(StructuralKey::Concat { lhs, rhs }),
+
+            SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            } => Some(StructuralKey::Mux {
+                cond,
+                when_true,
+                when_false,
+            }),
+
+            SignalData::InstanceOutput { instance, name } => {
+                Some(StructuralKey::InstanceOutput { instance, name })
+            }
+        }
+    }
+}
+
+#[derive(Eq, Hash, PartialEq)]
+pub(crate) enum StructuralKey<'a> {
+    // Split into halves to avoid the 16-byte alignment of u128, which would make every key larger
     Lit {
-        value: Constant,
-        bit_width: u32,
-    },
-
-    Input {
-        name: String,
+        value_low: u64,
+        value_high: u64,
         bit_width: u32,
     },
 
-    Reg {
-        data: &'a RegisterData<'a>,
-    },
-
     UnOp {
         source: &'a Signal<'a>,
         op: UnOp,
@@ -894,13 +2069,7 @@ pub(crate) enum SignalData<'a> {
 
     InstanceOutput {
         instance: &'a Instance<'a>,
-        name: String,
-    },
-
-    MemReadPortOutput {
-        mem: &'a Mem<'a>,
-        address: &'a Signal<'a>,
-        enable: &'a Signal<'a>,
+        name: &'a str,
     },
 }
 
@@ -937,27 +2106,12 @@ impl<'a> Add for &'a Signal<'a> {
     ///
     /// [`concat`]: #method.concat
     /// [`Module`]: ./struct.Module.html
+    #[track_caller]
     fn add(self, rhs: Self) -> Self {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
-        }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+        match self.try_add(rhs) {
+            Ok(signal) => signal,
+            Err(error) => panic!("{}", error),
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::AdditiveBinOp {
-                lhs: self,
-                rhs,
-                op: AdditiveBinOp::Add,
-            },
-        })
     }
 }
 
@@ -989,27 +2143,12 @@ impl<'a> BitAnd for &'a Signal<'a> {
     /// ```
     ///
     /// [`Module`]: ./struct.Module.html
+    #[track_caller]
     fn bitand(self, rhs: Self) -> Self {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
-        }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+        match self.try_bitand(rhs) {
+            Ok(signal) => signal,
+            Err(error) => panic!("{}", error),
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::SimpleBinOp {
-                lhs: self,
-                rhs,
-                op: SimpleBinOp::BitAnd,
-            },
-        })
     }
 }
 
@@ -1041,27 +2180,12 @@ impl<'a> BitOr for &'a Signal<'a> {
     /// ```
     ///
     /// [`Module`]: ./struct.Module.html
+    #[track_caller]
     fn bitor(self, rhs: Self) -> Self {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
-        }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+        match self.try_bitor(rhs) {
+            Ok(signal) => signal,
+            Err(error) => panic!("{}", error),
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::SimpleBinOp {
-                lhs: self,
-                rhs,
-                op: SimpleBinOp::BitOr,
-            },
-        })
     }
 }
 
@@ -1093,27 +2217,12 @@ impl<'a> BitXor for &'a Signal<'a> {
     /// ```
     ///
     /// [`Module`]: ./struct.Module.html
+    #[track_caller]
     fn bitxor(self, rhs: Self) -> Self {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
-        }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+        match self.try_bitxor(rhs) {
+            Ok(signal) => signal,
+            Err(error) => panic!("{}", error),
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::SimpleBinOp {
-                lhs: self,
-                rhs,
-                op: SimpleBinOp::BitXor,
-            },
-        })
     }
 }
 
@@ -1151,16 +2260,16 @@ impl<'a> Not for &'a Signal<'a> {
     /// let input2 = m.input("input2", 6);
     /// let multi_not = !input2;
     /// ```
+    #[track_caller]
     fn not(self) -> Self {
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::UnOp {
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::UnOp {
                 source: self,
                 op: UnOp::Not,
             },
-        })
+        )
     }
 }
 
@@ -1190,20 +2299,20 @@ impl<'a> Shl for &'a Signal<'a> {
     /// ```
     ///
     /// [`Module`]: ./struct.Module.html
+    #[track_caller]
     fn shl(self, rhs: Self) -> Self {
         if !ptr::eq(self.module, rhs.module) {
             panic!("Attempted to combine signals from different modules.");
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::ShiftBinOp {
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ShiftBinOp {
                 lhs: self,
                 rhs,
                 op: ShiftBinOp::Shl,
             },
-        })
+        )
     }
 }
 
@@ -1233,20 +2342,20 @@ impl<'a> Shr for &'a Signal<'a> {
     /// ```
     ///
     /// [`Module`]: ./struct.Module.html
+    #[track_caller]
     fn shr(self, rhs: Self) -> Self {
         if !ptr::eq(self.module, rhs.module) {
             panic!("Attempted to combine signals from different modules.");
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
-
-            data: SignalData::ShiftBinOp {
+        self.module.context.alloc_signal(
+            self.module,
+            Location::caller(),
+            SignalData::ShiftBinOp {
                 lhs: self,
                 rhs,
                 op: ShiftBinOp::Shr,
             },
-        })
+        )
     }
 }
 
@@ -1277,88 +2386,401 @@ impl<'a> Sub for &'a Signal<'a> {
     ///
     /// [`concat`]: #method.concat
     /// [`Module`]: ./struct.Module.html
+    #[track_caller]
     fn sub(self, rhs: Self) -> Self {
-        if !ptr::eq(self.module, rhs.module) {
-            panic!("Attempted to combine signals from different modules.");
+        match self.try_sub(rhs) {
+            Ok(signal) => signal,
+            Err(error) => panic!("{}", error),
         }
-        if self.bit_width() != rhs.bit_width() {
-            panic!(
-                "Signals have different bit widths ({} and {}, respectively).",
-                self.bit_width(),
-                rhs.bit_width()
-            );
+    }
+}
+
+/// A value that can be used as the other operand when combining it with a [`Signal`].
+///
+/// This is implemented for `&Signal`, as well as for Rust's unsigned integer types and `i32`.
+/// An integer operand is converted to a literal with the same bit width as the [`Signal`] it's combined with, so `counter + 1u32` is equivalent to `counter + m.lit(1u32, counter.bit_width())`.
+/// Integer operands can be used on either side of `+`, `-`, `&`, `|`, and `^`, on the right-hand side of `<<` and `>>`, and as the `rhs` of comparison methods like [`eq`] and [`lt`].
+///
+/// Unsuffixed integer literals default to `i32`, so `addr & 0xff` works as expected, but literals that don't fit in an `i32` (eg. `0xffffffff`) need a suffix, and negative values are rejected.
+///
+/// # Panics
+///
+/// Combining a [`Signal`] with an integer panics if the integer doesn't fit in the [`Signal`]'s bit width, or if it's negative.
+/// Shift amounts are exempt from the bit width check, since they're not required to have the same bit width as the [`Signal`] being shifted.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+///
+/// let counter = m.reg("counter", 32);
+/// counter.default_value(0u32);
+/// counter.drive_next(counter.value + 1u32);
+///
+/// let addr = m.input("addr", 16);
+/// m.output("low_byte", addr & 0xff);
+/// m.output("is_three", counter.value.eq(3u32));
+/// m.output("remaining", 100u32 - counter.value);
+/// m.output("doubled", counter.value << 1u32);
+/// ```
+///
+/// [`eq`]: ./struct.Signal.html#method.eq
+/// [`lt`]: ./struct.Signal.html#method.lt
+/// [`Signal`]: ./struct.Signal.html
+pub trait SignalOperand<'a> {
+    /// Converts this operand to a [`Signal`] that can be combined with `lhs`.
+    ///
+    /// [`Signal`]: ./struct.Signal.html
+    fn into_signal(self, lhs: &'a Signal<'a>) -> &'a Signal<'a>;
+}
+
+impl<'a> SignalOperand<'a> for &'a Signal<'a> {
+    fn into_signal(self, _lhs: &'a Signal<'a>) -> &'a Signal<'a> {
+        self
+    }
+}
+
+impl<'a> Signal<'a> {
+    #[track_caller]
+    fn integer_operand(&'a self, value: Constant) -> &'a Signal<'a> {
+        let required_bits = value.required_bits();
+        if required_bits > self.bit_width() {
+            panic!("Attempted to combine a {}-bit signal with the integer {}, which requires {} bit(s) and doesn't fit in the signal's bit width. The signal was created at {}.", self.bit_width(), value.numeric_value(), required_bits, self.location);
         }
-        self.context.signal_arena.alloc(Signal {
-            context: self.context,
-            module: self.module,
+        self.module.lit(value, self.bit_width())
+    }
 
-            data: SignalData::AdditiveBinOp {
-                lhs: self,
-                rhs,
-                op: AdditiveBinOp::Sub,
-            },
-        })
+    #[track_caller]
+    fn shift_amount_operand(&'a self, value: Constant) -> &'a Signal<'a> {
+        let bit_width = value.required_bits().max(MIN_SIGNAL_BIT_WIDTH);
+        self.module.lit(value, bit_width)
+    }
+}
+
+fn sign_extend(value: u128, bit_width: u32) -> i128 {
+    let shift = 128 - bit_width;
+    ((value << shift) as i128) >> shift
+}
+
+#[track_caller]
+fn non_negative_operand(lhs: &Signal, value: i32) -> Constant {
+    if value < 0 {
+        panic!("Attempted to combine a {}-bit signal with the negative integer {}, but integer operands must not be negative. The signal was created at {}.", lhs.bit_width(), value, lhs.location);
     }
+    (value as u32).into()
+}
+
+macro_rules! impl_integer_operand_binop {
+    ($ty:ty, $to_constant:expr, $trait:ident, $method:ident) => {
+        impl<'a> $trait<$ty> for &'a Signal<'a> {
+            type Output = Self;
+
+            #[track_caller]
+            fn $method(self, rhs: $ty) -> Self {
+                $trait::$method(self, self.integer_operand($to_constant(self, rhs)))
+            }
+        }
+
+        impl<'a> $trait<&'a Signal<'a>> for $ty {
+            type Output = &'a Signal<'a>;
+
+            #[track_caller]
+            fn $method(self, rhs: &'a Signal<'a>) -> &'a Signal<'a> {
+                $trait::$method(rhs.integer_operand($to_constant(rhs, self)), rhs)
+            }
+        }
+    };
+}
+
+macro_rules! impl_integer_operand_shift {
+    ($ty:ty, $to_constant:expr, $trait:ident, $method:ident) => {
+        // Only the shift amount can be an integer; there's no sensible bit width for an integer being shifted
+        impl<'a> $trait<$ty> for &'a Signal<'a> {
+            type Output = Self;
+
+            #[track_caller]
+            fn $method(self, rhs: $ty) -> Self {
+                $trait::$method(self, self.shift_amount_operand($to_constant(self, rhs)))
+            }
+        }
+    };
+}
+
+macro_rules! impl_integer_operand {
+    ($ty:ty, $to_constant:expr) => {
+        impl<'a> SignalOperand<'a> for $ty {
+            #[track_caller]
+            fn into_signal(self, lhs: &'a Signal<'a>) -> &'a Signal<'a> {
+                lhs.integer_operand($to_constant(lhs, self))
+            }
+        }
+
+        impl_integer_operand_binop!($ty, $to_constant, Add, add);
+        impl_integer_operand_binop!($ty, $to_constant, Sub, sub);
+        impl_integer_operand_binop!($ty, $to_constant, BitAnd, bitand);
+        impl_integer_operand_binop!($ty, $to_constant, BitOr, bitor);
+        impl_integer_operand_binop!($ty, $to_constant, BitXor, bitxor);
+        impl_integer_operand_shift!($ty, $to_constant, Shl, shl);
+        impl_integer_operand_shift!($ty, $to_constant, Shr, shr);
+    };
 }
 
-#[derive(Clone, Copy)]
+impl_integer_operand!(u8, |_, value: u8| Constant::from(value));
+impl_integer_operand!(u16, |_, value: u16| Constant::from(value));
+impl_integer_operand!(u32, |_, value: u32| Constant::from(value));
+impl_integer_operand!(u64, |_, value: u64| Constant::from(value));
+impl_integer_operand!(u128, |_, value: u128| Constant::from(value));
+impl_integer_operand!(i32, non_negative_operand);
+
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
 pub(crate) enum UnOp {
     Not,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
 pub(crate) enum SimpleBinOp {
     BitAnd,
     BitOr,
     BitXor,
 }
 
-#[derive(Clone, Copy)]
-pub(crate) enum ComparisonBinOp {
-    Equal,
-    GreaterThan,
-    GreaterThanEqual,
-    GreaterThanEqualSigned,
-    GreaterThanSigned,
-    LessThan,
-    LessThanEqual,
-    LessThanEqualSigned,
-    LessThanSigned,
-    NotEqual,
-}
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub(crate) enum ComparisonBinOp {
+    Equal,
+    GreaterThan,
+    GreaterThanEqual,
+    GreaterThanEqualSigned,
+    GreaterThanSigned,
+    LessThan,
+    LessThanEqual,
+    LessThanEqualSigned,
+    LessThanSigned,
+    NotEqual,
+}
+
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub(crate) enum AdditiveBinOp {
+    Add,
+    Sub,
+}
+
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub(crate) enum ShiftBinOp {
+    Shl,
+    Shr,
+    ShrArithmetic,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::context::*;
+    use super::*;
+
+    #[test]
+    fn structurally_identical_signals_shared() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+        let b = m.input("b", 8);
+
+        assert!(ptr::eq(a & b, a & b));
+        assert!(ptr::eq(m.lit(3u32, 8), m.lit(3u8, 8)));
+        assert!(ptr::eq(a.bits(3, 0), a.bits(3, 0)));
+        assert!(ptr::eq(m.mux(a.bit(0), a, b), m.mux(a.bit(0), a, b)));
+
+        // Different operators, operand orders, and parameters aren't shared
+        assert!(!ptr::eq(a & b, a | b));
+        assert!(!ptr::eq(a - b, b - a));
+        assert!(!ptr::eq(m.lit(3u32, 8), m.lit(3u32, 9)));
+        assert!(!ptr::eq(a.bits(3, 0), a.bits(4, 1)));
+
+        // Signals from different modules are never shared
+        let m2 = c.module("B");
+        assert!(!ptr::eq(m.lit(3u32, 8), m2.lit(3u32, 8)));
+    }
+
+    #[test]
+    fn state_elements_not_shared() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let r1 = m.reg("r", 8);
+        let r2 = m.reg("r", 8);
+        assert!(!ptr::eq(r1.value, r2.value));
+
+        let mem = m.mem("mem", 1, 8);
+        let address = m.input("address", 1);
+        assert!(!ptr::eq(
+            mem.read_port(address, m.high()),
+            mem.read_port(address, m.high())
+        ));
+    }
+
+    #[test]
+    fn visit_counts_nodes() {
+        let c = Context::new();
+
+        // From the Signal doc example
+        let m = c.module("MyModule");
+        let a = m.lit(0xffu8, 8);
+        let b = m.input("my_input", 27);
+        let c_ = b.bits(7, 0);
+        let d = a + c_;
+        m.output("my_output", d);
+
+        let mut count = 0;
+        d.visit(&mut |_| count += 1);
+        // d, a, c_, b
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn visit_each_node_once() {
+        let c = Context::new();
+
+        let m = c.module("MyModule");
+        let i = m.input("i", 8);
+        let shared = i + m.lit(1u32, 8);
+        let r = m.reg("r", 8);
+        r.drive_next(m.mux(m.input("sel", 1), shared, r.value ^ shared));
+        m.output("o", r.value);
+
+        let mut visited = Vec::new();
+        r.value.visit(&mut |s| visited.push(s));
+        // r.value, mux, sel, shared, i, lit, xor
+        assert_eq!(visited.len(), 7);
+        assert!(visited[0] == r.value);
+        assert_eq!(visited.iter().filter(|&&s| s == shared).count(), 1);
+        assert_eq!(visited.iter().filter(|&&s| s == r.value).count(), 1);
+    }
+
+    #[test]
+    fn visit_through_mems_and_instances() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("i", 4));
+
+        let m = c.module("MyModule");
+        let mem = m.mem("mem", 1, 4);
+        let inst = m.instance("inner", "Inner");
+        inst.drive_input("i", m.input("i", 4));
+        mem.write_port(m.input("wa", 1), inst.output("o"), m.input("we", 1));
+        let read = mem.read_port_comb(m.input("ra", 1));
+
+        let mut count = 0;
+        read.visit(&mut |_| count += 1);
+        // read, ra, wa, inst.o, i, we
+        assert_eq!(count, 6);
+        assert_eq!(inst.output("o").dependencies().len(), 1);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to name a signal \"a\" in module \"A\", but this module already contains a signal with this name."
+    )]
+    fn name_already_used_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let _ = m.high().name("a");
+
+        // Panic
+        let _ = m.low().name("a");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to take bit index 3 from a signal with a width of 3 bits. Bit indices must be in the range [0, 2] for a signal with a width of 3 bits."
+    )]
+    fn bit_index_oob_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 3);
+
+        let _ = i.bit(0); // OK
+        let _ = i.bit(1); // OK
+        let _ = i.bit(2); // OK
+
+        let _ = i.bit(3); // Panic, `index` too high
+    }
+
+    #[test]
+    #[should_panic(expected = "Attempted to combine signals from different modules.")]
+    fn bit_dyn_separate_module_error() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let b = c.module("B");
+
+        // Panic
+        let _ = a.input("i", 9).bit_dyn(b.input("index", 4));
+    }
+
+    #[test]
+    fn bit_dyn_bit_width() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 9);
+
+        assert_eq!(i.bit_dyn(m.input("narrow_index", 1)).bit_width(), 1);
+        assert_eq!(i.bit_dyn(m.input("wide_index", 64)).bit_width(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attempted to combine signals from different modules.")]
+    fn bits_dyn_separate_module_error() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let b = c.module("B");
 
-#[derive(Clone, Copy)]
-pub(crate) enum AdditiveBinOp {
-    Add,
-    Sub,
-}
+        // Panic
+        let _ = a.input("i", 9).bits_dyn(b.input("offset", 4), 2);
+    }
 
-#[derive(Clone, Copy)]
-pub(crate) enum ShiftBinOp {
-    Shl,
-    Shr,
-    ShrArithmetic,
-}
+    #[test]
+    #[should_panic(
+        expected = "Attempted to take 0 bit(s) at a dynamic offset from a signal with 9 bit(s), but at least 1 bit and at most the signal's bit width must be taken."
+    )]
+    fn bits_dyn_zero_bit_width_error() {
+        let c = Context::new();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let m = c.module("A");
+
+        // Panic
+        let _ = m.input("i", 9).bits_dyn(m.input("offset", 4), 0);
+    }
 
     #[test]
     #[should_panic(
-        expected = "Attempted to take bit index 3 from a signal with a width of 3 bits. Bit indices must be in the range [0, 2] for a signal with a width of 3 bits."
+        expected = "Attempted to take 10 bit(s) at a dynamic offset from a signal with 9 bit(s), but at least 1 bit and at most the signal's bit width must be taken."
     )]
-    fn bit_index_oob_error() {
+    fn bits_dyn_bit_width_too_large_error() {
         let c = Context::new();
 
         let m = c.module("A");
-        let i = m.input("i", 3);
 
-        let _ = i.bit(0); // OK
-        let _ = i.bit(1); // OK
-        let _ = i.bit(2); // OK
+        // Panic
+        let _ = m.input("i", 9).bits_dyn(m.input("offset", 4), 10);
+    }
 
-        let _ = i.bit(3); // Panic, `index` too high
+    #[test]
+    fn bits_dyn_bit_width() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 128);
+
+        assert_eq!(i.bits_dyn(m.input("narrow_offset", 1), 1).bit_width(), 1);
+        assert_eq!(i.bits_dyn(m.input("wide_offset", 64), 128).bit_width(), 128);
     }
 
     #[test]
@@ -1793,6 +3215,73 @@ mod tests {
         let _ = i1.ge_signed(i2);
     }
 
+    #[test]
+    #[should_panic(expected = "Signals have different bit widths (3 and 5, respectively).")]
+    fn min_incompatible_bit_widths_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i1 = m.input("a", 3);
+        let i2 = m.input("b", 5);
+
+        // Panic
+        let _ = i1.min(i2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot perform signed comparison of 1-bit signals.")]
+    fn max_signed_bit_width_1_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i1 = m.input("a", 1);
+        let i2 = m.input("b", 1);
+
+        // Panic
+        let _ = i1.max_signed(i2);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to clamp a signal to the range from 10 to 9, but the lower bound is greater than the upper bound."
+    )]
+    fn clamp_lo_gt_hi_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("a", 8);
+
+        // Panic
+        let _ = i.clamp(10, 9);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to clamp a signal to the range from 1 to -1 (as signed values), but the lower bound is greater than the upper bound."
+    )]
+    fn clamp_signed_lo_gt_hi_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("a", 8);
+
+        // Panic
+        let _ = i.clamp_signed(1, 0xff);
+    }
+
+    #[test]
+    fn clamp_signal_bounds() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("a", 8);
+
+        // Bounds that aren't both literals can't be checked, and don't cause a panic
+        let _ = i.clamp(m.input("lo", 8), 0);
+        // Bounds that are in order as signed values are accepted, even if they aren't as unsigned values
+        let _ = i.clamp_signed(0xff, 1);
+    }
+
     #[test]
     #[should_panic(expected = "Attempted to combine signals from different modules.")]
     fn shr_arithmetic_separate_module_error() {
@@ -1809,7 +3298,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Attempted to combine signals from different modules.")]
+    #[should_panic(
+        expected = "Attempted to multiplex signals from different modules. The \"when_true\" signal belongs to module \"B\", but the multiplexer is being created in module \"A\"."
+    )]
     fn mux_cond_separate_module_error() {
         let c = Context::new();
 
@@ -1825,7 +3316,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Attempted to combine signals from different modules.")]
+    #[should_panic(
+        expected = "Attempted to multiplex signals from different modules. The \"when_true\" signal belongs to module \"A\", but the multiplexer is being created in module \"B\"."
+    )]
     fn mux_when_true_separate_module_error() {
         let c = Context::new();
 
@@ -1841,7 +3334,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Attempted to combine signals from different modules.")]
+    #[should_panic(
+        expected = "Attempted to multiplex signals from different modules. The \"when_false\" signal belongs to module \"A\", but the multiplexer is being created in module \"B\"."
+    )]
     fn mux_when_false_separate_module_error() {
         let c = Context::new();
 
@@ -1857,7 +3352,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Multiplexer conditionals can only be 1 bit wide.")]
+    #[should_panic(
+        expected = "Multiplexer conditionals can only be 1 bit wide, but the condition has 2 bit(s)."
+    )]
     fn mux_cond_bit_width_error() {
         let c = Context::new();
 
@@ -2055,4 +3552,280 @@ mod tests {
         // Panic
         let _ = i1 - i2;
     }
+
+    #[test]
+    fn try_variants_return_errors() {
+        let c = Context::new();
+
+        let m1 = c.module("A");
+        let a = m1.input("a", 3);
+        let b = m1.input("b", 5);
+
+        let m2 = c.module("B");
+        let i = m2.high();
+
+        assert_eq!(a.try_bit(3).err().map(|e| e.to_string()), Some(format!("Attempted to take bit index 3 from a signal with a width of 3 bits. Bit indices must be in the range [0, 2] for a signal with a width of 3 bits. The signal was created at {}.", a.location)));
+        assert_eq!(
+            a.try_bits(1, 2).err(),
+            Some(GraphError::BitRangeInverted {
+                range_high: 1,
+                range_low: 2,
+            })
+        );
+        assert_eq!(a.try_concat(i).err(), Some(GraphError::DifferentModules));
+        for result in &[
+            a.try_add(b),
+            a.try_sub(b),
+            a.try_bitand(b),
+            a.try_bitor(b),
+            a.try_bitxor(b),
+        ] {
+            assert_eq!(
+                result.as_ref().err(),
+                Some(&GraphError::BitWidthMismatch {
+                    lhs_bit_width: 3,
+                    rhs_bit_width: 5,
+                    lhs_location: a.location,
+                    rhs_location: b.location,
+                })
+            );
+        }
+
+        // Errors don't create any signals
+        let signal_count = m1.signals.borrow().len();
+        assert!(a.try_bitor(i).is_err());
+        assert_eq!(m1.signals.borrow().len(), signal_count);
+    }
+
+    #[test]
+    fn integer_operands() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        for &bit_width in &[1, 32, 128] {
+            let a = m.input(format!("a{}", bit_width), bit_width);
+            let one = m.lit(1u32, bit_width);
+
+            assert!(ptr::eq(a + 1u32, a + one));
+            assert!(ptr::eq(1u32 + a, one + a));
+            assert!(ptr::eq(a - 1u8, a - one));
+            assert!(ptr::eq(1u16 - a, one - a));
+            assert!(ptr::eq(a & 1u64, a & one));
+            assert!(ptr::eq(1u128 & a, one & a));
+            // Unsuffixed integer literals default to i32
+            let or: &Signal = a | 1;
+            assert!(ptr::eq(or, a | one));
+            let or: &Signal = 1 | a;
+            assert!(ptr::eq(or, one | a));
+            assert!(ptr::eq(a ^ 1u32, a ^ one));
+            assert!(ptr::eq(1u32 ^ a, one ^ a));
+            assert!(ptr::eq(a << 1u32, a << m.lit(1u32, 1)));
+            assert!(ptr::eq(a >> 5u32, a >> m.lit(5u32, 3)));
+
+            assert!(ptr::eq(a.eq(1u32), a.eq(one)));
+            assert!(ptr::eq(a.ne(1u32), a.ne(one)));
+            assert!(ptr::eq(a.lt(1u32), a.lt(one)));
+            assert!(ptr::eq(a.le(1u32), a.le(one)));
+            assert!(ptr::eq(a.gt(1u32), a.gt(one)));
+            assert!(ptr::eq(a.ge(1u32), a.ge(one)));
+        }
+
+        let a = m.input("a", 128);
+        assert!(ptr::eq(
+            a & 0xffff_0000_0000_0000_0000_0000_0000_0000u128,
+            a & m.lit(0xffff_0000_0000_0000_0000_0000_0000_0000u128, 128)
+        ));
+        assert!(ptr::eq(
+            a.lt_signed(0x8000u32),
+            a.lt_signed(m.lit(0x8000u32, 128))
+        ));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to combine a 8-bit signal with the integer 256, which requires 9 bit(s) and doesn't fit in the signal's bit width."
+    )]
+    fn integer_operand_too_wide_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+
+        // Panic
+        let _ = a + 256u32;
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to combine a 1-bit signal with the integer 2, which requires 2 bit(s) and doesn't fit in the signal's bit width."
+    )]
+    fn integer_operand_lhs_too_wide_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 1);
+
+        // Panic
+        let _ = 2u8 - a;
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to combine a 32-bit signal with the integer 4294967296, which requires 33 bit(s) and doesn't fit in the signal's bit width."
+    )]
+    fn integer_operand_comparison_too_wide_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 32);
+
+        // Panic
+        let _ = a.eq(0x1_0000_0000u64);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to combine a 8-bit signal with the negative integer -1, but integer operands must not be negative."
+    )]
+    fn integer_operand_negative_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+
+        // Panic
+        let _ = a & -1;
+    }
+
+    #[test]
+    fn msb_lsb_head_tail() {
+        let c = Context::new();
+
+        let m = c.module("A");
+
+        for &bit_width in &[1, 8, 128] {
+            let a = m.input(format!("a{}", bit_width), bit_width);
+
+            assert!(ptr::eq(a.msb(), a.bit(bit_width - 1)));
+            assert!(ptr::eq(a.lsb(), a.bit(0)));
+            assert!(ptr::eq(a.head(1), a.msb()));
+            assert!(ptr::eq(a.head(bit_width), a.bits(bit_width - 1, 0)));
+            assert!(ptr::eq(a.tail(0), a.bits(bit_width - 1, 0)));
+            assert!(ptr::eq(a.tail(bit_width - 1), a.lsb()));
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to take the top 0 bit(s) of a signal with 8 bit(s), but at least 1 bit and at most the signal's bit width must be taken."
+    )]
+    fn head_zero_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+
+        // Panic
+        let _ = a.head(0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to take the top 9 bit(s) of a signal with 8 bit(s), but at least 1 bit and at most the signal's bit width must be taken."
+    )]
+    fn head_too_wide_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+
+        // Panic
+        let _ = a.head(9);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Attempted to drop the top 8 bit(s) of a signal with 8 bit(s), but at least 1 bit must remain."
+    )]
+    fn tail_too_wide_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+
+        // Panic
+        let _ = a.tail(8);
+    }
+
+    #[test]
+    fn chunks() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 128);
+
+        let bytes = a.bytes();
+        assert_eq!(bytes.len(), 16);
+        for (i, byte) in bytes.iter().enumerate() {
+            let i = i as u32;
+            assert!(ptr::eq(*byte, a.bits(i * 8 + 7, i * 8)));
+        }
+        assert!(ptr::eq(m.pack(&bytes), m.pack(&a.chunks(8))));
+        assert_eq!(a.chunks(128).len(), 1);
+        assert_eq!(a.chunks(1).len(), 128);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot split a signal with a width of 100 bit(s) into chunks of 8 bit(s), as its width isn't a multiple of the chunk width."
+    )]
+    fn chunks_indivisible_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 100);
+
+        // Panic
+        let _ = a.chunks(8);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot split a signal with a width of 100 bit(s) into chunks of 8 bit(s), as its width isn't a multiple of the chunk width."
+    )]
+    fn bytes_indivisible_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 100);
+
+        // Panic
+        let _ = a.bytes();
+    }
+
+    #[test]
+    fn reverse_chunks() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 16);
+
+        assert!(ptr::eq(a.swap_bytes(), a.bits(7, 0).concat(a.bits(15, 8))));
+        assert!(ptr::eq(a.reverse_chunks(16), a.bits(15, 0)));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot reverse the 8-bit chunks of a signal with a width of 12 bit(s), as its width isn't a multiple of the chunk width."
+    )]
+    fn swap_bytes_indivisible_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 12);
+
+        // Panic
+        let _ = a.swap_bytes();
+    }
 }
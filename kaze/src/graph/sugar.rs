@@ -2,8 +2,12 @@ use super::signal::*;
 
 /// **UNSTABLE:** Provides a convenient way to write conditional combinational logic.
 ///
+/// Conditions are checked in order, so the value of the first branch whose condition is high is selected, and the `else_` value is selected if none of them are.
+/// A branch's value can be a single [`Signal`] or a tuple of up to 8 [`Signal`]s, in which case each element is selected independently, which keeps related assignments grouped together.
+///
 /// # Panics
 ///
+/// Panics if `cond` (or the condition passed to any subsequent `else_if`) isn't 1 bit wide.
 /// Since this construct wraps the returned values with [`mux`], any panic conditions from that method apply to the generated code as well.
 ///
 /// # Examples
@@ -22,14 +26,37 @@ use super::signal::*;
 ///     i
 /// });
 /// m.output("o", o);
+///
+/// // Earlier conditions take priority over later ones
+/// let count = m.reg("count", 8);
+/// count.default_value(0u32);
+/// let (count_next, overflow) = if_(m.input("clear", 1), (m.lit(0u32, 8), m.low()))
+///     .else_if(m.input("en", 1), (count.value + m.lit(1u32, 8), count.value.eq(m.lit(0xffu32, 8))))
+///     .else_((count.value, m.low()));
+/// count.drive_next(count_next);
+/// m.output("count", count.value);
+/// m.output("overflow", overflow);
 /// ```
 ///
 /// [`mux`]: ./struct.Signal.html#method.mux
+/// [`Signal`]: ./struct.Signal.html
 // TODO: Can we constrain T more than this to make sure it's only a supported type?
+#[track_caller]
 pub fn if_<'a, T>(cond: &'a Signal<'a>, when_true: T) -> If<'a, T> {
     If::new(cond, when_true)
 }
 
+#[track_caller]
+fn check_cond(cond: &Signal) {
+    if cond.bit_width() != 1 {
+        panic!(
+            "Conditions passed to if_ and else_if must be 1 bit wide, but a condition with a width of {} bits was given. The condition was created at {}.",
+            cond.bit_width(),
+            cond.location
+        );
+    }
+}
+
 #[doc(hidden)]
 pub struct If<'a, T> {
     cond: &'a Signal<'a>,
@@ -37,11 +64,15 @@ pub struct If<'a, T> {
 }
 
 impl<'a, T> If<'a, T> {
+    #[track_caller]
     fn new(cond: &'a Signal<'a>, when_true: T) -> If<'a, T> {
+        check_cond(cond);
         If { cond, when_true }
     }
 
+    #[track_caller]
     pub fn else_if(self, cond: &'a Signal<'a>, when_true: T) -> ElseIf<'a, T> {
+        check_cond(cond);
         ElseIf {
             parent: ElseIfParent::If(self),
             cond,
@@ -51,6 +82,7 @@ impl<'a, T> If<'a, T> {
 }
 
 impl<'a> If<'a, &'a Signal<'a>> {
+    #[track_caller]
     pub fn else_(self, when_false: &'a Signal<'a>) -> &Signal<'a> {
         self.cond.mux(self.when_true, when_false)
     }
@@ -58,12 +90,14 @@ impl<'a> If<'a, &'a Signal<'a>> {
 
 // TODO: Come up with a nice way to generate these definitions with macros
 impl<'a> If<'a, (&'a Signal<'a>,)> {
+    #[track_caller]
     pub fn else_(self, when_false: (&'a Signal<'a>,)) -> (&Signal<'a>,) {
         (self.cond.mux(self.when_true.0, when_false.0),)
     }
 }
 
 impl<'a> If<'a, (&'a Signal<'a>, &'a Signal<'a>)> {
+    #[track_caller]
     pub fn else_(self, when_false: (&'a Signal<'a>, &'a Signal<'a>)) -> (&Signal<'a>, &Signal<'a>) {
         (
             self.cond.mux(self.when_true.0, when_false.0),
@@ -73,6 +107,7 @@ impl<'a> If<'a, (&'a Signal<'a>, &'a Signal<'a>)> {
 }
 
 impl<'a> If<'a, (&'a Signal<'a>, &'a Signal<'a>, &'a Signal<'a>)> {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (&'a Signal<'a>, &'a Signal<'a>, &'a Signal<'a>),
@@ -96,6 +131,7 @@ impl<'a>
         ),
     >
 {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (
@@ -126,6 +162,7 @@ impl<'a>
         ),
     >
 {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (
@@ -165,6 +202,7 @@ impl<'a>
         ),
     >
 {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (
@@ -208,6 +246,7 @@ impl<'a>
         ),
     >
 {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (
@@ -255,6 +294,7 @@ impl<'a>
         ),
     >
 {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (
@@ -303,7 +343,9 @@ pub struct ElseIf<'a, T> {
 }
 
 impl<'a, T> ElseIf<'a, T> {
+    #[track_caller]
     pub fn else_if(self, cond: &'a Signal<'a>, when_true: T) -> ElseIf<'a, T> {
+        check_cond(cond);
         ElseIf {
             parent: ElseIfParent::ElseIf(Box::new(self)),
             cond,
@@ -313,6 +355,7 @@ impl<'a, T> ElseIf<'a, T> {
 }
 
 impl<'a> ElseIf<'a, &'a Signal<'a>> {
+    #[track_caller]
     pub fn else_(self, when_false: &'a Signal<'a>) -> &Signal<'a> {
         let ret = self.cond.mux(self.when_true, when_false);
         match self.parent {
@@ -324,6 +367,7 @@ impl<'a> ElseIf<'a, &'a Signal<'a>> {
 
 // TODO: Come up with a nice way to generate these definitions with macros
 impl<'a> ElseIf<'a, (&'a Signal<'a>,)> {
+    #[track_caller]
     pub fn else_(self, when_false: (&'a Signal<'a>,)) -> (&Signal<'a>,) {
         let ret = (self.cond.mux(self.when_true.0, when_false.0),);
         match self.parent {
@@ -334,6 +378,7 @@ impl<'a> ElseIf<'a, (&'a Signal<'a>,)> {
 }
 
 impl<'a> ElseIf<'a, (&'a Signal<'a>, &'a Signal<'a>)> {
+    #[track_caller]
     pub fn else_(self, when_false: (&'a Signal<'a>, &'a Signal<'a>)) -> (&Signal<'a>, &Signal<'a>) {
         let ret = (
             self.cond.mux(self.when_true.0, when_false.0),
@@ -347,6 +392,7 @@ impl<'a> ElseIf<'a, (&'a Signal<'a>, &'a Signal<'a>)> {
 }
 
 impl<'a> ElseIf<'a, (&'a Signal<'a>, &'a Signal<'a>, &'a Signal<'a>)> {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (&'a Signal<'a>, &'a Signal<'a>, &'a Signal<'a>),
@@ -374,6 +420,7 @@ impl<'a>
         ),
     >
 {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (
@@ -408,6 +455,7 @@ impl<'a>
         ),
     >
 {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (
@@ -451,6 +499,7 @@ impl<'a>
         ),
     >
 {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (
@@ -498,6 +547,7 @@ impl<'a>
         ),
     >
 {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (
@@ -549,6 +599,7 @@ impl<'a>
         ),
     >
 {
+    #[track_caller]
     pub fn else_(
         self,
         when_false: (
@@ -587,3 +638,77 @@ impl<'a>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::*;
+
+    #[test]
+    #[should_panic(
+        expected = "Conditions passed to if_ and else_if must be 1 bit wide, but a condition with a width of 2 bits was given."
+    )]
+    fn if_cond_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 2);
+
+        let _ = if_(i, m.low());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Conditions passed to if_ and else_if must be 1 bit wide, but a condition with a width of 3 bits was given."
+    )]
+    fn else_if_cond_bit_width_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i1 = m.input("i1", 1);
+        let i2 = m.input("i2", 3);
+
+        // Reported at the offending else_if, before the mux chain is built by else_
+        let _ = if_(i1, m.low()).else_if(i1, m.high()).else_if(i2, m.low());
+    }
+
+    #[test]
+    fn first_condition_wins() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 1);
+        let b = m.input("b", 1);
+        let x = m.input("x", 4);
+        let y = m.input("y", 4);
+        let z = m.input("z", 4);
+
+        let o = if_(a, x).else_if(b, y).else_(z);
+
+        // Equivalent to a.mux(x, b.mux(y, z))
+        match o.data {
+            SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            } => {
+                assert!(cond == a);
+                assert!(when_true == x);
+                match when_false.data {
+                    SignalData::Mux {
+                        cond,
+                        when_true,
+                        when_false,
+                    } => {
+                        assert!(cond == b);
+                        assert!(when_true == y);
+                        assert!(when_false == z);
+                    }
+                    _ => panic!("Expected a mux"),
+                }
+            }
+            _ => panic!("Expected a mux"),
+        }
+    }
+}
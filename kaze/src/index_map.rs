@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Index;
+
+/// A map that iterates over its entries in insertion order.
+///
+/// Code generators key their declarations by graph node, and iterating over a `HashMap` would make the generated code's declaration order (and therefore its text) vary between runs.
+pub struct IndexMap<K, V> {
+    indices: HashMap<K, usize>,
+    entries: Vec<(K, V)>,
+}
+
+impl<K: Clone + Eq + Hash, V> IndexMap<K, V> {
+    pub fn new() -> IndexMap<K, V> {
+        IndexMap {
+            indices: HashMap::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Inserts `value` for `key`, replacing (but keeping the position of) any existing entry for `key`.
+    pub fn insert(&mut self, key: K, value: V) {
+        match self.indices.get(&key) {
+            Some(&index) => self.entries[index].1 = value,
+            None => {
+                self.indices.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.indices.get(key).map(|&index| &self.entries[index].1)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.indices.contains_key(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<K: Clone + Eq + Hash, V> Index<&K> for IndexMap<K, V> {
+    type Output = V;
+
+    fn index(&self, key: &K) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insertion_order() {
+        let mut map = IndexMap::new();
+        for key in (0..100).rev() {
+            map.insert(key, key * 2);
+        }
+
+        assert_eq!(map.len(), 100);
+        assert_eq!(
+            map.iter().map(|(&key, _)| key).collect::<Vec<_>>(),
+            (0..100).rev().collect::<Vec<_>>()
+        );
+        assert_eq!(map[&10], 20);
+        assert_eq!(map.get(&100), None);
+        assert!(map.contains_key(&99));
+    }
+
+    #[test]
+    fn replacing_keeps_position() {
+        let mut map = IndexMap::new();
+        map.insert("a", 1);
+        map.insert("b", 2);
+        map.insert("a", 3);
+
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&"a", &3), (&"b", &2)]);
+        assert_eq!(map.values().sum::<i32>(), 5);
+    }
+}
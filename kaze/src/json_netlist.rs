@@ -0,0 +1,1182 @@
+//! [Yosys](https://yosyshq.net/yosys/)-compatible JSON netlist generation, for use with tools that consume Yosys's `write_json` output (such as [nextpnr](https://github.com/YosysHQ/nextpnr)).
+
+use crate::analysis::*;
+use crate::code_writer;
+use crate::graph;
+use crate::index_map::*;
+use crate::validation::*;
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::{Result, Write};
+
+/// Generates a Yosys JSON netlist for the `Module` `m` and writes it to `w`.
+///
+/// Like [`firrtl::generate`], the netlist contains a module for each `Module` in `m`'s hierarchy (instantiated `Module`s first).
+/// Each module has `reset_n` and `clk` ports (unless its hierarchy is purely combinational, and renamed as specified by [`Module::clock_port_name`] and [`Module::reset_port_name`]) followed by its inputs and outputs, and every net is bit-blasted into Yosys's bit indices, with constant bits represented as `"0"` and `"1"`.
+///
+/// Operators map to the corresponding Yosys internal cells (`$not`, `$and`, `$or`, `$xor`, `$add`, `$sub`, `$eq`, `$ne`, `$lt`, `$le`, `$gt`, `$ge`, `$shl`, `$shr`, `$sshr`, and `$mux`), while bit slices, concatenations, and repetitions are expressed purely by connecting bits.
+/// [`Register`]s map to `$dff` cells clocked by `clk`, or to `$adff` cells that are reset asynchronously while `reset_n` is low if they have initial values, as in generated Verilog.
+/// [`Latch`]es map to `$dlatch` cells that are transparent while their enable signals are high.
+/// Each [`Mem`] maps to a single `$mem_v2` cell with its read ports followed by its combinational read ports, and its write ports in the order they were specified (later ports taking priority).
+/// [`Instance`]s map to cells whose type is the name of the instantiated `Module`, with any parameters specified by [`Instance::parameter`].
+/// Extern `Module`s aren't included in the netlist, so tools treat their instances as black boxes (or as their own primitives).
+/// Assertions, assumptions, covers, and traces aren't emitted.
+///
+/// Ports, [`Register`]s (called `__reg_{name}_{index}`), [`Latch`]es (called `__latch_{name}_{index}`), and `Signal`s named with [`Signal::name`] are included as named nets.
+///
+/// [`firrtl::generate`]: ../firrtl/fn.generate.html
+/// [`Instance`]: ../struct.Instance.html
+/// [`Instance::parameter`]: ../struct.Instance.html#method.parameter
+/// [`Latch`]: ../struct.Latch.html
+/// [`Mem`]: ../struct.Mem.html
+/// [`Module::clock_port_name`]: ../struct.Module.html#method.clock_port_name
+/// [`Module::reset_port_name`]: ../struct.Module.html#method.reset_port_name
+/// [`Register`]: ../struct.Register.html
+/// [`Signal::name`]: ../struct.Signal.html#method.name
+///
+/// # Panics
+///
+/// Panics if `m`'s hierarchy contains errors (for example, undriven registers or combinational loops), or if it contains a [`Mem`] with more than 31 address bits.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("Adder");
+/// m.output("sum", m.input("a", 8) + m.input("b", 8));
+///
+/// let mut w = Vec::new();
+/// json_netlist::generate(m, &mut w).unwrap();
+/// let json = String::from_utf8(w).unwrap();
+/// assert!(json.contains("\"Adder\": {"));
+/// assert!(json.contains("\"type\": \"$add\""));
+/// ```
+pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
+    validate_module_hierarchy(m);
+
+    let mut w = code_writer::CodeWriter::with_style(w, crate::CodeStyle::new().indent("  "));
+
+    w.append_line("{")?;
+    w.indent();
+    w.append_line("\"creator\": \"kaze\",")?;
+    w.append_line("\"modules\": {")?;
+    w.indent();
+    // Extern modules are left undefined, so tools treat their instances as black boxes (or their own primitives)
+    let modules = hierarchy_modules(m)
+        .into_iter()
+        .filter(|module| !module.is_extern)
+        .collect::<Vec<_>>();
+    for (index, module) in modules.iter().enumerate() {
+        w.append_indent()?;
+        w.append(&format!("{}: ", quote(&module.name)))?;
+        generate_module(module).write(&mut w)?;
+        if index < modules.len() - 1 {
+            w.append(",")?;
+        }
+        w.append_newline()?;
+    }
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Bit {
+    Net(u32),
+    Zero,
+    One,
+    Undefined,
+}
+
+impl Bit {
+    fn from_bool(value: bool) -> Bit {
+        if value {
+            Bit::One
+        } else {
+            Bit::Zero
+        }
+    }
+
+    fn json(&self) -> Json {
+        match *self {
+            Bit::Net(index) => Json::Number(index as u64),
+            Bit::Zero => Json::String("0".into()),
+            Bit::One => Json::String("1".into()),
+            Bit::Undefined => Json::String("x".into()),
+        }
+    }
+}
+
+// Returns `bit_width` bits of `value`, least significant bit first
+fn constant_bits(value: u128, bit_width: u32) -> Vec<Bit> {
+    (0..bit_width)
+        .map(|index| Bit::from_bool(index < 128 && (value >> index) & 1 != 0))
+        .collect()
+}
+
+// Yosys represents non-string parameters as strings of bits, most significant bit first
+fn const_param(bits: &[Bit]) -> Json {
+    Json::String(
+        bits.iter()
+            .rev()
+            .map(|bit| match bit {
+                Bit::One => '1',
+                Bit::Undefined => 'x',
+                _ => '0',
+            })
+            .collect(),
+    )
+}
+
+fn int_param(value: u32) -> Json {
+    const_param(&constant_bits(value as u128, 32))
+}
+
+fn parameter_value_param(value: &graph::ParameterValue) -> Json {
+    match *value {
+        graph::ParameterValue::Integer(value) => {
+            let bit_width = if (i32::MIN as i64..=i32::MAX as i64).contains(&value) {
+                32
+            } else {
+                64
+            };
+            const_param(&constant_bits(value as i128 as u128, bit_width))
+        }
+        graph::ParameterValue::Real(value) => Json::String(format!("{:?}", value)),
+        graph::ParameterValue::String(ref value) => {
+            // Yosys appends a space to strings that would otherwise be read back as bit strings
+            if value.chars().all(|c| matches!(c, '0' | '1' | 'x' | 'z')) {
+                Json::String(format!("{} ", value))
+            } else {
+                Json::String(value.clone())
+            }
+        }
+    }
+}
+
+fn bool_param(value: bool) -> Json {
+    int_param(value as u32)
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Input,
+    Output,
+}
+
+struct ModuleBuilder<'a> {
+    next_net: u32,
+    used_cell_names: HashSet<String>,
+    cells: Vec<(String, Json)>,
+
+    clk: Bit,
+    reset_n: Bit,
+    signal_bits: IndexMap<&'a graph::Signal<'a>, Vec<Bit>>,
+    instance_output_bits: IndexMap<&'a graph::Instance<'a>, BTreeMap<String, Vec<Bit>>>,
+    read_port_bits: IndexMap<&'a graph::Mem<'a>, ReadPortBits>,
+}
+
+// The data bits of a memory's read ports, followed by those of its combinational read ports
+type ReadPortBits = (Vec<Vec<Bit>>, Vec<Vec<Bit>>);
+
+impl<'a> ModuleBuilder<'a> {
+    fn alloc(&mut self, bit_width: u32) -> Vec<Bit> {
+        let ret = (self.next_net..self.next_net + bit_width)
+            .map(Bit::Net)
+            .collect();
+        self.next_net += bit_width;
+        ret
+    }
+
+    // Returns a cell name based on `name` that isn't used by any other cell in this module
+    fn unique_cell_name(&mut self, name: &str) -> String {
+        let mut ret = name.to_string();
+        let mut suffix = 0;
+        while self.used_cell_names.contains(&ret) {
+            ret = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        self.used_cell_names.insert(ret.clone());
+        ret
+    }
+
+    fn cell(
+        &mut self,
+        name: Option<&str>,
+        cell_type: &str,
+        parameters: Vec<(&str, Json)>,
+        connections: Vec<(&str, Direction, Vec<Bit>)>,
+    ) {
+        let (name, hide_name) = match name {
+            Some(name) => (self.unique_cell_name(name), false),
+            _ => {
+                let name = format!("{}${}", cell_type, self.cells.len());
+                (self.unique_cell_name(&name), true)
+            }
+        };
+        let cell = Json::Object(vec![
+            ("hide_name".into(), Json::Number(hide_name as u64)),
+            ("type".into(), Json::String(cell_type.into())),
+            (
+                "parameters".into(),
+                Json::Object(
+                    parameters
+                        .into_iter()
+                        .map(|(name, value)| (name.into(), value))
+                        .collect(),
+                ),
+            ),
+            ("attributes".into(), Json::Object(Vec::new())),
+            (
+                "port_directions".into(),
+                Json::Object(
+                    connections
+                        .iter()
+                        .map(|(name, direction, _)| {
+                            let direction = match direction {
+                                Direction::Input => "input",
+                                Direction::Output => "output",
+                            };
+                            (name.to_string(), Json::String(direction.into()))
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                "connections".into(),
+                Json::Object(
+                    connections
+                        .into_iter()
+                        .map(|(name, _, bits)| (name.into(), bits_json(&bits)))
+                        .collect(),
+                ),
+            ),
+        ]);
+        self.cells.push((name, cell));
+    }
+
+    fn unary_cell(&mut self, cell_type: &str, a: Vec<Bit>, y_width: u32) -> Vec<Bit> {
+        let y = self.alloc(y_width);
+        self.cell(
+            None,
+            cell_type,
+            vec![
+                ("A_SIGNED", bool_param(false)),
+                ("A_WIDTH", int_param(a.len() as u32)),
+                ("Y_WIDTH", int_param(y_width)),
+            ],
+            vec![
+                ("A", Direction::Input, a),
+                ("Y", Direction::Output, y.clone()),
+            ],
+        );
+        y
+    }
+
+    fn binary_cell(
+        &mut self,
+        cell_type: &str,
+        a: Vec<Bit>,
+        b: Vec<Bit>,
+        a_signed: bool,
+        b_signed: bool,
+        y_width: u32,
+    ) -> Vec<Bit> {
+        let y = self.alloc(y_width);
+        self.cell(
+            None,
+            cell_type,
+            vec![
+                ("A_SIGNED", bool_param(a_signed)),
+                ("A_WIDTH", int_param(a.len() as u32)),
+                ("B_SIGNED", bool_param(b_signed)),
+                ("B_WIDTH", int_param(b.len() as u32)),
+                ("Y_WIDTH", int_param(y_width)),
+            ],
+            vec![
+                ("A", Direction::Input, a),
+                ("B", Direction::Input, b),
+                ("Y", Direction::Output, y.clone()),
+            ],
+        );
+        y
+    }
+
+    fn bits(&mut self, signal: &'a graph::Signal<'a>) -> Vec<Bit> {
+        // Operands are visited bottom-up with an explicit worklist first (in the same order recursion would visit them, so net and cell numbering doesn't change), so that this never recurses more than one level deep, regardless of how deep the graph is
+        let mut stack = vec![(signal, false)];
+        while let Some((signal, operands_visited)) = stack.pop() {
+            if self.signal_bits.contains_key(&signal) {
+                continue;
+            }
+            if operands_visited {
+                self.single_bits(signal);
+                continue;
+            }
+            stack.push((signal, true));
+            stack.extend(
+                operands(signal)
+                    .into_iter()
+                    .rev()
+                    .map(|operand| (operand, false)),
+            );
+        }
+
+        self.single_bits(signal)
+    }
+
+    fn single_bits(&mut self, signal: &'a graph::Signal<'a>) -> Vec<Bit> {
+        if let Some(bits) = self.signal_bits.get(&signal) {
+            return bits.clone();
+        }
+
+        let bit_width = signal.bit_width();
+        let ret = match signal.data {
+            graph::SignalData::Lit {
+                ref value,
+                bit_width,
+            } => constant_bits(value.numeric_value(), bit_width),
+
+            // Inputs and state elements are allocated up front
+            graph::SignalData::Input { .. }
+            | graph::SignalData::Reg { .. }
+            | graph::SignalData::Latch { .. } => unreachable!(),
+
+            graph::SignalData::UnOp { source, op } => {
+                let source = self.bits(source);
+                match op {
+                    graph::UnOp::Not => self.unary_cell("$not", source, bit_width),
+                }
+            }
+            graph::SignalData::SimpleBinOp { lhs, rhs, op } => {
+                let lhs = self.bits(lhs);
+                let rhs = self.bits(rhs);
+                let cell_type = match op {
+                    graph::SimpleBinOp::BitAnd => "$and",
+                    graph::SimpleBinOp::BitOr => "$or",
+                    graph::SimpleBinOp::BitXor => "$xor",
+                };
+                self.binary_cell(cell_type, lhs, rhs, false, false, bit_width)
+            }
+            graph::SignalData::AdditiveBinOp { lhs, rhs, op } => {
+                let lhs = self.bits(lhs);
+                let rhs = self.bits(rhs);
+                let cell_type = match op {
+                    graph::AdditiveBinOp::Add => "$add",
+                    graph::AdditiveBinOp::Sub => "$sub",
+                };
+                self.binary_cell(cell_type, lhs, rhs, false, false, bit_width)
+            }
+            graph::SignalData::ComparisonBinOp { lhs, rhs, op } => {
+                let lhs = self.bits(lhs);
+                let rhs = self.bits(rhs);
+                let (cell_type, signed) = match op {
+                    graph::ComparisonBinOp::Equal => ("$eq", false),
+                    graph::ComparisonBinOp::NotEqual => ("$ne", false),
+                    graph::ComparisonBinOp::LessThan => ("$lt", false),
+                    graph::ComparisonBinOp::LessThanEqual => ("$le", false),
+                    graph::ComparisonBinOp::GreaterThan => ("$gt", false),
+                    graph::ComparisonBinOp::GreaterThanEqual => ("$ge", false),
+                    graph::ComparisonBinOp::LessThanSigned => ("$lt", true),
+                    graph::ComparisonBinOp::LessThanEqualSigned => ("$le", true),
+                    graph::ComparisonBinOp::GreaterThanSigned => ("$gt", true),
+                    graph::ComparisonBinOp::GreaterThanEqualSigned => ("$ge", true),
+                };
+                self.binary_cell(cell_type, lhs, rhs, signed, signed, bit_width)
+            }
+            graph::SignalData::ShiftBinOp { lhs, rhs, op } => {
+                let lhs = self.bits(lhs);
+                let rhs = self.bits(rhs);
+                let (cell_type, a_signed) = match op {
+                    graph::ShiftBinOp::Shl => ("$shl", false),
+                    graph::ShiftBinOp::Shr => ("$shr", false),
+                    graph::ShiftBinOp::ShrArithmetic => ("$sshr", true),
+                };
+                self.binary_cell(cell_type, lhs, rhs, a_signed, false, bit_width)
+            }
+
+            graph::SignalData::Bits {
+                source,
+                range_high,
+                range_low,
+            } => self.bits(source)[range_low as usize..=range_high as usize].to_vec(),
+
+            graph::SignalData::Repeat { source, count } => {
+                let source = self.bits(source);
+                (0..count).flat_map(|_| source.iter().cloned()).collect()
+            }
+            graph::SignalData::Concat { lhs, rhs } => {
+                // Bits are ordered least significant first, so `rhs` comes first
+                let mut ret = self.bits(rhs);
+                ret.extend(self.bits(lhs));
+                ret
+            }
+
+            graph::SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            } => {
+                let cond = self.bits(cond);
+                let when_true = self.bits(when_true);
+                let when_false = self.bits(when_false);
+                let y = self.alloc(bit_width);
+                self.cell(
+                    None,
+                    "$mux",
+                    vec![("WIDTH", int_param(bit_width))],
+                    vec![
+                        ("A", Direction::Input, when_false),
+                        ("B", Direction::Input, when_true),
+                        ("S", Direction::Input, cond),
+                        ("Y", Direction::Output, y.clone()),
+                    ],
+                );
+                y
+            }
+
+            graph::SignalData::InstanceOutput { instance, name } => {
+                self.instance_output_bits[&instance][name].clone()
+            }
+
+            graph::SignalData::MemReadPortOutput {
+                mem,
+                address,
+                enable,
+            } => {
+                let index = mem
+                    .read_ports
+                    .borrow()
+                    .iter()
+                    .position(|&(a, e)| a == address && e == enable)
+                    .unwrap();
+                self.read_port_bits[&mem].0[index].clone()
+            }
+            graph::SignalData::MemCombReadPortOutput { mem, address } => {
+                let index = mem
+                    .comb_read_ports
+                    .borrow()
+                    .iter()
+                    .position(|&a| a == address)
+                    .unwrap();
+                self.read_port_bits[&mem].1[index].clone()
+            }
+        };
+
+        self.signal_bits.insert(signal, ret.clone());
+        ret
+    }
+
+    fn mem(&mut self, mem: &'a graph::Mem<'a>) {
+        let abits = mem.address_bit_width;
+        let width = mem.element_bit_width;
+        let size = match 1u32.checked_shl(abits) {
+            Some(size) => size,
+            _ => panic!("Attempted to generate a JSON netlist for memory \"{}\" in module \"{}\", but this memory has {} address bits, and Yosys can't represent memories with more than 31 address bits.", mem.name, mem.module.name, abits),
+        };
+
+        let (read_port_bits, comb_read_port_bits) = {
+            let (read_port_bits, comb_read_port_bits) = &self.read_port_bits[&mem];
+            (read_port_bits.clone(), comb_read_port_bits.clone())
+        };
+        let read_ports = mem.read_ports.borrow().clone();
+        let comb_read_ports = mem.comb_read_ports.borrow().clone();
+        let write_ports = mem.write_ports.borrow().clone();
+        let rd_ports = (read_ports.len() + comb_read_ports.len()) as u32;
+        let wr_ports = write_ports.len() as u32;
+
+        let mut rd_clk = Vec::new();
+        let mut rd_en = Vec::new();
+        let mut rd_addr = Vec::new();
+        let mut rd_data = Vec::new();
+        let mut rd_clk_enable = Vec::new();
+        for (&(address, enable), data) in read_ports.iter().zip(read_port_bits) {
+            rd_clk.push(self.clk);
+            rd_en.extend(self.bits(enable));
+            rd_addr.extend(self.bits(address));
+            rd_data.extend(data);
+            rd_clk_enable.push(Bit::One);
+        }
+        for (&address, data) in comb_read_ports.iter().zip(comb_read_port_bits) {
+            rd_clk.push(Bit::Undefined);
+            rd_en.push(Bit::One);
+            rd_addr.extend(self.bits(address));
+            rd_data.extend(data);
+            rd_clk_enable.push(Bit::Zero);
+        }
+
+        // Only synchronous read ports can be transparent or have undefined collision behavior
+        let read_during_write = *mem.read_during_write.borrow();
+        let mut rd_transparency_mask = Vec::new();
+        let mut rd_collision_x_mask = Vec::new();
+        for port in 0..rd_ports {
+            let is_sync = (port as usize) < read_ports.len();
+            for _ in 0..wr_ports {
+                rd_transparency_mask.push(Bit::from_bool(
+                    is_sync && read_during_write == graph::ReadDuringWrite::New,
+                ));
+                rd_collision_x_mask.push(Bit::from_bool(
+                    is_sync && read_during_write == graph::ReadDuringWrite::Undefined,
+                ));
+            }
+        }
+
+        let mut wr_en = Vec::new();
+        let mut wr_addr = Vec::new();
+        let mut wr_data = Vec::new();
+        for &(address, value, enable, mask) in write_ports.iter() {
+            let enable = self.bits(enable)[0];
+            match mask {
+                Some(mask) => {
+                    // Each mask bit enables a lane of `lane_width` element bits
+                    let mask = self.bits(mask);
+                    let lane_width = width / mask.len() as u32;
+                    let lane_enables = self.binary_cell(
+                        "$and",
+                        vec![enable; mask.len()],
+                        mask,
+                        false,
+                        false,
+                        width / lane_width,
+                    );
+                    for lane_enable in lane_enables {
+                        wr_en.extend((0..lane_width).map(|_| lane_enable));
+                    }
+                }
+                _ => wr_en.extend((0..width).map(|_| enable)),
+            }
+            wr_addr.extend(self.bits(address));
+            wr_data.extend(self.bits(value));
+        }
+
+        // Later write ports take priority over earlier ones
+        let mut wr_priority_mask = Vec::new();
+        for port in 0..wr_ports {
+            for other_port in 0..wr_ports {
+                wr_priority_mask.push(Bit::from_bool(other_port < port));
+            }
+        }
+
+        let init = match *mem.initial_contents.borrow() {
+            Some(ref contents) => contents
+                .iter()
+                .flat_map(|element| constant_bits(element.numeric_value(), width))
+                .collect::<Vec<_>>(),
+            _ => vec![Bit::Undefined; (size * width) as usize],
+        };
+
+        let zeros = |count: u32| vec![Bit::Zero; count as usize];
+        let ones = |count: u32| vec![Bit::One; count as usize];
+        let undefined = |count: u32| vec![Bit::Undefined; count as usize];
+
+        self.cell(
+            Some(&mem.name),
+            "$mem_v2",
+            vec![
+                ("MEMID", Json::String(format!("\\{}", mem.name))),
+                ("SIZE", int_param(size)),
+                ("OFFSET", int_param(0)),
+                ("ABITS", int_param(abits)),
+                ("WIDTH", int_param(width)),
+                ("INIT", const_param(&init)),
+                ("RD_PORTS", int_param(rd_ports)),
+                ("RD_CLK_ENABLE", const_param(&rd_clk_enable)),
+                ("RD_CLK_POLARITY", const_param(&ones(rd_ports))),
+                ("RD_TRANSPARENCY_MASK", const_param(&rd_transparency_mask)),
+                ("RD_COLLISION_X_MASK", const_param(&rd_collision_x_mask)),
+                ("RD_WIDE_CONTINUATION", const_param(&zeros(rd_ports))),
+                ("RD_CE_OVER_SRST", const_param(&zeros(rd_ports))),
+                ("RD_ARST_VALUE", const_param(&undefined(rd_ports * width))),
+                ("RD_SRST_VALUE", const_param(&undefined(rd_ports * width))),
+                ("RD_INIT_VALUE", const_param(&undefined(rd_ports * width))),
+                ("WR_PORTS", int_param(wr_ports)),
+                ("WR_CLK_ENABLE", const_param(&ones(wr_ports))),
+                ("WR_CLK_POLARITY", const_param(&ones(wr_ports))),
+                ("WR_PRIORITY_MASK", const_param(&wr_priority_mask)),
+                ("WR_WIDE_CONTINUATION", const_param(&zeros(wr_ports))),
+            ],
+            vec![
+                ("RD_CLK", Direction::Input, rd_clk),
+                ("RD_EN", Direction::Input, rd_en),
+                ("RD_ARST", Direction::Input, zeros(rd_ports)),
+                ("RD_SRST", Direction::Input, zeros(rd_ports)),
+                ("RD_ADDR", Direction::Input, rd_addr),
+                ("RD_DATA", Direction::Output, rd_data),
+                (
+                    "WR_CLK",
+                    Direction::Input,
+                    vec![self.clk; wr_ports as usize],
+                ),
+                ("WR_EN", Direction::Input, wr_en),
+                ("WR_ADDR", Direction::Input, wr_addr),
+                ("WR_DATA", Direction::Input, wr_data),
+            ],
+        );
+    }
+}
+
+// The signals whose bits are needed to build `signal`'s bits, in the order they're visited
+fn operands<'a>(signal: &'a graph::Signal<'a>) -> Vec<&'a graph::Signal<'a>> {
+    match signal.data {
+        graph::SignalData::Lit { .. }
+        | graph::SignalData::Input { .. }
+        | graph::SignalData::Reg { .. }
+        | graph::SignalData::Latch { .. }
+        | graph::SignalData::InstanceOutput { .. }
+        | graph::SignalData::MemReadPortOutput { .. }
+        | graph::SignalData::MemCombReadPortOutput { .. } => Vec::new(),
+
+        graph::SignalData::Concat { lhs, rhs } => vec![rhs, lhs],
+
+        _ => signal.dependencies(),
+    }
+}
+
+fn generate_module<'a>(m: &'a graph::Module<'a>) -> Json {
+    let mut b = ModuleBuilder {
+        // Bit indices 0 and 1 are reserved for the constants 0 and 1
+        next_net: 2,
+        used_cell_names: HashSet::new(),
+        cells: Vec::new(),
+
+        clk: Bit::Undefined,
+        reset_n: Bit::Undefined,
+        signal_bits: IndexMap::new(),
+        instance_output_bits: IndexMap::new(),
+        read_port_bits: IndexMap::new(),
+    };
+    let mut ports = Vec::new();
+    let mut net_names = BTreeMap::new();
+
+    let port = |name: &str, direction: &str, bits: &[Bit]| {
+        (
+            name.to_string(),
+            Json::Object(vec![
+                ("direction".into(), Json::String(direction.into())),
+                ("bits".into(), bits_json(bits)),
+            ]),
+        )
+    };
+
+    // Purely combinational hierarchies don't need clock or reset ports
+    if uses_clock(m) {
+        let (clock, reset) = clock_reset_port_names(m, "clk", "reset_n");
+        b.reset_n = b.alloc(1)[0];
+        b.clk = b.alloc(1)[0];
+        ports.push(port(&reset, "input", &[b.reset_n]));
+        ports.push(port(&clock, "input", &[b.clk]));
+        net_names.insert(reset, vec![b.reset_n]);
+        net_names.insert(clock, vec![b.clk]);
+    }
+    for (name, &input) in m.inputs.borrow().iter() {
+        let bits = b.alloc(input.bit_width());
+        ports.push(port(name, "input", &bits));
+        net_names.insert(name.clone(), bits.clone());
+        b.signal_bits.insert(input, bits);
+    }
+
+    // State elements and instance outputs are allocated up front, since they can be referenced before they're driven
+    let registers = m.registers.borrow();
+    for &reg in registers.iter() {
+        let bits = b.alloc(reg.bit_width());
+        b.signal_bits.insert(reg, bits);
+    }
+    let latches = m.latches.borrow();
+    for &latch in latches.iter() {
+        let bits = b.alloc(latch.bit_width());
+        b.signal_bits.insert(latch, bits);
+    }
+    for &instance in m.instances.borrow().iter() {
+        let mut output_bits = BTreeMap::new();
+        for (name, output) in instance.instantiated_module.outputs.borrow().iter() {
+            output_bits.insert(name.clone(), b.alloc(output.bit_width()));
+        }
+        b.instance_output_bits.insert(instance, output_bits);
+    }
+    for &mem in m.mems.borrow().iter() {
+        let read_port_bits = (0..mem.read_ports.borrow().len())
+            .map(|_| b.alloc(mem.element_bit_width))
+            .collect();
+        let comb_read_port_bits = (0..mem.comb_read_ports.borrow().len())
+            .map(|_| b.alloc(mem.element_bit_width))
+            .collect();
+        b.read_port_bits
+            .insert(mem, (read_port_bits, comb_read_port_bits));
+    }
+
+    for (name, &output) in m.outputs.borrow().iter() {
+        let bits = b.bits(output);
+        ports.push(port(name, "output", &bits));
+        net_names.entry(name.clone()).or_insert(bits);
+    }
+
+    for (index, &reg) in registers.iter().enumerate() {
+        let data = match reg.data {
+            graph::SignalData::Reg { data } => data,
+            _ => unreachable!(),
+        };
+        let q = b.signal_bits[&reg].clone();
+        let d = b.bits(data.next.borrow().unwrap());
+        let name = format!("__reg_{}_{}", data.name, index);
+        match *data.initial_value.borrow() {
+            Some(ref initial_value) => {
+                let reset_value = constant_bits(initial_value.numeric_value(), data.bit_width);
+                let reset_n = b.reset_n;
+                let clk = b.clk;
+                b.cell(
+                    Some(&name),
+                    "$adff",
+                    vec![
+                        ("ARST_POLARITY", bool_param(false)),
+                        ("ARST_VALUE", const_param(&reset_value)),
+                        ("CLK_POLARITY", bool_param(true)),
+                        ("WIDTH", int_param(data.bit_width)),
+                    ],
+                    vec![
+                        ("ARST", Direction::Input, vec![reset_n]),
+                        ("CLK", Direction::Input, vec![clk]),
+                        ("D", Direction::Input, d),
+                        ("Q", Direction::Output, q.clone()),
+                    ],
+                );
+            }
+            None => {
+                let clk = b.clk;
+                b.cell(
+                    Some(&name),
+                    "$dff",
+                    vec![
+                        ("CLK_POLARITY", bool_param(true)),
+                        ("WIDTH", int_param(data.bit_width)),
+                    ],
+                    vec![
+                        ("CLK", Direction::Input, vec![clk]),
+                        ("D", Direction::Input, d),
+                        ("Q", Direction::Output, q.clone()),
+                    ],
+                );
+            }
+        }
+        net_names.entry(name).or_insert(q);
+    }
+
+    for (index, &latch) in latches.iter().enumerate() {
+        let data = match latch.data {
+            graph::SignalData::Latch { data } => data,
+            _ => unreachable!(),
+        };
+        let q = b.signal_bits[&latch].clone();
+        let d = b.bits(data.data.borrow().unwrap());
+        let en = b.bits(data.enable.borrow().unwrap());
+        let name = format!("__latch_{}_{}", data.name, index);
+        b.cell(
+            Some(&name),
+            "$dlatch",
+            vec![
+                ("EN_POLARITY", bool_param(true)),
+                ("WIDTH", int_param(data.bit_width)),
+            ],
+            vec![
+                ("D", Direction::Input, d),
+                ("EN", Direction::Input, en),
+                ("Q", Direction::Output, q.clone()),
+            ],
+        );
+        net_names.entry(name).or_insert(q);
+    }
+
+    for &instance in m.instances.borrow().iter() {
+        let instantiated_module = instance.instantiated_module;
+        let mut connections = Vec::new();
+        if uses_clock(instantiated_module) {
+            let (clock, reset) = clock_reset_port_names(instantiated_module, "clk", "reset_n");
+            connections.push((reset, Direction::Input, vec![b.reset_n]));
+            connections.push((clock, Direction::Input, vec![b.clk]));
+        }
+        for (name, &input) in instance.driven_inputs.borrow().iter() {
+            connections.push((name.clone(), Direction::Input, b.bits(input)));
+        }
+        for (name, bits) in b.instance_output_bits[&instance].clone() {
+            connections.push((name, Direction::Output, bits));
+        }
+        let parameters = instance.parameters.borrow();
+        b.cell(
+            Some(&instance.name),
+            &instantiated_module.name,
+            parameters
+                .iter()
+                .map(|(key, value)| (key.as_str(), parameter_value_param(value)))
+                .collect(),
+            connections
+                .iter()
+                .map(|(name, direction, bits)| (name.as_str(), *direction, bits.clone()))
+                .collect(),
+        );
+    }
+
+    for &mem in m.mems.borrow().iter() {
+        b.mem(mem);
+    }
+
+    for (name, &signal) in m.named_signals.borrow().iter() {
+        let bits = b.bits(signal);
+        net_names.entry(name.clone()).or_insert(bits);
+    }
+
+    Json::Object(vec![
+        ("attributes".into(), Json::Object(Vec::new())),
+        ("ports".into(), Json::Object(ports)),
+        ("cells".into(), Json::Object(b.cells)),
+        (
+            "netnames".into(),
+            Json::Object(
+                net_names
+                    .into_iter()
+                    .map(|(name, bits)| {
+                        (
+                            name,
+                            Json::Object(vec![
+                                ("hide_name".into(), Json::Number(0)),
+                                ("bits".into(), bits_json(&bits)),
+                                ("attributes".into(), Json::Object(Vec::new())),
+                            ]),
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+fn bits_json(bits: &[Bit]) -> Json {
+    Json::Array(bits.iter().map(Bit::json).collect())
+}
+
+enum Json {
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    // Writes this value starting at the current position; objects span multiple lines, while arrays (which only ever contain bits) are written on one line
+    fn write<W: Write>(&self, w: &mut code_writer::CodeWriter<W>) -> Result<()> {
+        match self {
+            Json::Number(value) => w.append(&value.to_string()),
+            Json::String(value) => w.append(&quote(value)),
+            Json::Array(values) => {
+                w.append("[")?;
+                for (index, value) in values.iter().enumerate() {
+                    w.append(" ")?;
+                    value.write(w)?;
+                    if index < values.len() - 1 {
+                        w.append(",")?;
+                    }
+                }
+                w.append(" ]")
+            }
+            Json::Object(members) => {
+                if members.is_empty() {
+                    return w.append("{}");
+                }
+                w.append("{")?;
+                w.append_newline()?;
+                w.indent();
+                for (index, (name, value)) in members.iter().enumerate() {
+                    w.append_indent()?;
+                    w.append(&format!("{}: ", quote(name)))?;
+                    value.write(w)?;
+                    if index < members.len() - 1 {
+                        w.append(",")?;
+                    }
+                    w.append_newline()?;
+                }
+                w.unindent()?;
+                w.append_indent()?;
+                w.append("}")
+            }
+        }
+    }
+}
+
+pub(crate) fn quote(s: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::*;
+
+    use std::collections::HashMap;
+
+    fn test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+        let inner = c.module("Inner");
+        let counter = inner.reg("counter", 4);
+        counter.default_value(0u32);
+        counter.drive_next(counter.value + inner.lit(1u32, 4));
+        inner.output("count", counter.value);
+        inner.output("o", !inner.input("i", 8));
+
+        let m = c.module("Netlist");
+        let a = m.input("a", 8);
+        let b = m.input("b", 8);
+        let sel = m.input("sel", 1);
+        let inner = m.instance("inner", "Inner");
+        inner.drive_input("i", a ^ b);
+
+        let acc = m.reg("acc", 8);
+        acc.drive_next(m.mux(sel, acc.value - b, (a & b) | inner.output("o")));
+
+        let ram = m.mem("ram", 2, 16);
+        ram.read_during_write(ReadDuringWrite::New);
+        ram.write_port_masked(a.bits(1, 0), a.concat(b), sel, b.bits(1, 0));
+        let read_data = ram.read_port(b.bits(1, 0), sel);
+        let lut = m.mem("lut", 2, 4);
+        lut.initial_contents(&[1u32, 2, 4, 8]);
+
+        m.output("read_data", read_data);
+        m.output("lut_data", lut.read_port_comb(a.bits(1, 0)));
+        m.output("count", inner.output("count"));
+        m.output("acc", acc.value);
+        m.output("shl", a << b);
+        m.output("sra", a.shr_arithmetic(b));
+        m.output("lt", a.lt_signed(b).concat(a.ge(b)).concat(a.eq(b)));
+        m.output("rep", sel.repeat(3));
+
+        m
+    }
+
+    fn cell_counts(module: &serde_json::Value) -> HashMap<String, usize> {
+        let mut ret = HashMap::new();
+        for cell in module["cells"].as_object().unwrap().values() {
+            *ret.entry(cell["type"].as_str().unwrap().to_string())
+                .or_insert(0) += 1;
+        }
+        ret
+    }
+
+    fn port_width(module: &serde_json::Value, name: &str) -> usize {
+        module["ports"][name]["bits"].as_array().unwrap().len()
+    }
+
+    #[test]
+    fn netlist() {
+        let c = Context::new();
+        let m = test_module(&c);
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&w).unwrap();
+
+        assert_eq!(json["creator"], "kaze");
+        let modules = json["modules"].as_object().unwrap();
+        // Instantiated modules come first
+        assert_eq!(modules.keys().collect::<Vec<_>>(), vec!["Inner", "Netlist"]);
+
+        let inner = &modules["Inner"];
+        assert_eq!(
+            cell_counts(inner),
+            [("$adff", 1), ("$add", 1), ("$not", 1)]
+                .iter()
+                .map(|&(t, n)| (t.to_string(), n))
+                .collect()
+        );
+        let counter = &inner["cells"]["__reg_counter_0"];
+        assert_eq!(
+            counter["parameters"]["ARST_POLARITY"],
+            format!("{:032b}", 0)
+        );
+        assert_eq!(counter["parameters"]["ARST_VALUE"], "0000");
+        assert_eq!(
+            counter["connections"]["ARST"],
+            inner["ports"]["reset_n"]["bits"]
+        );
+        assert_eq!(counter["connections"]["CLK"], inner["ports"]["clk"]["bits"]);
+        assert_eq!(counter["connections"]["Q"], inner["ports"]["count"]["bits"]);
+
+        let netlist = &modules["Netlist"];
+        assert_eq!(
+            cell_counts(netlist),
+            [
+                ("$and", 2),
+                ("$dff", 1),
+                ("$eq", 1),
+                ("$ge", 1),
+                ("$lt", 1),
+                ("$mem_v2", 2),
+                ("$mux", 1),
+                ("$or", 1),
+                ("$shl", 1),
+                ("$sshr", 1),
+                ("$sub", 1),
+                ("$xor", 1),
+                ("Inner", 1),
+            ]
+            .iter()
+            .map(|&(t, n)| (t.to_string(), n))
+            .collect()
+        );
+        for &(name, width) in &[
+            ("reset_n", 1),
+            ("clk", 1),
+            ("a", 8),
+            ("b", 8),
+            ("sel", 1),
+            ("read_data", 16),
+            ("lut_data", 4),
+            ("count", 4),
+            ("acc", 8),
+            ("lt", 3),
+            ("rep", 3),
+        ] {
+            assert_eq!(port_width(netlist, name), width, "port {}", name);
+        }
+
+        // Slices, concatenations, and repetitions are expressed by connecting bits directly
+        let a_bits = netlist["ports"]["a"]["bits"].as_array().unwrap();
+        let sel_bits = netlist["ports"]["sel"]["bits"].as_array().unwrap();
+        let rep_bits = netlist["ports"]["rep"]["bits"].as_array().unwrap();
+        assert!(rep_bits.iter().all(|bit| *bit == sel_bits[0]));
+        let lut = &netlist["cells"]["lut"];
+        assert_eq!(
+            lut["connections"]["RD_ADDR"].as_array().unwrap()[..],
+            a_bits[..2]
+        );
+        assert_eq!(lut["parameters"]["RD_CLK_ENABLE"], "0");
+        assert_eq!(lut["parameters"]["WR_PORTS"], format!("{:032b}", 0));
+        assert_eq!(lut["parameters"]["INIT"], "1000010000100001");
+
+        let ram = &netlist["cells"]["ram"];
+        assert_eq!(ram["parameters"]["RD_TRANSPARENCY_MASK"], "1");
+        assert_eq!(ram["connections"]["WR_EN"].as_array().unwrap().len(), 16);
+        assert_eq!(
+            ram["connections"]["RD_DATA"],
+            netlist["ports"]["read_data"]["bits"]
+        );
+
+        let inner = &netlist["cells"]["inner"];
+        assert_eq!(inner["hide_name"], 0);
+        assert_eq!(
+            inner["connections"]["count"],
+            netlist["ports"]["count"]["bits"]
+        );
+        assert!(netlist["netnames"]["__reg_acc_0"].is_object());
+    }
+
+    #[test]
+    fn combinational() {
+        let c = Context::new();
+
+        let m = c.module("Comb \"quoted\"");
+        m.output("o", !m.input("i", 2));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&w).unwrap();
+
+        let module = &json["modules"]["Comb \"quoted\""];
+        assert!(module["ports"]["clk"].is_null());
+        assert!(module["ports"]["reset_n"].is_null());
+        assert_eq!(module["ports"]["i"]["bits"], serde_json::json!([2, 3]));
+        assert_eq!(module["ports"]["o"]["bits"], serde_json::json!([4, 5]));
+    }
+
+    #[test]
+    fn latch() {
+        let c = Context::new();
+
+        let m = c.module("Top");
+        let l = m.latch("l", 4);
+        l.drive_data(m.input("data", 4));
+        l.drive_enable(m.input("enable", 1));
+        m.output("o", l.value);
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&w).unwrap();
+
+        // Latches aren't clocked
+        let module = &json["modules"]["Top"];
+        assert!(module["ports"]["clk"].is_null());
+        let cell = &module["cells"]["__latch_l_0"];
+        assert_eq!(cell["type"], "$dlatch");
+        assert_eq!(cell["parameters"]["EN_POLARITY"], format!("{:032b}", 1));
+        assert_eq!(cell["parameters"]["WIDTH"], format!("{:032b}", 4));
+        assert_eq!(cell["connections"]["D"], module["ports"]["data"]["bits"]);
+        assert_eq!(cell["connections"]["EN"], module["ports"]["enable"]["bits"]);
+        assert_eq!(cell["connections"]["Q"], module["ports"]["o"]["bits"]);
+        assert!(module["netnames"]["__latch_l_0"].is_object());
+    }
+
+    #[test]
+    fn extern_module() {
+        let c = Context::new();
+
+        let pll = c.extern_module("PLL");
+        pll.input("clk_in", 1);
+        pll.extern_output("clk_out", 1);
+
+        let m = c.module("Top");
+        let pll = m.instance("pll", "PLL");
+        pll.parameter("DIVIDE", -2);
+        pll.parameter("MULTIPLY", 0x1_0000_0000i64);
+        pll.parameter("PERIOD", 10.5);
+        pll.parameter("MODE", "FAST");
+        pll.parameter("PATTERN", "0101");
+        pll.drive_input("clk_in", m.input("clk_in", 1));
+        m.output("clk_out", pll.output("clk_out"));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&w).unwrap();
+
+        assert!(json["modules"]["PLL"].is_null());
+        let cell = &json["modules"]["Top"]["cells"]["pll"];
+        assert_eq!(cell["type"], "PLL");
+        let parameters = &cell["parameters"];
+        assert_eq!(parameters["DIVIDE"], format!("{:032b}", -2i32));
+        assert_eq!(parameters["MULTIPLY"], format!("{:064b}", 0x1_0000_0000i64));
+        assert_eq!(parameters["PERIOD"], "10.5");
+        assert_eq!(parameters["MODE"], "FAST");
+        assert_eq!(parameters["PATTERN"], "0101 ");
+        assert_eq!(cell["port_directions"]["clk_out"], "output");
+    }
+
+    #[test]
+    fn deep_signal_chain() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+        // A chain of ~100k signals, which overflows the stack if netlist generation recurses through it
+        let mut x = a;
+        for _ in 0..34_000 {
+            x = (x + a).bits(6, 0).concat(a.bit(0));
+        }
+        m.output("o", x);
+
+        generate(m, std::io::sink()).unwrap();
+    }
+}
@@ -13,6 +13,9 @@
 //! kaze = "0.1"
 //! ```
 //!
+//! The optional `serde` feature derives `Serialize` and `Deserialize` for plain data types such as [`ModuleInterface`].
+//! The optional `tokens` feature adds `sim::generate_tokens` and `sim::generate_sim_module_trait_tokens`, which generate simulators as `proc_macro2::TokenStream`s for procedural macros.
+//!
 //! # Examples
 //!
 //! ```rust
@@ -37,16 +40,28 @@
 //! ```
 //!
 //! [`Module`]: ./struct.Module.html
+//! [`ModuleInterface`]: ./struct.ModuleInterface.html
 //! [`Signal`]: ./struct.Signal.html
 
 // Must be kept up-to-date with version in Cargo.toml
 #![doc(html_root_url = "https://docs.rs/kaze/0.1.7")]
 
+pub mod analysis;
+pub mod build;
 mod code_writer;
+pub mod components;
+mod constant_folding;
+pub mod dot;
+pub mod firrtl;
+pub mod golden;
 mod graph;
+mod index_map;
+pub mod json_netlist;
 mod module_context;
 pub mod sim;
-mod validation;
+pub mod transform;
+pub mod validation;
 pub mod verilog;
 
+pub use code_writer::{CodeStyle, LineEnding};
 pub use graph::*;
@@ -10,17 +10,297 @@ use state_elements::*;
 
 use typed_arena::Arena;
 
-use crate::code_writer;
+use crate::analysis::hierarchy_modules;
+use crate::code_writer::{self, CodeStyle};
 use crate::graph;
 use crate::module_context::*;
 use crate::validation::*;
 
+use std::collections::{HashMap, HashSet};
 use std::io::{Result, Write};
 
+/// Options for Rust simulator code generation, used with [`generate_with_options`].
+///
+/// [`generate_with_options`]: ./fn.generate_with_options.html
+#[derive(Clone, Debug, Default)]
+pub struct GenerationOptions {
+    /// Internal signals to expose as getter methods in the generated simulator, in addition to the generated `Module`'s outputs.
+    ///
+    /// Each probe is specified as a `.`-separated path of instance names (starting from the generated `Module`) followed by the name of a [`Register`] or a [named `Signal`](../struct.Signal.html#method.name) in the instantiated `Module` at the end of that path.
+    /// For example, `"fetch.pc"` refers to a register or named signal called `pc` in an instance called `fetch`, and `"pc"` refers to one in the generated `Module` itself.
+    /// The generated getter method's name is the path with each `.` replaced by `__` (for example, `fetch__pc`), made into a valid identifier with [`identifier`].
+    ///
+    /// Register probes always reflect the register's current value.
+    /// Other probes reflect the value computed by the most recent call to `prop()`.
+    ///
+    /// [`Register`]: ../struct.Register.html
+    /// [`identifier`]: ./fn.identifier.html
+    pub probes: Vec<String>,
+
+    /// Generate `dump_vcd_header` and `dump_vcd` methods for writing [VCD](https://en.wikipedia.org/wiki/Value_change_dump) waveforms.
+    ///
+    /// `dump_vcd_header(&self, w: &mut impl std::io::Write) -> std::io::Result<()>` writes the VCD header, declaring the generated `Module`'s inputs and outputs and every [`Register`] in its hierarchy.
+    /// Registers in instances are declared in nested scopes named after the instance path.
+    /// `dump_vcd(&self, w: &mut impl std::io::Write, time: u64) -> std::io::Result<()>` records the current values of all declared signals at `time`, and is typically called once per cycle after `prop()`.
+    ///
+    /// Each signal's VCD identifier is determined only by the design, so identifiers are stable across runs.
+    ///
+    /// [`Register`]: ../struct.Register.html
+    pub vcd: bool,
+
+    /// Generate `save_state` and `load_state` methods for checkpointing the simulator's state.
+    ///
+    /// For a generated simulator struct called `Foo`, this also generates a `FooState` struct, with a field for each [`Register`] and [`Mem`] (as well as each [`Mem`] read port's most recently-read value) in the `Module`'s hierarchy.
+    /// Field names are derived from the `.`-separated instance path (starting from the generated `Module`) and name of each element, with each `.` replaced by `__`.
+    /// `save_state(&self) -> FooState` captures the current state and `load_state(&mut self, state: &FooState)` restores it; call `prop()` after restoring state (as `tick()` does) before inspecting outputs or clocking.
+    ///
+    /// [`Mem`]: ../struct.Mem.html
+    /// [`Register`]: ../struct.Register.html
+    pub save_state: bool,
+
+    /// Collect toggle coverage for every [`Register`] and [named `Signal`](../struct.Signal.html#method.name) in the generated `Module`'s hierarchy, and generate a `coverage` method for reporting it.
+    ///
+    /// Each covered signal's bits are sampled at every `posedge_clk()` (registers before they're updated, and named signals as computed by the most recent call to `prop()`), and a bit is covered once it's been observed as both 0 and 1.
+    /// For a generated simulator struct called `Foo`, this also generates a `FooCoverageReport` struct, which `coverage(&self) -> FooCoverageReport` returns:
+    ///
+    /// ```text
+    /// pub struct FooCoverageReport {
+    ///     // (path, bit width, bits observed as 0, bits observed as 1) for each covered signal
+    ///     pub signals: Vec<(&'static str, u32, u128, u128)>,
+    /// }
+    ///
+    /// impl FooCoverageReport {
+    ///     // Bits of the signal at `path` that haven't been observed as both 0 and 1
+    ///     pub fn uncovered_bits(&self, path: &str) -> u128 { /* ... */ }
+    ///     // Percentage of all covered signals' bits that have been observed as both 0 and 1
+    ///     pub fn percentage(&self) -> f64 { /* ... */ }
+    /// }
+    /// ```
+    ///
+    /// Paths are `.`-separated instance names followed by the signal's name, as with [`probes`](#structfield.probes).
+    /// Covering a signal causes it (and anything it depends on) to be simulated even if it doesn't affect any outputs.
+    /// When this option isn't set, no coverage bookkeeping is generated.
+    ///
+    /// [`Register`]: ../struct.Register.html
+    pub toggle_coverage: bool,
+
+    /// Implement the `SimModule` trait for the generated simulator, so that it can be driven by generic testbenches.
+    ///
+    /// The trait itself isn't generated by this function; it must be generated separately (exactly once per scope) with [`generate_sim_module_trait`].
+    ///
+    /// [`generate_sim_module_trait`]: ./fn.generate_sim_module_trait.html
+    pub impl_sim_module: bool,
+
+    /// Check that each input's value fits in its bit width when it's read by `prop()`.
+    ///
+    /// By default, bits above an input's width are silently ignored (for example, setting a 27-bit input to `0xffffffff` is the same as setting it to `0x07ffffff`).
+    /// With this option, `prop()` panics with a message naming the input and its value instead.
+    /// The checks use `debug_assert!`, so release builds of the generated code keep the default masking behavior.
+    ///
+    /// The default masking behavior matches generated Verilog, where a port truncates any wider signal that drives it (see [`verilog::generate_with_options`]).
+    ///
+    /// [`verilog::generate_with_options`]: ../verilog/fn.generate_with_options.html
+    pub check_inputs: bool,
+
+    /// How the generated simulator treats [`Register`] values before they're first written by `reset()` or `posedge_clk()`.
+    ///
+    /// [`Register`]: ../struct.Register.html
+    pub uninitialized_registers: UninitializedRegisters,
+
+    /// How the generated code is formatted (see [`CodeStyle`]).
+    ///
+    /// [`CodeStyle`]: ../struct.CodeStyle.html
+    pub code_style: CodeStyle,
+
+    /// The name of the generated simulator struct, instead of the generated `Module`'s name (made into a valid identifier with [`identifier`]).
+    ///
+    /// The names of the structs generated for [`save_state`](#structfield.save_state) and [`toggle_coverage`](#structfield.toggle_coverage) are derived from this name (for example, `CoreSimState` for `CoreSim`).
+    /// The name must be a valid Rust identifier that isn't a keyword.
+    ///
+    /// [`identifier`]: ./fn.identifier.html
+    pub struct_name: Option<String>,
+
+    /// The visibility of the generated structs.
+    ///
+    /// If [`module_name`](#structfield.module_name) is set, this is the visibility of the generated `mod` instead, and the structs inside it are `pub`.
+    pub visibility: Visibility,
+
+    /// The name of a `mod` to wrap the generated code in, if any.
+    ///
+    /// The generated `mod` imports `SimModule` from its parent when [`impl_sim_module`](#structfield.impl_sim_module) is set, so the trait should be generated outside of it.
+    /// The name must be a valid Rust identifier that isn't a keyword.
+    pub module_name: Option<String>,
+}
+
+/// The visibility of the items in a generated simulator, used with [`GenerationOptions`].
+///
+/// [`GenerationOptions`]: ./struct.GenerationOptions.html
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Visibility {
+    /// `pub` (the default)
+    #[default]
+    Public,
+    /// `pub(crate)`
+    Crate,
+    /// `pub(super)`
+    Super,
+    /// No visibility modifier (private to the enclosing module).
+    Private,
+}
+
+impl Visibility {
+    // The visibility keyword, including a trailing space if it's not empty
+    fn keyword(&self) -> &'static str {
+        match self {
+            Visibility::Public => "pub ",
+            Visibility::Crate => "pub(crate) ",
+            Visibility::Super => "pub(super) ",
+            Visibility::Private => "",
+        }
+    }
+}
+
+/// Policies for [`Register`] values in a generated simulator before they're first written by `reset()` or `posedge_clk()`, used with [`GenerationOptions`].
+///
+/// In hardware, a register's value is undefined until it's reset or clocked, which is never the case for registers without a [default value](../struct.Register.html#method.default_value).
+/// These policies offer different tradeoffs for modeling that in simulation.
+/// Generated Verilog can be configured to start registers out as zero too, so that both backends agree (see [`verilog::UninitializedRegisters`]).
+///
+/// [`GenerationOptions`]: ./struct.GenerationOptions.html
+/// [`Register`]: ../struct.Register.html
+/// [`verilog::UninitializedRegisters`]: ../verilog/enum.UninitializedRegisters.html
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum UninitializedRegisters {
+    /// All registers start out as zero.
+    #[default]
+    Zero,
+    /// Registers start out with a pseudo-random pattern, determined by a seed.
+    ///
+    /// The generated simulator has a `new_with_seed(seed: u64)` constructor; `new()` is equivalent to `new_with_seed(0)`.
+    /// Running a design with different seeds is a good way to uncover missing resets.
+    Random,
+    /// Registers start out poisoned, and `posedge_clk()` panics if a poisoned value would be clocked into a register or used for a [`Mem`] write.
+    ///
+    /// A register is no longer poisoned once it's been reset (if it has a default value) or clocked.
+    /// A register that only holds its own value (for example, `en.mux(d, r.value)` when `en` is low) stays poisoned instead of causing a panic.
+    /// Values observed on outputs and contents of [`Mem`]s aren't checked.
+    ///
+    /// [`Mem`]: ../struct.Mem.html
+    Poison,
+}
+
+/// Generates a Rust simulator for the `Module` `m` using the default [`GenerationOptions`] and writes it to `w`.
+///
+/// See [`generate_with_options`] for details.
+///
+/// [`GenerationOptions`]: ./struct.GenerationOptions.html
+/// [`generate_with_options`]: ./fn.generate_with_options.html
 // TODO: Note that mutable writer reference can be passed, see https://rust-lang.github.io/api-guidelines/interoperability.html#c-rw-value
 pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
+    generate_with_options(m, &GenerationOptions::default(), w)
+}
+
+/// Generates a Rust simulator for the `Module` `m` according to `options` and writes it to `w`.
+///
+/// The generated simulator's `tick()` method simulates a single clock cycle by propagating inputs with `prop()`, applying a clock edge with `posedge_clk()`, and propagating the new register values to the outputs with `prop()` again.
+/// `tick_n(n)` calls `tick()` `n` times.
+///
+/// The generated simulator has no clock or reset ports.
+/// Instead, `reset()` corresponds to asserting the generated Verilog module's active-low reset port, and `posedge_clk()` corresponds to a rising edge on its clock port.
+/// These ports are called `reset_n` and `clk` by default, but they keep these method names even if they're renamed with [`Module::reset_port_name`] and [`Module::clock_port_name`].
+///
+/// The generated simulator implements `std::fmt::Debug`, printing the generated `Module`'s inputs and outputs and the current value of each [`Register`] and [`Mem`] in its hierarchy (grouped by instance) in hex, along with their bit widths.
+/// Only the first few elements of each memory are printed, followed by a count of its non-zero elements.
+///
+/// Inputs, outputs, registers, and intermediate values are each stored in the narrowest of `bool` (1 bit), `u32` (2 to 32 bits), `u64` (33 to 64 bits), or `u128` (65 to 128 bits) that can hold them, and any bits above a value's width are always zero.
+/// `u8` and `u16` are deliberately not used, as arithmetic on them is no faster than on `u32`, and this keeps the types of generated ports from changing when a design's widths are tweaked.
+///
+/// Each [`Mem`] in `m`'s hierarchy that affects `m`'s outputs gets `_read(address)`/`_write(address, value)` methods for accessing individual elements, and `_contents()`/`_contents_mut()` methods for accessing its backing array directly (for example, to preload a program image).
+/// These methods' names are prefixed with the memory's `.`-separated instance path (starting from `m`) and name, with each `.` replaced by `__` (for example, `cpu__ram_read` for a memory called `ram` in an instance called `cpu`).
+///
+/// Names of the generated struct, its fields, and its methods are derived from the names in `m`'s hierarchy with [`identifier`], so names that aren't valid Rust identifiers (such as keywords) are still usable.
+///
+/// [`Context::extern_module`]: ../struct.Context.html#method.extern_module
+/// [`Mem`]: ../struct.Mem.html
+/// [`Module::clock_port_name`]: ../struct.Module.html#method.clock_port_name
+/// [`Module::reset_port_name`]: ../struct.Module.html#method.reset_port_name
+/// [`Register`]: ../struct.Register.html
+/// [`identifier`]: ./fn.identifier.html
+///
+/// # Panics
+///
+/// Panics if `m`'s hierarchy contains errors (for example, undriven registers or combinational loops) or any extern modules (see [`Context::extern_module`]), or if any of `options`' probes can't be resolved or would have a getter whose name is already taken.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let inner = c.module("Inner");
+/// let counter = inner.reg("counter", 8);
+/// counter.default_value(0u32);
+/// counter.drive_next(counter.value + inner.lit(1u32, 8));
+/// inner.output("o", counter.value.bit(7));
+///
+/// let m = c.module("Outer");
+/// let inner = m.instance("inner", "Inner");
+/// m.output("o", inner.output("o"));
+///
+/// let options = sim::GenerationOptions {
+///     probes: vec!["inner.counter".into()],
+///     ..Default::default()
+/// };
+/// let mut w = Vec::new();
+/// sim::generate_with_options(m, &options, &mut w).unwrap();
+/// assert!(std::str::from_utf8(&w).unwrap().contains("pub fn inner__counter(&self) -> u32"));
+/// ```
+pub fn generate_with_options<'a, W: Write>(
+    m: &'a graph::Module<'a>,
+    options: &GenerationOptions,
+    w: W,
+) -> Result<()> {
     validate_module_hierarchy(m);
 
+    if let Some(extern_module) = hierarchy_modules(m).into_iter().find(|m| m.is_extern) {
+        panic!("Cannot generate a simulator for module \"{}\" because its hierarchy contains extern module \"{}\", which is defined outside of kaze and can't be simulated.", m.name, extern_module.name);
+    }
+
+    let struct_name = match options.struct_name {
+        Some(ref struct_name) => {
+            if !is_valid_identifier(struct_name) {
+                panic!("Cannot generate a simulator for module \"{}\" with the struct name \"{}\", because it isn't a valid Rust identifier.", m.name, struct_name);
+            }
+            struct_name.clone()
+        }
+        _ => identifier(&m.name),
+    };
+    if let Some(ref module_name) = options.module_name {
+        if !is_valid_identifier(module_name) {
+            panic!("Cannot generate a simulator for module \"{}\" in a module called \"{}\", because it isn't a valid Rust identifier.", m.name, module_name);
+        }
+    }
+    let state_struct_name = if options.struct_name.is_some() {
+        format!("{}State", struct_name)
+    } else {
+        identifier(&format!("{}State", m.name))
+    };
+    let item_visibility = if options.module_name.is_some() {
+        Visibility::Public
+    } else {
+        options.visibility
+    }
+    .keyword();
+    let mut port_names = HashMap::new();
+    for name in m.inputs.borrow().keys().chain(m.outputs.borrow().keys()) {
+        if let Some(other_name) = port_names.insert(identifier(name), name.clone()) {
+            if other_name != *name {
+                panic!("Cannot generate code for module \"{}\" because its ports \"{}\" and \"{}\" would both be called \"{}\" in the generated simulator.", m.name, other_name, name, identifier(name));
+            }
+        }
+    }
+
     let context_arena = Arena::new();
     let root_context = context_arena.alloc(ModuleContext::new());
 
@@ -28,6 +308,22 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
     for (_, output) in m.outputs.borrow().iter() {
         state_elements.gather(&output, root_context, &context_arena);
     }
+    state_elements.gather_annotations(m, root_context, &context_arena, "");
+    for path in options.probes.iter() {
+        state_elements.gather_probe(m, path, root_context, &context_arena);
+    }
+    let mut toggled_signals = Vec::new();
+    if options.toggle_coverage {
+        gather_toggled_signals(
+            m,
+            "",
+            root_context,
+            &context_arena,
+            &mut state_elements,
+            &mut toggled_signals,
+        );
+    }
+    check_probe_getter_names(m, &state_elements);
 
     let mut prop_context = AssignmentContext::new();
     let mut c = Compiler::new(&state_elements, &context_arena);
@@ -35,7 +331,7 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
         let expr = c.compile_signal(&output, root_context, &mut prop_context);
         prop_context.push(Assignment {
             target: Expr::Ref {
-                name: name.clone(),
+                name: identifier(name),
                 scope: Scope::Member,
             },
             expr,
@@ -60,11 +356,17 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
                 expr: enable,
             });
         }
-        if let Some((address, value, enable)) = *mem.mem.write_port.borrow() {
+        for ((address, value, enable, mask), write_signal_names) in mem
+            .mem
+            .write_ports
+            .borrow()
+            .iter()
+            .zip(mem.write_signal_names.iter())
+        {
             let address = c.compile_signal(address, context, &mut prop_context);
             prop_context.push(Assignment {
                 target: Expr::Ref {
-                    name: mem.write_address_name.clone(),
+                    name: write_signal_names.address_name.clone(),
                     scope: Scope::Member,
                 },
                 expr: address,
@@ -72,7 +374,7 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
             let value = c.compile_signal(value, context, &mut prop_context);
             prop_context.push(Assignment {
                 target: Expr::Ref {
-                    name: mem.write_value_name.clone(),
+                    name: write_signal_names.value_name.clone(),
                     scope: Scope::Member,
                 },
                 expr: value,
@@ -80,11 +382,66 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
             let enable = c.compile_signal(enable, context, &mut prop_context);
             prop_context.push(Assignment {
                 target: Expr::Ref {
-                    name: mem.write_enable_name.clone(),
+                    name: write_signal_names.enable_name.clone(),
                     scope: Scope::Member,
                 },
                 expr: enable,
             });
+            if let (Some(mask), Some(mask_name)) = (mask, &write_signal_names.mask_name) {
+                let mask_bit_width = mask.bit_width();
+                let lane_bit_width = mem.mem.element_bit_width / mask_bit_width;
+                let mask = c.compile_signal(mask, context, &mut prop_context);
+                // Expand each mask bit to cover all of the element bits in its lane
+                let mut expanded_mask =
+                    Expr::from_constant(&0u32.into(), mem.mem.element_bit_width);
+                for lane_index in 0..mask_bit_width {
+                    let lane_enable = if mask_bit_width == 1 {
+                        mask.clone()
+                    } else {
+                        let lane_bit = prop_context.gen_temp(Expr::InfixBinOp {
+                            lhs: Box::new(mask.clone()),
+                            rhs: Box::new(Expr::from_constant(
+                                &(1u128 << lane_index).into(),
+                                mask_bit_width,
+                            )),
+                            op: InfixBinOp::BitAnd,
+                        });
+                        prop_context.gen_temp(Expr::InfixBinOp {
+                            lhs: Box::new(lane_bit),
+                            rhs: Box::new(Expr::from_constant(&0u32.into(), mask_bit_width)),
+                            op: InfixBinOp::NotEqual,
+                        })
+                    };
+                    let lane_bits = if lane_bit_width == 128 {
+                        !0
+                    } else {
+                        ((1u128 << lane_bit_width) - 1) << (lane_index * lane_bit_width)
+                    };
+                    let lane_mask = prop_context.gen_temp(Expr::Ternary {
+                        cond: Box::new(lane_enable),
+                        when_true: Box::new(Expr::from_constant(
+                            &lane_bits.into(),
+                            mem.mem.element_bit_width,
+                        )),
+                        when_false: Box::new(Expr::from_constant(
+                            &0u32.into(),
+                            mem.mem.element_bit_width,
+                        )),
+                    });
+                    expanded_mask = prop_context.gen_temp(Expr::InfixBinOp {
+                        lhs: Box::new(expanded_mask),
+                        rhs: Box::new(lane_mask),
+                        op: InfixBinOp::BitOr,
+                    });
+                }
+                prop_context.push(Assignment {
+                    target: Expr::Ref {
+                        name: mask_name.clone(),
+                        scope: Scope::Member,
+                    },
+                    expr: expanded_mask,
+                });
+            }
         }
     }
     for ((context, _), reg) in state_elements.regs.iter() {
@@ -97,22 +454,173 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
             expr,
         });
     }
+    let poison = options.uninitialized_registers == UninitializedRegisters::Poison;
+    if poison {
+        for ((context, signal), reg) in state_elements.regs.iter() {
+            let (next_poison, next_holds) = c.compile_next_poison(
+                signal,
+                reg.data.next.borrow().unwrap(),
+                context,
+                &mut prop_context,
+            );
+            prop_context.push(Assignment {
+                target: Expr::Ref {
+                    name: format!("{}_poisoned", reg.next_name),
+                    scope: Scope::Member,
+                },
+                expr: next_poison.unwrap_or_else(|| Expr::from_constant(&false.into(), 1)),
+            });
+            prop_context.push(Assignment {
+                target: Expr::Ref {
+                    name: format!("{}_holds", reg.next_name),
+                    scope: Scope::Member,
+                },
+                expr: next_holds.unwrap_or_else(|| Expr::from_constant(&false.into(), 1)),
+            });
+        }
+        for ((context, _), mem) in state_elements.mems.iter() {
+            for (index, (address, value, enable, mask)) in
+                mem.mem.write_ports.borrow().iter().enumerate()
+            {
+                let enable_poison = c.compile_poison(enable, context, &mut prop_context);
+                let mut port_poison = c.compile_poison(address, context, &mut prop_context);
+                let value_poison = c.compile_poison(value, context, &mut prop_context);
+                port_poison = gen_poison_or(port_poison, value_poison, &mut prop_context);
+                if let Some(mask) = mask {
+                    let mask_poison = c.compile_poison(mask, context, &mut prop_context);
+                    port_poison = gen_poison_or(port_poison, mask_poison, &mut prop_context);
+                }
+                // Disabled write ports don't use their other inputs
+                let port_poison =
+                    c.gen_poison_select(enable, context, port_poison, None, &mut prop_context);
+                let port_poison = gen_poison_or(enable_poison, port_poison, &mut prop_context);
+                prop_context.push(Assignment {
+                    target: Expr::Ref {
+                        name: format!("{}_write_port_{}_poisoned", mem.mem_name, index),
+                        scope: Scope::Member,
+                    },
+                    expr: port_poison.unwrap_or_else(|| Expr::from_constant(&false.into(), 1)),
+                });
+            }
+        }
+    }
+    for property in state_elements
+        .assertions
+        .iter()
+        .chain(state_elements.assumptions.iter())
+        .chain(state_elements.covers.iter())
+    {
+        let expr = c.compile_signal(property.condition, property.context, &mut prop_context);
+        prop_context.push(Assignment {
+            target: Expr::Ref {
+                name: property.name.clone(),
+                scope: Scope::Member,
+            },
+            expr,
+        });
+    }
+    for probe in state_elements.probes.iter() {
+        if let graph::SignalData::Reg { .. } = probe.signal.data {
+            continue;
+        }
+        let expr = c.compile_signal(probe.signal, probe.context, &mut prop_context);
+        prop_context.push(Assignment {
+            target: Expr::Ref {
+                name: probe.value_name.clone(),
+                scope: Scope::Member,
+            },
+            expr,
+        });
+    }
+    for trace in state_elements.traces.iter() {
+        let expr = c.compile_signal(trace.signal, trace.context, &mut prop_context);
+        prop_context.push(Assignment {
+            target: Expr::Ref {
+                name: trace.name.clone(),
+                scope: Scope::Member,
+            },
+            expr,
+        });
+    }
+    for toggled_signal in toggled_signals.iter().filter(|signal| !signal.is_reg) {
+        let expr = c.compile_signal(
+            toggled_signal.signal,
+            toggled_signal.context,
+            &mut prop_context,
+        );
+        prop_context.push(Assignment {
+            target: Expr::Ref {
+                name: toggled_signal.value_name.clone(),
+                scope: Scope::Member,
+            },
+            expr,
+        });
+    }
+    // Latches are updated last, so that every other expression in `prop` sees their previous values when they're disabled
+    for ((context, signal), latch) in state_elements.latches.iter() {
+        let expr = c.compile_signal(signal, context, &mut prop_context);
+        prop_context.push(Assignment {
+            target: Expr::Ref {
+                name: latch.value_name.clone(),
+                scope: Scope::Member,
+            },
+            expr,
+        });
+    }
 
-    let mut w = code_writer::CodeWriter::new(w);
-
+    let mut w = code_writer::CodeWriter::with_style(w, options.code_style.clone());
+
+    w.append_line(&format!(
+        "// Generated by kaze {} from module {:?}",
+        env!("CARGO_PKG_VERSION"),
+        m.name
+    ))?;
+    if let Some(ref module_name) = options.module_name {
+        w.append_line(&format!(
+            "/// Generated simulator for the kaze module `{}`.",
+            m.name
+        ))?;
+        w.append_line(&format!(
+            "{}mod {} {{",
+            options.visibility.keyword(),
+            module_name
+        ))?;
+        w.indent();
+        if options.impl_sim_module {
+            w.append_line("use super::SimModule;")?;
+            w.append_newline()?;
+        }
+    }
+    w.append_line(&format!(
+        "/// Simulator for the kaze module `{}`, generated by kaze {}.",
+        m.name,
+        env!("CARGO_PKG_VERSION")
+    ))?;
+    w.append_line("///")?;
+    w.append_line("/// Set the inputs and call `prop()` to update the outputs, and call `posedge_clk()` (or `tick()`) to simulate a clock cycle.")?;
+    // Fields and methods for elements in instances are named after their `__`-separated paths, and sanitized names (such as `Mod_1` or `Self_`) don't follow Rust's naming conventions either
+    w.append_line("#[allow(non_camel_case_types, non_snake_case)]")?;
     w.append_line("#[derive(Default)]")?;
-    w.append_line(&format!("pub struct {} {{", m.name))?;
+    w.append_line(&format!("{}struct {} {{", item_visibility, struct_name))?;
     w.indent();
 
     let inputs = m.inputs.borrow();
+    let port_descriptions = m.port_descriptions.borrow();
     if !inputs.is_empty() {
         w.append_line("// Inputs")?;
         for (name, input) in inputs.iter() {
-            w.append_line(&format!(
-                "pub {}: {}, // {} bit(s)",
+            gen_port_doc(
+                PortDirection::Input,
                 name,
-                ValueType::from_bit_width(input.bit_width()).name(),
-                input.bit_width()
+                input.bit_width(),
+                port_descriptions.get(name),
+                options.check_inputs,
+                &mut w,
+            )?;
+            w.append_line(&format!(
+                "pub {}: {},",
+                identifier(name),
+                ValueType::from_bit_width(input.bit_width()).name()
             ))?;
         }
     }
@@ -121,11 +629,18 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
     if !outputs.is_empty() {
         w.append_line("// Outputs")?;
         for (name, output) in outputs.iter() {
-            w.append_line(&format!(
-                "pub {}: {}, // {} bit(s)",
+            gen_port_doc(
+                PortDirection::Output,
                 name,
-                ValueType::from_bit_width(output.bit_width()).name(),
-                output.bit_width()
+                output.bit_width(),
+                port_descriptions.get(name),
+                options.check_inputs,
+                &mut w,
+            )?;
+            w.append_line(&format!(
+                "pub {}: {},",
+                identifier(name),
+                ValueType::from_bit_width(output.bit_width()).name()
             ))?;
         }
     }
@@ -140,6 +655,25 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
                 reg.value_name, type_name, reg.data.bit_width
             ))?;
             w.append_line(&format!("{}: {},", reg.next_name, type_name))?;
+            if poison {
+                let type_name = ValueType::Bool.name();
+                w.append_line(&format!("{}: {},", reg.written_name, type_name))?;
+                w.append_line(&format!("{}_poisoned: {},", reg.next_name, type_name))?;
+                w.append_line(&format!("{}_holds: {},", reg.next_name, type_name))?;
+            }
+        }
+    }
+
+    if !state_elements.latches.is_empty() {
+        w.append_newline()?;
+        w.append_line("// Latches")?;
+        for (_, latch) in state_elements.latches.iter() {
+            w.append_line(&format!(
+                "{}: {}, // {} bit(s)",
+                latch.value_name,
+                ValueType::from_bit_width(latch.data.bit_width).name(),
+                latch.data.bit_width
+            ))?;
         }
     }
 
@@ -166,14 +700,107 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
                 let type_name = ValueType::from_bit_width(mem.mem.element_bit_width).name();
                 w.append_line(&format!("{}: {},", read_signal_names.value_name, type_name))?;
             }
-            if mem.mem.write_port.borrow().is_some() {
+            for write_signal_names in mem.write_signal_names.iter() {
                 let type_name = ValueType::from_bit_width(mem.mem.address_bit_width).name();
-                w.append_line(&format!("{}: {},", mem.write_address_name, type_name))?;
+                w.append_line(&format!(
+                    "{}: {},",
+                    write_signal_names.address_name, type_name
+                ))?;
                 let type_name = ValueType::from_bit_width(mem.mem.element_bit_width).name();
-                w.append_line(&format!("{}: {},", mem.write_value_name, type_name))?;
+                w.append_line(&format!(
+                    "{}: {},",
+                    write_signal_names.value_name, type_name
+                ))?;
                 let type_name = ValueType::Bool.name();
-                w.append_line(&format!("{}: {},", mem.write_enable_name, type_name))?;
+                w.append_line(&format!(
+                    "{}: {},",
+                    write_signal_names.enable_name, type_name
+                ))?;
+                if let Some(mask_name) = &write_signal_names.mask_name {
+                    let type_name = ValueType::from_bit_width(mem.mem.element_bit_width).name();
+                    w.append_line(&format!("{}: {},", mask_name, type_name))?;
+                }
+            }
+            if poison {
+                for index in 0..mem.write_signal_names.len() {
+                    w.append_line(&format!(
+                        "{}_write_port_{}_poisoned: {},",
+                        mem.mem_name,
+                        index,
+                        ValueType::Bool.name()
+                    ))?;
+                }
+            }
+        }
+    }
+
+    if !state_elements.assertions.is_empty() {
+        w.append_newline()?;
+        w.append_line("// Assertions")?;
+        for assertion in state_elements.assertions.iter() {
+            w.append_line(&format!("{}: {},", assertion.name, ValueType::Bool.name()))?;
+        }
+    }
+
+    if !state_elements.assumptions.is_empty() {
+        w.append_newline()?;
+        w.append_line("// Assumptions")?;
+        for assumption in state_elements.assumptions.iter() {
+            w.append_line(&format!("{}: {},", assumption.name, ValueType::Bool.name()))?;
+        }
+    }
+
+    if !state_elements.covers.is_empty() {
+        w.append_newline()?;
+        w.append_line("// Covers")?;
+        for cover in state_elements.covers.iter() {
+            w.append_line(&format!("{}: {},", cover.name, ValueType::Bool.name()))?;
+        }
+        w.append_line("__cover_counts: Box<[u64]>,")?;
+    }
+
+    if !state_elements.probes.is_empty() {
+        w.append_newline()?;
+        w.append_line("// Probes")?;
+        for probe in state_elements.probes.iter() {
+            if let graph::SignalData::Reg { .. } = probe.signal.data {
+                continue;
+            }
+            w.append_line(&format!(
+                "{}: {}, // {} bit(s)",
+                probe.value_name,
+                ValueType::from_bit_width(probe.signal.bit_width()).name(),
+                probe.signal.bit_width()
+            ))?;
+        }
+    }
+
+    if !state_elements.traces.is_empty() {
+        w.append_newline()?;
+        w.append_line("// Traces")?;
+        for trace in state_elements.traces.iter() {
+            w.append_line(&format!(
+                "{}: {}, // {} bit(s)",
+                trace.name,
+                ValueType::from_bit_width(trace.signal.bit_width()).name(),
+                trace.signal.bit_width()
+            ))?;
+        }
+    }
+
+    if !toggled_signals.is_empty() {
+        w.append_newline()?;
+        w.append_line("// Toggle coverage")?;
+        for toggled_signal in toggled_signals.iter() {
+            let type_name = ValueType::from_bit_width(toggled_signal.bit_width).name();
+            if !toggled_signal.is_reg {
+                w.append_line(&format!(
+                    "{}: {}, // {} bit(s)",
+                    toggled_signal.value_name, type_name, toggled_signal.bit_width
+                ))?;
             }
+            w.append_line(&format!("{}: {},", toggled_signal.zeros_name, type_name))?;
+            w.append_line(&format!("{}: {},", toggled_signal.ones_name, type_name))?;
         }
     }
 
@@ -181,13 +808,107 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
     w.append_line("}")?;
     w.append_newline()?;
 
-    w.append_line(&format!("impl {} {{", m.name))?;
+    let mut state_fields = Vec::new();
+    if options.save_state {
+        gather_state_fields(
+            m,
+            "",
+            root_context,
+            &context_arena,
+            &state_elements,
+            poison,
+            &mut state_fields,
+        );
+
+        w.append_line(&format!(
+            "/// A snapshot of the registers and memories of a [`{}`], created by `save_state()` and restored by `load_state()`.",
+            struct_name
+        ))?;
+        w.append_line("#[allow(non_camel_case_types, non_snake_case)]")?;
+        w.append_line("#[derive(Clone, Debug, Default, Eq, PartialEq)]")?;
+        w.append_line(&format!(
+            "{}struct {} {{",
+            item_visibility, state_struct_name
+        ))?;
+        w.indent();
+        for field in state_fields.iter() {
+            w.append_line(&format!("pub {}: {},", field.name, field.type_name))?;
+        }
+        w.unindent()?;
+        w.append_line("}")?;
+        w.append_newline()?;
+    }
+
+    let coverage_report_struct_name = if options.struct_name.is_some() {
+        format!("{}CoverageReport", struct_name)
+    } else {
+        identifier(&format!("{}CoverageReport", m.name))
+    };
+    if options.toggle_coverage {
+        gen_coverage_report(m, &coverage_report_struct_name, item_visibility, &mut w)?;
+    }
+
+    w.append_line("#[allow(non_snake_case)]")?;
+    w.append_line(&format!("impl {} {{", struct_name))?;
     w.indent();
 
-    w.append_line(&format!("pub fn new() -> {} {{", m.name))?;
+    let constants = m.constants.borrow();
+    if !constants.is_empty() {
+        for (name, signal) in constants.iter() {
+            let (value, bit_width) = match signal.data {
+                graph::SignalData::Lit {
+                    ref value,
+                    bit_width,
+                } => (value, bit_width),
+                _ => unreachable!(),
+            };
+            w.append_indent()?;
+            w.append(&format!(
+                "pub const {}: {} = ",
+                identifier(name),
+                ValueType::from_bit_width(bit_width).name()
+            ))?;
+            Expr::from_constant(value, bit_width).write(&mut w)?;
+            w.append(";")?;
+            w.append_newline()?;
+        }
+        w.append_newline()?;
+    }
+
+    let randomize = options.uninitialized_registers == UninitializedRegisters::Random;
+    if randomize {
+        w.append_line("/// Creates a new simulator, with registers that don't have a default value randomized with seed `0`.")?;
+        w.append_line(&format!("pub fn new() -> {} {{", struct_name))?;
+        w.indent();
+        w.append_line(&format!("{}::new_with_seed(0)", struct_name))?;
+        w.unindent()?;
+        w.append_line("}")?;
+        w.append_newline()?;
+        w.append_line(
+            "/// Creates a new simulator, with registers that don't have a default value randomized with `seed`.",
+        )?;
+        w.append_line(&format!(
+            "pub fn new_with_seed({}: u64) -> {} {{",
+            if state_elements.regs.is_empty() {
+                "_seed"
+            } else {
+                "seed"
+            },
+            struct_name
+        ))?;
+    } else {
+        w.append_line("/// Creates a new simulator, with all inputs, outputs, and registers zeroed. Call `reset()` to give registers their default values.")?;
+        w.append_line(&format!("pub fn new() -> {} {{", struct_name))?;
+    }
     w.indent();
-    if !state_elements.mems.is_empty() {
-        w.append_line(&format!("let mut ret = {}::default();", m.name))?;
+    if !state_elements.mems.is_empty()
+        || !state_elements.covers.is_empty()
+        || (randomize && !state_elements.regs.is_empty())
+    {
+        w.append_line(&format!("let mut ret = {}::default();", struct_name))?;
+        if randomize && !state_elements.regs.is_empty() {
+            gen_register_randomization(&state_elements, &mut w)?;
+        }
         for (_, mem) in state_elements.mems.iter() {
             if let Some(ref initial_contents) = *mem.mem.initial_contents.borrow() {
                 w.append_line(&format!("ret.{} = vec![", mem.mem_name))?;
@@ -214,9 +935,15 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
                 ))?;
             }
         }
+        if !state_elements.covers.is_empty() {
+            w.append_line(&format!(
+                "ret.__cover_counts = vec![0; {}].into_boxed_slice();",
+                state_elements.covers.len()
+            ))?;
+        }
         w.append_line("ret")?;
     } else {
-        w.append_line(&format!("{}::default()", m.name))?;
+        w.append_line(&format!("{}::default()", struct_name))?;
     }
     w.unindent()?;
     w.append_line("}")?;
@@ -224,6 +951,42 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
     let mut reset_context = AssignmentContext::new();
     let mut posedge_clk_context = AssignmentContext::new();
 
+    // Sample covered signals before registers are updated
+    for toggled_signal in toggled_signals.iter() {
+        let value = Expr::Ref {
+            name: toggled_signal.value_name.clone(),
+            scope: Scope::Member,
+        };
+        let zeros = Expr::Ref {
+            name: toggled_signal.zeros_name.clone(),
+            scope: Scope::Member,
+        };
+        let inverted_value = posedge_clk_context.gen_temp(Expr::UnOp {
+            source: Box::new(value.clone()),
+            op: UnOp::Not,
+        });
+        posedge_clk_context.push(Assignment {
+            target: zeros.clone(),
+            expr: Expr::InfixBinOp {
+                lhs: Box::new(zeros),
+                rhs: Box::new(inverted_value),
+                op: InfixBinOp::BitOr,
+            },
+        });
+        let ones = Expr::Ref {
+            name: toggled_signal.ones_name.clone(),
+            scope: Scope::Member,
+        };
+        posedge_clk_context.push(Assignment {
+            target: ones.clone(),
+            expr: Expr::InfixBinOp {
+                lhs: Box::new(ones),
+                rhs: Box::new(value),
+                op: InfixBinOp::BitOr,
+            },
+        });
+    }
+
     for (_, reg) in state_elements.regs.iter() {
         let target = Expr::Ref {
             name: reg.value_name.clone(),
@@ -235,6 +998,15 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
                 target: target.clone(),
                 expr: Expr::from_constant(initial_value, reg.data.bit_width),
             });
+            if poison {
+                reset_context.push(Assignment {
+                    target: Expr::Ref {
+                        name: reg.written_name.clone(),
+                        scope: Scope::Member,
+                    },
+                    expr: Expr::from_constant(&true.into(), 1),
+                });
+            }
         }
 
         posedge_clk_context.push(Assignment {
@@ -247,63 +1019,67 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
     }
 
     for (_, mem) in state_elements.mems.iter() {
-        for (_, read_signal_names) in mem.read_signal_names.iter() {
-            let address = Expr::Ref {
-                name: read_signal_names.address_name.clone(),
-                scope: Scope::Member,
-            };
-            let enable = Expr::Ref {
-                name: read_signal_names.enable_name.clone(),
-                scope: Scope::Member,
-            };
-            let value = Expr::Ref {
-                name: read_signal_names.value_name.clone(),
+        match *mem.mem.read_during_write.borrow() {
+            graph::ReadDuringWrite::New => {
+                // Perform writes before reads so that reads observe newly-written values
+                gen_mem_writes(mem, &mut posedge_clk_context);
+                gen_mem_reads(mem, &mut posedge_clk_context);
+            }
+            graph::ReadDuringWrite::Old | graph::ReadDuringWrite::Undefined => {
+                gen_mem_reads(mem, &mut posedge_clk_context);
+                gen_mem_writes(mem, &mut posedge_clk_context);
+            }
+        }
+    }
+
+    for (index, cover) in state_elements.covers.iter().enumerate() {
+        let count = Expr::ArrayIndex {
+            target: Box::new(Expr::Ref {
+                name: "__cover_counts".into(),
                 scope: Scope::Member,
-            };
-            let element = Expr::ArrayIndex {
-                target: Box::new(Expr::Ref {
-                    name: mem.mem_name.clone(),
+            }),
+            index: Box::new(Expr::from_constant(&(index as u32).into(), 32)),
+        };
+        let incremented_count = posedge_clk_context.gen_temp(Expr::UnaryMemberCall {
+            target: Box::new(count.clone()),
+            name: "saturating_add".into(),
+            arg: Box::new(Expr::Constant {
+                value: Constant::U64(1),
+            }),
+        });
+        posedge_clk_context.push(Assignment {
+            target: count.clone(),
+            expr: Expr::Ternary {
+                cond: Box::new(Expr::Ref {
+                    name: cover.name.clone(),
                     scope: Scope::Member,
                 }),
-                index: Box::new(address),
-            };
-            // TODO: Conditional assign statement instead of always writing ternary
-            posedge_clk_context.push(Assignment {
-                target: value.clone(),
-                expr: Expr::Ternary {
-                    cond: Box::new(enable),
-                    when_true: Box::new(element),
-                    when_false: Box::new(value),
-                },
-            });
-        }
-        if mem.mem.write_port.borrow().is_some() {
-            let address = Expr::Ref {
-                name: mem.write_address_name.clone(),
-                scope: Scope::Member,
-            };
-            let value = Expr::Ref {
-                name: mem.write_value_name.clone(),
-                scope: Scope::Member,
-            };
-            let enable = Expr::Ref {
-                name: mem.write_enable_name.clone(),
+                when_true: Box::new(incremented_count),
+                when_false: Box::new(count),
+            },
+        });
+    }
+
+    if poison {
+        for (_, reg) in state_elements.regs.iter() {
+            // A register is written unless it only held its (poisoned) value
+            let written = Expr::Ref {
+                name: reg.written_name.clone(),
                 scope: Scope::Member,
             };
-            let element = Expr::ArrayIndex {
-                target: Box::new(Expr::Ref {
-                    name: mem.mem_name.clone(),
+            let written_next = posedge_clk_context.gen_temp(Expr::UnOp {
+                source: Box::new(Expr::Ref {
+                    name: format!("{}_holds", reg.next_name),
                     scope: Scope::Member,
                 }),
-                index: Box::new(address),
-            };
-            // TODO: Conditional assign statement instead of always writing ternary
+                op: UnOp::Not,
+            });
             posedge_clk_context.push(Assignment {
-                target: element.clone(),
-                expr: Expr::Ternary {
-                    cond: Box::new(enable),
-                    when_true: Box::new(value),
-                    when_false: Box::new(element),
+                target: written.clone(),
+                expr: Expr::InfixBinOp {
+                    lhs: Box::new(written),
+                    rhs: Box::new(written_next),
+                    op: InfixBinOp::BitOr,
                 },
             });
         }
@@ -311,6 +1087,9 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
 
     if !reset_context.is_empty() {
         w.append_newline()?;
+        w.append_line(
+            "/// Asserts reset, which gives registers that have a default value that value.",
+        )?;
         w.append_line("pub fn reset(&mut self) {")?;
         w.indent();
 
@@ -322,9 +1101,38 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
 
     if !posedge_clk_context.is_empty() {
         w.append_newline()?;
+        w.append_line("/// Applies a rising clock edge, which updates registers and memories with the values computed by the last `prop()`.")?;
         w.append_line("pub fn posedge_clk(&mut self) {")?;
         w.indent();
 
+        if poison {
+            for (_, reg) in state_elements.regs.iter() {
+                w.append_line(&format!("if self.{}_poisoned {{", reg.next_name))?;
+                w.indent();
+                w.append_line(&format!(
+                    "panic!(\"Register \\\"{{}}\\\" in module \\\"{{}}\\\" was clocked with a value derived from a register that hasn't been written yet.\", {:?}, {:?});",
+                    reg.data.name, reg.data.module.name
+                ))?;
+                w.unindent()?;
+                w.append_line("}")?;
+            }
+            for (_, mem) in state_elements.mems.iter() {
+                for index in 0..mem.write_signal_names.len() {
+                    w.append_line(&format!(
+                        "if self.{}_write_port_{}_poisoned {{",
+                        mem.mem_name, index
+                    ))?;
+                    w.indent();
+                    w.append_line(&format!(
+                        "panic!(\"Memory \\\"{{}}\\\" in module \\\"{{}}\\\" was written with a value derived from a register that hasn't been written yet.\", {:?}, {:?});",
+                        mem.mem.name, mem.mem.module.name
+                    ))?;
+                    w.unindent()?;
+                    w.append_line("}")?;
+                }
+            }
+        }
+
         posedge_clk_context.write(&mut w)?;
 
         w.unindent()?;
@@ -332,26 +1140,2009 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
     }
 
     w.append_newline()?;
+    w.append_line("/// Propagates the current inputs and register values to the outputs.")?;
     w.append_line("pub fn prop(&mut self) {")?;
     w.indent();
 
+    if options.check_inputs {
+        gen_input_checks(m, &mut w)?;
+    }
     prop_context.write(&mut w)?;
 
     w.unindent()?;
     w.append_line("}")?;
 
+    w.append_newline()?;
+    w.append_line("/// Simulates a single clock cycle: propagates the inputs, applies a rising clock edge, and propagates the new register values to the outputs.")?;
+    w.append_line("pub fn tick(&mut self) {")?;
+    w.indent();
+    if posedge_clk_context.is_empty() {
+        // Without any state elements, a clock edge has no effect
+        w.append_line("self.prop();")?;
+    } else {
+        w.append_line("self.prop();")?;
+        w.append_line("self.posedge_clk();")?;
+        w.append_line("self.prop();")?;
+    }
     w.unindent()?;
     w.append_line("}")?;
-    w.append_newline()?;
 
-    Ok(())
-}
+    w.append_newline()?;
+    w.append_line("/// Simulates `n` clock cycles with `tick()`.")?;
+    w.append_line("pub fn tick_n(&mut self, n: u32) {")?;
+    w.indent();
+    w.append_line("for _ in 0..n {")?;
+    w.indent();
+    w.append_line("self.tick();")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    if !state_elements.assertions.is_empty() || !state_elements.assumptions.is_empty() {
+        w.append_newline()?;
+        w.append_line("/// Panics if any assertion failed or any assumption was violated as of the last `prop()`, reporting `cycle` as the current cycle.")?;
+        w.append_line("pub fn check(&self, cycle: u64) {")?;
+        w.indent();
 
-    use crate::*;
+        for assertion in state_elements.assertions.iter() {
+            w.append_line(&format!("if !self.{} {{", assertion.name))?;
+            w.indent();
+            w.append_line(&format!(
+                "panic!(\"Assertion in module \\\"{{}}\\\" failed on cycle {{}}: {{}}\", {:?}, cycle, {:?});",
+                assertion.module.name,
+                assertion.message.as_ref().unwrap()
+            ))?;
+            w.unindent()?;
+            w.append_line("}")?;
+        }
+        for assumption in state_elements.assumptions.iter() {
+            w.append_line(&format!("if !self.{} {{", assumption.name))?;
+            w.indent();
+            w.append_line(&format!(
+                "panic!(\"Assumption in module \\\"{{}}\\\" was violated on cycle {{}}\", {:?}, cycle);",
+                assumption.module.name
+            ))?;
+            w.unindent()?;
+            w.append_line("}")?;
+        }
+
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    if !state_elements.covers.is_empty() {
+        w.append_newline()?;
+        w.append_line("/// Returns the number of clock cycles on which each cover property held, in the order the properties were declared.")?;
+        w.append_line("pub fn cover_counts(&self) -> &[u64] {")?;
+        w.indent();
+        w.append_line("&self.__cover_counts")?;
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    for probe in state_elements.probes.iter() {
+        w.append_newline()?;
+        w.append_line(&format!(
+            "/// Returns the current value of the probed signal `{}` ({} bit(s)).",
+            probe.path,
+            probe.signal.bit_width()
+        ))?;
+        w.append_line(&format!(
+            "pub fn {}(&self) -> {} {{",
+            probe.getter_name,
+            ValueType::from_bit_width(probe.signal.bit_width()).name()
+        ))?;
+        w.indent();
+        w.append_line(&format!("self.{}", probe.value_name))?;
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    if !state_elements.traces.is_empty() {
+        w.append_newline()?;
+        w.append_line("/// Calls `f` with the label and current value of each traced signal.")?;
+        w.append_line("pub fn trace(&self, f: &mut impl FnMut(&str, u128)) {")?;
+        w.indent();
+        for trace in state_elements.traces.iter() {
+            w.append_line(&format!(
+                "f({:?}, self.{} as u128);",
+                trace.label, trace.name
+            ))?;
+        }
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    if options.toggle_coverage {
+        w.append_newline()?;
+        w.append_line("/// Returns the toggle coverage collected on each clock edge so far.")?;
+        w.append_line(&format!(
+            "pub fn coverage(&self) -> {} {{",
+            coverage_report_struct_name
+        ))?;
+        w.indent();
+        w.append_line(&format!("{} {{", coverage_report_struct_name))?;
+        w.indent();
+        w.append_line("signals: vec![")?;
+        w.indent();
+        for toggled_signal in toggled_signals.iter() {
+            let mask = if toggled_signal.bit_width < 128 {
+                (1u128 << toggled_signal.bit_width) - 1
+            } else {
+                !0
+            };
+            w.append_line(&format!(
+                "({:?}, {}, self.{} as u128 & 0x{:x}, self.{} as u128 & 0x{:x}),",
+                toggled_signal.path,
+                toggled_signal.bit_width,
+                toggled_signal.zeros_name,
+                mask,
+                toggled_signal.ones_name,
+                mask
+            ))?;
+        }
+        w.unindent()?;
+        w.append_line("],")?;
+        w.unindent()?;
+        w.append_line("}")?;
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    gen_mem_accessors(m, "", root_context, &context_arena, &state_elements, &mut w)?;
+
+    if options.save_state {
+        w.append_newline()?;
+        w.append_line("/// Captures the current values of all registers and memories.")?;
+        w.append_line(&format!(
+            "pub fn save_state(&self) -> {} {{",
+            state_struct_name
+        ))?;
+        w.indent();
+        w.append_line(&format!("{} {{", state_struct_name))?;
+        w.indent();
+        for field in state_fields.iter() {
+            w.append_line(&format!(
+                "{}: self.{}{},",
+                field.name,
+                field.sim_name,
+                if field.is_mem { ".clone()" } else { "" }
+            ))?;
+        }
+        w.unindent()?;
+        w.append_line("}")?;
+        w.unindent()?;
+        w.append_line("}")?;
+
+        w.append_newline()?;
+        w.append_line("/// Restores register and memory values captured by `save_state()`. Call `prop()` afterwards to update the outputs.")?;
+        w.append_line(&format!(
+            "pub fn load_state(&mut self, {}: &{}) {{",
+            if state_fields.is_empty() {
+                "_state"
+            } else {
+                "state"
+            },
+            state_struct_name
+        ))?;
+        w.indent();
+        for field in state_fields.iter() {
+            if field.is_mem {
+                w.append_line(&format!(
+                    "self.{}.clone_from_slice(&state.{});",
+                    field.sim_name, field.name
+                ))?;
+            } else {
+                w.append_line(&format!("self.{} = state.{};", field.sim_name, field.name))?;
+            }
+        }
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    if options.vcd {
+        let mut vcd_vars = Vec::new();
+        let mut header_lines = vec!["$timescale 1ns $end".to_string()];
+        gen_vcd_scope(
+            m,
+            &m.name,
+            root_context,
+            &context_arena,
+            &state_elements,
+            &mut header_lines,
+            &mut vcd_vars,
+        );
+        header_lines.push("$enddefinitions $end".into());
+
+        w.append_newline()?;
+        w.append_line("/// Writes the header of a VCD file describing the dumped signals to `w`.")?;
+        w.append_line(
+            "pub fn dump_vcd_header(&self, w: &mut impl std::io::Write) -> std::io::Result<()> {",
+        )?;
+        w.indent();
+        for line in header_lines.iter() {
+            w.append_line(&format!("writeln!(w, \"{{}}\", {:?})?;", line))?;
+        }
+        w.append_line("Ok(())")?;
+        w.unindent()?;
+        w.append_line("}")?;
+
+        w.append_newline()?;
+        w.append_line("/// Writes the current values of the dumped signals to `w` as a VCD timestep at `time`.")?;
+        w.append_line("pub fn dump_vcd(&self, w: &mut impl std::io::Write, time: u64) -> std::io::Result<()> {")?;
+        w.indent();
+        w.append_line("writeln!(w, \"#{}\", time)?;")?;
+        for var in vcd_vars.iter() {
+            let value_type = ValueType::from_bit_width(var.bit_width);
+            w.append_line(&match value_type {
+                ValueType::Bool => format!(
+                    "writeln!(w, \"{{}}{{}}\", self.{} as u8, {:?})?;",
+                    var.field_name, var.id
+                ),
+                _ if value_type.bit_width() == var.bit_width => format!(
+                    "writeln!(w, \"b{{:b}} {{}}\", self.{}, {:?})?;",
+                    var.field_name, var.id
+                ),
+                _ => format!(
+                    "writeln!(w, \"b{{:b}} {{}}\", self.{} & 0x{:x}, {:?})?;",
+                    var.field_name,
+                    (1u128 << var.bit_width) - 1,
+                    var.id
+                ),
+            })?;
+        }
+        w.append_line("Ok(())")?;
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+
+    gen_debug_impl(
+        m,
+        &struct_name,
+        root_context,
+        &context_arena,
+        &state_elements,
+        &mut w,
+    )?;
+
+    if options.impl_sim_module {
+        gen_sim_module_impl(
+            m,
+            &struct_name,
+            &state_elements,
+            !reset_context.is_empty(),
+            !posedge_clk_context.is_empty(),
+            &mut w,
+        )?;
+    }
+
+    if options.module_name.is_some() {
+        w.unindent()?;
+        w.append_line("}")?;
+        w.append_newline()?;
+    }
+
+    Ok(())
+}
+
+/// Generates the `SimModule` trait, which generated simulators implement when [`GenerationOptions::impl_sim_module`] is set, and writes it to `w`.
+///
+/// The trait abstracts over the shape shared by all generated simulators, so that generic testbenches (such as table-driven test vector runners) can drive any of them:
+///
+/// ```text
+/// pub trait SimModule {
+///     fn new() -> Self where Self: Sized;
+///     fn reset(&mut self);
+///     fn prop(&mut self);
+///     fn posedge_clk(&mut self);
+///     fn tick(&mut self) { /* prop(), posedge_clk(), prop() */ }
+///
+///     // (name, bit width) pairs, in name order
+///     fn input_names() -> &'static [(&'static str, u32)] where Self: Sized;
+///     fn output_names() -> &'static [(&'static str, u32)] where Self: Sized;
+///
+///     fn set_input(&mut self, name: &str, value: u128);
+///     fn get_output(&self, name: &str) -> u128;
+///
+///     // Sets each input to a random value that fits in its bit width
+///     fn randomize_inputs(&mut self, next_u64: &mut dyn FnMut() -> u64) where Self: Sized { /* ... */ }
+///
+///     // The message of the first assertion that failed as of the last `prop()`, if any
+///     fn failed_assertion(&self) -> Option<&'static str> { None }
+///     // Whether all assumptions held as of the last `prop()`
+///     fn assumptions_hold(&self) -> bool { true }
+/// }
+///
+/// // Creates and resets an `M`, and then for each cycle, randomizes its inputs, calls `prop()`, passes it to `check`, and calls `posedge_clk()`
+/// pub fn fuzz_sim_module<M: SimModule>(num_cycles: u32, next_u64: &mut dyn FnMut() -> u64, check: impl FnMut(&M)) { /* ... */ }
+///
+/// // Returns a line per port that differs between the interfaces of `A` and `B` ("-" lines are only in `A`, "+" lines only in `B`), or `None` if they match
+/// pub fn sim_module_interface_diff<A: SimModule, B: SimModule>() -> Option<String> { /* ... */ }
+///
+/// // Checks that `A` and `B` behave identically for `num_cycles` cycles of random stimulus
+/// pub fn assert_sim_modules_equivalent<A: SimModule, B: SimModule>(num_cycles: u32, next_u64: &mut dyn FnMut() -> u64) { /* ... */ }
+///
+/// // Checks that `A` and `B` behave identically for `num_cycles` cycles of stimulus, where `stimulus` fills in each cycle's input values (in `input_names()` order)
+/// pub fn assert_sim_modules_equivalent_with_stimulus<A: SimModule, B: SimModule>(num_cycles: u32, stimulus: impl FnMut(u32, &mut [u128])) { /* ... */ }
+///
+/// pub enum BmcStrategy<'a> {
+///     // Tries every input sequence, shortest first
+///     Exhaustive,
+///     // Tries `num_runs` random input sequences of `depth` cycles each
+///     Random { num_runs: u32, next_u64: &'a mut dyn FnMut() -> u64 },
+/// }
+///
+/// // Limits on the number of input bits per cycle and per sequence that `BmcStrategy::Exhaustive` accepts
+/// pub const BMC_MAX_EXHAUSTIVE_INPUT_BITS: u32 = 8;
+/// pub const BMC_MAX_EXHAUSTIVE_SEQUENCE_BITS: u32 = 24;
+///
+/// #[derive(Clone, Debug, PartialEq, Eq)]
+/// pub struct BmcFailure {
+///     pub cycle: u32,
+///     pub assertion: &'static str,
+///     // One (name, value) pair per input for each cycle up to and including `cycle`, in `input_names()` order
+///     pub inputs: Vec<Vec<(&'static str, u128)>>,
+/// }
+///
+/// // Searches input sequences of up to `depth` cycles for one that makes an assertion fail
+/// pub fn bmc_sim_module<M: SimModule>(depth: u32, strategy: BmcStrategy) -> Option<BmcFailure> { /* ... */ }
+///
+/// // Feeds `inputs` (one slice of (name, value) pairs per cycle) into a new `M`, returning the first assertion failure, if any
+/// pub fn replay_sim_module<M: SimModule, C: AsRef<[(&'static str, u128)]>>(inputs: &[C]) -> Option<BmcFailure> { /* ... */ }
+/// ```
+///
+/// `reset` and `posedge_clk` do nothing for simulators without any state. `set_input` and `get_output` panic if the simulator doesn't have an input or output called `name`.
+///
+/// The `assert_sim_modules_equivalent` functions are intended for checking that a refactored `Module` behaves the same as the original, eg. in a `#[test]`.
+/// They panic up front with the interface diff if the simulators' inputs and outputs (names and bit widths) don't match.
+/// Otherwise, both simulators are created and reset, and then for each cycle, they're given the same input values, `prop()` is called, and all of their outputs are compared before `posedge_clk()` is called.
+/// The first cycle where an output differs causes a panic naming the cycle, the output, both of its values, and the input values on that cycle.
+///
+/// `bmc_sim_module` is a bounded model checker built on a simulator's assertions (see [`Module::assert`]).
+/// Each input sequence it tries is run on a freshly created and reset simulator: for each cycle, the inputs are set, `prop()` is called, and the assertions and assumptions are checked before `posedge_clk()` is called.
+/// Sequences where an assumption is violated are discarded, and the first assertion failure found is returned along with the inputs that lead up to it.
+/// `BmcStrategy::Exhaustive` refuses (panics) if the simulator's inputs have more than `BMC_MAX_EXHAUSTIVE_INPUT_BITS` bits in total, or if `depth` cycles of them have more than `BMC_MAX_EXHAUSTIVE_SEQUENCE_BITS` bits; use `BmcStrategy::Random` for larger input spaces.
+/// A failure's `inputs` field formatted with `{:?}` is a Rust array expression, which can be pasted into a regression test and passed to `replay_sim_module` (which panics if an assumption is violated).
+///
+/// Random values are taken from `next_u64`, so that generated code doesn't depend on any particular random number generator (for example, with the `rand` crate, `&mut || rng.next_u64()` can be passed).
+///
+/// Generate this trait exactly once in the scope that the generated simulators are included into.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("Adder");
+/// m.output("sum", m.input("a", 8) + m.input("b", 8));
+///
+/// let options = sim::GenerationOptions {
+///     impl_sim_module: true,
+///     ..Default::default()
+/// };
+/// let mut w = Vec::new();
+/// sim::generate_sim_module_trait(&mut w).unwrap();
+/// sim::generate_with_options(m, &options, &mut w).unwrap();
+/// assert!(std::str::from_utf8(&w).unwrap().contains("impl SimModule for Adder {"));
+/// ```
+///
+/// [`GenerationOptions::impl_sim_module`]: ./struct.GenerationOptions.html#structfield.impl_sim_module
+/// [`Module::assert`]: ../struct.Module.html#method.assert
+pub fn generate_sim_module_trait<W: Write>(w: W) -> Result<()> {
+    let mut w = code_writer::CodeWriter::new(w);
+
+    w.append_line("pub trait SimModule {")?;
+    w.indent();
+    w.append_line("fn new() -> Self")?;
+    w.append_line("where")?;
+    w.append_line("    Self: Sized;")?;
+    w.append_line("fn reset(&mut self);")?;
+    w.append_line("fn prop(&mut self);")?;
+    w.append_line("fn posedge_clk(&mut self);")?;
+    w.append_newline()?;
+    w.append_line("fn tick(&mut self) {")?;
+    w.indent();
+    w.append_line("self.prop();")?;
+    w.append_line("self.posedge_clk();")?;
+    w.append_line("self.prop();")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+    w.append_line("fn input_names() -> &'static [(&'static str, u32)]")?;
+    w.append_line("where")?;
+    w.append_line("    Self: Sized;")?;
+    w.append_line("fn output_names() -> &'static [(&'static str, u32)]")?;
+    w.append_line("where")?;
+    w.append_line("    Self: Sized;")?;
+    w.append_newline()?;
+    w.append_line("fn set_input(&mut self, name: &str, value: u128);")?;
+    w.append_line("fn get_output(&self, name: &str) -> u128;")?;
+    w.append_newline()?;
+    w.append_line("fn randomize_inputs(&mut self, next_u64: &mut dyn FnMut() -> u64)")?;
+    w.append_line("where")?;
+    w.append_line("    Self: Sized,")?;
+    w.append_line("{")?;
+    w.indent();
+    w.append_line("for &(name, bit_width) in Self::input_names() {")?;
+    w.indent();
+    w.append_line("let value = (next_u64() as u128) << 64 | next_u64() as u128;")?;
+    w.append_line("let mask = if bit_width < 128 { (1 << bit_width) - 1 } else { !0 };")?;
+    w.append_line("self.set_input(name, value & mask);")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+    w.append_line("fn failed_assertion(&self) -> Option<&'static str> {")?;
+    w.indent();
+    w.append_line("None")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("fn assumptions_hold(&self) -> bool {")?;
+    w.indent();
+    w.append_line("true")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+
+    w.append_line("pub fn fuzz_sim_module<M: SimModule>(")?;
+    w.indent();
+    w.append_line("num_cycles: u32,")?;
+    w.append_line("next_u64: &mut dyn FnMut() -> u64,")?;
+    w.append_line("mut check: impl FnMut(&M),")?;
+    w.unindent()?;
+    w.append_line(") {")?;
+    w.indent();
+    w.append_line("let mut m = M::new();")?;
+    w.append_line("m.reset();")?;
+    w.append_line("for _ in 0..num_cycles {")?;
+    w.indent();
+    w.append_line("m.randomize_inputs(next_u64);")?;
+    w.append_line("m.prop();")?;
+    w.append_line("check(&m);")?;
+    w.append_line("m.posedge_clk();")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+
+    w.append_line(
+        "pub fn sim_module_interface_diff<A: SimModule, B: SimModule>() -> Option<String> {",
+    )?;
+    w.indent();
+    w.append_line("let mut diff = String::new();")?;
+    w.append_line("for &(kind, a_ports, b_ports) in &[")?;
+    w.indent();
+    w.append_line("(\"input\", A::input_names(), B::input_names()),")?;
+    w.append_line("(\"output\", A::output_names(), B::output_names()),")?;
+    w.unindent()?;
+    w.append_line("] {")?;
+    w.indent();
+    w.append_line(
+        "for &(name, bit_width) in a_ports.iter().filter(|port| !b_ports.contains(port)) {",
+    )?;
+    w.indent();
+    w.append_line("diff.push_str(&format!(\"- {} {}: {} bit(s)\\n\", kind, name, bit_width));")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line(
+        "for &(name, bit_width) in b_ports.iter().filter(|port| !a_ports.contains(port)) {",
+    )?;
+    w.indent();
+    w.append_line("diff.push_str(&format!(\"+ {} {}: {} bit(s)\\n\", kind, name, bit_width));")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("if diff.is_empty() {")?;
+    w.indent();
+    w.append_line("None")?;
+    w.unindent()?;
+    w.append_line("} else {")?;
+    w.indent();
+    w.append_line("Some(diff)")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+    w.append_line("pub fn assert_sim_modules_equivalent<A: SimModule, B: SimModule>(")?;
+    w.indent();
+    w.append_line("num_cycles: u32,")?;
+    w.append_line("next_u64: &mut dyn FnMut() -> u64,")?;
+    w.unindent()?;
+    w.append_line(") {")?;
+    w.indent();
+    w.append_line("assert_sim_modules_equivalent_with_stimulus::<A, B>(num_cycles, |_, inputs| {")?;
+    w.indent();
+    w.append_line("for (value, &(_, bit_width)) in inputs.iter_mut().zip(A::input_names()) {")?;
+    w.indent();
+    w.append_line("let mask = if bit_width < 128 { (1 << bit_width) - 1 } else { !0 };")?;
+    w.append_line("*value = ((next_u64() as u128) << 64 | next_u64() as u128) & mask;")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("});")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+    w.append_line(
+        "pub fn assert_sim_modules_equivalent_with_stimulus<A: SimModule, B: SimModule>(",
+    )?;
+    w.indent();
+    w.append_line("num_cycles: u32,")?;
+    w.append_line("mut stimulus: impl FnMut(u32, &mut [u128]),")?;
+    w.unindent()?;
+    w.append_line(") {")?;
+    w.indent();
+    w.append_line("let a_name = std::any::type_name::<A>().rsplit(\"::\").next().unwrap();")?;
+    w.append_line("let b_name = std::any::type_name::<B>().rsplit(\"::\").next().unwrap();")?;
+    w.append_line("if let Some(diff) = sim_module_interface_diff::<A, B>() {")?;
+    w.indent();
+    w.append_line("panic!(")?;
+    w.indent();
+    w.append_line("\"Simulators {} and {} have different interfaces (\\\"-\\\" ports are only in {}, \\\"+\\\" ports are only in {}):\\n{}\",")?;
+    w.append_line("a_name, b_name, a_name, b_name, diff")?;
+    w.unindent()?;
+    w.append_line(");")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+    w.append_line("let mut a = A::new();")?;
+    w.append_line("let mut b = B::new();")?;
+    w.append_line("a.reset();")?;
+    w.append_line("b.reset();")?;
+    w.append_line("let mut inputs = vec![0; A::input_names().len()];")?;
+    w.append_line("for cycle in 0..num_cycles {")?;
+    w.indent();
+    w.append_line("stimulus(cycle, &mut inputs);")?;
+    w.append_line("for (&(name, _), &value) in A::input_names().iter().zip(&inputs) {")?;
+    w.indent();
+    w.append_line("a.set_input(name, value);")?;
+    w.append_line("b.set_input(name, value);")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("a.prop();")?;
+    w.append_line("b.prop();")?;
+    w.append_line("for &(name, _) in A::output_names() {")?;
+    w.indent();
+    w.append_line("let (a_value, b_value) = (a.get_output(name), b.get_output(name));")?;
+    w.append_line("if a_value != b_value {")?;
+    w.indent();
+    w.append_line("let inputs = A::input_names()")?;
+    w.indent();
+    w.append_line(".iter()")?;
+    w.append_line(".zip(&inputs)")?;
+    w.append_line(".map(|(&(name, _), value)| format!(\"{} = {:#x}\", name, value))")?;
+    w.append_line(".collect::<Vec<_>>();")?;
+    w.unindent()?;
+    w.append_line("panic!(")?;
+    w.indent();
+    w.append_line("\"Simulators {} and {} diverged on cycle {}: output \\\"{}\\\" is {:#x} in {} but {:#x} in {}. Inputs on that cycle: [{}].\",")?;
+    w.append_line(
+        "a_name, b_name, cycle, name, a_value, a_name, b_value, b_name, inputs.join(\", \")",
+    )?;
+    w.unindent()?;
+    w.append_line(");")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("a.posedge_clk();")?;
+    w.append_line("b.posedge_clk();")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+
+    w.append_line("pub enum BmcStrategy<'a> {")?;
+    w.indent();
+    w.append_line("Exhaustive,")?;
+    w.append_line("Random {")?;
+    w.indent();
+    w.append_line("num_runs: u32,")?;
+    w.append_line("next_u64: &'a mut dyn FnMut() -> u64,")?;
+    w.unindent()?;
+    w.append_line("},")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+    w.append_line("pub const BMC_MAX_EXHAUSTIVE_INPUT_BITS: u32 = 8;")?;
+    w.append_line("pub const BMC_MAX_EXHAUSTIVE_SEQUENCE_BITS: u32 = 24;")?;
+    w.append_newline()?;
+    w.append_line("#[derive(Clone, Debug, PartialEq, Eq)]")?;
+    w.append_line("pub struct BmcFailure {")?;
+    w.indent();
+    w.append_line("pub cycle: u32,")?;
+    w.append_line("pub assertion: &'static str,")?;
+    w.append_line("pub inputs: Vec<Vec<(&'static str, u128)>>,")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+    w.append_line(
+        "pub fn bmc_sim_module<M: SimModule>(depth: u32, strategy: BmcStrategy) -> Option<BmcFailure> {",
+    )?;
+    w.indent();
+    w.append_line("let run = |inputs: &[Vec<(&'static str, u128)>]| {")?;
+    w.indent();
+    w.append_line("let mut m = M::new();")?;
+    w.append_line("m.reset();")?;
+    w.append_line("for (cycle, values) in inputs.iter().enumerate() {")?;
+    w.indent();
+    w.append_line("for &(name, value) in values {")?;
+    w.indent();
+    w.append_line("m.set_input(name, value);")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("m.prop();")?;
+    w.append_line("if !m.assumptions_hold() {")?;
+    w.indent();
+    w.append_line("return None;")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("if let Some(assertion) = m.failed_assertion() {")?;
+    w.indent();
+    w.append_line("return Some(BmcFailure {")?;
+    w.indent();
+    w.append_line("cycle: cycle as u32,")?;
+    w.append_line("assertion,")?;
+    w.append_line("inputs: inputs[..=cycle].to_vec(),")?;
+    w.unindent()?;
+    w.append_line("});")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("m.posedge_clk();")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("None")?;
+    w.unindent()?;
+    w.append_line("};")?;
+    w.append_newline()?;
+    w.append_line("match strategy {")?;
+    w.indent();
+    w.append_line("BmcStrategy::Exhaustive => {")?;
+    w.indent();
+    w.append_line("let name = std::any::type_name::<M>().rsplit(\"::\").next().unwrap();")?;
+    w.append_line(
+        "let input_bits: u32 = M::input_names().iter().map(|&(_, bit_width)| bit_width).sum();",
+    )?;
+    w.append_line("if input_bits > BMC_MAX_EXHAUSTIVE_INPUT_BITS {")?;
+    w.indent();
+    w.append_line("panic!(")?;
+    w.indent();
+    w.append_line("\"Cannot exhaustively check simulator {}, because its inputs have {} bit(s) per cycle, which is more than BMC_MAX_EXHAUSTIVE_INPUT_BITS ({}). Use BmcStrategy::Random instead.\",")?;
+    w.append_line("name, input_bits, BMC_MAX_EXHAUSTIVE_INPUT_BITS")?;
+    w.unindent()?;
+    w.append_line(");")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("if input_bits * depth > BMC_MAX_EXHAUSTIVE_SEQUENCE_BITS {")?;
+    w.indent();
+    w.append_line("panic!(")?;
+    w.indent();
+    w.append_line("\"Cannot exhaustively check simulator {} to a depth of {} cycle(s), because its input sequences would have {} bit(s), which is more than BMC_MAX_EXHAUSTIVE_SEQUENCE_BITS ({}). Use a smaller depth or BmcStrategy::Random instead.\",")?;
+    w.append_line("name, depth, input_bits * depth, BMC_MAX_EXHAUSTIVE_SEQUENCE_BITS")?;
+    w.unindent()?;
+    w.append_line(");")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+    w.append_line(
+        "// Shorter sequences are tried first, so the first failure found is a shortest one",
+    )?;
+    w.append_line("for length in 1..=depth {")?;
+    w.indent();
+    w.append_line("for sequence in 0..1u64 << (input_bits * length) {")?;
+    w.indent();
+    w.append_line("let mut bits = sequence;")?;
+    w.append_line("let inputs = (0..length)")?;
+    w.indent();
+    w.append_line(".map(|_| {")?;
+    w.indent();
+    w.append_line("M::input_names()")?;
+    w.indent();
+    w.append_line(".iter()")?;
+    w.append_line(".map(|&(name, bit_width)| {")?;
+    w.indent();
+    w.append_line("let value = bits & ((1 << bit_width) - 1);")?;
+    w.append_line("bits >>= bit_width;")?;
+    w.append_line("(name, value as u128)")?;
+    w.unindent()?;
+    w.append_line("})")?;
+    w.append_line(".collect()")?;
+    w.unindent()?;
+    w.unindent()?;
+    w.append_line("})")?;
+    w.append_line(".collect::<Vec<_>>();")?;
+    w.unindent()?;
+    w.append_line("if let Some(failure) = run(&inputs) {")?;
+    w.indent();
+    w.append_line("return Some(failure);")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("None")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("BmcStrategy::Random { num_runs, next_u64 } => {")?;
+    w.indent();
+    w.append_line("for _ in 0..num_runs {")?;
+    w.indent();
+    w.append_line("let inputs = (0..depth)")?;
+    w.indent();
+    w.append_line(".map(|_| {")?;
+    w.indent();
+    w.append_line("M::input_names()")?;
+    w.indent();
+    w.append_line(".iter()")?;
+    w.append_line(".map(|&(name, bit_width)| {")?;
+    w.indent();
+    w.append_line("let mask = if bit_width < 128 { (1 << bit_width) - 1 } else { !0 };")?;
+    w.append_line("(name, ((next_u64() as u128) << 64 | next_u64() as u128) & mask)")?;
+    w.unindent()?;
+    w.append_line("})")?;
+    w.append_line(".collect()")?;
+    w.unindent()?;
+    w.unindent()?;
+    w.append_line("})")?;
+    w.append_line(".collect::<Vec<_>>();")?;
+    w.unindent()?;
+    w.append_line("if let Some(failure) = run(&inputs) {")?;
+    w.indent();
+    w.append_line("return Some(failure);")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("None")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+    w.append_line(
+        "pub fn replay_sim_module<M: SimModule, C: AsRef<[(&'static str, u128)]>>(inputs: &[C]) -> Option<BmcFailure> {",
+    )?;
+    w.indent();
+    w.append_line("let mut m = M::new();")?;
+    w.append_line("m.reset();")?;
+    w.append_line("for (cycle, values) in inputs.iter().enumerate() {")?;
+    w.indent();
+    w.append_line("for &(name, value) in values.as_ref() {")?;
+    w.indent();
+    w.append_line("m.set_input(name, value);")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("m.prop();")?;
+    w.append_line("if !m.assumptions_hold() {")?;
+    w.indent();
+    w.append_line("panic!(")?;
+    w.indent();
+    w.append_line(
+        "\"An assumption was violated on cycle {} while replaying inputs into simulator {}.\",",
+    )?;
+    w.append_line("cycle,")?;
+    w.append_line("std::any::type_name::<M>().rsplit(\"::\").next().unwrap()")?;
+    w.unindent()?;
+    w.append_line(");")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("if let Some(assertion) = m.failed_assertion() {")?;
+    w.indent();
+    w.append_line("return Some(BmcFailure {")?;
+    w.indent();
+    w.append_line("cycle: cycle as u32,")?;
+    w.append_line("assertion,")?;
+    w.append_line(
+        "inputs: inputs[..=cycle].iter().map(|values| values.as_ref().to_vec()).collect(),",
+    )?;
+    w.unindent()?;
+    w.append_line("});")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("m.posedge_clk();")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line("None")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+
+    Ok(())
+}
+
+/// Generates a Rust simulator for the `Module` `m` according to `options` as a [`TokenStream`], rather than writing it as text.
+///
+/// This is intended for procedural macros that generate simulators inline, instead of with a build script that writes code to be `include!`d, and is only available with the `tokens` feature enabled.
+/// The generated code is identical to that of [`generate_with_options`], except that comments aren't preserved (doc comments become `#[doc]` attributes).
+///
+/// [`TokenStream`]: https://docs.rs/proc-macro2/1.0/proc_macro2/struct.TokenStream.html
+/// [`generate_with_options`]: ./fn.generate_with_options.html
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`generate_with_options`].
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("Adder");
+/// m.output("sum", m.input("a", 8) + m.input("b", 8));
+///
+/// let tokens = sim::generate_tokens(m, &sim::GenerationOptions::default());
+/// assert!(tokens.to_string().contains("# [derive (Default)] pub struct Adder"));
+/// ```
+#[cfg(feature = "tokens")]
+pub fn generate_tokens<'a>(
+    m: &'a graph::Module<'a>,
+    options: &GenerationOptions,
+) -> proc_macro2::TokenStream {
+    let mut w = Vec::new();
+    generate_with_options(m, options, &mut w).expect("Writing to a Vec can't fail");
+    parse_tokens(w)
+}
+
+/// Generates the `SimModule` trait (see [`generate_sim_module_trait`]) as a [`TokenStream`].
+///
+/// This is only available with the `tokens` feature enabled.
+///
+/// [`generate_sim_module_trait`]: ./fn.generate_sim_module_trait.html
+/// [`TokenStream`]: https://docs.rs/proc-macro2/1.0/proc_macro2/struct.TokenStream.html
+#[cfg(feature = "tokens")]
+pub fn generate_sim_module_trait_tokens() -> proc_macro2::TokenStream {
+    let mut w = Vec::new();
+    generate_sim_module_trait(&mut w).expect("Writing to a Vec can't fail");
+    parse_tokens(w)
+}
+
+#[cfg(feature = "tokens")]
+fn parse_tokens(code: Vec<u8>) -> proc_macro2::TokenStream {
+    let code = String::from_utf8(code).expect("Generated code should be valid UTF-8");
+    code.parse()
+        .unwrap_or_else(|e| panic!("Generated code should be valid Rust tokens: {}", e))
+}
+
+/// Returns the Rust identifier that the generated simulator uses for `name`, such as the name of an input, output, or the generated `Module` itself.
+///
+/// Names that are already valid identifiers are used as-is, except for keywords, which become raw identifiers (for example, an input called `type` becomes the field `r#type`).
+/// The keywords `self`, `Self`, `super`, and `crate` can't be raw identifiers, so they get a trailing `_` instead.
+/// Otherwise, each character that isn't an ASCII letter, digit, or `_` is replaced with `_`, and names that don't start with a letter or `_` get a leading `_` (for example, `Mod-1` becomes `Mod_1`, and `1st` becomes `_1st`).
+///
+/// Generated simulators panic if two of a `Module`'s inputs and outputs would map to the same identifier.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// assert_eq!(sim::identifier("data"), "data");
+/// assert_eq!(sim::identifier("type"), "r#type");
+/// assert_eq!(sim::identifier("self"), "self_");
+/// assert_eq!(sim::identifier("Mod-1"), "Mod_1");
+/// assert_eq!(sim::identifier("1st"), "_1st");
+/// ```
+pub fn identifier(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let",
+        "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+        "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+        "virtual", "where", "while", "yield",
+    ];
+    const NON_RAW_KEYWORDS: &[&str] = &["_", "crate", "self", "Self", "super"];
+
+    if KEYWORDS.contains(&name) {
+        return format!("r#{}", name);
+    }
+    if NON_RAW_KEYWORDS.contains(&name) {
+        return format!("{}_", name);
+    }
+
+    sanitize_identifier(name)
+}
+
+// Like `identifier`, but without keyword handling, for names that are used as part of larger identifiers
+fn sanitize_identifier(name: &str) -> String {
+    let mut ret = String::with_capacity(name.len() + 1);
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        ret.push('_');
+    }
+    ret.extend(name.chars().map(|c| {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            c
+        } else {
+            '_'
+        }
+    }));
+    ret
+}
+
+// Whether `name` can be used as-is as a (non-raw) identifier in generated code
+fn is_valid_identifier(name: &str) -> bool {
+    identifier(name) == name
+}
+
+#[derive(Clone, Copy)]
+enum PortDirection {
+    Input,
+    Output,
+}
+
+fn gen_port_doc<W: Write>(
+    direction: PortDirection,
+    name: &str,
+    bit_width: u32,
+    description: Option<&String>,
+    check_inputs: bool,
+    w: &mut code_writer::CodeWriter<W>,
+) -> Result<()> {
+    if let Some(description) = description {
+        for line in description.lines() {
+            w.append_line(format!("/// {}", line).trim_end())?;
+        }
+        w.append_line("///")?;
+    }
+    let value_type = ValueType::from_bit_width(bit_width);
+    w.append_line(&format!(
+        "/// {} `{}`: {} bit(s), stored as `{}`.",
+        match direction {
+            PortDirection::Input => "Input",
+            PortDirection::Output => "Output",
+        },
+        name,
+        bit_width,
+        value_type.name()
+    ))?;
+    if value_type != ValueType::Bool && bit_width != value_type.bit_width() {
+        let mask = (1u128 << bit_width) - 1;
+        w.append_line(&match direction {
+            PortDirection::Input if check_inputs => format!("/// Only the bits in `0x{:x}` are used, and setting any other bit panics in `prop()` in debug builds.", mask),
+            PortDirection::Input => format!("/// Only the bits in `0x{:x}` are used; any other bits are masked off.", mask),
+            PortDirection::Output => format!("/// Bits outside of `0x{:x}` are always zero.", mask),
+        })?;
+    }
+
+    Ok(())
+}
+
+fn gen_input_checks<'a, W: Write>(
+    m: &'a graph::Module<'a>,
+    w: &mut code_writer::CodeWriter<W>,
+) -> Result<()> {
+    for (name, input) in m.inputs.borrow().iter() {
+        let bit_width = input.bit_width();
+        let value_type = ValueType::from_bit_width(bit_width);
+        // Inputs that fill their storage type can't be out of range
+        if bit_width == value_type.bit_width() {
+            continue;
+        }
+        w.append_line(&format!(
+            "debug_assert!(self.{} <= 0x{:x}{}, \"Input \\\"{}\\\" of module \\\"{}\\\" was set to {{:#x}}, which doesn't fit in {} bit(s).\", self.{});",
+            identifier(name),
+            (1u128 << bit_width) - 1,
+            value_type.name(),
+            name,
+            m.name,
+            bit_width,
+            identifier(name)
+        ))?;
+    }
+
+    Ok(())
+}
+
+fn gen_sim_module_impl<'a, W: Write>(
+    m: &'a graph::Module<'a>,
+    struct_name: &str,
+    state_elements: &StateElements,
+    has_reset: bool,
+    has_posedge_clk: bool,
+    w: &mut code_writer::CodeWriter<W>,
+) -> Result<()> {
+    let inputs = m.inputs.borrow();
+    let outputs = m.outputs.borrow();
+
+    w.append_line(&format!("impl SimModule for {} {{", struct_name))?;
+    w.indent();
+
+    w.append_line(&format!("fn new() -> {} {{", struct_name))?;
+    w.indent();
+    w.append_line(&format!("{}::new()", struct_name))?;
+    w.unindent()?;
+    w.append_line("}")?;
+
+    w.append_newline()?;
+    if has_reset {
+        w.append_line("fn reset(&mut self) {")?;
+        w.indent();
+        w.append_line(&format!("{}::reset(self)", struct_name))?;
+        w.unindent()?;
+        w.append_line("}")?;
+    } else {
+        w.append_line("fn reset(&mut self) {}")?;
+    }
+
+    w.append_newline()?;
+    w.append_line("fn prop(&mut self) {")?;
+    w.indent();
+    w.append_line(&format!("{}::prop(self)", struct_name))?;
+    w.unindent()?;
+    w.append_line("}")?;
+
+    w.append_newline()?;
+    if has_posedge_clk {
+        w.append_line("fn posedge_clk(&mut self) {")?;
+        w.indent();
+        w.append_line(&format!("{}::posedge_clk(self)", struct_name))?;
+        w.unindent()?;
+        w.append_line("}")?;
+    } else {
+        w.append_line("fn posedge_clk(&mut self) {}")?;
+    }
+
+    for (fn_name, ports) in [("input_names", &inputs), ("output_names", &outputs)].iter() {
+        w.append_newline()?;
+        w.append_line(&format!(
+            "fn {}() -> &'static [(&'static str, u32)] {{",
+            fn_name
+        ))?;
+        w.indent();
+        w.append_line("&[")?;
+        w.indent();
+        for (name, signal) in ports.iter() {
+            w.append_line(&format!("({:?}, {}),", name, signal.bit_width()))?;
+        }
+        w.unindent()?;
+        w.append_line("]")?;
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    w.append_newline()?;
+    w.append_line(&format!(
+        "fn set_input(&mut self, name: &str, {}: u128) {{",
+        if inputs.is_empty() { "_value" } else { "value" }
+    ))?;
+    w.indent();
+    w.append_line("match name {")?;
+    w.indent();
+    for (name, input) in inputs.iter() {
+        w.append_line(&match ValueType::from_bit_width(input.bit_width()) {
+            ValueType::Bool => format!("{:?} => self.{} = value & 1 != 0,", name, identifier(name)),
+            value_type => format!(
+                "{:?} => self.{} = value as {},",
+                name,
+                identifier(name),
+                value_type.name()
+            ),
+        })?;
+    }
+    w.append_line(&format!(
+        "_ => panic!(\"Module \\\"{}\\\" doesn't have an input called \\\"{{}}\\\".\", name),",
+        m.name
+    ))?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+
+    w.append_newline()?;
+    w.append_line("fn get_output(&self, name: &str) -> u128 {")?;
+    w.indent();
+    w.append_line("match name {")?;
+    w.indent();
+    for (name, _) in outputs.iter() {
+        w.append_line(&format!("{:?} => self.{} as u128,", name, identifier(name)))?;
+    }
+    w.append_line(&format!(
+        "_ => panic!(\"Module \\\"{}\\\" doesn't have an output called \\\"{{}}\\\".\", name),",
+        m.name
+    ))?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+
+    if !state_elements.assertions.is_empty() {
+        w.append_newline()?;
+        w.append_line("fn failed_assertion(&self) -> Option<&'static str> {")?;
+        w.indent();
+        for assertion in state_elements.assertions.iter() {
+            w.append_line(&format!("if !self.{} {{", assertion.name))?;
+            w.indent();
+            w.append_line(&format!(
+                "return Some({:?});",
+                assertion.message.as_ref().unwrap()
+            ))?;
+            w.unindent()?;
+            w.append_line("}")?;
+        }
+        w.append_line("None")?;
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    if !state_elements.assumptions.is_empty() {
+        w.append_newline()?;
+        w.append_line("fn assumptions_hold(&self) -> bool {")?;
+        w.indent();
+        w.append_line(
+            &state_elements
+                .assumptions
+                .iter()
+                .map(|assumption| format!("self.{}", assumption.name))
+                .collect::<Vec<_>>()
+                .join(" && "),
+        )?;
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+
+    Ok(())
+}
+
+fn gen_register_randomization<W: Write>(
+    state_elements: &StateElements,
+    w: &mut code_writer::CodeWriter<W>,
+) -> Result<()> {
+    // splitmix64
+    w.append_line("let mut state = seed;")?;
+    w.append_line("let mut next_random = || {")?;
+    w.indent();
+    w.append_line("state = state.wrapping_add(0x9e3779b97f4a7c15);")?;
+    w.append_line("let mut z = state;")?;
+    w.append_line("z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);")?;
+    w.append_line("z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);")?;
+    w.append_line("z ^ (z >> 31)")?;
+    w.unindent()?;
+    w.append_line("};")?;
+
+    // Registers are enumerated in a deterministic order, so each seed always produces the same values
+    for reg in state_elements.regs.values() {
+        let bit_width = reg.data.bit_width;
+        let value = match ValueType::from_bit_width(bit_width) {
+            ValueType::Bool => "next_random() & 1 != 0".into(),
+            ValueType::U32 | ValueType::U64 => format!(
+                "(next_random() & 0x{:x}) as {}",
+                (1u128 << bit_width) - 1,
+                ValueType::from_bit_width(bit_width).name()
+            ),
+            ValueType::U128 => format!(
+                "(((next_random() as u128) << 64) | next_random() as u128) & 0x{:x}",
+                if bit_width == 128 {
+                    !0
+                } else {
+                    (1u128 << bit_width) - 1
+                }
+            ),
+            ValueType::I32 | ValueType::I64 | ValueType::I128 => unreachable!(),
+        };
+        w.append_line(&format!("ret.{} = {};", reg.value_name, value))?;
+    }
+
+    Ok(())
+}
+
+struct StateField {
+    name: String,
+    sim_name: String,
+    type_name: String,
+    is_mem: bool,
+}
+
+fn gather_state_fields<'graph, 'arena>(
+    m: &'graph graph::Module<'graph>,
+    prefix: &str,
+    context: &'arena ModuleContext<'graph, 'arena>,
+    context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
+    state_elements: &StateElements<'graph, 'arena>,
+    poison: bool,
+    state_fields: &mut Vec<StateField>,
+) {
+    let mut add_field = |name: String, sim_name: &str, type_name: String, is_mem: bool| {
+        // Element names aren't necessarily unique, so disambiguate them with a numeric suffix
+        let name = identifier(&name);
+        let mut unique_name = name.clone();
+        let mut suffix = 1;
+        while state_fields.iter().any(|field| field.name == unique_name) {
+            unique_name = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        state_fields.push(StateField {
+            name: unique_name,
+            sim_name: sim_name.into(),
+            type_name,
+            is_mem,
+        });
+    };
+
+    for &reg in m.registers.borrow().iter() {
+        // Registers that don't affect anything aren't simulated
+        if let Some(reg) = state_elements.regs.get(&(context, reg)) {
+            let name = format!("{}{}", prefix, reg.data.name);
+            if poison {
+                add_field(
+                    format!("{}_written", name),
+                    &reg.written_name,
+                    ValueType::Bool.name().into(),
+                    false,
+                );
+            }
+            add_field(
+                name,
+                &reg.value_name,
+                ValueType::from_bit_width(reg.data.bit_width).name().into(),
+                false,
+            );
+        }
+    }
+
+    for &latch in m.latches.borrow().iter() {
+        if let Some(latch) = state_elements.latches.get(&(context, latch)) {
+            add_field(
+                format!("{}{}", prefix, latch.data.name),
+                &latch.value_name,
+                ValueType::from_bit_width(latch.data.bit_width)
+                    .name()
+                    .into(),
+                false,
+            );
+        }
+    }
+
+    for &mem in m.mems.borrow().iter() {
+        if let Some(mem) = state_elements.mems.get(&(context, mem)) {
+            let name = format!("{}{}", prefix, mem.mem.name);
+            let element_type_name = ValueType::from_bit_width(mem.mem.element_bit_width).name();
+            add_field(
+                name.clone(),
+                &mem.mem_name,
+                format!("Box<[{}]>", element_type_name),
+                true,
+            );
+            for (index, (address, enable)) in mem.mem.read_ports.borrow().iter().enumerate() {
+                add_field(
+                    format!("{}__read_port_{}", name, index),
+                    &mem.read_signal_names[&(*address, *enable)].value_name,
+                    element_type_name.into(),
+                    false,
+                );
+            }
+        }
+    }
+
+    for &instance in m.instances.borrow().iter() {
+        gather_state_fields(
+            instance.instantiated_module,
+            &format!("{}{}__", prefix, instance.name),
+            context.get_child(instance, context_arena),
+            context_arena,
+            state_elements,
+            poison,
+            state_fields,
+        );
+    }
+}
+
+struct ToggledSignal<'graph, 'arena> {
+    context: &'arena ModuleContext<'graph, 'arena>,
+    signal: &'graph graph::Signal<'graph>,
+    is_reg: bool,
+    path: String,
+    bit_width: u32,
+    value_name: String,
+    zeros_name: String,
+    ones_name: String,
+}
+
+fn gather_toggled_signals<'graph, 'arena>(
+    m: &'graph graph::Module<'graph>,
+    prefix: &str,
+    context: &'arena ModuleContext<'graph, 'arena>,
+    context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
+    state_elements: &mut StateElements<'graph, 'arena>,
+    toggled_signals: &mut Vec<ToggledSignal<'graph, 'arena>>,
+) {
+    let mut add_signal =
+        |signal: &'graph graph::Signal<'graph>,
+         name: &str,
+         state_elements: &mut StateElements<'graph, 'arena>| {
+            state_elements.gather(signal, context, context_arena);
+            let index = toggled_signals.len();
+            let (is_reg, value_name) = match signal.data {
+                graph::SignalData::Reg { .. } => (
+                    true,
+                    state_elements.regs[&(context, signal)].value_name.clone(),
+                ),
+                _ => (false, format!("__toggle_value_{}", index)),
+            };
+            toggled_signals.push(ToggledSignal {
+                context,
+                signal,
+                is_reg,
+                path: format!("{}{}", prefix, name),
+                bit_width: signal.bit_width(),
+                value_name,
+                zeros_name: format!("__toggle_zeros_{}", index),
+                ones_name: format!("__toggle_ones_{}", index),
+            });
+        };
+
+    for &reg in m.registers.borrow().iter() {
+        let name = match reg.data {
+            graph::SignalData::Reg { data } => &data.name,
+            _ => unreachable!(),
+        };
+        add_signal(reg, name, state_elements);
+    }
+    for (name, &signal) in m.named_signals.borrow().iter() {
+        add_signal(signal, name, state_elements);
+    }
+
+    for &instance in m.instances.borrow().iter() {
+        gather_toggled_signals(
+            instance.instantiated_module,
+            &format!("{}{}.", prefix, instance.name),
+            context.get_child(instance, context_arena),
+            context_arena,
+            state_elements,
+            toggled_signals,
+        );
+    }
+}
+
+fn gen_coverage_report<W: Write>(
+    m: &graph::Module,
+    struct_name: &str,
+    visibility: &str,
+    w: &mut code_writer::CodeWriter<W>,
+) -> Result<()> {
+    w.append_line(&format!(
+        "/// Toggle coverage of the registers and named signals in the hierarchy of the kaze module `{}`.",
+        m.name
+    ))?;
+    w.append_line("#[allow(non_camel_case_types)]")?;
+    w.append_line("#[derive(Clone, Debug, Eq, PartialEq)]")?;
+    w.append_line(&format!("{}struct {} {{", visibility, struct_name))?;
+    w.indent();
+    w.append_line(
+        "/// (path, bit width, bits observed as 0, bits observed as 1) for each covered signal",
+    )?;
+    w.append_line("pub signals: Vec<(&'static str, u32, u128, u128)>,")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+
+    w.append_line(&format!("impl {} {{", struct_name))?;
+    w.indent();
+    w.append_line("/// Returns a mask of the bits of the signal at `path` that haven't been observed as both 0 and 1.")?;
+    w.append_line("pub fn uncovered_bits(&self, path: &str) -> u128 {")?;
+    w.indent();
+    w.append_line("match self.signals.iter().find(|signal| signal.0 == path) {")?;
+    w.indent();
+    w.append_line("Some(&(_, bit_width, zeros, ones)) => {")?;
+    w.indent();
+    w.append_line("let mask = if bit_width < 128 { (1 << bit_width) - 1 } else { !0 };")?;
+    w.append_line("!(zeros & ones) & mask")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_line(&format!(
+        "None => panic!(\"Module \\\"{}\\\" doesn't have a register or named signal called \\\"{{}}\\\".\", path),",
+        m.name
+    ))?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+    w.append_line(
+        "/// Returns the percentage of covered bits that have been observed as both 0 and 1.",
+    )?;
+    w.append_line("pub fn percentage(&self) -> f64 {")?;
+    w.indent();
+    w.append_line("let (covered_bits, total_bits) = self.signals.iter().fold(")?;
+    w.indent();
+    w.append_line("(0, 0),")?;
+    w.append_line("|(covered_bits, total_bits), &(_, bit_width, zeros, ones)| {")?;
+    w.indent();
+    w.append_line("(covered_bits + (zeros & ones).count_ones(), total_bits + bit_width)")?;
+    w.unindent()?;
+    w.append_line("},")?;
+    w.unindent()?;
+    w.append_line(");")?;
+    w.append_line("if total_bits == 0 {")?;
+    w.indent();
+    w.append_line("100.0")?;
+    w.unindent()?;
+    w.append_line("} else {")?;
+    w.indent();
+    w.append_line("covered_bits as f64 * 100.0 / total_bits as f64")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+
+    Ok(())
+}
+
+fn gen_mem_accessors<'graph, 'arena, W: Write>(
+    m: &'graph graph::Module<'graph>,
+    prefix: &str,
+    context: &'arena ModuleContext<'graph, 'arena>,
+    context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
+    state_elements: &StateElements<'graph, 'arena>,
+    w: &mut code_writer::CodeWriter<W>,
+) -> Result<()> {
+    for &mem in m.mems.borrow().iter() {
+        // Memories that don't affect anything aren't simulated
+        let mem = match state_elements.mems.get(&(context, mem)) {
+            Some(mem) => mem,
+            _ => continue,
+        };
+        let name = sanitize_identifier(&format!("{}{}", prefix, mem.mem.name));
+        let address_type_name = ValueType::from_bit_width(mem.mem.address_bit_width).name();
+        let element_type = ValueType::from_bit_width(mem.mem.element_bit_width);
+        let element_type_name = element_type.name();
+
+        let path = format!("{}{}", prefix, mem.mem.name);
+
+        w.append_newline()?;
+        w.append_line(&format!(
+            "/// Returns the contents of the memory `{}` ({} elements of {} bit(s)).",
+            path,
+            1u128 << mem.mem.address_bit_width,
+            mem.mem.element_bit_width
+        ))?;
+        w.append_line(&format!(
+            "pub fn {}_contents(&self) -> &[{}] {{",
+            name, element_type_name
+        ))?;
+        w.indent();
+        w.append_line(&format!("&self.{}", mem.mem_name))?;
+        w.unindent()?;
+        w.append_line("}")?;
+
+        w.append_newline()?;
+        w.append_line(&format!(
+            "/// Returns the contents of the memory `{}` mutably. Bits above each element's width must be left zero.",
+            path
+        ))?;
+        w.append_line(&format!(
+            "pub fn {}_contents_mut(&mut self) -> &mut [{}] {{",
+            name, element_type_name
+        ))?;
+        w.indent();
+        w.append_line(&format!("&mut self.{}", mem.mem_name))?;
+        w.unindent()?;
+        w.append_line("}")?;
+
+        w.append_newline()?;
+        w.append_line(&format!(
+            "/// Returns the element of the memory `{}` at `address`.",
+            path
+        ))?;
+        w.append_line(&format!(
+            "pub fn {}_read(&self, address: {}) -> {} {{",
+            name, address_type_name, element_type_name
+        ))?;
+        w.indent();
+        w.append_line(&format!("self.{}[address as usize]", mem.mem_name))?;
+        w.unindent()?;
+        w.append_line("}")?;
+
+        w.append_newline()?;
+        w.append_line(&format!(
+            "/// Writes `value` to the element of the memory `{}` at `address`{}.",
+            path,
+            if element_type == ValueType::Bool
+                || element_type.bit_width() == mem.mem.element_bit_width
+            {
+                "".into()
+            } else {
+                format!(
+                    ", masked with `0x{:x}`",
+                    (1u128 << mem.mem.element_bit_width) - 1
+                )
+            }
+        ))?;
+        w.append_line(&format!(
+            "pub fn {}_write(&mut self, address: {}, value: {}) {{",
+            name, address_type_name, element_type_name
+        ))?;
+        w.indent();
+        if element_type == ValueType::Bool || element_type.bit_width() == mem.mem.element_bit_width
+        {
+            w.append_line(&format!("self.{}[address as usize] = value;", mem.mem_name))?;
+        } else {
+            w.append_line(&format!(
+                "self.{}[address as usize] = value & 0x{:x};",
+                mem.mem_name,
+                (1u128 << mem.mem.element_bit_width) - 1
+            ))?;
+        }
+        w.unindent()?;
+        w.append_line("}")?;
+    }
+
+    for &instance in m.instances.borrow().iter() {
+        gen_mem_accessors(
+            instance.instantiated_module,
+            &format!("{}{}__", prefix, instance.name),
+            context.get_child(instance, context_arena),
+            context_arena,
+            state_elements,
+            w,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Probe getters share the generated simulator's method namespace, so they can't reuse a name that's already taken
+fn check_probe_getter_names<'graph, 'arena>(
+    m: &'graph graph::Module<'graph>,
+    state_elements: &StateElements<'graph, 'arena>,
+) {
+    const RESERVED_METHOD_NAMES: &[&str] = &[
+        "new",
+        "new_with_seed",
+        "reset",
+        "posedge_clk",
+        "prop",
+        "tick",
+        "tick_n",
+        "check",
+        "cover_counts",
+        "trace",
+        "coverage",
+        "save_state",
+        "load_state",
+        "dump_vcd_header",
+        "dump_vcd",
+    ];
+
+    let mut method_names: HashSet<String> = RESERVED_METHOD_NAMES
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    for (&(context, mem), _) in state_elements.mems.iter() {
+        let mut path = mem.name.clone();
+        let mut context = context;
+        while let Some((instance, parent)) = context.instance_and_parent {
+            path = format!("{}__{}", instance.name, path);
+            context = parent;
+        }
+        let name = sanitize_identifier(&path);
+        for suffix in ["_contents", "_contents_mut", "_read", "_write"].iter() {
+            method_names.insert(format!("{}{}", name, suffix));
+        }
+    }
+
+    let mut probe_paths = HashMap::new();
+    for probe in state_elements.probes.iter() {
+        if let Some(other_path) = probe_paths.insert(&probe.getter_name, &probe.path) {
+            panic!("Cannot generate code for module \"{}\" because its probes \"{}\" and \"{}\" would both have a getter called \"{}\" in the generated simulator.", m.name, other_path, probe.path, probe.getter_name);
+        }
+        if method_names.contains(&probe.getter_name) {
+            panic!("Cannot generate code for module \"{}\" because the getter for its probe \"{}\" would be called \"{}\", which is already the name of a method in the generated simulator.", m.name, probe.path, probe.getter_name);
+        }
+    }
+}
+
+struct VcdVar {
+    id: String,
+    field_name: String,
+    bit_width: u32,
+}
+
+// Memories longer than this only show their first elements
+const DEBUG_MEM_PREVIEW_LEN: usize = 8;
+
+fn gen_debug_impl<'graph, 'arena, W: Write>(
+    m: &'graph graph::Module<'graph>,
+    struct_name: &str,
+    context: &'arena ModuleContext<'graph, 'arena>,
+    context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
+    state_elements: &StateElements<'graph, 'arena>,
+    w: &mut code_writer::CodeWriter<W>,
+) -> Result<()> {
+    let mut lines = vec![format!("write!(f, \"{{}}\", {:?})?;", m.name)];
+    for (section, ports) in [
+        ("inputs", m.inputs.borrow()),
+        ("outputs", m.outputs.borrow()),
+    ]
+    .iter()
+    {
+        if ports.is_empty() {
+            continue;
+        }
+        lines.push(format!("write!(f, \"\\n  {}:\")?;", section));
+        for (name, port) in ports.iter() {
+            lines.push(gen_debug_value(
+                2,
+                name,
+                &identifier(name),
+                port.bit_width(),
+            ));
+        }
+    }
+    gen_debug_state(m, 1, context, context_arena, state_elements, &mut lines);
+
+    w.append_line(&format!("impl std::fmt::Debug for {} {{", struct_name))?;
+    w.indent();
+    w.append_line("fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {")?;
+    w.indent();
+    for line in lines.iter() {
+        w.append_line(line)?;
+    }
+    w.append_line("Ok(())")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.unindent()?;
+    w.append_line("}")?;
+    w.append_newline()?;
+
+    Ok(())
+}
+
+fn gen_debug_state<'graph, 'arena>(
+    m: &'graph graph::Module<'graph>,
+    depth: usize,
+    context: &'arena ModuleContext<'graph, 'arena>,
+    context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
+    state_elements: &StateElements<'graph, 'arena>,
+    lines: &mut Vec<String>,
+) {
+    let indent = "  ".repeat(depth);
+
+    // Registers and memories that don't affect anything aren't simulated
+    let regs = m
+        .registers
+        .borrow()
+        .iter()
+        .filter_map(|&reg| state_elements.regs.get(&(context, reg)))
+        .collect::<Vec<_>>();
+    if !regs.is_empty() {
+        lines.push(format!("write!(f, \"\\n{}registers:\")?;", indent));
+        for reg in regs {
+            lines.push(gen_debug_value(
+                depth + 1,
+                &reg.data.name,
+                &reg.value_name,
+                reg.data.bit_width,
+            ));
+        }
+    }
+
+    let latches = m
+        .latches
+        .borrow()
+        .iter()
+        .filter_map(|&latch| state_elements.latches.get(&(context, latch)))
+        .collect::<Vec<_>>();
+    if !latches.is_empty() {
+        lines.push(format!("write!(f, \"\\n{}latches:\")?;", indent));
+        for latch in latches {
+            lines.push(gen_debug_value(
+                depth + 1,
+                &latch.data.name,
+                &latch.value_name,
+                latch.data.bit_width,
+            ));
+        }
+    }
+
+    let mems = m
+        .mems
+        .borrow()
+        .iter()
+        .filter_map(|&mem| state_elements.mems.get(&(context, mem)))
+        .collect::<Vec<_>>();
+    if !mems.is_empty() {
+        lines.push(format!("write!(f, \"\\n{}mems:\")?;", indent));
+        for mem in mems {
+            let field = format!("self.{}", mem.mem_name);
+            let bit_width = mem.mem.element_bit_width;
+            lines.push(format!(
+                "write!(f, \"\\n{{}}[\", {:?})?;",
+                format!("{}  {}: ", indent, mem.mem.name)
+            ));
+            lines.push(format!(
+                "for (i, x) in {}.iter().take({}).enumerate() {{",
+                field, DEBUG_MEM_PREVIEW_LEN
+            ));
+            lines.push("    if i > 0 {".into());
+            lines.push("        write!(f, \", \")?;".into());
+            lines.push("    }".into());
+            lines.push(format!(
+                "    write!(f, \"{}'h{{:0{}x}}\", *x as u128)?;",
+                bit_width,
+                (bit_width as usize).div_ceil(4)
+            ));
+            lines.push("}".into());
+            if 1 << mem.mem.address_bit_width > DEBUG_MEM_PREVIEW_LEN {
+                lines.push("write!(f, \", ...\")?;".into());
+            }
+            lines.push(format!(
+                "write!(f, \"] ({{}}/{} non-zero)\", {}.iter().filter(|&&x| x as u128 != 0).count())?;",
+                1u128 << mem.mem.address_bit_width,
+                field
+            ));
+        }
+    }
+
+    for &instance in m.instances.borrow().iter() {
+        let mut instance_lines = Vec::new();
+        gen_debug_state(
+            instance.instantiated_module,
+            depth + 1,
+            context.get_child(instance, context_arena),
+            context_arena,
+            state_elements,
+            &mut instance_lines,
+        );
+        // Instances without any simulated state are omitted
+        if !instance_lines.is_empty() {
+            lines.push(format!(
+                "write!(f, \"\\n{{}}\", {:?})?;",
+                format!(
+                    "{}{} ({}):",
+                    indent, instance.name, instance.instantiated_module.name
+                )
+            ));
+            lines.extend(instance_lines);
+        }
+    }
+}
+
+fn gen_debug_value(depth: usize, name: &str, field_name: &str, bit_width: u32) -> String {
+    format!(
+        "write!(f, \"\\n{{}}{}'h{{:0{}x}}\", {:?}, self.{} as u128)?;",
+        bit_width,
+        bit_width.div_ceil(4),
+        format!("{}{}: ", "  ".repeat(depth), name),
+        field_name
+    )
+}
+
+fn gen_vcd_scope<'graph, 'arena>(
+    m: &'graph graph::Module<'graph>,
+    scope_name: &str,
+    context: &'arena ModuleContext<'graph, 'arena>,
+    context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
+    state_elements: &StateElements<'graph, 'arena>,
+    header_lines: &mut Vec<String>,
+    vcd_vars: &mut Vec<VcdVar>,
+) {
+    header_lines.push(format!("$scope module {} $end", scope_name));
+
+    let mut add_var = |name: &str, field_name: &str, bit_width: u32| {
+        let id = vcd_id(vcd_vars.len());
+        header_lines.push(format!("$var wire {} {} {} $end", bit_width, id, name));
+        vcd_vars.push(VcdVar {
+            id,
+            field_name: field_name.into(),
+            bit_width,
+        });
+    };
+
+    // Only the generated module's inputs and outputs are stored in the generated struct
+    if context.instance_and_parent.is_none() {
+        for (name, input) in m.inputs.borrow().iter() {
+            add_var(name, &identifier(name), input.bit_width());
+        }
+        for (name, output) in m.outputs.borrow().iter() {
+            add_var(name, &identifier(name), output.bit_width());
+        }
+    }
+    for &reg in m.registers.borrow().iter() {
+        // Registers that don't affect anything aren't simulated
+        if let Some(reg) = state_elements.regs.get(&(context, reg)) {
+            add_var(&reg.data.name, &reg.value_name, reg.data.bit_width);
+        }
+    }
+    for &latch in m.latches.borrow().iter() {
+        if let Some(latch) = state_elements.latches.get(&(context, latch)) {
+            add_var(&latch.data.name, &latch.value_name, latch.data.bit_width);
+        }
+    }
+
+    for &instance in m.instances.borrow().iter() {
+        gen_vcd_scope(
+            instance.instantiated_module,
+            &instance.name,
+            context.get_child(instance, context_arena),
+            context_arena,
+            state_elements,
+            header_lines,
+            vcd_vars,
+        );
+    }
+
+    header_lines.push("$upscope $end".into());
+}
+
+fn vcd_id(mut index: usize) -> String {
+    // VCD identifiers are made up of printable ASCII characters other than space
+    const FIRST_CHAR: usize = 33;
+    const NUM_CHARS: usize = 94;
+    let mut ret = String::new();
+    loop {
+        ret.push((FIRST_CHAR + index % NUM_CHARS) as u8 as char);
+        index /= NUM_CHARS;
+        if index == 0 {
+            break;
+        }
+        index -= 1;
+    }
+    ret
+}
+
+fn gen_mem_reads(mem: &Mem, a: &mut AssignmentContext) {
+    for (_, read_signal_names) in mem.read_signal_names.iter() {
+        let address = Expr::Ref {
+            name: read_signal_names.address_name.clone(),
+            scope: Scope::Member,
+        };
+        let enable = Expr::Ref {
+            name: read_signal_names.enable_name.clone(),
+            scope: Scope::Member,
+        };
+        let value = Expr::Ref {
+            name: read_signal_names.value_name.clone(),
+            scope: Scope::Member,
+        };
+        let element = Expr::ArrayIndex {
+            target: Box::new(Expr::Ref {
+                name: mem.mem_name.clone(),
+                scope: Scope::Member,
+            }),
+            index: Box::new(address),
+        };
+        // TODO: Conditional assign statement instead of always writing ternary
+        a.push(Assignment {
+            target: value.clone(),
+            expr: Expr::Ternary {
+                cond: Box::new(enable),
+                when_true: Box::new(element),
+                when_false: Box::new(value),
+            },
+        });
+    }
+}
+
+fn gen_mem_writes(mem: &Mem, a: &mut AssignmentContext) {
+    // Write ports specified later take priority, so they're applied last
+    for write_signal_names in mem.write_signal_names.iter() {
+        let address = Expr::Ref {
+            name: write_signal_names.address_name.clone(),
+            scope: Scope::Member,
+        };
+        let value = Expr::Ref {
+            name: write_signal_names.value_name.clone(),
+            scope: Scope::Member,
+        };
+        let enable = Expr::Ref {
+            name: write_signal_names.enable_name.clone(),
+            scope: Scope::Member,
+        };
+        let element = Expr::ArrayIndex {
+            target: Box::new(Expr::Ref {
+                name: mem.mem_name.clone(),
+                scope: Scope::Member,
+            }),
+            index: Box::new(address),
+        };
+        let value = match &write_signal_names.mask_name {
+            Some(mask_name) => {
+                // Read-modify-write; only lanes selected by the (expanded) mask are updated
+                let mask = Expr::Ref {
+                    name: mask_name.clone(),
+                    scope: Scope::Member,
+                };
+                let inverted_mask = a.gen_temp(Expr::UnOp {
+                    source: Box::new(mask.clone()),
+                    op: UnOp::Not,
+                });
+                let kept_bits = a.gen_temp(Expr::InfixBinOp {
+                    lhs: Box::new(element.clone()),
+                    rhs: Box::new(inverted_mask),
+                    op: InfixBinOp::BitAnd,
+                });
+                let written_bits = a.gen_temp(Expr::InfixBinOp {
+                    lhs: Box::new(value),
+                    rhs: Box::new(mask),
+                    op: InfixBinOp::BitAnd,
+                });
+                a.gen_temp(Expr::InfixBinOp {
+                    lhs: Box::new(kept_bits),
+                    rhs: Box::new(written_bits),
+                    op: InfixBinOp::BitOr,
+                })
+            }
+            _ => value,
+        };
+        // TODO: Conditional assign statement instead of always writing ternary
+        a.push(Assignment {
+            target: element.clone(),
+            expr: Expr::Ternary {
+                cond: Box::new(enable),
+                when_true: Box::new(value),
+                when_false: Box::new(element),
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::*;
+
+    #[test]
+    fn value_types() {
+        for &(bit_width, name) in &[
+            (1, "bool"),
+            (2, "u32"),
+            (8, "u32"),
+            (9, "u32"),
+            (32, "u32"),
+            (33, "u64"),
+            (64, "u64"),
+            (65, "u128"),
+            (128, "u128"),
+        ] {
+            assert_eq!(ValueType::from_bit_width(bit_width).name(), name);
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate a simulator for module \"Outer\" because its hierarchy contains extern module \"Blackbox\", which is defined outside of kaze and can't be simulated."
+    )]
+    fn extern_module_error() {
+        let c = Context::new();
+
+        let blackbox = c.extern_module("Blackbox");
+        blackbox.input("i", 1);
+        blackbox.extern_output("o", 1);
+
+        let outer = c.module("Outer");
+        let blackbox = outer.instance("blackbox", "Blackbox");
+        blackbox.drive_input("i", outer.input("i", 1));
+        outer.output("o", blackbox.output("o"));
+
+        // Panic
+        generate(outer, Vec::new()).unwrap();
+    }
 
     #[test]
     #[should_panic(
@@ -515,4 +3306,666 @@ mod tests {
         // Panic
         generate(b, Vec::new()).unwrap();
     }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate code for module \"b\" because module \"a\" contains an output called \"o\" which forms a combinational loop with itself."
+    )]
+    fn mem_comb_read_port_combinational_loop_error() {
+        let c = Context::new();
+
+        let a = c.module("a");
+        let m = a.mem("m", 2, 2);
+        m.initial_contents(&[0u32, 1u32, 2u32, 3u32]);
+        a.output("o", m.read_port_comb(a.input("i", 2)));
+
+        let b = c.module("b");
+        let a_inst = b.instance("a_inst", "a");
+        let a_inst_o = a_inst.output("o");
+        a_inst.drive_input("i", a_inst_o);
+
+        // Panic
+        generate(b, Vec::new()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot probe \"inner.missing.r\" in module \"A\" because module \"B\" doesn't contain an instance called \"missing\"."
+    )]
+    fn probe_missing_instance_error() {
+        let c = Context::new();
+
+        let b = c.module("B");
+        b.output("o", b.high());
+
+        let a = c.module("A");
+        let inner = a.instance("inner", "B");
+        a.output("o", inner.output("o"));
+
+        let options = GenerationOptions {
+            probes: vec!["inner.missing.r".into()],
+            ..Default::default()
+        };
+
+        // Panic
+        generate_with_options(a, &options, Vec::new()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot probe \"inner.s\" in module \"A\" because module \"B\" doesn't contain a register or named signal called \"s\"."
+    )]
+    fn probe_missing_signal_error() {
+        let c = Context::new();
+
+        let b = c.module("B");
+        b.output("o", b.high());
+
+        let a = c.module("A");
+        let inner = a.instance("inner", "B");
+        a.output("o", inner.output("o"));
+
+        let options = GenerationOptions {
+            probes: vec!["inner.s".into()],
+            ..Default::default()
+        };
+
+        // Panic
+        generate_with_options(a, &options, Vec::new()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate code for module \"A\" because the getter for its probe \"prop\" would be called \"prop\", which is already the name of a method in the generated simulator."
+    )]
+    fn probe_reserved_getter_name_error() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let prop = a.reg("prop", 32);
+        prop.default_value(0u32);
+        prop.drive_next(prop.value);
+        a.output("o", prop.value);
+
+        let options = GenerationOptions {
+            probes: vec!["prop".into()],
+            ..Default::default()
+        };
+
+        // Panic
+        generate_with_options(a, &options, Vec::new()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate code for module \"A\" because the getter for its probe \"inner.ram_read\" would be called \"inner__ram_read\", which is already the name of a method in the generated simulator."
+    )]
+    fn probe_mem_accessor_getter_name_error() {
+        let c = Context::new();
+
+        let b = c.module("B");
+        let ram = b.mem("ram", 1, 1);
+        ram.initial_contents(&[false, true]);
+        let addr = b.input("a", 1);
+        b.output("o", ram.read_port(addr, b.high()));
+        (addr ^ b.high()).name("ram_read");
+
+        let a = c.module("A");
+        let inner = a.instance("inner", "B");
+        inner.drive_input("a", a.input("a", 1));
+        a.output("o", inner.output("o"));
+
+        let options = GenerationOptions {
+            probes: vec!["inner.ram_read".into()],
+            ..Default::default()
+        };
+
+        // Panic
+        generate_with_options(a, &options, Vec::new()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate code for module \"A\" because its probes \"inner.r\" and \"inner__r\" would both have a getter called \"inner__r\" in the generated simulator."
+    )]
+    fn probe_getter_name_collision_error() {
+        let c = Context::new();
+
+        let b = c.module("B");
+        let r = b.reg("r", 1);
+        r.default_value(false);
+        r.drive_next(!r.value);
+        b.output("o", r.value);
+
+        let a = c.module("A");
+        let inner = a.instance("inner", "B");
+        let r = a.reg("inner__r", 1);
+        r.default_value(false);
+        r.drive_next(inner.output("o"));
+        a.output("o", r.value);
+
+        let options = GenerationOptions {
+            probes: vec!["inner.r".into(), "inner__r".into()],
+            ..Default::default()
+        };
+
+        // Panic
+        generate_with_options(a, &options, Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn dead_logic_not_emitted() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let i = a.input("i", 32);
+        let _ = (i + a.lit(0xdeadbeefu32, 32)) ^ i;
+        let dead_reg = a.reg("dead_reg", 32);
+        dead_reg.drive_next(dead_reg.value + a.lit(0xabcdefu32, 32));
+        a.output("o", i);
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(!output.contains("0xdeadbeef"));
+        assert!(!output.contains("0xabcdef"));
+        assert!(!output.contains("dead_reg"));
+    }
+
+    #[test]
+    fn doc_comments() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let r = m.reg("r", 4);
+        r.default_value(0u32);
+        r.drive_next(m.input("d", 4));
+        m.output("q", r.value);
+        m.output("valid", m.input("enable", 1));
+        m.output("wide", m.input("wide_in", 64));
+        m.port_description("d", "Data to register\nSampled on every cycle");
+        m.port_description("valid", "High when `q` is valid");
+
+        let options = GenerationOptions {
+            check_inputs: true,
+            ..Default::default()
+        };
+        let mut w = Vec::new();
+        generate_with_options(m, &options, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains(&format!(
+            "// Generated by kaze {} from module \"A\"",
+            env!("CARGO_PKG_VERSION")
+        )));
+        assert!(output.contains(&format!(
+            "/// Simulator for the kaze module `A`, generated by kaze {}.",
+            env!("CARGO_PKG_VERSION")
+        )));
+        assert!(output.contains(
+            "    /// Data to register\n    /// Sampled on every cycle\n    ///\n    /// Input `d`: 4 bit(s), stored as `u32`.\n    /// Only the bits in `0xf` are used, and setting any other bit panics in `prop()` in debug builds.\n    pub d: u32,\n"
+        ));
+        assert!(output.contains(
+            "    /// Input `enable`: 1 bit(s), stored as `bool`.\n    pub enable: bool,\n"
+        ));
+        assert!(output.contains(
+            "    /// Input `wide_in`: 64 bit(s), stored as `u64`.\n    pub wide_in: u64,\n"
+        ));
+        assert!(output.contains(
+            "    /// Output `q`: 4 bit(s), stored as `u32`.\n    /// Bits outside of `0xf` are always zero.\n    pub q: u32,\n"
+        ));
+        assert!(output.contains(
+            "    /// High when `q` is valid\n    ///\n    /// Output `valid`: 1 bit(s), stored as `bool`.\n    pub valid: bool,\n"
+        ));
+        for method in &["new", "reset", "posedge_clk", "prop", "tick", "tick_n"] {
+            let declaration = format!("pub fn {}(", method);
+            let line = output
+                .lines()
+                .position(|line| line.trim_start().starts_with(&declaration))
+                .unwrap();
+            assert!(output
+                .lines()
+                .nth(line - 1)
+                .unwrap()
+                .trim_start()
+                .starts_with("/// "));
+        }
+
+        let options = GenerationOptions {
+            check_inputs: false,
+            ..Default::default()
+        };
+        let mut w = Vec::new();
+        generate_with_options(m, &options, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output
+            .contains("    /// Only the bits in `0xf` are used; any other bits are masked off.\n    pub d: u32,\n"));
+    }
+
+    fn duplicated_tree<'a>(a: &'a Signal<'a>, b: &'a Signal<'a>, depth: u32) -> &'a Signal<'a> {
+        if depth == 0 {
+            a & b
+        } else {
+            // Each subtree is rebuilt from scratch, so without structural sharing this would produce 2^depth copies
+            duplicated_tree(a, b, depth - 1) + duplicated_tree(a, b, depth - 1)
+        }
+    }
+
+    #[test]
+    fn duplicated_expressions_emitted_once() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let o = duplicated_tree(a.input("a", 32), a.input("b", 32), 12);
+        a.output("o", o);
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        // One node for the leaf, one for each level of the tree
+        assert_eq!(output.matches("let __temp_").count(), 13);
+    }
+
+    #[test]
+    fn vcd_scopes_follow_instance_paths() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let r = inner.reg("r", 4);
+        r.default_value(0u32);
+        r.drive_next(inner.input("i", 4));
+        let unused = inner.reg("unused", 4);
+        unused.drive_next(unused.value);
+        inner.output("o", r.value);
+
+        let outer = c.module("Outer");
+        let inst = outer.instance("inst", "Inner");
+        inst.drive_input("i", outer.input("i", 4));
+        let r = outer.reg("r", 1);
+        r.drive_next(outer.input("e", 1));
+        outer.output("o", inst.output("o").concat(r.value));
+
+        let options = GenerationOptions {
+            vcd: true,
+            ..Default::default()
+        };
+        let mut w = Vec::new();
+        generate_with_options(outer, &options, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+
+        let header = code
+            .lines()
+            .filter_map(|line| {
+                line.trim()
+                    .strip_prefix("writeln!(w, \"{}\", \"")
+                    .and_then(|line| line.strip_suffix("\")?;"))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            header,
+            vec![
+                "$timescale 1ns $end",
+                "$scope module Outer $end",
+                "$var wire 1 ! e $end",
+                "$var wire 4 \\\" i $end",
+                "$var wire 5 # o $end",
+                "$var wire 1 $ r $end",
+                "$scope module inst $end",
+                "$var wire 4 % r $end",
+                "$upscope $end",
+                "$upscope $end",
+                "$enddefinitions $end",
+            ]
+        );
+    }
+
+    #[test]
+    fn vcd_ids() {
+        assert_eq!(vcd_id(0), "!");
+        assert_eq!(vcd_id(93), "~");
+        assert_eq!(vcd_id(94), "!!");
+        assert_eq!(vcd_id(95), "\"!");
+        assert_eq!(vcd_id(94 + 94 * 94), "!!!");
+    }
+
+    #[test]
+    fn save_state_field_names() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let r = inner.reg("r", 4);
+        r.drive_next(inner.input("i", 4));
+        inner.output("o", r.value);
+
+        let outer = c.module("Outer");
+        let inst = outer.instance("inst", "Inner");
+        inst.drive_input("i", outer.input("i", 4));
+        let r1 = outer.reg("r", 4);
+        r1.drive_next(inst.output("o"));
+        let r2 = outer.reg("r", 4);
+        r2.drive_next(r1.value);
+        outer.output("o", r2.value);
+
+        let options = GenerationOptions {
+            save_state: true,
+            ..Default::default()
+        };
+        let mut w = Vec::new();
+        generate_with_options(outer, &options, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+
+        assert!(code.contains("pub struct OuterState {"));
+        assert!(code.contains("pub r: u32,"));
+        assert!(code.contains("pub r_1: u32,"));
+        assert!(code.contains("pub inst__r: u32,"));
+        assert!(code.contains("pub fn save_state(&self) -> OuterState {"));
+        assert!(code.contains("pub fn load_state(&mut self, state: &OuterState) {"));
+    }
+
+    fn deterministic_output_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+        let inner = c.module("Inner");
+        let i = inner.input("i", 8);
+        let r = inner.reg("r", 8);
+        r.drive_next(i);
+        inner.output("o", r.value);
+
+        let m = c.module("Outer");
+        let mut acc = m.input("i", 8);
+        for index in 0..16 {
+            let r = m.reg(format!("r{}", index), 8);
+            r.default_value(index as u32);
+            r.drive_next(acc);
+            let mem = m.mem(format!("mem{}", index), 2, 8);
+            mem.write_port(acc.bits(1, 0), r.value, acc.bit(7));
+            let inst = m.instance(format!("inst{}", index), "Inner");
+            inst.drive_input("i", mem.read_port(r.value.bits(1, 0), acc.bit(0)));
+            acc = inst.output("o") ^ r.value;
+        }
+        m.output("o", acc);
+
+        m
+    }
+
+    #[test]
+    fn deterministic_output() {
+        let generate_text = || {
+            let c = Context::new();
+            let m = deterministic_output_test_module(&c);
+            let mut w = Vec::new();
+            generate(m, &mut w).unwrap();
+            String::from_utf8(w).unwrap()
+        };
+
+        let expected = generate_text();
+        for _ in 0..4 {
+            assert_eq!(generate_text(), expected);
+        }
+    }
+
+    #[test]
+    fn shared_subexpressions_generate_linear_code() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let mut x = m.input("x", 32);
+        let y = m.input("y", 32);
+        // Each level references the previous level three times, so re-expanding shared subexpressions would grow exponentially
+        let num_levels = 64;
+        for _ in 0..num_levels {
+            x = (x + y) ^ (x >> m.lit(1u32, 5)) ^ x.lt(y).mux(y, x);
+        }
+        for index in 0..10 {
+            m.output(format!("o{}", index), x ^ m.lit(index as u32, 32));
+        }
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+
+        assert!(code.lines().count() < num_levels * 20);
+    }
+
+    fn parse_generated<'a>(m: &'a Module<'a>, options: &GenerationOptions) -> syn::File {
+        let mut w = Vec::new();
+        generate_with_options(m, options, &mut w).unwrap();
+        syn::parse_file(std::str::from_utf8(&w).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn struct_name_and_visibility() {
+        let c = Context::new();
+        let m = deterministic_output_test_module(&c);
+
+        let options = GenerationOptions {
+            save_state: true,
+            toggle_coverage: true,
+            struct_name: Some("OuterSim".into()),
+            visibility: Visibility::Crate,
+            ..Default::default()
+        };
+        let file = parse_generated(m, &options);
+        let structs = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Struct(s) => Some((s.ident.to_string(), s.vis.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            structs
+                .iter()
+                .map(|(name, _)| &name[..])
+                .collect::<Vec<_>>(),
+            vec!["OuterSim", "OuterSimState", "OuterSimCoverageReport"]
+        );
+        for (_, vis) in structs {
+            assert!(matches!(vis, syn::Visibility::Restricted(ref r) if r.path.is_ident("crate")));
+        }
+        assert!(file.items.iter().any(|item| match item {
+            syn::Item::Impl(i) => match (&i.trait_, &*i.self_ty) {
+                (Some((_, path, _)), syn::Type::Path(ty)) =>
+                    path.segments.last().unwrap().ident == "Debug" && ty.path.is_ident("OuterSim"),
+                _ => false,
+            },
+            _ => false,
+        }));
+
+        let options = GenerationOptions {
+            impl_sim_module: true,
+            struct_name: Some("OuterSim".into()),
+            visibility: Visibility::Private,
+            module_name: Some("outer".into()),
+            ..Default::default()
+        };
+        let file = parse_generated(m, &options);
+        assert_eq!(file.items.len(), 1);
+        let items = match &file.items[0] {
+            syn::Item::Mod(module) => {
+                assert_eq!(module.ident, "outer");
+                assert!(matches!(module.vis, syn::Visibility::Inherited));
+                &module.content.as_ref().unwrap().1
+            }
+            _ => panic!("Expected a module"),
+        };
+        assert!(items.iter().any(|item| match item {
+            syn::Item::Use(u) => match &u.tree {
+                syn::UseTree::Path(path) => {
+                    path.ident == "super"
+                        && matches!(&*path.tree, syn::UseTree::Name(name) if name.ident == "SimModule")
+                }
+                _ => false,
+            },
+            _ => false,
+        }));
+        assert!(items.iter().any(|item| match item {
+            syn::Item::Struct(s) =>
+                s.ident == "OuterSim" && matches!(s.vis, syn::Visibility::Public(_)),
+            _ => false,
+        }));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate a simulator for module \"Outer\" with the struct name \"type\", because it isn't a valid Rust identifier."
+    )]
+    fn struct_name_keyword_error() {
+        let c = Context::new();
+        let m = deterministic_output_test_module(&c);
+
+        let options = GenerationOptions {
+            struct_name: Some("type".into()),
+            ..Default::default()
+        };
+
+        // Panic
+        generate_with_options(m, &options, Vec::new()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate a simulator for module \"Outer\" with the struct name \"Outer Sim\", because it isn't a valid Rust identifier."
+    )]
+    fn struct_name_invalid_error() {
+        let c = Context::new();
+        let m = deterministic_output_test_module(&c);
+
+        let options = GenerationOptions {
+            struct_name: Some("Outer Sim".into()),
+            ..Default::default()
+        };
+
+        // Panic
+        generate_with_options(m, &options, Vec::new()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate a simulator for module \"Outer\" in a module called \"1st\", because it isn't a valid Rust identifier."
+    )]
+    fn module_name_invalid_error() {
+        let c = Context::new();
+        let m = deterministic_output_test_module(&c);
+
+        let options = GenerationOptions {
+            module_name: Some("1st".into()),
+            ..Default::default()
+        };
+
+        // Panic
+        generate_with_options(m, &options, Vec::new()).unwrap();
+    }
+
+    #[cfg(feature = "tokens")]
+    #[test]
+    fn tokens_round_trip() {
+        let c = Context::new();
+        let m = deterministic_output_test_module(&c);
+
+        for uninitialized_registers in &[
+            UninitializedRegisters::Zero,
+            UninitializedRegisters::Random,
+            UninitializedRegisters::Poison,
+        ] {
+            let options = GenerationOptions {
+                probes: vec!["inst0.r".into()],
+                vcd: true,
+                save_state: true,
+                toggle_coverage: true,
+                impl_sim_module: true,
+                check_inputs: true,
+                uninitialized_registers: *uninitialized_registers,
+                code_style: CodeStyle::new().indent("  ").max_line_width(40),
+                struct_name: None,
+                visibility: Visibility::Public,
+                module_name: None,
+            };
+            let file: syn::File = syn::parse2(generate_tokens(m, &options)).unwrap();
+            assert!(file.items.iter().any(|item| match item {
+                syn::Item::Struct(s) => s.ident == "Outer",
+                _ => false,
+            }));
+        }
+
+        let file: syn::File = syn::parse2(generate_sim_module_trait_tokens()).unwrap();
+        assert_eq!(file.items.len(), 11);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate code for module \"A\" because its ports \"a-b\" and \"a_b\" would both be called \"a_b\" in the generated simulator."
+    )]
+    fn port_identifier_collision_error() {
+        let c = Context::new();
+        let m = c.module("A");
+        m.output("a_b", m.input("a-b", 1));
+
+        generate(m, Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn instance_memory_accessors_allow_non_snake_case() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let ram = inner.mem("ram", 2, 8);
+        ram.write_port(inner.input("a", 2), inner.input("d", 8), inner.high());
+        inner.output("o", ram.read_port(inner.input("a", 2), inner.high()));
+
+        let m = c.module("Outer");
+        let u0 = m.instance("u0", "Inner");
+        u0.drive_input("a", m.input("a", 2));
+        u0.drive_input("d", m.input("d", 8));
+        m.output("o", u0.output("o"));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+
+        // Accessor names are derived from instance paths, so they're not snake case
+        assert!(code.contains("pub fn u0__ram_write("));
+        assert!(code.contains("pub fn u0__ram_contents_mut("));
+        assert!(code.contains("#[allow(non_snake_case)]\nimpl Outer {"));
+    }
+
+    #[test]
+    fn mem_masked_write_port_full_width_lane() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let mem = m.mem("mem", 1, 128);
+        mem.write_port_masked(
+            m.input("write_addr", 1),
+            m.input("write_value", 128),
+            m.input("write_enable", 1),
+            m.input("write_mask", 1),
+        );
+        m.output("o", mem.read_port(m.input("read_addr", 1), m.high()));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+
+        // A single lane covers the whole element
+        assert!(code.contains("0xffffffffffffffffffffffffffffffffu128"));
+    }
+
+    #[test]
+    fn deep_signal_chain() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+        // A chain of ~100k signals, which overflows the stack if code generation recurses through it
+        let mut x = a;
+        for _ in 0..34_000 {
+            x = (x + a).bits(6, 0).concat(a.bit(0));
+        }
+        m.output("o", x);
+
+        generate(m, std::io::sink()).unwrap();
+    }
 }
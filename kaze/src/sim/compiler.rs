@@ -1,6 +1,7 @@
 use super::ir::*;
 use super::state_elements::*;
 
+use crate::constant_folding::*;
 use crate::graph;
 use crate::module_context::*;
 
@@ -8,10 +9,12 @@ use typed_arena::Arena;
 
 use std::collections::HashMap;
 
+// Each signal is compiled at most once per module context, and (other than trivial expressions like references and constants) is bound to a local, so the generated code stays linear in the size of the graph regardless of fan-out.
 pub(super) struct Compiler<'graph, 'arena> {
     state_elements: &'arena StateElements<'graph, 'arena>,
     context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
 
+    constant_folder: ConstantFolder<'graph>,
     signal_exprs: HashMap<
         (
             &'arena ModuleContext<'graph, 'arena>,
@@ -19,6 +22,13 @@ pub(super) struct Compiler<'graph, 'arena> {
         ),
         Expr,
     >,
+    poison_exprs: HashMap<
+        (
+            &'arena ModuleContext<'graph, 'arena>,
+            &'graph graph::Signal<'graph>,
+        ),
+        Option<Expr>,
+    >,
 }
 
 impl<'graph, 'arena> Compiler<'graph, 'arena> {
@@ -30,7 +40,9 @@ impl<'graph, 'arena> Compiler<'graph, 'arena> {
             state_elements,
             context_arena,
 
+            constant_folder: ConstantFolder::new(),
             signal_exprs: HashMap::new(),
+            poison_exprs: HashMap::new(),
         }
     }
 
@@ -40,6 +52,86 @@ impl<'graph, 'arena> Compiler<'graph, 'arena> {
         context: &'arena ModuleContext<'graph, 'arena>,
         a: &mut AssignmentContext,
     ) -> Expr {
+        // Operands are compiled bottom-up with an explicit stack first (in the same order recursion would compile them), so that compiling `signal` itself never recurses more than one level deep, regardless of how deep the graph is
+        let mut stack = vec![((context, signal), false)];
+        while let Some(((context, signal), operands_compiled)) = stack.pop() {
+            if self.constant_folder.evaluate(signal).is_some()
+                || self.signal_exprs.contains_key(&(context, signal))
+            {
+                continue;
+            }
+            if operands_compiled {
+                self.compile_single_signal(signal, context, a);
+                continue;
+            }
+            stack.push(((context, signal), true));
+            let operands = self.operands(signal, context);
+            stack.extend(operands.into_iter().rev().map(|operand| (operand, false)));
+        }
+
+        self.compile_single_signal(signal, context, a)
+    }
+
+    // The signals (and the contexts they're in) that compiling `signal` or its poison expression compiles first, in order
+    fn operands(
+        &mut self,
+        signal: &'graph graph::Signal<'graph>,
+        context: &'arena ModuleContext<'graph, 'arena>,
+    ) -> Vec<(
+        &'arena ModuleContext<'graph, 'arena>,
+        &'graph graph::Signal<'graph>,
+    )> {
+        match signal.data {
+            graph::SignalData::Lit { .. }
+            | graph::SignalData::Reg { .. }
+            | graph::SignalData::MemReadPortOutput { .. } => Vec::new(),
+
+            graph::SignalData::Input { name, .. } => match context.instance_and_parent {
+                Some((instance, parent)) => vec![(parent, instance.driven_inputs.borrow()[name])],
+                None => Vec::new(),
+            },
+
+            graph::SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            } => match self.constant_folder.evaluate(cond) {
+                Some(cond) => vec![(
+                    context,
+                    if cond.numeric_value() != 0 {
+                        when_true
+                    } else {
+                        when_false
+                    },
+                )],
+                None => vec![(context, cond), (context, when_true), (context, when_false)],
+            },
+
+            graph::SignalData::InstanceOutput { instance, name } => vec![(
+                context.get_child(instance, self.context_arena),
+                instance.instantiated_module.outputs.borrow()[name],
+            )],
+
+            graph::SignalData::MemCombReadPortOutput { address, .. } => vec![(context, address)],
+
+            _ => signal
+                .dependencies()
+                .into_iter()
+                .map(|operand| (context, operand))
+                .collect(),
+        }
+    }
+
+    fn compile_single_signal(
+        &mut self,
+        signal: &'graph graph::Signal<'graph>,
+        context: &'arena ModuleContext<'graph, 'arena>,
+        a: &mut AssignmentContext,
+    ) -> Expr {
+        if let Some(value) = self.constant_folder.evaluate(signal) {
+            return Expr::from_constant(&value, signal.bit_width());
+        }
+
         let key = (context, signal);
         if !self.signal_exprs.contains_key(&key) {
             let expr = match signal.data {
@@ -48,16 +140,13 @@ impl<'graph, 'arena> Compiler<'graph, 'arena> {
                     bit_width,
                 } => Expr::from_constant(value, bit_width),
 
-                graph::SignalData::Input {
-                    ref name,
-                    bit_width,
-                } => {
+                graph::SignalData::Input { name, bit_width } => {
                     if let Some((instance, parent)) = context.instance_and_parent {
                         self.compile_signal(instance.driven_inputs.borrow()[name], parent, a)
                     } else {
                         let target_type = ValueType::from_bit_width(bit_width);
                         let expr = Expr::Ref {
-                            name: name.clone(),
+                            name: super::identifier(name),
                             scope: Scope::Member,
                         };
                         self.gen_mask(expr, bit_width, target_type, a)
@@ -69,6 +158,20 @@ impl<'graph, 'arena> Compiler<'graph, 'arena> {
                     scope: Scope::Member,
                 },
 
+                // A latch passes its data through while enabled, and otherwise holds the value it had at the end of the previous `prop` call
+                graph::SignalData::Latch { data } => {
+                    let latch_data = self.compile_signal(data.data.borrow().unwrap(), context, a);
+                    let enable = self.compile_signal(data.enable.borrow().unwrap(), context, a);
+                    a.gen_temp(Expr::Ternary {
+                        cond: Box::new(enable),
+                        when_true: Box::new(latch_data),
+                        when_false: Box::new(Expr::Ref {
+                            name: self.state_elements.latches[&key].value_name.clone(),
+                            scope: Scope::Member,
+                        }),
+                    })
+                }
+
                 graph::SignalData::UnOp { source, op } => {
                     let expr = self.compile_signal(source, context, a);
                     let expr = a.gen_temp(Expr::UnOp {
@@ -215,7 +318,7 @@ impl<'graph, 'arena> Compiler<'graph, 'arena> {
                             },
                         }),
                     };
-                    let rhs = self.gen_cast(rhs, lhs_op_input_type, ValueType::U32, a);
+                    let rhs = self.gen_cast(rhs, rhs_op_input_type, ValueType::U32, a);
                     let expr = Expr::UnaryMemberCall {
                         target: Box::new(lhs.clone()),
                         name: match op {
@@ -326,18 +429,28 @@ impl<'graph, 'arena> Compiler<'graph, 'arena> {
                     cond,
                     when_true,
                     when_false,
-                } => {
-                    let cond = self.compile_signal(cond, context, a);
-                    let when_true = self.compile_signal(when_true, context, a);
-                    let when_false = self.compile_signal(when_false, context, a);
-                    a.gen_temp(Expr::Ternary {
-                        cond: Box::new(cond),
-                        when_true: Box::new(when_true),
-                        when_false: Box::new(when_false),
-                    })
-                }
+                } => match self.constant_folder.evaluate(cond) {
+                    Some(cond) => {
+                        let source = if cond.numeric_value() != 0 {
+                            when_true
+                        } else {
+                            when_false
+                        };
+                        self.compile_signal(source, context, a)
+                    }
+                    None => {
+                        let cond = self.compile_signal(cond, context, a);
+                        let when_true = self.compile_signal(when_true, context, a);
+                        let when_false = self.compile_signal(when_false, context, a);
+                        a.gen_temp(Expr::Ternary {
+                            cond: Box::new(cond),
+                            when_true: Box::new(when_true),
+                            when_false: Box::new(when_false),
+                        })
+                    }
+                },
 
-                graph::SignalData::InstanceOutput { instance, ref name } => {
+                graph::SignalData::InstanceOutput { instance, name } => {
                     let output = instance.instantiated_module.outputs.borrow()[name];
                     self.compile_signal(output, context.get_child(instance, self.context_arena), a)
                 }
@@ -354,6 +467,17 @@ impl<'graph, 'arena> Compiler<'graph, 'arena> {
                         scope: Scope::Member,
                     }
                 }
+                graph::SignalData::MemCombReadPortOutput { mem, address } => {
+                    let address = self.compile_signal(address, context, a);
+                    let mem = &self.state_elements.mems[&(context, mem)];
+                    a.gen_temp(Expr::ArrayIndex {
+                        target: Box::new(Expr::Ref {
+                            name: mem.mem_name.clone(),
+                            scope: Scope::Member,
+                        }),
+                        index: Box::new(address),
+                    })
+                }
             };
             self.signal_exprs.insert(key.clone(), expr);
         }
@@ -361,6 +485,199 @@ impl<'graph, 'arena> Compiler<'graph, 'arena> {
         self.signal_exprs[&key].clone()
     }
 
+    /// Compiles an expression that's `true` when `signal`'s value is derived from a register that hasn't been written yet, or returns `None` if that can never happen.
+    pub fn compile_poison(
+        &mut self,
+        signal: &'graph graph::Signal<'graph>,
+        context: &'arena ModuleContext<'graph, 'arena>,
+        a: &mut AssignmentContext,
+    ) -> Option<Expr> {
+        // Like `compile_signal`, operands are compiled bottom-up first to bound the recursion depth
+        let mut stack = vec![((context, signal), false)];
+        while let Some(((context, signal), operands_compiled)) = stack.pop() {
+            if self.constant_folder.evaluate(signal).is_some()
+                || self.poison_exprs.contains_key(&(context, signal))
+            {
+                continue;
+            }
+            if operands_compiled {
+                self.compile_single_poison(signal, context, a);
+                continue;
+            }
+            stack.push(((context, signal), true));
+            let operands = self.operands(signal, context);
+            stack.extend(operands.into_iter().rev().map(|operand| (operand, false)));
+        }
+
+        self.compile_single_poison(signal, context, a)
+    }
+
+    fn compile_single_poison(
+        &mut self,
+        signal: &'graph graph::Signal<'graph>,
+        context: &'arena ModuleContext<'graph, 'arena>,
+        a: &mut AssignmentContext,
+    ) -> Option<Expr> {
+        if self.constant_folder.evaluate(signal).is_some() {
+            return None;
+        }
+
+        let key = (context, signal);
+        if !self.poison_exprs.contains_key(&key) {
+            let expr = match signal.data {
+                // Latches hold 0 until they're first enabled, so they're never poisoned by their own state
+                graph::SignalData::Lit { .. }
+                | graph::SignalData::Latch { .. }
+                | graph::SignalData::MemReadPortOutput { .. } => None,
+
+                graph::SignalData::Input { name, .. } => match context.instance_and_parent {
+                    Some((instance, parent)) => {
+                        self.compile_poison(instance.driven_inputs.borrow()[name], parent, a)
+                    }
+                    None => None,
+                },
+
+                graph::SignalData::Reg { .. } => Some(Expr::UnOp {
+                    source: Box::new(Expr::Ref {
+                        name: self.state_elements.regs[&key].written_name.clone(),
+                        scope: Scope::Member,
+                    }),
+                    op: UnOp::Not,
+                }),
+
+                graph::SignalData::UnOp { source, .. }
+                | graph::SignalData::Bits { source, .. }
+                | graph::SignalData::Repeat { source, .. } => {
+                    self.compile_poison(source, context, a)
+                }
+                graph::SignalData::SimpleBinOp { lhs, rhs, .. }
+                | graph::SignalData::AdditiveBinOp { lhs, rhs, .. }
+                | graph::SignalData::ComparisonBinOp { lhs, rhs, .. }
+                | graph::SignalData::ShiftBinOp { lhs, rhs, .. }
+                | graph::SignalData::Concat { lhs, rhs } => {
+                    let lhs = self.compile_poison(lhs, context, a);
+                    let rhs = self.compile_poison(rhs, context, a);
+                    gen_poison_or(lhs, rhs, a)
+                }
+
+                graph::SignalData::Mux {
+                    cond,
+                    when_true,
+                    when_false,
+                } => match self.constant_folder.evaluate(cond) {
+                    Some(cond) => {
+                        let source = if cond.numeric_value() != 0 {
+                            when_true
+                        } else {
+                            when_false
+                        };
+                        self.compile_poison(source, context, a)
+                    }
+                    None => {
+                        // Only the selected source can poison the result
+                        let cond_poison = self.compile_poison(cond, context, a);
+                        let when_true = self.compile_poison(when_true, context, a);
+                        let when_false = self.compile_poison(when_false, context, a);
+                        let selected_poison =
+                            self.gen_poison_select(cond, context, when_true, when_false, a);
+                        gen_poison_or(cond_poison, selected_poison, a)
+                    }
+                },
+
+                graph::SignalData::InstanceOutput { instance, name } => {
+                    let output = instance.instantiated_module.outputs.borrow()[name];
+                    self.compile_poison(output, context.get_child(instance, self.context_arena), a)
+                }
+
+                graph::SignalData::MemCombReadPortOutput { address, .. } => {
+                    self.compile_poison(address, context, a)
+                }
+            };
+            self.poison_exprs.insert(key, expr);
+        }
+
+        self.poison_exprs[&key].clone()
+    }
+
+    /// Compiles expressions for the next value `next` of the register `reg`: one that's `true` when `next` is derived from a register that hasn't been written yet, and one that's `true` when `next` is just `reg`'s current value, selected by (possibly nested) muxes.
+    ///
+    /// A register that only holds its own value doesn't read it, so such paths don't poison the next value; instead, the register stays unwritten.
+    pub fn compile_next_poison(
+        &mut self,
+        reg: &'graph graph::Signal<'graph>,
+        next: &'graph graph::Signal<'graph>,
+        context: &'arena ModuleContext<'graph, 'arena>,
+        a: &mut AssignmentContext,
+    ) -> (Option<Expr>, Option<Expr>) {
+        if next == reg {
+            return (None, Some(Expr::from_constant(&true.into(), 1)));
+        }
+
+        match next.data {
+            graph::SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            } if self.constant_folder.evaluate(next).is_none() => {
+                match self.constant_folder.evaluate(cond) {
+                    Some(cond) => {
+                        let source = if cond.numeric_value() != 0 {
+                            when_true
+                        } else {
+                            when_false
+                        };
+                        self.compile_next_poison(reg, source, context, a)
+                    }
+                    None => {
+                        let cond_poison = self.compile_poison(cond, context, a);
+                        let (when_true_poison, when_true_holds) =
+                            self.compile_next_poison(reg, when_true, context, a);
+                        let (when_false_poison, when_false_holds) =
+                            self.compile_next_poison(reg, when_false, context, a);
+                        let selected_poison = self.gen_poison_select(
+                            cond,
+                            context,
+                            when_true_poison,
+                            when_false_poison,
+                            a,
+                        );
+                        let holds = self.gen_poison_select(
+                            cond,
+                            context,
+                            when_true_holds,
+                            when_false_holds,
+                            a,
+                        );
+                        (gen_poison_or(cond_poison, selected_poison, a), holds)
+                    }
+                }
+            }
+            _ => (self.compile_poison(next, context, a), None),
+        }
+    }
+
+    pub fn gen_poison_select(
+        &mut self,
+        cond: &'graph graph::Signal<'graph>,
+        context: &'arena ModuleContext<'graph, 'arena>,
+        when_true: Option<Expr>,
+        when_false: Option<Expr>,
+        a: &mut AssignmentContext,
+    ) -> Option<Expr> {
+        if when_true.is_none() && when_false.is_none() {
+            return None;
+        }
+
+        let cond = self.compile_signal(cond, context, a);
+        let when_true = when_true.unwrap_or_else(|| Expr::from_constant(&false.into(), 1));
+        let when_false = when_false.unwrap_or_else(|| Expr::from_constant(&false.into(), 1));
+        Some(a.gen_temp(Expr::Ternary {
+            cond: Box::new(cond),
+            when_true: Box::new(when_true),
+            when_false: Box::new(when_false),
+        }))
+    }
+
     fn gen_mask(
         &mut self,
         expr: Expr,
@@ -464,3 +781,18 @@ impl<'graph, 'arena> Compiler<'graph, 'arena> {
         self.gen_shift_right(expr, shift, a)
     }
 }
+
+pub(super) fn gen_poison_or(
+    lhs: Option<Expr>,
+    rhs: Option<Expr>,
+    a: &mut AssignmentContext,
+) -> Option<Expr> {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Some(a.gen_temp(Expr::InfixBinOp {
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            op: InfixBinOp::BitOr,
+        })),
+        (lhs, rhs) => lhs.or(rhs),
+    }
+}
@@ -156,6 +156,7 @@ impl Expr {
                 w.append(&format!("{}(", name))?;
                 lhs.write(w)?;
                 w.append(", ")?;
+                w.append_break();
                 rhs.write(w)?;
                 w.append(")")?;
             }
@@ -169,6 +170,7 @@ impl Expr {
             }
             Expr::InfixBinOp { lhs, rhs, op } => {
                 lhs.write(w)?;
+                w.append_break();
                 w.append(&format!(
                     " {} ",
                     match op {
@@ -270,6 +272,7 @@ pub enum ValueType {
 }
 
 impl ValueType {
+    // Note that this mapping determines the types of generated simulators' ports, and is documented in `sim::generate_with_options`
     pub fn from_bit_width(bit_width: u32) -> ValueType {
         if bit_width == 1 {
             ValueType::Bool
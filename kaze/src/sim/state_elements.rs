@@ -1,23 +1,29 @@
 use crate::graph;
+use crate::index_map::*;
 use crate::module_context::*;
 
 use typed_arena::Arena;
 
-use std::collections::HashMap;
+use std::collections::HashSet;
 
 pub(super) struct Register<'a> {
     pub data: &'a graph::RegisterData<'a>,
     pub value_name: String,
     pub next_name: String,
+    pub written_name: String,
+}
+
+pub(super) struct Latch<'a> {
+    pub data: &'a graph::LatchData<'a>,
+    pub value_name: String,
 }
 
 pub(super) struct Mem<'a> {
     pub mem: &'a graph::Mem<'a>,
     pub mem_name: String,
-    pub read_signal_names: HashMap<(&'a graph::Signal<'a>, &'a graph::Signal<'a>), ReadSignalNames>,
-    pub write_address_name: String,
-    pub write_value_name: String,
-    pub write_enable_name: String,
+    pub read_signal_names:
+        IndexMap<(&'a graph::Signal<'a>, &'a graph::Signal<'a>), ReadSignalNames>,
+    pub write_signal_names: Vec<WriteSignalNames>,
 }
 
 pub struct ReadSignalNames {
@@ -26,43 +32,249 @@ pub struct ReadSignalNames {
     pub value_name: String,
 }
 
+pub struct WriteSignalNames {
+    pub address_name: String,
+    pub value_name: String,
+    pub enable_name: String,
+    pub mask_name: Option<String>,
+}
+
+pub(super) struct Property<'graph, 'arena> {
+    pub context: &'arena ModuleContext<'graph, 'arena>,
+    pub module: &'graph graph::Module<'graph>,
+    pub condition: &'graph graph::Signal<'graph>,
+    pub message: Option<String>,
+    pub name: String,
+}
+
+pub(super) struct Trace<'graph, 'arena> {
+    pub context: &'arena ModuleContext<'graph, 'arena>,
+    pub signal: &'graph graph::Signal<'graph>,
+    pub label: String,
+    pub name: String,
+}
+
+pub(super) struct Probe<'graph, 'arena> {
+    pub context: &'arena ModuleContext<'graph, 'arena>,
+    pub signal: &'graph graph::Signal<'graph>,
+    pub path: String,
+    pub getter_name: String,
+    pub value_name: String,
+}
+
 pub(super) struct StateElements<'graph, 'arena> {
-    pub mems: HashMap<
+    pub mems: IndexMap<
         (
             &'arena ModuleContext<'graph, 'arena>,
             &'graph graph::Mem<'graph>,
         ),
         Mem<'graph>,
     >,
-    pub regs: HashMap<
+    pub regs: IndexMap<
         (
             &'arena ModuleContext<'graph, 'arena>,
             &'graph graph::Signal<'graph>,
         ),
         Register<'graph>,
     >,
+    pub latches: IndexMap<
+        (
+            &'arena ModuleContext<'graph, 'arena>,
+            &'graph graph::Signal<'graph>,
+        ),
+        Latch<'graph>,
+    >,
+    pub assertions: Vec<Property<'graph, 'arena>>,
+    pub assumptions: Vec<Property<'graph, 'arena>>,
+    pub covers: Vec<Property<'graph, 'arena>>,
+    pub traces: Vec<Trace<'graph, 'arena>>,
+    pub probes: Vec<Probe<'graph, 'arena>>,
+    gathered_signals: HashSet<(
+        &'arena ModuleContext<'graph, 'arena>,
+        &'graph graph::Signal<'graph>,
+    )>,
 }
 
 impl<'graph, 'arena> StateElements<'graph, 'arena> {
     pub fn new() -> StateElements<'graph, 'arena> {
         StateElements {
-            mems: HashMap::new(),
-            regs: HashMap::new(),
+            mems: IndexMap::new(),
+            regs: IndexMap::new(),
+            latches: IndexMap::new(),
+            assertions: Vec::new(),
+            assumptions: Vec::new(),
+            covers: Vec::new(),
+            traces: Vec::new(),
+            probes: Vec::new(),
+            gathered_signals: HashSet::new(),
         }
     }
 
+    pub fn gather_annotations(
+        &mut self,
+        m: &'graph graph::Module<'graph>,
+        context: &'arena ModuleContext<'graph, 'arena>,
+        context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
+        label_prefix: &str,
+    ) {
+        for (condition, message) in m.assertions.borrow().iter() {
+            let name = format!("__assertion_{}", self.assertions.len());
+            self.assertions.push(Property {
+                context,
+                module: m,
+                condition,
+                message: Some(message.clone()),
+                name,
+            });
+            self.gather(condition, context, context_arena);
+        }
+        for condition in m.assumptions.borrow().iter() {
+            let name = format!("__assumption_{}", self.assumptions.len());
+            self.assumptions.push(Property {
+                context,
+                module: m,
+                condition,
+                message: None,
+                name,
+            });
+            self.gather(condition, context, context_arena);
+        }
+        for condition in m.covers.borrow().iter() {
+            let name = format!("__cover_{}", self.covers.len());
+            self.covers.push(Property {
+                context,
+                module: m,
+                condition,
+                message: None,
+                name,
+            });
+            self.gather(condition, context, context_arena);
+        }
+        for (label, signal) in m.traces.borrow().iter() {
+            let name = format!("__trace_{}", self.traces.len());
+            self.traces.push(Trace {
+                context,
+                signal,
+                label: format!("{}{}", label_prefix, label),
+                name,
+            });
+            self.gather(signal, context, context_arena);
+        }
+
+        for instance in m.instances.borrow().iter() {
+            self.gather_annotations(
+                instance.instantiated_module,
+                context.get_child(instance, context_arena),
+                context_arena,
+                &format!("{}{}.", label_prefix, instance.name),
+            );
+        }
+    }
+
+    pub fn gather_probe(
+        &mut self,
+        root: &'graph graph::Module<'graph>,
+        path: &str,
+        root_context: &'arena ModuleContext<'graph, 'arena>,
+        context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
+    ) {
+        let mut m = root;
+        let mut context = root_context;
+        let mut names = path.split('.').peekable();
+        let mut signal_name = "";
+        while let Some(name) = names.next() {
+            if names.peek().is_none() {
+                signal_name = name;
+                break;
+            }
+            let instance = match m
+                .instances
+                .borrow()
+                .iter()
+                .find(|instance| instance.name == name)
+            {
+                Some(instance) => *instance,
+                _ => panic!("Cannot probe \"{}\" in module \"{}\" because module \"{}\" doesn't contain an instance called \"{}\".", path, root.name, m.name, name),
+            };
+            m = instance.instantiated_module;
+            context = context.get_child(instance, context_arena);
+        }
+
+        let reg = m
+            .registers
+            .borrow()
+            .iter()
+            .cloned()
+            .find(|reg| match reg.data {
+                graph::SignalData::Reg { data } => data.name == signal_name,
+                _ => unreachable!(),
+            });
+        let getter_name = super::identifier(&path.replace('.', "__"));
+        let (signal, value_name) = if let Some(reg) = reg {
+            self.gather(reg, context, context_arena);
+            (reg, self.regs[&(context, reg)].value_name.clone())
+        } else if let Some(signal) = m.named_signals.borrow().get(signal_name) {
+            self.gather(signal, context, context_arena);
+            (
+                *signal,
+                format!(
+                    "__probe_{}",
+                    super::sanitize_identifier(&path.replace('.', "__"))
+                ),
+            )
+        } else {
+            panic!("Cannot probe \"{}\" in module \"{}\" because module \"{}\" doesn't contain a register or named signal called \"{}\".", path, root.name, m.name, signal_name);
+        };
+        self.probes.push(Probe {
+            context,
+            signal,
+            path: path.into(),
+            getter_name,
+            value_name,
+        });
+    }
+
     pub fn gather(
         &mut self,
         signal: &'graph graph::Signal<'graph>,
         context: &'arena ModuleContext<'graph, 'arena>,
         context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
     ) {
+        // An explicit stack is used instead of recursion so that very deep graphs can't overflow the call stack.
+        //  Items are pushed in reverse so that they're visited in the same (depth-first) order recursion would visit them,
+        //  which keeps the numbering of generated state element names stable.
+        let mut stack = vec![GatherItem::Signal(context, signal)];
+        while let Some(item) = stack.pop() {
+            match item {
+                GatherItem::Signal(context, signal) => {
+                    self.gather_signal(signal, context, context_arena, &mut stack)
+                }
+                GatherItem::Mem(context, mem) => self.gather_mem(mem, context, &mut stack),
+            }
+        }
+    }
+
+    fn gather_signal(
+        &mut self,
+        signal: &'graph graph::Signal<'graph>,
+        context: &'arena ModuleContext<'graph, 'arena>,
+        context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
+        stack: &mut Vec<GatherItem<'graph, 'arena>>,
+    ) {
+        // Signals can be shared by many others, so only visit each one once per context
+        if !self.gathered_signals.insert((context, signal)) {
+            return;
+        }
+
         match signal.data {
             graph::SignalData::Lit { .. } => (),
 
-            graph::SignalData::Input { ref name, .. } => {
+            graph::SignalData::Input { name, .. } => {
                 if let Some((instance, parent)) = context.instance_and_parent {
-                    self.gather(instance.driven_inputs.borrow()[name], parent, context_arena);
+                    stack.push(GatherItem::Signal(
+                        parent,
+                        instance.driven_inputs.borrow()[name],
+                    ));
                 }
             }
 
@@ -71,114 +283,143 @@ impl<'graph, 'arena> StateElements<'graph, 'arena> {
                 if self.regs.contains_key(&key) {
                     return;
                 }
-                let value_name = format!("__reg_{}_{}", data.name, self.regs.len());
+                let value_name = format!(
+                    "__reg_{}_{}",
+                    super::sanitize_identifier(&data.name),
+                    self.regs.len()
+                );
                 let next_name = format!("{}_next", value_name);
+                let written_name = format!("{}_written", value_name);
                 self.regs.insert(
                     key,
                     Register {
                         data,
                         value_name,
                         next_name,
+                        written_name,
                     },
                 );
-                self.gather(data.next.borrow().unwrap(), context, context_arena);
+                stack.push(GatherItem::Signal(context, data.next.borrow().unwrap()));
             }
 
-            graph::SignalData::UnOp { source, .. } => {
-                self.gather(source, context, context_arena);
-            }
-            graph::SignalData::SimpleBinOp { lhs, rhs, .. } => {
-                self.gather(lhs, context, context_arena);
-                self.gather(rhs, context, context_arena);
-            }
-            graph::SignalData::AdditiveBinOp { lhs, rhs, .. } => {
-                self.gather(lhs, context, context_arena);
-                self.gather(rhs, context, context_arena);
-            }
-            graph::SignalData::ComparisonBinOp { lhs, rhs, .. } => {
-                self.gather(lhs, context, context_arena);
-                self.gather(rhs, context, context_arena);
-            }
-            graph::SignalData::ShiftBinOp { lhs, rhs, .. } => {
-                self.gather(lhs, context, context_arena);
-                self.gather(rhs, context, context_arena);
+            graph::SignalData::Latch { data } => {
+                let value_name = format!(
+                    "__latch_{}_{}",
+                    super::sanitize_identifier(&data.name),
+                    self.latches.len()
+                );
+                self.latches
+                    .insert((context, signal), Latch { data, value_name });
+                stack.push(GatherItem::Signal(context, data.enable.borrow().unwrap()));
+                stack.push(GatherItem::Signal(context, data.data.borrow().unwrap()));
             }
 
-            graph::SignalData::Bits { source, .. } => {
-                self.gather(source, context, context_arena);
+            graph::SignalData::InstanceOutput { instance, name } => {
+                let output = instance.instantiated_module.outputs.borrow()[name];
+                let context = context.get_child(instance, context_arena);
+                stack.push(GatherItem::Signal(context, output));
             }
 
-            graph::SignalData::Repeat { source, .. } => {
-                self.gather(source, context, context_arena);
+            graph::SignalData::MemReadPortOutput { mem, .. } => {
+                stack.push(GatherItem::Mem(context, mem));
             }
-            graph::SignalData::Concat { lhs, rhs } => {
-                self.gather(lhs, context, context_arena);
-                self.gather(rhs, context, context_arena);
+            graph::SignalData::MemCombReadPortOutput { mem, address } => {
+                stack.push(GatherItem::Mem(context, mem));
+                stack.push(GatherItem::Signal(context, address));
             }
 
-            graph::SignalData::Mux {
-                cond,
-                when_true,
-                when_false,
-            } => {
-                self.gather(cond, context, context_arena);
-                self.gather(when_true, context, context_arena);
-                self.gather(when_false, context, context_arena);
-            }
-
-            graph::SignalData::InstanceOutput { instance, ref name } => {
-                let output = instance.instantiated_module.outputs.borrow()[name];
-                let context = context.get_child(instance, context_arena);
-                self.gather(output, context, context_arena);
-            }
+            _ => stack.extend(
+                signal
+                    .dependencies()
+                    .into_iter()
+                    .rev()
+                    .map(|operand| GatherItem::Signal(context, operand)),
+            ),
+        }
+    }
 
-            graph::SignalData::MemReadPortOutput { mem, .. } => {
-                let key = (context, mem);
-                if self.mems.contains_key(&key) {
-                    return;
-                }
-                let mem_name = format!("__mem_{}_{}", mem.name, self.mems.len());
-                // TODO: It might actually be too conservative to trace all read ports,
-                //  as we only know that the write port and _this_ read port are reachable
-                //  at this point, but we have to keep some extra state to know whether or
-                //  not we've hit each read port otherwise.
-                let mut read_signal_names = HashMap::new();
-                for (index, (address, enable)) in mem.read_ports.borrow().iter().enumerate() {
-                    let name_prefix = format!("{}_read_port_{}_", mem_name, index);
-                    read_signal_names.insert(
-                        (*address, *enable),
-                        ReadSignalNames {
-                            address_name: format!("{}address", name_prefix),
-                            enable_name: format!("{}enable", name_prefix),
-                            value_name: format!("{}value", name_prefix),
-                        },
-                    );
-                }
-                let name_prefix = format!("{}_write_port_", mem_name);
-                let write_address_name = format!("{}address", name_prefix);
-                let write_value_name = format!("{}value", name_prefix);
-                let write_enable_name = format!("{}enable", name_prefix);
-                self.mems.insert(
-                    key,
-                    Mem {
-                        mem,
-                        mem_name,
-                        write_address_name,
-                        write_value_name,
-                        write_enable_name,
-                        read_signal_names,
-                    },
-                );
-                for (address, enable) in mem.read_ports.borrow().iter() {
-                    self.gather(address, context, context_arena);
-                    self.gather(enable, context, context_arena);
-                }
-                if let Some((address, value, enable)) = *mem.write_port.borrow() {
-                    self.gather(address, context, context_arena);
-                    self.gather(value, context, context_arena);
-                    self.gather(enable, context, context_arena);
+    fn gather_mem(
+        &mut self,
+        mem: &'graph graph::Mem<'graph>,
+        context: &'arena ModuleContext<'graph, 'arena>,
+        stack: &mut Vec<GatherItem<'graph, 'arena>>,
+    ) {
+        let key = (context, mem);
+        if self.mems.contains_key(&key) {
+            return;
+        }
+        let mem_name = format!(
+            "__mem_{}_{}",
+            super::sanitize_identifier(&mem.name),
+            self.mems.len()
+        );
+        // TODO: It might actually be too conservative to trace all read ports,
+        //  as we only know that the write port and _this_ read port are reachable
+        //  at this point, but we have to keep some extra state to know whether or
+        //  not we've hit each read port otherwise.
+        let mut read_signal_names = IndexMap::new();
+        for (index, (address, enable)) in mem.read_ports.borrow().iter().enumerate() {
+            let name_prefix = format!("{}_read_port_{}_", mem_name, index);
+            read_signal_names.insert(
+                (*address, *enable),
+                ReadSignalNames {
+                    address_name: format!("{}address", name_prefix),
+                    enable_name: format!("{}enable", name_prefix),
+                    value_name: format!("{}value", name_prefix),
+                },
+            );
+        }
+        let write_signal_names = mem
+            .write_ports
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(index, (_, _, _, mask))| {
+                let name_prefix = format!("{}_write_port_{}_", mem_name, index);
+                WriteSignalNames {
+                    address_name: format!("{}address", name_prefix),
+                    value_name: format!("{}value", name_prefix),
+                    enable_name: format!("{}enable", name_prefix),
+                    mask_name: mask.map(|_| format!("{}mask", name_prefix)),
                 }
-            }
+            })
+            .collect();
+        self.mems.insert(
+            key,
+            Mem {
+                mem,
+                mem_name,
+                read_signal_names,
+                write_signal_names,
+            },
+        );
+        let mut port_signals = Vec::new();
+        for &(address, enable) in mem.read_ports.borrow().iter() {
+            port_signals.push(address);
+            port_signals.push(enable);
+        }
+        for &(address, value, enable, mask) in mem.write_ports.borrow().iter() {
+            port_signals.push(address);
+            port_signals.push(value);
+            port_signals.push(enable);
+            port_signals.extend(mask);
         }
+        stack.extend(
+            port_signals
+                .into_iter()
+                .rev()
+                .map(|signal| GatherItem::Signal(context, signal)),
+        );
     }
 }
+
+enum GatherItem<'graph, 'arena> {
+    Signal(
+        &'arena ModuleContext<'graph, 'arena>,
+        &'graph graph::Signal<'graph>,
+    ),
+    Mem(
+        &'arena ModuleContext<'graph, 'arena>,
+        &'graph graph::Mem<'graph>,
+    ),
+}
@@ -0,0 +1,1054 @@
+//! Graph-to-graph transformations.
+
+use crate::analysis::{hierarchy_modules, signal_depths};
+use crate::graph;
+use crate::module_context::*;
+use crate::validation::*;
+
+use typed_arena::Arena;
+
+use std::collections::{HashMap, HashSet};
+
+/// Creates a copy of the `Module` `m` in the `Context` `c` with all of `m`'s [`Instance`]s (and their [`Instance`]s, recursively) inlined, and returns it.
+///
+/// The returned `Module` has the same name, inputs, and outputs as `m`, contains no [`Instance`]s, and behaves identically to `m`, so it can be passed to either code generator in place of `m`.
+/// `c` is typically a fresh `Context`, since it must not already contain a `Module` with `m`'s name.
+///
+/// [`Register`]s, [`Latch`]es, and [`Mem`]s from instantiated `Module`s are renamed by prefixing their names with the `_`-separated path of instance names leading to them (for example, a register called `pc` in an instance called `fetch` is renamed to `fetch_pc`), and [named `Signal`s](../struct.Signal.html#method.name) are renamed the same way.
+/// If a renamed state element or named `Signal` would collide with a name that's already taken, a `_1`, `_2`, etc. suffix is appended, with names claimed in hierarchy order (`m`'s own state elements first, then each [`Instance`]'s, depth-first in instantiation order), so the resulting names are deterministic.
+/// Assertions, assumptions, and cover points are carried over, and trace labels are prefixed with the `.`-separated instance path, matching the labels reported by the generated simulator's `trace` method.
+///
+/// # Panics
+///
+/// Panics if `m`'s hierarchy contains errors (for example, undriven registers or combinational loops), or if `c` already contains a `Module` with `m`'s name.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let inner = c.module("Inner");
+/// let r = inner.reg("r", 8);
+/// r.default_value(0u32);
+/// r.drive_next(inner.input("i", 8));
+/// inner.output("o", r.value);
+///
+/// let outer = c.module("Outer");
+/// let inner_inst = outer.instance("inner_inst", "Inner");
+/// inner_inst.drive_input("i", outer.input("i", 8));
+/// outer.output("o", inner_inst.output("o"));
+///
+/// let flat_c = Context::new();
+/// let flat = transform::flatten(outer, &flat_c);
+///
+/// assert_eq!(flat.name(), "Outer");
+/// assert!(flat.instances().is_empty());
+/// assert_eq!(flat.registers().len(), 1);
+/// ```
+///
+/// [`Instance`]: ../struct.Instance.html
+/// [`Latch`]: ../struct.Latch.html
+/// [`Mem`]: ../struct.Mem.html
+/// [`Register`]: ../struct.Register.html
+pub fn flatten<'a, 'b>(
+    m: &'b graph::Module<'b>,
+    c: &'a graph::Context<'a>,
+) -> &'a graph::Module<'a> {
+    validate_module_hierarchy(m);
+
+    if let Some(extern_module) = hierarchy_modules(m).into_iter().find(|m| m.is_extern) {
+        panic!("Cannot flatten module \"{}\" because its hierarchy contains extern module \"{}\", which has no body to inline.", m.name, extern_module.name);
+    }
+
+    let context_arena = Arena::new();
+    let root_context = context_arena.alloc(ModuleContext::new());
+
+    let flat = c.module(m.name.clone());
+    let mut flattener = Flattener::new(flat, &context_arena);
+
+    for (name, input) in m.inputs.borrow().iter() {
+        let flat_input = flat.input(name.clone(), input.bit_width());
+        flattener.signals.insert((root_context, *input), flat_input);
+    }
+
+    flattener.declare_state_elements(m, root_context, String::new(), String::new());
+    flattener.connect_state_elements();
+
+    for (name, output) in m.outputs.borrow().iter() {
+        let flat_output = flattener.signal(output, root_context);
+        flat.output(name.clone(), flat_output);
+    }
+
+    flat
+}
+
+struct FlattenedModuleContext<'b, 'arena> {
+    module: &'b graph::Module<'b>,
+    context: &'arena ModuleContext<'b, 'arena>,
+    name_prefix: String,
+    label_prefix: String,
+}
+
+struct Flattener<'a, 'b, 'arena> {
+    flat: &'a graph::Module<'a>,
+    context_arena: &'arena Arena<ModuleContext<'b, 'arena>>,
+
+    contexts: Vec<FlattenedModuleContext<'b, 'arena>>,
+    state_element_names: HashSet<String>,
+
+    regs: HashMap<
+        (
+            &'arena ModuleContext<'b, 'arena>,
+            *const graph::RegisterData<'b>,
+        ),
+        &'a graph::Register<'a>,
+    >,
+    latches: HashMap<
+        (
+            &'arena ModuleContext<'b, 'arena>,
+            *const graph::LatchData<'b>,
+        ),
+        &'a graph::Latch<'a>,
+    >,
+    mems: HashMap<(&'arena ModuleContext<'b, 'arena>, &'b graph::Mem<'b>), &'a graph::Mem<'a>>,
+    signals:
+        HashMap<(&'arena ModuleContext<'b, 'arena>, &'b graph::Signal<'b>), &'a graph::Signal<'a>>,
+}
+
+impl<'a, 'b, 'arena> Flattener<'a, 'b, 'arena> {
+    fn new(
+        flat: &'a graph::Module<'a>,
+        context_arena: &'arena Arena<ModuleContext<'b, 'arena>>,
+    ) -> Flattener<'a, 'b, 'arena> {
+        Flattener {
+            flat,
+            context_arena,
+
+            contexts: Vec::new(),
+            state_element_names: HashSet::new(),
+
+            regs: HashMap::new(),
+            latches: HashMap::new(),
+            mems: HashMap::new(),
+            signals: HashMap::new(),
+        }
+    }
+
+    fn unique_state_element_name(&mut self, name: String) -> String {
+        let mut ret = name.clone();
+        let mut suffix = 1;
+        while self.state_element_names.contains(&ret) {
+            ret = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        self.state_element_names.insert(ret.clone());
+        ret
+    }
+
+    fn declare_state_elements(
+        &mut self,
+        m: &'b graph::Module<'b>,
+        context: &'arena ModuleContext<'b, 'arena>,
+        name_prefix: String,
+        label_prefix: String,
+    ) {
+        for reg in m.registers.borrow().iter() {
+            let data = match reg.data {
+                graph::SignalData::Reg { data } => data,
+                _ => unreachable!(),
+            };
+            let name = self.unique_state_element_name(format!("{}{}", name_prefix, data.name));
+            let flat_reg = self.flat.reg(name, data.bit_width);
+            if let Some(ref initial_value) = *data.initial_value.borrow() {
+                flat_reg.default_value(initial_value.numeric_value());
+            }
+            *flat_reg.data.attributes.borrow_mut() = data.attributes.borrow().clone();
+            self.regs.insert((context, data), flat_reg);
+        }
+
+        for latch in m.latches.borrow().iter() {
+            let data = match latch.data {
+                graph::SignalData::Latch { data } => data,
+                _ => unreachable!(),
+            };
+            let name = self.unique_state_element_name(format!("{}{}", name_prefix, data.name));
+            let flat_latch = self.flat.latch(name, data.bit_width);
+            self.latches.insert((context, data), flat_latch);
+        }
+
+        for &mem in m.mems.borrow().iter() {
+            let name = self.unique_state_element_name(format!("{}{}", name_prefix, mem.name));
+            let flat_mem = self
+                .flat
+                .mem(name, mem.address_bit_width, mem.element_bit_width);
+            if let Some(ref initial_contents) = *mem.initial_contents.borrow() {
+                flat_mem.initial_contents(
+                    &initial_contents
+                        .iter()
+                        .map(|x| x.numeric_value())
+                        .collect::<Vec<_>>(),
+                );
+                *flat_mem.initial_contents_path.borrow_mut() =
+                    mem.initial_contents_path.borrow().clone();
+            }
+            flat_mem.read_during_write(*mem.read_during_write.borrow());
+            *flat_mem.attributes.borrow_mut() = mem.attributes.borrow().clone();
+            self.mems.insert((context, mem), flat_mem);
+        }
+
+        // Constants are declared up front, since they're distinct from literals with the same value
+        for (name, &constant) in m.constants.borrow().iter() {
+            let (value, bit_width) = match constant.data {
+                graph::SignalData::Lit {
+                    ref value,
+                    bit_width,
+                } => (value.numeric_value(), bit_width),
+                _ => unreachable!(),
+            };
+            let name = format!("{}{}", name_prefix, name);
+            let mut unique_name = name.clone();
+            let mut suffix = 1;
+            while self.flat.constants.borrow().contains_key(&unique_name) {
+                unique_name = format!("{}_{}", name, suffix);
+                suffix += 1;
+            }
+            let flat_constant = self.flat.constant(unique_name, value, bit_width);
+            self.signals.insert((context, constant), flat_constant);
+        }
+
+        self.contexts.push(FlattenedModuleContext {
+            module: m,
+            context,
+            name_prefix: name_prefix.clone(),
+            label_prefix: label_prefix.clone(),
+        });
+
+        for &instance in m.instances.borrow().iter() {
+            let child = context.get_child(instance, self.context_arena);
+            self.declare_state_elements(
+                instance.instantiated_module,
+                child,
+                format!("{}{}_", name_prefix, instance.name),
+                format!("{}{}.", label_prefix, instance.name),
+            );
+        }
+    }
+
+    fn connect_state_elements(&mut self) {
+        for i in 0..self.contexts.len() {
+            let m = self.contexts[i].module;
+            let context = self.contexts[i].context;
+            let name_prefix = self.contexts[i].name_prefix.clone();
+            let label_prefix = self.contexts[i].label_prefix.clone();
+
+            for reg in m.registers.borrow().iter() {
+                let data = match reg.data {
+                    graph::SignalData::Reg { data } => data,
+                    _ => unreachable!(),
+                };
+                if let Some(next) = *data.next.borrow() {
+                    let flat_next = self.signal(next, context);
+                    self.regs[&(context, data as *const _)].drive_next(flat_next);
+                }
+            }
+
+            for latch in m.latches.borrow().iter() {
+                let data = match latch.data {
+                    graph::SignalData::Latch { data } => data,
+                    _ => unreachable!(),
+                };
+                let flat_latch = self.latches[&(context, data as *const _)];
+                if let Some(latch_data) = *data.data.borrow() {
+                    flat_latch.drive_data(self.signal(latch_data, context));
+                }
+                if let Some(enable) = *data.enable.borrow() {
+                    flat_latch.drive_enable(self.signal(enable, context));
+                }
+            }
+
+            for &mem in m.mems.borrow().iter() {
+                for &(address, value, enable, mask) in mem.write_ports.borrow().iter() {
+                    let address = self.signal(address, context);
+                    let value = self.signal(value, context);
+                    let enable = self.signal(enable, context);
+                    let flat_mem = self.mems[&(context, mem)];
+                    match mask {
+                        Some(mask) => {
+                            let mask = self.signal(mask, context);
+                            flat_mem.write_port_masked(address, value, enable, mask);
+                        }
+                        None => flat_mem.write_port(address, value, enable),
+                    }
+                }
+            }
+
+            for (condition, message) in m.assertions.borrow().iter() {
+                let condition = self.signal(condition, context);
+                self.flat.assert(condition, message.clone());
+            }
+            for condition in m.assumptions.borrow().iter() {
+                let condition = self.signal(condition, context);
+                self.flat.assume(condition);
+            }
+            for condition in m.covers.borrow().iter() {
+                let condition = self.signal(condition, context);
+                self.flat.cover(condition);
+            }
+            for (label, signal) in m.traces.borrow().iter() {
+                let signal = self.signal(signal, context);
+                self.flat
+                    .trace(format!("{}{}", label_prefix, label), signal);
+            }
+
+            for (name, signal) in m.named_signals.borrow().iter() {
+                let signal = self.signal(signal, context);
+                let name = format!("{}{}", name_prefix, name);
+                let mut unique_name = name.clone();
+                let mut suffix = 1;
+                while self.flat.named_signals.borrow().contains_key(&unique_name) {
+                    unique_name = format!("{}_{}", name, suffix);
+                    suffix += 1;
+                }
+                signal.name(unique_name);
+            }
+        }
+    }
+
+    fn signal(
+        &mut self,
+        signal: &'b graph::Signal<'b>,
+        context: &'arena ModuleContext<'b, 'arena>,
+    ) -> &'a graph::Signal<'a> {
+        // Operands are copied bottom-up with an explicit stack first, so that this never recurses, regardless of how deep the graph is
+        let mut stack = vec![((context, signal), false)];
+        while let Some(((context, signal), operands_copied)) = stack.pop() {
+            if self.signals.contains_key(&(context, signal)) {
+                continue;
+            }
+            if operands_copied {
+                let ret = self.single_signal(signal, context);
+                self.signals.insert((context, signal), ret);
+                continue;
+            }
+            stack.push(((context, signal), true));
+            let operands = self.operands(signal, context);
+            stack.extend(operands.into_iter().rev().map(|operand| (operand, false)));
+        }
+
+        self.signals[&(context, signal)]
+    }
+
+    // The signals (and the contexts they're in) that copying `signal` depends on
+    fn operands(
+        &self,
+        signal: &'b graph::Signal<'b>,
+        context: &'arena ModuleContext<'b, 'arena>,
+    ) -> Vec<(&'arena ModuleContext<'b, 'arena>, &'b graph::Signal<'b>)> {
+        match signal.data {
+            graph::SignalData::Input { name, .. } => {
+                // Root inputs are always already mapped, so this must be an instance input
+                let (instance, parent) = context.instance_and_parent.unwrap();
+                vec![(parent, instance.driven_inputs.borrow()[name])]
+            }
+
+            graph::SignalData::Reg { .. } | graph::SignalData::Latch { .. } => Vec::new(),
+
+            graph::SignalData::InstanceOutput { instance, name } => vec![(
+                context.get_child(instance, self.context_arena),
+                instance.instantiated_module.outputs.borrow()[name],
+            )],
+
+            graph::SignalData::MemReadPortOutput {
+                address, enable, ..
+            } => vec![(context, address), (context, enable)],
+            graph::SignalData::MemCombReadPortOutput { address, .. } => vec![(context, address)],
+
+            _ => signal
+                .dependencies()
+                .into_iter()
+                .map(|source| (context, source))
+                .collect(),
+        }
+    }
+
+    // Copies `signal`, whose operands must already be copied
+    fn single_signal(
+        &self,
+        signal: &'b graph::Signal<'b>,
+        context: &'arena ModuleContext<'b, 'arena>,
+    ) -> &'a graph::Signal<'a> {
+        match signal.data {
+            graph::SignalData::Input { name, .. } => {
+                let (instance, parent) = context.instance_and_parent.unwrap();
+                self.signals[&(parent, instance.driven_inputs.borrow()[name])]
+            }
+
+            graph::SignalData::Reg { data } => self.regs[&(context, data as *const _)].value,
+            graph::SignalData::Latch { data } => self.latches[&(context, data as *const _)].value,
+
+            graph::SignalData::InstanceOutput { instance, name } => {
+                let child = context.get_child(instance, self.context_arena);
+                self.signals[&(child, instance.instantiated_module.outputs.borrow()[name])]
+            }
+
+            graph::SignalData::MemReadPortOutput {
+                mem,
+                address,
+                enable,
+            } => {
+                let address = self.signals[&(context, address)];
+                let enable = self.signals[&(context, enable)];
+                self.mems[&(context, mem)].read_port(address, enable)
+            }
+            graph::SignalData::MemCombReadPortOutput { mem, address } => {
+                let address = self.signals[&(context, address)];
+                self.mems[&(context, mem)].read_port_comb(address)
+            }
+
+            _ => copy_combinational(self.flat, signal, |source| self.signals[&(context, source)]),
+        }
+    }
+}
+
+/// A pipelined copy of a `Module`, as returned by [`pipeline`].
+///
+/// [`pipeline`]: ./fn.pipeline.html
+pub struct PipelinedModule<'a> {
+    /// The pipelined `Module`.
+    pub module: &'a graph::Module<'a>,
+    /// The number of cycles of latency added to each output, in output name order.
+    ///
+    /// This is the requested number of stages for every output, except for outputs that don't depend on any inputs, which aren't delayed.
+    pub output_latencies: Vec<(String, u32)>,
+}
+
+/// Creates a copy of the purely combinational `Module` `m` in the `Context` `c`, split into `stages` pipeline stages of roughly equal logic depth, and returns it along with the latency added to each of its outputs.
+///
+/// Each signal's depth is estimated the same way as in [`analysis::logic_depth`], and the logic is cut into `stages` levels that each cover an equal share of the deepest output's depth.
+/// Every signal that's used in a later level than the one it's computed in is passed through [`Register`]s at each cut it crosses, and these delay registers are shared, so an input that feeds several levels gets a single chain of registers that's tapped at each level.
+/// Each output is followed by a register as well, so every output that depends on an input is delayed by exactly `stages` cycles, and all of them stay aligned with each other.
+/// Outputs that only depend on literals are left undelayed, which is reflected in [`PipelinedModule::output_latencies`].
+///
+/// The returned `Module` has the same name, inputs, and outputs as `m`, and after the first `stages` cycles, its outputs match `m`'s outputs from `stages` cycles earlier.
+/// The inserted registers don't have default values, so they don't need a reset.
+/// Register names are derived from the `Signal`s they delay (`m`'s inputs and [named `Signal`s](../struct.Signal.html#method.name), or `pipe` otherwise), followed by `_stage` and the number of the cut they're in (starting at 1), with a `_1`, `_2`, etc. suffix appended if a name is already taken.
+/// `c` is typically a fresh `Context`, since it must not already contain a `Module` with `m`'s name.
+///
+/// # Panics
+///
+/// Panics if `m` contains errors (for example, combinational loops), if it contains any [`Register`]s, [`Latch`]es, [`Mem`]s, or [`Instance`]s (modules with instances can be [`flatten`]ed first), or if `c` already contains a `Module` with `m`'s name.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("Mac");
+/// let a = m.input("a", 8);
+/// let b = m.input("b", 8);
+/// let acc = m.input("acc", 8);
+/// m.output("o", ((a ^ b) + acc) & !a);
+/// m.output("zero", m.lit(0u32, 8));
+///
+/// let pipelined_c = Context::new();
+/// let pipelined = transform::pipeline(m, 2, &pipelined_c);
+///
+/// assert_eq!(pipelined.module.name(), "Mac");
+/// assert_eq!(
+///     pipelined.output_latencies,
+///     vec![("o".to_string(), 2), ("zero".to_string(), 0)]
+/// );
+/// ```
+///
+/// [`analysis::logic_depth`]: ../analysis/fn.logic_depth.html
+/// [`flatten`]: ./fn.flatten.html
+/// [`Instance`]: ../struct.Instance.html
+/// [`Latch`]: ../struct.Latch.html
+/// [`Mem`]: ../struct.Mem.html
+/// [`PipelinedModule::output_latencies`]: ./struct.PipelinedModule.html#structfield.output_latencies
+/// [`Register`]: ../struct.Register.html
+pub fn pipeline<'a, 'b>(
+    m: &'b graph::Module<'b>,
+    stages: u32,
+    c: &'a graph::Context<'a>,
+) -> PipelinedModule<'a> {
+    validate_module_hierarchy(m);
+
+    let state_element = if let Some(reg) = m.registers.borrow().first() {
+        match reg.data {
+            graph::SignalData::Reg { data } => Some(format!("register \"{}\"", data.name)),
+            _ => unreachable!(),
+        }
+    } else if let Some(latch) = m.latches.borrow().first() {
+        match latch.data {
+            graph::SignalData::Latch { data } => Some(format!("latch \"{}\"", data.name)),
+            _ => unreachable!(),
+        }
+    } else if let Some(mem) = m.mems.borrow().first() {
+        Some(format!("memory \"{}\"", mem.name))
+    } else {
+        m.instances
+            .borrow()
+            .first()
+            .map(|instance| format!("instance \"{}\"", instance.name))
+    };
+    if let Some(state_element) = state_element {
+        panic!("Cannot pipeline module \"{}\" because it contains {}. Only purely combinational modules can be pipelined (modules with instances can be flattened first).", m.name, state_element);
+    }
+
+    let depths = signal_depths(m);
+    let max_depth = depths.values().copied().max().unwrap_or(0);
+
+    let pipelined = c.module(m.name.clone());
+    let mut pipeliner = Pipeliner {
+        pipelined,
+        stages,
+        max_depth,
+        depths,
+        names: HashMap::new(),
+        reg_names: HashSet::new(),
+        levels: HashMap::new(),
+        signals: HashMap::new(),
+    };
+
+    for (name, input) in m.inputs.borrow().iter() {
+        let pipelined_input = pipelined.input(name.clone(), input.bit_width());
+        pipeliner.signals.insert((*input, 0), pipelined_input);
+        pipeliner.names.insert(*input, name.clone());
+        pipeliner.reg_names.insert(name.clone());
+    }
+    for (name, signal) in m.named_signals.borrow().iter() {
+        pipeliner
+            .names
+            .entry(*signal)
+            .or_insert_with(|| name.clone());
+    }
+    pipeliner
+        .reg_names
+        .extend(m.outputs.borrow().keys().cloned());
+
+    let mut output_latencies = Vec::new();
+    for (name, output) in m.outputs.borrow().iter() {
+        let latency = match pipeliner.level(output) {
+            Some(_) => stages,
+            None => 0,
+        };
+        let pipelined_output = pipeliner.signal(output, latency);
+        pipelined.output(name.clone(), pipelined_output);
+        output_latencies.push((name.clone(), latency));
+    }
+
+    PipelinedModule {
+        module: pipelined,
+        output_latencies,
+    }
+}
+
+struct Pipeliner<'a, 'b> {
+    pipelined: &'a graph::Module<'a>,
+    stages: u32,
+    max_depth: u32,
+    depths: HashMap<*const graph::Signal<'b>, u32>,
+
+    names: HashMap<&'b graph::Signal<'b>, String>,
+    reg_names: HashSet<String>,
+
+    // The level each signal is computed in, or `None` if it only depends on literals
+    levels: HashMap<&'b graph::Signal<'b>, Option<u32>>,
+    // Each signal's copy, delayed by the given number of cuts
+    signals: HashMap<(&'b graph::Signal<'b>, u32), &'a graph::Signal<'a>>,
+}
+
+impl<'a, 'b> Pipeliner<'a, 'b> {
+    fn level(&mut self, signal: &'b graph::Signal<'b>) -> Option<u32> {
+        // Sources are visited bottom-up with an explicit stack first, so that this never recurses, regardless of how deep the graph is
+        let mut stack = vec![(signal, false)];
+        while let Some((signal, sources_visited)) = stack.pop() {
+            if self.levels.contains_key(&signal) {
+                continue;
+            }
+            if sources_visited {
+                let level = self.single_level(signal);
+                self.levels.insert(signal, level);
+                continue;
+            }
+            stack.push((signal, true));
+            stack.extend(
+                signal
+                    .dependencies()
+                    .into_iter()
+                    .rev()
+                    .map(|source| (source, false)),
+            );
+        }
+
+        self.levels[&signal]
+    }
+
+    // Computes the level of `signal`, whose sources' levels must already be known
+    fn single_level(&self, signal: &'b graph::Signal<'b>) -> Option<u32> {
+        let sources = signal.dependencies();
+        let source_levels = sources.iter().map(|source| self.levels[source]);
+        match signal.data {
+            graph::SignalData::Lit { .. } => None,
+            graph::SignalData::Input { .. } => Some(0),
+
+            // Wiring belongs to the level of its latest source
+            graph::SignalData::Bits { .. }
+            | graph::SignalData::Repeat { .. }
+            | graph::SignalData::Concat { .. } => source_levels.max().unwrap(),
+
+            _ => {
+                if source_levels.clone().any(|level| level.is_some()) {
+                    // Logic nodes have a depth of at least 1, and deeper nodes never land in an earlier level than their sources
+                    Some((self.depths[&(signal as *const _)] - 1) * self.stages / self.max_depth)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn signal(&mut self, signal: &'b graph::Signal<'b>, level: u32) -> &'a graph::Signal<'a> {
+        // Each copy depends either on the same signal delayed by one less cut, or on its sources' copies in the same level.
+        // These are created bottom-up with an explicit stack first, so that this never recurses, regardless of how deep the graph is.
+        let mut stack = vec![((signal, level), false)];
+        while let Some(((signal, level), dependencies_created)) = stack.pop() {
+            if self.signals.contains_key(&(signal, level)) {
+                continue;
+            }
+            let delayed = match self.level(signal) {
+                Some(signal_level) => level > signal_level,
+                None => false,
+            };
+            if dependencies_created {
+                let ret = if delayed {
+                    let source = self.signals[&(signal, level - 1)];
+                    let name = self.reg_name(signal, level);
+                    let reg = self.pipelined.reg(name, signal.bit_width());
+                    reg.drive_next(source);
+                    reg.value
+                } else {
+                    copy_combinational(self.pipelined, signal, |source| {
+                        self.signals[&(source, level)]
+                    })
+                };
+                self.signals.insert((signal, level), ret);
+                continue;
+            }
+            stack.push(((signal, level), true));
+            if delayed {
+                stack.push(((signal, level - 1), false));
+            } else {
+                stack.extend(
+                    signal
+                        .dependencies()
+                        .into_iter()
+                        .rev()
+                        .map(|source| ((source, level), false)),
+                );
+            }
+        }
+
+        self.signals[&(signal, level)]
+    }
+
+    fn reg_name(&mut self, signal: &'b graph::Signal<'b>, cut: u32) -> String {
+        let name = format!(
+            "{}_stage{}",
+            self.names
+                .get(&signal)
+                .map(String::as_str)
+                .unwrap_or("pipe"),
+            cut
+        );
+        let mut ret = name.clone();
+        let mut suffix = 1;
+        while self.reg_names.contains(&ret) {
+            ret = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        self.reg_names.insert(ret.clone());
+        ret
+    }
+}
+
+// Copies a combinational node into `m`, mapping each of its operands (in order) with `operand`
+fn copy_combinational<'a, 'b>(
+    m: &'a graph::Module<'a>,
+    signal: &'b graph::Signal<'b>,
+    mut operand: impl FnMut(&'b graph::Signal<'b>) -> &'a graph::Signal<'a>,
+) -> &'a graph::Signal<'a> {
+    match signal.data {
+        graph::SignalData::Lit {
+            ref value,
+            bit_width,
+        } => m.lit(value.numeric_value(), bit_width),
+        graph::SignalData::UnOp { source, op } => {
+            let source = operand(source);
+            match op {
+                graph::UnOp::Not => !source,
+            }
+        }
+        graph::SignalData::SimpleBinOp { lhs, rhs, op } => {
+            let lhs = operand(lhs);
+            let rhs = operand(rhs);
+            match op {
+                graph::SimpleBinOp::BitAnd => lhs & rhs,
+                graph::SimpleBinOp::BitOr => lhs | rhs,
+                graph::SimpleBinOp::BitXor => lhs ^ rhs,
+            }
+        }
+        graph::SignalData::AdditiveBinOp { lhs, rhs, op } => {
+            let lhs = operand(lhs);
+            let rhs = operand(rhs);
+            match op {
+                graph::AdditiveBinOp::Add => lhs + rhs,
+                graph::AdditiveBinOp::Sub => lhs - rhs,
+            }
+        }
+        graph::SignalData::ComparisonBinOp { lhs, rhs, op } => {
+            let lhs = operand(lhs);
+            let rhs = operand(rhs);
+            match op {
+                graph::ComparisonBinOp::Equal => lhs.eq(rhs),
+                graph::ComparisonBinOp::NotEqual => lhs.ne(rhs),
+                graph::ComparisonBinOp::LessThan => lhs.lt(rhs),
+                graph::ComparisonBinOp::LessThanEqual => lhs.le(rhs),
+                graph::ComparisonBinOp::GreaterThan => lhs.gt(rhs),
+                graph::ComparisonBinOp::GreaterThanEqual => lhs.ge(rhs),
+                graph::ComparisonBinOp::LessThanSigned => lhs.lt_signed(rhs),
+                graph::ComparisonBinOp::LessThanEqualSigned => lhs.le_signed(rhs),
+                graph::ComparisonBinOp::GreaterThanSigned => lhs.gt_signed(rhs),
+                graph::ComparisonBinOp::GreaterThanEqualSigned => lhs.ge_signed(rhs),
+            }
+        }
+        graph::SignalData::ShiftBinOp { lhs, rhs, op } => {
+            let lhs = operand(lhs);
+            let rhs = operand(rhs);
+            match op {
+                graph::ShiftBinOp::Shl => lhs << rhs,
+                graph::ShiftBinOp::Shr => lhs >> rhs,
+                graph::ShiftBinOp::ShrArithmetic => lhs.shr_arithmetic(rhs),
+            }
+        }
+
+        graph::SignalData::Bits {
+            source,
+            range_high,
+            range_low,
+        } => operand(source).bits(range_high, range_low),
+
+        graph::SignalData::Repeat { source, count } => operand(source).repeat(count),
+        graph::SignalData::Concat { lhs, rhs } => {
+            let lhs = operand(lhs);
+            let rhs = operand(rhs);
+            lhs.concat(rhs)
+        }
+
+        graph::SignalData::Mux {
+            cond,
+            when_true,
+            when_false,
+        } => {
+            let cond = operand(cond);
+            let when_true = operand(when_true);
+            let when_false = operand(when_false);
+            m.mux(cond, when_true, when_false)
+        }
+
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::*;
+
+    #[test]
+    fn flatten_renames_state_elements() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let r = inner.reg("r", 4);
+        r.default_value(3u32);
+        r.drive_next(inner.input("i", 4));
+        let mem = inner.mem("mem", 1, 4);
+        mem.initial_contents(&[1u32, 2]);
+        inner.output("o", mem.read_port_comb(r.value.bit(0)) ^ r.value);
+        (r.value + inner.lit(1u32, 4)).name("r_plus_one");
+
+        let outer = c.module("Outer");
+        let a = outer.instance("a", "Inner");
+        a.drive_input("i", outer.input("i", 4));
+        let b = outer.instance("b", "Inner");
+        b.drive_input("i", a.output("o"));
+        outer.output("o", b.output("o"));
+
+        let flat_c = Context::new();
+        let flat = flatten(outer, &flat_c);
+
+        assert_eq!(flat.name(), "Outer");
+        assert!(flat.instances().is_empty());
+        assert_eq!(flat.inputs().keys().collect::<Vec<_>>(), vec!["i"]);
+        assert_eq!(flat.outputs().keys().collect::<Vec<_>>(), vec!["o"]);
+        assert_eq!(
+            flat.mems()
+                .iter()
+                .map(|mem| mem.name().to_string())
+                .collect::<Vec<_>>(),
+            vec!["a_mem", "b_mem"]
+        );
+        assert_eq!(flat.registers().len(), 2);
+        assert_eq!(
+            flat.named_signals.borrow().keys().collect::<Vec<_>>(),
+            vec!["a_r_plus_one", "b_r_plus_one"]
+        );
+    }
+
+    #[test]
+    fn flatten_resolves_name_collisions_in_hierarchy_order() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let r = inner.reg("r", 1);
+        r.drive_next(!r.value);
+        inner.output("o", r.value);
+
+        let outer = c.module("Outer");
+        let a = outer.instance("a", "Inner");
+        let a_r = outer.reg("a_r", 1);
+        a_r.drive_next(a.output("o"));
+        let a_r_1 = outer.mem("a_r_1", 1, 1);
+        a_r_1.initial_contents(&[false, true]);
+        outer.output("o", a_r_1.read_port(a_r.value, outer.high()));
+
+        let flat_c = Context::new();
+        let flat = flatten(outer, &flat_c);
+
+        let reg_names = flat
+            .registers()
+            .iter()
+            .map(|reg| match reg.data {
+                graph::SignalData::Reg { data } => data.name.clone(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(reg_names, vec!["a_r", "a_r_2"]);
+        assert_eq!(flat.mems()[0].name(), "a_r_1");
+    }
+
+    #[test]
+    fn flatten_resolves_named_signal_collisions() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let i = inner.input("i", 1);
+        inner.output("o", (!i).name("x"));
+
+        let outer = c.module("Outer");
+        let a = outer.instance("a", "Inner");
+        let i = outer.input("i", 1);
+        a.drive_input("i", i);
+        outer.output("o", a.output("o") ^ (i & outer.high()).name("a_x"));
+
+        let flat_c = Context::new();
+        let flat = flatten(outer, &flat_c);
+
+        assert_eq!(
+            flat.named_signals.borrow().keys().collect::<Vec<_>>(),
+            vec!["a_x", "a_x_1"]
+        );
+    }
+
+    #[test]
+    fn flatten_deep_signal_chain() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let a = inner.input("a", 8);
+        // A chain of ~100k signals, which overflows the stack if flattening recurses through it
+        let mut x = a;
+        for _ in 0..34_000 {
+            x = (x + a).bits(6, 0).concat(a.bit(0));
+        }
+        inner.output("o", x);
+
+        let outer = c.module("Outer");
+        let inner_inst = outer.instance("inner_inst", "Inner");
+        inner_inst.drive_input("a", outer.input("a", 8));
+        outer.output("o", inner_inst.output("o"));
+
+        let flat_c = Context::new();
+        let flat = flatten(outer, &flat_c);
+
+        assert!(flat.instances.borrow().is_empty());
+    }
+
+    #[test]
+    fn flatten_prefixes_trace_labels() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let i = inner.input("i", 1);
+        inner.trace("i", i);
+        inner.assert(i | !i, "tautology");
+        inner.output("o", i);
+
+        let outer = c.module("Outer");
+        let inner = outer.instance("inner", "Inner");
+        inner.drive_input("i", outer.input("i", 1));
+        outer.output("o", inner.output("o"));
+
+        let flat_c = Context::new();
+        let flat = flatten(outer, &flat_c);
+
+        assert_eq!(flat.traces.borrow()[0].0, "inner.i");
+        assert_eq!(flat.assertions.borrow()[0].1, "tautology");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot flatten module \"Outer\" because its hierarchy contains extern module \"Blackbox\", which has no body to inline."
+    )]
+    fn flatten_extern_module_error() {
+        let c = Context::new();
+
+        let blackbox = c.extern_module("Blackbox");
+        blackbox.input("i", 1);
+        blackbox.extern_output("o", 1);
+
+        let outer = c.module("Outer");
+        let blackbox = outer.instance("blackbox", "Blackbox");
+        blackbox.drive_input("i", outer.input("i", 1));
+        outer.output("o", blackbox.output("o"));
+
+        let flat_c = Context::new();
+
+        // Panic
+        let _ = flatten(outer, &flat_c);
+    }
+
+    fn reg_names<'a>(m: &'a Module<'a>) -> Vec<String> {
+        m.registers()
+            .iter()
+            .map(|reg| match reg.data {
+                graph::SignalData::Reg { data } => data.name.clone(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pipeline_cuts_at_equal_depths() {
+        let c = Context::new();
+
+        let m = c.module("Chain");
+        let a = m.input("a", 8);
+        let b = m.input("b", 8);
+        let mixed = ((a + b) ^ a).name("mixed");
+        m.output("o", !(mixed + b));
+
+        let pipelined_c = Context::new();
+        let pipelined = pipeline(m, 2, &pipelined_c);
+
+        // Depths 1 and 2 land in the first level, and depths 3 and 4 in the second, so only
+        // `mixed` and `b` cross the first cut
+        assert_eq!(
+            reg_names(pipelined.module),
+            vec!["mixed_stage1", "b_stage1", "pipe_stage2"]
+        );
+        assert_eq!(pipelined.output_latencies, vec![("o".to_string(), 2)]);
+    }
+
+    #[test]
+    fn pipeline_shares_delay_registers() {
+        let c = Context::new();
+
+        let m = c.module("Fanout");
+        let a = m.input("a", 4);
+        let b = m.input("b", 4);
+        let x = m.input("a_stage1", 4);
+        let x1 = a + b;
+        let x2 = x1 + a;
+        let x3 = x2 + a;
+        m.output("o", x3 ^ x);
+        m.output("p", x1.concat(a));
+        m.output("one", m.lit(1u32, 1) & m.high());
+
+        let pipelined_c = Context::new();
+        let pipelined = pipeline(m, 4, &pipelined_c);
+
+        // `a` feeds levels 0, 1, and 2 through a single chain of registers, whose names avoid the
+        // input called "a_stage1", and `p`'s wiring is delayed as a whole
+        assert_eq!(
+            reg_names(pipelined.module),
+            vec![
+                "pipe_stage1",
+                "a_stage1_1",
+                "pipe_stage2",
+                "a_stage2",
+                "pipe_stage3",
+                "a_stage1_stage1",
+                "a_stage1_stage2",
+                "a_stage1_stage3",
+                "pipe_stage4",
+                "pipe_stage1_1",
+                "pipe_stage2_1",
+                "pipe_stage3_1",
+                "pipe_stage4_1",
+            ]
+        );
+        assert_eq!(
+            pipelined.output_latencies,
+            vec![
+                ("o".to_string(), 4),
+                ("one".to_string(), 0),
+                ("p".to_string(), 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn pipeline_deep_signal_chain() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+        // A chain of ~100k signals, which overflows the stack if pipelining recurses through it
+        let mut x = a;
+        for _ in 0..34_000 {
+            x = (x + a).bits(6, 0).concat(a.bit(0));
+        }
+        m.output("o", x);
+
+        let pipelined_c = Context::new();
+        let pipelined = pipeline(m, 4, &pipelined_c);
+
+        assert_eq!(pipelined.output_latencies, vec![("o".to_string(), 4)]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot pipeline module \"Counter\" because it contains register \"r\". Only purely combinational modules can be pipelined (modules with instances can be flattened first)."
+    )]
+    fn pipeline_register_error() {
+        let c = Context::new();
+
+        let m = c.module("Counter");
+        let r = m.reg("r", 4);
+        r.drive_next(r.value + m.input("i", 4));
+        m.output("o", r.value);
+
+        let pipelined_c = Context::new();
+
+        // Panic
+        let _ = pipeline(m, 2, &pipelined_c);
+    }
+}
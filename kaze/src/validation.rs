@@ -1,11 +1,131 @@
+//! Design checks, reported as structured [`Diagnostic`]s by [`lint`].
+//!
+//! [`Diagnostic`]: ./struct.Diagnostic.html
+//! [`lint`]: ./fn.lint.html
+
+use std::collections::HashSet;
+use std::fmt;
 use std::ptr;
 
 use super::module_context::*;
 
+use crate::constant_folding::ConstantFolder;
 use crate::graph;
 
 use typed_arena::Arena;
 
+/// How serious the issue described by a [`Diagnostic`] is.
+///
+/// [`Diagnostic`]: ./struct.Diagnostic.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// A potential issue which doesn't prevent code generation, but usually indicates a mistake in the design.
+    Warning,
+    /// An issue which prevents code generation; generating code for a design with any of these panics.
+    Error,
+}
+
+/// The kind of issue described by a [`Diagnostic`], along with the contents of its [`names`] field.
+///
+/// [`Diagnostic`]: ./struct.Diagnostic.html
+/// [`names`]: ./struct.Diagnostic.html#structfield.names
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DiagnosticKind {
+    /// A `Module` contains an instance of itself, directly or indirectly. `names` contains the name of the instance.
+    ///
+    /// Instances that form recursive definitions aren't checked any further, and the rest of the hierarchy isn't checked for undriven registers or latches, memory errors, or combinational loops.
+    RecursiveDefinition,
+    /// An instance has an input that isn't driven. `names` contains the name of the instance followed by the name of the input.
+    UndrivenInput,
+    /// A register's next value isn't driven. `names` contains the name of the register.
+    UndrivenRegister,
+    /// A latch's data or enable signal isn't driven. `names` contains the name of the latch.
+    UndrivenLatch,
+    /// A memory doesn't have any read ports. `names` contains the name of the memory.
+    MemWithoutReadPorts,
+    /// A memory has neither initial contents nor a write port. `names` contains the name of the memory.
+    MemWithoutContents,
+    /// An instance's output or a latch's value depends combinationally on itself. `names` contains the name of the output or latch.
+    CombinationalLoop,
+    /// A cover point's condition is a constant literal, so it's either always or never hit. `names` contains the cover point's index in its `Module`.
+    ConstantCover,
+    /// A `Module`'s input doesn't affect any of its outputs, registers, instances, memory writes, or verification statements. `names` contains the name of the input.
+    UnusedInput,
+    /// An instance's output is never read, either because [`Instance::output`] was never called for it, or because the resulting `Signal` doesn't drive anything. `names` contains the name of the instance followed by the name of the output.
+    ///
+    /// [`Instance::output`]: ../struct.Instance.html#method.output
+    UnusedInstanceOutput,
+    /// A `Signal` named with [`Signal::name`] doesn't drive anything. `names` contains the signal's name.
+    ///
+    /// [`Signal::name`]: ../struct.Signal.html#method.name
+    UnusedNamedSignal,
+    /// A `Module` contains a [`Latch`], which is usually unintentional. `names` contains the name of the latch.
+    ///
+    /// [`Latch`]: ../struct.Latch.html
+    Latch,
+    /// A state of an [`Fsm`] can't be reached from its initial state by taking transitions that can fire. `names` contains the name of the state machine followed by the name of the state.
+    ///
+    /// [`Fsm`]: ../struct.Fsm.html
+    UnreachableFsmState,
+    /// A transition of an [`Fsm`] can never be taken, either because its condition is always low, or because an earlier transition from the same state is always taken. `names` contains the name of the state machine followed by the names of the states the transition leads from and to.
+    ///
+    /// [`Fsm`]: ../struct.Fsm.html
+    UnreachableFsmTransition,
+}
+
+/// An issue found in a `Module` hierarchy by [`lint`].
+///
+/// [`lint`]: ./fn.lint.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// How serious this issue is.
+    pub severity: Severity,
+    /// What kind of issue this is.
+    pub kind: DiagnosticKind,
+    /// The names of the instances leading from the linted `Module` to the instance of the `Module` containing this issue, outermost first, or an empty `Vec` if the issue is in the linted `Module` itself.
+    pub instance_path: Vec<String>,
+    /// The name of the `Module` containing this issue.
+    pub module: String,
+    /// The names of the items involved in this issue, as described by [`DiagnosticKind`].
+    ///
+    /// [`DiagnosticKind`]: ./enum.DiagnosticKind.html
+    pub names: Vec<String>,
+    /// A human-readable description of this issue, which is also the panic message for errors encountered when generating code.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+struct Linter<'graph> {
+    root: &'graph graph::Module<'graph>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'graph> Linter<'graph> {
+    fn push(
+        &mut self,
+        severity: Severity,
+        kind: DiagnosticKind,
+        instance_path: Vec<String>,
+        module: &graph::Module<'graph>,
+        names: Vec<String>,
+        message: String,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            kind,
+            instance_path,
+            module: module.name.clone(),
+            names,
+            message,
+        });
+    }
+}
+
 struct ModuleStackFrame<'graph, 'frame> {
     parent: Option<(
         &'graph graph::Instance<'graph>,
@@ -14,64 +134,434 @@ struct ModuleStackFrame<'graph, 'frame> {
     module: &'graph graph::Module<'graph>,
 }
 
-pub fn validate_module_hierarchy<'graph>(m: &'graph graph::Module<'graph>) {
-    detect_recursive_definitions(
-        m,
-        &ModuleStackFrame {
-            parent: None,
-            module: m,
-        },
-        m,
-    );
-    detect_undriven_registers(
-        m,
-        &ModuleStackFrame {
-            parent: None,
-            module: m,
-        },
-        m,
-    );
-    detect_mem_errors(
-        m,
-        &ModuleStackFrame {
-            parent: None,
-            module: m,
-        },
-        m,
+fn frame_path(module_stack_frame: &ModuleStackFrame) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut frame = module_stack_frame;
+    while let Some((instance, parent)) = frame.parent {
+        path.push(instance.name.clone());
+        frame = parent;
+    }
+    path.reverse();
+    path
+}
+
+fn context_path(context: &ModuleContext) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut context = context;
+    while let Some((instance, parent)) = context.instance_and_parent {
+        path.push(instance.name.clone());
+        context = parent;
+    }
+    path.reverse();
+    path
+}
+
+/// Checks the hierarchy rooted at `m` for issues, and returns a [`Diagnostic`] for each one that's found.
+///
+/// Unlike generating code, which panics on the first error it finds, this collects every issue in a single pass, so they can all be fixed at once.
+/// Errors are reported first, in the order in which code generation would encounter them, followed by warnings.
+/// Currently, the following issues are reported (see [`DiagnosticKind`] for details):
+///
+/// - Errors: recursive definitions, undriven instance inputs, undriven registers, undriven latches, memories without read ports, memories without initial contents or write ports, and combinational loops.
+/// - Warnings: cover points (see [`Module::cover`]) whose conditions are constant literals, as they're either never hit or trivially hit on every cycle, unused inputs, instance outputs, and named signals, latches (see [`Module::latch`]), as well as state machine (see [`Module::fsm`]) states that can't be reached and transitions that can never be taken.
+///
+/// A `Signal` is considered used if it (transitively) drives an output, a register's next value, an instance input, a memory write port, or an assertion, assumption, cover point, or trace.
+///
+/// Issues inside a `Module` that's instantiated more than once are reported once per instance (except for warnings, which are reported once per `Module`).
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+/// use kaze::validation::*;
+///
+/// let c = Context::new();
+///
+/// let inner = c.module("Inner");
+/// let r = inner.reg("r", 1);
+/// inner.output("o", r.value & inner.input("i", 1));
+///
+/// let m = c.module("Outer");
+/// let inner = m.instance("inner", "Inner");
+/// m.output("o", inner.output("o"));
+///
+/// let diagnostics = lint(m);
+/// assert_eq!(diagnostics.len(), 2);
+///
+/// assert_eq!(diagnostics[0].severity, Severity::Error);
+/// assert_eq!(diagnostics[0].kind, DiagnosticKind::UndrivenInput);
+/// assert_eq!(diagnostics[0].module, "Outer");
+/// assert_eq!(diagnostics[0].names, vec!["inner", "i"]);
+///
+/// assert_eq!(diagnostics[1].kind, DiagnosticKind::UndrivenRegister);
+/// assert_eq!(diagnostics[1].instance_path, vec!["inner"]);
+/// assert_eq!(diagnostics[1].module, "Inner");
+/// assert_eq!(diagnostics[1].names, vec!["r"]);
+/// ```
+///
+/// [`Diagnostic`]: ./struct.Diagnostic.html
+/// [`DiagnosticKind`]: ./enum.DiagnosticKind.html
+/// [`Module::cover`]: ../struct.Module.html#method.cover
+/// [`Module::fsm`]: ../struct.Module.html#method.fsm
+/// [`Module::latch`]: ../struct.Module.html#method.latch
+pub fn lint<'graph>(m: &'graph graph::Module<'graph>) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        root: m,
+        diagnostics: Vec::new(),
+    };
+    detect_errors(m, &mut linter);
+    detect_warnings(m, Vec::new(), &mut Vec::new(), &mut linter);
+    linter.diagnostics
+}
+
+/// Returns a list of human-readable warnings describing potential issues in the hierarchy rooted at `m`.
+///
+/// These are the messages of the [`Severity::Warning`] diagnostics returned by [`lint`].
+/// Unlike the errors reported when generating code (which cause a panic), these issues don't prevent code generation, but usually indicate a mistake in the design.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+/// m.cover(m.high());
+/// m.output("o", m.input("i", 1));
+///
+/// assert_eq!(
+///     validation::warnings(m),
+///     vec!["Module \"MyModule\" contains a cover point (index 0) whose condition is a constant literal, so it's either always or never hit."],
+/// );
+/// ```
+///
+/// [`lint`]: ./fn.lint.html
+/// [`Severity::Warning`]: ./enum.Severity.html#variant.Warning
+pub fn warnings<'graph>(m: &'graph graph::Module<'graph>) -> Vec<String> {
+    lint(m)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+        .map(|diagnostic| diagnostic.message)
+        .collect()
+}
+
+fn detect_warnings<'graph>(
+    m: &'graph graph::Module<'graph>,
+    instance_path: Vec<String>,
+    visited_modules: &mut Vec<&'graph graph::Module<'graph>>,
+    linter: &mut Linter<'graph>,
+) {
+    // Modules may be instantiated multiple times, but should only be reported once
+    if visited_modules.iter().any(|visited| ptr::eq(*visited, m)) {
+        return;
+    }
+    visited_modules.push(m);
+
+    for (index, condition) in m.covers.borrow().iter().enumerate() {
+        if let graph::SignalData::Lit { .. } = condition.data {
+            let message = format!("Module \"{}\" contains a cover point (index {}) whose condition is a constant literal, so it's either always or never hit.", m.name, index);
+            linter.push(
+                Severity::Warning,
+                DiagnosticKind::ConstantCover,
+                instance_path.clone(),
+                m,
+                vec![index.to_string()],
+                message,
+            );
+        }
+    }
+
+    detect_unused_signals(m, &instance_path, linter);
+
+    for latch in m.latches.borrow().iter() {
+        let data = match latch.data {
+            graph::SignalData::Latch { data } => data,
+            _ => unreachable!(),
+        };
+        let message = format!("Module \"{}\" contains a latch called \"{}\". Latches are usually unintentional; consider using a register instead. The latch was created at {}.", m.name, data.name, latch.location);
+        linter.push(
+            Severity::Warning,
+            DiagnosticKind::Latch,
+            instance_path.clone(),
+            m,
+            vec![data.name.clone()],
+            message,
+        );
+    }
+
+    for fsm in m.fsms.borrow().iter() {
+        detect_unreachable_fsm_states(m, fsm, &instance_path, linter);
+    }
+
+    for instance in m.instances.borrow().iter() {
+        let mut instance_path = instance_path.clone();
+        instance_path.push(instance.name.clone());
+        detect_warnings(
+            instance.instantiated_module,
+            instance_path,
+            visited_modules,
+            linter,
+        );
+    }
+}
+
+fn detect_unreachable_fsm_states<'graph>(
+    m: &'graph graph::Module<'graph>,
+    fsm: &graph::FsmData<'graph>,
+    instance_path: &[String],
+    linter: &mut Linter<'graph>,
+) {
+    let mut constant_folder = ConstantFolder::new();
+
+    // A transition can fire unless its condition is always low, or an earlier transition from the same state is always taken
+    let mut can_fire = Vec::with_capacity(fsm.transitions.len());
+    let mut always_leaves = vec![false; fsm.states.len()];
+    for transition in fsm.transitions.iter() {
+        let value = constant_folder
+            .evaluate(transition.condition)
+            .map(|value| value.numeric_value() != 0);
+        let reason = if always_leaves[transition.from] {
+            Some("an earlier transition from the same state is always taken")
+        } else if value == Some(false) {
+            Some("its condition is always low")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            let from = &fsm.states[transition.from];
+            let to = &fsm.states[transition.to];
+            let message = format!("Module \"{}\" contains a state machine called \"{}\" with a transition from state \"{}\" to state \"{}\" that can never be taken, because {}. The condition was created at {}.", m.name, fsm.name, from, to, reason, transition.condition.location);
+            linter.push(
+                Severity::Warning,
+                DiagnosticKind::UnreachableFsmTransition,
+                instance_path.to_vec(),
+                m,
+                vec![fsm.name.clone(), from.clone(), to.clone()],
+                message,
+            );
+        }
+        can_fire.push(reason.is_none());
+        if value == Some(true) {
+            always_leaves[transition.from] = true;
+        }
+    }
+
+    let mut reachable = vec![false; fsm.states.len()];
+    reachable[0] = true;
+    let mut stack = vec![0];
+    while let Some(state) = stack.pop() {
+        for (transition, &can_fire) in fsm.transitions.iter().zip(can_fire.iter()) {
+            if transition.from == state && can_fire && !reachable[transition.to] {
+                reachable[transition.to] = true;
+                stack.push(transition.to);
+            }
+        }
+    }
+    for (state, &reachable) in fsm.states.iter().zip(reachable.iter()) {
+        if !reachable {
+            let message = format!("Module \"{}\" contains a state machine called \"{}\" whose state \"{}\" can't be reached from its initial state \"{}\". The state machine was created at {}.", m.name, fsm.name, state, fsm.states[0], fsm.location);
+            linter.push(
+                Severity::Warning,
+                DiagnosticKind::UnreachableFsmState,
+                instance_path.to_vec(),
+                m,
+                vec![fsm.name.clone(), state.clone()],
+                message,
+            );
+        }
+    }
+}
+
+fn detect_unused_signals<'graph>(
+    m: &'graph graph::Module<'graph>,
+    instance_path: &[String],
+    linter: &mut Linter<'graph>,
+) {
+    let mut roots = Vec::new();
+    roots.extend(m.outputs.borrow().values().copied());
+    for register in m.registers.borrow().iter() {
+        match register.data {
+            graph::SignalData::Reg { data } => roots.extend(*data.next.borrow()),
+            _ => unreachable!(),
+        }
+    }
+    for instance in m.instances.borrow().iter() {
+        roots.extend(instance.driven_inputs.borrow().values().copied());
+    }
+    for mem in m.mems.borrow().iter() {
+        for &(address, value, enable, mask) in mem.write_ports.borrow().iter() {
+            roots.extend(&[address, value, enable]);
+            roots.extend(mask);
+        }
+    }
+    roots.extend(
+        m.assertions
+            .borrow()
+            .iter()
+            .map(|&(condition, _)| condition),
     );
+    roots.extend(m.assumptions.borrow().iter().copied());
+    roots.extend(m.covers.borrow().iter().copied());
+    roots.extend(m.traces.borrow().iter().map(|&(_, signal)| signal));
+
+    let mut used = HashSet::new();
+    for root in roots {
+        root.visit_unvisited(&mut used, &mut |_| ());
+    }
+
+    // Extern modules don't have a body, so their inputs are always considered used
+    if !m.is_extern {
+        for (name, input) in m.inputs.borrow().iter() {
+            if !used.contains(input) {
+                let message = format!(
+                    "Module \"{}\" contains an input called \"{}\" which is never used.",
+                    m.name, name
+                );
+                linter.push(
+                    Severity::Warning,
+                    DiagnosticKind::UnusedInput,
+                    instance_path.to_vec(),
+                    m,
+                    vec![name.clone()],
+                    message,
+                );
+            }
+        }
+    }
+
+    let mut used_instance_outputs = HashSet::new();
+    for signal in used.iter() {
+        if let graph::SignalData::InstanceOutput { instance, name } = signal.data {
+            used_instance_outputs.insert((instance as *const _, name));
+        }
+    }
+    for instance in m.instances.borrow().iter() {
+        let instantiated_module = instance.instantiated_module;
+        for name in instantiated_module.outputs.borrow().keys() {
+            if !used_instance_outputs.contains(&(*instance as *const _, name.as_str())) {
+                let message = format!("Module \"{}\" contains an instance of module \"{}\" called \"{}\" whose output \"{}\" is never used.", m.name, instantiated_module.name, instance.name, name);
+                linter.push(
+                    Severity::Warning,
+                    DiagnosticKind::UnusedInstanceOutput,
+                    instance_path.to_vec(),
+                    m,
+                    vec![instance.name.clone(), name.clone()],
+                    message,
+                );
+            }
+        }
+    }
+
+    for (name, signal) in m.named_signals.borrow().iter() {
+        if !used.contains(signal) {
+            let message = format!(
+                "Module \"{}\" contains a signal named \"{}\" which is never used.",
+                m.name, name
+            );
+            linter.push(
+                Severity::Warning,
+                DiagnosticKind::UnusedNamedSignal,
+                instance_path.to_vec(),
+                m,
+                vec![name.clone()],
+                message,
+            );
+        }
+    }
+}
+
+pub(crate) fn validate_module_hierarchy<'graph>(m: &'graph graph::Module<'graph>) {
+    let mut linter = Linter {
+        root: m,
+        diagnostics: Vec::new(),
+    };
+    detect_errors(m, &mut linter);
+    if let Some(error) = linter.diagnostics.first() {
+        panic!("{}", error.message);
+    }
+}
+
+fn detect_errors<'graph>(m: &'graph graph::Module<'graph>, linter: &mut Linter<'graph>) {
+    let root_frame = ModuleStackFrame {
+        parent: None,
+        module: m,
+    };
+    detect_recursive_definitions(m, &root_frame, linter);
+    // The remaining checks walk the entire hierarchy, which never ends if it's recursive
+    if linter
+        .diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.kind == DiagnosticKind::RecursiveDefinition)
+    {
+        return;
+    }
+    detect_undriven_state_elements(m, &root_frame, linter);
+    detect_mem_errors(m, &root_frame, linter);
     let context_arena = Arena::new();
     let root_context = context_arena.alloc(ModuleContext::new());
-    detect_combinational_loops(m, root_context, &context_arena, m);
+    detect_combinational_loops(m, root_context, &context_arena, linter);
 }
 
 fn detect_recursive_definitions<'graph, 'frame>(
-    m: &graph::Module<'graph>,
+    m: &'graph graph::Module<'graph>,
     module_stack_frame: &ModuleStackFrame<'graph, 'frame>,
-    root: &graph::Module<'graph>,
+    linter: &mut Linter<'graph>,
 ) {
     for instance in m.instances.borrow().iter() {
         let instantiated_module = instance.instantiated_module;
 
+        let mut is_recursive = false;
         if ptr::eq(instantiated_module, m) {
-            panic!("Cannot generate code for module \"{}\" because it has a recursive definition formed by an instance of itself called \"{}\".", m.name, instance.name);
-        }
-
-        let mut frame = module_stack_frame;
-        loop {
-            if ptr::eq(instantiated_module, frame.module) {
-                panic!("Cannot generate code for module \"{}\" because it has a recursive definition formed by an instance of itself called \"{}\" in module \"{}\".", root.name, instance.name, m.name);
-            }
+            let message = format!("Cannot generate code for module \"{}\" because it has a recursive definition formed by an instance of itself called \"{}\". The instance was created at {}.", m.name, instance.name, instance.location);
+            linter.push(
+                Severity::Error,
+                DiagnosticKind::RecursiveDefinition,
+                frame_path(module_stack_frame),
+                m,
+                vec![instance.name.clone()],
+                message,
+            );
+            is_recursive = true;
+        } else {
+            let mut frame = module_stack_frame;
+            loop {
+                if ptr::eq(instantiated_module, frame.module) {
+                    let message = format!("Cannot generate code for module \"{}\" because it has a recursive definition formed by an instance of itself called \"{}\" in module \"{}\". The instance was created at {}.", linter.root.name, instance.name, m.name, instance.location);
+                    linter.push(
+                        Severity::Error,
+                        DiagnosticKind::RecursiveDefinition,
+                        frame_path(module_stack_frame),
+                        m,
+                        vec![instance.name.clone()],
+                        message,
+                    );
+                    is_recursive = true;
+                    break;
+                }
 
-            if let Some((_, parent)) = frame.parent {
-                frame = parent;
-            } else {
-                break;
+                if let Some((_, parent)) = frame.parent {
+                    frame = parent;
+                } else {
+                    break;
+                }
             }
         }
+        if is_recursive {
+            continue;
+        }
 
         for input_name in instantiated_module.inputs.borrow().keys() {
             if !instance.driven_inputs.borrow().contains_key(input_name) {
-                panic!("Cannot generate code for module \"{}\" because module \"{}\" contains an instance of module \"{}\" called \"{}\" whose input \"{}\" is not driven.", root.name, m.name, instantiated_module.name, instance.name, input_name);
+                let message = format!("Cannot generate code for module \"{}\" because module \"{}\" contains an instance of module \"{}\" called \"{}\" whose input \"{}\" is not driven. The instance was created at {}.", linter.root.name, m.name, instantiated_module.name, instance.name, input_name, instance.location);
+                linter.push(
+                    Severity::Error,
+                    DiagnosticKind::UndrivenInput,
+                    frame_path(module_stack_frame),
+                    m,
+                    vec![instance.name.clone(), input_name.clone()],
+                    message,
+                );
             }
         }
 
@@ -81,53 +571,102 @@ fn detect_recursive_definitions<'graph, 'frame>(
                 parent: Some((instance, module_stack_frame)),
                 module: instantiated_module,
             },
-            root,
+            linter,
         );
     }
 }
 
-fn detect_undriven_registers<'graph, 'frame>(
-    m: &graph::Module<'graph>,
+fn detect_undriven_state_elements<'graph, 'frame>(
+    m: &'graph graph::Module<'graph>,
     module_stack_frame: &ModuleStackFrame<'graph, 'frame>,
-    root: &graph::Module<'graph>,
+    linter: &mut Linter<'graph>,
 ) {
     for register in m.registers.borrow().iter() {
         match register.data {
             graph::SignalData::Reg { ref data } => {
                 if data.next.borrow().is_none() {
-                    panic!("Cannot generate code for module \"{}\" because module \"{}\" contains a register called \"{}\" which is not driven.", root.name, m.name, data.name);
+                    let message = format!("Cannot generate code for module \"{}\" because module \"{}\" contains a register called \"{}\" which is not driven. The register was created at {}.", linter.root.name, m.name, data.name, register.location);
+                    linter.push(
+                        Severity::Error,
+                        DiagnosticKind::UndrivenRegister,
+                        frame_path(module_stack_frame),
+                        m,
+                        vec![data.name.clone()],
+                        message,
+                    );
                 }
             }
             _ => unreachable!(),
         }
     }
 
+    for latch in m.latches.borrow().iter() {
+        let data = match latch.data {
+            graph::SignalData::Latch { data } => data,
+            _ => unreachable!(),
+        };
+        let undriven = if data.data.borrow().is_none() {
+            Some("data")
+        } else if data.enable.borrow().is_none() {
+            Some("enable")
+        } else {
+            None
+        };
+        if let Some(undriven) = undriven {
+            let message = format!("Cannot generate code for module \"{}\" because module \"{}\" contains a latch called \"{}\" whose {} is not driven. The latch was created at {}.", linter.root.name, m.name, data.name, undriven, latch.location);
+            linter.push(
+                Severity::Error,
+                DiagnosticKind::UndrivenLatch,
+                frame_path(module_stack_frame),
+                m,
+                vec![data.name.clone()],
+                message,
+            );
+        }
+    }
+
     for instance in m.instances.borrow().iter() {
         let instantiated_module = instance.instantiated_module;
 
-        detect_undriven_registers(
+        detect_undriven_state_elements(
             instantiated_module,
             &ModuleStackFrame {
                 parent: Some((instance, module_stack_frame)),
                 module: instantiated_module,
             },
-            root,
+            linter,
         );
     }
 }
 
 fn detect_mem_errors<'graph, 'frame>(
-    m: &graph::Module<'graph>,
+    m: &'graph graph::Module<'graph>,
     module_stack_frame: &ModuleStackFrame<'graph, 'frame>,
-    root: &graph::Module<'graph>,
+    linter: &mut Linter<'graph>,
 ) {
     for mem in m.mems.borrow().iter() {
-        if mem.read_ports.borrow().is_empty() {
-            panic!("Cannot generate code for module \"{}\" because module \"{}\" contains a memory called \"{}\" which doesn't have any read ports.", root.name, m.name, mem.name);
+        if mem.read_ports.borrow().is_empty() && mem.comb_read_ports.borrow().is_empty() {
+            let message = format!("Cannot generate code for module \"{}\" because module \"{}\" contains a memory called \"{}\" which doesn't have any read ports. The memory was created at {}.", linter.root.name, m.name, mem.name, mem.location);
+            linter.push(
+                Severity::Error,
+                DiagnosticKind::MemWithoutReadPorts,
+                frame_path(module_stack_frame),
+                m,
+                vec![mem.name.clone()],
+                message,
+            );
         }
 
-        if mem.initial_contents.borrow().is_none() && mem.write_port.borrow().is_none() {
-            panic!("Cannot generate code for module \"{}\" because module \"{}\" contains a memory called \"{}\" which doesn't have initial contents or a write port specified. At least one of the two is required.", root.name, m.name, mem.name);
+        if mem.initial_contents.borrow().is_none() && mem.write_ports.borrow().is_empty() {
+            let message = format!("Cannot generate code for module \"{}\" because module \"{}\" contains a memory called \"{}\" which doesn't have initial contents or a write port specified. At least one of the two is required. The memory was created at {}.", linter.root.name, m.name, mem.name, mem.location);
+            linter.push(
+                Severity::Error,
+                DiagnosticKind::MemWithoutContents,
+                frame_path(module_stack_frame),
+                m,
+                vec![mem.name.clone()],
+                message,
+            );
         }
     }
 
@@ -140,7 +679,7 @@ fn detect_mem_errors<'graph, 'frame>(
                 parent: Some((instance, module_stack_frame)),
                 module: instantiated_module,
             },
-            root,
+            linter,
         );
     }
 }
@@ -149,110 +688,534 @@ fn detect_combinational_loops<'graph, 'arena>(
     m: &graph::Module<'graph>,
     context: &'arena ModuleContext<'graph, 'arena>,
     context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
-    root: &graph::Module<'graph>,
+    linter: &mut Linter<'graph>,
 ) {
+    for &latch in m.latches.borrow().iter() {
+        // Tracing starts from the latch's dependencies, since only reaching the latch again forms a loop
+        let mut visited = HashSet::new();
+        for dependency in latch.dependencies() {
+            trace_signal(
+                dependency,
+                context,
+                context_arena,
+                LoopSource::Latch(context, latch),
+                linter,
+                &mut visited,
+            );
+        }
+    }
+
     for instance in m.instances.borrow().iter() {
         let instantiated_module = instance.instantiated_module;
 
+        // Extern modules have no body, so there's nothing inside them to trace
+        if instantiated_module.is_extern {
+            continue;
+        }
+
         let context = context.get_child(instance, context_arena);
 
         for (_, output) in instantiated_module.outputs.borrow().iter() {
-            trace_signal(output, context, context_arena, (context, output), root);
+            trace_signal(
+                output,
+                context,
+                context_arena,
+                LoopSource::InstanceOutput(context, output),
+                linter,
+                &mut HashSet::new(),
+            );
         }
 
-        detect_combinational_loops(instantiated_module, context, context_arena, root);
+        detect_combinational_loops(instantiated_module, context, context_arena, linter);
     }
 }
 
+// The signal whose combinational dependencies are being traced, which forms a loop if it's reached again
+#[derive(Clone, Copy, PartialEq)]
+enum LoopSource<'graph, 'arena> {
+    InstanceOutput(
+        &'arena ModuleContext<'graph, 'arena>,
+        &'graph graph::Signal<'graph>,
+    ),
+    Latch(
+        &'arena ModuleContext<'graph, 'arena>,
+        &'graph graph::Signal<'graph>,
+    ),
+}
+
 fn trace_signal<'graph, 'arena>(
-    signal: &graph::Signal<'graph>,
+    signal: &'graph graph::Signal<'graph>,
     context: &'arena ModuleContext<'graph, 'arena>,
     context_arena: &'arena Arena<ModuleContext<'graph, 'arena>>,
-    source_output: (
+    source: LoopSource<'graph, 'arena>,
+    linter: &mut Linter<'graph>,
+    visited: &mut HashSet<(
         &'arena ModuleContext<'graph, 'arena>,
         &'graph graph::Signal<'graph>,
-    ),
-    root: &graph::Module<'graph>,
+    )>,
 ) {
-    match signal.data {
-        graph::SignalData::Lit { .. } => (),
-
-        graph::SignalData::Input { ref name, .. } => {
-            if let Some((instance, parent)) = context.instance_and_parent {
-                trace_signal(
-                    instance.driven_inputs.borrow()[name],
-                    parent,
-                    context_arena,
-                    source_output,
-                    root,
+    // An explicit stack is used instead of recursion so that very deep graphs can't overflow the call stack
+    let mut stack = vec![(signal, context)];
+    while let Some((signal, context)) = stack.pop() {
+        // Any loop through this signal will already have been found the first time it was traced
+        if !visited.insert((context, signal)) {
+            continue;
+        }
+
+        match signal.data {
+            graph::SignalData::Lit { .. }
+            | graph::SignalData::Reg { .. }
+            | graph::SignalData::MemReadPortOutput { .. } => (),
+
+            graph::SignalData::Input { name, .. } => {
+                if let Some((instance, parent)) = context.instance_and_parent {
+                    // Undriven inputs are reported separately
+                    if let Some(&input) = instance.driven_inputs.borrow().get(name) {
+                        stack.push((input, parent));
+                    }
+                }
+            }
+
+            graph::SignalData::InstanceOutput { instance, name } => {
+                let instantiated_module = instance.instantiated_module;
+                let output = instantiated_module.outputs.borrow()[name];
+                let context = context.get_child(instance, context_arena);
+                if source == LoopSource::InstanceOutput(context, output) {
+                    let message = format!("Cannot generate code for module \"{}\" because module \"{}\" contains an output called \"{}\" which forms a combinational loop with itself. The signal driving this output was created at {}.", linter.root.name, instantiated_module.name, name, output.location);
+                    linter.push(
+                        Severity::Error,
+                        DiagnosticKind::CombinationalLoop,
+                        context_path(context),
+                        instantiated_module,
+                        vec![name.to_string()],
+                        message,
+                    );
+                    continue;
+                }
+                stack.push((output, context));
+            }
+
+            graph::SignalData::Latch { data } if source == LoopSource::Latch(context, signal) => {
+                let message = format!("Cannot generate code for module \"{}\" because module \"{}\" contains a latch called \"{}\" which forms a combinational loop with itself. The latch was created at {}.", linter.root.name, signal.module.name, data.name, signal.location);
+                linter.push(
+                    Severity::Error,
+                    DiagnosticKind::CombinationalLoop,
+                    context_path(context),
+                    signal.module,
+                    vec![data.name.clone()],
+                    message,
                 );
             }
-        }
 
-        graph::SignalData::Reg { .. } => (),
+            graph::SignalData::MemCombReadPortOutput { address, .. } => {
+                stack.push((address, context));
+            }
 
-        graph::SignalData::UnOp { ref source, .. } => {
-            trace_signal(source, context, context_arena, source_output, root);
-        }
-        graph::SignalData::SimpleBinOp {
-            ref lhs, ref rhs, ..
-        } => {
-            trace_signal(lhs, context, context_arena, source_output, root);
-            trace_signal(rhs, context, context_arena, source_output, root);
-        }
-        graph::SignalData::AdditiveBinOp {
-            ref lhs, ref rhs, ..
-        } => {
-            trace_signal(lhs, context, context_arena, source_output, root);
-            trace_signal(rhs, context, context_arena, source_output, root);
-        }
-        graph::SignalData::ComparisonBinOp {
-            ref lhs, ref rhs, ..
-        } => {
-            trace_signal(lhs, context, context_arena, source_output, root);
-            trace_signal(rhs, context, context_arena, source_output, root);
-        }
-        graph::SignalData::ShiftBinOp {
-            ref lhs, ref rhs, ..
-        } => {
-            trace_signal(lhs, context, context_arena, source_output, root);
-            trace_signal(rhs, context, context_arena, source_output, root);
+            // Operands are pushed in reverse so that they're traced in order
+            _ => stack.extend(
+                signal
+                    .dependencies()
+                    .into_iter()
+                    .rev()
+                    .map(|dependency| (dependency, context)),
+            ),
         }
+    }
+}
 
-        graph::SignalData::Bits { ref source, .. } => {
-            trace_signal(source, context, context_arena, source_output, root);
-        }
+#[cfg(test)]
+mod tests {
+    use crate::*;
 
-        graph::SignalData::Repeat { ref source, .. } => {
-            trace_signal(source, context, context_arena, source_output, root);
-        }
-        graph::SignalData::Concat {
-            ref lhs, ref rhs, ..
-        } => {
-            trace_signal(lhs, context, context_arena, source_output, root);
-            trace_signal(rhs, context, context_arena, source_output, root);
-        }
+    use std::panic::{self, AssertUnwindSafe};
 
-        graph::SignalData::Mux {
-            ref cond,
-            ref when_true,
-            ref when_false,
-        } => {
-            trace_signal(cond, context, context_arena, source_output, root);
-            trace_signal(when_true, context, context_arena, source_output, root);
-            trace_signal(when_false, context, context_arena, source_output, root);
+    // Runs `f`, which is expected to panic, and returns its panic message
+    fn panic_message<F: FnOnce()>(f: F) -> String {
+        let payload = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+        match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => payload.downcast_ref::<&str>().unwrap().to_string(),
         }
+    }
 
-        graph::SignalData::InstanceOutput { instance, ref name } => {
-            let instantiated_module = instance.instantiated_module;
-            let output = instantiated_module.outputs.borrow()[name];
-            let context = context.get_child(instance, context_arena);
-            if context == source_output.0 && output == source_output.1 {
-                panic!("Cannot generate code for module \"{}\" because module \"{}\" contains an output called \"{}\" which forms a combinational loop with itself.", root.name, instantiated_module.name, name);
-            }
-            trace_signal(output, context, context_arena, source_output, root);
+    #[test]
+    fn constant_cover_warnings() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.cover(inner.low());
+        inner.cover(inner.input("i", 1));
+        inner.output("o", inner.input("i", 1));
+
+        let a = c.module("A");
+        a.cover(a.lit(true, 1));
+        let i = a.input("i", 1);
+        let inner1 = a.instance("inner1", "Inner");
+        inner1.drive_input("i", i);
+        let inner2 = a.instance("inner2", "Inner");
+        inner2.drive_input("i", i);
+        a.output("o", inner1.output("o") & inner2.output("o"));
+
+        assert_eq!(
+            validation::warnings(a),
+            vec![
+                "Module \"A\" contains a cover point (index 0) whose condition is a constant literal, so it's either always or never hit.",
+                "Module \"Inner\" contains a cover point (index 0) whose condition is a constant literal, so it's either always or never hit.",
+            ]
+        );
+    }
+
+    #[test]
+    fn undriven_register_location() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let line = line!() + 1;
+        let r = m.reg("r", 1);
+        m.output("o", r.value);
+
+        let message = panic_message(|| sim::generate(m, Vec::new()).unwrap());
+        assert!(message.contains(&format!(
+            "The register was created at {}:{}:",
+            file!(),
+            line
+        )));
+    }
+
+    #[test]
+    fn undriven_instance_input_location() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("i", 1));
+
+        let m = c.module("A");
+        let line = line!() + 1;
+        let inner = m.instance("inner", "Inner");
+        m.output("o", inner.output("o"));
+
+        let message = panic_message(|| verilog::generate(m, Vec::new()).unwrap());
+        assert!(message.contains(&format!(
+            "The instance was created at {}:{}:",
+            file!(),
+            line
+        )));
+    }
+
+    #[test]
+    fn mem_error_location() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let line = line!() + 1;
+        let mem = m.mem("mem", 1, 1);
+        mem.initial_contents(&[false, true]);
+        m.output("o", m.input("i", 1));
+
+        let message = panic_message(|| verilog::generate(m, Vec::new()).unwrap());
+        assert!(message.contains(&format!("The memory was created at {}:{}:", file!(), line)));
+    }
+
+    #[test]
+    fn bit_width_mismatch_location() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let lhs_line = line!() + 1;
+        let lhs = m.input("lhs", 3);
+        let rhs_line = line!() + 1;
+        let rhs = m.input("rhs", 5);
+
+        let message = panic_message(|| {
+            let _ = lhs & rhs;
+        });
+        assert!(message.contains(&format!(
+            "The signals were created at {}:{}:",
+            file!(),
+            lhs_line
+        )));
+        assert!(message.contains(&format!(" and {}:{}:", file!(), rhs_line)));
+    }
+
+    #[test]
+    fn lint_reports_all_errors() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let r = inner.reg("r", 1);
+        inner.output("o", r.value & inner.input("i", 1));
+
+        let m = c.module("Outer");
+        let inner = m.instance("inner", "Inner");
+        let mem = m.mem("mem", 1, 1);
+        mem.initial_contents(&[false, true]);
+        m.output("o", inner.output("o"));
+
+        let diagnostics = validation::lint(m);
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == validation::Severity::Error));
+
+        assert_eq!(
+            diagnostics[0].kind,
+            validation::DiagnosticKind::UndrivenInput
+        );
+        assert!(diagnostics[0].instance_path.is_empty());
+        assert_eq!(diagnostics[0].module, "Outer");
+        assert_eq!(diagnostics[0].names, vec!["inner", "i"]);
+
+        assert_eq!(
+            diagnostics[1].kind,
+            validation::DiagnosticKind::UndrivenRegister
+        );
+        assert_eq!(diagnostics[1].instance_path, vec!["inner"]);
+        assert_eq!(diagnostics[1].module, "Inner");
+        assert_eq!(diagnostics[1].names, vec!["r"]);
+
+        assert_eq!(
+            diagnostics[2].kind,
+            validation::DiagnosticKind::MemWithoutReadPorts
+        );
+        assert!(diagnostics[2].instance_path.is_empty());
+        assert_eq!(diagnostics[2].module, "Outer");
+        assert_eq!(diagnostics[2].names, vec!["mem"]);
+
+        // Code generation still panics with the first error's message
+        let message = panic_message(|| verilog::generate(m, Vec::new()).unwrap());
+        assert_eq!(message, diagnostics[0].message);
+    }
+
+    #[test]
+    fn lint_combinational_loop() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("i", 1));
+
+        let m = c.module("Outer");
+        let a = m.instance("a", "Inner");
+        a.drive_input("i", a.output("o"));
+        m.output("o", a.output("o"));
+        m.cover(m.low());
+
+        let diagnostics = validation::lint(m);
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(diagnostics[0].severity, validation::Severity::Error);
+        assert_eq!(
+            diagnostics[0].kind,
+            validation::DiagnosticKind::CombinationalLoop
+        );
+        assert_eq!(diagnostics[0].instance_path, vec!["a"]);
+        assert_eq!(diagnostics[0].module, "Inner");
+        assert_eq!(diagnostics[0].names, vec!["o"]);
+
+        assert_eq!(diagnostics[1].severity, validation::Severity::Warning);
+        assert_eq!(
+            diagnostics[1].kind,
+            validation::DiagnosticKind::ConstantCover
+        );
+        assert_eq!(diagnostics[1].names, vec!["0"]);
+    }
+
+    #[test]
+    fn lint_latches() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let l = inner.latch("l", 4);
+        l.drive_data(l.value + inner.lit(1u32, 4));
+        l.drive_enable(inner.input("en", 1));
+        inner.output("o", l.value);
+
+        let m = c.module("Outer");
+        let undriven = m.latch("undriven", 4);
+        undriven.drive_data(m.input("data", 4));
+        let a = m.instance("a", "Inner");
+        a.drive_input("en", m.input("en", 1));
+        m.output("o", a.output("o") ^ undriven.value);
+
+        let diagnostics = validation::lint(m);
+        assert_eq!(diagnostics.len(), 4);
+
+        assert_eq!(diagnostics[0].severity, validation::Severity::Error);
+        assert_eq!(
+            diagnostics[0].kind,
+            validation::DiagnosticKind::UndrivenLatch
+        );
+        assert!(diagnostics[0].instance_path.is_empty());
+        assert_eq!(diagnostics[0].names, vec!["undriven"]);
+        assert!(diagnostics[0]
+            .message
+            .contains("contains a latch called \"undriven\" whose enable is not driven."));
+
+        assert_eq!(diagnostics[1].severity, validation::Severity::Error);
+        assert_eq!(
+            diagnostics[1].kind,
+            validation::DiagnosticKind::CombinationalLoop
+        );
+        assert_eq!(diagnostics[1].instance_path, vec!["a"]);
+        assert_eq!(diagnostics[1].module, "Inner");
+        assert_eq!(diagnostics[1].names, vec!["l"]);
+
+        assert_eq!(diagnostics[2].severity, validation::Severity::Warning);
+        assert_eq!(diagnostics[2].kind, validation::DiagnosticKind::Latch);
+        assert_eq!(diagnostics[2].module, "Outer");
+        assert_eq!(diagnostics[2].names, vec!["undriven"]);
+
+        assert_eq!(diagnostics[3].kind, validation::DiagnosticKind::Latch);
+        assert_eq!(diagnostics[3].instance_path, vec!["a"]);
+        assert_eq!(diagnostics[3].names, vec!["l"]);
+    }
+
+    #[test]
+    fn lint_fsms() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let go = m.input("go", 1);
+        let fsm = m.fsm(
+            "fsm",
+            &["Idle", "Run", "Stop", "Orphan", "Hidden"],
+            FsmEncoding::Binary,
+        );
+        fsm.transition("Idle", go, "Run");
+        // Constant after folding, even though it's not a literal
+        fsm.transition("Run", m.lit(1u32, 2).eq(m.lit(1u32, 2)), "Stop");
+        fsm.transition("Run", go, "Idle");
+        fsm.transition("Stop", m.lit(3u32, 2).ne(m.lit(3u32, 2)), "Hidden");
+        fsm.transition("Stop", go, "Idle");
+        fsm.transition("Orphan", go, "Idle");
+        m.output("o", fsm.state());
+        fsm.build();
+
+        let diagnostics = validation::lint(m);
+        assert_eq!(diagnostics.len(), 4);
+
+        for diagnostic in diagnostics.iter() {
+            assert_eq!(diagnostic.severity, validation::Severity::Warning);
+            assert_eq!(diagnostic.module, "A");
         }
 
-        graph::SignalData::MemReadPortOutput { .. } => (),
+        assert_eq!(
+            diagnostics[0].kind,
+            validation::DiagnosticKind::UnreachableFsmTransition
+        );
+        assert_eq!(diagnostics[0].names, vec!["fsm", "Run", "Idle"]);
+        assert!(diagnostics[0].message.contains("contains a state machine called \"fsm\" with a transition from state \"Run\" to state \"Idle\" that can never be taken, because an earlier transition from the same state is always taken."));
+
+        assert_eq!(
+            diagnostics[1].kind,
+            validation::DiagnosticKind::UnreachableFsmTransition
+        );
+        assert_eq!(diagnostics[1].names, vec!["fsm", "Stop", "Hidden"]);
+        assert!(diagnostics[1]
+            .message
+            .contains("that can never be taken, because its condition is always low."));
+
+        assert_eq!(
+            diagnostics[2].kind,
+            validation::DiagnosticKind::UnreachableFsmState
+        );
+        assert_eq!(diagnostics[2].names, vec!["fsm", "Orphan"]);
+        assert!(diagnostics[2].message.contains("contains a state machine called \"fsm\" whose state \"Orphan\" can't be reached from its initial state \"Idle\"."));
+
+        assert_eq!(
+            diagnostics[3].kind,
+            validation::DiagnosticKind::UnreachableFsmState
+        );
+        assert_eq!(diagnostics[3].names, vec!["fsm", "Hidden"]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate code for module \"A\" because module \"A\" contains a latch called \"l\" which forms a combinational loop with itself."
+    )]
+    fn latch_combinational_loop_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let l = m.latch("l", 1);
+        l.drive_data(m.input("data", 1));
+        l.drive_enable(!l.value);
+        m.output("o", l.value);
+
+        // Panic
+        sim::generate(m, Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn unused_signal_warnings() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        let i = inner.input("i", 1);
+        inner.output("a", i);
+        inner.output("b", !i);
+
+        let m = c.module("Outer");
+        let used = m.input("used", 1);
+        let _ = m.input("unused", 1);
+        let inner1 = m.instance("inner1", "Inner");
+        inner1.drive_input("i", used);
+        let _ = (used & inner1.output("b")).name("dangling");
+        m.output("o", inner1.output("a").name("driven"));
+
+        let diagnostics = validation::lint(m);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == validation::Severity::Warning));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.kind, diagnostic.names.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    validation::DiagnosticKind::UnusedInput,
+                    vec!["unused".to_string()]
+                ),
+                (
+                    validation::DiagnosticKind::UnusedInstanceOutput,
+                    vec!["inner1".to_string(), "b".to_string()]
+                ),
+                (
+                    validation::DiagnosticKind::UnusedNamedSignal,
+                    vec!["dangling".to_string()]
+                ),
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "Module \"Outer\" contains an input called \"unused\" which is never used."
+        );
+
+        // Warnings don't prevent code generation
+        verilog::generate(m, Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn unused_signal_warnings_respect_sequential_and_verification_readers() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let r = m.reg("r", 1);
+        r.drive_next(m.input("next", 1));
+        m.assert(m.input("asserted", 1), "asserted");
+        m.trace("traced", m.input("traced", 1));
+        let mem = m.mem("mem", 1, 1);
+        mem.write_port(m.input("addr", 1), m.input("value", 1), m.high());
+        m.output("o", mem.read_port_comb(m.low()));
+
+        // An extern module's inputs are never reported as unused
+        let e = c.extern_module("E");
+        e.input("i", 1);
+        e.extern_output("o", 1);
+        let e1 = m.instance("e1", "E");
+        e1.drive_input("i", r.value);
+        m.output("e", e1.output("o"));
+
+        assert_eq!(validation::lint(m), Vec::new());
     }
 }
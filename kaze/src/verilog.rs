@@ -8,74 +8,418 @@ use compiler::*;
 use ir::*;
 use module_decls::*;
 
-use crate::code_writer;
+use crate::analysis::*;
+use crate::code_writer::{self, CodeStyle};
 use crate::graph;
+use crate::index_map::*;
 use crate::validation::*;
 
-use std::collections::HashMap;
-use std::io::{Result, Write};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{BufWriter, Error, ErrorKind, Result, Write};
+use std::path::{Component, Path};
 
+/// Options for Verilog code generation, used with [`generate_with_options`].
+///
+/// [`generate_with_options`]: ./fn.generate_with_options.html
+#[derive(Clone, Debug, Default)]
+pub struct GenerationOptions {
+    /// Omit registers, memories, and memory read ports that can't affect the generated `Module`'s outputs, instances, or annotations (see [`analysis::dead_signals`]).
+    ///
+    /// Dead combinational logic is never emitted, regardless of this option.
+    ///
+    /// [`analysis::dead_signals`]: ../analysis/fn.dead_signals.html
+    pub eliminate_dead_logic: bool,
+
+    /// If specified, each memory array is preceded by a `(* ram_style = "..." *)` attribute with this value (for example, `"block"` or `"distributed"`), which tells synthesis tools how to implement it.
+    /// Memories with their own `ram_style` attribute (see [`Mem::attribute`]) keep that value instead.
+    ///
+    /// [`Mem::attribute`]: ../struct.Mem.html#method.attribute
+    pub ram_style: Option<String>,
+
+    /// Annotate the generated code with comments that relate it back to the graph it was generated from.
+    ///
+    /// Each generated assignment gets a trailing comment naming the output, register, instance input, memory port, or annotation it was generated for.
+    /// Intermediate wires and registers are preceded by a comment with the name of the `Signal` they were generated from (if it was given one with [`Signal::name`]) and the source location where it was created.
+    ///
+    /// Comments are omitted by default, so that generated code doesn't change when the code that builds the graph moves around.
+    ///
+    /// [`Signal::name`]: ../struct.Signal.html#method.name
+    pub comments: bool,
+
+    /// Assign intermediate combinational logic procedurally in an `always_comb` block instead of with `assign` statements.
+    ///
+    /// Intermediate values are declared as `logic`, and multiplexers are lowered to `if`/`else` statements, with chains of multiplexers (such as those built with [`if_`]) becoming a single `if`/`else if` chain.
+    /// Outputs, instance inputs, and other module-level nets are still driven by `assign` statements.
+    /// [`Latch`]es are written in `always_latch` blocks instead of `always @(*)` blocks.
+    ///
+    /// [`if_`]: ../fn.if_.html
+    /// [`Latch`]: ../struct.Latch.html
+    pub always_comb: bool,
+
+    /// The name of the clock port of each generated module (and each instantiated module) that doesn't specify its own with [`Module::clock_port_name`], or `clk` if not specified.
+    ///
+    /// [`Module::clock_port_name`]: ../struct.Module.html#method.clock_port_name
+    pub clock_port_name: Option<String>,
+
+    /// The name of the active-low reset port of each generated module (and each instantiated module) that doesn't specify its own with [`Module::reset_port_name`], or `reset_n` if not specified.
+    ///
+    /// [`Module::reset_port_name`]: ../struct.Module.html#method.reset_port_name
+    pub reset_port_name: Option<String>,
+
+    /// How the generated code is formatted (see [`CodeStyle`]).
+    ///
+    /// [`CodeStyle`]: ../struct.CodeStyle.html
+    pub code_style: CodeStyle,
+
+    /// If specified, literals wider than this many bits are declared as `localparam`s at the top of the generated module (called `__const_0`, `__const_1`, and so on) and referred to by name, instead of appearing inline in expressions.
+    ///
+    /// Constants created by [`Module::constant`] are always declared as `localparam`s with their own names, regardless of this option.
+    ///
+    /// [`Module::constant`]: ../struct.Module.html#method.constant
+    pub localparam_bit_width_threshold: Option<u32>,
+
+    /// How registers' values are initialized in simulation, before they're first reset or clocked (see [`UninitializedRegisters`]).
+    ///
+    /// [`UninitializedRegisters`]: ./enum.UninitializedRegisters.html
+    pub uninitialized_registers: UninitializedRegisters,
+}
+
+/// How generated Verilog initializes registers' values, used with [`GenerationOptions`].
+///
+/// Registers are normally only assigned by their `always` blocks, so in simulation they're `X` until they're first reset (if they have a default value) or clocked.
+/// Many FPGAs initialize all registers in hardware instead (typically to zero), which can hide bugs that depend on a register's value before it's reset.
+/// The other variants emit an `initial` block that assigns every register in the module, which makes simulation behave more like such hardware; [`Zero`](#variant.Zero) matches the generated Rust simulator's default (see [`sim::UninitializedRegisters`]).
+/// Registers with default values are still assigned those values when reset, regardless of this option.
+///
+/// [`GenerationOptions`]: ./struct.GenerationOptions.html
+/// [`sim::UninitializedRegisters`]: ../sim/enum.UninitializedRegisters.html
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum UninitializedRegisters {
+    /// Registers aren't initialized, so they start out as `X` in simulation (the default).
+    #[default]
+    Undefined,
+    /// All registers start out as zero.
+    Zero,
+    /// Each register starts out with a pseudo-random constant, determined by `seed` and the register's position in its module.
+    ///
+    /// The seed is noted in a comment at the top of each generated module, so a simulation run can be reproduced from its generated code.
+    /// The values are chosen when the code is generated, so different seeds (rather than different simulation runs) are needed to try different values.
+    Random {
+        /// The seed the values are derived from.
+        seed: u64,
+    },
+}
+
+impl GenerationOptions {
+    fn clock(&self) -> &str {
+        self.clock_port_name.as_deref().unwrap_or("clk")
+    }
+
+    fn reset(&self) -> &str {
+        self.reset_port_name.as_deref().unwrap_or("reset_n")
+    }
+}
+
+/// Generates a Verilog module for the `Module` `m` using the default [`GenerationOptions`] and writes it to `w`.
+///
+/// See [`generate_with_options`] for details.
+///
+/// [`GenerationOptions`]: ./struct.GenerationOptions.html
+/// [`generate_with_options`]: ./fn.generate_with_options.html
 // TODO: Note that mutable writer reference can be passed, see https://rust-lang.github.io/api-guidelines/interoperability.html#c-rw-value
 pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
+    generate_with_options(m, &GenerationOptions::default(), w)
+}
+
+/// Generates a Verilog module for the `Module` `m` according to `options` and writes it to `w`.
+///
+/// Only `m` itself is generated; instantiated `Module`s must be generated separately (see [`generate_hierarchy_with_options`]).
+///
+/// The generated module only has `reset_n` and `clk` ports (before its inputs and outputs) if `m` or any `Module` in its hierarchy contains registers, memories, or annotations (see [`Module::assert`], [`Module::assume`], [`Module::cover`], and [`Module::trace`]).
+/// Purely combinational modules don't need them, and instances of such modules aren't connected to them.
+/// [`Latch`]es aren't clocked, so they don't need these ports either; each one is written as an `always @(*)` block that only assigns the latch while its enable signal is high.
+/// These ports can be renamed for each `Module` with [`Module::clock_port_name`] and [`Module::reset_port_name`], or for all `Module`s that don't specify their own names with [`GenerationOptions::clock_port_name`] and [`GenerationOptions::reset_port_name`]; instances are connected to their `Module`'s ports using the names configured for that `Module`.
+///
+/// Each combinational node is emitted at most once per module, as an intermediate wire (called `__temp_0`, `__temp_1`, and so on, in a deterministic order) that all of its consumers refer to by name, so shared subexpressions aren't duplicated no matter how many signals use them.
+///
+/// Each input is declared with exactly its bit width, so its value is always the low bits of whatever drives it: SystemVerilog truncates a wider driver to the port's width and zero-extends a narrower one.
+/// This matches the generated Rust simulator, which ignores any bits above an input's width (see [`sim::GenerationOptions::check_inputs`]), so the same stimulus produces the same outputs in both.
+/// Connections between kaze `Module`s can't have mismatched widths, as [`Instance::drive_input`] checks them when the graph is built; mismatches can only come from hand-written code that instantiates the generated module, which SystemVerilog lint tools (such as Verilator's `WIDTH` warnings) can flag.
+///
+/// [`Instance::drive_input`]: ../struct.Instance.html#method.drive_input
+/// [`sim::GenerationOptions::check_inputs`]: ../sim/struct.GenerationOptions.html#structfield.check_inputs
+/// [`GenerationOptions::clock_port_name`]: ./struct.GenerationOptions.html#structfield.clock_port_name
+/// [`GenerationOptions::reset_port_name`]: ./struct.GenerationOptions.html#structfield.reset_port_name
+/// [`generate_hierarchy_with_options`]: ./fn.generate_hierarchy_with_options.html
+/// [`Latch`]: ../struct.Latch.html
+/// [`Module::assert`]: ../struct.Module.html#method.assert
+/// [`Module::assume`]: ../struct.Module.html#method.assume
+/// [`Module::clock_port_name`]: ../struct.Module.html#method.clock_port_name
+/// [`Module::cover`]: ../struct.Module.html#method.cover
+/// [`Module::reset_port_name`]: ../struct.Module.html#method.reset_port_name
+/// [`Module::trace`]: ../struct.Module.html#method.trace
+/// [`Context::extern_module`]: ../struct.Context.html#method.extern_module
+///
+/// # Panics
+///
+/// Panics if `m`'s hierarchy contains errors (for example, undriven registers or combinational loops), if `m` is an extern module (see [`Context::extern_module`]), or if the clock and reset port names of `m` (or of a `Module` it instantiates) are the same as each other or as one of its input or output names.
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("MyModule");
+/// let unused = m.reg("unused", 8);
+/// unused.drive_next(unused.value);
+/// m.output("o", m.input("i", 1));
+///
+/// let options = verilog::GenerationOptions {
+///     eliminate_dead_logic: true,
+///     ..Default::default()
+/// };
+/// let mut w = Vec::new();
+/// verilog::generate_with_options(m, &options, &mut w).unwrap();
+/// assert!(!std::str::from_utf8(&w).unwrap().contains("unused"));
+/// ```
+pub fn generate_with_options<'a, W: Write>(
+    m: &'a graph::Module<'a>,
+    options: &GenerationOptions,
+    w: W,
+) -> Result<()> {
+    if m.is_extern {
+        panic!("Cannot generate Verilog code for module \"{}\" because it's an extern module, which must be defined outside of kaze.", m.name);
+    }
+
     validate_module_hierarchy(m);
 
-    let mut instances = HashMap::new();
+    let live_signals = if options.eliminate_dead_logic {
+        Some(live_signals(m))
+    } else {
+        None
+    };
+    let is_live = |signal: &'a graph::Signal<'a>| {
+        live_signals
+            .as_ref()
+            .is_none_or(|live_signals| live_signals.contains(&signal))
+    };
+
+    // Every declared name is uniquified against the module's ports and all previously-declared names, so that generated names can't collide with user-specified ones
+    let has_clock = uses_clock(m);
+    let (clock, reset) = if has_clock {
+        clock_reset_port_names(m, options.clock(), options.reset())
+    } else {
+        Default::default()
+    };
+    let mut names = UsedNames::new();
+    if has_clock {
+        names.reserve(&reset);
+        names.reserve(&clock);
+    }
+    for name in m.inputs.borrow().keys().chain(m.outputs.borrow().keys()) {
+        names.reserve(name);
+    }
+
+    // Instance arrays whose inputs can all be expressed in terms of the lane index are emitted as a single generate loop; the rest are emitted as individual instances
+    let mut instance_arrays = Vec::new();
+    let mut instance_array_lanes = IndexMap::new();
+    for (array_name, array_instances) in m.instance_arrays.borrow().iter() {
+        // Parameters can't vary per lane in a generate loop
+        let parameters = array_instances[0].parameters.borrow();
+        if array_instances
+            .iter()
+            .any(|instance| *instance.parameters.borrow() != *parameters)
+        {
+            continue;
+        }
+        let inputs = array_instances[0]
+            .instantiated_module
+            .inputs
+            .borrow()
+            .keys()
+            .map(|name| {
+                instance_array_input(array_instances, name).map(|input| (name.clone(), input))
+            })
+            .collect::<Option<Vec<_>>>();
+        let inputs = match inputs {
+            Some(inputs) => inputs,
+            _ => continue,
+        };
+
+        let name = names.unique(array_name);
+        let index_name = names.unique(&format!("__{}_index", array_name));
+
+        let mut input_names = BTreeMap::new();
+        for (input_name, input) in inputs {
+            let decl_name = names.unique(&format!("__{}_input_{}", array_name, input_name));
+            input_names.insert(input_name, (decl_name, input));
+        }
+
+        let mut output_names = BTreeMap::new();
+        for (output_name, _) in array_instances[0]
+            .instantiated_module
+            .outputs
+            .borrow()
+            .iter()
+        {
+            output_names.insert(
+                output_name.clone(),
+                names.unique(&format!("__{}_output_{}", array_name, output_name)),
+            );
+        }
+
+        for (lane, instance) in array_instances.iter().enumerate() {
+            instance_array_lanes.insert(*instance, (instance_arrays.len(), lane as u32));
+        }
+        instance_arrays.push(InstanceArrayDecls {
+            instances: array_instances.clone(),
+            name,
+            index_name,
+            input_names,
+            output_names,
+        });
+    }
+
+    // Outputs that just forward an instance's output are connected to the instance directly instead of through an intermediate wire.
+    //  If several outputs forward the same instance output, only the first one is connected directly, and the rest are assigned from it.
+    let mut forwarded_outputs = HashMap::new();
+    for (name, output) in m.outputs.borrow().iter() {
+        if let graph::SignalData::InstanceOutput {
+            instance,
+            name: output_name,
+        } = output.data
+        {
+            if !instance_array_lanes.contains_key(&instance) {
+                forwarded_outputs
+                    .entry((instance as *const _, output_name))
+                    .or_insert_with(|| name.clone());
+            }
+        }
+    }
+    let directly_connected_outputs = forwarded_outputs.values().cloned().collect::<HashSet<_>>();
+
+    let mut instances = IndexMap::new();
     for instance in m.instances.borrow().iter() {
-        let mut input_names = HashMap::new();
-        for (name, _) in instance.instantiated_module.inputs.borrow().iter() {
-            input_names.insert(name.clone(), format!("__{}_input_{}", instance.name, name));
+        if instance_array_lanes.contains_key(instance) {
+            continue;
+        }
+
+        let name = names.unique(&instance.name);
+
+        let mut input_names = BTreeMap::new();
+        for (input_name, _) in instance.instantiated_module.inputs.borrow().iter() {
+            input_names.insert(
+                input_name.clone(),
+                names.unique(&format!("__{}_input_{}", instance.name, input_name)),
+            );
         }
 
-        let mut output_names = HashMap::new();
-        for (name, _) in instance.instantiated_module.outputs.borrow().iter() {
-            output_names.insert(name.clone(), format!("__{}_output_{}", instance.name, name));
+        let mut output_names = BTreeMap::new();
+        let mut forwarded_output_names = HashSet::new();
+        for (output_name, _) in instance.instantiated_module.outputs.borrow().iter() {
+            let decl_name =
+                match forwarded_outputs.get(&(*instance as *const _, output_name.as_str())) {
+                    Some(port_name) => {
+                        forwarded_output_names.insert(output_name.clone());
+                        identifier(port_name)
+                    }
+                    _ => names.unique(&format!("__{}_output_{}", instance.name, output_name)),
+                };
+            output_names.insert(output_name.clone(), decl_name);
         }
 
         instances.insert(
             *instance,
             InstanceDecls {
+                name,
                 input_names,
                 output_names,
+                forwarded_output_names,
             },
         );
     }
 
-    let mut mems = HashMap::new();
+    let mut mems = IndexMap::new();
     for mem in m.mems.borrow().iter() {
+        let live_read_ports = m
+            .signals
+            .borrow()
+            .iter()
+            .filter(|signal| is_live(signal))
+            .filter_map(|signal| match signal.data {
+                graph::SignalData::MemReadPortOutput {
+                    mem: read_mem,
+                    address,
+                    enable,
+                } if read_mem == *mem => Some((address, enable)),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        let has_live_comb_read_ports = m.signals.borrow().iter().any(|signal| match signal.data {
+            graph::SignalData::MemCombReadPortOutput { mem: read_mem, .. } => {
+                read_mem == *mem && is_live(signal)
+            }
+            _ => false,
+        });
+        if live_read_ports.is_empty() && !has_live_comb_read_ports {
+            continue;
+        }
+
+        let name = names.unique(&mem.name);
         let mem_name = format!("__mem_{}", mem.name);
-        let mut read_signal_names = HashMap::new();
+        let mut read_signal_names = IndexMap::new();
         for (index, (address, enable)) in mem.read_ports.borrow().iter().enumerate() {
+            if !live_read_ports.contains(&(*address, *enable)) {
+                continue;
+            }
             let name_prefix = format!("{}_read_port_{}_", mem_name, index);
             read_signal_names.insert(
                 (*address, *enable),
                 ReadSignalNames {
-                    address_name: format!("{}address", name_prefix),
-                    enable_name: format!("{}enable", name_prefix),
-                    value_name: format!("{}value", name_prefix),
+                    address_name: names.unique(&format!("{}address", name_prefix)),
+                    enable_name: names.unique(&format!("{}enable", name_prefix)),
+                    value_name: names.unique(&format!("{}value", name_prefix)),
                 },
             );
         }
-        let name_prefix = format!("{}_write_port_", mem_name);
-        let write_address_name = format!("{}address", name_prefix);
-        let write_value_name = format!("{}value", name_prefix);
-        let write_enable_name = format!("{}enable", name_prefix);
+        let write_signal_names = mem
+            .write_ports
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(index, (_, _, _, mask))| {
+                let name_prefix = format!("{}_write_port_{}_", mem_name, index);
+                WriteSignalNames {
+                    address_name: names.unique(&format!("{}address", name_prefix)),
+                    value_name: names.unique(&format!("{}value", name_prefix)),
+                    enable_name: names.unique(&format!("{}enable", name_prefix)),
+                    mask_name: mask.map(|_| names.unique(&format!("{}mask", name_prefix))),
+                }
+            })
+            .collect();
         mems.insert(
             *mem,
             MemDecls {
+                name,
                 read_signal_names,
-                write_address_name,
-                write_value_name,
-                write_enable_name,
+                write_signal_names,
             },
         );
     }
 
-    let mut regs = HashMap::new();
+    let mut regs = IndexMap::new();
     for reg in m.registers.borrow().iter() {
+        if !is_live(reg) {
+            continue;
+        }
         match reg.data {
             graph::SignalData::Reg { data } => {
-                let value_name = format!("__reg_{}_{}", data.name, regs.len());
-                let next_name = format!("{}_next", value_name);
+                let name = format!("__reg_{}_{}", data.name, regs.len());
+                let value_name = names.unique(&name);
+                let next_name = names.unique(&format!("{}_next", name));
                 regs.insert(
                     *reg,
                     RegisterDecls {
@@ -89,21 +433,60 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
         }
     }
 
+    let mut latches = IndexMap::new();
+    for latch in m.latches.borrow().iter() {
+        if !is_live(latch) {
+            continue;
+        }
+        match latch.data {
+            graph::SignalData::Latch { data } => {
+                let name = format!("__latch_{}_{}", data.name, latches.len());
+                let value_name = names.unique(&name);
+                let data_name = names.unique(&format!("{}_data", name));
+                let enable_name = names.unique(&format!("{}_enable", name));
+                latches.insert(
+                    *latch,
+                    LatchDecls {
+                        data,
+                        value_name,
+                        data_name,
+                        enable_name,
+                    },
+                );
+            }
+            _ => unreachable!(),
+        }
+    }
+
     let module_decls = ModuleDecls {
         instances,
+        instance_arrays,
+        instance_array_lanes,
         mems,
         regs,
+        latches,
     };
 
-    let mut c = Compiler::new();
+    let mut localparam_names = HashMap::new();
+    for (name, signal) in m.constants.borrow().iter() {
+        localparam_names.insert(*signal, names.unique(name));
+    }
+
+    let mut c = Compiler::new(localparam_names, options.localparam_bit_width_threshold);
 
-    let mut assignments = AssignmentContext::new();
+    let mut assignments = AssignmentContext::new(options.always_comb, options.comments, names);
     for (name, output) in m.outputs.borrow().iter() {
-        let expr = c.compile_signal(&output, &module_decls, &mut assignments);
-        assignments.push(Assignment {
-            target_name: name.clone(),
-            expr,
-        });
+        if directly_connected_outputs.contains(name) {
+            continue;
+        }
+        compile_assignment(
+            &mut c,
+            output,
+            &module_decls,
+            &mut assignments,
+            identifier(name),
+            format!("output {}", name),
+        );
     }
 
     let mut node_decls = Vec::new();
@@ -114,158 +497,390 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
                 net_type: NetType::Wire,
                 name: decl_name.clone(),
                 bit_width: instance.instantiated_module.inputs.borrow()[name].bit_width(),
+                comment: None,
+                attributes: Vec::new(),
             });
 
-            let expr = c.compile_signal(
+            compile_assignment(
+                &mut c,
                 instance.driven_inputs.borrow()[name],
                 &module_decls,
                 &mut assignments,
+                decl_name.clone(),
+                format!("instance {} input {}", instance.name, name),
             );
-            assignments.push(Assignment {
-                target_name: decl_name.clone(),
-                expr,
-            });
         }
 
         for (name, decl_name) in instance_decls.output_names.iter() {
+            if instance_decls.forwarded_output_names.contains(name) {
+                continue;
+            }
             node_decls.push(NodeDecl {
                 net_type: NetType::Wire,
                 name: decl_name.clone(),
                 bit_width: instance.instantiated_module.outputs.borrow()[name].bit_width(),
+                comment: None,
+                attributes: Vec::new(),
+            });
+        }
+    }
+
+    for array_decls in module_decls.instance_arrays.iter() {
+        let instantiated_module = array_decls.instances[0].instantiated_module;
+        let count = array_decls.instances.len() as u32;
+        for (name, (decl_name, input)) in array_decls.input_names.iter() {
+            let bit_width = instantiated_module.inputs.borrow()[name].bit_width();
+            let label = format!(
+                "instance array {} input {}",
+                array_decls.instances[0].name, name
+            );
+            match *input {
+                InstanceArrayInput::Broadcast(signal) => {
+                    node_decls.push(NodeDecl {
+                        net_type: NetType::Wire,
+                        name: decl_name.clone(),
+                        bit_width,
+                        comment: None,
+                        attributes: Vec::new(),
+                    });
+
+                    compile_assignment(
+                        &mut c,
+                        signal,
+                        &module_decls,
+                        &mut assignments,
+                        decl_name.clone(),
+                        label,
+                    );
+                }
+                InstanceArrayInput::Sliced { source, range_low } => {
+                    node_decls.push(NodeDecl {
+                        net_type: NetType::Wire,
+                        name: decl_name.clone(),
+                        bit_width: bit_width * count,
+                        comment: None,
+                        attributes: Vec::new(),
+                    });
+
+                    assignments.set_label(label);
+                    let expr = c.compile_signal(source, &module_decls, &mut assignments);
+                    let expr = if range_low == 0 && bit_width * count == source.bit_width() {
+                        expr
+                    } else {
+                        // Only named nets can be sliced
+                        let source_expr = match expr {
+                            Expr::Ref { .. } => expr,
+                            _ => assignments.gen_temp(expr, source.bit_width()),
+                        };
+                        Expr::Bits {
+                            source: Box::new(source_expr),
+                            range_high: range_low + bit_width * count - 1,
+                            range_low,
+                        }
+                    };
+                    assignments.push(Assignment {
+                        target_name: decl_name.clone(),
+                        expr,
+                        comment: assignments.label(),
+                    });
+                }
+            }
+        }
+
+        for (name, decl_name) in array_decls.output_names.iter() {
+            node_decls.push(NodeDecl {
+                net_type: NetType::Wire,
+                name: decl_name.clone(),
+                bit_width: instantiated_module.outputs.borrow()[name].bit_width() * count,
+                comment: None,
+                attributes: Vec::new(),
             });
         }
     }
 
     for (mem, mem_decls) in module_decls.mems.iter() {
         for ((address, enable), read_signal_names) in mem_decls.read_signal_names.iter() {
-            let expr = c.compile_signal(address, &module_decls, &mut assignments);
+            compile_assignment(
+                &mut c,
+                address,
+                &module_decls,
+                &mut assignments,
+                read_signal_names.address_name.clone(),
+                format!("mem {} read port address", mem.name),
+            );
             node_decls.push(NodeDecl {
                 net_type: NetType::Wire,
                 name: read_signal_names.address_name.clone(),
                 bit_width: address.bit_width(),
+                comment: None,
+                attributes: Vec::new(),
             });
-            assignments.push(Assignment {
-                target_name: read_signal_names.address_name.clone(),
-                expr,
-            });
-            let expr = c.compile_signal(enable, &module_decls, &mut assignments);
+            compile_assignment(
+                &mut c,
+                enable,
+                &module_decls,
+                &mut assignments,
+                read_signal_names.enable_name.clone(),
+                format!("mem {} read port enable", mem.name),
+            );
             node_decls.push(NodeDecl {
                 net_type: NetType::Wire,
                 name: read_signal_names.enable_name.clone(),
                 bit_width: enable.bit_width(),
-            });
-            assignments.push(Assignment {
-                target_name: read_signal_names.enable_name.clone(),
-                expr,
+                comment: None,
+                attributes: Vec::new(),
             });
             node_decls.push(NodeDecl {
                 net_type: NetType::Reg,
                 name: read_signal_names.value_name.clone(),
                 bit_width: mem.element_bit_width,
+                comment: None,
+                attributes: Vec::new(),
             });
         }
-        if let Some((address, value, enable)) = *mem.write_port.borrow() {
-            let expr = c.compile_signal(address, &module_decls, &mut assignments);
+        for ((address, value, enable, mask), write_signal_names) in mem
+            .write_ports
+            .borrow()
+            .iter()
+            .zip(mem_decls.write_signal_names.iter())
+        {
+            compile_assignment(
+                &mut c,
+                address,
+                &module_decls,
+                &mut assignments,
+                write_signal_names.address_name.clone(),
+                format!("mem {} write port address", mem.name),
+            );
             node_decls.push(NodeDecl {
                 net_type: NetType::Wire,
-                name: mem_decls.write_address_name.clone(),
+                name: write_signal_names.address_name.clone(),
                 bit_width: address.bit_width(),
+                comment: None,
+                attributes: Vec::new(),
             });
-            assignments.push(Assignment {
-                target_name: mem_decls.write_address_name.clone(),
-                expr,
-            });
-            let expr = c.compile_signal(value, &module_decls, &mut assignments);
+            compile_assignment(
+                &mut c,
+                value,
+                &module_decls,
+                &mut assignments,
+                write_signal_names.value_name.clone(),
+                format!("mem {} write port value", mem.name),
+            );
             node_decls.push(NodeDecl {
                 net_type: NetType::Wire,
-                name: mem_decls.write_value_name.clone(),
+                name: write_signal_names.value_name.clone(),
                 bit_width: value.bit_width(),
+                comment: None,
+                attributes: Vec::new(),
             });
-            assignments.push(Assignment {
-                target_name: mem_decls.write_value_name.clone(),
-                expr,
-            });
-            let expr = c.compile_signal(enable, &module_decls, &mut assignments);
+            compile_assignment(
+                &mut c,
+                enable,
+                &module_decls,
+                &mut assignments,
+                write_signal_names.enable_name.clone(),
+                format!("mem {} write port enable", mem.name),
+            );
             node_decls.push(NodeDecl {
                 net_type: NetType::Wire,
-                name: mem_decls.write_enable_name.clone(),
+                name: write_signal_names.enable_name.clone(),
                 bit_width: enable.bit_width(),
+                comment: None,
+                attributes: Vec::new(),
             });
-            assignments.push(Assignment {
-                target_name: mem_decls.write_enable_name.clone(),
-                expr,
-            });
+            if let (Some(mask), Some(mask_name)) = (mask, &write_signal_names.mask_name) {
+                compile_assignment(
+                    &mut c,
+                    mask,
+                    &module_decls,
+                    &mut assignments,
+                    mask_name.clone(),
+                    format!("mem {} write port mask", mem.name),
+                );
+                node_decls.push(NodeDecl {
+                    net_type: NetType::Wire,
+                    name: mask_name.clone(),
+                    bit_width: mask.bit_width(),
+                    comment: None,
+                    attributes: Vec::new(),
+                });
+            }
         }
     }
 
-    for reg in module_decls.regs.values() {
+    for (reg_signal, reg) in module_decls.regs.iter() {
         node_decls.push(NodeDecl {
             net_type: NetType::Reg,
             name: reg.value_name.clone(),
             bit_width: reg.data.bit_width,
+            comment: if options.comments {
+                Some(format!(
+                    "register {} ({})",
+                    reg.data.name, reg_signal.location
+                ))
+            } else {
+                None
+            },
+            attributes: reg.data.attributes.borrow().clone(),
         });
         node_decls.push(NodeDecl {
             net_type: NetType::Wire,
             name: reg.next_name.clone(),
             bit_width: reg.data.bit_width,
+            comment: None,
+            attributes: Vec::new(),
         });
 
-        let expr = c.compile_signal(
+        compile_assignment(
+            &mut c,
             reg.data.next.borrow().unwrap(),
             &module_decls,
             &mut assignments,
+            reg.next_name.clone(),
+            format!("register {}", reg.data.name),
         );
-        assignments.push(Assignment {
-            target_name: reg.next_name.clone(),
-            expr,
+    }
+
+    for (latch_signal, latch) in module_decls.latches.iter() {
+        node_decls.push(NodeDecl {
+            net_type: NetType::Reg,
+            name: latch.value_name.clone(),
+            bit_width: latch.data.bit_width,
+            comment: if options.comments {
+                Some(format!(
+                    "latch {} ({})",
+                    latch.data.name, latch_signal.location
+                ))
+            } else {
+                None
+            },
+            attributes: Vec::new(),
+        });
+        node_decls.push(NodeDecl {
+            net_type: NetType::Wire,
+            name: latch.data_name.clone(),
+            bit_width: latch.data.bit_width,
+            comment: None,
+            attributes: Vec::new(),
+        });
+        node_decls.push(NodeDecl {
+            net_type: NetType::Wire,
+            name: latch.enable_name.clone(),
+            bit_width: 1,
+            comment: None,
+            attributes: Vec::new(),
         });
+
+        compile_assignment(
+            &mut c,
+            latch.data.data.borrow().unwrap(),
+            &module_decls,
+            &mut assignments,
+            latch.data_name.clone(),
+            format!("latch {} data", latch.data.name),
+        );
+        compile_assignment(
+            &mut c,
+            latch.data.enable.borrow().unwrap(),
+            &module_decls,
+            &mut assignments,
+            latch.enable_name.clone(),
+            format!("latch {} enable", latch.data.name),
+        );
     }
 
-    let mut w = code_writer::CodeWriter::new(w);
+    let assertions = m.assertions.borrow();
+    let assumptions = m.assumptions.borrow();
+    let covers = m.covers.borrow();
+    let traces = m.traces.borrow();
+    let mut annotation_names = |kind: &str, signals: Vec<&'a graph::Signal<'a>>| {
+        signals
+            .into_iter()
+            .enumerate()
+            .map(|(index, signal)| {
+                let name = assignments.unique_name(&format!("__{}_{}", kind, index));
+                node_decls.push(NodeDecl {
+                    net_type: NetType::Wire,
+                    name: name.clone(),
+                    bit_width: signal.bit_width(),
+                    comment: None,
+                    attributes: Vec::new(),
+                });
+                compile_assignment(
+                    &mut c,
+                    signal,
+                    &module_decls,
+                    &mut assignments,
+                    name.clone(),
+                    format!("{} {}", kind, index),
+                );
+                name
+            })
+            .collect::<Vec<_>>()
+    };
+    let assertion_names = annotation_names(
+        "assertion",
+        assertions.iter().map(|(condition, _)| *condition).collect(),
+    );
+    let assumption_names = annotation_names("assumption", assumptions.clone());
+    let cover_names = annotation_names("cover", covers.clone());
+    let trace_names = annotation_names("trace", traces.iter().map(|(_, signal)| *signal).collect());
 
-    w.append_line(&format!("module {}(", m.name))?;
+    let mut w = code_writer::CodeWriter::with_style(w, options.code_style.clone());
+
+    if let UninitializedRegisters::Random { seed } = options.uninitialized_registers {
+        w.append_line(&format!(
+            "// Registers are initialized with pseudo-random values from seed 0x{:016x}",
+            seed
+        ))?;
+    }
+    w.append_line(&format!("module {}(", identifier(&m.name)))?;
     w.indent();
 
-    // TODO: Make conditional based on the presence of (resetable) state elements
-    w.append_line("input wire reset_n,")?;
-    w.append_indent()?;
-    w.append("input wire clk")?;
-    if !m.inputs.borrow().is_empty() || !m.outputs.borrow().is_empty() {
-        w.append(",")?;
-        w.append_newline()?;
+    // Purely combinational hierarchies don't need clock or reset ports
+    let mut ports = Vec::new();
+    if has_clock {
+        ports.push(format!("input wire {}", reset));
+        ports.push(format!("input wire {}", clock));
     }
-    w.append_newline()?;
     let inputs = m.inputs.borrow();
-    let num_inputs = inputs.len();
-    for (i, (name, source)) in inputs.iter().enumerate() {
-        w.append_indent()?;
-        w.append("input wire ")?;
-        if source.bit_width() > 1 {
-            w.append(&format!("[{}:{}] ", source.bit_width() - 1, 0))?;
-        }
-        w.append(name)?;
-        if !m.outputs.borrow().is_empty() || i < num_inputs - 1 {
-            w.append(",")?;
-        }
-        w.append_newline()?;
+    for (name, source) in inputs.iter() {
+        ports.push(format!(
+            "input wire {}{}",
+            bit_range(source.bit_width()),
+            identifier(name)
+        ));
     }
     let outputs = m.outputs.borrow();
-    let num_outputs = outputs.len();
-    for (i, (name, output)) in outputs.iter().enumerate() {
+    for (name, output) in outputs.iter() {
+        ports.push(format!(
+            "output wire {}{}",
+            bit_range(output.bit_width()),
+            identifier(name)
+        ));
+    }
+    for (i, port) in ports.iter().enumerate() {
         w.append_indent()?;
-        w.append("output wire ")?;
-        if output.bit_width() > 1 {
-            w.append(&format!("[{}:{}] ", output.bit_width() - 1, 0))?;
-        }
-        w.append(name)?;
-        if i < num_outputs - 1 {
+        w.append(port)?;
+        if i < ports.len() - 1 {
             w.append(",")?;
         }
         w.append_newline()?;
+        if has_clock && i == 1 {
+            w.append_newline()?;
+        }
     }
     w.append_line(");")?;
     w.append_newline()?;
 
+    if !c.localparams().is_empty() {
+        for localparam in c.localparams() {
+            localparam.write(&mut w)?;
+        }
+        w.append_newline()?;
+    }
+
     if !node_decls.is_empty() {
         for node_decl in node_decls {
             node_decl.write(&mut w)?;
@@ -274,38 +889,111 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
     }
 
     for (instance, instance_decls) in module_decls.instances.iter() {
-        w.append_line(&format!(
-            "{} {}(",
-            instance.instantiated_module.name, instance.name
-        ))?;
+        write_instance_header(&mut w, instance, &instance_decls.name)?;
         w.indent();
-        // TODO: Make conditional based on the presence of (resetable) state elements
-        w.append_line(".reset_n(reset_n),")?;
-        w.append_indent()?;
-        w.append(".clk(clk)")?;
-        if !instance_decls.input_names.is_empty() {
-            for (name, decl_name) in instance_decls.input_names.iter() {
+        let mut connections = Vec::new();
+        if uses_clock(instance.instantiated_module) {
+            let (instance_clock, instance_reset) = clock_reset_port_names(
+                instance.instantiated_module,
+                options.clock(),
+                options.reset(),
+            );
+            connections.push(format!(".{}({})", instance_reset, reset));
+            connections.push(format!(".{}({})", instance_clock, clock));
+        }
+        for (name, decl_name) in instance_decls
+            .input_names
+            .iter()
+            .chain(instance_decls.output_names.iter())
+        {
+            connections.push(format!(".{}({})", identifier(name), decl_name));
+        }
+        for (i, connection) in connections.iter().enumerate() {
+            w.append_indent()?;
+            w.append(connection)?;
+            if i < connections.len() - 1 {
                 w.append(",")?;
                 w.append_newline()?;
-                w.append_indent()?;
-                w.append(&format!(".{}({})", name, decl_name))?;
             }
         }
-        if !instance_decls.output_names.is_empty() {
-            for (name, decl_name) in instance_decls.output_names.iter() {
+        w.unindent()?;
+        w.append(");")?;
+        w.append_newline()?;
+        w.append_newline()?;
+    }
+
+    for array_decls in module_decls.instance_arrays.iter() {
+        let instantiated_module = array_decls.instances[0].instantiated_module;
+        let index_name = &array_decls.index_name;
+        w.append_line(&format!("genvar {};", index_name))?;
+        w.append_line("generate")?;
+        w.indent();
+        w.append_line(&format!(
+            "for ({index} = 0; {index} < {}; {index} = {index} + 1) begin : {}",
+            array_decls.instances.len(),
+            array_decls.name,
+            index = index_name
+        ))?;
+        w.indent();
+        write_instance_header(&mut w, array_decls.instances[0], "inst")?;
+        w.indent();
+        // Each lane's port is connected to its own slice of a wire shared by all lanes, unless every lane shares the same input signal
+        let lane_slice = |bit_width: u32| {
+            if bit_width == 1 {
+                format!("[{}]", index_name)
+            } else {
+                format!("[{} * {} +: {}]", index_name, bit_width, bit_width)
+            }
+        };
+        let mut connections = Vec::new();
+        if uses_clock(instantiated_module) {
+            let (instance_clock, instance_reset) =
+                clock_reset_port_names(instantiated_module, options.clock(), options.reset());
+            connections.push(format!(".{}({})", instance_reset, reset));
+            connections.push(format!(".{}({})", instance_clock, clock));
+        }
+        for (name, (decl_name, input)) in array_decls.input_names.iter() {
+            let slice = match input {
+                InstanceArrayInput::Broadcast(_) => String::new(),
+                InstanceArrayInput::Sliced { .. } => {
+                    lane_slice(instantiated_module.inputs.borrow()[name].bit_width())
+                }
+            };
+            connections.push(format!(".{}({}{})", identifier(name), decl_name, slice));
+        }
+        for (name, decl_name) in array_decls.output_names.iter() {
+            let slice = lane_slice(instantiated_module.outputs.borrow()[name].bit_width());
+            connections.push(format!(".{}({}{})", identifier(name), decl_name, slice));
+        }
+        for (i, connection) in connections.iter().enumerate() {
+            w.append_indent()?;
+            w.append(connection)?;
+            if i < connections.len() - 1 {
                 w.append(",")?;
                 w.append_newline()?;
-                w.append_indent()?;
-                w.append(&format!(".{}({})", name, decl_name))?;
             }
         }
         w.unindent()?;
         w.append(");")?;
         w.append_newline()?;
+        w.unindent()?;
+        w.append_line("end")?;
+        w.unindent()?;
+        w.append_line("endgenerate")?;
         w.append_newline()?;
     }
 
+    // Memories follow the usual block RAM inference templates: an unreset array, one clocked process per port, and registered read data
     for (mem, mem_decls) in module_decls.mems.iter() {
+        let attributes = mem.attributes.borrow();
+        for (key, value) in attributes.iter() {
+            write_attribute(&mut w, key, value)?;
+        }
+        if let Some(ref ram_style) = options.ram_style {
+            if !attributes.iter().any(|(key, _)| key == "ram_style") {
+                write_attribute(&mut w, "ram_style", ram_style)?;
+            }
+        }
         w.append_indent()?;
         w.append("reg ")?;
         if mem.element_bit_width > 1 {
@@ -313,7 +1001,7 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
         }
         w.append(&format!(
             "{}[{}:{}];",
-            mem.name,
+            mem_decls.name,
             0,
             (1 << mem.address_bit_width) - 1
         ))?;
@@ -325,7 +1013,7 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
             for (i, element) in initial_contents.iter().enumerate() {
                 w.append_line(&format!(
                     "{}[{}] = {}'h{:x};",
-                    mem.name,
+                    mem_decls.name,
                     i,
                     mem.element_bit_width,
                     element.numeric_value()
@@ -335,48 +1023,121 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
             w.append_line("end")?;
             w.append_newline()?;
         }
-        if !mem_decls.read_signal_names.is_empty() || mem.write_port.borrow().is_some() {
-            w.append_line("always @(posedge clk) begin")?;
-            w.indent();
-        }
+        let write_ports = mem
+            .write_ports
+            .borrow()
+            .iter()
+            .zip(mem_decls.write_signal_names.iter())
+            .map(|((_, _, _, mask), write_signal_names)| {
+                (write_signal_names, mask.map(|mask| mask.bit_width()))
+            })
+            .collect::<Vec<_>>();
         for (_, read_signal_names) in mem_decls.read_signal_names.iter() {
+            w.append_line(&format!("always @(posedge {}) begin", clock))?;
+            w.indent();
             w.append_line(&format!("if ({}) begin", read_signal_names.enable_name))?;
             w.indent();
             w.append_line(&format!(
                 "{} <= {}[{}];",
-                read_signal_names.value_name, mem.name, read_signal_names.address_name
+                read_signal_names.value_name, mem_decls.name, read_signal_names.address_name
             ))?;
+            if let graph::ReadDuringWrite::New = *mem.read_during_write.borrow() {
+                // Write-first; forward colliding write values to the read data register.
+                // Write ports specified later take priority, so they're emitted last.
+                for &(write_signal_names, mask_bit_width) in write_ports.iter() {
+                    w.append_line(&format!(
+                        "if ({} && ({} == {})) begin",
+                        write_signal_names.enable_name,
+                        write_signal_names.address_name,
+                        read_signal_names.address_name
+                    ))?;
+                    w.indent();
+                    write_mem_write(
+                        &mut w,
+                        &read_signal_names.value_name,
+                        write_signal_names,
+                        mask_bit_width,
+                        mem.element_bit_width,
+                        &[],
+                    )?;
+                    w.unindent()?;
+                    w.append_line("end")?;
+                }
+            }
+            w.unindent()?;
+            w.append_line("end")?;
             w.unindent()?;
             w.append_line("end")?;
+            w.append_newline()?;
         }
-        if mem.write_port.borrow().is_some() {
-            w.append_line(&format!("if ({}) begin", mem_decls.write_enable_name))?;
+        for (index, &(write_signal_names, mask_bit_width)) in write_ports.iter().enumerate() {
+            w.append_line(&format!("always @(posedge {}) begin", clock))?;
             w.indent();
-            w.append_line(&format!(
-                "{}[{}] <= {};",
-                mem.name, mem_decls.write_address_name, mem_decls.write_value_name
-            ))?;
+            w.append_line(&format!("if ({}) begin", write_signal_names.enable_name))?;
+            w.indent();
+            write_mem_write(
+                &mut w,
+                &format!("{}[{}]", mem_decls.name, write_signal_names.address_name),
+                write_signal_names,
+                mask_bit_width,
+                mem.element_bit_width,
+                &write_ports[index + 1..],
+            )?;
             w.unindent()?;
             w.append_line("end")?;
-        }
-        if !mem_decls.read_signal_names.is_empty() || mem.write_port.borrow().is_some() {
             w.unindent()?;
             w.append_line("end")?;
             w.append_newline()?;
         }
     }
 
+    if options.uninitialized_registers != UninitializedRegisters::Undefined
+        && !module_decls.regs.is_empty()
+    {
+        w.append_line("initial begin")?;
+        w.indent();
+        // Registers are enumerated in a deterministic order, so each seed always produces the same values
+        let mut state = match options.uninitialized_registers {
+            UninitializedRegisters::Random { seed } => seed,
+            _ => 0,
+        };
+        for reg in module_decls.regs.values() {
+            let bit_width = reg.data.bit_width;
+            let value = match options.uninitialized_registers {
+                UninitializedRegisters::Random { .. } if bit_width > 64 => {
+                    ((splitmix64(&mut state) as u128) << 64) | splitmix64(&mut state) as u128
+                }
+                UninitializedRegisters::Random { .. } => splitmix64(&mut state) as u128,
+                _ => 0,
+            };
+            let mask = if bit_width < 128 {
+                (1u128 << bit_width) - 1
+            } else {
+                !0
+            };
+            w.append_line(&format!(
+                "{} = {}'h{:x};",
+                reg.value_name,
+                bit_width,
+                value & mask
+            ))?;
+        }
+        w.unindent()?;
+        w.append_line("end")?;
+        w.append_newline()?;
+    }
+
     for reg in module_decls.regs.values() {
         w.append_indent()?;
-        w.append("always @(posedge clk")?;
+        w.append(&format!("always @(posedge {}", clock))?;
         if reg.data.initial_value.borrow().is_some() {
-            w.append(", negedge reset_n")?;
+            w.append(&format!(", negedge {}", reset))?;
         }
         w.append(") begin")?;
         w.append_newline()?;
         w.indent();
         if let Some(ref initial_value) = *reg.data.initial_value.borrow() {
-            w.append_line("if (~reset_n) begin")?;
+            w.append_line(&format!("if (~{}) begin", reset))?;
             w.indent();
             w.append_line(&format!(
                 "{} <= {}'h{:x};",
@@ -399,6 +1160,75 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
         w.append_newline()?;
     }
 
+    // Latches are written in the usual inference template: a sensitivity-list-free process that only assigns while enabled
+    for latch in module_decls.latches.values() {
+        w.append_line(if options.always_comb {
+            "always_latch begin"
+        } else {
+            "always @(*) begin"
+        })?;
+        w.indent();
+        w.append_line(&format!("if ({}) begin", latch.enable_name))?;
+        w.indent();
+        w.append_line(&format!("{} <= {};", latch.value_name, latch.data_name))?;
+        w.unindent()?;
+        w.append_line("end")?;
+        w.unindent()?;
+        w.append_line("end")?;
+        w.append_newline()?;
+    }
+
+    if !assertions.is_empty() || !assumptions.is_empty() || !traces.is_empty() {
+        // Assertions, assumptions, and traces are only used in simulation, and are ignored by synthesis tools
+        w.append("`ifndef SYNTHESIS")?;
+        w.append_newline()?;
+        w.append_line(&format!("always @(posedge {}) begin", clock))?;
+        w.indent();
+        for ((_, message), name) in assertions.iter().zip(assertion_names.iter()) {
+            w.append_line(&format!(
+                "assert ({}) else $error(\"{}\");",
+                name,
+                escape_string(message)
+            ))?;
+        }
+        for name in assumption_names.iter() {
+            w.append_line(&format!(
+                "assert ({}) else $error(\"Assumption violated\");",
+                name
+            ))?;
+        }
+        for ((label, _), name) in traces.iter().zip(trace_names.iter()) {
+            w.append_line(&format!(
+                "$display(\"%m: {} = %h\", {});",
+                escape_string(label),
+                name
+            ))?;
+        }
+        w.unindent()?;
+        w.append_line("end")?;
+        w.append("`endif")?;
+        w.append_newline()?;
+        w.append_newline()?;
+    }
+
+    if !assertions.is_empty() || !assumptions.is_empty() || !covers.is_empty() {
+        // Concurrent properties for formal verification tools
+        w.append("`ifdef FORMAL")?;
+        w.append_newline()?;
+        for name in assertion_names.iter() {
+            w.append_line(&format!("assert property (@(posedge {}) {});", clock, name))?;
+        }
+        for name in assumption_names.iter() {
+            w.append_line(&format!("assume property (@(posedge {}) {});", clock, name))?;
+        }
+        for name in cover_names.iter() {
+            w.append_line(&format!("cover property (@(posedge {}) {});", clock, name))?;
+        }
+        w.append("`endif")?;
+        w.append_newline()?;
+        w.append_newline()?;
+    }
+
     if !assignments.is_empty() {
         assignments.write(&mut w)?;
         w.append_newline()?;
@@ -411,28 +1241,896 @@ pub fn generate<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generates Verilog modules for the `Module` `m` and every `Module` it instantiates (directly or indirectly) using the default [`GenerationOptions`] and writes them to `w`.
+///
+/// See [`generate_hierarchy_with_options`] for details.
+///
+/// [`GenerationOptions`]: ./struct.GenerationOptions.html
+/// [`generate_hierarchy_with_options`]: ./fn.generate_hierarchy_with_options.html
+pub fn generate_hierarchy<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
+    generate_hierarchy_with_options(m, &GenerationOptions::default(), w)
+}
 
-    use crate::*;
+/// Generates Verilog modules for the `Module` `m` and every `Module` it instantiates (directly or indirectly) according to `options` and writes them to `w`.
+///
+/// Each `Module` is generated once, no matter how many times it's instantiated, and before any `Module`s that instantiate it, so `m` is generated last.
+/// Extern `Module`s are skipped, since they're defined outside of kaze.
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`generate_with_options`].
+///
+/// [`generate_with_options`]: ./fn.generate_with_options.html
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let inner = c.module("Inner");
+/// inner.output("o", !inner.input("i", 1));
+///
+/// let m = c.module("Outer");
+/// let inner1 = m.instance("inner1", "Inner");
+/// inner1.drive_input("i", m.input("i", 1));
+/// let inner2 = m.instance("inner2", "Inner");
+/// inner2.drive_input("i", inner1.output("o"));
+/// m.output("o", inner2.output("o"));
+///
+/// let mut w = Vec::new();
+/// verilog::generate_hierarchy(m, &mut w).unwrap();
+/// let verilog = String::from_utf8(w).unwrap();
+/// assert_eq!(verilog.matches("module Inner(").count(), 1);
+/// assert!(verilog.find("module Inner(").unwrap() < verilog.find("module Outer(").unwrap());
+/// ```
+pub fn generate_hierarchy_with_options<'a, W: Write>(
+    m: &'a graph::Module<'a>,
+    options: &GenerationOptions,
+    mut w: W,
+) -> Result<()> {
+    validate_module_hierarchy(m);
 
-    #[test]
-    #[should_panic(
-        expected = "Cannot generate code for module \"A\" because it has a recursive definition formed by an instance of itself called \"a\"."
-    )]
-    fn recursive_module_definition_error1() {
-        let c = Context::new();
+    for module in hierarchy_modules(m) {
+        if !module.is_extern {
+            generate_with_options(module, options, &mut w)?;
+        }
+    }
 
-        let a = c.module("A");
+    Ok(())
+}
 
-        let _ = a.instance("a", "A");
+/// Generates Verilog modules for the `Module` `m` and every `Module` it instantiates (directly or indirectly) using the default [`GenerationOptions`] and writes each one to its own file in the directory `path`.
+///
+/// See [`generate_to_dir_with_options`] for details.
+///
+/// [`GenerationOptions`]: ./struct.GenerationOptions.html
+/// [`generate_to_dir_with_options`]: ./fn.generate_to_dir_with_options.html
+pub fn generate_to_dir<'a, P: AsRef<Path>>(m: &'a graph::Module<'a>, path: P) -> Result<()> {
+    generate_to_dir_with_options(m, &GenerationOptions::default(), path)
+}
 
-        // Panic
-        generate(a, Vec::new()).unwrap();
-    }
+/// Generates Verilog modules for the `Module` `m` and every `Module` it instantiates (directly or indirectly) according to `options` and writes each one to its own file in the directory `path`.
+///
+/// Each `Module` is written once to `<name>.v`, where `<name>` is the `Module`'s name.
+/// Extern `Module`s are skipped (and not listed in `filelist.f`), since they're defined outside of kaze.
+/// A `filelist.f` file listing the generated files (relative to `path`) is also written, in the same order as [`generate_hierarchy_with_options`] would generate them, so every `Module` is listed after the `Module`s it instantiates.
+/// `path` (and any missing parent directories) are created if they don't already exist, and existing files with the same names are overwritten.
+///
+/// # Errors
+///
+/// Returns an error if `path` can't be created or any file can't be written, with a message including the offending path.
+/// Also returns an error with [`ErrorKind::InvalidInput`] (before writing anything) if a `Module`'s name can't be used as a file name inside `path` (for example, if it contains a path separator), so files outside of `path` are never written.
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`generate_with_options`].
+///
+/// [`ErrorKind::InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+/// [`generate_hierarchy_with_options`]: ./fn.generate_hierarchy_with_options.html
+/// [`generate_with_options`]: ./fn.generate_with_options.html
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let inner = c.module("Inner");
+/// inner.output("o", !inner.input("i", 1));
+///
+/// let m = c.module("Outer");
+/// let inner1 = m.instance("inner1", "Inner");
+/// inner1.drive_input("i", m.input("i", 1));
+/// m.output("o", inner1.output("o"));
+///
+/// let dir = std::env::temp_dir().join("kaze_generate_to_dir_doc");
+/// verilog::generate_to_dir(m, &dir).unwrap();
+/// assert!(dir.join("Inner.v").is_file());
+/// assert!(dir.join("Outer.v").is_file());
+/// assert_eq!(std::fs::read_to_string(dir.join("filelist.f")).unwrap(), "Inner.v\nOuter.v\n");
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn generate_to_dir_with_options<'a, P: AsRef<Path>>(
+    m: &'a graph::Module<'a>,
+    options: &GenerationOptions,
+    path: P,
+) -> Result<()> {
+    let path = path.as_ref();
 
-    #[test]
+    validate_module_hierarchy(m);
+
+    let modules = hierarchy_modules(m)
+        .into_iter()
+        .filter(|module| !module.is_extern)
+        .collect::<Vec<_>>();
+    let mut file_names = Vec::new();
+    for module in modules.iter() {
+        let file_name = format!("{}.v", module.name);
+        // Names containing path separators (or other special components) could otherwise write outside of `path`
+        let is_plain_file_name = match Path::new(&file_name).components().collect::<Vec<_>>()[..] {
+            [Component::Normal(name)] => name == file_name.as_str(),
+            _ => false,
+        };
+        if !is_plain_file_name {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{}: module \"{}\"'s name can't be used as a file name in this directory",
+                    path.join(&file_name).display(),
+                    module.name
+                ),
+            ));
+        }
+        file_names.push(file_name);
+    }
+
+    let with_path =
+        |path: &Path, e: Error| Error::new(e.kind(), format!("{}: {}", path.display(), e));
+
+    fs::create_dir_all(path).map_err(|e| with_path(path, e))?;
+
+    for (module, file_name) in modules.iter().zip(file_names.iter()) {
+        let file_path = path.join(file_name);
+        let file = fs::File::create(&file_path).map_err(|e| with_path(&file_path, e))?;
+        generate_with_options(module, options, BufWriter::new(file))
+            .map_err(|e| with_path(&file_path, e))?;
+    }
+
+    let filelist_path = path.join("filelist.f");
+    let mut filelist = String::new();
+    for file_name in file_names.iter() {
+        filelist.push_str(file_name);
+        filelist.push('\n');
+    }
+    fs::write(&filelist_path, filelist).map_err(|e| with_path(&filelist_path, e))?;
+
+    Ok(())
+}
+
+/// Generates a Verilog testbench skeleton for the `Module` `m` and writes it to `w`.
+///
+/// The testbench declares a `reg` for each of `m`'s inputs and a `wire` for each of its outputs, instantiates `m` as `dut`, and dumps all signals to `<name>_tb.vcd`.
+/// If `m` uses a clock, the testbench also generates `clk` (with a 10 time unit period) and holds the active-low `reset_n` asserted for the first two cycles, using `m`'s own clock and reset port names if it specifies them (see [`Module::clock_port_name`] and [`Module::reset_port_name`]).
+/// All inputs are driven to `0` initially, and a `// TODO` comment marks where stimulus should be added.
+///
+/// [`Module::clock_port_name`]: ../struct.Module.html#method.clock_port_name
+/// [`Module::reset_port_name`]: ../struct.Module.html#method.reset_port_name
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// let c = Context::new();
+///
+/// let m = c.module("Counter");
+/// let count = m.reg("count", 8);
+/// count.default_value(0u32);
+/// count.drive_next(if_(m.input("en", 1), count.value + m.lit(1u32, 8)).else_(count.value));
+/// m.output("count", count.value);
+///
+/// let mut w = Vec::new();
+/// verilog::generate_testbench(m, &mut w).unwrap();
+/// let testbench = String::from_utf8(w).unwrap();
+/// assert!(testbench.contains("module Counter_tb;"));
+/// assert!(testbench.contains("Counter dut("));
+/// ```
+pub fn generate_testbench<'a, W: Write>(m: &'a graph::Module<'a>, w: W) -> Result<()> {
+    let has_clock = uses_clock(m);
+    let (clock, reset) = if has_clock {
+        clock_reset_port_names(m, "clk", "reset_n")
+    } else {
+        Default::default()
+    };
+    let tb_name = identifier(&format!("{}_tb", m.name));
+
+    let mut names = UsedNames::new();
+    if has_clock {
+        names.reserve(&reset);
+        names.reserve(&clock);
+    }
+    let inputs = m.inputs.borrow();
+    let outputs = m.outputs.borrow();
+    for name in inputs.keys().chain(outputs.keys()) {
+        names.reserve(name);
+    }
+    let dut_name = names.unique("dut");
+
+    let mut w = code_writer::CodeWriter::new(w);
+
+    w.append_line("`timescale 1ns / 1ps")?;
+    w.append_newline()?;
+    w.append_line(&format!("module {};", tb_name))?;
+    w.indent();
+
+    if has_clock {
+        w.append_line(&format!("reg {};", reset))?;
+        w.append_line(&format!("reg {};", clock))?;
+        w.append_newline()?;
+    }
+    for (name, source) in inputs.iter() {
+        w.append_line(&format!(
+            "reg {}{};",
+            bit_range(source.bit_width()),
+            identifier(name)
+        ))?;
+    }
+    for (name, output) in outputs.iter() {
+        w.append_line(&format!(
+            "wire {}{};",
+            bit_range(output.bit_width()),
+            identifier(name)
+        ))?;
+    }
+    if !inputs.is_empty() || !outputs.is_empty() {
+        w.append_newline()?;
+    }
+
+    let mut connections = Vec::new();
+    if has_clock {
+        connections.push(format!(".{}({})", reset, reset));
+        connections.push(format!(".{}({})", clock, clock));
+    }
+    for name in inputs.keys().chain(outputs.keys()) {
+        let name = identifier(name);
+        connections.push(format!(".{}({})", name, name));
+    }
+    w.append_line(&format!("{} {}(", identifier(&m.name), dut_name))?;
+    w.indent();
+    for (i, connection) in connections.iter().enumerate() {
+        w.append_indent()?;
+        w.append(connection)?;
+        if i < connections.len() - 1 {
+            w.append(",")?;
+            w.append_newline()?;
+        }
+    }
+    w.unindent()?;
+    w.append(");")?;
+    w.append_newline()?;
+    w.append_newline()?;
+
+    if has_clock {
+        w.append_line("initial begin")?;
+        w.indent();
+        w.append_line(&format!("{} = 1'b0;", clock))?;
+        w.append_line(&format!("forever #5 {} = ~{};", clock, clock))?;
+        w.unindent()?;
+        w.append_line("end")?;
+        w.append_newline()?;
+    }
+
+    w.append_line("initial begin")?;
+    w.indent();
+    w.append_line(&format!(
+        "$dumpfile(\"{}.vcd\");",
+        escape_string(&format!("{}_tb", m.name))
+    ))?;
+    w.append_line(&format!("$dumpvars(0, {});", tb_name))?;
+    w.unindent()?;
+    w.append_line("end")?;
+    w.append_newline()?;
+
+    w.append_line("initial begin")?;
+    w.indent();
+    if has_clock {
+        w.append_line(&format!("{} = 1'b0;", reset))?;
+    }
+    for (name, source) in inputs.iter() {
+        w.append_line(&format!(
+            "{} = {}'h0;",
+            identifier(name),
+            source.bit_width()
+        ))?;
+    }
+    if has_clock {
+        w.append_line(&format!("repeat (2) @(posedge {});", clock))?;
+        w.append_line(&format!("{} = 1'b1;", reset))?;
+    }
+    w.append_newline()?;
+    w.append_line("// TODO: Apply stimulus here")?;
+    w.append_newline()?;
+    if has_clock {
+        w.append_line(&format!("repeat (10) @(posedge {});", clock))?;
+    } else {
+        w.append_line("#100;")?;
+    }
+    w.append_line("$finish;")?;
+    w.unindent()?;
+    w.append_line("end")?;
+    w.append_newline()?;
+
+    w.unindent()?;
+    w.append_line("endmodule")?;
+    w.append_newline()?;
+
+    Ok(())
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Returns the Verilog identifier that generated code uses for `name`, such as the name of a `Module`, input, output, instance, or [`Mem`].
+///
+/// Names that are already valid simple identifiers (made up of ASCII letters, digits, `_`, and `$`, and not starting with a digit or `$`) are used as-is, except for SystemVerilog keywords.
+/// All other names are emitted as escaped identifiers (for example, an input called `logic` becomes `\logic `, and a `Module` called `Mod-1` becomes `\Mod-1 `), which other tools (such as those instantiating the generated module) can refer to by the original name.
+/// Escaped identifiers can't contain whitespace, so any whitespace characters are replaced with `_`.
+///
+/// [`Mem`]: ../struct.Mem.html
+///
+/// # Examples
+///
+/// ```
+/// use kaze::*;
+///
+/// assert_eq!(verilog::identifier("data"), "data");
+/// assert_eq!(verilog::identifier("logic"), "\\logic ");
+/// assert_eq!(verilog::identifier("Mod-1"), "\\Mod-1 ");
+/// ```
+pub fn identifier(name: &str) -> String {
+    // Sorted, so they can be binary searched
+    const KEYWORDS: &[&str] = &[
+        "accept_on",
+        "alias",
+        "always",
+        "always_comb",
+        "always_ff",
+        "always_latch",
+        "and",
+        "assert",
+        "assign",
+        "assume",
+        "automatic",
+        "before",
+        "begin",
+        "bind",
+        "bins",
+        "binsof",
+        "bit",
+        "break",
+        "buf",
+        "bufif0",
+        "bufif1",
+        "byte",
+        "case",
+        "casex",
+        "casez",
+        "cell",
+        "chandle",
+        "checker",
+        "class",
+        "clocking",
+        "cmos",
+        "config",
+        "const",
+        "constraint",
+        "context",
+        "continue",
+        "cover",
+        "covergroup",
+        "coverpoint",
+        "cross",
+        "deassign",
+        "default",
+        "defparam",
+        "design",
+        "disable",
+        "dist",
+        "do",
+        "edge",
+        "else",
+        "end",
+        "endcase",
+        "endchecker",
+        "endclass",
+        "endclocking",
+        "endconfig",
+        "endfunction",
+        "endgenerate",
+        "endgroup",
+        "endinterface",
+        "endmodule",
+        "endpackage",
+        "endprimitive",
+        "endprogram",
+        "endproperty",
+        "endsequence",
+        "endspecify",
+        "endtable",
+        "endtask",
+        "enum",
+        "event",
+        "eventually",
+        "expect",
+        "export",
+        "extends",
+        "extern",
+        "final",
+        "first_match",
+        "for",
+        "force",
+        "foreach",
+        "forever",
+        "fork",
+        "forkjoin",
+        "function",
+        "generate",
+        "genvar",
+        "global",
+        "highz0",
+        "highz1",
+        "if",
+        "iff",
+        "ifnone",
+        "ignore_bins",
+        "illegal_bins",
+        "implements",
+        "implies",
+        "import",
+        "incdir",
+        "include",
+        "initial",
+        "inout",
+        "input",
+        "inside",
+        "instance",
+        "int",
+        "integer",
+        "interconnect",
+        "interface",
+        "intersect",
+        "join",
+        "join_any",
+        "join_none",
+        "large",
+        "let",
+        "liblist",
+        "library",
+        "local",
+        "localparam",
+        "logic",
+        "longint",
+        "macromodule",
+        "matches",
+        "medium",
+        "modport",
+        "module",
+        "nand",
+        "negedge",
+        "nettype",
+        "new",
+        "nexttime",
+        "nmos",
+        "nor",
+        "noshowcancelled",
+        "not",
+        "notif0",
+        "notif1",
+        "null",
+        "or",
+        "output",
+        "package",
+        "packed",
+        "parameter",
+        "pmos",
+        "posedge",
+        "primitive",
+        "priority",
+        "program",
+        "property",
+        "protected",
+        "pull0",
+        "pull1",
+        "pulldown",
+        "pullup",
+        "pulsestyle_ondetect",
+        "pulsestyle_onevent",
+        "pure",
+        "rand",
+        "randc",
+        "randcase",
+        "randsequence",
+        "rcmos",
+        "real",
+        "realtime",
+        "ref",
+        "reg",
+        "reject_on",
+        "release",
+        "repeat",
+        "restrict",
+        "return",
+        "rnmos",
+        "rpmos",
+        "rtran",
+        "rtranif0",
+        "rtranif1",
+        "s_always",
+        "s_eventually",
+        "s_nexttime",
+        "s_until",
+        "s_until_with",
+        "scalared",
+        "sequence",
+        "shortint",
+        "shortreal",
+        "showcancelled",
+        "signed",
+        "small",
+        "soft",
+        "solve",
+        "specify",
+        "specparam",
+        "static",
+        "string",
+        "strong",
+        "strong0",
+        "strong1",
+        "struct",
+        "super",
+        "supply0",
+        "supply1",
+        "sync_accept_on",
+        "sync_reject_on",
+        "table",
+        "tagged",
+        "task",
+        "this",
+        "throughout",
+        "time",
+        "timeprecision",
+        "timeunit",
+        "tran",
+        "tranif0",
+        "tranif1",
+        "tri",
+        "tri0",
+        "tri1",
+        "triand",
+        "trior",
+        "trireg",
+        "type",
+        "typedef",
+        "union",
+        "unique",
+        "unique0",
+        "unsigned",
+        "until",
+        "until_with",
+        "untyped",
+        "use",
+        "uwire",
+        "var",
+        "vectored",
+        "virtual",
+        "void",
+        "wait",
+        "wait_order",
+        "wand",
+        "weak",
+        "weak0",
+        "weak1",
+        "while",
+        "wildcard",
+        "wire",
+        "with",
+        "within",
+        "wor",
+        "xnor",
+        "xor",
+    ];
+
+    let is_simple = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_simple && KEYWORDS.binary_search(&name).is_err() {
+        return name.into();
+    }
+
+    let escaped = name
+        .chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect::<String>();
+    format!("\\{} ", escaped)
+}
+
+// Compiles `signal` and assigns it to `target_name`, commenting the generated assignments with `label` if comments are enabled
+fn compile_assignment<'graph>(
+    c: &mut Compiler<'graph>,
+    signal: &'graph graph::Signal<'graph>,
+    module_decls: &ModuleDecls<'graph>,
+    assignments: &mut AssignmentContext,
+    target_name: String,
+    label: String,
+) {
+    assignments.set_label(label);
+    let expr = c.compile_signal(signal, module_decls, assignments);
+    assignments.push(Assignment {
+        target_name,
+        expr,
+        comment: assignments.label(),
+    });
+}
+
+// Returns how every lane of an instance array drives the input called `name`, or `None` if it can't be expressed in terms of the lane index
+fn instance_array_input<'a>(
+    instances: &[&'a graph::Instance<'a>],
+    name: &str,
+) -> Option<InstanceArrayInput<'a>> {
+    let driven_inputs = instances
+        .iter()
+        .map(|instance| instance.driven_inputs.borrow()[name])
+        .collect::<Vec<_>>();
+    if driven_inputs.iter().all(|&input| input == driven_inputs[0]) {
+        return Some(InstanceArrayInput::Broadcast(driven_inputs[0]));
+    }
+
+    let bit_width = driven_inputs[0].bit_width();
+    let (source, range_low) = match driven_inputs[0].data {
+        graph::SignalData::Bits {
+            source, range_low, ..
+        } => (source, range_low),
+        _ => return None,
+    };
+    let is_sliced = driven_inputs
+        .iter()
+        .enumerate()
+        .all(|(lane, input)| match input.data {
+            graph::SignalData::Bits {
+                source: lane_source,
+                range_low: lane_range_low,
+                ..
+            } => lane_source == source && lane_range_low == range_low + lane as u32 * bit_width,
+            _ => false,
+        });
+    if is_sliced {
+        Some(InstanceArrayInput::Sliced { source, range_low })
+    } else {
+        None
+    }
+}
+
+fn write_attribute<W: Write>(
+    w: &mut code_writer::CodeWriter<W>,
+    key: &str,
+    value: &str,
+) -> Result<()> {
+    w.append_line(&format!("(* {} = {} *)", key, string_literal(value)))
+}
+
+// Writes the start of an instantiation (up to and including the opening parenthesis of its port connections), including any parameters
+fn write_instance_header<W: Write>(
+    w: &mut code_writer::CodeWriter<W>,
+    instance: &graph::Instance,
+    name: &str,
+) -> Result<()> {
+    let module_name = identifier(&instance.instantiated_module.name);
+    let parameters = instance.parameters.borrow();
+    if parameters.is_empty() {
+        return w.append_line(&format!("{} {}(", module_name, name));
+    }
+
+    w.append_line(&format!("{} #(", module_name))?;
+    w.indent();
+    for (i, (key, value)) in parameters.iter().enumerate() {
+        let value = match value {
+            graph::ParameterValue::Integer(value) => value.to_string(),
+            graph::ParameterValue::Real(value) => format!("{:?}", value),
+            graph::ParameterValue::String(value) => string_literal(value),
+        };
+        w.append_indent()?;
+        w.append(&format!(".{}({})", key, value))?;
+        if i < parameters.len() - 1 {
+            w.append(",")?;
+        }
+        w.append_newline()?;
+    }
+    w.unindent()?;
+    w.append_line(&format!(") {}(", name))
+}
+
+fn string_literal(s: &str) -> String {
+    let s: String = s
+        .chars()
+        .map(|c| match c {
+            '"' => "\\\"".into(),
+            '\\' => "\\\\".into(),
+            '\n' => "\\n".into(),
+            _ => c.to_string(),
+        })
+        .collect();
+    format!("\"{}\"", s)
+}
+
+fn bit_range(bit_width: u32) -> String {
+    if bit_width > 1 {
+        format!("[{}:{}] ", bit_width - 1, 0)
+    } else {
+        String::new()
+    }
+}
+
+fn escape_string(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '"' => "\\\"".into(),
+            '\\' => "\\\\".into(),
+            '\n' => "\\n".into(),
+            '%' => "%%".into(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+// Writes `write_signal_names`' value to `target`.
+// Each port's writes are in a separate process, so lanes that `later_write_ports` (which take priority) also write to the same address in the same cycle are skipped.
+fn write_mem_write<W: Write>(
+    w: &mut code_writer::CodeWriter<W>,
+    target: &str,
+    write_signal_names: &WriteSignalNames,
+    mask_bit_width: Option<u32>,
+    element_bit_width: u32,
+    later_write_ports: &[(&WriteSignalNames, Option<u32>)],
+) -> Result<()> {
+    if later_write_ports.is_empty() {
+        match (mask_bit_width, &write_signal_names.mask_name) {
+            (Some(mask_bit_width), Some(mask_name)) if mask_bit_width > 1 => {
+                // One part-select write per lane so synthesis tools can infer byte/lane enables
+                let lane_bit_width = element_bit_width / mask_bit_width;
+                for lane_index in 0..mask_bit_width {
+                    let range_low = lane_index * lane_bit_width;
+                    let range_high = range_low + lane_bit_width - 1;
+                    w.append_line(&format!("if ({}[{}]) begin", mask_name, lane_index))?;
+                    w.indent();
+                    w.append_line(&format!(
+                        "{}[{}:{}] <= {}[{}:{}];",
+                        target,
+                        range_high,
+                        range_low,
+                        write_signal_names.value_name,
+                        range_high,
+                        range_low
+                    ))?;
+                    w.unindent()?;
+                    w.append_line("end")?;
+                }
+            }
+            (_, Some(mask_name)) => {
+                // A single-bit mask covers the entire element
+                w.append_line(&format!("if ({}) begin", mask_name))?;
+                w.indent();
+                w.append_line(&format!("{} <= {};", target, write_signal_names.value_name))?;
+                w.unindent()?;
+                w.append_line("end")?;
+            }
+            _ => {
+                w.append_line(&format!("{} <= {};", target, write_signal_names.value_name))?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Lanes are as wide as possible while still lining up with every involved port's mask lanes
+    let lane_bit_width = std::iter::once(mask_bit_width)
+        .chain(
+            later_write_ports
+                .iter()
+                .map(|&(_, mask_bit_width)| mask_bit_width),
+        )
+        .flatten()
+        .filter(|&mask_bit_width| mask_bit_width > 1)
+        .map(|mask_bit_width| element_bit_width / mask_bit_width)
+        .fold(element_bit_width, gcd);
+    let mask_bit = |write_signal_names: &WriteSignalNames,
+                    mask_bit_width: Option<u32>,
+                    range_low: u32| {
+        match (mask_bit_width, &write_signal_names.mask_name) {
+            (Some(mask_bit_width), Some(mask_name)) if mask_bit_width > 1 => Some(format!(
+                "{}[{}]",
+                mask_name,
+                range_low / (element_bit_width / mask_bit_width)
+            )),
+            (_, Some(mask_name)) => Some(mask_name.clone()),
+            _ => None,
+        }
+    };
+    for range_low in (0..element_bit_width).step_by(lane_bit_width as usize) {
+        let mut conditions = Vec::new();
+        if let Some(mask_bit) = mask_bit(write_signal_names, mask_bit_width, range_low) {
+            conditions.push(mask_bit);
+        }
+        for &(later_write_signal_names, later_mask_bit_width) in later_write_ports.iter() {
+            let mut collision = format!(
+                "{} && ({} == {})",
+                later_write_signal_names.enable_name,
+                later_write_signal_names.address_name,
+                write_signal_names.address_name
+            );
+            if let Some(mask_bit) =
+                mask_bit(later_write_signal_names, later_mask_bit_width, range_low)
+            {
+                collision = format!("{} && {}", collision, mask_bit);
+            }
+            conditions.push(format!("!({})", collision));
+        }
+        let range = if lane_bit_width < element_bit_width {
+            format!("[{}:{}]", range_low + lane_bit_width - 1, range_low)
+        } else {
+            String::new()
+        };
+        w.append_line(&format!("if ({}) begin", conditions.join(" && ")))?;
+        w.indent();
+        w.append_line(&format!(
+            "{}{} <= {}{};",
+            target, range, write_signal_names.value_name, range
+        ))?;
+        w.unindent()?;
+        w.append_line("end")?;
+    }
+
+    Ok(())
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::*;
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate code for module \"A\" because it has a recursive definition formed by an instance of itself called \"a\"."
+    )]
+    fn recursive_module_definition_error1() {
+        let c = Context::new();
+
+        let a = c.module("A");
+
+        let _ = a.instance("a", "A");
+
+        // Panic
+        generate(a, Vec::new()).unwrap();
+    }
+
+    #[test]
     #[should_panic(
         expected = "Cannot generate code for module \"A\" because it has a recursive definition formed by an instance of itself called \"a\" in module \"B\"."
     )]
@@ -579,4 +2277,1954 @@ mod tests {
         // Panic
         generate(b, Vec::new()).unwrap();
     }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate code for module \"b\" because module \"a\" contains an output called \"o\" which forms a combinational loop with itself."
+    )]
+    fn mem_comb_read_port_combinational_loop_error() {
+        let c = Context::new();
+
+        let a = c.module("a");
+        let m = a.mem("m", 2, 2);
+        m.initial_contents(&[0u32, 1u32, 2u32, 3u32]);
+        a.output("o", m.read_port_comb(a.input("i", 2)));
+
+        let b = c.module("b");
+        let a_inst = b.instance("a_inst", "a");
+        let a_inst_o = a_inst.output("o");
+        a_inst.drive_input("i", a_inst_o);
+
+        // Panic
+        generate(b, Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn mem_comb_read_port() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let m = a.mem("mem", 2, 8);
+        m.initial_contents(&[0x12u8, 0x34u8, 0x56u8, 0x78u8]);
+        a.output("read_data", m.read_port_comb(a.input("read_addr", 2)));
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains("    assign __temp_0 = mem[read_addr];\n"));
+        assert!(output.contains("    assign read_data = __temp_0;\n"));
+        assert!(!output.contains("always"));
+    }
+
+    #[test]
+    fn mem_initial_contents_from_file() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let m = a.mem("mem", 1, 32);
+        let path = std::env::temp_dir().join(format!(
+            "kaze_verilog_mem_initial_contents_from_file_{}.hex",
+            std::process::id()
+        ));
+        std::fs::write(&path, "deadbeef // first\n0badf00d\n").unwrap();
+        m.initial_contents_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        a.output("read_data", m.read_port_comb(a.input("read_addr", 1)));
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains(
+            "    initial begin
+        mem[0] = 32'hdeadbeef;
+        mem[1] = 32'hbadf00d;
+    end
+"
+        ));
+    }
+
+    #[test]
+    fn assertions() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let i = a.input("i", 4);
+        a.assert(i.ne(a.lit(0xfu32, 4)), "i must not be 0xf");
+        a.assert(i.bit(0), "i must be \"odd\" (100% of the time)");
+        a.output("o", i);
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains("    wire __assertion_0;\n"));
+        assert!(output.contains("    wire __assertion_1;\n"));
+        assert!(output.contains(
+            "`ifndef SYNTHESIS
+    always @(posedge clk) begin
+        assert (__assertion_0) else $error(\"i must not be 0xf\");
+        assert (__assertion_1) else $error(\"i must be \\\"odd\\\" (100%% of the time)\");
+    end
+`endif
+"
+        ));
+    }
+
+    #[test]
+    fn assumptions_and_covers() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let i = a.input("i", 4);
+        a.assert(i.ne(a.lit(0xfu32, 4)), "i must not be 0xf");
+        a.assume(i.lt(a.lit(0xcu32, 4)));
+        a.cover(i.eq(a.lit(0xbu32, 4)));
+        a.cover(i.eq(a.lit(0x0u32, 4)));
+        a.output("o", i);
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains("    wire __assumption_0;\n"));
+        assert!(output.contains("    wire __cover_0;\n"));
+        assert!(output.contains("    wire __cover_1;\n"));
+        assert!(output.contains(
+            "`ifndef SYNTHESIS
+    always @(posedge clk) begin
+        assert (__assertion_0) else $error(\"i must not be 0xf\");
+        assert (__assumption_0) else $error(\"Assumption violated\");
+    end
+`endif
+
+`ifdef FORMAL
+    assert property (@(posedge clk) __assertion_0);
+    assume property (@(posedge clk) __assumption_0);
+    cover property (@(posedge clk) __cover_0);
+    cover property (@(posedge clk) __cover_1);
+`endif
+"
+        ));
+    }
+
+    #[test]
+    fn traces() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let i = a.input("i", 4);
+        a.trace("i", i);
+        a.trace("i_plus_one", i + a.lit(1u32, 4));
+        a.output("o", i);
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains("    wire [3:0] __trace_0;\n"));
+        assert!(output.contains("    wire [3:0] __trace_1;\n"));
+        assert!(output.contains("    assign __trace_0 = i;\n"));
+        assert!(output.contains(
+            "`ifndef SYNTHESIS
+    always @(posedge clk) begin
+        $display(\"%m: i = %h\", __trace_0);
+        $display(\"%m: i_plus_one = %h\", __trace_1);
+    end
+`endif
+"
+        ));
+    }
+
+    #[test]
+    fn mem_masked_write_port() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let m = a.mem("mem", 2, 16);
+        m.write_port_masked(
+            a.input("write_addr", 2),
+            a.input("write_value", 16),
+            a.input("write_enable", 1),
+            a.input("write_mask", 2),
+        );
+        a.output("read_data", m.read_port_comb(a.input("read_addr", 2)));
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains("    wire [1:0] __mem_mem_write_port_0_mask;\n"));
+        assert!(output.contains("    assign __mem_mem_write_port_0_mask = write_mask;\n"));
+        assert!(output.contains(
+            "    always @(posedge clk) begin
+        if (__mem_mem_write_port_0_enable) begin
+            if (__mem_mem_write_port_0_mask[0]) begin
+                mem[__mem_mem_write_port_0_address][7:0] <= __mem_mem_write_port_0_value[7:0];
+            end
+            if (__mem_mem_write_port_0_mask[1]) begin
+                mem[__mem_mem_write_port_0_address][15:8] <= __mem_mem_write_port_0_value[15:8];
+            end
+        end
+    end
+"
+        ));
+    }
+
+    #[test]
+    fn mem_read_during_write_new() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let m = a.mem("mem", 2, 16);
+        m.read_during_write(ReadDuringWrite::New);
+        m.write_port_masked(
+            a.input("write_addr", 2),
+            a.input("write_value", 16),
+            a.input("write_enable", 1),
+            a.input("write_mask", 2),
+        );
+        a.output(
+            "read_data",
+            m.read_port(a.input("read_addr", 2), a.input("read_enable", 1)),
+        );
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains(
+            "        if (__mem_mem_read_port_0_enable) begin
+            __mem_mem_read_port_0_value <= mem[__mem_mem_read_port_0_address];
+            if (__mem_mem_write_port_0_enable && (__mem_mem_write_port_0_address == __mem_mem_read_port_0_address)) begin
+                if (__mem_mem_write_port_0_mask[0]) begin
+                    __mem_mem_read_port_0_value[7:0] <= __mem_mem_write_port_0_value[7:0];
+                end
+                if (__mem_mem_write_port_0_mask[1]) begin
+                    __mem_mem_read_port_0_value[15:8] <= __mem_mem_write_port_0_value[15:8];
+                end
+            end
+        end
+"
+        ));
+    }
+
+    #[test]
+    fn mem_read_during_write_old() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let m = a.mem("mem", 2, 16);
+        m.write_port(
+            a.input("write_addr", 2),
+            a.input("write_value", 16),
+            a.input("write_enable", 1),
+        );
+        a.output(
+            "read_data",
+            m.read_port(a.input("read_addr", 2), a.input("read_enable", 1)),
+        );
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains(
+            "        if (__mem_mem_read_port_0_enable) begin
+            __mem_mem_read_port_0_value <= mem[__mem_mem_read_port_0_address];
+        end
+    end
+
+    always @(posedge clk) begin
+        if (__mem_mem_write_port_0_enable) begin
+            mem[__mem_mem_write_port_0_address] <= __mem_mem_write_port_0_value;
+        end
+"
+        ));
+    }
+
+    #[test]
+    fn mem_multiple_write_ports() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let m = a.mem("mem", 2, 8);
+        m.write_port(
+            a.input("write_addr_0", 2),
+            a.input("write_value_0", 8),
+            a.input("write_enable_0", 1),
+        );
+        m.write_port(
+            a.input("write_addr_1", 2),
+            a.input("write_value_1", 8),
+            a.input("write_enable_1", 1),
+        );
+        a.output("read_data", m.read_port_comb(a.input("read_addr", 2)));
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        let port_0 = output
+            .find(
+                "        if (__mem_mem_write_port_0_enable) begin
+            if (!(__mem_mem_write_port_1_enable && (__mem_mem_write_port_1_address == __mem_mem_write_port_0_address))) begin
+                mem[__mem_mem_write_port_0_address] <= __mem_mem_write_port_0_value;
+            end
+        end
+",
+            )
+            .unwrap();
+        let port_1 = output
+            .find(
+                "        if (__mem_mem_write_port_1_enable) begin
+            mem[__mem_mem_write_port_1_address] <= __mem_mem_write_port_1_value;
+        end
+",
+            )
+            .unwrap();
+        assert!(port_0 < port_1);
+    }
+
+    #[test]
+    fn mem_read_port_enable() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let m = a.mem("mem", 2, 8);
+        m.initial_contents(&[0x12u8, 0x34u8, 0x56u8, 0x78u8]);
+        a.output(
+            "read_data",
+            m.read_port(a.input("read_addr", 2), a.input("read_enable", 1)),
+        );
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        assert_eq!(
+            std::str::from_utf8(&w).unwrap(),
+            "module A(
+    input wire reset_n,
+    input wire clk,
+
+    input wire [1:0] read_addr,
+    input wire read_enable,
+    output wire [7:0] read_data
+    );
+
+    wire [1:0] __mem_mem_read_port_0_address;
+    wire __mem_mem_read_port_0_enable;
+    reg [7:0] __mem_mem_read_port_0_value;
+
+    reg [7:0] mem[0:3];
+
+    initial begin
+        mem[0] = 8'h12;
+        mem[1] = 8'h34;
+        mem[2] = 8'h56;
+        mem[3] = 8'h78;
+    end
+
+    always @(posedge clk) begin
+        if (__mem_mem_read_port_0_enable) begin
+            __mem_mem_read_port_0_value <= mem[__mem_mem_read_port_0_address];
+        end
+    end
+
+    assign read_data = __mem_mem_read_port_0_value;
+    assign __mem_mem_read_port_0_address = read_addr;
+    assign __mem_mem_read_port_0_enable = read_enable;
+
+endmodule
+
+"
+        );
+    }
+
+    #[test]
+    fn dangling_logic_not_emitted() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let i = a.input("i", 32);
+        let _ = (i + a.lit(0xdeadbeefu32, 32)) ^ i;
+        a.output("o", i);
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(!output.contains("deadbeef"));
+        assert!(!output.contains("__temp"));
+    }
+
+    #[test]
+    fn eliminate_dead_logic() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let i = a.input("i", 1);
+
+        // Register that only feeds itself
+        let dead_reg = a.reg("dead_reg", 8);
+        dead_reg.drive_next(dead_reg.value + a.lit(0xabu32, 8));
+
+        // Memory whose read ports are never used
+        let dead_mem = a.mem("dead_mem", 1, 8);
+        dead_mem.initial_contents(&[0u32, 1]);
+        let _ = dead_mem.read_port(i, a.high());
+
+        // Memory with one live and one dead read port
+        let live_mem = a.mem("live_mem", 1, 8);
+        live_mem.initial_contents(&[2u32, 3]);
+        let _ = live_mem.read_port(!i, a.high());
+        let live_reg = a.reg("live_reg", 8);
+        live_reg.drive_next(live_mem.read_port(i, a.high()));
+        a.output("o", live_reg.value);
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains("dead_reg"));
+        assert!(output.contains("dead_mem"));
+        assert!(output.contains("__mem_live_mem_read_port_1_value"));
+
+        let mut w = Vec::new();
+        generate_with_options(
+            a,
+            &GenerationOptions {
+                eliminate_dead_logic: true,
+                ..Default::default()
+            },
+            &mut w,
+        )
+        .unwrap();
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(!output.contains("dead_reg"));
+        assert!(!output.contains("8'hab"));
+        assert!(!output.contains("dead_mem"));
+        assert!(output.contains("live_reg"));
+        assert!(!output.contains("__mem_live_mem_read_port_0"));
+        assert!(output.contains("__mem_live_mem_read_port_1_value"));
+    }
+
+    fn duplicated_tree<'a>(a: &'a Signal<'a>, b: &'a Signal<'a>, depth: u32) -> &'a Signal<'a> {
+        if depth == 0 {
+            a & b
+        } else {
+            // Each subtree is rebuilt from scratch, so without structural sharing this would produce 2^depth copies
+            duplicated_tree(a, b, depth - 1) + duplicated_tree(a, b, depth - 1)
+        }
+    }
+
+    #[test]
+    fn duplicated_expressions_emitted_once() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let o = duplicated_tree(a.input("a", 32), a.input("b", 32), 12);
+        a.output("o", o);
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        // One node for the leaf, one for each level of the tree, and one for the output
+        assert_eq!(output.matches("assign ").count(), 14);
+    }
+
+    #[test]
+    fn fan_out_expression_emitted_once() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("i", 8));
+
+        let m = c.module("A");
+        let shared = m.input("a", 8) + m.input("b", 8);
+        // Ten consumers of different kinds
+        for i in 0..7u32 {
+            m.output(format!("o{}", i), shared ^ m.lit(i, 8));
+        }
+        let r = m.reg("r", 8);
+        r.drive_next(shared);
+        m.output("r", r.value);
+        let inst = m.instance("inner", "Inner");
+        inst.drive_input("i", shared);
+        m.output("inner_o", inst.output("o"));
+        m.assert(shared.ne(m.lit(0u32, 8)), "shared must not be zero");
+
+        for &always_comb in &[false, true] {
+            let options = GenerationOptions {
+                always_comb,
+                ..Default::default()
+            };
+            let mut w = Vec::new();
+            generate_with_options(m, &options, &mut w).unwrap();
+            let output = String::from_utf8(w).unwrap();
+
+            assert_eq!(output.matches("a + b").count(), 1);
+
+            // Output is deterministic
+            let mut w = Vec::new();
+            generate_with_options(m, &options, &mut w).unwrap();
+            assert_eq!(String::from_utf8(w).unwrap(), output);
+        }
+    }
+
+    #[test]
+    fn constant_folding() {
+        let c = Context::new();
+
+        let a = c.module("A");
+        let i = a.input("i", 8);
+        a.output("sum", a.lit(1u32, 8) + a.lit(2u32, 8));
+        a.output("selected", a.mux(a.lit(1u32, 8).eq(a.lit(2u32, 8)), i, !i));
+
+        let mut w = Vec::new();
+        generate(a, &mut w).unwrap();
+
+        let output = std::str::from_utf8(&w).unwrap();
+        assert!(output.contains("    assign sum = 8'h3;\n"));
+        assert!(output.contains("    assign __temp_0 = ~i;\n"));
+        assert!(output.contains("    assign selected = __temp_0;\n"));
+        assert!(!output.contains("?"));
+    }
+
+    fn deterministic_output_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+        let inner = c.module("Inner");
+        let i = inner.input("i", 8);
+        let r = inner.reg("r", 8);
+        r.drive_next(i);
+        inner.output("o", r.value);
+
+        let m = c.module("Outer");
+        let mut acc = m.input("i", 8);
+        for index in 0..16 {
+            let r = m.reg(format!("r{}", index), 8);
+            r.default_value(index as u32);
+            r.drive_next(acc);
+            let mem = m.mem(format!("mem{}", index), 2, 8);
+            mem.write_port(acc.bits(1, 0), r.value, acc.bit(7));
+            let inst = m.instance(format!("inst{}", index), "Inner");
+            inst.drive_input("i", mem.read_port(r.value.bits(1, 0), acc.bit(0)));
+            acc = inst.output("o") ^ r.value;
+        }
+        m.output("o", acc);
+
+        m
+    }
+
+    #[test]
+    fn deterministic_output() {
+        let generate_text = || {
+            let c = Context::new();
+            let m = deterministic_output_test_module(&c);
+            let mut w = Vec::new();
+            generate(m, &mut w).unwrap();
+            String::from_utf8(w).unwrap()
+        };
+
+        let expected = generate_text();
+        for _ in 0..4 {
+            assert_eq!(generate_text(), expected);
+        }
+    }
+
+    #[test]
+    fn escaped_identifiers() {
+        let c = Context::new();
+
+        let inner = c.module("Inner-1");
+        inner.output("output", inner.input("input", 1));
+
+        let m = c.module("logic");
+        let mem = m.mem("reg", 1, 1);
+        mem.initial_contents(&[false, true]);
+        let inst = m.instance("wire", "Inner-1");
+        inst.drive_input("input", mem.read_port_comb(m.input("my input", 1)));
+        m.output("valid_name", inst.output("output"));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+
+        assert!(code.contains("module \\logic ("));
+        assert!(code.contains("input wire \\my_input ,"));
+        assert!(code.contains("output wire valid_name"));
+        assert!(code.contains("reg \\reg [0:1];"));
+        assert!(code.contains("\\Inner-1  \\wire (\n"));
+        assert!(code.contains(".\\input (__wire_input_input)"));
+        assert!(code.contains(".\\output (valid_name)"));
+    }
+
+    #[test]
+    fn negate_abs() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let i = m.input("i", 128);
+        m.output("n", i.negate());
+        m.output("a", i.abs());
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+
+        assert!(code.contains("assign __temp_1 = 128'h0 - i;"));
+        assert!(code.contains("assign __temp_2 = __temp_0 ? __temp_1 : i;"));
+    }
+
+    #[test]
+    fn forwarded_instance_outputs() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("i", 4));
+        inner.output("p", !inner.input("j", 1));
+
+        let m = c.module("Outer");
+        let inst = m.instance("inner", "Inner");
+        inst.drive_input("i", m.input("i", 4));
+        inst.drive_input("j", m.input("j", 1));
+        m.export_outputs(inst);
+        m.output("o_copy", inst.output("o"));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+
+        assert!(code.contains(".o(o)"));
+        assert!(code.contains(".p(p)"));
+        assert!(!code.contains("wire [3:0] __inner_output_o;"));
+        assert!(code.contains("assign o_copy = o;"));
+    }
+
+    #[test]
+    fn generate_hierarchy_order() {
+        let c = Context::new();
+
+        let m = c.module("NestedInner");
+        m.output("o", m.input("i", 32));
+
+        let m = c.module("Nested");
+        let inner = m.instance("inner", "NestedInner");
+        inner.drive_input("i", m.input("i1", 32) & m.input("i2", 32));
+        m.output("o", inner.output("o"));
+
+        let m = c.module("Top");
+        let inner1 = m.instance("inner1", "Nested");
+        inner1.drive_input("i1", m.input("i1", 32));
+        inner1.drive_input("i2", m.input("i2", 32));
+        let inner2 = m.instance("inner2", "Nested");
+        inner2.drive_input("i1", m.input("i3", 32));
+        inner2.drive_input("i2", m.input("i4", 32));
+        let inner3 = m.instance("inner3", "NestedInner");
+        inner3.drive_input("i", inner1.output("o") ^ inner2.output("o"));
+        m.output("o", inner3.output("o"));
+
+        let mut w = Vec::new();
+        generate_hierarchy(m, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+
+        let module_indices = ["NestedInner", "Nested", "Top"]
+            .iter()
+            .map(|name| {
+                let decl = format!("module {}(", name);
+                assert_eq!(code.matches(&decl).count(), 1);
+                code.find(&decl).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert!(module_indices[0] < module_indices[1] && module_indices[1] < module_indices[2]);
+    }
+
+    #[test]
+    fn generate_hierarchy_module_once() {
+        fn adder<'a>(c: &'a Context<'a>, bit_width: u32) -> &'a Module<'a> {
+            c.module_once(format!("Adder{}", bit_width), |m| {
+                m.output("sum", m.input("a", bit_width) + m.input("b", bit_width));
+            })
+        }
+
+        fn parent<'a>(c: &'a Context<'a>, name: &str) -> &'a Module<'a> {
+            let adder = adder(c, 8);
+            let m = c.module(name);
+            let inner = m.instance("adder", adder.name());
+            inner.drive_input("a", m.input("a", 8));
+            inner.drive_input("b", m.input("b", 8));
+            m.output("sum", inner.output("sum"));
+            m
+        }
+
+        let c = Context::new();
+
+        let left = parent(&c, "Left");
+        let right = parent(&c, "Right");
+
+        let m = c.module("Top");
+        let left = m.instance("left", left.name());
+        left.drive_input("a", m.input("a", 8));
+        left.drive_input("b", m.input("b", 8));
+        let right = m.instance("right", right.name());
+        right.drive_input("a", left.output("sum"));
+        right.drive_input("b", m.input("c", 8));
+        m.output("sum", right.output("sum"));
+
+        let mut w = Vec::new();
+        generate_hierarchy(m, &mut w).unwrap();
+        let code = String::from_utf8(w).unwrap();
+
+        assert_eq!(code.matches("module Adder8(").count(), 1);
+        assert_eq!(code.matches("Adder8 adder(").count(), 2);
+    }
+
+    #[test]
+    fn latch() {
+        let c = Context::new();
+
+        let m = c.module("Latched");
+        let l = m.latch("l", 4);
+        l.drive_data(m.input("data", 4));
+        l.drive_enable(m.input("enable", 1));
+        m.output("o", l.value);
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            "module Latched(
+    input wire [3:0] data,
+    input wire enable,
+    output wire [3:0] o
+    );
+
+    reg [3:0] __latch_l_0;
+    wire [3:0] __latch_l_0_data;
+    wire __latch_l_0_enable;
+
+    always @(*) begin
+        if (__latch_l_0_enable) begin
+            __latch_l_0 <= __latch_l_0_data;
+        end
+    end
+
+    assign o = __latch_l_0;
+    assign __latch_l_0_data = data;
+    assign __latch_l_0_enable = enable;
+
+endmodule
+
+"
+        );
+
+        let mut w = Vec::new();
+        let options = GenerationOptions {
+            always_comb: true,
+            ..Default::default()
+        };
+        generate_with_options(m, &options, &mut w).unwrap();
+        assert!(String::from_utf8(w)
+            .unwrap()
+            .contains("always_latch begin\n"));
+    }
+
+    #[test]
+    fn combinational_module_ports() {
+        let c = Context::new();
+
+        let m = c.module("Comb");
+        m.output("o", !m.input("i", 4));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            "module Comb(
+    input wire [3:0] i,
+    output wire [3:0] o
+    );
+
+    wire [3:0] __temp_0;
+
+    assign __temp_0 = ~i;
+    assign o = __temp_0;
+
+endmodule
+
+"
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let c = Context::new();
+
+        let m = c.module("Commented");
+        let sum = (m.input("a", 4) + m.input("b", 4)).name("sum");
+        let sum_line = line!() - 1;
+        let r = m.reg("r", 4);
+        let r_line = line!() - 1;
+        r.drive_next(!sum);
+        let not_line = line!() - 1;
+        m.output("o", r.value ^ sum);
+        let xor_line = line!() - 1;
+
+        let options = GenerationOptions {
+            comments: true,
+            ..Default::default()
+        };
+        let mut w = Vec::new();
+        generate_with_options(m, &options, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            format!(
+                "module Commented(
+    input wire reset_n,
+    input wire clk,
+
+    input wire [3:0] a,
+    input wire [3:0] b,
+    output wire [3:0] o
+    );
+
+    // register r ({file}:{r_line}:19)
+    reg [3:0] __reg_r_0;
+    wire [3:0] __reg_r_0_next;
+
+    always @(posedge clk) begin
+        __reg_r_0 <= __reg_r_0_next;
+    end
+
+    // sum ({file}:{sum_line}:19)
+    wire [3:0] __temp_0;
+    // {file}:{xor_line}:23
+    wire [3:0] __temp_1;
+    // {file}:{not_line}:22
+    wire [3:0] __temp_2;
+
+    assign __temp_0 = a + b; // output o
+    assign __temp_1 = __reg_r_0 ^ __temp_0; // output o
+    assign o = __temp_1; // output o
+    assign __temp_2 = ~__temp_0; // register r
+    assign __reg_r_0_next = __temp_2; // register r
+
+endmodule
+
+",
+                file = file!(),
+                r_line = r_line,
+                sum_line = sum_line,
+                not_line = not_line,
+                xor_line = xor_line
+            )
+        );
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        assert!(!String::from_utf8(w).unwrap().contains("//"));
+    }
+
+    #[test]
+    fn localparams() {
+        let c = Context::new();
+
+        let m = c.module("LocalParams");
+        let i = m.input("i", 32);
+        let magic = m.constant("MAGIC", 0xdeadbeefu32, 32);
+        m.output("MAGIC", i.eq(magic));
+        m.output(
+            "xor_magic",
+            i ^ m.constant("MAGIC_ALIAS", 0xdeadbeefu32, 32),
+        );
+        m.output("sum", i + m.lit(0x1234u32, 16).concat(m.lit(0x5678u32, 16)));
+        m.output("diff", i - m.lit(0x12345678u32, 32));
+        m.output("low_bits", i.bits(3, 0) & m.lit(0xau32, 4));
+
+        let mut w = Vec::new();
+        generate_with_options(
+            m,
+            &GenerationOptions {
+                localparam_bit_width_threshold: Some(4),
+                ..Default::default()
+            },
+            &mut w,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            "module LocalParams(
+    input wire [31:0] i,
+    output wire MAGIC,
+    output wire [31:0] diff,
+    output wire [3:0] low_bits,
+    output wire [31:0] sum,
+    output wire [31:0] xor_magic
+    );
+
+    localparam [31:0] MAGIC_0 = 32'hdeadbeef;
+    localparam [31:0] __const_0 = 32'h12345678;
+    localparam [31:0] MAGIC_ALIAS = 32'hdeadbeef;
+
+    wire __temp_0;
+    wire [31:0] __temp_1;
+    wire [3:0] __temp_2;
+    wire [3:0] __temp_3;
+    wire [31:0] __temp_4;
+    wire [31:0] __temp_5;
+
+    assign __temp_0 = i == MAGIC_0;
+    assign MAGIC = __temp_0;
+    assign __temp_1 = i - __const_0;
+    assign diff = __temp_1;
+    assign __temp_2 = i[3:0];
+    assign __temp_3 = __temp_2 & 4'ha;
+    assign low_bits = __temp_3;
+    assign __temp_4 = i + __const_0;
+    assign sum = __temp_4;
+    assign __temp_5 = i ^ MAGIC_ALIAS;
+    assign xor_magic = __temp_5;
+
+endmodule
+
+"
+        );
+    }
+
+    #[test]
+    fn named_constants_are_distinct_from_literals() {
+        let c = Context::new();
+
+        let m = c.module("NamedConstants");
+        let i = m.input("i", 8);
+        let magic = m.constant("MAGIC", 5u32, 8);
+        let other = m.constant("OTHER", 5u32, 8);
+        m.output("plus_lit", i + m.lit(5u32, 8));
+        m.output("xor_magic", i ^ magic);
+        m.output("minus_other", i - other);
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+
+        // Each constant is only referred to where it was used, and the plain literal stays a literal
+        assert!(verilog.contains("localparam [7:0] MAGIC = 8'h5;"));
+        assert!(verilog.contains("localparam [7:0] OTHER = 8'h5;"));
+        assert!(verilog.contains(" = i + 8'h5;"));
+        assert!(verilog.contains(" = i ^ MAGIC;"));
+        assert!(verilog.contains(" = i - OTHER;"));
+    }
+
+    #[test]
+    fn generated_name_collisions() {
+        let c = Context::new();
+
+        let inner = c.module("Inner");
+        inner.output("o", inner.input("i", 1));
+
+        let m = c.module("Collisions");
+        let r = m.reg("r", 1);
+        r.drive_next(!m.input("__reg_r_0", 1));
+        let inst = m.instance("inner", "Inner");
+        inst.drive_input("i", m.input("__inner_input_i", 1) ^ r.value);
+        let ram = m.mem("ram", 1, 1);
+        ram.write_port(r.value, r.value, m.high());
+        m.output(
+            "__temp_0",
+            inst.output("o") & ram.read_port(r.value, m.high()),
+        );
+        m.output("ram", r.value);
+        m.output("inner", m.input("__reg_r_0_next", 1));
+        m.assert(r.value, "r");
+        m.output("__assertion_0", m.low());
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+
+        let mut declared_names = HashSet::new();
+        for line in verilog.lines() {
+            let line = line.trim();
+            let is_decl = ["input ", "output ", "wire ", "reg ", "localparam "]
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+                || (line.starts_with("Inner ") && line.ends_with('('));
+            if !is_decl {
+                continue;
+            }
+            let name = line
+                .trim_end_matches([',', ';', '('])
+                .split_whitespace()
+                .last()
+                .unwrap();
+            let name = name.split('[').next().unwrap().to_string();
+            assert!(
+                declared_names.insert(name.clone()),
+                "\"{}\" is declared more than once:\n{}",
+                name,
+                verilog
+            );
+        }
+        for name in ["__reg_r_0", "__inner_input_i", "__temp_0", "ram", "inner"].iter() {
+            assert!(declared_names.contains(*name));
+        }
+        assert!(verilog.contains("    reg __reg_r_0_0;\n"));
+        assert!(verilog.contains("    reg ram_0[0:1];\n"));
+        assert!(verilog.contains("Inner inner_0(\n"));
+    }
+
+    #[test]
+    fn combinational_instance_ports() {
+        let c = Context::new();
+
+        let comb = c.module("Comb");
+        comb.output("o", !comb.input("i", 4));
+
+        let m = c.module("Registered");
+        let r = m.reg("r", 4);
+        let inst = m.instance("comb", "Comb");
+        inst.drive_input("i", r.value);
+        r.drive_next(inst.output("o"));
+        m.output("o", r.value);
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            "module Registered(
+    input wire reset_n,
+    input wire clk,
+
+    output wire [3:0] o
+    );
+
+    wire [3:0] __comb_input_i;
+    wire [3:0] __comb_output_o;
+    reg [3:0] __reg_r_0;
+    wire [3:0] __reg_r_0_next;
+
+    Comb comb(
+        .i(__comb_input_i),
+        .o(__comb_output_o));
+
+    always @(posedge clk) begin
+        __reg_r_0 <= __reg_r_0_next;
+    end
+
+    assign o = __reg_r_0;
+    assign __comb_input_i = __reg_r_0;
+    assign __reg_r_0_next = __comb_output_o;
+
+endmodule
+
+"
+        );
+    }
+
+    fn always_comb_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+        let m = c.module("AlwaysComb");
+        let a = m.input("a", 8);
+        let b = m.input("b", 8);
+        let op = m.input("op", 2);
+        let sum = a + b;
+        let result = if_(op.eq(m.lit(0u32, 2)), sum)
+            .else_if(op.eq(m.lit(1u32, 2)), a - b)
+            .else_if(op.eq(m.lit(2u32, 2)), a & b)
+            .else_(a | b);
+        m.output("result", result);
+        m.output("sum_is_zero", sum.eq(m.lit(0u32, 8)));
+        m
+    }
+
+    #[test]
+    fn always_comb_default() {
+        let c = Context::new();
+
+        let m = always_comb_test_module(&c);
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            include_str!("verilog/golden/always_comb_default.v")
+        );
+    }
+
+    #[test]
+    fn always_comb() {
+        let c = Context::new();
+
+        let m = always_comb_test_module(&c);
+
+        let mut w = Vec::new();
+        generate_with_options(
+            m,
+            &GenerationOptions {
+                always_comb: true,
+                ..Default::default()
+            },
+            &mut w,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            include_str!("verilog/golden/always_comb.v")
+        );
+    }
+
+    fn uninitialized_registers_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+        let m = c.module("UninitializedRegisters");
+        let flag = m.reg("flag", 1);
+        flag.drive_next(m.input("flag_in", 1));
+        m.output("flag", flag.value);
+        let count = m.reg("count", 8);
+        count.default_value(0u32);
+        count.drive_next(count.value + m.lit(1u32, 8));
+        m.output("count", count.value);
+        let wide = m.reg("wide", 100);
+        wide.drive_next(m.input("wide_in", 100));
+        m.output("wide", wide.value);
+        m
+    }
+
+    #[test]
+    fn uninitialized_registers_undefined() {
+        let c = Context::new();
+
+        let m = uninitialized_registers_test_module(&c);
+
+        crate::golden::assert_verilog_golden(
+            m,
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/src/verilog/golden/uninitialized_registers_undefined.v"
+            ),
+        );
+    }
+
+    #[test]
+    fn uninitialized_registers_zero() {
+        let c = Context::new();
+
+        let m = uninitialized_registers_test_module(&c);
+
+        let mut w = Vec::new();
+        generate_with_options(
+            m,
+            &GenerationOptions {
+                uninitialized_registers: UninitializedRegisters::Zero,
+                ..Default::default()
+            },
+            &mut w,
+        )
+        .unwrap();
+        crate::golden::assert_golden(
+            &String::from_utf8(w).unwrap(),
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/src/verilog/golden/uninitialized_registers_zero.v"
+            ),
+        );
+    }
+
+    #[test]
+    fn uninitialized_registers_random() {
+        let c = Context::new();
+
+        let m = uninitialized_registers_test_module(&c);
+
+        let generate_with_seed = |seed| {
+            let mut w = Vec::new();
+            generate_with_options(
+                m,
+                &GenerationOptions {
+                    uninitialized_registers: UninitializedRegisters::Random { seed },
+                    ..Default::default()
+                },
+                &mut w,
+            )
+            .unwrap();
+            String::from_utf8(w).unwrap()
+        };
+
+        let verilog = generate_with_seed(0x1234);
+        crate::golden::assert_golden(
+            &verilog,
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/src/verilog/golden/uninitialized_registers_random.v"
+            ),
+        );
+        assert_eq!(generate_with_seed(0x1234), verilog);
+        assert_ne!(generate_with_seed(0x1235), verilog);
+    }
+
+    #[test]
+    fn generate_to_dir() {
+        let c = Context::new();
+
+        let leaf = c.module("Leaf");
+        leaf.output("o", !leaf.input("i", 1));
+
+        let middle = c.module("Middle");
+        let leaf1 = middle.instance("leaf1", "Leaf");
+        leaf1.drive_input("i", middle.input("i", 1));
+        let leaf2 = middle.instance("leaf2", "Leaf");
+        leaf2.drive_input("i", leaf1.output("o"));
+        middle.output("o", leaf2.output("o"));
+
+        let m = c.module("Top");
+        let middle1 = m.instance("middle1", "Middle");
+        middle1.drive_input("i", m.input("i", 1));
+        let leaf3 = m.instance("leaf3", "Leaf");
+        leaf3.drive_input("i", middle1.output("o"));
+        m.output("o", leaf3.output("o"));
+
+        let dir = std::env::temp_dir().join(format!("kaze_generate_to_dir_{}", std::process::id()));
+        // Nested to check that missing parent directories are created
+        let path = dir.join("out");
+        super::generate_to_dir(m, &path).unwrap();
+
+        let mut file_names = fs::read_dir(&path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        file_names.sort();
+        assert_eq!(file_names, ["Leaf.v", "Middle.v", "Top.v", "filelist.f"]);
+        assert_eq!(
+            fs::read_to_string(path.join("filelist.f")).unwrap(),
+            "Leaf.v\nMiddle.v\nTop.v\n"
+        );
+        for module in [leaf, middle, m].iter() {
+            let mut w = Vec::new();
+            generate(module, &mut w).unwrap();
+            assert_eq!(
+                fs::read(path.join(format!("{}.v", module.name))).unwrap(),
+                w
+            );
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn generate_to_dir_invalid_file_name_error() {
+        let c = Context::new();
+
+        let inner = c.module("../Inner");
+        inner.output("o", !inner.input("i", 1));
+
+        let m = c.module("Outer");
+        let inner1 = m.instance("inner1", "../Inner");
+        inner1.drive_input("i", m.input("i", 1));
+        m.output("o", inner1.output("o"));
+
+        let dir = std::env::temp_dir().join(format!(
+            "kaze_generate_to_dir_invalid_file_name_error_{}",
+            std::process::id()
+        ));
+        let path = dir.join("out");
+        let error = super::generate_to_dir(m, &path).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("../Inner.v"));
+        assert!(!dir.exists());
+    }
+
+    fn instance_array_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+        let lane = c.module("Lane");
+        let acc = lane.reg("acc", 8);
+        acc.default_value(0u32);
+        let sum = acc.value + lane.input("i", 8);
+        acc.drive_next(if_(lane.input("en", 1), sum).else_(acc.value));
+        lane.output("acc", acc.value);
+        lane.output("carry", sum.lt(acc.value));
+
+        let m = c.module("Lanes");
+        let i = m.input("i", 40);
+        let en = m.input("en", 1);
+        let lanes = m.instance_array("lane", "Lane", 4);
+        for (index, lane) in lanes.iter().enumerate() {
+            let index = index as u32;
+            lane.drive_input("en", en);
+            lane.drive_input("i", i.bits(index * 8 + 15, index * 8 + 8));
+        }
+        m.output(
+            "acc",
+            lanes[3]
+                .output("acc")
+                .concat(lanes[2].output("acc"))
+                .concat(lanes[1].output("acc"))
+                .concat(lanes[0].output("acc")),
+        );
+        m.output(
+            "carry",
+            lanes
+                .iter()
+                .fold(m.low(), |acc, lane| acc | lane.output("carry")),
+        );
+        m
+    }
+
+    #[test]
+    fn instance_array() {
+        let c = Context::new();
+
+        let m = instance_array_test_module(&c);
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+        assert_eq!(verilog, include_str!("verilog/golden/instance_array.v"));
+        assert_eq!(verilog.matches("generate").count(), 2);
+        assert!(!verilog.contains("lane_0"));
+    }
+
+    #[test]
+    fn instance_array_fallback() {
+        let c = Context::new();
+
+        let lane = c.module("Lane");
+        lane.output("o", !lane.input("i", 4));
+
+        let m = c.module("Lanes");
+        let i = m.input("i", 16);
+        let lanes = m.instance_array("lane", "Lane", 4);
+        // Reversed lane order can't be expressed as an index-dependent slice
+        for (index, lane) in lanes.iter().enumerate() {
+            let index = 3 - index as u32;
+            lane.drive_input("i", i.bits(index * 4 + 3, index * 4));
+        }
+        m.output(
+            "o",
+            lanes[0]
+                .output("o")
+                .concat(lanes[1].output("o"))
+                .concat(lanes[2].output("o"))
+                .concat(lanes[3].output("o")),
+        );
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+        assert!(!verilog.contains("generate"));
+        for index in 0..4 {
+            assert!(verilog.contains(&format!("Lane lane_{}(", index)));
+        }
+    }
+
+    #[test]
+    fn extern_module() {
+        let c = Context::new();
+
+        let pll = c.extern_module("PLLE2_BASE");
+        pll.input("CLKIN1", 1);
+        pll.input("RST", 1);
+        pll.extern_output("CLKOUT0", 1);
+        pll.extern_output("LOCKED", 1);
+
+        let buf = c.extern_module("Buf");
+        buf.input("i", 4);
+        buf.extern_output("o", 4);
+
+        let m = c.module("Top");
+        let pll_inst = m.instance("pll", "PLLE2_BASE");
+        pll_inst.parameter("BANDWIDTH", "OPTIMIZED");
+        pll_inst.parameter("CLKFBOUT_MULT", 10);
+        pll_inst.parameter("CLKIN1_PERIOD", 10.0);
+        pll_inst.parameter("CLKOUT0_PHASE", -90.5);
+        pll_inst.parameter("DIVCLK_DIVIDE", -1);
+        pll_inst.parameter("NOTE", "say \"hi\"");
+        pll_inst.drive_input("CLKIN1", m.input("clk_in", 1));
+        pll_inst.drive_input("RST", m.low());
+        m.output(
+            "clk_out",
+            pll_inst.output("CLKOUT0") & pll_inst.output("LOCKED"),
+        );
+
+        // Instance arrays of extern modules share their parameters across all lanes
+        let i = m.input("i", 8);
+        let bufs = m.instance_array("bufs", "Buf", 2);
+        for (index, buf) in bufs.iter().enumerate() {
+            let index = index as u32;
+            buf.parameter("DRIVE", 12);
+            buf.drive_input("i", i.bits(index * 4 + 3, index * 4));
+        }
+        m.output("o", bufs[1].output("o").concat(bufs[0].output("o")));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+        assert_eq!(verilog, include_str!("verilog/golden/extern_module.v"));
+
+        let mut w = Vec::new();
+        generate_hierarchy(m, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+        assert!(!verilog.contains("module PLLE2_BASE"));
+        assert!(!verilog.contains("module Buf"));
+        assert_eq!(verilog.matches("endmodule").count(), 1);
+    }
+
+    #[test]
+    fn extern_module_array_parameter_fallback() {
+        let c = Context::new();
+
+        let buf = c.extern_module("Buf");
+        buf.input("i", 1);
+        buf.extern_output("o", 1);
+
+        let m = c.module("Top");
+        let i = m.input("i", 2);
+        let bufs = m.instance_array("bufs", "Buf", 2);
+        for (index, buf) in bufs.iter().enumerate() {
+            // Parameters that differ per lane can't be expressed in a generate loop
+            buf.parameter("DRIVE", 4 * (index as u32 + 1));
+            buf.drive_input("i", i.bit(index as u32));
+        }
+        m.output("o", bufs[1].output("o").concat(bufs[0].output("o")));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+        assert!(!verilog.contains("generate"));
+        assert!(verilog.contains(".DRIVE(4)"));
+        assert!(verilog.contains(".DRIVE(8)"));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate Verilog code for module \"Buf\" because it's an extern module, which must be defined outside of kaze."
+    )]
+    fn extern_module_generate_error() {
+        let c = Context::new();
+
+        let buf = c.extern_module("Buf");
+        buf.input("i", 1);
+        buf.extern_output("o", 1);
+
+        // Panic
+        generate(buf, Vec::new()).unwrap();
+    }
+
+    fn clock_reset_port_names_test_module<'a>(c: &'a Context<'a>) -> &'a Module<'a> {
+        let child = c.module("Child");
+        child.clock_port_name("i_clk");
+        child.reset_port_name("i_rst_n");
+        let r = child.reg("r", 4);
+        r.default_value(0u32);
+        r.drive_next(child.input("i", 4));
+        child.output("o", r.value);
+
+        let m = c.module("Parent");
+        m.clock_port_name("sys_clk");
+        m.reset_port_name("sys_rst_n");
+        let child = m.instance("child", "Child");
+        child.drive_input("i", m.input("i", 4));
+        let r = m.reg("r", 4);
+        r.drive_next(child.output("o"));
+        m.output("o", r.value);
+        m.assert(r.value.ne(m.lit(0xfu32, 4)), "r is never 0xf");
+
+        m
+    }
+
+    #[test]
+    fn clock_reset_port_names() {
+        let c = Context::new();
+
+        let m = clock_reset_port_names_test_module(&c);
+
+        let mut w = Vec::new();
+        generate_hierarchy(m, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            include_str!("verilog/golden/clock_reset_port_names.v")
+        );
+    }
+
+    #[test]
+    fn clock_reset_port_names_options() {
+        let c = Context::new();
+
+        let child = c.module("Child");
+        child.clock_port_name("i_clk");
+        let r = child.reg("r", 1);
+        r.drive_next(child.input("i", 1));
+        child.output("o", r.value);
+
+        let m = c.module("Parent");
+        let child = m.instance("child", "Child");
+        child.drive_input("i", m.input("i", 1));
+        m.output("o", child.output("o"));
+
+        let options = GenerationOptions {
+            clock_port_name: Some("i_clk".into()),
+            reset_port_name: Some("i_rst_n".into()),
+            ..Default::default()
+        };
+        let mut w = Vec::new();
+        generate_with_options(m, &options, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+        assert!(verilog.contains("input wire i_rst_n,\n    input wire i_clk,"));
+        assert!(verilog.contains(".i_rst_n(i_rst_n),\n        .i_clk(i_clk),"));
+        assert!(!verilog.contains("reset_n("));
+
+        // Modules' own names take priority over the options
+        let options = GenerationOptions {
+            clock_port_name: Some("clock".into()),
+            ..Default::default()
+        };
+        let mut w = Vec::new();
+        generate_with_options(m, &options, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+        assert!(verilog.contains("input wire clock,"));
+        assert!(verilog.contains(".reset_n(reset_n),\n        .i_clk(clock),"));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate code for module \"A\" because it has a port called \"clock\", which is also the name of its clock or reset port."
+    )]
+    fn clock_port_name_collision_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        m.clock_port_name("clock");
+        let r = m.reg("r", 1);
+        r.drive_next(m.input("clock", 1));
+        m.output("o", r.value);
+
+        // Panic
+        generate(m, Vec::new()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot generate code for module \"A\" because its clock and reset ports would both be called \"clk\"."
+    )]
+    fn reset_port_name_same_as_clock_error() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        m.reset_port_name("clk");
+        let r = m.reg("r", 1);
+        r.drive_next(m.input("i", 1));
+        m.output("o", r.value);
+
+        // Panic
+        generate(m, Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn testbench_clock_reset_port_names() {
+        let c = Context::new();
+
+        let m = clock_reset_port_names_test_module(&c);
+
+        let mut w = Vec::new();
+        generate_testbench(m, &mut w).unwrap();
+        let testbench = String::from_utf8(w).unwrap();
+        assert!(testbench.contains("reg sys_rst_n;"));
+        assert!(testbench.contains("forever #5 sys_clk = ~sys_clk;"));
+        assert!(testbench.contains(".sys_rst_n(sys_rst_n),"));
+        assert!(!testbench.contains("reset_n"));
+    }
+
+    #[test]
+    fn testbench() {
+        let c = Context::new();
+
+        let m = c.module("Counter");
+        let count = m.reg("count", 8);
+        count.default_value(0u32);
+        count.drive_next(if_(m.input("en", 1), count.value + m.lit(1u32, 8)).else_(count.value));
+        m.output("count", count.value);
+        m.output("wrapped", count.value.eq(m.lit(0xffu32, 8)));
+        m.output("dut", m.input("step", 4));
+
+        let mut w = Vec::new();
+        generate_testbench(m, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            include_str!("verilog/golden/testbench.v")
+        );
+    }
+
+    #[test]
+    fn testbench_combinational() {
+        let c = Context::new();
+
+        let m = c.module("Inverter");
+        m.output("o", !m.input("i", 1));
+
+        let mut w = Vec::new();
+        generate_testbench(m, &mut w).unwrap();
+        let testbench = String::from_utf8(w).unwrap();
+        assert!(!testbench.contains("clk"));
+        assert!(!testbench.contains("reset_n"));
+        assert!(testbench.contains("Inverter dut(\n        .i(i),\n        .o(o));"));
+        assert!(testbench.contains("#100;"));
+    }
+
+    #[test]
+    fn code_style() {
+        let c = Context::new();
+
+        let m = c.module("CodeStyle");
+        let inputs = (0..8)
+            .map(|i| m.input(format!("a_rather_long_input_name_{}", i), 16))
+            .collect::<Vec<_>>();
+        let sum = inputs.iter().skip(1).fold(inputs[0], |acc, &i| acc + i);
+        let wide = inputs
+            .iter()
+            .skip(1)
+            .fold(inputs[0], |acc, &i| acc.concat(i));
+        m.output("sum", sum ^ (inputs[0] & inputs[1] & inputs[2] & inputs[3]));
+        m.output("wide", wide);
+        m.output(
+            "selected",
+            inputs[0]
+                .lt(inputs[1])
+                .mux(inputs[2] | inputs[3] | inputs[4], inputs[5] - inputs[6]),
+        );
+
+        let options = GenerationOptions {
+            code_style: CodeStyle::new().indent("  ").max_line_width(60),
+            ..Default::default()
+        };
+        let mut w = Vec::new();
+        generate_with_options(m, &options, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+        for line in verilog.lines() {
+            assert!(line.len() <= 60, "Line is too long: \"{}\"", line);
+        }
+        assert!(verilog.contains("\n  input wire [15:0] a_rather_long_input_name_0,\n"));
+
+        // Wrapping only changes whitespace
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let unwrapped = String::from_utf8(w).unwrap();
+        assert!(unwrapped.lines().any(|line| line.len() > 60));
+        let tokens = |s: &str| {
+            s.split_whitespace()
+                .map(|token| token.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tokens(&verilog), tokens(&unwrapped));
+
+        let options = GenerationOptions {
+            code_style: CodeStyle::new().line_ending(LineEnding::CrLf),
+            ..Default::default()
+        };
+        let mut w = Vec::new();
+        generate_with_options(m, &options, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            unwrapped.replace('\n', "\r\n")
+        );
+    }
+
+    #[test]
+    fn mem_single_port() {
+        let c = Context::new();
+
+        let m = c.module("SinglePort");
+        let ram = m.mem("ram", 4, 8);
+        let addr = m.input("addr", 4);
+        ram.write_port(addr, m.input("write_data", 8), m.input("write_enable", 1));
+        m.output("read_data", ram.read_port(addr, m.input("read_enable", 1)));
+
+        let mut w = Vec::new();
+        generate_with_options(
+            m,
+            &GenerationOptions {
+                ram_style: Some("block".into()),
+                ..Default::default()
+            },
+            &mut w,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            include_str!("verilog/golden/single_port.v")
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let c = Context::new();
+
+        let m = c.module("Attributes");
+        let r = m.reg("r", 1);
+        r.attribute("max_fanout", "16");
+        r.attribute("comment", "a \"quoted\" \\ value");
+        r.drive_next(!r.value);
+        let ram = m.mem("ram", 1, 1);
+        ram.attribute("ram_style", "distributed");
+        ram.initial_contents(&[false, true]);
+        m.output("o", r.value & ram.read_port(r.value, m.high()));
+
+        let mut w = Vec::new();
+        generate_with_options(
+            m,
+            &GenerationOptions {
+                ram_style: Some("block".into()),
+                ..Default::default()
+            },
+            &mut w,
+        )
+        .unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+        assert!(verilog.contains(
+            "    (* max_fanout = \"16\" *)
+    (* comment = \"a \\\"quoted\\\" \\\\ value\" *)
+    reg __reg_r_0;
+"
+        ));
+        assert!(verilog.contains(
+            "    (* ram_style = \"distributed\" *)
+    reg ram[0:1];
+"
+        ));
+        assert!(!verilog.contains("block"));
+    }
+
+    #[test]
+    fn synchronizer_attributes() {
+        let c = Context::new();
+
+        let m = c.module("Synchronizer");
+        m.output("o", m.synchronizer("sync", m.input("i", 1), 2));
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        let verilog = String::from_utf8(w).unwrap();
+        assert!(verilog.contains(
+            "    (* ASYNC_REG = \"TRUE\" *)
+    reg __reg_sync_stage0_0;
+"
+        ));
+        assert!(verilog.contains(
+            "    (* ASYNC_REG = \"TRUE\" *)
+    reg __reg_sync_stage1_1;
+"
+        ));
+    }
+
+    #[test]
+    fn mem_simple_dual_port() {
+        let c = Context::new();
+
+        let m = c.module("SimpleDualPort");
+        let ram = m.mem("ram", 4, 8);
+        ram.write_port(
+            m.input("write_addr", 4),
+            m.input("write_data", 8),
+            m.input("write_enable", 1),
+        );
+        m.output(
+            "read_data",
+            ram.read_port(m.input("read_addr", 4), m.input("read_enable", 1)),
+        );
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            include_str!("verilog/golden/simple_dual_port.v")
+        );
+    }
+
+    #[test]
+    fn mem_true_dual_port() {
+        let c = Context::new();
+
+        let m = c.module("TrueDualPort");
+        let ram = m.mem("ram", 4, 8);
+        for port in ["a", "b"].iter() {
+            let addr = m.input(format!("{}_addr", port), 4);
+            ram.write_port(
+                addr,
+                m.input(format!("{}_write_data", port), 8),
+                m.input(format!("{}_write_enable", port), 1),
+            );
+            m.output(
+                format!("{}_read_data", port),
+                ram.read_port(addr, m.input(format!("{}_read_enable", port), 1)),
+            );
+        }
+
+        let mut w = Vec::new();
+        generate(m, &mut w).unwrap();
+        assert_eq!(
+            String::from_utf8(w).unwrap(),
+            include_str!("verilog/golden/true_dual_port.v")
+        );
+    }
+
+    #[test]
+    fn deep_signal_chain() {
+        let c = Context::new();
+
+        let m = c.module("A");
+        let a = m.input("a", 8);
+        // A chain of ~100k signals, which overflows the stack if code generation recurses through it
+        let mut x = a;
+        for _ in 0..34_000 {
+            x = (x + a).bits(6, 0).concat(a.bit(0));
+        }
+        m.output("o", x);
+
+        generate(m, std::io::sink()).unwrap();
+    }
+
+    type CorpusModuleFn = for<'a> fn(&'a Context<'a>) -> &'a Module<'a>;
+
+    // Representative modules covering every kind of signal and state element, checked against the golden files in `verilog/golden/corpus` (rerun with `UPDATE_GOLDENS=1` to update them)
+    #[test]
+    fn golden_corpus() {
+        let corpus: &[(&str, CorpusModuleFn)] = &[
+            ("lit", |c| {
+                let m = c.module("Lit");
+                m.output("narrow", m.lit(0xa5u32, 8));
+                m.output("wide", m.lit(0x1234_5678_9abc_def0u64, 100));
+                m
+            }),
+            ("input", |c| {
+                let m = c.module("Input");
+                m.output("o", m.input("i", 16));
+                m
+            }),
+            ("reg_with_reset", |c| {
+                let m = c.module("RegWithReset");
+                let r = m.reg("r", 8);
+                r.default_value(0x42u32);
+                r.drive_next(m.input("i", 8));
+                m.output("o", r.value);
+                m
+            }),
+            ("reg_without_reset", |c| {
+                let m = c.module("RegWithoutReset");
+                let r = m.reg("r", 8);
+                r.drive_next(m.input("i", 8));
+                m.output("o", r.value);
+                m
+            }),
+            ("un_op", |c| {
+                let m = c.module("UnOp");
+                m.output("not", !m.input("i", 4));
+                m
+            }),
+            ("simple_bin_op", |c| {
+                let m = c.module("SimpleBinOp");
+                let lhs = m.input("lhs", 4);
+                let rhs = m.input("rhs", 4);
+                m.output("bitand", lhs & rhs);
+                m.output("bitor", lhs | rhs);
+                m.output("bitxor", lhs ^ rhs);
+                m
+            }),
+            ("additive_bin_op", |c| {
+                let m = c.module("AdditiveBinOp");
+                let lhs = m.input("lhs", 4);
+                let rhs = m.input("rhs", 4);
+                m.output("add", lhs + rhs);
+                m.output("sub", lhs - rhs);
+                m
+            }),
+            ("comparison_bin_op", |c| {
+                let m = c.module("ComparisonBinOp");
+                let lhs = m.input("lhs", 4);
+                let rhs = m.input("rhs", 4);
+                m.output("eq", lhs.eq(rhs));
+                m.output("ne", lhs.ne(rhs));
+                m.output("lt", lhs.lt(rhs));
+                m.output("le", lhs.le(rhs));
+                m.output("gt", lhs.gt(rhs));
+                m.output("ge", lhs.ge(rhs));
+                m.output("lt_signed", lhs.lt_signed(rhs));
+                m.output("le_signed", lhs.le_signed(rhs));
+                m.output("gt_signed", lhs.gt_signed(rhs));
+                m.output("ge_signed", lhs.ge_signed(rhs));
+                m
+            }),
+            ("shift_bin_op", |c| {
+                let m = c.module("ShiftBinOp");
+                let lhs = m.input("lhs", 8);
+                let rhs = m.input("rhs", 3);
+                m.output("shl", lhs << rhs);
+                m.output("shr", lhs >> rhs);
+                m.output("shr_arithmetic", lhs.shr_arithmetic(rhs));
+                m
+            }),
+            ("bits", |c| {
+                let m = c.module("Bits");
+                let i = m.input("i", 8);
+                m.output("bit", i.bit(7));
+                m.output("bits", i.bits(5, 2));
+                m
+            }),
+            ("repeat", |c| {
+                let m = c.module("Repeat");
+                m.output("o", m.input("i", 3).repeat(4));
+                m
+            }),
+            ("concat", |c| {
+                let m = c.module("Concat");
+                m.output("o", m.input("lhs", 3).concat(m.input("rhs", 5)));
+                m
+            }),
+            ("mux", |c| {
+                let m = c.module("Mux");
+                m.output(
+                    "o",
+                    m.mux(m.input("cond", 1), m.input("a", 8), m.input("b", 8)),
+                );
+                m
+            }),
+            ("instance", |c| {
+                let child = c.module("Child");
+                child.output("o", !child.input("i", 4));
+
+                let m = c.module("Instance");
+                let child = m.instance("child", "Child");
+                child.drive_input("i", m.input("i", 4));
+                m.output("o", child.output("o"));
+                m
+            }),
+            ("mem_read_port", |c| {
+                let m = c.module("MemReadPort");
+                let mem = m.mem("mem", 4, 8);
+                mem.write_port(
+                    m.input("write_address", 4),
+                    m.input("write_value", 8),
+                    m.input("write_enable", 1),
+                );
+                m.output(
+                    "read_value",
+                    mem.read_port(m.input("read_address", 4), m.input("read_enable", 1)),
+                );
+                m
+            }),
+            ("mem_comb_read_port", |c| {
+                let m = c.module("MemCombReadPort");
+                let mem = m.mem("mem", 2, 8);
+                mem.initial_contents(&[0x01u32, 0x23, 0x45, 0x67]);
+                m.output("read_value", mem.read_port_comb(m.input("read_address", 2)));
+                m
+            }),
+        ];
+
+        for (name, build) in corpus {
+            let c = Context::new();
+            let m = build(&c);
+            crate::golden::assert_verilog_golden(
+                m,
+                format!(
+                    "{}/src/verilog/golden/corpus/{}.sv",
+                    env!("CARGO_MANIFEST_DIR"),
+                    name
+                ),
+            );
+        }
+    }
 }
@@ -1,19 +1,79 @@
 use super::ir::*;
 use super::module_decls::*;
 
+use crate::constant_folding::*;
 use crate::graph;
 
 use std::collections::HashMap;
+use std::ptr;
 
 pub struct Compiler<'graph> {
+    constant_folder: ConstantFolder<'graph>,
     signal_exprs: HashMap<&'graph graph::Signal<'graph>, Expr>,
+
+    localparam_names: HashMap<&'graph graph::Signal<'graph>, String>,
+    localparam_bit_width_threshold: Option<u32>,
+    localparams: Vec<LocalParam>,
+    unnamed_localparams: HashMap<(u128, u32), String>,
 }
 
 impl<'graph> Compiler<'graph> {
-    pub fn new() -> Compiler<'graph> {
+    pub fn new(
+        localparam_names: HashMap<&'graph graph::Signal<'graph>, String>,
+        localparam_bit_width_threshold: Option<u32>,
+    ) -> Compiler<'graph> {
         Compiler {
+            constant_folder: ConstantFolder::new(),
             signal_exprs: HashMap::new(),
+
+            localparam_names,
+            localparam_bit_width_threshold,
+            localparams: Vec::new(),
+            unnamed_localparams: HashMap::new(),
+        }
+    }
+
+    // Localparams referenced by compiled signals, in the order they were first referenced
+    pub fn localparams(&self) -> &[LocalParam] {
+        &self.localparams
+    }
+
+    // Returns an expression for the constant `value`, declaring it as a localparam if it's a named constant or wider than the localparam threshold
+    fn constant(
+        &mut self,
+        signal: &'graph graph::Signal<'graph>,
+        value: graph::Constant,
+        bit_width: u32,
+        a: &mut AssignmentContext,
+    ) -> Expr {
+        let name = match self.localparam_names.get(&signal) {
+            Some(name) => name.clone(),
+            _ => match self.localparam_bit_width_threshold {
+                Some(threshold) if bit_width > threshold => {
+                    let key = (value.numeric_value(), bit_width);
+                    if let Some(name) = self.unnamed_localparams.get(&key) {
+                        return Expr::Ref { name: name.clone() };
+                    }
+                    let name =
+                        a.unique_name(&format!("__const_{}", self.unnamed_localparams.len()));
+                    self.unnamed_localparams.insert(key, name.clone());
+                    name
+                }
+                _ => return Expr::from_constant(&value, bit_width),
+            },
+        };
+        if !self
+            .localparams
+            .iter()
+            .any(|localparam| localparam.name == name)
+        {
+            self.localparams.push(LocalParam {
+                name: name.clone(),
+                bit_width,
+                value: Expr::from_constant(&value, bit_width),
+            });
         }
+        Expr::Ref { name }
     }
 
     pub fn compile_signal(
@@ -22,6 +82,68 @@ impl<'graph> Compiler<'graph> {
         module_decls: &ModuleDecls<'graph>,
         a: &mut AssignmentContext,
     ) -> Expr {
+        // Operands are compiled bottom-up with an explicit stack first (in the same order recursion would compile them), so that compiling `signal` itself never recurses more than one level deep, regardless of how deep the graph is
+        let mut stack = vec![(signal, false)];
+        while let Some((signal, operands_compiled)) = stack.pop() {
+            if self.constant_folder.evaluate(signal).is_some()
+                || self.signal_exprs.contains_key(&signal)
+            {
+                continue;
+            }
+            if operands_compiled {
+                self.compile_single_signal(signal, module_decls, a);
+                continue;
+            }
+            stack.push((signal, true));
+            let operands = self.operands(signal);
+            stack.extend(operands.into_iter().rev().map(|operand| (operand, false)));
+        }
+
+        self.compile_single_signal(signal, module_decls, a)
+    }
+
+    // The signals that compiling `signal` compiles first, in order
+    fn operands(
+        &mut self,
+        signal: &'graph graph::Signal<'graph>,
+    ) -> Vec<&'graph graph::Signal<'graph>> {
+        match signal.data {
+            graph::SignalData::Lit { .. }
+            | graph::SignalData::Input { .. }
+            | graph::SignalData::Reg { .. }
+            | graph::SignalData::Latch { .. }
+            | graph::SignalData::InstanceOutput { .. }
+            | graph::SignalData::MemReadPortOutput { .. } => Vec::new(),
+
+            graph::SignalData::Mux {
+                cond,
+                when_true,
+                when_false,
+            } => match self.constant_folder.evaluate(cond) {
+                Some(cond) => vec![if cond.numeric_value() != 0 {
+                    when_true
+                } else {
+                    when_false
+                }],
+                None => vec![cond, when_true, when_false],
+            },
+
+            graph::SignalData::MemCombReadPortOutput { address, .. } => vec![address],
+
+            _ => signal.dependencies(),
+        }
+    }
+
+    fn compile_single_signal(
+        &mut self,
+        signal: &'graph graph::Signal<'graph>,
+        module_decls: &ModuleDecls<'graph>,
+        a: &mut AssignmentContext,
+    ) -> Expr {
+        if let Some(value) = self.constant_folder.evaluate(signal) {
+            return self.constant(signal, value, signal.bit_width(), a);
+        }
+
         if !self.signal_exprs.contains_key(&signal) {
             let expr = match signal.data {
                 graph::SignalData::Lit {
@@ -29,11 +151,16 @@ impl<'graph> Compiler<'graph> {
                     bit_width,
                 } => Expr::from_constant(value, bit_width),
 
-                graph::SignalData::Input { ref name, .. } => Expr::Ref { name: name.clone() },
+                graph::SignalData::Input { name, .. } => Expr::Ref {
+                    name: super::identifier(name),
+                },
 
                 graph::SignalData::Reg { .. } => Expr::Ref {
                     name: module_decls.regs[&signal].value_name.clone(),
                 },
+                graph::SignalData::Latch { .. } => Expr::Ref {
+                    name: module_decls.latches[&signal].value_name.clone(),
+                },
 
                 graph::SignalData::UnOp { source, op } => {
                     let bit_width = source.bit_width();
@@ -186,25 +313,49 @@ impl<'graph> Compiler<'graph> {
                     cond,
                     when_true,
                     when_false,
-                } => {
-                    let bit_width = when_true.bit_width();
-                    let cond = self.compile_signal(cond, module_decls, a);
-                    let when_true = self.compile_signal(when_true, module_decls, a);
-                    let when_false = self.compile_signal(when_false, module_decls, a);
-                    a.gen_temp(
-                        Expr::Ternary {
-                            cond: Box::new(cond),
-                            when_true: Box::new(when_true),
-                            when_false: Box::new(when_false),
-                        },
-                        bit_width,
-                    )
-                }
+                } => match self.constant_folder.evaluate(cond) {
+                    Some(cond) => {
+                        let source = if cond.numeric_value() != 0 {
+                            when_true
+                        } else {
+                            when_false
+                        };
+                        self.compile_signal(source, module_decls, a)
+                    }
+                    None => {
+                        let bit_width = when_true.bit_width();
+                        let cond = self.compile_signal(cond, module_decls, a);
+                        let when_true = self.compile_signal(when_true, module_decls, a);
+                        let when_false = self.compile_signal(when_false, module_decls, a);
+                        a.gen_temp(
+                            Expr::Ternary {
+                                cond: Box::new(cond),
+                                when_true: Box::new(when_true),
+                                when_false: Box::new(when_false),
+                            },
+                            bit_width,
+                        )
+                    }
+                },
 
-                graph::SignalData::InstanceOutput { instance, ref name } => {
-                    let instance_decls = &module_decls.instances[&instance];
-                    Expr::Ref {
-                        name: instance_decls.output_names[name].clone(),
+                graph::SignalData::InstanceOutput { instance, name } => {
+                    match module_decls.instances.get(&instance) {
+                        Some(instance_decls) => Expr::Ref {
+                            name: instance_decls.output_names[name].clone(),
+                        },
+                        _ => {
+                            // Instances in arrays drive slices of a wide wire shared by all lanes
+                            let (array_index, lane) = module_decls.instance_array_lanes[&instance];
+                            let array_decls = &module_decls.instance_arrays[array_index];
+                            let bit_width = signal.bit_width();
+                            Expr::Bits {
+                                source: Box::new(Expr::Ref {
+                                    name: array_decls.output_names[name].clone(),
+                                }),
+                                range_high: lane * bit_width + bit_width - 1,
+                                range_low: lane * bit_width,
+                            }
+                        }
                     }
                 }
 
@@ -219,10 +370,41 @@ impl<'graph> Compiler<'graph> {
                         name: read_signal_names.value_name.clone(),
                     }
                 }
+                graph::SignalData::MemCombReadPortOutput { mem, address } => {
+                    let bit_width = signal.bit_width();
+                    let address = self.compile_signal(address, module_decls, a);
+                    a.gen_temp(
+                        Expr::ArrayIndex {
+                            target: Box::new(Expr::Ref {
+                                name: module_decls.mems[&mem].name.clone(),
+                            }),
+                            index: Box::new(address),
+                        },
+                        bit_width,
+                    )
+                }
             };
+            if a.comments() {
+                a.comment_temp(&expr, describe_signal(signal));
+            }
             self.signal_exprs.insert(signal, expr);
         }
 
         self.signal_exprs[&signal].clone()
     }
 }
+
+// Describes where `signal` came from, using its name (if any) and the source location that created it
+pub fn describe_signal(signal: &graph::Signal) -> String {
+    let name = signal
+        .module
+        .named_signals
+        .borrow()
+        .iter()
+        .find(|(_, &named_signal)| ptr::eq(named_signal, signal))
+        .map(|(name, _)| name.clone());
+    match name {
+        Some(name) => format!("{} ({})", name, signal.location),
+        _ => signal.location.to_string(),
+    }
+}
@@ -0,0 +1,46 @@
+module AlwaysComb(
+    input wire [7:0] a,
+    input wire [7:0] b,
+    input wire [1:0] op,
+    output wire [7:0] result,
+    output wire sum_is_zero
+    );
+
+    logic __temp_0;
+    logic [7:0] __temp_1;
+    logic __temp_2;
+    logic [7:0] __temp_3;
+    logic __temp_4;
+    logic [7:0] __temp_5;
+    logic [7:0] __temp_6;
+    logic [7:0] __temp_9;
+    logic __temp_10;
+
+    always_comb begin
+        __temp_0 = op == 2'h0;
+        __temp_1 = a + b;
+        __temp_2 = op == 2'h1;
+        __temp_3 = a - b;
+        __temp_4 = op == 2'h2;
+        __temp_5 = a & b;
+        __temp_6 = a | b;
+        if (__temp_0) begin
+            __temp_9 = __temp_1;
+        end
+        else if (__temp_2) begin
+            __temp_9 = __temp_3;
+        end
+        else if (__temp_4) begin
+            __temp_9 = __temp_5;
+        end
+        else begin
+            __temp_9 = __temp_6;
+        end
+        __temp_10 = __temp_1 == 8'h0;
+    end
+
+    assign result = __temp_9;
+    assign sum_is_zero = __temp_10;
+
+endmodule
+
@@ -0,0 +1,36 @@
+module AlwaysComb(
+    input wire [7:0] a,
+    input wire [7:0] b,
+    input wire [1:0] op,
+    output wire [7:0] result,
+    output wire sum_is_zero
+    );
+
+    wire __temp_0;
+    wire [7:0] __temp_1;
+    wire __temp_2;
+    wire [7:0] __temp_3;
+    wire __temp_4;
+    wire [7:0] __temp_5;
+    wire [7:0] __temp_6;
+    wire [7:0] __temp_7;
+    wire [7:0] __temp_8;
+    wire [7:0] __temp_9;
+    wire __temp_10;
+
+    assign __temp_0 = op == 2'h0;
+    assign __temp_1 = a + b;
+    assign __temp_2 = op == 2'h1;
+    assign __temp_3 = a - b;
+    assign __temp_4 = op == 2'h2;
+    assign __temp_5 = a & b;
+    assign __temp_6 = a | b;
+    assign __temp_7 = __temp_4 ? __temp_5 : __temp_6;
+    assign __temp_8 = __temp_2 ? __temp_3 : __temp_7;
+    assign __temp_9 = __temp_0 ? __temp_1 : __temp_8;
+    assign result = __temp_9;
+    assign __temp_10 = __temp_1 == 8'h0;
+    assign sum_is_zero = __temp_10;
+
+endmodule
+
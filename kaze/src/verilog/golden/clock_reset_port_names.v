@@ -0,0 +1,69 @@
+module Child(
+    input wire i_rst_n,
+    input wire i_clk,
+
+    input wire [3:0] i,
+    output wire [3:0] o
+    );
+
+    reg [3:0] __reg_r_0;
+    wire [3:0] __reg_r_0_next;
+
+    always @(posedge i_clk, negedge i_rst_n) begin
+        if (~i_rst_n) begin
+            __reg_r_0 <= 4'h0;
+        end
+        else begin
+            __reg_r_0 <= __reg_r_0_next;
+        end
+    end
+
+    assign o = __reg_r_0;
+    assign __reg_r_0_next = i;
+
+endmodule
+
+module Parent(
+    input wire sys_rst_n,
+    input wire sys_clk,
+
+    input wire [3:0] i,
+    output wire [3:0] o
+    );
+
+    wire [3:0] __child_input_i;
+    wire [3:0] __child_output_o;
+    reg [3:0] __reg_r_0;
+    wire [3:0] __reg_r_0_next;
+    wire __assertion_0;
+
+    Child child(
+        .i_rst_n(sys_rst_n),
+        .i_clk(sys_clk),
+        .i(__child_input_i),
+        .o(__child_output_o));
+
+    always @(posedge sys_clk) begin
+        __reg_r_0 <= __reg_r_0_next;
+    end
+
+`ifndef SYNTHESIS
+    always @(posedge sys_clk) begin
+        assert (__assertion_0) else $error("r is never 0xf");
+    end
+`endif
+
+`ifdef FORMAL
+    assert property (@(posedge sys_clk) __assertion_0);
+`endif
+
+    wire __temp_0;
+
+    assign o = __reg_r_0;
+    assign __child_input_i = i;
+    assign __reg_r_0_next = __child_output_o;
+    assign __temp_0 = __reg_r_0 != 4'hf;
+    assign __assertion_0 = __temp_0;
+
+endmodule
+
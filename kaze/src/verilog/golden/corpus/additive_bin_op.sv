@@ -0,0 +1,17 @@
+module AdditiveBinOp(
+    input wire [3:0] lhs,
+    input wire [3:0] rhs,
+    output wire [3:0] add,
+    output wire [3:0] sub
+    );
+
+    wire [3:0] __temp_0;
+    wire [3:0] __temp_1;
+
+    assign __temp_0 = lhs + rhs;
+    assign add = __temp_0;
+    assign __temp_1 = lhs - rhs;
+    assign sub = __temp_1;
+
+endmodule
+
@@ -0,0 +1,16 @@
+module Bits(
+    input wire [7:0] i,
+    output wire \bit ,
+    output wire [3:0] bits
+    );
+
+    wire __temp_0;
+    wire [3:0] __temp_1;
+
+    assign __temp_0 = i[7];
+    assign \bit  = __temp_0;
+    assign __temp_1 = i[5:2];
+    assign bits = __temp_1;
+
+endmodule
+
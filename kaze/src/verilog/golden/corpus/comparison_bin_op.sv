@@ -0,0 +1,49 @@
+module ComparisonBinOp(
+    input wire [3:0] lhs,
+    input wire [3:0] rhs,
+    output wire eq,
+    output wire ge,
+    output wire ge_signed,
+    output wire gt,
+    output wire gt_signed,
+    output wire le,
+    output wire le_signed,
+    output wire lt,
+    output wire lt_signed,
+    output wire ne
+    );
+
+    wire __temp_0;
+    wire __temp_1;
+    wire __temp_2;
+    wire __temp_3;
+    wire __temp_4;
+    wire __temp_5;
+    wire __temp_6;
+    wire __temp_7;
+    wire __temp_8;
+    wire __temp_9;
+
+    assign __temp_0 = lhs == rhs;
+    assign eq = __temp_0;
+    assign __temp_1 = lhs >= rhs;
+    assign ge = __temp_1;
+    assign __temp_2 = $signed(lhs) >= $signed(rhs);
+    assign ge_signed = __temp_2;
+    assign __temp_3 = lhs > rhs;
+    assign gt = __temp_3;
+    assign __temp_4 = $signed(lhs) > $signed(rhs);
+    assign gt_signed = __temp_4;
+    assign __temp_5 = lhs <= rhs;
+    assign le = __temp_5;
+    assign __temp_6 = $signed(lhs) <= $signed(rhs);
+    assign le_signed = __temp_6;
+    assign __temp_7 = lhs < rhs;
+    assign lt = __temp_7;
+    assign __temp_8 = $signed(lhs) < $signed(rhs);
+    assign lt_signed = __temp_8;
+    assign __temp_9 = lhs != rhs;
+    assign ne = __temp_9;
+
+endmodule
+
@@ -0,0 +1,13 @@
+module Concat(
+    input wire [2:0] lhs,
+    input wire [4:0] rhs,
+    output wire [7:0] o
+    );
+
+    wire [7:0] __temp_0;
+
+    assign __temp_0 = {lhs, rhs};
+    assign o = __temp_0;
+
+endmodule
+
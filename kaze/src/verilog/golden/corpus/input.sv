@@ -0,0 +1,9 @@
+module Input(
+    input wire [15:0] i,
+    output wire [15:0] o
+    );
+
+    assign o = i;
+
+endmodule
+
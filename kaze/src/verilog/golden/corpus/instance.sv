@@ -0,0 +1,27 @@
+module Child(
+    input wire [3:0] i,
+    output wire [3:0] o
+    );
+
+    wire [3:0] __temp_0;
+
+    assign __temp_0 = ~i;
+    assign o = __temp_0;
+
+endmodule
+
+module Instance(
+    input wire [3:0] i,
+    output wire [3:0] o
+    );
+
+    wire [3:0] __child_input_i;
+
+    Child child(
+        .i(__child_input_i),
+        .o(o));
+
+    assign __child_input_i = i;
+
+endmodule
+
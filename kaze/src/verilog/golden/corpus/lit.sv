@@ -0,0 +1,10 @@
+module Lit(
+    output wire [7:0] narrow,
+    output wire [99:0] wide
+    );
+
+    assign narrow = 8'ha5;
+    assign wide = 100'h123456789abcdef0;
+
+endmodule
+
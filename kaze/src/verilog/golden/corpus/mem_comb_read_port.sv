@@ -0,0 +1,24 @@
+module MemCombReadPort(
+    input wire reset_n,
+    input wire clk,
+
+    input wire [1:0] read_address,
+    output wire [7:0] read_value
+    );
+
+    reg [7:0] mem[0:3];
+
+    initial begin
+        mem[0] = 8'h1;
+        mem[1] = 8'h23;
+        mem[2] = 8'h45;
+        mem[3] = 8'h67;
+    end
+
+    wire [7:0] __temp_0;
+
+    assign __temp_0 = mem[read_address];
+    assign read_value = __temp_0;
+
+endmodule
+
@@ -0,0 +1,42 @@
+module MemReadPort(
+    input wire reset_n,
+    input wire clk,
+
+    input wire [3:0] read_address,
+    input wire read_enable,
+    input wire [3:0] write_address,
+    input wire write_enable,
+    input wire [7:0] write_value,
+    output wire [7:0] read_value
+    );
+
+    wire [3:0] __mem_mem_read_port_0_address;
+    wire __mem_mem_read_port_0_enable;
+    reg [7:0] __mem_mem_read_port_0_value;
+    wire [3:0] __mem_mem_write_port_0_address;
+    wire [7:0] __mem_mem_write_port_0_value;
+    wire __mem_mem_write_port_0_enable;
+
+    reg [7:0] mem[0:15];
+
+    always @(posedge clk) begin
+        if (__mem_mem_read_port_0_enable) begin
+            __mem_mem_read_port_0_value <= mem[__mem_mem_read_port_0_address];
+        end
+    end
+
+    always @(posedge clk) begin
+        if (__mem_mem_write_port_0_enable) begin
+            mem[__mem_mem_write_port_0_address] <= __mem_mem_write_port_0_value;
+        end
+    end
+
+    assign read_value = __mem_mem_read_port_0_value;
+    assign __mem_mem_read_port_0_address = read_address;
+    assign __mem_mem_read_port_0_enable = read_enable;
+    assign __mem_mem_write_port_0_address = write_address;
+    assign __mem_mem_write_port_0_value = write_value;
+    assign __mem_mem_write_port_0_enable = write_enable;
+
+endmodule
+
@@ -0,0 +1,14 @@
+module Mux(
+    input wire [7:0] a,
+    input wire [7:0] b,
+    input wire cond,
+    output wire [7:0] o
+    );
+
+    wire [7:0] __temp_0;
+
+    assign __temp_0 = cond ? a : b;
+    assign o = __temp_0;
+
+endmodule
+
@@ -0,0 +1,25 @@
+module RegWithReset(
+    input wire reset_n,
+    input wire clk,
+
+    input wire [7:0] i,
+    output wire [7:0] o
+    );
+
+    reg [7:0] __reg_r_0;
+    wire [7:0] __reg_r_0_next;
+
+    always @(posedge clk, negedge reset_n) begin
+        if (~reset_n) begin
+            __reg_r_0 <= 8'h42;
+        end
+        else begin
+            __reg_r_0 <= __reg_r_0_next;
+        end
+    end
+
+    assign o = __reg_r_0;
+    assign __reg_r_0_next = i;
+
+endmodule
+
@@ -0,0 +1,12 @@
+module Repeat(
+    input wire [2:0] i,
+    output wire [11:0] o
+    );
+
+    wire [11:0] __temp_0;
+
+    assign __temp_0 = {4{i}};
+    assign o = __temp_0;
+
+endmodule
+
@@ -0,0 +1,21 @@
+module ShiftBinOp(
+    input wire [7:0] lhs,
+    input wire [2:0] rhs,
+    output wire [7:0] shl,
+    output wire [7:0] shr,
+    output wire [7:0] shr_arithmetic
+    );
+
+    wire [7:0] __temp_0;
+    wire [7:0] __temp_1;
+    wire [7:0] __temp_2;
+
+    assign __temp_0 = lhs << rhs;
+    assign shl = __temp_0;
+    assign __temp_1 = lhs >> rhs;
+    assign shr = __temp_1;
+    assign __temp_2 = lhs >>> rhs;
+    assign shr_arithmetic = __temp_2;
+
+endmodule
+
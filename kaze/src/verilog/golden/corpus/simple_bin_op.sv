@@ -0,0 +1,21 @@
+module SimpleBinOp(
+    input wire [3:0] lhs,
+    input wire [3:0] rhs,
+    output wire [3:0] bitand,
+    output wire [3:0] bitor,
+    output wire [3:0] bitxor
+    );
+
+    wire [3:0] __temp_0;
+    wire [3:0] __temp_1;
+    wire [3:0] __temp_2;
+
+    assign __temp_0 = lhs & rhs;
+    assign bitand = __temp_0;
+    assign __temp_1 = lhs | rhs;
+    assign bitor = __temp_1;
+    assign __temp_2 = lhs ^ rhs;
+    assign bitxor = __temp_2;
+
+endmodule
+
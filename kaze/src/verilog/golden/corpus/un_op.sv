@@ -0,0 +1,12 @@
+module UnOp(
+    input wire [3:0] i,
+    output wire [3:0] \not 
+    );
+
+    wire [3:0] __temp_0;
+
+    assign __temp_0 = ~i;
+    assign \not  = __temp_0;
+
+endmodule
+
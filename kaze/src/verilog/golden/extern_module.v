@@ -0,0 +1,51 @@
+module Top(
+    input wire clk_in,
+    input wire [7:0] i,
+    output wire clk_out,
+    output wire [7:0] o
+    );
+
+    wire __pll_input_CLKIN1;
+    wire __pll_input_RST;
+    wire __pll_output_CLKOUT0;
+    wire __pll_output_LOCKED;
+    wire [7:0] __bufs_input_i;
+    wire [7:0] __bufs_output_o;
+
+    PLLE2_BASE #(
+        .BANDWIDTH("OPTIMIZED"),
+        .CLKFBOUT_MULT(10),
+        .CLKIN1_PERIOD(10.0),
+        .CLKOUT0_PHASE(-90.5),
+        .DIVCLK_DIVIDE(-1),
+        .NOTE("say \"hi\"")
+    ) pll(
+        .CLKIN1(__pll_input_CLKIN1),
+        .RST(__pll_input_RST),
+        .CLKOUT0(__pll_output_CLKOUT0),
+        .LOCKED(__pll_output_LOCKED));
+
+    genvar __bufs_index;
+    generate
+        for (__bufs_index = 0; __bufs_index < 2; __bufs_index = __bufs_index + 1) begin : bufs
+            Buf #(
+                .DRIVE(12)
+            ) inst(
+                .i(__bufs_input_i[__bufs_index * 4 +: 4]),
+                .o(__bufs_output_o[__bufs_index * 4 +: 4]));
+        end
+    endgenerate
+
+    wire __temp_0;
+    wire [7:0] __temp_1;
+
+    assign __temp_0 = __pll_output_CLKOUT0 & __pll_output_LOCKED;
+    assign clk_out = __temp_0;
+    assign __temp_1 = {__bufs_output_o[7:4], __bufs_output_o[3:0]};
+    assign o = __temp_1;
+    assign __pll_input_CLKIN1 = clk_in;
+    assign __pll_input_RST = 1'h0;
+    assign __bufs_input_i = i;
+
+endmodule
+
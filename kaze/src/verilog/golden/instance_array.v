@@ -0,0 +1,50 @@
+module Lanes(
+    input wire reset_n,
+    input wire clk,
+
+    input wire en,
+    input wire [39:0] i,
+    output wire [31:0] acc,
+    output wire carry
+    );
+
+    wire __lane_input_en;
+    wire [31:0] __lane_input_i;
+    wire [31:0] __lane_output_acc;
+    wire [3:0] __lane_output_carry;
+
+    genvar __lane_index;
+    generate
+        for (__lane_index = 0; __lane_index < 4; __lane_index = __lane_index + 1) begin : lane
+            Lane inst(
+                .reset_n(reset_n),
+                .clk(clk),
+                .en(__lane_input_en),
+                .i(__lane_input_i[__lane_index * 8 +: 8]),
+                .acc(__lane_output_acc[__lane_index * 8 +: 8]),
+                .carry(__lane_output_carry[__lane_index]));
+        end
+    endgenerate
+
+    wire [15:0] __temp_0;
+    wire [23:0] __temp_1;
+    wire [31:0] __temp_2;
+    wire __temp_3;
+    wire __temp_4;
+    wire __temp_5;
+    wire __temp_6;
+
+    assign __temp_0 = {__lane_output_acc[31:24], __lane_output_acc[23:16]};
+    assign __temp_1 = {__temp_0, __lane_output_acc[15:8]};
+    assign __temp_2 = {__temp_1, __lane_output_acc[7:0]};
+    assign acc = __temp_2;
+    assign __temp_3 = 1'h0 | __lane_output_carry[0];
+    assign __temp_4 = __temp_3 | __lane_output_carry[1];
+    assign __temp_5 = __temp_4 | __lane_output_carry[2];
+    assign __temp_6 = __temp_5 | __lane_output_carry[3];
+    assign carry = __temp_6;
+    assign __lane_input_en = en;
+    assign __lane_input_i = i[39:8];
+
+endmodule
+
@@ -0,0 +1,42 @@
+module SimpleDualPort(
+    input wire reset_n,
+    input wire clk,
+
+    input wire [3:0] read_addr,
+    input wire read_enable,
+    input wire [3:0] write_addr,
+    input wire [7:0] write_data,
+    input wire write_enable,
+    output wire [7:0] read_data
+    );
+
+    wire [3:0] __mem_ram_read_port_0_address;
+    wire __mem_ram_read_port_0_enable;
+    reg [7:0] __mem_ram_read_port_0_value;
+    wire [3:0] __mem_ram_write_port_0_address;
+    wire [7:0] __mem_ram_write_port_0_value;
+    wire __mem_ram_write_port_0_enable;
+
+    reg [7:0] ram[0:15];
+
+    always @(posedge clk) begin
+        if (__mem_ram_read_port_0_enable) begin
+            __mem_ram_read_port_0_value <= ram[__mem_ram_read_port_0_address];
+        end
+    end
+
+    always @(posedge clk) begin
+        if (__mem_ram_write_port_0_enable) begin
+            ram[__mem_ram_write_port_0_address] <= __mem_ram_write_port_0_value;
+        end
+    end
+
+    assign read_data = __mem_ram_read_port_0_value;
+    assign __mem_ram_read_port_0_address = read_addr;
+    assign __mem_ram_read_port_0_enable = read_enable;
+    assign __mem_ram_write_port_0_address = write_addr;
+    assign __mem_ram_write_port_0_value = write_data;
+    assign __mem_ram_write_port_0_enable = write_enable;
+
+endmodule
+
@@ -0,0 +1,46 @@
+`timescale 1ns / 1ps
+
+module Counter_tb;
+    reg reset_n;
+    reg clk;
+
+    reg en;
+    reg [3:0] step;
+    wire [7:0] count;
+    wire [3:0] dut;
+    wire wrapped;
+
+    Counter dut_0(
+        .reset_n(reset_n),
+        .clk(clk),
+        .en(en),
+        .step(step),
+        .count(count),
+        .dut(dut),
+        .wrapped(wrapped));
+
+    initial begin
+        clk = 1'b0;
+        forever #5 clk = ~clk;
+    end
+
+    initial begin
+        $dumpfile("Counter_tb.vcd");
+        $dumpvars(0, Counter_tb);
+    end
+
+    initial begin
+        reset_n = 1'b0;
+        en = 1'h0;
+        step = 4'h0;
+        repeat (2) @(posedge clk);
+        reset_n = 1'b1;
+
+        // TODO: Apply stimulus here
+
+        repeat (10) @(posedge clk);
+        $finish;
+    end
+
+endmodule
+
@@ -0,0 +1,72 @@
+module TrueDualPort(
+    input wire reset_n,
+    input wire clk,
+
+    input wire [3:0] a_addr,
+    input wire a_read_enable,
+    input wire [7:0] a_write_data,
+    input wire a_write_enable,
+    input wire [3:0] b_addr,
+    input wire b_read_enable,
+    input wire [7:0] b_write_data,
+    input wire b_write_enable,
+    output wire [7:0] a_read_data,
+    output wire [7:0] b_read_data
+    );
+
+    wire [3:0] __mem_ram_read_port_0_address;
+    wire __mem_ram_read_port_0_enable;
+    reg [7:0] __mem_ram_read_port_0_value;
+    wire [3:0] __mem_ram_read_port_1_address;
+    wire __mem_ram_read_port_1_enable;
+    reg [7:0] __mem_ram_read_port_1_value;
+    wire [3:0] __mem_ram_write_port_0_address;
+    wire [7:0] __mem_ram_write_port_0_value;
+    wire __mem_ram_write_port_0_enable;
+    wire [3:0] __mem_ram_write_port_1_address;
+    wire [7:0] __mem_ram_write_port_1_value;
+    wire __mem_ram_write_port_1_enable;
+
+    reg [7:0] ram[0:15];
+
+    always @(posedge clk) begin
+        if (__mem_ram_read_port_0_enable) begin
+            __mem_ram_read_port_0_value <= ram[__mem_ram_read_port_0_address];
+        end
+    end
+
+    always @(posedge clk) begin
+        if (__mem_ram_read_port_1_enable) begin
+            __mem_ram_read_port_1_value <= ram[__mem_ram_read_port_1_address];
+        end
+    end
+
+    always @(posedge clk) begin
+        if (__mem_ram_write_port_0_enable) begin
+            if (!(__mem_ram_write_port_1_enable && (__mem_ram_write_port_1_address == __mem_ram_write_port_0_address))) begin
+                ram[__mem_ram_write_port_0_address] <= __mem_ram_write_port_0_value;
+            end
+        end
+    end
+
+    always @(posedge clk) begin
+        if (__mem_ram_write_port_1_enable) begin
+            ram[__mem_ram_write_port_1_address] <= __mem_ram_write_port_1_value;
+        end
+    end
+
+    assign a_read_data = __mem_ram_read_port_0_value;
+    assign b_read_data = __mem_ram_read_port_1_value;
+    assign __mem_ram_read_port_0_address = a_addr;
+    assign __mem_ram_read_port_0_enable = a_read_enable;
+    assign __mem_ram_read_port_1_address = b_addr;
+    assign __mem_ram_read_port_1_enable = b_read_enable;
+    assign __mem_ram_write_port_0_address = a_addr;
+    assign __mem_ram_write_port_0_value = a_write_data;
+    assign __mem_ram_write_port_0_enable = a_write_enable;
+    assign __mem_ram_write_port_1_address = b_addr;
+    assign __mem_ram_write_port_1_value = b_write_data;
+    assign __mem_ram_write_port_1_enable = b_write_enable;
+
+endmodule
+
@@ -0,0 +1,54 @@
+// Registers are initialized with pseudo-random values from seed 0x0000000000001234
+module UninitializedRegisters(
+    input wire reset_n,
+    input wire clk,
+
+    input wire flag_in,
+    input wire [99:0] wide_in,
+    output wire [7:0] count,
+    output wire flag,
+    output wire [99:0] wide
+    );
+
+    reg __reg_flag_0;
+    wire __reg_flag_0_next;
+    reg [7:0] __reg_count_1;
+    wire [7:0] __reg_count_1_next;
+    reg [99:0] __reg_wide_2;
+    wire [99:0] __reg_wide_2_next;
+
+    initial begin
+        __reg_flag_0 = 1'h0;
+        __reg_count_1 = 8'hb5;
+        __reg_wide_2 = 100'haea35a5a6ad002edb4259d53a;
+    end
+
+    always @(posedge clk) begin
+        __reg_flag_0 <= __reg_flag_0_next;
+    end
+
+    always @(posedge clk, negedge reset_n) begin
+        if (~reset_n) begin
+            __reg_count_1 <= 8'h0;
+        end
+        else begin
+            __reg_count_1 <= __reg_count_1_next;
+        end
+    end
+
+    always @(posedge clk) begin
+        __reg_wide_2 <= __reg_wide_2_next;
+    end
+
+    wire [7:0] __temp_0;
+
+    assign count = __reg_count_1;
+    assign flag = __reg_flag_0;
+    assign wide = __reg_wide_2;
+    assign __reg_flag_0_next = flag_in;
+    assign __temp_0 = __reg_count_1 + 8'h1;
+    assign __reg_count_1_next = __temp_0;
+    assign __reg_wide_2_next = wide_in;
+
+endmodule
+
@@ -1,16 +1,27 @@
+use super::module_decls::UsedNames;
+
 use crate::code_writer;
 use crate::graph;
 
+use std::collections::{HashMap, HashSet};
 use std::io::{Result, Write};
 
 pub struct NodeDecl {
     pub net_type: NetType,
     pub name: String,
     pub bit_width: u32,
+    pub comment: Option<String>,
+    pub attributes: Vec<(String, String)>,
 }
 
 impl NodeDecl {
     pub fn write<W: Write>(&self, w: &mut code_writer::CodeWriter<W>) -> Result<()> {
+        if let Some(ref comment) = self.comment {
+            w.append_line(&format!("// {}", comment))?;
+        }
+        for (key, value) in self.attributes.iter() {
+            super::write_attribute(w, key, value)?;
+        }
         w.append_indent()?;
         self.net_type.write(w)?;
         w.append(" ")?;
@@ -24,7 +35,30 @@ impl NodeDecl {
     }
 }
 
+pub struct LocalParam {
+    pub name: String,
+    pub bit_width: u32,
+    pub value: Expr,
+}
+
+impl LocalParam {
+    pub fn write<W: Write>(&self, w: &mut code_writer::CodeWriter<W>) -> Result<()> {
+        w.append_indent()?;
+        w.append("localparam ")?;
+        if self.bit_width > 1 {
+            w.append(&format!("[{}:{}] ", self.bit_width - 1, 0))?;
+        }
+        w.append(&format!("{} = ", self.name))?;
+        self.value.write(w)?;
+        w.append(";")?;
+        w.append_newline()?;
+
+        Ok(())
+    }
+}
+
 pub enum NetType {
+    Logic,
     Reg,
     Wire,
 }
@@ -32,6 +66,7 @@ pub enum NetType {
 impl NetType {
     pub fn write<W: Write>(&self, w: &mut code_writer::CodeWriter<W>) -> Result<()> {
         w.append(match self {
+            NetType::Logic => "logic",
             NetType::Reg => "reg",
             NetType::Wire => "wire",
         })
@@ -41,28 +76,79 @@ impl NetType {
 pub struct AssignmentContext {
     assignments: Vec<Assignment>,
     local_decls: Vec<NodeDecl>,
+
+    names: UsedNames,
+
+    always_comb: bool,
+
+    comments: bool,
+    label: Option<String>,
 }
 
 impl AssignmentContext {
-    pub fn new() -> AssignmentContext {
+    pub fn new(always_comb: bool, comments: bool, names: UsedNames) -> AssignmentContext {
         AssignmentContext {
             assignments: Vec::new(),
             local_decls: Vec::new(),
+
+            names,
+
+            always_comb,
+
+            comments,
+            label: None,
+        }
+    }
+
+    pub fn unique_name(&mut self, name: &str) -> String {
+        self.names.unique(name)
+    }
+
+    pub fn comments(&self) -> bool {
+        self.comments
+    }
+
+    // Sets the label used to comment the assignments generated until the next call, if comments are enabled.
+    pub fn set_label(&mut self, label: String) {
+        if self.comments {
+            self.label = Some(label);
+        }
+    }
+
+    pub fn label(&self) -> Option<String> {
+        self.label.clone()
+    }
+
+    // Comments the declaration of `expr` if it refers to the most recently generated temporary and that temporary isn't already commented.
+    pub fn comment_temp(&mut self, expr: &Expr, comment: String) {
+        if let (Expr::Ref { name }, Some(decl)) = (expr, self.local_decls.last_mut()) {
+            if decl.name == *name && decl.comment.is_none() {
+                decl.comment = Some(comment);
+            }
         }
     }
 
     pub fn gen_temp(&mut self, expr: Expr, bit_width: u32) -> Expr {
-        let name = format!("__temp_{}", self.local_decls.len());
+        let name = self
+            .names
+            .unique(&format!("__temp_{}", self.local_decls.len()));
 
         self.local_decls.push(NodeDecl {
-            net_type: NetType::Wire,
+            net_type: if self.always_comb {
+                NetType::Logic
+            } else {
+                NetType::Wire
+            },
             name: name.clone(),
             bit_width,
+            comment: None,
+            attributes: Vec::new(),
         });
 
         self.assignments.push(Assignment {
             target_name: name.clone(),
             expr,
+            comment: self.label.clone(),
         });
 
         Expr::Ref { name }
@@ -77,6 +163,10 @@ impl AssignmentContext {
     }
 
     pub fn write<W: Write>(&self, w: &mut code_writer::CodeWriter<W>) -> Result<()> {
+        if self.always_comb {
+            return self.write_always_comb(w);
+        }
+
         if !self.local_decls.is_empty() {
             for node_decl in self.local_decls.iter() {
                 node_decl.write(w)?;
@@ -90,11 +180,219 @@ impl AssignmentContext {
 
         Ok(())
     }
+
+    // Temporaries are assigned procedurally in a single always_comb block, with mux trees lowered to if/else chains.
+    // Temporaries that are only used as a branch of another mux are folded into that mux's chain and not declared at all.
+    // Everything else (outputs, instance inputs, etc.) is still driven by continuous assignments.
+    fn write_always_comb<W: Write>(&self, w: &mut code_writer::CodeWriter<W>) -> Result<()> {
+        let temps = self
+            .local_decls
+            .iter()
+            .map(|decl| decl.name.as_str())
+            .collect::<HashSet<_>>();
+        let mut ref_counts = HashMap::new();
+        for assignment in self.assignments.iter() {
+            assignment.expr.visit_refs(&mut |name| {
+                *ref_counts.entry(name.to_string()).or_insert(0) += 1;
+            });
+        }
+        let temp_exprs = self
+            .assignments
+            .iter()
+            .filter(|assignment| temps.contains(assignment.target_name.as_str()))
+            .map(|assignment| (assignment.target_name.as_str(), &assignment.expr))
+            .collect::<HashMap<_, _>>();
+        let is_inlined = |name: &str| {
+            ref_counts.get(name) == Some(&1)
+                && matches!(temp_exprs.get(name), Some(Expr::Ternary { .. }))
+        };
+        let inlined = self
+            .assignments
+            .iter()
+            .filter_map(|assignment| match assignment.expr {
+                Expr::Ternary {
+                    ref when_true,
+                    ref when_false,
+                    ..
+                } => Some([when_true, when_false]),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|branch| match **branch {
+                Expr::Ref { ref name } if is_inlined(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+
+        let statements = self
+            .assignments
+            .iter()
+            .filter(|assignment| temps.contains(assignment.target_name.as_str()))
+            .filter(|assignment| !inlined.contains(assignment.target_name.as_str()))
+            .map(|assignment| {
+                Statement::lower(
+                    &assignment.target_name,
+                    &assignment.expr,
+                    &assignment.comment,
+                    &|name| {
+                        if inlined.contains(name) {
+                            Some(temp_exprs[name])
+                        } else {
+                            None
+                        }
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let decls = self
+            .local_decls
+            .iter()
+            .filter(|decl| !inlined.contains(decl.name.as_str()))
+            .collect::<Vec<_>>();
+        if !decls.is_empty() {
+            for node_decl in decls {
+                node_decl.write(w)?;
+            }
+            w.append_newline()?;
+        }
+
+        if !statements.is_empty() {
+            w.append_line("always_comb begin")?;
+            w.indent();
+            for statement in statements.iter() {
+                statement.write(w)?;
+            }
+            w.unindent()?;
+            w.append_line("end")?;
+            w.append_newline()?;
+        }
+
+        for assignment in self
+            .assignments
+            .iter()
+            .filter(|assignment| !temps.contains(assignment.target_name.as_str()))
+        {
+            assignment.write(w)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub enum Statement {
+    BlockingAssignment {
+        target_name: String,
+        expr: Expr,
+        comment: Option<String>,
+    },
+    If {
+        cond: Expr,
+        when_true: Box<Statement>,
+        when_false: Box<Statement>,
+    },
+}
+
+impl Statement {
+    // Lowers an assignment of `expr` to `target_name` into statements, turning muxes into if/else statements.
+    // `inlined_temp` returns the expression for temporaries that should be folded into the mux that refers to them.
+    fn lower<'a>(
+        target_name: &str,
+        expr: &'a Expr,
+        comment: &Option<String>,
+        inlined_temp: &dyn Fn(&str) -> Option<&'a Expr>,
+    ) -> Statement {
+        match expr {
+            Expr::Ternary {
+                cond,
+                when_true,
+                when_false,
+            } => Statement::If {
+                cond: (**cond).clone(),
+                when_true: Box::new(Statement::lower(
+                    target_name,
+                    when_true,
+                    comment,
+                    inlined_temp,
+                )),
+                when_false: Box::new(Statement::lower(
+                    target_name,
+                    when_false,
+                    comment,
+                    inlined_temp,
+                )),
+            },
+            Expr::Ref { name } if inlined_temp(name).is_some() => Statement::lower(
+                target_name,
+                inlined_temp(name).unwrap(),
+                comment,
+                inlined_temp,
+            ),
+            _ => Statement::BlockingAssignment {
+                target_name: target_name.to_string(),
+                expr: expr.clone(),
+                comment: comment.clone(),
+            },
+        }
+    }
+
+    fn write<W: Write>(&self, w: &mut code_writer::CodeWriter<W>) -> Result<()> {
+        w.append_indent()?;
+        self.write_inline(w)
+    }
+
+    // Writes this statement assuming the current line has already been started (so `else if` chains stay on one line)
+    fn write_inline<W: Write>(&self, w: &mut code_writer::CodeWriter<W>) -> Result<()> {
+        match self {
+            Statement::BlockingAssignment {
+                target_name,
+                expr,
+                comment,
+            } => {
+                w.append(&format!("{} = ", target_name))?;
+                expr.write(w)?;
+                w.append(";")?;
+                if let Some(ref comment) = comment {
+                    w.append(&format!(" // {}", comment))?;
+                }
+                w.append_newline()?;
+            }
+            Statement::If {
+                cond,
+                when_true,
+                when_false,
+            } => {
+                w.append("if (")?;
+                cond.write(w)?;
+                w.append(") begin")?;
+                w.append_newline()?;
+                w.indent();
+                when_true.write(w)?;
+                w.unindent()?;
+                w.append_line("end")?;
+                w.append_indent()?;
+                w.append("else ")?;
+                if let Statement::If { .. } = **when_false {
+                    when_false.write_inline(w)?;
+                } else {
+                    w.append("begin")?;
+                    w.append_newline()?;
+                    w.indent();
+                    when_false.write(w)?;
+                    w.unindent()?;
+                    w.append_line("end")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 pub struct Assignment {
     pub target_name: String,
     pub expr: Expr,
+    pub comment: Option<String>,
 }
 
 impl Assignment {
@@ -104,6 +402,9 @@ impl Assignment {
         w.append(" = ")?;
         self.expr.write(w)?;
         w.append(";")?;
+        if let Some(ref comment) = self.comment {
+            w.append(&format!(" // {}", comment))?;
+        }
         w.append_newline()?;
 
         Ok(())
@@ -112,6 +413,10 @@ impl Assignment {
 
 #[derive(Clone)]
 pub enum Expr {
+    ArrayIndex {
+        target: Box<Expr>,
+        index: Box<Expr>,
+    },
     BinOp {
         lhs: Box<Expr>,
         rhs: Box<Expr>,
@@ -152,6 +457,34 @@ pub enum Expr {
 }
 
 impl Expr {
+    pub fn visit_refs(&self, f: &mut impl FnMut(&str)) {
+        match self {
+            Expr::ArrayIndex { target, index } => {
+                target.visit_refs(f);
+                index.visit_refs(f);
+            }
+            Expr::BinOp { lhs, rhs, .. } | Expr::Concat { lhs, rhs } => {
+                lhs.visit_refs(f);
+                rhs.visit_refs(f);
+            }
+            Expr::Bits { source, .. }
+            | Expr::Repeat { source, .. }
+            | Expr::Signed { source }
+            | Expr::UnOp { source, .. } => source.visit_refs(f),
+            Expr::Constant { .. } => (),
+            Expr::Ref { name } => f(name),
+            Expr::Ternary {
+                cond,
+                when_true,
+                when_false,
+            } => {
+                cond.visit_refs(f);
+                when_true.visit_refs(f);
+                when_false.visit_refs(f);
+            }
+        }
+    }
+
     pub fn from_constant(value: &graph::Constant, bit_width: u32) -> Expr {
         Expr::Constant {
             bit_width,
@@ -161,8 +494,15 @@ impl Expr {
 
     pub fn write<W: Write>(&self, w: &mut code_writer::CodeWriter<W>) -> Result<()> {
         match self {
+            Expr::ArrayIndex { target, index } => {
+                target.write(w)?;
+                w.append("[")?;
+                index.write(w)?;
+                w.append("]")?;
+            }
             Expr::BinOp { lhs, rhs, op } => {
                 lhs.write(w)?;
+                w.append_break();
                 w.append(&format!(
                     " {} ",
                     match op {
@@ -200,6 +540,7 @@ impl Expr {
                 w.append("{")?;
                 lhs.write(w)?;
                 w.append(", ")?;
+                w.append_break();
                 rhs.write(w)?;
                 w.append("}")?;
             }
@@ -225,8 +566,10 @@ impl Expr {
                 when_false,
             } => {
                 cond.write(w)?;
+                w.append_break();
                 w.append(" ? ")?;
                 when_true.write(w)?;
+                w.append_break();
                 w.append(" : ")?;
                 when_false.write(w)?;
             }
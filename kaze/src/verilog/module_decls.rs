@@ -1,17 +1,39 @@
 use crate::graph;
+use crate::index_map::*;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
 
 pub struct InstanceDecls {
-    pub input_names: HashMap<String, String>,
-    pub output_names: HashMap<String, String>,
+    pub name: String,
+    pub input_names: BTreeMap<String, String>,
+    pub output_names: BTreeMap<String, String>,
+    // Outputs that are connected directly to one of the generated module's output ports (whose name is in `output_names`), so they don't need their own wires
+    pub forwarded_output_names: HashSet<String>,
+}
+
+pub struct InstanceArrayDecls<'a> {
+    pub instances: Vec<&'a graph::Instance<'a>>,
+    pub name: String,
+    pub index_name: String,
+    pub input_names: BTreeMap<String, (String, InstanceArrayInput<'a>)>,
+    pub output_names: BTreeMap<String, String>,
+}
+
+pub enum InstanceArrayInput<'a> {
+    // Every lane is driven by the same signal
+    Broadcast(&'a graph::Signal<'a>),
+    // Lane `i` is driven by `bit_width` bits of `source` starting at `range_low + i * bit_width`
+    Sliced {
+        source: &'a graph::Signal<'a>,
+        range_low: u32,
+    },
 }
 
 pub struct MemDecls<'a> {
-    pub read_signal_names: HashMap<(&'a graph::Signal<'a>, &'a graph::Signal<'a>), ReadSignalNames>,
-    pub write_address_name: String,
-    pub write_value_name: String,
-    pub write_enable_name: String,
+    pub name: String,
+    pub read_signal_names:
+        IndexMap<(&'a graph::Signal<'a>, &'a graph::Signal<'a>), ReadSignalNames>,
+    pub write_signal_names: Vec<WriteSignalNames>,
 }
 
 pub struct ReadSignalNames {
@@ -20,14 +42,62 @@ pub struct ReadSignalNames {
     pub value_name: String,
 }
 
+pub struct WriteSignalNames {
+    pub address_name: String,
+    pub value_name: String,
+    pub enable_name: String,
+    pub mask_name: Option<String>,
+}
+
 pub struct RegisterDecls<'a> {
     pub(super) data: &'a graph::RegisterData<'a>,
     pub value_name: String,
     pub next_name: String,
 }
 
+pub struct LatchDecls<'a> {
+    pub(super) data: &'a graph::LatchData<'a>,
+    pub value_name: String,
+    pub data_name: String,
+    pub enable_name: String,
+}
+
 pub struct ModuleDecls<'a> {
-    pub instances: HashMap<&'a graph::Instance<'a>, InstanceDecls>,
-    pub mems: HashMap<&'a graph::Mem<'a>, MemDecls<'a>>,
-    pub regs: HashMap<&'a graph::Signal<'a>, RegisterDecls<'a>>,
+    pub instances: IndexMap<&'a graph::Instance<'a>, InstanceDecls>,
+    pub instance_arrays: Vec<InstanceArrayDecls<'a>>,
+    // Maps each instance emitted as part of an array to the array's index in `instance_arrays` and its lane index
+    pub instance_array_lanes: IndexMap<&'a graph::Instance<'a>, (usize, u32)>,
+    pub mems: IndexMap<&'a graph::Mem<'a>, MemDecls<'a>>,
+    pub regs: IndexMap<&'a graph::Signal<'a>, RegisterDecls<'a>>,
+    pub latches: IndexMap<&'a graph::Signal<'a>, LatchDecls<'a>>,
+}
+
+// Tracks the identifiers declared in a generated module, so that generated names never collide with ports or with each other
+pub struct UsedNames {
+    names: HashSet<String>,
+}
+
+impl UsedNames {
+    pub fn new() -> UsedNames {
+        UsedNames {
+            names: HashSet::new(),
+        }
+    }
+
+    // Reserves `name` as-is; used for ports, whose names can't change
+    pub fn reserve(&mut self, name: &str) {
+        self.names.insert(super::identifier(name));
+    }
+
+    // Returns an identifier for `name` that hasn't been used yet, appending a numeric suffix if necessary
+    pub fn unique(&mut self, name: &str) -> String {
+        let mut ret = super::identifier(name);
+        let mut suffix = 0;
+        while self.names.contains(&ret) {
+            ret = super::identifier(&format!("{}_{}", name, suffix));
+            suffix += 1;
+        }
+        self.names.insert(ret.clone());
+        ret
+    }
 }